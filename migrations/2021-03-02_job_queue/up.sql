@@ -0,0 +1,16 @@
+-- Durable queue of background jobs, e.g. for (re-)indexing and
+-- sending notifications after modifications have been committed
+CREATE TABLE job_queue (
+    rowid      INTEGER PRIMARY KEY,
+    --
+    id         TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    run_at     INTEGER NOT NULL, -- due time stamp or lease expiration while running
+    attempts   INTEGER NOT NULL,
+    payload    TEXT NOT NULL,
+    last_error TEXT,
+    --
+    UNIQUE (id)
+);
+
+CREATE INDEX job_queue_idx_run_at ON job_queue(run_at);
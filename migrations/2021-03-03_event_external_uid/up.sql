@@ -0,0 +1,12 @@
+-- Unique identifiers of events that have been imported by an
+-- organization from an external calendar, e.g. the UID of a VEVENT
+CREATE TABLE event_external_uid (
+    org_rowid    INTEGER NOT NULL,
+    external_uid TEXT NOT NULL,
+    event_rowid  INTEGER NOT NULL,
+    --
+    PRIMARY KEY (org_rowid, external_uid),
+    UNIQUE (event_rowid),
+    FOREIGN KEY (org_rowid) REFERENCES organization(rowid),
+    FOREIGN KEY (event_rowid) REFERENCES events(id)
+);
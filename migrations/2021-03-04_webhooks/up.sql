@@ -0,0 +1,42 @@
+-- Webhooks of organizations that receive notifications about changes
+CREATE TABLE webhook (
+    rowid          INTEGER PRIMARY KEY,
+    org_rowid      INTEGER NOT NULL,
+    --
+    id             TEXT NOT NULL,
+    created_at     INTEGER NOT NULL,
+    url            TEXT NOT NULL,
+    secret         TEXT NOT NULL,
+    event_types    TEXT NOT NULL, -- comma-separated
+    south_west_lat REAL,
+    south_west_lng REAL,
+    north_east_lat REAL,
+    north_east_lng REAL,
+    tags           TEXT NOT NULL, -- comma-separated
+    --
+    UNIQUE (id),
+    FOREIGN KEY (org_rowid) REFERENCES organization(rowid)
+);
+
+CREATE INDEX webhook_idx_org_rowid ON webhook(org_rowid);
+
+-- Delivery log of webhooks
+CREATE TABLE webhook_delivery (
+    rowid           INTEGER PRIMARY KEY,
+    webhook_rowid   INTEGER NOT NULL,
+    --
+    id              TEXT NOT NULL,
+    created_at      INTEGER NOT NULL,
+    updated_at      INTEGER NOT NULL,
+    event_type      TEXT NOT NULL,
+    payload         TEXT NOT NULL,
+    status          SMALLINT NOT NULL, -- 0 = pending, 1 = delivered, 2 = failed
+    attempts        INTEGER NOT NULL,
+    response_status INTEGER,
+    last_error      TEXT,
+    --
+    UNIQUE (id),
+    FOREIGN KEY (webhook_rowid) REFERENCES webhook(rowid)
+);
+
+CREATE INDEX webhook_delivery_idx_webhook_rowid ON webhook_delivery(webhook_rowid);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+-- Removing columns from a table is not supported by SQLite
+DROP INDEX events_idx_series_uid;
@@ -0,0 +1,3 @@
+-- The preferred language for notifications as a BCP 47 tag, e.g. 'de' or 'en-GB'
+ALTER TABLE users ADD COLUMN locale TEXT;
+ALTER TABLE bbox_subscriptions ADD COLUMN locale TEXT;
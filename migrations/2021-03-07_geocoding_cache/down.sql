@@ -0,0 +1 @@
+DROP TABLE geocoding_cache;
@@ -0,0 +1,19 @@
+-- Results of geocoding requests. Queries that could not be
+-- resolved are cached without a position and address.
+CREATE TABLE geocoding_cache (
+    rowid     INTEGER PRIMARY KEY,
+    --
+    query     TEXT NOT NULL,
+    cached_at INTEGER NOT NULL,
+    lat       REAL,
+    lng       REAL,
+    street    TEXT,
+    zip       TEXT,
+    city      TEXT,
+    country   TEXT,
+    state     TEXT,
+    --
+    UNIQUE (query)
+);
+
+CREATE INDEX geocoding_cache_idx_cached_at ON geocoding_cache(cached_at);
@@ -0,0 +1,20 @@
+-- The plaintext tokens cannot be restored
+CREATE TABLE organization_old (
+    rowid     INTEGER PRIMARY KEY NOT NULL,
+    --
+    id        TEXT NOT NULL,
+    name      TEXT NOT NULL,
+    api_token TEXT NOT NULL,
+    --
+    UNIQUE (id)
+);
+
+INSERT INTO organization_old
+SELECT rowid, id, name, lower(hex(randomblob(16)))
+FROM organization;
+
+DROP TABLE organization;
+
+ALTER TABLE organization_old RENAME TO organization;
+
+DROP TABLE organization_api_token;
@@ -0,0 +1,52 @@
+-- Named API tokens of organizations with restricted scopes
+CREATE TABLE organization_api_token (
+    rowid        INTEGER PRIMARY KEY,
+    org_rowid    INTEGER NOT NULL,
+    --
+    id           TEXT NOT NULL,
+    name         TEXT NOT NULL,
+    token_hash   TEXT NOT NULL, -- hex-encoded SHA-256
+    scopes       TEXT NOT NULL, -- comma-separated
+    created_at   INTEGER NOT NULL,
+    expires_at   INTEGER,
+    last_used_at INTEGER,
+    revoked_at   INTEGER,
+    --
+    UNIQUE (id),
+    UNIQUE (token_hash),
+    FOREIGN KEY (org_rowid) REFERENCES organization(rowid)
+);
+
+CREATE INDEX organization_api_token_idx_org_rowid ON organization_api_token(org_rowid);
+
+-- The single, legacy API token of each organization is replaced
+-- by a named token with all scopes that can be revoked. Only its
+-- hash is stored. The function sha256_hex() is provided by the
+-- application while running the migrations.
+INSERT OR IGNORE INTO organization_api_token
+    (org_rowid, id, name, token_hash, scopes, created_at)
+SELECT
+    rowid,
+    lower(hex(randomblob(16))),
+    'legacy',
+    sha256_hex(api_token),
+    'events:read,events:write,events:clearance,places:read,places:write,places:clearance,places:history:read,webhooks:write,images:write',
+    CAST(strftime('%s', 'now') AS INTEGER) * 1000
+FROM organization;
+
+CREATE TABLE organization_new (
+    rowid     INTEGER PRIMARY KEY NOT NULL,
+    --
+    id        TEXT NOT NULL,
+    name      TEXT NOT NULL,
+    --
+    UNIQUE (id)
+);
+
+INSERT INTO organization_new
+SELECT rowid, id, name
+FROM organization;
+
+DROP TABLE organization;
+
+ALTER TABLE organization_new RENAME TO organization;
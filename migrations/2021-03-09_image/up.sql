@@ -0,0 +1,15 @@
+-- Metadata of uploaded images. The binary data of the
+-- original image and its thumbnails is kept in a separate
+-- storage backend.
+CREATE TABLE image (
+    rowid      INTEGER PRIMARY KEY,
+    --
+    id         TEXT NOT NULL,
+    format     TEXT NOT NULL,
+    width      INTEGER NOT NULL,
+    height     INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    created_by TEXT,
+    --
+    UNIQUE (id)
+);
@@ -0,0 +1,8 @@
+-- Places that have been merged into another place
+CREATE TABLE place_redirect (
+    old_place_rowid INTEGER PRIMARY KEY,
+    new_place_rowid INTEGER NOT NULL,
+    --
+    FOREIGN KEY (old_place_rowid) REFERENCES place(rowid),
+    FOREIGN KEY (new_place_rowid) REFERENCES place(rowid)
+);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+-- Removing columns from a table is not supported by SQLite
+DROP TABLE organization_event_clearance;
+DROP TABLE event_revision_tag;
+DROP TABLE event_revision;
@@ -0,0 +1,15 @@
+-- Append-only log of privileged actions
+CREATE TABLE audit_log (
+    rowid      INTEGER PRIMARY KEY,
+    at         INTEGER NOT NULL,
+    actor      TEXT,
+    action     TEXT NOT NULL,
+    -- comma-separated
+    target_ids TEXT NOT NULL,
+    old_value  TEXT,
+    new_value  TEXT
+);
+
+CREATE INDEX audit_log_idx_at ON audit_log (at);
+CREATE INDEX audit_log_idx_actor ON audit_log (actor);
+CREATE INDEX audit_log_idx_action ON audit_log (action);
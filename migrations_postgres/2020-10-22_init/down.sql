@@ -0,0 +1,17 @@
+DROP TABLE organization_place_clearance;
+DROP TABLE organization_tag;
+DROP TABLE organization;
+DROP TABLE place_rating_comment;
+DROP TABLE place_rating;
+DROP TABLE place_revision_custom_link;
+DROP TABLE place_revision_tag;
+DROP TABLE place_revision_review;
+DROP TABLE place_revision;
+DROP TABLE place;
+DROP TABLE bbox_subscriptions;
+DROP TABLE event_tags;
+DROP TABLE events;
+DROP TABLE user_tokens;
+DROP TABLE users;
+DROP TABLE tags;
+DROP COLLATION nocase;
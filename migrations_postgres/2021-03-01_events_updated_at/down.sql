@@ -0,0 +1,2 @@
+DROP INDEX events_idx_updated_at;
+ALTER TABLE events DROP COLUMN updated_at;
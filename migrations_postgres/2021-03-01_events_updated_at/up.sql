@@ -0,0 +1,4 @@
+-- Time stamp (milliseconds) of the last modification of an event.
+-- Used for incrementally updating the search index.
+ALTER TABLE events ADD COLUMN updated_at BIGINT NOT NULL DEFAULT 0;
+CREATE INDEX events_idx_updated_at ON events (updated_at);
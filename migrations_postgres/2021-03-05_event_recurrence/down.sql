@@ -0,0 +1,4 @@
+DROP INDEX events_idx_series_uid;
+ALTER TABLE events DROP COLUMN series_uid;
+ALTER TABLE events DROP COLUMN recurrence_exdates;
+ALTER TABLE events DROP COLUMN recurrence_rule;
@@ -0,0 +1,8 @@
+-- Recurring events (RFC 5545) are stored as a single series
+-- with a recurrence rule (RRULE) and the excluded start times
+-- (EXDATE) as comma-separated time stamps in seconds.
+ALTER TABLE events ADD COLUMN recurrence_rule TEXT;
+ALTER TABLE events ADD COLUMN recurrence_exdates TEXT;
+-- Occurrences that have been detached from their series
+ALTER TABLE events ADD COLUMN series_uid TEXT;
+CREATE INDEX events_idx_series_uid ON events (series_uid);
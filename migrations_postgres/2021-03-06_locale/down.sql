@@ -0,0 +1,2 @@
+ALTER TABLE bbox_subscriptions DROP COLUMN locale;
+ALTER TABLE users DROP COLUMN locale;
@@ -0,0 +1,5 @@
+-- The plaintext tokens cannot be restored
+ALTER TABLE organization ADD COLUMN api_token TEXT NOT NULL DEFAULT md5(random()::TEXT);
+ALTER TABLE organization ALTER COLUMN api_token DROP DEFAULT;
+
+DROP TABLE organization_api_token;
@@ -0,0 +1,37 @@
+-- Named API tokens of organizations with restricted scopes
+CREATE TABLE organization_api_token (
+    rowid        BIGSERIAL PRIMARY KEY,
+    org_rowid    BIGINT NOT NULL,
+    --
+    id           TEXT NOT NULL,
+    name         TEXT NOT NULL,
+    token_hash   TEXT NOT NULL, -- hex-encoded SHA-256
+    scopes       TEXT NOT NULL, -- comma-separated
+    created_at   BIGINT NOT NULL,
+    expires_at   BIGINT,
+    last_used_at BIGINT,
+    revoked_at   BIGINT,
+    --
+    UNIQUE (id),
+    UNIQUE (token_hash),
+    FOREIGN KEY (org_rowid) REFERENCES organization(rowid)
+);
+
+CREATE INDEX organization_api_token_idx_org_rowid ON organization_api_token(org_rowid);
+
+-- The single, legacy API token of each organization is replaced
+-- by a named token with all scopes that can be revoked. Only its
+-- hash is stored.
+INSERT INTO organization_api_token
+    (org_rowid, id, name, token_hash, scopes, created_at)
+SELECT
+    rowid,
+    md5(random()::TEXT || id),
+    'legacy',
+    encode(sha256(convert_to(api_token, 'UTF8')), 'hex'),
+    'events:read,events:write,events:clearance,places:read,places:write,places:clearance,places:history:read,webhooks:write,images:write',
+    (EXTRACT(EPOCH FROM now()) * 1000)::BIGINT
+FROM organization
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE organization DROP COLUMN api_token;
@@ -0,0 +1,15 @@
+-- Metadata of uploaded images. The binary data of the
+-- original image and its thumbnails is kept in a separate
+-- storage backend.
+CREATE TABLE image (
+    rowid      BIGSERIAL PRIMARY KEY,
+    --
+    id         TEXT NOT NULL,
+    format     TEXT NOT NULL,
+    width      BIGINT NOT NULL,
+    height     BIGINT NOT NULL,
+    created_at BIGINT NOT NULL,
+    created_by TEXT,
+    --
+    UNIQUE (id)
+);
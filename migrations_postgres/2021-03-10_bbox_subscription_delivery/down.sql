@@ -0,0 +1,6 @@
+DROP TABLE bbox_subscription_change;
+ALTER TABLE bbox_subscriptions DROP COLUMN last_digest_at;
+ALTER TABLE bbox_subscriptions DROP COLUMN delivery;
+ALTER TABLE bbox_subscriptions DROP COLUMN events;
+ALTER TABLE bbox_subscriptions DROP COLUMN categories;
+ALTER TABLE bbox_subscriptions DROP COLUMN tags;
@@ -0,0 +1,21 @@
+-- Optional filters and the delivery mode of subscriptions.
+-- Existing subscriptions keep receiving notifications about
+-- events immediately.
+ALTER TABLE bbox_subscriptions ADD COLUMN tags TEXT NOT NULL DEFAULT ''; -- comma-separated
+ALTER TABLE bbox_subscriptions ADD COLUMN categories TEXT NOT NULL DEFAULT ''; -- comma-separated
+ALTER TABLE bbox_subscriptions ADD COLUMN events BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE bbox_subscriptions ADD COLUMN delivery SMALLINT NOT NULL DEFAULT 0; -- 0 = immediate, 1 = daily, 2 = weekly
+ALTER TABLE bbox_subscriptions ADD COLUMN last_digest_at BIGINT;
+
+-- Changes that are delivered with the next digest of a subscription
+CREATE TABLE bbox_subscription_change (
+    rowid              BIGSERIAL PRIMARY KEY,
+    subscription_rowid BIGINT NOT NULL,
+    --
+    kind               TEXT NOT NULL,
+    item_id            TEXT NOT NULL,
+    created_at         BIGINT NOT NULL,
+    --
+    UNIQUE (subscription_rowid, item_id),
+    FOREIGN KEY (subscription_rowid) REFERENCES bbox_subscriptions(id)
+);
@@ -0,0 +1,4 @@
+DROP TABLE organization_event_clearance;
+DROP TABLE event_revision_tag;
+DROP TABLE event_revision;
+ALTER TABLE events DROP COLUMN current_rev;
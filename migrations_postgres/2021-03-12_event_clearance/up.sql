@@ -0,0 +1,71 @@
+-- The current revision of an event is stored in the events
+-- table and additionally in the history of all revisions.
+ALTER TABLE events ADD COLUMN current_rev BIGINT NOT NULL DEFAULT 0;
+
+CREATE TABLE event_revision (
+    rowid          BIGSERIAL PRIMARY KEY,
+    parent_rowid   BIGINT NOT NULL,
+    rev            BIGINT NOT NULL,
+    created_at     BIGINT NOT NULL,
+    --
+    title          TEXT NOT NULL,
+    description    TEXT,
+    start          BIGINT NOT NULL,
+    "end"          BIGINT,
+    lat            DOUBLE PRECISION,
+    lng            DOUBLE PRECISION,
+    street         TEXT,
+    zip            TEXT,
+    city           TEXT,
+    country        TEXT,
+    state          TEXT,
+    email          TEXT,
+    telephone      TEXT,
+    homepage       TEXT,
+    created_by     BIGINT,
+    registration   SMALLINT,
+    organizer      TEXT,
+    image_url      TEXT,
+    image_link_url TEXT,
+    recurrence_rule    TEXT,
+    recurrence_exdates TEXT,
+    series_uid         TEXT,
+    --
+    UNIQUE (parent_rowid, rev),
+    FOREIGN KEY (parent_rowid) REFERENCES events(id),
+    FOREIGN KEY (created_by) REFERENCES users(id)
+);
+
+CREATE TABLE event_revision_tag (
+    parent_rowid BIGINT NOT NULL,
+    tag          TEXT NOT NULL,
+    --
+    PRIMARY KEY (parent_rowid, tag),
+    FOREIGN KEY (parent_rowid) REFERENCES event_revision(rowid)
+);
+
+-- The initial revision of all existing events
+INSERT INTO event_revision (parent_rowid, rev, created_at, title, description, start, "end", lat, lng, street, zip, city, country, state, email, telephone, homepage, created_by, registration, organizer, image_url, image_link_url, recurrence_rule, recurrence_exdates, series_uid)
+SELECT id, 0, updated_at, title, description, start, "end", lat, lng, street, zip, city, country, state, email, telephone, homepage, created_by, registration, organizer, image_url, image_link_url, recurrence_rule, recurrence_exdates, series_uid
+FROM events;
+
+INSERT INTO event_revision_tag (parent_rowid, tag)
+SELECT rev.rowid, tag.tag
+FROM event_revision rev
+JOIN event_tags tag
+ON tag.event_id=rev.parent_rowid;
+
+-- Pending authorization/approval of events by organizations
+CREATE TABLE organization_event_clearance (
+    rowid        BIGSERIAL PRIMARY KEY,
+    --
+    org_rowid    BIGINT NOT NULL,
+    event_rowid  BIGINT NOT NULL,
+    --
+    created_at            BIGINT NOT NULL,
+    last_cleared_revision BIGINT, -- last cleared revision number or NULL if the event is new and has not been cleared yet
+    --
+    UNIQUE (org_rowid, event_rowid),
+    FOREIGN KEY (org_rowid) REFERENCES organization(rowid),
+    FOREIGN KEY (event_rowid) REFERENCES events(id)
+);
@@ -0,0 +1,3 @@
+ALTER TABLE event_revision DROP COLUMN image_id;
+ALTER TABLE events DROP COLUMN image_id;
+ALTER TABLE place_revision DROP COLUMN image_id;
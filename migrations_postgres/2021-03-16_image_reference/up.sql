@@ -0,0 +1,4 @@
+-- Uploaded images that are attached to places and events
+ALTER TABLE place_revision ADD COLUMN image_id TEXT;
+ALTER TABLE events ADD COLUMN image_id TEXT;
+ALTER TABLE event_revision ADD COLUMN image_id TEXT;
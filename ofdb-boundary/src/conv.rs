@@ -75,11 +75,62 @@ impl From<e::user::User> for User {
             email_confirmed,
             role,
             password: _password,
+            locale,
         } = from;
         Self {
             email,
             email_confirmed,
             role: role.into(),
+            locale: locale.map(|l| l.to_string()),
+        }
+    }
+}
+
+impl From<e::audit::AuditLogEntry> for AuditLogEntry {
+    fn from(from: e::audit::AuditLogEntry) -> Self {
+        let e::audit::AuditLogEntry {
+            at,
+            actor,
+            action,
+            target_ids,
+            before,
+            after,
+        } = from;
+        Self {
+            at: at.into_inner(),
+            actor,
+            action: action.to_string(),
+            target_ids,
+            before,
+            after,
+        }
+    }
+}
+
+impl From<e::subscription::BboxSubscription> for BboxSubscription {
+    fn from(from: e::subscription::BboxSubscription) -> Self {
+        let e::subscription::BboxSubscription {
+            id,
+            user_email: _,
+            bbox,
+            locale,
+            tags,
+            categories,
+            events,
+            delivery,
+            last_digest_at: _,
+        } = from;
+        Self {
+            id: id.into(),
+            south_west_lat: bbox.southwest().lat().to_deg(),
+            south_west_lng: bbox.southwest().lng().to_deg(),
+            north_east_lat: bbox.northeast().lat().to_deg(),
+            north_east_lng: bbox.northeast().lng().to_deg(),
+            locale: locale.map(|l| l.to_string()),
+            tags,
+            categories: categories.into_iter().map(Into::into).collect(),
+            events,
+            delivery: delivery.to_string(),
         }
     }
 }
@@ -185,6 +236,9 @@ impl From<e::event::Event> for Event {
             registration,
             image_url,
             image_link_url,
+            image_id,
+            recurrence,
+            series_id,
             ..
         } = e;
 
@@ -247,6 +301,19 @@ impl From<e::event::Event> for Event {
             organizer,
             image_url: image_url.map(Url::into_string),
             image_link_url: image_link_url.map(Url::into_string),
+            image_id: image_id.map(Into::into),
+            recurrence: recurrence.map(Into::into),
+            series_id: series_id.map(Into::into),
+        }
+    }
+}
+
+impl From<e::event::Recurrence> for EventRecurrence {
+    fn from(from: e::event::Recurrence) -> Self {
+        let e::event::Recurrence { rule, exdates } = from;
+        Self {
+            rrule: rule.to_string(),
+            exdates: exdates.iter().map(|exdate| exdate.timestamp()).collect(),
         }
     }
 }
@@ -279,6 +346,34 @@ impl From<ClearanceForPlace> for e::clearance::ClearanceForPlace {
     }
 }
 
+impl From<e::clearance::PendingClearanceForEvent> for PendingClearanceForEvent {
+    fn from(from: e::clearance::PendingClearanceForEvent) -> Self {
+        let e::clearance::PendingClearanceForEvent {
+            event_id,
+            created_at,
+            last_cleared_revision,
+        } = from;
+        Self {
+            event_id: event_id.into(),
+            created_at: created_at.into_inner(),
+            last_cleared_revision: last_cleared_revision.map(Into::into),
+        }
+    }
+}
+
+impl From<ClearanceForEvent> for e::clearance::ClearanceForEvent {
+    fn from(from: ClearanceForEvent) -> Self {
+        let ClearanceForEvent {
+            event_id,
+            cleared_revision,
+        } = from;
+        Self {
+            event_id: event_id.into(),
+            cleared_revision: cleared_revision.map(Into::into),
+        }
+    }
+}
+
 impl From<e::geo::MapPoint> for LatLonDegrees {
     fn from(from: e::geo::MapPoint) -> Self {
         Self(from.lat().to_deg(), from.lng().to_deg())
@@ -387,12 +482,14 @@ impl From<e::links::Links> for Links {
             homepage,
             image,
             image_href,
+            image_id,
             custom,
         } = from;
         Self {
             homepage: homepage.map(Url::into_string),
             image: image.map(Url::into_string),
             image_href: image_href.map(Url::into_string),
+            image_id: image_id.map(Into::into),
             custom: custom.into_iter().map(Into::into).collect(),
         }
     }
@@ -404,12 +501,14 @@ impl From<Links> for e::links::Links {
             homepage,
             image,
             image_href,
+            image_id,
             custom,
         } = from;
         Self {
             homepage: homepage.and_then(|url| url.parse().ok()),
             image: image.and_then(|url| url.parse().ok()),
             image_href: image_href.and_then(|url| url.parse().ok()),
+            image_id: image_id.map(Into::into),
             custom: custom.into_iter().map(Into::into).collect(),
         }
     }
@@ -622,3 +721,137 @@ impl From<ReviewStatusLog> for e::review::ReviewStatusLog {
         }
     }
 }
+
+impl From<e::webhook::Webhook> for Webhook {
+    fn from(from: e::webhook::Webhook) -> Self {
+        let e::webhook::Webhook {
+            id,
+            url,
+            event_types,
+            bbox,
+            tags,
+            created_at,
+            ..
+        } = from;
+        Self {
+            id: id.into(),
+            url: url.into_string(),
+            event_types: event_types
+                .into_iter()
+                .map(|t| t.as_str().to_owned())
+                .collect(),
+            bbox: bbox.map(Into::into),
+            tags,
+            created_at: created_at.into_inner(),
+        }
+    }
+}
+
+impl From<e::webhook::WebhookDelivery> for WebhookDelivery {
+    fn from(from: e::webhook::WebhookDelivery) -> Self {
+        let e::webhook::WebhookDelivery {
+            id,
+            event_type,
+            payload,
+            status,
+            attempts,
+            response_status,
+            last_error,
+            created_at,
+            updated_at,
+            ..
+        } = from;
+        let status = match status {
+            e::webhook::WebhookDeliveryStatus::Pending => WebhookDeliveryStatus::Pending,
+            e::webhook::WebhookDeliveryStatus::Delivered => WebhookDeliveryStatus::Delivered,
+            e::webhook::WebhookDeliveryStatus::Failed => WebhookDeliveryStatus::Failed,
+        };
+        Self {
+            id: id.into(),
+            event_type: event_type.as_str().to_owned(),
+            payload,
+            status,
+            attempts,
+            response_status,
+            last_error,
+            created_at: created_at.into_inner(),
+            updated_at: updated_at.into_inner(),
+        }
+    }
+}
+
+impl From<ModeratedTag> for e::organization::ModeratedTag {
+    fn from(from: ModeratedTag) -> Self {
+        let ModeratedTag {
+            label,
+            allow_add,
+            allow_remove,
+            require_clearance,
+        } = from;
+        Self {
+            label,
+            allow_add,
+            allow_remove,
+            require_clearance,
+        }
+    }
+}
+
+impl From<e::organization::ModeratedTag> for ModeratedTag {
+    fn from(from: e::organization::ModeratedTag) -> Self {
+        let e::organization::ModeratedTag {
+            label,
+            allow_add,
+            allow_remove,
+            require_clearance,
+        } = from;
+        Self {
+            label,
+            allow_add,
+            allow_remove,
+            require_clearance,
+        }
+    }
+}
+
+impl From<e::organization::Organization> for Organization {
+    fn from(from: e::organization::Organization) -> Self {
+        let e::organization::Organization {
+            id,
+            name,
+            moderated_tags,
+        } = from;
+        Self {
+            id: id.into(),
+            name,
+            api_token: None,
+            moderated_tags: moderated_tags.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<e::api_token::ApiToken> for ApiToken {
+    fn from(from: e::api_token::ApiToken) -> Self {
+        let e::api_token::ApiToken {
+            id,
+            org_id: _,
+            name,
+            token_hash: _,
+            scopes,
+            created_at,
+            expires_at,
+            last_used_at,
+            revoked_at,
+        } = from;
+        Self {
+            id: id.into(),
+            name,
+            scopes: scopes.iter().map(ToString::to_string).collect(),
+            created_at: created_at.into_inner(),
+            expires_at: expires_at.map(e::time::TimestampMs::into_inner),
+            last_used_at: last_used_at.map(e::time::TimestampMs::into_inner),
+            revoked_at: revoked_at.map(e::time::TimestampMs::into_inner),
+            token: None,
+        }
+    }
+}
@@ -46,6 +46,7 @@ pub struct Entry {
     pub license        : Option<String>,
     pub image_url      : Option<Url>,
     pub image_link_url : Option<Url>,
+    pub image_id       : Option<String>,
 
     #[serde(rename = "custom", skip_serializing_if = "Vec::is_empty", default = "Default::default")]
     pub custom_links   : Vec<CustomLink>,
@@ -84,6 +85,7 @@ pub struct NewPlace {
     pub license        : String,
     pub image_url      : Option<String>,
     pub image_link_url : Option<String>,
+    pub image_id       : Option<String>,
 
     #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
     pub links          : Vec<CustomLink>,
@@ -113,6 +115,7 @@ pub struct UpdatePlace {
     pub tags           : Vec<String>,
     pub image_url      : Option<String>,
     pub image_link_url : Option<String>,
+    pub image_id       : Option<String>,
 
     #[serde(skip_serializing_if = "Vec::is_empty", default = "Default::default")]
     pub links          : Vec<CustomLink>,
@@ -157,6 +160,22 @@ pub struct Event {
     pub image_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub image_link_url: Option<String>,
+    /// An uploaded image
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub image_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub recurrence: Option<EventRecurrence>,
+    /// The recurring event this event is an occurrence of
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub series_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct EventRecurrence {
+    pub rrule: String,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub exdates: Vec<i64>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -172,6 +191,8 @@ pub struct User {
     pub email: String,
     pub email_confirmed: bool,
     pub role: UserRole,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub locale: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -256,6 +277,15 @@ pub struct PlaceSearchResult {
     pub ratings: EntrySearchRatings,
 }
 
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct PlaceCluster {
+    pub centroid: MapPoint,
+    pub count: u64,
+    pub category: Option<String>,
+    pub bbox: MapBbox,
+}
+
 #[derive(Serialize, Deserialize)]
 #[cfg_attr(
     feature = "extra-derive",
@@ -276,6 +306,48 @@ pub struct Review {
     pub comment: Option<String>,
 }
 
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "extra-derive",
+    derive(Debug, Clone, Copy, PartialEq, Eq, Hash)
+)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaceMergeField {
+    Title,
+    Description,
+    Location,
+    Contact,
+    OpeningHours,
+    FoundedOn,
+    Links,
+    Tags,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct MergePlaces {
+    /// The id of the place that is merged into the other place
+    pub merged: String,
+    /// The new revision of the surviving place
+    pub version: u64,
+    /// The fields that are taken from the merged place
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields_from_merged: Vec<PlaceMergeField>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct RevertPlace {
+    /// The earlier revision that is restored
+    pub revision: u64,
+    /// The new revision of the place
+    pub version: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
 #[derive(Serialize, Deserialize)]
 #[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
 pub struct SearchResponse {
@@ -304,6 +376,37 @@ pub struct BboxSubscription {
     pub south_west_lng: f64,
     pub north_east_lat: f64,
     pub north_east_lng: f64,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub locale: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub events: bool,
+    #[serde(default)]
+    pub delivery: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct NewBboxSubscription {
+    pub south_west_lat: f64,
+    pub south_west_lng: f64,
+    pub north_east_lat: f64,
+    pub north_east_lng: f64,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub locale: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub categories: Vec<String>,
+    /// Notifications about events are opt-in
+    #[serde(default)]
+    pub events: bool,
+    /// `immediate` (default), `daily` or `weekly`
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub delivery: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -371,12 +474,224 @@ pub struct ClearanceForPlace {
     pub cleared_revision: Option<RevisionValue>,
 }
 
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct PendingClearanceForEvent {
+    pub event_id: String,
+    pub created_at: i64,
+    pub last_cleared_revision: Option<RevisionValue>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct ClearanceForEvent {
+    pub event_id: String,
+    pub cleared_revision: Option<RevisionValue>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct FailedEventImport {
+    pub uid: String,
+    pub error: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct EventImportResult {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub archived: Vec<String>,
+    pub failed: Vec<FailedEventImport>,
+}
+
 #[derive(Serialize, Deserialize)]
 #[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
 pub struct ResultCount {
     pub count: u64,
 }
 
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct NewWebhook {
+    pub url: String,
+    pub secret: String,
+    pub event_types: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bbox: Option<MapBbox>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+// The secret is never disclosed
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    pub event_types: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bbox: Option<MapBbox>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tags: Vec<String>,
+    pub created_at: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "extra-derive",
+    derive(Debug, Clone, Copy, PartialEq, Eq, Hash)
+)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub event_type: String,
+    pub payload: String,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq))]
+pub struct ModeratedTag {
+    pub label: String,
+    #[serde(default)]
+    pub allow_add: bool,
+    #[serde(default)]
+    pub allow_remove: bool,
+    #[serde(default)]
+    pub require_clearance: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct NewOrganization {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub moderated_tags: Vec<ModeratedTag>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct UpdateOrganization {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct Organization {
+    pub id: String,
+    pub name: String,
+    /// The secret of the initial API token is only returned once
+    /// after creation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_token: Option<String>,
+    pub moderated_tags: Vec<ModeratedTag>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct OrganizationEntries {
+    pub places: Vec<String>,
+    pub events: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct NewApiToken {
+    pub name: String,
+    pub scopes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub created_at: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<i64>,
+    /// The secret token is only returned once after creation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct ImageThumbnails {
+    pub small: String,
+    pub medium: String,
+    pub large: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq, Eq))]
+pub struct Image {
+    pub id: String,
+    pub width: u32,
+    pub height: u32,
+    pub url: String,
+    pub thumbnails: ImageThumbnails,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct UserActivity {
+    pub kind: String,
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub at: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct UserDataExport {
+    pub user: User,
+    pub bbox_subscriptions: Vec<BboxSubscription>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_token_expires_at: Option<i64>,
+    pub activities: Vec<UserActivity>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct AuditLogEntry {
+    pub at: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    pub action: String,
+    pub target_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+}
+
 #[derive(Serialize, Deserialize)]
 #[cfg_attr(feature = "extra-derive", derive(Debug, PartialEq))]
 pub struct LatLonDegrees(f64, f64);
@@ -455,6 +770,9 @@ pub struct Links {
     #[serde(rename = "img_href", skip_serializing_if = "Option::is_none")]
     pub image_href: Option<Url>,
 
+    #[serde(rename = "img_id", skip_serializing_if = "Option::is_none")]
+    pub image_id: Option<String>,
+
     #[serde(
         rename = "custom",
         skip_serializing_if = "Vec::is_empty",
@@ -469,9 +787,14 @@ impl Links {
             homepage,
             image,
             image_href,
+            image_id,
             custom,
         } = self;
-        homepage.is_none() && image.is_none() && image_href.is_none() && custom.is_empty()
+        homepage.is_none()
+            && image.is_none()
+            && image_href.is_none()
+            && image_id.is_none()
+            && custom.is_empty()
     }
 }
 
@@ -545,6 +868,68 @@ pub struct PlaceHistory {
     pub revisions: Vec<(PlaceRevision, Vec<ReviewStatusLog>)>,
 }
 
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "extra-derive",
+    derive(Debug, Clone, Copy, PartialEq, Eq, Hash)
+)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaceDiffField {
+    Title,
+    Description,
+    Street,
+    Zip,
+    City,
+    Country,
+    State,
+    ContactName,
+    ContactEmail,
+    ContactPhone,
+    OpeningHours,
+    FoundedOn,
+    Homepage,
+    Image,
+    ImageHref,
+    ImageId,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone, PartialEq))]
+pub struct ChangedPlaceField {
+    pub field: PlaceDiffField,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct MovedPlace {
+    pub from: MapPoint,
+    pub to: MapPoint,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance_meters: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "extra-derive", derive(Debug, Clone))]
+pub struct PlaceDiff {
+    pub id: String,
+    pub from: u64,
+    pub to: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changed_fields: Vec<ChangedPlaceField>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_custom_links: Vec<CustomLink>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_custom_links: Vec<CustomLink>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub moved: Option<MovedPlace>,
+}
+
 #[derive(Serialize, Deserialize)]
 #[cfg_attr(feature = "extra-derive", derive(Debug))]
 pub struct ActivityLog {
@@ -591,6 +976,7 @@ impl From<Entry> for UpdatePlace {
             tags,
             image_url,
             image_link_url,
+            image_id,
             custom_links,
             ..
         } = e;
@@ -616,6 +1002,7 @@ impl From<Entry> for UpdatePlace {
             tags,
             image_url,
             image_link_url,
+            image_id,
             links: custom_links,
         }
     }
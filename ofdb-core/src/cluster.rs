@@ -0,0 +1,206 @@
+use ofdb_entities::{category::*, geo::*, id::*};
+use std::collections::HashMap;
+
+// Clusters at the highest zoom level contain places
+// within a few meters
+pub const MAX_CLUSTER_ZOOM: u8 = 20;
+
+// Number of cells along each axis of a map tile, i.e. a
+// cell covers 64x64 pixels on a map with 256x256 pixel tiles.
+const CELLS_PER_TILE_AXIS: u32 = 4;
+
+// A regular grid in geographic coordinates that is aligned at
+// the south-western corner of the world. Cells are identified
+// by their (row, column) indexes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClusterGrid {
+    cell_size_deg: f64,
+}
+
+impl ClusterGrid {
+    pub fn with_zoom(zoom: u8) -> Self {
+        let zoom = zoom.min(MAX_CLUSTER_ZOOM);
+        let cells = f64::from((1u32 << zoom) * CELLS_PER_TILE_AXIS);
+        Self {
+            cell_size_deg: (LngCoord::max().to_deg() - LngCoord::min().to_deg()) / cells,
+        }
+    }
+
+    pub fn cell_size_deg(self) -> f64 {
+        self.cell_size_deg
+    }
+
+    fn cell_of(self, pos: MapPoint) -> (i64, i64) {
+        let row = (pos.lat().to_deg() - LatCoord::min().to_deg()) / self.cell_size_deg;
+        let col = (pos.lng().to_deg() - LngCoord::min().to_deg()) / self.cell_size_deg;
+        (row.floor() as i64, col.floor() as i64)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaceCluster {
+    pub centroid: MapPoint,
+    pub count: usize,
+    // The most frequent category among all places
+    pub category: Option<Id>,
+    // The bounding box of all places
+    pub bbox: MapBbox,
+}
+
+#[derive(Debug, Clone)]
+struct CellAggregate {
+    count: usize,
+    lat_sum: f64,
+    lng_sum: f64,
+    lat_min: f64,
+    lat_max: f64,
+    lng_min: f64,
+    lng_max: f64,
+    non_profit_count: usize,
+    commercial_count: usize,
+}
+
+impl CellAggregate {
+    fn new(lat: f64, lng: f64) -> Self {
+        Self {
+            count: 0,
+            lat_sum: 0.0,
+            lng_sum: 0.0,
+            lat_min: lat,
+            lat_max: lat,
+            lng_min: lng,
+            lng_max: lng,
+            non_profit_count: 0,
+            commercial_count: 0,
+        }
+    }
+
+    fn dominant_category(&self) -> Option<Id> {
+        if self.non_profit_count == 0 && self.commercial_count == 0 {
+            return None;
+        }
+        if self.non_profit_count >= self.commercial_count {
+            Some(Category::ID_NON_PROFIT.into())
+        } else {
+            Some(Category::ID_COMMERCIAL.into())
+        }
+    }
+}
+
+impl From<CellAggregate> for PlaceCluster {
+    fn from(from: CellAggregate) -> Self {
+        let count = from.count as f64;
+        Self {
+            centroid: MapPoint::from_lat_lng_deg(from.lat_sum / count, from.lng_sum / count),
+            count: from.count,
+            category: from.dominant_category(),
+            bbox: MapBbox::new(
+                MapPoint::from_lat_lng_deg(from.lat_min, from.lng_min),
+                MapPoint::from_lat_lng_deg(from.lat_max, from.lng_max),
+            ),
+        }
+    }
+}
+
+// Aggregates places into the cells of a grid
+#[derive(Debug, Clone)]
+pub struct PlaceClusterer {
+    grid: ClusterGrid,
+    cells: HashMap<(i64, i64), CellAggregate>,
+}
+
+impl PlaceClusterer {
+    pub fn new(grid: ClusterGrid) -> Self {
+        Self {
+            grid,
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn add_place<'a>(&mut self, pos: MapPoint, tags: impl IntoIterator<Item = &'a str>) {
+        let (lat, lng) = pos.to_lat_lng_deg();
+        let cell = self
+            .cells
+            .entry(self.grid.cell_of(pos))
+            .or_insert_with(|| CellAggregate::new(lat, lng));
+        cell.count += 1;
+        cell.lat_sum += lat;
+        cell.lng_sum += lng;
+        cell.lat_min = cell.lat_min.min(lat);
+        cell.lat_max = cell.lat_max.max(lat);
+        cell.lng_min = cell.lng_min.min(lng);
+        cell.lng_max = cell.lng_max.max(lng);
+        for tag in tags {
+            match tag {
+                Category::TAG_NON_PROFIT => cell.non_profit_count += 1,
+                Category::TAG_COMMERCIAL => cell.commercial_count += 1,
+                _ => {}
+            }
+        }
+    }
+
+    // Returns all clusters, starting with the largest
+    pub fn into_clusters(self) -> Vec<PlaceCluster> {
+        let mut cells: Vec<_> = self.cells.into_iter().collect();
+        // Break ties by the cell index to get a deterministic order
+        cells.sort_by(|(lhs_idx, lhs), (rhs_idx, rhs)| {
+            rhs.count.cmp(&lhs.count).then(lhs_idx.cmp(rhs_idx))
+        });
+        cells.into_iter().map(|(_, cell)| cell.into()).collect()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_cell_size_by_zoom() {
+        assert_eq!(90.0, ClusterGrid::with_zoom(0).cell_size_deg());
+        assert_eq!(45.0, ClusterGrid::with_zoom(1).cell_size_deg());
+        assert_eq!(
+            ClusterGrid::with_zoom(MAX_CLUSTER_ZOOM),
+            ClusterGrid::with_zoom(MAX_CLUSTER_ZOOM + 1)
+        );
+    }
+
+    #[test]
+    fn cluster_places_by_grid_cells() {
+        let mut clusterer = PlaceClusterer::new(ClusterGrid::with_zoom(4));
+        clusterer.add_place(
+            MapPoint::from_lat_lng_deg(48.0, 9.0),
+            vec![Category::TAG_COMMERCIAL],
+        );
+        clusterer.add_place(
+            MapPoint::from_lat_lng_deg(49.0, 10.0),
+            vec![Category::TAG_NON_PROFIT, "foo"],
+        );
+        clusterer.add_place(
+            MapPoint::from_lat_lng_deg(48.5, 9.5),
+            vec![Category::TAG_NON_PROFIT],
+        );
+        clusterer.add_place(MapPoint::from_lat_lng_deg(-33.9, 18.4), vec![]);
+        let clusters = clusterer.into_clusters();
+        assert_eq!(2, clusters.len());
+
+        let cluster = &clusters[0];
+        assert_eq!(3, cluster.count);
+        assert_eq!(Some(Category::ID_NON_PROFIT.into()), cluster.category);
+        let (lat, lng) = cluster.centroid.to_lat_lng_deg();
+        assert!((lat - 48.5).abs() < 1e-6);
+        assert!((lng - 9.5).abs() < 1e-6);
+        assert_eq!(
+            MapBbox::new(
+                MapPoint::from_lat_lng_deg(48.0, 9.0),
+                MapPoint::from_lat_lng_deg(49.0, 10.0),
+            ),
+            cluster.bbox
+        );
+
+        let cluster = &clusters[1];
+        assert_eq!(1, cluster.count);
+        assert_eq!(None, cluster.category);
+        assert_eq!(cluster.bbox.southwest(), cluster.bbox.northeast());
+    }
+}
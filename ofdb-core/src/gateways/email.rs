@@ -2,4 +2,13 @@ use ofdb_entities::email::Email;
 
 pub trait EmailGateway {
     fn compose_and_send(&self, recipients: &[Email], subject: &str, body: &str);
+    /// Sends an e-mail to a single recipient who could unsubscribe
+    /// from it with a single click on the given URL (RFC 2369, RFC 8058).
+    fn compose_and_send_unsubscribable(
+        &self,
+        recipient: &Email,
+        subject: &str,
+        body: &str,
+        unsubscribe_url: &str,
+    );
 }
@@ -1,5 +1,155 @@
-use ofdb_entities::address::Address;
+use ofdb_entities::{address::Address, geo::MapPoint};
+use thiserror::Error;
 
+#[derive(Debug, Error)]
+pub enum GeoCodingError {
+    #[error("Failed to send request: {0}")]
+    Request(String),
+    #[error("Unexpected response status: {0}")]
+    Status(u16),
+    #[error("Invalid response: {0}")]
+    Response(String),
+}
+
+pub type GeoCodingResult<T> = Result<T, GeoCodingError>;
+
+/// Resolves addresses and positions. A result of `Ok(None)`
+/// means that the query was answered but could not be resolved,
+/// while errors are only temporary failures.
 pub trait GeoCodingGateway {
-    fn resolve_address_lat_lng(&self, addr: &Address) -> Option<(f64, f64)>;
+    fn resolve_address_lat_lng(&self, addr: &Address) -> GeoCodingResult<Option<(f64, f64)>>;
+    // Reverse geocoding
+    fn resolve_pos_address(&self, pos: MapPoint) -> GeoCodingResult<Option<Address>>;
+}
+
+/// Tries multiple gateways in order until one of them resolves
+/// the query.
+pub struct GeoCodingChain {
+    gateways: Vec<Box<dyn GeoCodingGateway + Send + Sync>>,
+}
+
+impl GeoCodingChain {
+    pub fn new(gateways: Vec<Box<dyn GeoCodingGateway + Send + Sync>>) -> Self {
+        Self { gateways }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.gateways.is_empty()
+    }
+
+    // Unresolved queries are only reported as `Ok(None)` if no
+    // gateway has failed. Otherwise the last error is returned.
+    fn first_resolved<T, F>(&self, resolve: F) -> GeoCodingResult<Option<T>>
+    where
+        F: Fn(&dyn GeoCodingGateway) -> GeoCodingResult<Option<T>>,
+    {
+        let mut last_err = None;
+        for gw in &self.gateways {
+            match resolve(gw.as_ref()) {
+                Ok(Some(resolved)) => return Ok(Some(resolved)),
+                Ok(None) => {}
+                Err(err) => last_err = Some(err),
+            }
+        }
+        match last_err {
+            Some(err) => Err(err),
+            None => Ok(None),
+        }
+    }
+}
+
+impl GeoCodingGateway for GeoCodingChain {
+    fn resolve_address_lat_lng(&self, addr: &Address) -> GeoCodingResult<Option<(f64, f64)>> {
+        self.first_resolved(|gw| gw.resolve_address_lat_lng(addr))
+    }
+    fn resolve_pos_address(&self, pos: MapPoint) -> GeoCodingResult<Option<Address>> {
+        self.first_resolved(|gw| gw.resolve_pos_address(pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    enum Answer {
+        Resolved,
+        Unresolved,
+        Failed,
+    }
+
+    struct FakeGateway {
+        answer: Answer,
+        requests: AtomicUsize,
+    }
+
+    impl FakeGateway {
+        fn new(answer: Answer) -> Self {
+            Self {
+                answer,
+                requests: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    impl GeoCodingGateway for FakeGateway {
+        fn resolve_address_lat_lng(&self, _: &Address) -> GeoCodingResult<Option<(f64, f64)>> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            match self.answer {
+                Answer::Resolved => Ok(Some((1.0, 2.0))),
+                Answer::Unresolved => Ok(None),
+                Answer::Failed => Err(GeoCodingError::Status(503)),
+            }
+        }
+        fn resolve_pos_address(&self, _: MapPoint) -> GeoCodingResult<Option<Address>> {
+            self.resolve_address_lat_lng(&Address::default())
+                .map(|res| res.map(|_| Address::default()))
+        }
+    }
+
+    fn chain(answers: Vec<Answer>) -> GeoCodingChain {
+        GeoCodingChain::new(
+            answers
+                .into_iter()
+                .map(|a| Box::new(FakeGateway::new(a)) as Box<dyn GeoCodingGateway + Send + Sync>)
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn fall_back_to_next_gateway() {
+        let addr = Address::default();
+        let c = chain(vec![Answer::Failed, Answer::Unresolved, Answer::Resolved]);
+        assert_eq!(Some((1.0, 2.0)), c.resolve_address_lat_lng(&addr).unwrap());
+        let c = chain(vec![Answer::Unresolved, Answer::Unresolved]);
+        assert_eq!(None, c.resolve_address_lat_lng(&addr).unwrap());
+        // Unresolved due to a failure
+        let c = chain(vec![Answer::Unresolved, Answer::Failed]);
+        assert!(c.resolve_address_lat_lng(&addr).is_err());
+        let c = chain(vec![]);
+        assert_eq!(None, c.resolve_address_lat_lng(&addr).unwrap());
+    }
+
+    impl GeoCodingGateway for Arc<FakeGateway> {
+        fn resolve_address_lat_lng(&self, addr: &Address) -> GeoCodingResult<Option<(f64, f64)>> {
+            self.as_ref().resolve_address_lat_lng(addr)
+        }
+        fn resolve_pos_address(&self, pos: MapPoint) -> GeoCodingResult<Option<Address>> {
+            self.as_ref().resolve_pos_address(pos)
+        }
+    }
+
+    #[test]
+    fn stop_after_first_resolved() {
+        let first = Arc::new(FakeGateway::new(Answer::Resolved));
+        let second = Arc::new(FakeGateway::new(Answer::Resolved));
+        let c = GeoCodingChain::new(vec![Box::new(first.clone()), Box::new(second.clone())]);
+        let pos = MapPoint::from_lat_lng_deg(1.0, 2.0);
+        assert!(c.resolve_pos_address(pos).unwrap().is_some());
+        assert_eq!(1, first.requests.load(Ordering::SeqCst));
+        assert_eq!(0, second.requests.load(Ordering::SeqCst));
+    }
 }
@@ -0,0 +1,30 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ImageStorageError {
+    #[error("Invalid storage key: {0}")]
+    InvalidKey(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+pub type ImageStorageResult<T> = Result<T, ImageStorageError>;
+
+/// A backend for storing the binary data of images.
+///
+/// Keys only consist of alphanumeric ASCII characters, `-` and `.`.
+pub trait ImageStorageGateway {
+    // Replaces any existing data with the same key
+    fn store(&self, key: &str, data: &[u8]) -> ImageStorageResult<()>;
+    fn load(&self, key: &str) -> ImageStorageResult<Option<Vec<u8>>>;
+    // Deleting a missing key is not an error
+    fn delete(&self, key: &str) -> ImageStorageResult<()>;
+}
+
+pub fn is_valid_storage_key(key: &str) -> bool {
+    !key.is_empty()
+        && !key.starts_with('.')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+}
@@ -1,3 +1,5 @@
 pub mod email;
 pub mod geocode;
+pub mod image;
 pub mod notify;
+pub mod webhook;
@@ -1,19 +1,37 @@
 use ofdb_entities::{
-    category::Category, event::Event, nonce::EmailNonce, place::Place, user::User,
+    category::Category, event::Event, id::Id, locale::Locale, nonce::EmailNonce, place::Place,
+    user::User,
 };
 
+/// The receiver of a notification together with the
+/// preferred language for its content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipient {
+    pub email: String,
+    pub locale: Option<Locale>,
+    /// The subscription that caused the notification and that
+    /// could be cancelled by the recipient
+    pub subscription_id: Id,
+}
+
+/// A created or updated item that is included in the digest
+/// of a subscription.
+#[derive(Debug, Clone)]
+pub enum DigestItem {
+    PlaceAdded(Place),
+    PlaceUpdated(Place),
+    EventCreated(Event),
+    EventUpdated(Event),
+}
+
 pub trait NotificationGateway {
-    fn place_added(&self, email_addresses: &[String], place: &Place, all_categories: Vec<Category>);
-    fn place_updated(
-        &self,
-        email_addresses: &[String],
-        place: &Place,
-        all_categories: Vec<Category>,
-    );
-    fn event_created(&self, email_addresses: &[String], event: &Event);
-    fn event_updated(&self, email_addresses: &[String], event: &Event);
+    fn place_added(&self, recipients: &[Recipient], place: &Place, all_categories: Vec<Category>);
+    fn place_updated(&self, recipients: &[Recipient], place: &Place, all_categories: Vec<Category>);
+    fn event_created(&self, recipients: &[Recipient], event: &Event);
+    fn event_updated(&self, recipients: &[Recipient], event: &Event);
+    fn subscription_digest(&self, recipient: &Recipient, items: &[DigestItem]);
     fn user_registered_kvm(&self, user: &User);
     fn user_registered_ofdb(&self, user: &User);
     fn user_registered(&self, user: &User, url: &str);
-    fn user_reset_password_requested(&self, email_nonce: &EmailNonce);
+    fn user_reset_password_requested(&self, email_nonce: &EmailNonce, locale: Option<&Locale>);
 }
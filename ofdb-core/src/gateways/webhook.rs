@@ -0,0 +1,23 @@
+use ofdb_entities::{url::Url, webhook::WebhookEventType};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum WebhookDeliveryError {
+    #[error("Unexpected response status: {0}")]
+    Status(u16),
+    #[error("Failed to send request: {0}")]
+    Request(String),
+}
+
+pub trait WebhookGateway {
+    // Posts the JSON payload signed with the shared secret and
+    // returns the status code of a successful response.
+    fn deliver(
+        &self,
+        url: &Url,
+        secret: &str,
+        delivery_id: &str,
+        event_type: WebhookEventType,
+        payload: &str,
+    ) -> Result<u16, WebhookDeliveryError>;
+}
@@ -1,6 +1,9 @@
 pub mod bbox;
+pub mod cluster;
 pub mod gateways;
+pub mod opening_hours;
 pub mod rating;
+pub mod recurrence;
 pub mod tag;
 pub mod text;
 pub mod user;
@@ -0,0 +1,469 @@
+//! A subset of the OpenStreetMap `opening_hours` syntax
+//!
+//! <https://wiki.openstreetmap.org/wiki/Key:opening_hours/specification>
+//!
+//! Supported are `24/7`, rules separated by `;`, weekday selectors
+//! (e.g. `Mo-Fr` or `Sa,Su`), time ranges (e.g. `08:00-12:00,13:00-18:00`
+//! or `22:00-02:00` across midnight) and the modifiers `open`, `off`
+//! and `closed`. Rules that only select public or school holidays
+//! (`PH`, `SH`) are accepted, but don't affect the weekly schedule.
+
+use std::str::FromStr;
+use thiserror::Error;
+
+pub const MINUTES_PER_DAY: u32 = 24 * 60;
+pub const MINUTES_PER_WEEK: u32 = 7 * MINUTES_PER_DAY;
+
+const WEEKDAYS: [&str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+
+const HOLIDAYS: [&str; 2] = ["PH", "SH"];
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+// Time ranges may extend into the next day, e.g. `20:00-26:00`
+const MAX_END_OF_DAY: u32 = 2 * MINUTES_PER_DAY;
+
+/// The local time within a week, starting on Monday at 00:00
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MinuteOfWeek(u32);
+
+impl MinuteOfWeek {
+    /// The weekday is counted from Monday (0) to Sunday (6)
+    pub fn new(weekday: u32, hour: u32, minute: u32) -> Self {
+        debug_assert!(weekday < 7);
+        debug_assert!(hour < 24);
+        debug_assert!(minute < 60);
+        Self(weekday * MINUTES_PER_DAY + hour * 60 + minute)
+    }
+
+    pub const fn to_inner(self) -> u32 {
+        self.0
+    }
+
+    /// The index of the slot of the given length that contains this time
+    pub fn slot(self, slot_minutes: u32) -> u32 {
+        debug_assert!(slot_minutes > 0);
+        self.0 / slot_minutes
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{0}")]
+pub struct OpeningHoursParseError(String);
+
+impl OpeningHoursParseError {
+    fn new(msg: impl Into<String>) -> Self {
+        Self(msg.into())
+    }
+}
+
+type Result<T> = std::result::Result<T, OpeningHoursParseError>;
+
+/// The regular opening hours of a week
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeeklySchedule {
+    // Sorted and disjoint half-open intervals [start, end)
+    // in minutes of the week
+    intervals: Vec<(u32, u32)>,
+}
+
+impl WeeklySchedule {
+    pub fn is_open_at(&self, t: MinuteOfWeek) -> bool {
+        self.intervals
+            .iter()
+            .any(|(start, end)| *start <= t.0 && t.0 < *end)
+    }
+
+    pub fn is_always_open(&self) -> bool {
+        self.intervals == [(0, MINUTES_PER_WEEK)]
+    }
+
+    pub fn is_never_open(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The indexes of all slots of the given length that are open
+    /// at their start time.
+    pub fn open_slots(&self, slot_minutes: u32) -> Vec<u32> {
+        debug_assert!(slot_minutes > 0);
+        (0..(MINUTES_PER_WEEK + slot_minutes - 1) / slot_minutes)
+            .filter(|slot| self.is_open_at(MinuteOfWeek(slot * slot_minutes)))
+            .collect()
+    }
+
+    fn from_days(days: &[Vec<(u32, u32)>]) -> Self {
+        let mut intervals = Vec::new();
+        for (day, ranges) in days.iter().enumerate() {
+            let offset = day as u32 * MINUTES_PER_DAY;
+            for (start, end) in ranges {
+                let (start, end) = (offset + start, offset + end);
+                if end > MINUTES_PER_WEEK {
+                    // Sunday night until Monday morning
+                    intervals.push((start, MINUTES_PER_WEEK));
+                    intervals.push((0, end - MINUTES_PER_WEEK));
+                } else {
+                    intervals.push((start, end));
+                }
+            }
+        }
+        intervals.sort_unstable();
+        let mut merged: Vec<(u32, u32)> = Vec::with_capacity(intervals.len());
+        for (start, end) in intervals {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        Self { intervals: merged }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Modifier {
+    Open,
+    Closed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    // None if all days are selected
+    weekdays: Option<Vec<usize>>,
+    holidays: bool,
+    // Empty if the whole day is selected
+    times: Vec<(u32, u32)>,
+    modifier: Modifier,
+}
+
+impl Rule {
+    fn apply(&self, days: &mut [Vec<(u32, u32)>]) {
+        let selected: Vec<usize> = match &self.weekdays {
+            Some(weekdays) => weekdays.clone(),
+            // Rules for holidays only are not part of the weekly schedule
+            None if self.holidays => return,
+            None => (0..days.len()).collect(),
+        };
+        for day in selected {
+            let ranges = &mut days[day];
+            match self.modifier {
+                Modifier::Open => {
+                    // Replaces all previous rules for the same day
+                    *ranges = if self.times.is_empty() {
+                        vec![(0, MINUTES_PER_DAY)]
+                    } else {
+                        self.times.clone()
+                    };
+                }
+                Modifier::Closed => {
+                    if self.times.is_empty() {
+                        ranges.clear();
+                    } else {
+                        for closed in &self.times {
+                            *ranges = subtract(ranges, *closed);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn subtract(ranges: &[(u32, u32)], (closed_start, closed_end): (u32, u32)) -> Vec<(u32, u32)> {
+    let mut remaining = Vec::with_capacity(ranges.len() + 1);
+    for &(start, end) in ranges {
+        if closed_end <= start || end <= closed_start {
+            remaining.push((start, end));
+            continue;
+        }
+        if start < closed_start {
+            remaining.push((start, closed_start));
+        }
+        if closed_end < end {
+            remaining.push((closed_end, end));
+        }
+    }
+    remaining
+}
+
+fn parse_weekday(s: &str) -> Result<usize> {
+    if let Some(day) = WEEKDAYS.iter().position(|d| *d == s) {
+        return Ok(day);
+    }
+    if MONTHS.iter().any(|m| s.starts_with(m)) {
+        return Err(OpeningHoursParseError::new(
+            "Month selectors are not supported",
+        ));
+    }
+    Err(OpeningHoursParseError::new(format!(
+        "Invalid weekday '{}'",
+        s
+    )))
+}
+
+// Returns the selected weekdays and if holidays are selected
+fn parse_weekday_selector(s: &str) -> Result<(Vec<usize>, bool)> {
+    let mut weekdays = Vec::new();
+    let mut holidays = false;
+    for part in s.split(',') {
+        if HOLIDAYS.contains(&part) {
+            holidays = true;
+            continue;
+        }
+        let mut range = part.splitn(2, '-');
+        let first = parse_weekday(range.next().unwrap_or_default())?;
+        let last = range
+            .next()
+            .map(parse_weekday)
+            .transpose()?
+            .unwrap_or(first);
+        // Ranges may wrap around the end of the week, e.g. `Fr-Mo`
+        let mut day = first;
+        loop {
+            if !weekdays.contains(&day) {
+                weekdays.push(day);
+            }
+            if day == last {
+                break;
+            }
+            day = (day + 1) % WEEKDAYS.len();
+        }
+    }
+    Ok((weekdays, holidays))
+}
+
+fn parse_time(s: &str) -> Result<u32> {
+    let invalid = || OpeningHoursParseError::new(format!("Invalid time '{}'", s));
+    let mut parts = s.splitn(2, ':');
+    let hour = parts.next().unwrap_or_default();
+    let minute = parts.next().ok_or_else(invalid)?;
+    if hour.len() != 2 || minute.len() != 2 {
+        return Err(invalid());
+    }
+    let hour: u32 = hour.parse().map_err(|_| invalid())?;
+    let minute: u32 = minute.parse().map_err(|_| invalid())?;
+    if minute >= 60 {
+        return Err(invalid());
+    }
+    Ok(hour * 60 + minute)
+}
+
+fn parse_time_range(s: &str) -> Result<(u32, u32)> {
+    if s.ends_with('+') {
+        return Err(OpeningHoursParseError::new(
+            "Open end times are not supported",
+        ));
+    }
+    let mut parts = s.splitn(2, '-');
+    let start = parse_time(parts.next().unwrap_or_default())?;
+    let end =
+        parts.next().map(parse_time).transpose()?.ok_or_else(|| {
+            OpeningHoursParseError::new(format!("Missing end of time range '{}'", s))
+        })?;
+    if start >= MINUTES_PER_DAY {
+        return Err(OpeningHoursParseError::new(format!(
+            "Invalid start of time range '{}'",
+            s
+        )));
+    }
+    // A time range that ends before it starts continues on the next day
+    let end = if end <= start {
+        end + MINUTES_PER_DAY
+    } else {
+        end
+    };
+    if end > MAX_END_OF_DAY || end - start > MINUTES_PER_DAY {
+        return Err(OpeningHoursParseError::new(format!(
+            "Invalid end of time range '{}'",
+            s
+        )));
+    }
+    Ok((start, end))
+}
+
+fn starts_with_digit(s: &str) -> bool {
+    s.chars()
+        .next()
+        .map(|c| c.is_ascii_digit())
+        .unwrap_or(false)
+}
+
+fn parse_rule(s: &str) -> Result<Rule> {
+    if s.contains('"') {
+        return Err(OpeningHoursParseError::new("Comments are not supported"));
+    }
+    // Whitespace around the separators of lists is optional
+    let normalized = s.split(',').map(str::trim).collect::<Vec<_>>().join(",");
+    let mut tokens = normalized.split_whitespace().peekable();
+    let mut rule = Rule {
+        weekdays: None,
+        holidays: false,
+        times: vec![],
+        modifier: Modifier::Open,
+    };
+    if tokens.peek() == Some(&"24/7") {
+        tokens.next();
+        rule.times = vec![(0, MINUTES_PER_DAY)];
+    } else {
+        if let Some(token) = tokens
+            .peek()
+            .filter(|t| !starts_with_digit(t) && !is_modifier(t))
+        {
+            let (weekdays, holidays) = parse_weekday_selector(token)?;
+            if !weekdays.is_empty() {
+                rule.weekdays = Some(weekdays);
+            }
+            rule.holidays = holidays;
+            tokens.next();
+        }
+        if let Some(token) = tokens.peek().filter(|t| starts_with_digit(t)) {
+            rule.times = token
+                .split(',')
+                .map(parse_time_range)
+                .collect::<Result<_>>()?;
+            tokens.next();
+        }
+    }
+    if let Some(token) = tokens.peek().filter(|t| is_modifier(t)) {
+        if *token != "open" {
+            rule.modifier = Modifier::Closed;
+        }
+        tokens.next();
+    }
+    if let Some(token) = tokens.next() {
+        return Err(OpeningHoursParseError::new(format!(
+            "Unexpected '{}' in rule '{}'",
+            token, s
+        )));
+    }
+    if rule.weekdays.is_none() && !rule.holidays && rule.times.is_empty() {
+        return Err(OpeningHoursParseError::new(format!(
+            "Incomplete rule '{}'",
+            s
+        )));
+    }
+    Ok(rule)
+}
+
+fn is_modifier(s: &str) -> bool {
+    matches!(s, "open" | "off" | "closed")
+}
+
+impl FromStr for WeeklySchedule {
+    type Err = OpeningHoursParseError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(OpeningHoursParseError::new("Empty opening hours"));
+        }
+        if s.contains("||") {
+            return Err(OpeningHoursParseError::new(
+                "Fallback rules are not supported",
+            ));
+        }
+        let mut days = vec![vec![]; WEEKDAYS.len()];
+        for rule in s.split(';') {
+            let rule = rule.trim();
+            if rule.is_empty() {
+                return Err(OpeningHoursParseError::new("Empty rule"));
+            }
+            parse_rule(rule)?.apply(&mut days);
+        }
+        Ok(Self::from_days(&days))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> WeeklySchedule {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn always_open() {
+        let schedule = parse("24/7");
+        assert!(schedule.is_always_open());
+        assert!(schedule.is_open_at(MinuteOfWeek::new(6, 23, 59)));
+        assert_eq!(7 * 24 * 4, schedule.open_slots(15).len());
+        assert!(parse("Mo-Su 00:00-24:00").is_always_open());
+    }
+
+    #[test]
+    fn weekdays_and_time_ranges() {
+        let schedule = parse("Mo-Fr 08:00-12:00, 13:00-18:30; Sa 10:00-14:00");
+        assert!(!schedule.is_open_at(MinuteOfWeek::new(0, 7, 59)));
+        assert!(schedule.is_open_at(MinuteOfWeek::new(0, 8, 0)));
+        assert!(!schedule.is_open_at(MinuteOfWeek::new(2, 12, 30)));
+        assert!(schedule.is_open_at(MinuteOfWeek::new(4, 18, 29)));
+        assert!(!schedule.is_open_at(MinuteOfWeek::new(4, 18, 30)));
+        assert!(schedule.is_open_at(MinuteOfWeek::new(5, 11, 0)));
+        assert!(!schedule.is_open_at(MinuteOfWeek::new(6, 11, 0)));
+        assert_eq!(
+            parse("Sa 10:00-14:00; Mo,Tu,We,Th,Fr 08:00-12:00,13:00-18:30"),
+            schedule
+        );
+    }
+
+    #[test]
+    fn overnight_and_wrapping_ranges() {
+        let schedule = parse("Fr-Mo 22:00-02:00");
+        assert!(schedule.is_open_at(MinuteOfWeek::new(4, 23, 0)));
+        assert!(schedule.is_open_at(MinuteOfWeek::new(5, 1, 0)));
+        assert!(schedule.is_open_at(MinuteOfWeek::new(0, 1, 0)));
+        assert!(schedule.is_open_at(MinuteOfWeek::new(1, 1, 0)));
+        assert!(!schedule.is_open_at(MinuteOfWeek::new(1, 3, 0)));
+        assert!(!schedule.is_open_at(MinuteOfWeek::new(2, 1, 0)));
+    }
+
+    #[test]
+    fn later_rules_override_earlier_rules() {
+        let schedule = parse("Mo-Sa 09:00-18:00; We 09:00-12:00; Sa off; PH off");
+        assert!(schedule.is_open_at(MinuteOfWeek::new(1, 15, 0)));
+        assert!(!schedule.is_open_at(MinuteOfWeek::new(2, 15, 0)));
+        assert!(!schedule.is_open_at(MinuteOfWeek::new(5, 10, 0)));
+
+        let schedule = parse("Mo-Fr 08:00-18:00; Mo-Fr 12:00-13:00 off");
+        assert!(schedule.is_open_at(MinuteOfWeek::new(0, 11, 59)));
+        assert!(!schedule.is_open_at(MinuteOfWeek::new(0, 12, 0)));
+        assert!(schedule.is_open_at(MinuteOfWeek::new(0, 13, 0)));
+
+        assert!(parse("Mo-Su closed").is_never_open());
+        assert!(parse("Sa,Su").is_open_at(MinuteOfWeek::new(6, 12, 0)));
+    }
+
+    #[test]
+    fn open_slots() {
+        let schedule = parse("Mo 00:10-01:00");
+        assert_eq!(vec![1, 2, 3], schedule.open_slots(15));
+        assert_eq!(3, MinuteOfWeek::new(0, 0, 59).slot(15));
+    }
+
+    #[test]
+    fn reject_invalid_or_unsupported_syntax() {
+        for invalid in &[
+            "",
+            "open",
+            "Mo-Fr 8:00-18:00",
+            "Mo-Fr 08:00",
+            "Mo-Fr 08:60-18:00",
+            "Mo-Fr 24:00-26:00",
+            "Mo-Fr 08:00-18:00;",
+            "Mo-Fx 08:00-18:00",
+            "Mo-Fr: 08:00-18:00",
+            "Mo-Fr 08:00-18:00 foo",
+            "Mo-Fr 08:00+",
+            "Jan-Mar Mo-Fr 08:00-18:00",
+            "Mo-Fr 08:00-18:00 || \"by appointment\"",
+            "Mo-Fr 08:00-18:00 \"by appointment\"",
+        ] {
+            assert!(
+                invalid.parse::<WeeklySchedule>().is_err(),
+                "{} should be rejected",
+                invalid
+            );
+        }
+    }
+}
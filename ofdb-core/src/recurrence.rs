@@ -0,0 +1,347 @@
+use chrono::{prelude::*, Duration};
+use ofdb_entities::event::*;
+use std::collections::VecDeque;
+
+// Safety limit for rules that never or only rarely produce
+// occurrences, e.g. monthly on the 31st of February
+const MAX_PERIODS: u32 = 10_000;
+
+// Far beyond any reasonable event
+const MAX_YEAR: i32 = 9999;
+
+/// The start times of all occurrences of a recurring event in
+/// chronological order, starting with the first occurrence.
+///
+/// Occurrences that are excluded (EXDATE) are skipped but still
+/// count for the maximum number of occurrences (COUNT).
+#[derive(Debug, Clone)]
+pub struct Occurrences<'a> {
+    start: NaiveDateTime,
+    recurrence: &'a Recurrence,
+    started: bool,
+    period: u32,
+    pending: VecDeque<NaiveDateTime>,
+    generated: u32,
+    done: bool,
+}
+
+pub fn occurrences(start: NaiveDateTime, recurrence: &Recurrence) -> Occurrences {
+    Occurrences {
+        start,
+        recurrence,
+        started: false,
+        // The first period also contains the start
+        period: 0,
+        pending: VecDeque::new(),
+        generated: 0,
+        done: false,
+    }
+}
+
+/// An upper bound for the start times of all occurrences of a
+/// recurring event or `None` if the series is open-ended.
+///
+/// Series that are only bounded by UNTIL end at that date without
+/// generating their occurrences.
+pub fn latest_occurrence_start(
+    start: NaiveDateTime,
+    recurrence: &Recurrence,
+) -> Option<NaiveDateTime> {
+    let rule = &recurrence.rule;
+    if rule.count.is_some() {
+        // Also bounded by UNTIL if present
+        return Some(occurrences(start, recurrence).last().unwrap_or(start));
+    }
+    rule.until.map(|until| until.max(start))
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd(next_year, next_month, 1).pred().day()
+}
+
+fn monthly_days(rule: &RecurrenceRule, year: i32, month: u32, start_day: u32) -> Vec<u32> {
+    let last_day = days_in_month(year, month);
+    let mut by_day: Vec<u32> = Vec::new();
+    for WeekdayNum { ordinal, weekday } in &rule.by_day {
+        let days: Vec<u32> = (1..=last_day)
+            .filter(|day| NaiveDate::from_ymd(year, month, *day).weekday() == *weekday)
+            .collect();
+        match ordinal {
+            None => by_day.extend(days),
+            Some(n) if *n > 0 => by_day.extend(days.get(*n as usize - 1)),
+            Some(n) => by_day.extend(
+                days.len()
+                    .checked_sub(n.abs() as usize)
+                    .and_then(|i| days.get(i)),
+            ),
+        }
+    }
+    let by_month_day: Vec<u32> = rule
+        .by_month_day
+        .iter()
+        .filter_map(|day| {
+            let day = if *day > 0 {
+                *day as i64
+            } else {
+                i64::from(last_day) + 1 + i64::from(*day)
+            };
+            if day >= 1 && day <= i64::from(last_day) {
+                Some(day as u32)
+            } else {
+                None
+            }
+        })
+        .collect();
+    // Days that don't exist in this month must not lift the restriction
+    match (rule.by_day.is_empty(), rule.by_month_day.is_empty()) {
+        (true, true) => Some(start_day)
+            .filter(|day| *day <= last_day)
+            .into_iter()
+            .collect(),
+        (false, true) => by_day,
+        (true, false) => by_month_day,
+        // Both selectors restrict the days of the month
+        (false, false) => by_day
+            .into_iter()
+            .filter(|day| by_month_day.contains(day))
+            .collect(),
+    }
+}
+
+impl<'a> Occurrences<'a> {
+    // All candidates of the given period in chronological order
+    fn period_candidates(&self, period: u32) -> Option<Vec<NaiveDateTime>> {
+        let rule = &self.recurrence.rule;
+        let step = i64::from(period) * i64::from(rule.interval);
+        let date = self.start.date();
+        let time = self.start.time();
+        let mut dates = match rule.frequency {
+            Frequency::Daily => vec![date.checked_add_signed(Duration::days(step))?],
+            Frequency::Weekly => {
+                let week_start = date
+                    .checked_sub_signed(Duration::days(i64::from(
+                        date.weekday().num_days_from_monday(),
+                    )))?
+                    .checked_add_signed(Duration::weeks(step))?;
+                let mut weekdays: Vec<_> = rule.by_day.iter().map(|d| d.weekday).collect();
+                if weekdays.is_empty() {
+                    weekdays.push(date.weekday());
+                }
+                weekdays
+                    .into_iter()
+                    .map(|weekday| {
+                        week_start + Duration::days(i64::from(weekday.num_days_from_monday()))
+                    })
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let month_index = i64::from(date.year()) * 12 + i64::from(date.month0()) + step;
+                let year = month_index.div_euclid(12);
+                if year > i64::from(MAX_YEAR) {
+                    return None;
+                }
+                let year = year as i32;
+                let month = month_index.rem_euclid(12) as u32 + 1;
+                monthly_days(rule, year, month, date.day())
+                    .into_iter()
+                    .map(|day| NaiveDate::from_ymd(year, month, day))
+                    .collect()
+            }
+            Frequency::Yearly => {
+                let year = i64::from(date.year()) + step;
+                if year > i64::from(MAX_YEAR) {
+                    return None;
+                }
+                // Skipped if the day doesn't exist, e.g. February 29th
+                NaiveDate::from_ymd_opt(year as i32, date.month(), date.day())
+                    .into_iter()
+                    .collect()
+            }
+        };
+        dates.sort_unstable();
+        dates.dedup();
+        Some(dates.into_iter().map(|date| date.and_time(time)).collect())
+    }
+}
+
+impl<'a> Iterator for Occurrences<'a> {
+    type Item = NaiveDateTime;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let recurrence = self.recurrence;
+        let rule = &recurrence.rule;
+        loop {
+            if self.done {
+                return None;
+            }
+            let candidate = if !self.started {
+                // The start is always the first occurrence
+                self.started = true;
+                self.start
+            } else if let Some(candidate) = self.pending.pop_front() {
+                candidate
+            } else {
+                if self.period > MAX_PERIODS {
+                    self.done = true;
+                    continue;
+                }
+                match self.period_candidates(self.period) {
+                    Some(candidates) => {
+                        let start = self.start;
+                        self.pending
+                            .extend(candidates.into_iter().filter(|c| *c > start));
+                    }
+                    None => self.done = true,
+                }
+                self.period += 1;
+                continue;
+            };
+            if rule
+                .count
+                .map(|count| self.generated >= count)
+                .unwrap_or(false)
+                || rule.until.map(|until| candidate > until).unwrap_or(false)
+            {
+                self.done = true;
+                continue;
+            }
+            self.generated += 1;
+            if recurrence.exdates.contains(&candidate) {
+                continue;
+            }
+            return Some(candidate);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn recurrence(rule: &str) -> Recurrence {
+        Recurrence {
+            rule: rule.parse().unwrap(),
+            exdates: vec![],
+        }
+    }
+
+    fn first_occurrences(start: &str, recurrence: &Recurrence, n: usize) -> Vec<NaiveDateTime> {
+        occurrences(dt(start), recurrence).take(n).collect()
+    }
+
+    #[test]
+    fn daily_with_count() {
+        let r = recurrence("FREQ=DAILY;INTERVAL=2;COUNT=3");
+        assert_eq!(
+            vec![
+                dt("2021-03-01 10:00"),
+                dt("2021-03-03 10:00"),
+                dt("2021-03-05 10:00")
+            ],
+            first_occurrences("2021-03-01 10:00", &r, 10)
+        );
+    }
+
+    #[test]
+    fn weekly_by_day_until() {
+        // Monday, 2021-03-01
+        let r = recurrence("FREQ=WEEKLY;BYDAY=MO,TH;UNTIL=20210311T100000Z");
+        assert_eq!(
+            vec![
+                dt("2021-03-01 10:00"),
+                dt("2021-03-04 10:00"),
+                dt("2021-03-08 10:00"),
+                dt("2021-03-11 10:00"),
+            ],
+            first_occurrences("2021-03-01 10:00", &r, 10)
+        );
+    }
+
+    #[test]
+    fn monthly_by_ordinal_weekday() {
+        // The first Saturday of each month
+        let r = recurrence("FREQ=MONTHLY;BYDAY=1SA");
+        assert_eq!(
+            vec![
+                dt("2021-03-06 14:00"),
+                dt("2021-04-03 14:00"),
+                dt("2021-05-01 14:00"),
+            ],
+            first_occurrences("2021-03-06 14:00", &r, 3)
+        );
+        // The last day of each month
+        let r = recurrence("FREQ=MONTHLY;BYMONTHDAY=-1");
+        assert_eq!(
+            vec![
+                dt("2021-01-31 18:00"),
+                dt("2021-02-28 18:00"),
+                dt("2021-03-31 18:00"),
+            ],
+            first_occurrences("2021-01-31 18:00", &r, 3)
+        );
+        // Months without a 31st are skipped
+        let r = recurrence("FREQ=MONTHLY");
+        assert_eq!(
+            vec![dt("2021-01-31 18:00"), dt("2021-03-31 18:00")],
+            first_occurrences("2021-01-31 18:00", &r, 2)
+        );
+    }
+
+    #[test]
+    fn yearly_skips_missing_days() {
+        let r = recurrence("FREQ=YEARLY;COUNT=3");
+        assert_eq!(
+            vec![
+                dt("2020-02-29 12:00"),
+                dt("2024-02-29 12:00"),
+                dt("2028-02-29 12:00")
+            ],
+            first_occurrences("2020-02-29 12:00", &r, 10)
+        );
+    }
+
+    #[test]
+    fn exclude_occurrences() {
+        let mut r = recurrence("FREQ=WEEKLY;COUNT=3");
+        r.exdates = vec![dt("2021-03-08 10:00")];
+        assert_eq!(
+            vec![dt("2021-03-01 10:00"), dt("2021-03-15 10:00")],
+            first_occurrences("2021-03-01 10:00", &r, 10)
+        );
+    }
+
+    #[test]
+    fn latest_occurrence_start_of_series() {
+        let start = dt("2021-03-01 10:00");
+        let r = recurrence("FREQ=DAILY;INTERVAL=2;COUNT=3");
+        assert_eq!(
+            Some(dt("2021-03-05 10:00")),
+            latest_occurrence_start(start, &r)
+        );
+        let r = recurrence("FREQ=WEEKLY;BYDAY=MO,TH;UNTIL=20210311T100000Z");
+        assert_eq!(
+            Some(dt("2021-03-11 10:00")),
+            latest_occurrence_start(start, &r)
+        );
+        let r = recurrence("FREQ=MONTHLY");
+        assert_eq!(None, latest_occurrence_start(start, &r));
+    }
+
+    #[test]
+    fn rules_without_occurrences_terminate() {
+        let r = recurrence("FREQ=MONTHLY;BYMONTHDAY=31;BYDAY=2MO");
+        assert_eq!(
+            vec![dt("2021-01-31 18:00")],
+            first_occurrences("2021-01-31 18:00", &r, 2)
+        );
+    }
+}
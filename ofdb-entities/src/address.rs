@@ -16,4 +16,29 @@ impl Address {
             && self.country.is_none()
             && self.state.is_none()
     }
+
+    /// Completes all missing fields with those of another address
+    /// while keeping the existing fields.
+    pub fn fill_missing(&mut self, other: Address) {
+        let Address {
+            street,
+            zip,
+            city,
+            country,
+            state,
+        } = other;
+        self.street = self.street.take().or(street);
+        self.zip = self.zip.take().or(zip);
+        self.city = self.city.take().or(city);
+        self.country = self.country.take().or(country);
+        self.state = self.state.take().or(state);
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.street.is_some()
+            && self.zip.is_some()
+            && self.city.is_some()
+            && self.country.is_some()
+            && self.state.is_some()
+    }
 }
@@ -0,0 +1,135 @@
+use crate::{id::*, time::TimestampMs};
+use sha2::{Digest, Sha256};
+use std::{fmt, str::FromStr};
+use strum::EnumIter;
+use thiserror::Error;
+
+/// The permissions that are granted to an API token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum ApiTokenScope {
+    EventsRead,
+    EventsWrite,
+    EventsClearance,
+    PlacesRead,
+    PlacesWrite,
+    PlacesClearance,
+    PlacesHistoryRead,
+    WebhooksWrite,
+    ImagesWrite,
+}
+
+impl ApiTokenScope {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::EventsRead => "events:read",
+            Self::EventsWrite => "events:write",
+            Self::EventsClearance => "events:clearance",
+            Self::PlacesRead => "places:read",
+            Self::PlacesWrite => "places:write",
+            Self::PlacesClearance => "places:clearance",
+            Self::PlacesHistoryRead => "places:history:read",
+            Self::WebhooksWrite => "webhooks:write",
+            Self::ImagesWrite => "images:write",
+        }
+    }
+}
+
+impl fmt::Display for ApiTokenScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid API token scope: {0}")]
+pub struct ApiTokenScopeParseError(String);
+
+impl FromStr for ApiTokenScope {
+    type Err = ApiTokenScopeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use strum::IntoEnumIterator;
+        Self::iter()
+            .find(|t| t.as_str() == s)
+            .ok_or_else(|| ApiTokenScopeParseError(s.to_string()))
+    }
+}
+
+/// A named API token of an organization.
+///
+/// Only the hash of the secret token is stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiToken {
+    pub id: Id,
+    pub org_id: Id,
+    pub name: String,
+    pub token_hash: String,
+    pub scopes: Vec<ApiTokenScope>,
+    pub created_at: TimestampMs,
+    pub expires_at: Option<TimestampMs>,
+    pub last_used_at: Option<TimestampMs>,
+    pub revoked_at: Option<TimestampMs>,
+}
+
+impl ApiToken {
+    /// The hex-encoded SHA-256 hash of a secret token.
+    pub fn hash_token(token: &str) -> String {
+        Sha256::digest(token.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    pub fn is_valid_at(&self, now: TimestampMs) -> bool {
+        self.revoked_at.is_none() && self.expires_at.map(|t| now < t).unwrap_or(true)
+    }
+
+    pub fn has_scope(&self, scope: ApiTokenScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_scopes() {
+        use strum::IntoEnumIterator;
+        for scope in ApiTokenScope::iter() {
+            assert_eq!(scope, scope.as_str().parse().unwrap());
+        }
+        assert!("events".parse::<ApiTokenScope>().is_err());
+    }
+
+    #[test]
+    fn hash_token() {
+        assert_eq!(
+            "2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae",
+            ApiToken::hash_token("foo")
+        );
+    }
+
+    #[test]
+    fn validity() {
+        let now = TimestampMs::now();
+        let mut token = ApiToken {
+            id: Id::new(),
+            org_id: Id::new(),
+            name: "foo".into(),
+            token_hash: ApiToken::hash_token("foo"),
+            scopes: vec![ApiTokenScope::EventsWrite],
+            created_at: now,
+            expires_at: None,
+            last_used_at: None,
+            revoked_at: None,
+        };
+        assert!(token.is_valid_at(now));
+        assert!(token.has_scope(ApiTokenScope::EventsWrite));
+        assert!(!token.has_scope(ApiTokenScope::PlacesClearance));
+        token.expires_at = Some(now);
+        assert!(!token.is_valid_at(now));
+        token.expires_at = None;
+        token.revoked_at = Some(now);
+        assert!(!token.is_valid_at(now));
+    }
+}
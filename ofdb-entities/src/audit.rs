@@ -0,0 +1,113 @@
+use crate::time::TimestampMs;
+use std::{fmt, str::FromStr};
+use strum::EnumIter;
+use thiserror::Error;
+
+/// Privileged actions that are recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum AuditAction {
+    ChangeUserRole,
+    ReviewPlaces,
+    ArchiveEvents,
+    ArchiveRatings,
+    ArchiveComments,
+    ClearPlace,
+    ClearEvent,
+    DeleteUser,
+    EraseUserData,
+}
+
+impl AuditAction {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::ChangeUserRole => "change_user_role",
+            Self::ReviewPlaces => "review_places",
+            Self::ArchiveEvents => "archive_events",
+            Self::ArchiveRatings => "archive_ratings",
+            Self::ArchiveComments => "archive_comments",
+            Self::ClearPlace => "clear_place",
+            Self::ClearEvent => "clear_event",
+            Self::DeleteUser => "delete_user",
+            Self::EraseUserData => "erase_user_data",
+        }
+    }
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid audit action: {0}")]
+pub struct AuditActionParseError(String);
+
+impl FromStr for AuditAction {
+    type Err = AuditActionParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use strum::IntoEnumIterator;
+        Self::iter()
+            .find(|a| a.as_str() == s)
+            .ok_or_else(|| AuditActionParseError(s.to_string()))
+    }
+}
+
+/// An entry of the append-only audit log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditLogEntry {
+    pub at: TimestampMs,
+    /// The e-mail address of a user or the id of an organization,
+    /// none if invoked from the command line
+    pub actor: Option<String>,
+    pub action: AuditAction,
+    /// The ids of the affected places, events, ratings, comments,
+    /// or the e-mail addresses of the affected users
+    pub target_ids: Vec<String>,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl AuditLogEntry {
+    pub fn now(actor: Option<&str>, action: AuditAction, target_ids: Vec<String>) -> Self {
+        Self {
+            at: TimestampMs::now(),
+            actor: actor.filter(|a| !a.is_empty()).map(ToString::to_string),
+            action,
+            target_ids,
+            before: None,
+            after: None,
+        }
+    }
+}
+
+/// Filters for the audit log that are all optional.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditLogQuery {
+    pub actor: Option<String>,
+    pub action: Option<AuditAction>,
+    /// Inclusive lower bound
+    pub since: Option<TimestampMs>,
+    /// Exclusive upper bound
+    pub until: Option<TimestampMs>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_audit_actions() {
+        use strum::IntoEnumIterator;
+        for a in AuditAction::iter() {
+            assert_eq!(a, a.as_str().parse().unwrap());
+        }
+        assert!("drop_tables".parse::<AuditAction>().is_err());
+    }
+
+    #[test]
+    fn empty_actor_is_none() {
+        let entry = AuditLogEntry::now(Some(""), AuditAction::ArchiveEvents, vec![]);
+        assert!(entry.actor.is_none());
+    }
+}
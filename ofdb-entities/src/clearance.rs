@@ -12,3 +12,16 @@ pub struct ClearanceForPlace {
     pub place_id: Id,
     pub cleared_revision: Option<Revision>,
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingClearanceForEvent {
+    pub event_id: Id,
+    pub created_at: TimestampMs,
+    pub last_cleared_revision: Option<Revision>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClearanceForEvent {
+    pub event_id: Id,
+    pub cleared_revision: Option<Revision>,
+}
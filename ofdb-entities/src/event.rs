@@ -1,6 +1,7 @@
 use crate::{contact::*, id::*, location::*, time::*, url::*};
 use chrono::prelude::*;
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
+use thiserror::Error;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum RegistrationType {
@@ -24,6 +25,284 @@ impl FromStr for RegistrationType {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Daily => "DAILY",
+            Self::Weekly => "WEEKLY",
+            Self::Monthly => "MONTHLY",
+            Self::Yearly => "YEARLY",
+        }
+    }
+}
+
+/// A weekday with an optional ordinal within the month,
+/// e.g. `1SA` for the first Saturday or `-1FR` for the
+/// last Friday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeekdayNum {
+    pub ordinal: Option<i8>,
+    pub weekday: Weekday,
+}
+
+/// A subset of the recurrence rules (RRULE) of RFC 5545
+///
+/// Supported are the frequencies `DAILY`, `WEEKLY`, `MONTHLY`
+/// and `YEARLY` with `INTERVAL`, `COUNT` or `UNTIL`, `BYDAY`
+/// (weekly and monthly), and `BYMONTHDAY` (monthly).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    // Inclusive, stored with second precision like the start of events
+    pub until: Option<NaiveDateTime>,
+    pub by_day: Vec<WeekdayNum>,
+    pub by_month_day: Vec<i8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid recurrence rule: {0}")]
+pub struct RecurrenceRuleParseError(String);
+
+impl RecurrenceRuleParseError {
+    fn new(msg: impl Into<String>) -> Self {
+        Self(msg.into())
+    }
+}
+
+const RRULE_DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+fn weekday_as_str(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn parse_weekday(s: &str) -> Result<Weekday, RecurrenceRuleParseError> {
+    let weekday = match s {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => {
+            return Err(RecurrenceRuleParseError::new(format!(
+                "invalid weekday '{}'",
+                s
+            )))
+        }
+    };
+    Ok(weekday)
+}
+
+impl fmt::Display for WeekdayNum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ordinal) = self.ordinal {
+            write!(f, "{}", ordinal)?;
+        }
+        f.write_str(weekday_as_str(self.weekday))
+    }
+}
+
+impl FromStr for WeekdayNum {
+    type Err = RecurrenceRuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.is_ascii() {
+            return Err(RecurrenceRuleParseError::new(format!(
+                "invalid weekday '{}'",
+                s
+            )));
+        }
+        let split = s.len().saturating_sub(2);
+        let (ordinal, weekday) = (&s[..split], &s[split..]);
+        let weekday = parse_weekday(weekday)?;
+        let ordinal = if ordinal.is_empty() {
+            None
+        } else {
+            let ordinal = ordinal
+                .trim_start_matches('+')
+                .parse::<i8>()
+                .ok()
+                .filter(|n| *n != 0 && n.abs() <= 5)
+                .ok_or_else(|| RecurrenceRuleParseError::new(format!("invalid weekday '{}'", s)))?;
+            Some(ordinal)
+        };
+        Ok(Self { ordinal, weekday })
+    }
+}
+
+fn parse_until(s: &str) -> Result<NaiveDateTime, RecurrenceRuleParseError> {
+    // Local times and dates without a time are interpreted as UTC
+    let s = s.trim_end_matches('Z');
+    NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M%S")
+        .or_else(|_| NaiveDate::parse_from_str(s, "%Y%m%d").map(|d| d.and_hms(23, 59, 59)))
+        .map_err(|_| RecurrenceRuleParseError::new(format!("invalid UNTIL '{}'", s)))
+}
+
+fn parse_positive(key: &str, value: &str) -> Result<u32, RecurrenceRuleParseError> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| RecurrenceRuleParseError::new(format!("invalid {} '{}'", key, value)))
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = RecurrenceRuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_prefix("RRULE:").unwrap_or(s);
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut count = None;
+        let mut until = None;
+        let mut by_day: Vec<WeekdayNum> = vec![];
+        let mut by_month_day = vec![];
+        for part in s.split(';').filter(|p| !p.is_empty()) {
+            let mut key_value = part.splitn(2, '=');
+            let key = key_value.next().unwrap_or_default().to_uppercase();
+            let value = key_value.next().ok_or_else(|| {
+                RecurrenceRuleParseError::new(format!("missing value of '{}'", key))
+            })?;
+            match key.as_str() {
+                "FREQ" => {
+                    frequency = Some(match value {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => {
+                            return Err(RecurrenceRuleParseError::new(format!(
+                                "unsupported FREQ '{}'",
+                                value
+                            )))
+                        }
+                    });
+                }
+                "INTERVAL" => interval = parse_positive(&key, value)?,
+                "COUNT" => count = Some(parse_positive(&key, value)?),
+                "UNTIL" => until = Some(parse_until(value)?),
+                "BYDAY" => {
+                    by_day = value.split(',').map(str::parse).collect::<Result<_, _>>()?;
+                }
+                "BYMONTHDAY" => {
+                    by_month_day = value
+                        .split(',')
+                        .map(|d| {
+                            d.trim_start_matches('+')
+                                .parse::<i8>()
+                                .ok()
+                                .filter(|d| *d != 0 && d.abs() <= 31)
+                                .ok_or_else(|| {
+                                    RecurrenceRuleParseError::new(format!(
+                                        "invalid BYMONTHDAY '{}'",
+                                        d
+                                    ))
+                                })
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                // Weeks always start on Monday
+                "WKST" if value == "MO" => {}
+                _ => {
+                    return Err(RecurrenceRuleParseError::new(format!(
+                        "unsupported '{}'",
+                        part
+                    )))
+                }
+            }
+        }
+        let frequency = frequency.ok_or_else(|| RecurrenceRuleParseError::new("missing FREQ"))?;
+        if count.is_some() && until.is_some() {
+            return Err(RecurrenceRuleParseError::new(
+                "COUNT and UNTIL are mutually exclusive",
+            ));
+        }
+        match frequency {
+            Frequency::Weekly => {
+                if by_day.iter().any(|d| d.ordinal.is_some()) {
+                    return Err(RecurrenceRuleParseError::new(
+                        "BYDAY with ordinals requires FREQ=MONTHLY",
+                    ));
+                }
+                if !by_month_day.is_empty() {
+                    return Err(RecurrenceRuleParseError::new(
+                        "BYMONTHDAY requires FREQ=MONTHLY",
+                    ));
+                }
+            }
+            Frequency::Monthly => {}
+            Frequency::Daily | Frequency::Yearly => {
+                if !by_day.is_empty() || !by_month_day.is_empty() {
+                    return Err(RecurrenceRuleParseError::new(format!(
+                        "BYDAY and BYMONTHDAY are not supported for FREQ={}",
+                        frequency.as_str()
+                    )));
+                }
+            }
+        }
+        Ok(Self {
+            frequency,
+            interval,
+            count,
+            until,
+            by_day,
+            by_month_day,
+        })
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FREQ={}", self.frequency.as_str())?;
+        if self.interval > 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format(RRULE_DATE_TIME_FORMAT))?;
+        }
+        if !self.by_day.is_empty() {
+            let by_day: Vec<_> = self.by_day.iter().map(ToString::to_string).collect();
+            write!(f, ";BYDAY={}", by_day.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            let by_month_day: Vec<_> = self.by_month_day.iter().map(ToString::to_string).collect();
+            write!(f, ";BYMONTHDAY={}", by_month_day.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// The recurrence of an event series
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    pub rule: RecurrenceRule,
+    /// The start times of excluded occurrences (EXDATE)
+    pub exdates: Vec<NaiveDateTime>,
+}
+
 #[rustfmt::skip]
 #[derive(Debug, Clone, PartialEq)]
 pub struct Event {
@@ -43,6 +322,12 @@ pub struct Event {
     pub archived     : Option<Timestamp>,
     pub image_url     : Option<Url>,
     pub image_link_url: Option<Url>,
+    // An uploaded image
+    pub image_id     : Option<Id>,
+    // Only for the series of recurring events
+    pub recurrence   : Option<Recurrence>,
+    // The series of an occurrence
+    pub series_id    : Option<Id>,
 }
 
 impl Event {
@@ -107,4 +392,67 @@ mod tests {
         assert!(RegistrationType::from_str("foo").is_err());
         assert!(RegistrationType::from_str("").is_err());
     }
+
+    #[test]
+    fn parse_and_format_recurrence_rules() {
+        let rule: RecurrenceRule = "RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=TU,TH;WKST=MO"
+            .parse()
+            .unwrap();
+        assert_eq!(Frequency::Weekly, rule.frequency);
+        assert_eq!(2, rule.interval);
+        assert_eq!(
+            vec![Weekday::Tue, Weekday::Thu],
+            rule.by_day.iter().map(|d| d.weekday).collect::<Vec<_>>()
+        );
+        assert_eq!("FREQ=WEEKLY;INTERVAL=2;BYDAY=TU,TH", rule.to_string());
+
+        let rule: RecurrenceRule = "FREQ=MONTHLY;BYDAY=1SA,-1FR;UNTIL=20211231"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            vec![
+                WeekdayNum {
+                    ordinal: Some(1),
+                    weekday: Weekday::Sat
+                },
+                WeekdayNum {
+                    ordinal: Some(-1),
+                    weekday: Weekday::Fri
+                }
+            ],
+            rule.by_day
+        );
+        assert_eq!(
+            "FREQ=MONTHLY;UNTIL=20211231T235959Z;BYDAY=1SA,-1FR",
+            rule.to_string()
+        );
+        assert_eq!(rule, rule.to_string().parse().unwrap());
+
+        let rule: RecurrenceRule = "FREQ=MONTHLY;COUNT=3;BYMONTHDAY=-1".parse().unwrap();
+        assert_eq!(vec![-1], rule.by_month_day);
+        assert_eq!(Some(3), rule.count);
+    }
+
+    #[test]
+    fn reject_invalid_or_unsupported_recurrence_rules() {
+        for invalid in &[
+            "",
+            "INTERVAL=2",
+            "FREQ=HOURLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;COUNT=2;UNTIL=20211231",
+            "FREQ=DAILY;BYDAY=MO",
+            "FREQ=WEEKLY;BYDAY=1MO",
+            "FREQ=WEEKLY;BYDAY=XX",
+            "FREQ=MONTHLY;BYMONTHDAY=32",
+            "FREQ=YEARLY;BYMONTH=3",
+            "FREQ=DAILY;UNTIL=tomorrow",
+        ] {
+            assert!(
+                invalid.parse::<RecurrenceRule>().is_err(),
+                "{} should be rejected",
+                invalid
+            );
+        }
+    }
 }
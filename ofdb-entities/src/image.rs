@@ -0,0 +1,150 @@
+use crate::{email::Email, id::Id, time::TimestampMs};
+use std::{fmt, str::FromStr};
+use strum::EnumIter;
+use thiserror::Error;
+
+/// The format of stored images.
+///
+/// Uploaded images are always re-encoded into one of these formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+}
+
+impl ImageFormat {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpeg",
+            Self::Png => "png",
+        }
+    }
+
+    pub const fn content_type(self) -> &'static str {
+        match self {
+            Self::Jpeg => "image/jpeg",
+            Self::Png => "image/png",
+        }
+    }
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid image format: {0}")]
+pub struct ImageFormatParseError(String);
+
+impl FromStr for ImageFormat {
+    type Err = ImageFormatParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "jpeg" => Ok(Self::Jpeg),
+            "png" => Ok(Self::Png),
+            _ => Err(ImageFormatParseError(s.to_string())),
+        }
+    }
+}
+
+/// The predefined sizes of thumbnails that are generated
+/// for every uploaded image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum ThumbnailSize {
+    Small,
+    Medium,
+    Large,
+}
+
+impl ThumbnailSize {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Small => "small",
+            Self::Medium => "medium",
+            Self::Large => "large",
+        }
+    }
+
+    /// The maximum width and height in pixels
+    pub const fn max_dimension(self) -> u32 {
+        match self {
+            Self::Small => 160,
+            Self::Medium => 480,
+            Self::Large => 1024,
+        }
+    }
+}
+
+impl fmt::Display for ThumbnailSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid thumbnail size: {0}")]
+pub struct ThumbnailSizeParseError(String);
+
+impl FromStr for ThumbnailSize {
+    type Err = ThumbnailSizeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use strum::IntoEnumIterator;
+        Self::iter()
+            .find(|t| t.as_str() == s)
+            .ok_or_else(|| ThumbnailSizeParseError(s.to_string()))
+    }
+}
+
+/// The metadata of an uploaded image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub id: Id,
+    pub format: ImageFormat,
+    pub width: u32,
+    pub height: u32,
+    pub created_at: TimestampMs,
+    pub created_by: Option<Email>,
+}
+
+impl Image {
+    /// The key of the stored original or thumbnail image.
+    pub fn storage_key(&self, size: Option<ThumbnailSize>) -> String {
+        match size {
+            Some(size) => format!("{}-{}.{}", self.id, size, self.format),
+            None => format!("{}.{}", self.id, self.format),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn parse_thumbnail_sizes() {
+        for size in ThumbnailSize::iter() {
+            assert_eq!(size, size.as_str().parse().unwrap());
+        }
+        assert!("tiny".parse::<ThumbnailSize>().is_err());
+    }
+
+    #[test]
+    fn storage_keys() {
+        let image = Image {
+            id: "a1b2".into(),
+            format: ImageFormat::Png,
+            width: 1,
+            height: 1,
+            created_at: TimestampMs::now(),
+            created_by: None,
+        };
+        assert_eq!("a1b2.png", image.storage_key(None));
+        assert_eq!(
+            "a1b2-small.png",
+            image.storage_key(Some(ThumbnailSize::Small))
+        );
+    }
+}
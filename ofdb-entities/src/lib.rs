@@ -11,6 +11,8 @@
 
 pub mod activity;
 pub mod address;
+pub mod api_token;
+pub mod audit;
 pub mod category;
 pub mod clearance;
 pub mod comment;
@@ -19,7 +21,9 @@ pub mod email;
 pub mod event;
 pub mod geo;
 pub mod id;
+pub mod image;
 pub mod links;
+pub mod locale;
 pub mod location;
 pub mod nonce;
 pub mod organization;
@@ -32,6 +36,7 @@ pub mod subscription;
 pub mod tag;
 pub mod time;
 pub mod user;
+pub mod webhook;
 #[cfg(feature = "rusturl")]
 pub mod url {
     pub use url::{ParseError, Url};
@@ -1,10 +1,12 @@
-use crate::url::Url;
+use crate::{id::Id, url::Url};
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Links {
     pub homepage: Option<Url>,
     pub image: Option<Url>,
     pub image_href: Option<Url>,
+    /// An uploaded image
+    pub image_id: Option<Id>,
     pub custom: Vec<CustomLink>,
 }
 
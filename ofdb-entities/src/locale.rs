@@ -0,0 +1,81 @@
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+/// The preferred language of a user as a simplified BCP 47
+/// language tag, e.g. `de` or `en-GB`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Locale(String);
+
+impl Locale {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The primary language without any region, e.g. `en` for `en-GB`.
+    pub fn language(&self) -> &str {
+        self.0.split('-').next().unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid locale: {0}")]
+pub struct LocaleParseError(String);
+
+impl FromStr for Locale {
+    type Err = LocaleParseError;
+
+    // Accepts both `-` and `_` as separators and normalizes the
+    // case of the language (lower) and region (upper).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || LocaleParseError(s.to_string());
+        let mut subtags = s.trim().split(|c| c == '-' || c == '_');
+        let language = subtags.next().ok_or_else(err)?;
+        if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic())
+        {
+            return Err(err());
+        }
+        let mut locale = language.to_ascii_lowercase();
+        for subtag in subtags {
+            if !(1..=8).contains(&subtag.len())
+                || !subtag.chars().all(|c| c.is_ascii_alphanumeric())
+            {
+                return Err(err());
+            }
+            locale.push('-');
+            if subtag.len() == 2 {
+                locale.push_str(&subtag.to_ascii_uppercase());
+            } else {
+                locale.push_str(&subtag.to_ascii_lowercase());
+            }
+        }
+        Ok(Self(locale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_normalize_locales() {
+        assert_eq!("de", "de".parse::<Locale>().unwrap().as_str());
+        assert_eq!("en-GB", "en_gb".parse::<Locale>().unwrap().as_str());
+        assert_eq!("en", "EN-gb".parse::<Locale>().unwrap().language());
+        assert_eq!("pt-BR", " pt-BR ".parse::<Locale>().unwrap().as_str());
+        assert_eq!(
+            "zh-hant-TW",
+            "zh-Hant-TW".parse::<Locale>().unwrap().as_str()
+        );
+        assert!("".parse::<Locale>().is_err());
+        assert!("d".parse::<Locale>().is_err());
+        assert!("de-".parse::<Locale>().is_err());
+        assert!("de/en".parse::<Locale>().is_err());
+        assert!("../de".parse::<Locale>().is_err());
+    }
+}
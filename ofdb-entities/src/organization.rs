@@ -25,6 +25,5 @@ impl From<&str> for ModeratedTag {
 pub struct Organization {
     pub id: Id,
     pub name: String,
-    pub api_token: String,
     pub moderated_tags: Vec<ModeratedTag>,
 }
@@ -20,6 +20,10 @@ impl OpeningHours {
     pub const fn min_len() -> usize {
         4
     }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
 }
 
 impl FromStr for OpeningHours {
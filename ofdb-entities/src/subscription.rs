@@ -1,8 +1,249 @@
-use crate::{geo::*, id::*};
+use crate::{category::*, geo::*, id::*, locale::*, time::TimestampMs};
+use std::{fmt, str::FromStr};
+use strum::EnumIter;
+use thiserror::Error;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// How notifications about changes are delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum SubscriptionDelivery {
+    /// One e-mail per change
+    Immediate,
+    /// All changes of a day in a single e-mail
+    Daily,
+    /// All changes of a week in a single e-mail
+    Weekly,
+}
+
+impl SubscriptionDelivery {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Immediate => "immediate",
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+
+    /// The minimum period between two digests in milliseconds.
+    pub const fn digest_period_ms(self) -> Option<i64> {
+        match self {
+            Self::Immediate => None,
+            Self::Daily => Some(DAY_MS),
+            Self::Weekly => Some(7 * DAY_MS),
+        }
+    }
+}
+
+impl Default for SubscriptionDelivery {
+    fn default() -> Self {
+        Self::Immediate
+    }
+}
+
+impl fmt::Display for SubscriptionDelivery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid subscription delivery: {0}")]
+pub struct SubscriptionDeliveryParseError(String);
+
+impl FromStr for SubscriptionDelivery {
+    type Err = SubscriptionDeliveryParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use strum::IntoEnumIterator;
+        Self::iter()
+            .find(|d| d.as_str() == s)
+            .ok_or_else(|| SubscriptionDeliveryParseError(s.to_string()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum SubscriptionChangeKind {
+    PlaceAdded,
+    PlaceUpdated,
+    EventCreated,
+    EventUpdated,
+}
+
+impl SubscriptionChangeKind {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::PlaceAdded => "place_added",
+            Self::PlaceUpdated => "place_updated",
+            Self::EventCreated => "event_created",
+            Self::EventUpdated => "event_updated",
+        }
+    }
+
+    pub const fn is_event(self) -> bool {
+        matches!(self, Self::EventCreated | Self::EventUpdated)
+    }
+}
+
+impl fmt::Display for SubscriptionChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid subscription change: {0}")]
+pub struct SubscriptionChangeKindParseError(String);
+
+impl FromStr for SubscriptionChangeKind {
+    type Err = SubscriptionChangeKindParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use strum::IntoEnumIterator;
+        Self::iter()
+            .find(|k| k.as_str() == s)
+            .ok_or_else(|| SubscriptionChangeKindParseError(s.to_string()))
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct BboxSubscription {
     pub id: Id,
     pub user_email: String,
     pub bbox: MapBbox,
+    /// The language of notifications if different from the
+    /// preferred language of the user
+    pub locale: Option<Locale>,
+    /// Only changes of items with at least one of these tags (optional)
+    pub tags: Vec<String>,
+    /// Only changes of items in one of these categories (optional)
+    pub categories: Vec<Id>,
+    /// Notifications about events are opt-in
+    pub events: bool,
+    pub delivery: SubscriptionDelivery,
+    /// When the last digest has been sent
+    pub last_digest_at: Option<TimestampMs>,
+}
+
+impl BboxSubscription {
+    /// Checks if a change of an item at the given position
+    /// and with the given tags should be notified.
+    pub fn matches(&self, kind: SubscriptionChangeKind, pos: MapPoint, tags: &[String]) -> bool {
+        if kind.is_event() && !self.events {
+            return false;
+        }
+        if !self.bbox.contains_point(pos) {
+            return false;
+        }
+        if !(self.tags.is_empty() || self.tags.iter().any(|t| tags.contains(t))) {
+            return false;
+        }
+        self.categories.is_empty()
+            || (kind.is_event()
+                && self
+                    .categories
+                    .iter()
+                    .any(|c| c.as_str() == Category::ID_EVENT))
+            || Category::merge_ids_into_tags(&self.categories, vec![])
+                .iter()
+                .any(|t| tags.contains(t))
+    }
+
+    /// The earliest time when a digest that contains a change from
+    /// the given time should be sent. Digests are sent at most once
+    /// per period.
+    pub fn digest_due_at(&self, oldest_change_at: TimestampMs) -> Option<TimestampMs> {
+        self.delivery.digest_period_ms().map(|period| {
+            let since = self.last_digest_at.unwrap_or(oldest_change_at);
+            TimestampMs::from_inner(since.into_inner() + period)
+        })
+    }
+}
+
+/// A change that will be delivered with the next digest
+/// of a subscription.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingSubscriptionChange {
+    pub subscription_id: Id,
+    pub kind: SubscriptionChangeKind,
+    /// The id of the place or event
+    pub item_id: Id,
+    pub created_at: TimestampMs,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subscription() -> BboxSubscription {
+        BboxSubscription {
+            id: Id::new(),
+            user_email: "user@example.com".into(),
+            bbox: MapBbox::new(
+                MapPoint::from_lat_lng_deg(0.0, 0.0),
+                MapPoint::from_lat_lng_deg(2.0, 2.0),
+            ),
+            locale: None,
+            tags: vec![],
+            categories: vec![],
+            events: false,
+            delivery: SubscriptionDelivery::Immediate,
+            last_digest_at: None,
+        }
+    }
+
+    #[test]
+    fn parse_delivery_and_change_kinds() {
+        use strum::IntoEnumIterator;
+        for d in SubscriptionDelivery::iter() {
+            assert_eq!(d, d.as_str().parse().unwrap());
+        }
+        for k in SubscriptionChangeKind::iter() {
+            assert_eq!(k, k.as_str().parse().unwrap());
+        }
+        assert!("hourly".parse::<SubscriptionDelivery>().is_err());
+    }
+
+    #[test]
+    fn match_events_tags_and_categories() {
+        let pos = MapPoint::from_lat_lng_deg(1.0, 1.0);
+        let mut s = subscription();
+        assert!(s.matches(SubscriptionChangeKind::PlaceAdded, pos, &[]));
+        assert!(!s.matches(
+            SubscriptionChangeKind::PlaceAdded,
+            MapPoint::from_lat_lng_deg(3.0, 1.0),
+            &[]
+        ));
+        assert!(!s.matches(SubscriptionChangeKind::EventCreated, pos, &[]));
+        s.events = true;
+        assert!(s.matches(SubscriptionChangeKind::EventCreated, pos, &[]));
+
+        s.tags = vec!["foo".into()];
+        assert!(!s.matches(SubscriptionChangeKind::PlaceUpdated, pos, &[]));
+        let tags = vec!["foo".to_string(), Category::TAG_COMMERCIAL.to_string()];
+        assert!(s.matches(SubscriptionChangeKind::PlaceUpdated, pos, &tags));
+
+        s.categories = vec![Category::ID_NON_PROFIT.into()];
+        assert!(!s.matches(SubscriptionChangeKind::PlaceUpdated, pos, &tags));
+        s.categories.push(Category::ID_COMMERCIAL.into());
+        assert!(s.matches(SubscriptionChangeKind::PlaceUpdated, pos, &tags));
+        assert!(!s.matches(SubscriptionChangeKind::EventUpdated, pos, &["foo".into()]));
+        s.categories.push(Category::ID_EVENT.into());
+        assert!(s.matches(SubscriptionChangeKind::EventUpdated, pos, &["foo".into()]));
+    }
+
+    #[test]
+    fn digests_are_sent_at_most_once_per_period() {
+        let mut s = subscription();
+        let t = TimestampMs::from_inner(1_000);
+        assert!(s.digest_due_at(t).is_none());
+        s.delivery = SubscriptionDelivery::Daily;
+        assert_eq!(
+            Some(TimestampMs::from_inner(1_000 + DAY_MS)),
+            s.digest_due_at(t)
+        );
+        s.last_digest_at = Some(TimestampMs::from_inner(500));
+        assert_eq!(
+            Some(TimestampMs::from_inner(500 + DAY_MS)),
+            s.digest_due_at(t)
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use crate::password::Password;
+use crate::{locale::Locale, password::Password};
 use num_derive::{FromPrimitive, ToPrimitive};
 
 #[rustfmt::skip]
@@ -8,6 +8,7 @@ pub struct User {
     pub email_confirmed : bool,
     pub password        : Password,
     pub role            : Role,
+    pub locale          : Option<Locale>,
 }
 
 #[rustfmt::skip]
@@ -0,0 +1,164 @@
+use crate::{geo::*, id::*, time::TimestampMs, url::Url};
+use std::{fmt, str::FromStr};
+use strum::EnumIter;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum WebhookEventType {
+    PlaceAdded,
+    PlaceUpdated,
+    EventCreated,
+    EventUpdated,
+    ClearancePending,
+}
+
+impl WebhookEventType {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::PlaceAdded => "place_added",
+            Self::PlaceUpdated => "place_updated",
+            Self::EventCreated => "event_created",
+            Self::EventUpdated => "event_updated",
+            Self::ClearancePending => "clearance_pending",
+        }
+    }
+}
+
+impl fmt::Display for WebhookEventType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid webhook event type: {0}")]
+pub struct WebhookEventTypeParseError(String);
+
+impl FromStr for WebhookEventType {
+    type Err = WebhookEventTypeParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use strum::IntoEnumIterator;
+        Self::iter()
+            .find(|t| t.as_str() == s)
+            .ok_or_else(|| WebhookEventTypeParseError(s.to_string()))
+    }
+}
+
+/// A target URL of an organization that receives signed
+/// notifications about changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Webhook {
+    pub id: Id,
+    pub org_id: Id,
+    pub url: Url,
+    /// Shared secret for signing the payload
+    pub secret: String,
+    pub event_types: Vec<WebhookEventType>,
+    /// Only changes within this area (optional)
+    pub bbox: Option<MapBbox>,
+    /// Only changes of items with at least one of these tags (optional)
+    pub tags: Vec<String>,
+    pub created_at: TimestampMs,
+}
+
+impl Webhook {
+    /// Checks if a change of an item with an optional position
+    /// and the given tags should be delivered.
+    pub fn matches(
+        &self,
+        event_type: WebhookEventType,
+        pos: Option<MapPoint>,
+        tags: &[String],
+    ) -> bool {
+        if !self.event_types.contains(&event_type) {
+            return false;
+        }
+        if let Some(bbox) = &self.bbox {
+            match pos {
+                Some(pos) if bbox.contains_point(pos) => (),
+                _ => return false,
+            }
+        }
+        self.tags.is_empty() || self.tags.iter().any(|t| tags.contains(t))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    /// The last attempt failed, but the delivery might still be retried
+    Failed,
+}
+
+/// An entry of the delivery log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookDelivery {
+    pub id: Id,
+    pub webhook_id: Id,
+    pub event_type: WebhookEventType,
+    /// JSON
+    pub payload: String,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: u32,
+    /// The HTTP status code of the last response
+    pub response_status: Option<u16>,
+    pub last_error: Option<String>,
+    pub created_at: TimestampMs,
+    pub updated_at: TimestampMs,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn webhook() -> Webhook {
+        Webhook {
+            id: Id::new(),
+            org_id: Id::new(),
+            url: "https://example.com/hook".parse().unwrap(),
+            secret: "secret".into(),
+            event_types: vec![WebhookEventType::PlaceAdded],
+            bbox: None,
+            tags: vec![],
+            created_at: TimestampMs::now(),
+        }
+    }
+
+    #[test]
+    fn parse_event_types() {
+        use strum::IntoEnumIterator;
+        for t in WebhookEventType::iter() {
+            assert_eq!(t, t.as_str().parse().unwrap());
+        }
+        assert!("place-added".parse::<WebhookEventType>().is_err());
+    }
+
+    #[test]
+    fn match_event_type_bbox_and_tags() {
+        let pos = MapPoint::from_lat_lng_deg(1.0, 1.0);
+        let mut hook = webhook();
+        assert!(hook.matches(WebhookEventType::PlaceAdded, None, &[]));
+        assert!(!hook.matches(WebhookEventType::PlaceUpdated, None, &[]));
+
+        hook.bbox = Some(MapBbox::new(
+            MapPoint::from_lat_lng_deg(0.0, 0.0),
+            MapPoint::from_lat_lng_deg(2.0, 2.0),
+        ));
+        assert!(hook.matches(WebhookEventType::PlaceAdded, Some(pos), &[]));
+        assert!(!hook.matches(
+            WebhookEventType::PlaceAdded,
+            Some(MapPoint::from_lat_lng_deg(3.0, 1.0)),
+            &[]
+        ));
+        assert!(!hook.matches(WebhookEventType::PlaceAdded, None, &[]));
+
+        hook.tags = vec!["foo".into()];
+        assert!(!hook.matches(WebhookEventType::PlaceAdded, Some(pos), &[]));
+        assert!(hook.matches(
+            WebhookEventType::PlaceAdded,
+            Some(pos),
+            &["bar".into(), "foo".into()]
+        ));
+    }
+}
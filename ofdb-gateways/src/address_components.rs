@@ -0,0 +1,46 @@
+use ofdb_entities::address::Address;
+use serde::Deserialize;
+
+/// The address details of OpenStreetMap based geocoding services
+/// like Nominatim or OpenCage.
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct AddressComponents {
+    road: Option<String>,
+    pedestrian: Option<String>,
+    house_number: Option<String>,
+    postcode: Option<String>,
+    city: Option<String>,
+    town: Option<String>,
+    village: Option<String>,
+    municipality: Option<String>,
+    country: Option<String>,
+    state: Option<String>,
+}
+
+impl From<AddressComponents> for Address {
+    fn from(from: AddressComponents) -> Self {
+        let AddressComponents {
+            road,
+            pedestrian,
+            house_number,
+            postcode,
+            city,
+            town,
+            village,
+            municipality,
+            country,
+            state,
+        } = from;
+        let street = road.or(pedestrian).map(|road| match house_number {
+            Some(number) => format!("{} {}", road, number),
+            None => road,
+        });
+        Self {
+            street,
+            zip: postcode,
+            city: city.or(town).or(village).or(municipality),
+            country,
+            state,
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use ofdb_core::gateways::image::{
+    is_valid_storage_key, ImageStorageError, ImageStorageGateway, ImageStorageResult,
+};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Stores images as files in a local directory.
+#[derive(Debug, Clone)]
+pub struct LocalImageStorage {
+    root_dir: PathBuf,
+}
+
+impl LocalImageStorage {
+    pub fn new<P: Into<PathBuf>>(root_dir: P) -> Self {
+        Self {
+            root_dir: root_dir.into(),
+        }
+    }
+
+    pub fn root_dir(&self) -> &Path {
+        &self.root_dir
+    }
+
+    fn file_path(&self, key: &str) -> ImageStorageResult<PathBuf> {
+        if !is_valid_storage_key(key) {
+            return Err(ImageStorageError::InvalidKey(key.to_string()));
+        }
+        Ok(self.root_dir.join(key))
+    }
+}
+
+impl ImageStorageGateway for LocalImageStorage {
+    fn store(&self, key: &str, data: &[u8]) -> ImageStorageResult<()> {
+        let path = self.file_path(key)?;
+        fs::create_dir_all(&self.root_dir)?;
+        // Never expose partially written files
+        let tmp_path = self.root_dir.join(format!(".{}.tmp", key));
+        fs::write(&tmp_path, data)?;
+        if let Err(err) = fs::rename(&tmp_path, &path) {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(err.into());
+        }
+        Ok(())
+    }
+
+    fn load(&self, key: &str) -> ImageStorageResult<Option<Vec<u8>>> {
+        let path = self.file_path(key)?;
+        match fs::read(path) {
+            Ok(data) => Ok(Some(data)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn delete(&self, key: &str) -> ImageStorageResult<()> {
+        let path = self.file_path(key)?;
+        match fs::remove_file(path) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+
+    #[test]
+    fn store_load_and_delete_files() {
+        let root_dir = env::temp_dir().join(format!("ofdb-images-{}", process::id()));
+        let storage = LocalImageStorage::new(&root_dir);
+        assert!(storage.load("foo.png").unwrap().is_none());
+        storage.store("foo.png", b"foo").unwrap();
+        storage.store("foo.png", b"bar").unwrap();
+        assert_eq!(b"bar".to_vec(), storage.load("foo.png").unwrap().unwrap());
+        storage.delete("foo.png").unwrap();
+        storage.delete("foo.png").unwrap();
+        assert!(storage.load("foo.png").unwrap().is_none());
+        assert!(storage.store("../foo.png", b"foo").is_err());
+        assert!(storage.load("").is_err());
+        fs::remove_dir_all(root_dir).unwrap();
+    }
+}
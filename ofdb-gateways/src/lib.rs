@@ -1,8 +1,12 @@
 #[macro_use]
 extern crate log;
 
+mod address_components;
+pub mod filesystem;
 pub mod mailgun;
+pub mod nominatim;
 pub mod notify;
 pub mod opencage;
 pub mod sendmail;
 pub mod user_communication;
+pub mod webhook;
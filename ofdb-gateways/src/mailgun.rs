@@ -52,6 +52,7 @@ fn send_raw(url: &str, api_key: &str, params: Vec<(&'static str, String)>) -> Re
 /// Don't actually send emails while running the tests or
 /// if the `email` feature is disabled.
 #[cfg(test)]
+#[allow(clippy::unnecessary_wraps)]
 fn send_raw(_: &str, _: &str, params: Vec<(&'static str, String)>) -> Result<()> {
     debug!("Would send e-mail: {:?}", params);
     Ok(())
@@ -79,4 +80,27 @@ impl EmailGateway for Mailgun {
         ];
         self.send(params);
     }
+
+    fn compose_and_send_unsubscribable(
+        &self,
+        recipient: &Email,
+        subject: &str,
+        body: &str,
+        unsubscribe_url: &str,
+    ) {
+        debug!("Sending e-mail to: {:?}", recipient);
+        // One-click unsubscription from the mail client (RFC 8058)
+        let params = vec![
+            ("from", (*self.from_email).clone()),
+            ("to", (**recipient).clone()),
+            ("subject", subject.to_owned()),
+            ("text", body.to_owned()),
+            ("h:List-Unsubscribe", format!("<{}>", unsubscribe_url)),
+            (
+                "h:List-Unsubscribe-Post",
+                "List-Unsubscribe=One-Click".to_owned(),
+            ),
+        ];
+        self.send(params);
+    }
 }
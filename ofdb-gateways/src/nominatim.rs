@@ -0,0 +1,234 @@
+use crate::address_components::AddressComponents;
+use ofdb_core::gateways::geocode::{GeoCodingError, GeoCodingGateway, GeoCodingResult};
+use ofdb_entities::{address::Address, geo::MapPoint};
+use serde::Deserialize;
+use std::time::Duration;
+
+pub const DEFAULT_API_URL: &str = "https://nominatim.openstreetmap.org";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Geocoding with the API of [Nominatim](https://nominatim.org)
+/// or any compatible service.
+///
+/// Please respect the usage policy of the public instance and
+/// consider running your own instance for heavy use.
+#[derive(Debug, Clone)]
+pub struct Nominatim {
+    api_url: String,
+    client: reqwest::blocking::Client,
+}
+
+impl Nominatim {
+    pub fn new(api_url: String) -> Self {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .user_agent(concat!("openfairdb/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .expect("HTTP client");
+        Self {
+            api_url: api_url.trim_end_matches('/').to_owned(),
+            client,
+        }
+    }
+
+    fn get<T>(&self, path: &str, params: &[(&str, &str)]) -> GeoCodingResult<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let res = self
+            .client
+            .get(&format!("{}/{}", self.api_url, path))
+            .query(&[("format", "jsonv2")])
+            .query(params)
+            .send()
+            .map_err(|err| GeoCodingError::Request(err.to_string()))?;
+        let status = res.status();
+        if !status.is_success() {
+            return Err(GeoCodingError::Status(status.as_u16()));
+        }
+        res.json()
+            .map_err(|err| GeoCodingError::Response(err.to_string()))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResult {
+    lat: String,
+    lon: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReverseResult {
+    address: Option<AddressComponents>,
+    // The position could not be resolved
+    error: Option<String>,
+}
+
+impl GeoCodingGateway for Nominatim {
+    fn resolve_address_lat_lng(&self, addr: &Address) -> GeoCodingResult<Option<(f64, f64)>> {
+        if addr.is_empty() {
+            return Ok(None);
+        }
+        let params: Vec<_> = [
+            ("street", &addr.street),
+            ("postalcode", &addr.zip),
+            ("city", &addr.city),
+            ("country", &addr.country),
+            ("state", &addr.state),
+        ]
+        .iter()
+        .filter_map(|(key, value)| value.as_deref().map(|value| (*key, value)))
+        .chain(std::iter::once(("limit", "1")))
+        .collect();
+        let results: Vec<SearchResult> = self.get("search", &params)?;
+        let result = match results.into_iter().next() {
+            Some(result) => result,
+            None => return Ok(None),
+        };
+        let parse = |coord: &str| {
+            coord
+                .parse::<f64>()
+                .map_err(|err| GeoCodingError::Response(err.to_string()))
+        };
+        Ok(Some((parse(&result.lat)?, parse(&result.lon)?)))
+    }
+
+    fn resolve_pos_address(&self, pos: MapPoint) -> GeoCodingResult<Option<Address>> {
+        let (lat, lng) = pos.to_lat_lng_deg();
+        let lat = format!("{:.7}", lat);
+        let lng = format!("{:.7}", lng);
+        let result: ReverseResult = self.get(
+            "reverse",
+            &[("lat", &lat), ("lon", &lng), ("addressdetails", "1")],
+        )?;
+        if let Some(err) = result.error {
+            debug!("Failed to resolve address of {}: {}", pos, err);
+        }
+        Ok(result
+            .address
+            .map(Into::into)
+            .filter(|a: &Address| !a.is_empty()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::mpsc,
+        thread,
+    };
+
+    // A local HTTP stand-in that responds to a single request with
+    // the given status and body and returns the request line.
+    fn serve_once(status: u16, body: &'static str) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim_end().is_empty() {
+                    break;
+                }
+            }
+            let mut stream = reader.into_inner();
+            write!(
+                stream,
+                "HTTP/1.1 {} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+            tx.send(request_line.trim_end().to_string()).unwrap();
+        });
+        (url, rx)
+    }
+
+    fn address() -> Address {
+        Address {
+            street: Some("Hauptstraße 1".into()),
+            city: Some("Berlin".into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn resolve_address() {
+        let (url, rx) = serve_once(200, r#"[{"lat":"52.5170365","lon":"13.3888599"}]"#);
+        let gw = Nominatim::new(url);
+        let pos = gw.resolve_address_lat_lng(&address()).unwrap();
+        assert_eq!(Some((52.5170365, 13.3888599)), pos);
+        let request_line = rx.recv().unwrap();
+        assert!(request_line.starts_with("GET /search?format=jsonv2&"));
+        assert!(request_line.contains("street=Hauptstra%C3%9Fe+1"));
+        assert!(request_line.contains("city=Berlin"));
+        assert!(!request_line.contains("postalcode"));
+    }
+
+    #[test]
+    fn unresolved_address() {
+        let (url, _) = serve_once(200, "[]");
+        let gw = Nominatim::new(url);
+        assert_eq!(None, gw.resolve_address_lat_lng(&address()).unwrap());
+        // Empty addresses are not sent
+        assert_eq!(
+            None,
+            gw.resolve_address_lat_lng(&Address::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn failed_request() {
+        let (url, _) = serve_once(503, "");
+        let gw = Nominatim::new(url);
+        match gw.resolve_address_lat_lng(&address()) {
+            Err(GeoCodingError::Status(503)) => {}
+            res => panic!("Unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn resolve_position() {
+        let (url, rx) = serve_once(
+            200,
+            r#"{"address":{"house_number":"1","road":"Unter den Linden","town":"Berlin","state":"Berlin","postcode":"10117","country":"Deutschland"}}"#,
+        );
+        let gw = Nominatim::new(url);
+        let addr = gw
+            .resolve_pos_address(MapPoint::from_lat_lng_deg(52.5, 13.4))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Address {
+                street: Some("Unter den Linden 1".into()),
+                zip: Some("10117".into()),
+                city: Some("Berlin".into()),
+                country: Some("Deutschland".into()),
+                state: Some("Berlin".into()),
+            },
+            addr
+        );
+        let request_line = rx.recv().unwrap();
+        assert!(
+            request_line.starts_with("GET /reverse?format=jsonv2&lat=52.5000000&lon=13.4000000&")
+        );
+
+        let (url, _) = serve_once(200, r#"{"error":"Unable to geocode"}"#);
+        let gw = Nominatim::new(url);
+        assert_eq!(
+            None,
+            gw.resolve_pos_address(MapPoint::from_lat_lng_deg(0.0, 0.0))
+                .unwrap()
+        );
+    }
+}
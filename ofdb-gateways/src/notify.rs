@@ -1,116 +1,149 @@
-use crate::user_communication;
-use ofdb_core::gateways::{email::EmailGateway, notify::NotificationGateway};
-use ofdb_entities::{category::*, email::*, event::*, nonce::*, place::*, user::*};
+use crate::user_communication::{self, EmailContent, EmailTemplates};
+use ofdb_core::gateways::{
+    email::EmailGateway,
+    notify::{DigestItem, NotificationGateway, Recipient},
+};
+use ofdb_entities::{
+    category::*, email::*, event::*, id::*, locale::*, nonce::*, place::*, user::*,
+};
+use std::sync::Arc;
 
 pub struct Notify {
     email_gw: Box<dyn EmailGateway + Send + Sync + 'static>,
+    templates: Arc<EmailTemplates>,
+    public_api_url: String,
 }
 
 impl Notify {
-    pub fn new<G>(gw: G) -> Self
+    /// The public URL of the API is needed for the unsubscribe
+    /// links in notifications about subscriptions.
+    pub fn new<G>(gw: G, templates: Arc<EmailTemplates>, public_api_url: impl Into<String>) -> Self
     where
         G: EmailGateway + Send + Sync + 'static,
     {
         Self {
             email_gw: Box::new(gw),
+            templates,
+            public_api_url: public_api_url.into(),
         }
     }
-}
 
-impl NotificationGateway for Notify {
-    fn place_added(
-        &self,
-        email_addresses: &[String],
-        place: &Place,
-        all_categories: Vec<Category>,
-    ) {
-        let mut place = place.clone();
-        let (tags, categories) = Category::split_from_tags(place.tags);
-        place.tags = tags;
-        let category_names: Vec<String> = all_categories
-            .into_iter()
-            .filter(|c1| categories.iter().any(|c2| c1.id == c2.id))
-            .map(|c| c.name())
-            .collect();
-        let content = user_communication::place_created_email(&place, &category_names);
+    fn unsubscribe_url(&self, subscription_id: &Id) -> String {
+        format!(
+            "{}/bbox-subscriptions/{}/unsubscribe",
+            self.public_api_url.trim_end_matches('/'),
+            subscription_id
+        )
+    }
 
-        {
-            info!(
-                "Sending e-mails to {} recipients after new place {} added",
-                email_addresses.len(),
-                place.id,
-            );
-            compose_and_send_emails(
-                &*self.email_gw,
-                email_addresses,
+    // Every recipient receives a separate e-mail with the
+    // unsubscribe link of the matching subscription.
+    fn send_subscription_emails<F>(&self, recipients: &[Recipient], render: F)
+    where
+        F: Fn(&EmailTemplates, Option<&Locale>, &str) -> EmailContent,
+    {
+        for r in recipients {
+            let unsubscribe_url = self.unsubscribe_url(&r.subscription_id);
+            let content = render(&self.templates, r.locale.as_ref(), &unsubscribe_url);
+            self.email_gw.compose_and_send_unsubscribable(
+                &Email::from(r.email.clone()),
                 &content.subject,
                 &content.body,
+                &unsubscribe_url,
             );
         }
     }
+}
+
+fn category_names(place: &mut Place, all_categories: Vec<Category>) -> Vec<String> {
+    let (tags, categories) = Category::split_from_tags(std::mem::take(&mut place.tags));
+    place.tags = tags;
+    all_categories
+        .into_iter()
+        .filter(|c1| categories.iter().any(|c2| c1.id == c2.id))
+        .map(|c| c.name())
+        .collect()
+}
+
+impl NotificationGateway for Notify {
+    fn place_added(&self, recipients: &[Recipient], place: &Place, all_categories: Vec<Category>) {
+        let mut place = place.clone();
+        let category_names = category_names(&mut place, all_categories);
+        info!(
+            "Sending e-mails to {} recipients after new place {} added",
+            recipients.len(),
+            place.id,
+        );
+        self.send_subscription_emails(recipients, |templates, locale, unsubscribe_url| {
+            user_communication::place_created_email(
+                templates,
+                locale,
+                &place,
+                &category_names,
+                unsubscribe_url,
+            )
+        });
+    }
     fn place_updated(
         &self,
-        email_addresses: &[String],
+        recipients: &[Recipient],
         place: &Place,
         all_categories: Vec<Category>,
     ) {
         let mut place = place.clone();
-        let (tags, categories) = Category::split_from_tags(place.tags);
-        place.tags = tags;
-        let category_names: Vec<String> = all_categories
-            .into_iter()
-            .filter(|c1| categories.iter().any(|c2| c1.id == c2.id))
-            .map(|c| c.name())
-            .collect();
-        let content = user_communication::place_updated_email(&place, &category_names);
-
-        {
-            info!(
-                "Sending e-mails to {} recipients after place {} updated",
-                email_addresses.len(),
-                place.id
-            );
-            compose_and_send_emails(
-                &*self.email_gw,
-                email_addresses,
-                &content.subject,
-                &content.body,
-            );
-        }
+        let category_names = category_names(&mut place, all_categories);
+        info!(
+            "Sending e-mails to {} recipients after place {} updated",
+            recipients.len(),
+            place.id
+        );
+        self.send_subscription_emails(recipients, |templates, locale, unsubscribe_url| {
+            user_communication::place_updated_email(
+                templates,
+                locale,
+                &place,
+                &category_names,
+                unsubscribe_url,
+            )
+        });
     }
-    fn event_created(&self, email_addresses: &[String], event: &Event) {
-        let content = user_communication::event_created_email(&event);
-
-        {
-            info!(
-                "Sending e-mails to {} recipients after new event {} created",
-                email_addresses.len(),
-                event.id,
-            );
-            compose_and_send_emails(
-                &*self.email_gw,
-                email_addresses,
-                &content.subject,
-                &content.body,
-            );
-        }
+    fn event_created(&self, recipients: &[Recipient], event: &Event) {
+        info!(
+            "Sending e-mails to {} recipients after new event {} created",
+            recipients.len(),
+            event.id,
+        );
+        self.send_subscription_emails(recipients, |templates, locale, unsubscribe_url| {
+            user_communication::event_created_email(templates, locale, event, unsubscribe_url)
+        });
     }
-    fn event_updated(&self, email_addresses: &[String], event: &Event) {
-        let content = user_communication::event_updated_email(&event);
-
-        {
-            info!(
-                "Sending e-mails to {} recipients after event {} updated",
-                email_addresses.len(),
-                event.id
-            );
-            compose_and_send_emails(
-                &*self.email_gw,
-                email_addresses,
-                &content.subject,
-                &content.body,
-            );
-        }
+    fn event_updated(&self, recipients: &[Recipient], event: &Event) {
+        info!(
+            "Sending e-mails to {} recipients after event {} updated",
+            recipients.len(),
+            event.id
+        );
+        self.send_subscription_emails(recipients, |templates, locale, unsubscribe_url| {
+            user_communication::event_updated_email(templates, locale, event, unsubscribe_url)
+        });
+    }
+    fn subscription_digest(&self, recipient: &Recipient, items: &[DigestItem]) {
+        info!(
+            "Sending digest with {} changes to {}",
+            items.len(),
+            recipient.email
+        );
+        self.send_subscription_emails(
+            std::slice::from_ref(recipient),
+            |templates, locale, unsubscribe_url| {
+                user_communication::subscription_digest_email(
+                    templates,
+                    locale,
+                    items,
+                    unsubscribe_url,
+                )
+            },
+        );
     }
     fn user_registered_kvm(&self, user: &User) {
         let token = EmailNonce {
@@ -131,7 +164,11 @@ impl NotificationGateway for Notify {
         self.user_registered(user, &url);
     }
     fn user_registered(&self, user: &User, url: &str) {
-        let content = user_communication::user_registration_email(&url);
+        let content = user_communication::user_registration_email(
+            &self.templates,
+            user.locale.as_ref(),
+            &url,
+        );
 
         {
             info!("Sending confirmation e-mail to user {}", user.email);
@@ -143,12 +180,12 @@ impl NotificationGateway for Notify {
             );
         }
     }
-    fn user_reset_password_requested(&self, email_nonce: &EmailNonce) {
+    fn user_reset_password_requested(&self, email_nonce: &EmailNonce, locale: Option<&Locale>) {
         let url = format!(
             "https://openfairdb.org/reset-password?token={}",
             email_nonce.encode_to_string()
         );
-        let content = user_communication::user_reset_password_email(&url);
+        let content = user_communication::user_reset_password_email(&self.templates, locale, &url);
 
         {
             info!(
@@ -1,15 +1,27 @@
+use crate::address_components::AddressComponents;
 use ::geocoding::{Forward, Opencage};
 use itertools::Itertools;
-use ofdb_core::gateways::geocode::GeoCodingGateway;
-use ofdb_entities::address::Address;
+use ofdb_core::gateways::geocode::{GeoCodingError, GeoCodingGateway, GeoCodingResult};
+use ofdb_entities::{address::Address, geo::MapPoint};
+use serde::Deserialize;
+use std::time::Duration;
+
+const REVERSE_API_URL: &str = "https://api.opencagedata.com/geocode/v1/json";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub struct OpenCage {
-    api_key: Option<String>,
+    api_key: String,
+    client: reqwest::blocking::Client,
 }
 
 impl OpenCage {
-    pub fn new(api_key: Option<String>) -> Self {
-        Self { api_key }
+    pub fn new(api_key: String) -> Self {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("HTTP client");
+        Self { api_key, client }
     }
 }
 
@@ -18,33 +30,58 @@ fn address_to_forward_query_string(addr: &Address) -> String {
     addr_parts.iter().filter_map(|x| x.as_ref()).join(",")
 }
 
-fn oc_resolve_address_lat_lng(oc_api_key: String, addr: &Address) -> Option<(f64, f64)> {
-    let oc_req = Opencage::new(oc_api_key);
-    let addr_str = address_to_forward_query_string(addr);
-    match oc_req.forward(&addr_str) {
-        Ok(res) => {
-            if !res.is_empty() {
-                let point = &res[0];
-                debug!("Resolved address location '{}': {:?}", addr_str, point);
-                return Some((point.lat(), point.lng()));
-            }
-        }
-        Err(err) => {
-            warn!("Failed to resolve address location '{}': {}", addr_str, err);
-        }
-    }
-    None
+#[derive(Debug, Deserialize)]
+struct ReverseResponse {
+    results: Vec<ReverseResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReverseResult {
+    components: AddressComponents,
 }
 
 impl GeoCodingGateway for OpenCage {
-    fn resolve_address_lat_lng(&self, addr: &Address) -> Option<(f64, f64)> {
+    fn resolve_address_lat_lng(&self, addr: &Address) -> GeoCodingResult<Option<(f64, f64)>> {
         if addr.is_empty() {
-            None
-        } else {
-            self.api_key
-                .as_ref()
-                .and_then(|key| oc_resolve_address_lat_lng(key.clone(), addr))
+            return Ok(None);
+        }
+        let oc_req = Opencage::new(self.api_key.clone());
+        let addr_str = address_to_forward_query_string(addr);
+        let res = oc_req.forward(&addr_str).map_err(|err| {
+            warn!("Failed to resolve address location '{}': {}", addr_str, err);
+            GeoCodingError::Request(err.to_string())
+        })?;
+        Ok(res.first().map(|point| {
+            debug!("Resolved address location '{}': {:?}", addr_str, point);
+            (point.lat(), point.lng())
+        }))
+    }
+
+    fn resolve_pos_address(&self, pos: MapPoint) -> GeoCodingResult<Option<Address>> {
+        let (lat, lng) = pos.to_lat_lng_deg();
+        let res = self
+            .client
+            .get(REVERSE_API_URL)
+            .query(&[
+                ("q", format!("{:.7}+{:.7}", lat, lng)),
+                ("key", self.api_key.clone()),
+                ("no_annotations", "1".to_string()),
+            ])
+            .send()
+            .map_err(|err| GeoCodingError::Request(err.to_string()))?;
+        let status = res.status();
+        if !status.is_success() {
+            return Err(GeoCodingError::Status(status.as_u16()));
         }
+        let res: ReverseResponse = res
+            .json()
+            .map_err(|err| GeoCodingError::Response(err.to_string()))?;
+        Ok(res
+            .results
+            .into_iter()
+            .next()
+            .map(|r| Address::from(r.components))
+            .filter(|addr| !addr.is_empty()))
     }
 }
 
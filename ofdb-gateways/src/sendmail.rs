@@ -48,6 +48,7 @@ fn send_raw(mail: &str) -> Result<()> {
 /// Don't actually send emails while running the tests or
 /// if the `email` feature is disabled.
 #[cfg(test)]
+#[allow(clippy::unnecessary_wraps)]
 fn send_raw(email: &str) -> Result<()> {
     debug!("Would send e-mail: {}", email);
     Ok(())
@@ -67,6 +68,36 @@ impl EmailGateway for Sendmail {
             }
         }
     }
+
+    fn compose_and_send_unsubscribable(
+        &self,
+        recipient: &Email,
+        subject: &str,
+        body: &str,
+        unsubscribe_url: &str,
+    ) {
+        debug!("Sending e-mail to: {:?}", recipient);
+        let headers = unsubscribe_headers(unsubscribe_url);
+        match compose_with_headers(&self.from, &[recipient], subject, &headers, body) {
+            Ok(email) => {
+                self.send(email);
+            }
+            Err(err) => {
+                warn!("Failed to compose e-mail: {}", err);
+            }
+        }
+    }
+}
+
+// One-click unsubscription from the mail client (RFC 8058)
+fn unsubscribe_headers(unsubscribe_url: &str) -> Vec<(&'static str, String)> {
+    vec![
+        ("List-Unsubscribe", format!("<{}>", unsubscribe_url)),
+        (
+            "List-Unsubscribe-Post",
+            "List-Unsubscribe=One-Click".to_string(),
+        ),
+    ]
 }
 
 // quoted_printable limits the length of lines to 76 chars
@@ -136,6 +167,18 @@ fn encode_header_field(name: &str, input: &str) -> String {
 }
 
 pub fn compose(from: &str, to: &[&str], subject: &str, body: &str) -> Result<String> {
+    compose_with_headers(from, to, subject, &[], body)
+}
+
+/// Composes an e-mail with additional header fields that
+/// must only contain ASCII characters.
+pub fn compose_with_headers(
+    from: &str,
+    to: &[&str],
+    subject: &str,
+    headers: &[(&str, String)],
+    body: &str,
+) -> Result<String> {
     let to: Vec<_> = to.iter().filter(|m| is_valid_email(m)).cloned().collect();
 
     if to.is_empty() {
@@ -152,6 +195,7 @@ pub fn compose(from: &str, to: &[&str], subject: &str, body: &str) -> Result<Str
          From:{from}\r\n\
          To:{to}\r\n\
          {subject_header}\r\n\
+         {headers}\
          MIME-Version:1.0\r\n\
          Content-Type:text/plain;charset=utf-8\r\n\r\n\
          {body}",
@@ -159,6 +203,10 @@ pub fn compose(from: &str, to: &[&str], subject: &str, body: &str) -> Result<Str
         from = from,
         to = to.join(","),
         subject_header = encode_header_field("Subject", &subject),
+        headers = headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}{}", name, value, LINE_BREAK))
+            .collect::<String>(),
         body = body
     );
 
@@ -190,6 +238,23 @@ mod tests {
         assert!(mail.contains(expected));
     }
 
+    #[test]
+    fn create_mail_with_unsubscribe_headers() {
+        let mail = compose_with_headers(
+            "from@ofdb.io",
+            &["mail@test.org"],
+            "Subject",
+            &unsubscribe_headers("https://api.ofdb.io/v0/bbox-subscriptions/123/unsubscribe"),
+            "Hello Mail",
+        )
+        .unwrap();
+        let expected = "Subject:=?UTF-8?Q?Subject?=\r\n\
+             List-Unsubscribe:<https://api.ofdb.io/v0/bbox-subscriptions/123/unsubscribe>\r\n\
+             List-Unsubscribe-Post:List-Unsubscribe=One-Click\r\n\
+             MIME-Version:1.0\r\n";
+        assert!(mail.contains(expected));
+    }
+
     #[test]
     fn check_addresses() {
         assert!(compose("from@mail.org", &[], "foo", "bar").is_err());
@@ -1,4 +1,6 @@
-use ofdb_entities::{address::*, contact::*, event::*, place::*, url::*};
+use ofdb_core::gateways::notify::DigestItem;
+use ofdb_entities::{address::*, contact::*, event::*, locale::*, place::*, url::*};
+use std::{collections::HashMap, fs, io, path::Path};
 
 pub struct EmailContent {
     pub subject: String,
@@ -7,20 +9,243 @@ pub struct EmailContent {
 
 const DATE_TIME_FORMAT: &str = "%Y.%m.%d %H:%M:%S";
 
-const INTRO_ENTRY_CREATED: &str = "ein neuer Eintrag auf der Karte von morgen wurde erstellt";
+// Used if neither the requested nor the default locale is available
+const FALLBACK_LANGUAGE: &str = "en";
 
-const INTRO_ENTRY_UPDATED: &str = "folgender Eintrag auf der Karte von morgen wurde verändert";
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EmailTemplate {
+    UserRegistration,
+    UserResetPassword,
+    PlaceCreated,
+    PlaceUpdated,
+    EventCreated,
+    EventUpdated,
+    SubscriptionDigest,
+}
+
+impl EmailTemplate {
+    pub const ALL: [Self; 7] = [
+        Self::UserRegistration,
+        Self::UserResetPassword,
+        Self::PlaceCreated,
+        Self::PlaceUpdated,
+        Self::EventCreated,
+        Self::EventUpdated,
+        Self::SubscriptionDigest,
+    ];
+
+    /// The file name of the template without the extension.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::UserRegistration => "user_registration",
+            Self::UserResetPassword => "user_reset_password",
+            Self::PlaceCreated => "place_created",
+            Self::PlaceUpdated => "place_updated",
+            Self::EventCreated => "event_created",
+            Self::EventUpdated => "event_updated",
+            Self::SubscriptionDigest => "subscription_digest",
+        }
+    }
+}
+
+const BUILTIN_TEMPLATES: &[(&str, EmailTemplate, &str)] = &[
+    (
+        "de",
+        EmailTemplate::UserRegistration,
+        include_str!("../templates/de/user_registration.txt"),
+    ),
+    (
+        "de",
+        EmailTemplate::UserResetPassword,
+        include_str!("../templates/de/user_reset_password.txt"),
+    ),
+    (
+        "de",
+        EmailTemplate::PlaceCreated,
+        include_str!("../templates/de/place_created.txt"),
+    ),
+    (
+        "de",
+        EmailTemplate::PlaceUpdated,
+        include_str!("../templates/de/place_updated.txt"),
+    ),
+    (
+        "de",
+        EmailTemplate::EventCreated,
+        include_str!("../templates/de/event_created.txt"),
+    ),
+    (
+        "de",
+        EmailTemplate::EventUpdated,
+        include_str!("../templates/de/event_updated.txt"),
+    ),
+    (
+        "de",
+        EmailTemplate::SubscriptionDigest,
+        include_str!("../templates/de/subscription_digest.txt"),
+    ),
+    (
+        "en",
+        EmailTemplate::UserRegistration,
+        include_str!("../templates/en/user_registration.txt"),
+    ),
+    (
+        "en",
+        EmailTemplate::UserResetPassword,
+        include_str!("../templates/en/user_reset_password.txt"),
+    ),
+    (
+        "en",
+        EmailTemplate::PlaceCreated,
+        include_str!("../templates/en/place_created.txt"),
+    ),
+    (
+        "en",
+        EmailTemplate::PlaceUpdated,
+        include_str!("../templates/en/place_updated.txt"),
+    ),
+    (
+        "en",
+        EmailTemplate::EventCreated,
+        include_str!("../templates/en/event_created.txt"),
+    ),
+    (
+        "en",
+        EmailTemplate::EventUpdated,
+        include_str!("../templates/en/event_updated.txt"),
+    ),
+    (
+        "en",
+        EmailTemplate::SubscriptionDigest,
+        include_str!("../templates/en/subscription_digest.txt"),
+    ),
+];
+
+/// Text templates for all notification e-mails by locale.
+///
+/// The first line of a template contains the subject, followed
+/// by an empty line and the body. Placeholders like `{{title}}`
+/// are replaced when rendering.
+#[derive(Debug, Clone)]
+pub struct EmailTemplates {
+    default_locale: Locale,
+    templates: HashMap<(String, EmailTemplate), String>,
+}
+
+impl Default for EmailTemplates {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl EmailTemplates {
+    /// The German and English templates that are shipped with
+    /// the application, using German as the default.
+    pub fn builtin() -> Self {
+        let templates = BUILTIN_TEMPLATES
+            .iter()
+            .map(|(locale, t, text)| ((locale.to_string(), *t), text.to_string()))
+            .collect();
+        Self {
+            default_locale: "de".parse().expect("valid locale"),
+            templates,
+        }
+    }
+
+    pub fn with_default_locale(mut self, default_locale: Locale) -> Self {
+        self.default_locale = default_locale;
+        self
+    }
+
+    /// Loads templates from the sub-directories of `dir` that are
+    /// named by their locale, e.g. `<dir>/en/place_created.txt`.
+    /// Loaded templates replace the existing ones while missing
+    /// templates are kept.
+    pub fn load_dir(mut self, dir: &Path) -> io::Result<Self> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let locale = match path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(str::parse::<Locale>)
+            {
+                Some(Ok(locale)) => locale,
+                _ => {
+                    warn!("Ignoring e-mail templates in {}", path.display());
+                    continue;
+                }
+            };
+            for t in EmailTemplate::ALL.iter() {
+                let file = path.join(t.name()).with_extension("txt");
+                if !file.is_file() {
+                    continue;
+                }
+                debug!("Loading e-mail template {}", file.display());
+                let text = fs::read_to_string(&file)?;
+                self.templates
+                    .insert((locale.as_str().to_string(), *t), text);
+            }
+        }
+        Ok(self)
+    }
 
-const OUTRO_HINT: &str = "Weitere Hinweise und Tipps zur Nutzung, z.B. wie du interaktive Karten
-per <iframe> auf deiner Webseite einbettest oder Papierkarten erstellst,
-findest du hier: https://blog.vonmorgen.org";
+    fn template(&self, locale: Option<&Locale>, t: EmailTemplate) -> &str {
+        let requested = locale
+            .into_iter()
+            .flat_map(|l| vec![l.as_str(), l.language()]);
+        let default = vec![
+            self.default_locale.as_str(),
+            self.default_locale.language(),
+            FALLBACK_LANGUAGE,
+        ];
+        requested
+            .chain(default)
+            .find_map(|l| self.templates.get(&(l.to_string(), t)))
+            .map(String::as_str)
+            .unwrap_or_default()
+    }
 
-fn subject_entry_created(entry_title: &str) -> String {
-    format!("Kvm - neuer Eintrag: {}", entry_title)
+    /// Renders the template for the requested locale or falls back
+    /// to the default locale if no matching template is available.
+    pub fn render(
+        &self,
+        locale: Option<&Locale>,
+        t: EmailTemplate,
+        vars: &[(&str, &str)],
+    ) -> EmailContent {
+        let text = render_placeholders(self.template(locale, t), vars);
+        let mut lines = text.splitn(2, '\n');
+        let subject = lines.next().unwrap_or_default().trim().to_string();
+        let body = lines
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches(|c| c == '\r' || c == '\n')
+            .to_string();
+        EmailContent { subject, body }
+    }
 }
 
-fn subject_entry_updated(entry_title: &str) -> String {
-    format!("Kvm - Eintrag verändert: {}", entry_title)
+fn render_placeholders(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end,
+            None => break,
+        };
+        rendered.push_str(&rest[..start]);
+        let name = rest[start + 2..end].trim();
+        match vars.iter().find(|(key, _)| *key == name) {
+            Some((_, value)) => rendered.push_str(value),
+            None => warn!("Unknown placeholder in e-mail template: {}", name),
+        }
+        rest = &rest[end + 2..];
+    }
+    rendered.push_str(rest);
+    rendered
 }
 
 fn address_line(address: Option<&Address>) -> String {
@@ -44,55 +269,66 @@ fn address_line(address: Option<&Address>) -> String {
     }
 }
 
-pub fn user_registration_email(url: &str) -> EmailContent {
-    let subject = "Karte von morgen: Bitte bestätige deine Email-Adresse".into();
-    let body = format!(
-        "Na du Weltverbesserer*,\n
-wir freuen uns, dass du bei der Karte von morgen mit dabei bist!\n\n
-Bitte bestätige deine Email-Adresse hier:\n
-{url}\n\n
-euphorische Grüße,\n
-das Karte von morgen-Team\n
-{outro_text}",
-        url = url,
-        outro_text = OUTRO_HINT,
-    );
-    EmailContent { subject, body }
+pub fn user_registration_email(
+    templates: &EmailTemplates,
+    locale: Option<&Locale>,
+    url: &str,
+) -> EmailContent {
+    templates.render(locale, EmailTemplate::UserRegistration, &[("url", url)])
 }
 
-pub fn user_reset_password_email(url: &str) -> EmailContent {
-    let subject = "Karte von morgen: Passwort zurücksetzen".into();
-    let body = format!(
-        "Na du Weltverbesserer*,\n
-hast du uns kürzlich gebeten dein Passwort zurücksetzen?\n\n
-Bitte folge zur Eingabe eines neuen Passworts diesem Link:\n
-{url}\n\n
-euphorische Grüße,\n
-das Karte von morgen-Team",
-        url = url,
-    );
-    EmailContent { subject, body }
+pub fn user_reset_password_email(
+    templates: &EmailTemplates,
+    locale: Option<&Locale>,
+    url: &str,
+) -> EmailContent {
+    templates.render(locale, EmailTemplate::UserResetPassword, &[("url", url)])
 }
 
-pub fn place_created_email(place: &Place, category_names: &[String]) -> EmailContent {
-    let subject = subject_entry_created(&place.title);
-    let body = place_email(place, category_names, INTRO_ENTRY_CREATED);
-    EmailContent { subject, body }
+pub fn place_created_email(
+    templates: &EmailTemplates,
+    locale: Option<&Locale>,
+    place: &Place,
+    category_names: &[String],
+    unsubscribe_url: &str,
+) -> EmailContent {
+    place_email(
+        templates,
+        locale,
+        EmailTemplate::PlaceCreated,
+        place,
+        category_names,
+        unsubscribe_url,
+    )
 }
 
 //TODO: calc diff
-pub fn place_updated_email(place: &Place, category_names: &[String]) -> EmailContent {
-    let subject = subject_entry_updated(&place.title);
-    let body = place_email(place, category_names, INTRO_ENTRY_UPDATED);
-    EmailContent { subject, body }
+pub fn place_updated_email(
+    templates: &EmailTemplates,
+    locale: Option<&Locale>,
+    place: &Place,
+    category_names: &[String],
+    unsubscribe_url: &str,
+) -> EmailContent {
+    place_email(
+        templates,
+        locale,
+        EmailTemplate::PlaceUpdated,
+        place,
+        category_names,
+        unsubscribe_url,
+    )
 }
 
-fn place_email(place: &Place, category_names: &[String], intro_sentence: &str) -> String {
-    let category = if !category_names.is_empty() {
-        category_names[0].clone()
-    } else {
-        "".to_string()
-    };
+fn place_email(
+    templates: &EmailTemplates,
+    locale: Option<&Locale>,
+    template: EmailTemplate,
+    place: &Place,
+    category_names: &[String],
+    unsubscribe_url: &str,
+) -> EmailContent {
+    let category = category_names.first().map(String::as_str).unwrap_or("");
 
     let Contact {
         name: _,
@@ -104,56 +340,70 @@ fn place_email(place: &Place, category_names: &[String], intro_sentence: &str) -
         phone: None,
     });
 
-    format!(
-        "Hallo,\n
-{intro_sentence}:\n
-{title} ({category})
-{description}\n
-    Tags: {tags}
-    Adresse: {address_line}
-    Webseite: {homepage}
-    Email-Adresse: {email}
-    Telefon: {phone}\n
-Eintrag anschauen oder bearbeiten:
-https://kartevonmorgen.org/#/?entry={id}\n
-Du kannst dein Abonnement des Kartenbereichs abbestellen,
-indem du dich auf https://kartevonmorgen.org einloggst.\n
-euphorische Grüße,\n
-das Karte von morgen-Team\n
-{outro_text}",
-        intro_sentence = intro_sentence,
-        outro_text = OUTRO_HINT,
-        id = &place.id,
-        title = &place.title,
-        description = &place.description,
-        address_line = address_line(place.location.address.as_ref()),
-        email = email.map(|e| e.to_string()).unwrap_or_default(),
-        phone = phone.unwrap_or_default(),
-        homepage = place
-            .links
-            .as_ref()
-            .and_then(|l| l.homepage.as_ref())
-            .map(Url::as_str)
-            .unwrap_or_else(|| ""),
-        category = category,
-        tags = place.tags.join(", ")
+    templates.render(
+        locale,
+        template,
+        &[
+            ("id", place.id.as_str()),
+            ("title", &place.title),
+            ("description", &place.description),
+            ("category", category),
+            ("tags", &place.tags.join(", ")),
+            ("address", &address_line(place.location.address.as_ref())),
+            (
+                "homepage",
+                place
+                    .links
+                    .as_ref()
+                    .and_then(|l| l.homepage.as_ref())
+                    .map(Url::as_str)
+                    .unwrap_or(""),
+            ),
+            ("email", &email.map(|e| e.to_string()).unwrap_or_default()),
+            ("phone", phone.as_deref().unwrap_or("")),
+            ("unsubscribe_url", unsubscribe_url),
+        ],
     )
 }
 
-pub fn event_created_email(event: &Event) -> EmailContent {
-    let subject = subject_entry_created(&event.title);
-    let body = event_email(event, INTRO_ENTRY_CREATED);
-    EmailContent { subject, body }
+pub fn event_created_email(
+    templates: &EmailTemplates,
+    locale: Option<&Locale>,
+    event: &Event,
+    unsubscribe_url: &str,
+) -> EmailContent {
+    event_email(
+        templates,
+        locale,
+        EmailTemplate::EventCreated,
+        event,
+        unsubscribe_url,
+    )
 }
 
 //TODO: calc diff
-pub fn event_updated_email(event: &Event) -> EmailContent {
-    let subject = subject_entry_updated(&event.title);
-    let body = event_email(event, INTRO_ENTRY_UPDATED);
-    EmailContent { subject, body }
+pub fn event_updated_email(
+    templates: &EmailTemplates,
+    locale: Option<&Locale>,
+    event: &Event,
+    unsubscribe_url: &str,
+) -> EmailContent {
+    event_email(
+        templates,
+        locale,
+        EmailTemplate::EventUpdated,
+        event,
+        unsubscribe_url,
+    )
 }
 
-fn event_email(event: &Event, intro_sentence: &str) -> String {
+fn event_email(
+    templates: &EmailTemplates,
+    locale: Option<&Locale>,
+    template: EmailTemplate,
+    event: &Event,
+    unsubscribe_url: &str,
+) -> EmailContent {
     let Contact {
         name: _,
         email,
@@ -164,43 +414,78 @@ fn event_email(event: &Event, intro_sentence: &str) -> String {
         phone: None,
     });
 
+    templates.render(
+        locale,
+        template,
+        &[
+            ("id", event.id.as_str()),
+            ("title", &event.title),
+            ("description", event.description.as_deref().unwrap_or("")),
+            ("start", &event.start.format(DATE_TIME_FORMAT).to_string()),
+            (
+                "end",
+                &event
+                    .end
+                    .map(|end| end.format(DATE_TIME_FORMAT).to_string())
+                    .unwrap_or_default(),
+            ),
+            ("tags", &event.tags.join(", ")),
+            (
+                "organizer",
+                event.organizer().map(String::as_str).unwrap_or(""),
+            ),
+            (
+                "address",
+                &address_line(event.location.as_ref().and_then(|l| l.address.as_ref())),
+            ),
+            (
+                "homepage",
+                event.homepage.as_ref().map(Url::as_str).unwrap_or(""),
+            ),
+            ("email", &email.map(|e| e.to_string()).unwrap_or_default()),
+            ("phone", phone.as_deref().unwrap_or("")),
+            ("unsubscribe_url", unsubscribe_url),
+        ],
+    )
+}
+
+// New items are marked with `+` and changed items with `*`
+fn digest_item_lines(item: &DigestItem) -> String {
+    let (marker, title, id) = match item {
+        DigestItem::PlaceAdded(place) => ('+', place.title.clone(), place.id.as_str()),
+        DigestItem::PlaceUpdated(place) => ('*', place.title.clone(), place.id.as_str()),
+        DigestItem::EventCreated(event) => (
+            '+',
+            format!("{} ({})", event.title, event.start.format(DATE_TIME_FORMAT)),
+            event.id.as_str(),
+        ),
+        DigestItem::EventUpdated(event) => (
+            '*',
+            format!("{} ({})", event.title, event.start.format(DATE_TIME_FORMAT)),
+            event.id.as_str(),
+        ),
+    };
     format!(
-        "Hallo,\n
-{intro_sentence}:\n
-{title} ({category})
-{description}\n
-    Beginn: {start}
-    Ende: {end}
-    Tags: {tags}
-    Veranstalter: {organizer}
-    Adresse: {address_line}
-    Webseite: {homepage}
-    Email-Adresse: {email}
-    Telefon: {phone}\n
-Eintrag anschauen oder bearbeiten:
-https://kartevonmorgen.org/#/?entry={id}\n
-Du kannst dein Abonnement des Kartenbereichs abbestellen,
-indem du dich auf https://kartevonmorgen.org einloggst.\n
-euphorische Grüße,\n
-das Karte von morgen-Team\n
-{outro_text}",
-        intro_sentence = intro_sentence,
-        outro_text = OUTRO_HINT,
-        category = "Event",
-        id = &event.id,
-        title = &event.title,
-        start = event.start.format(DATE_TIME_FORMAT),
-        end = event
-            .end
-            .map(|end| end.format(DATE_TIME_FORMAT).to_string())
-            .unwrap_or_default(),
-        description = event.description.as_deref().unwrap_or(""),
-        organizer = event.organizer().map(String::as_str).unwrap_or(""),
-        address_line = address_line(event.location.as_ref().and_then(|l| l.address.as_ref())),
-        email = email.map(|e| e.to_string()).unwrap_or_default(),
-        phone = phone.unwrap_or_default(),
-        homepage = event.homepage.as_ref().map(Url::as_str).unwrap_or(""),
-        tags = event.tags.join(", ")
+        "{} {}\n  https://kartevonmorgen.org/#/?entry={}",
+        marker, title, id
+    )
+}
+
+pub fn subscription_digest_email(
+    templates: &EmailTemplates,
+    locale: Option<&Locale>,
+    items: &[DigestItem],
+    unsubscribe_url: &str,
+) -> EmailContent {
+    let lines: Vec<_> = items.iter().map(digest_item_lines).collect();
+    templates.render(
+        locale,
+        EmailTemplate::SubscriptionDigest,
+        &[
+            ("count", &items.len().to_string()),
+            ("items", &lines.join("\n\n")),
+            ("unsubscribe_url", unsubscribe_url),
+        ],
     )
 }
 
@@ -294,14 +579,25 @@ mod tests {
             homepage: Some("https://kartevonmorgen.org".parse().unwrap()),
             image_url: None,
             image_link_url: None,
+            image_id: None,
+            recurrence: None,
+            series_id: None,
             tags: vec!["<tag1>".into(), "<tag2>".into()],
         }
     }
 
+    fn de() -> Locale {
+        "de".parse().unwrap()
+    }
+
+    const INTRO_ENTRY_CREATED: &str = "ein neuer Eintrag auf der Karte von morgen wurde erstellt";
+    const INTRO_ENTRY_UPDATED: &str = "folgender Eintrag auf der Karte von morgen wurde verändert";
+    const OUTRO_HINT: &str = "findest du hier: https://blog.vonmorgen.org";
+
     #[test]
     fn print_user_registration_email() {
         let url = "https://kartevonmorgen.org/confirm-email/";
-        let email = user_registration_email(url);
+        let email = user_registration_email(&EmailTemplates::builtin(), Some(&de()), url);
         assert!(email.body.contains(OUTRO_HINT));
         assert!(email.body.contains(url));
         print_email(&email);
@@ -310,52 +606,166 @@ mod tests {
     #[test]
     fn print_user_reset_password_email() {
         let url = "https://kartevonmorgen.org/reset-password/";
-        let email = user_reset_password_email(url);
+        let email = user_reset_password_email(&EmailTemplates::builtin(), Some(&de()), url);
         assert!(email.body.contains(url));
         print_email(&email);
     }
 
+    const UNSUBSCRIBE_URL: &str = "https://api.ofdb.io/v0/bbox-subscriptions/<id>/unsubscribe";
+
     #[test]
     fn print_place_created_email() {
         let place = new_place();
-        let email = place_created_email(&place, &["<category>".into()]);
+        let email = place_created_email(
+            &EmailTemplates::builtin(),
+            Some(&de()),
+            &place,
+            &["<category>".into()],
+            UNSUBSCRIBE_URL,
+        );
+        assert_eq!("Kvm - neuer Eintrag: <title>", email.subject);
         assert!(email.body.contains(INTRO_ENTRY_CREATED));
         assert!(email.body.contains(OUTRO_HINT));
         assert!(email.body.contains(place.id.as_str()));
-        assert!(email.body.contains(&place.title));
+        assert!(email.body.contains("<title> (<category>)"));
+        assert!(email.body.contains(UNSUBSCRIBE_URL));
         print_email(&email);
     }
 
     #[test]
     fn print_place_updated_email() {
         let place = new_place();
-        let email = place_updated_email(&place, &["<category>".into()]);
+        let email = place_updated_email(
+            &EmailTemplates::builtin(),
+            Some(&de()),
+            &place,
+            &["<category>".into()],
+            UNSUBSCRIBE_URL,
+        );
         assert!(email.body.contains(INTRO_ENTRY_UPDATED));
         assert!(email.body.contains(OUTRO_HINT));
         assert!(email.body.contains(place.id.as_str()));
         assert!(email.body.contains(&place.title));
+        assert!(email.body.contains(UNSUBSCRIBE_URL));
         print_email(&email);
     }
 
     #[test]
     fn print_event_created_email() {
         let event = new_event();
-        let email = event_created_email(&event);
+        let email = event_created_email(
+            &EmailTemplates::builtin(),
+            Some(&de()),
+            &event,
+            UNSUBSCRIBE_URL,
+        );
         assert!(email.body.contains(INTRO_ENTRY_CREATED));
         assert!(email.body.contains(OUTRO_HINT));
         assert!(email.body.contains(event.id.as_str()));
         assert!(email.body.contains(&event.title));
+        assert!(email.body.contains("Veranstalter: <organizer>"));
+        assert!(email.body.contains(UNSUBSCRIBE_URL));
         print_email(&email);
     }
 
     #[test]
     fn print_event_updated_email() {
         let event = new_event();
-        let email = event_updated_email(&event);
+        let email = event_updated_email(
+            &EmailTemplates::builtin(),
+            Some(&de()),
+            &event,
+            UNSUBSCRIBE_URL,
+        );
         assert!(email.body.contains(INTRO_ENTRY_UPDATED));
         assert!(email.body.contains(OUTRO_HINT));
         assert!(email.body.contains(event.id.as_str()));
         assert!(email.body.contains(&event.title));
+        assert!(email.body.contains(UNSUBSCRIBE_URL));
+        print_email(&email);
+    }
+
+    #[test]
+    fn print_subscription_digest_email() {
+        let place = new_place();
+        let event = new_event();
+        let url = UNSUBSCRIBE_URL;
+        let email = subscription_digest_email(
+            &EmailTemplates::builtin(),
+            Some(&de()),
+            &[
+                DigestItem::PlaceAdded(place),
+                DigestItem::EventUpdated(event),
+            ],
+            url,
+        );
+        assert_eq!("Kvm - 2 Änderungen in deinem Kartenbereich", email.subject);
+        assert!(email
+            .body
+            .contains("+ <title>\n  https://kartevonmorgen.org/#/?entry=<id>"));
+        assert!(email.body.contains("* <title> ("));
+        assert!(email.body.contains(url));
         print_email(&email);
     }
+
+    #[test]
+    fn print_english_place_created_email() {
+        let place = new_place();
+        let en_gb = "en-GB".parse().unwrap();
+        let email = place_created_email(
+            &EmailTemplates::builtin(),
+            Some(&en_gb),
+            &place,
+            &["<category>".into()],
+            UNSUBSCRIBE_URL,
+        );
+        assert_eq!("MoT - new entry: <title>", email.subject);
+        assert!(email.body.starts_with("Hello,"));
+        assert!(email.body.contains("Address: <street>, <zip> <city>"));
+        print_email(&email);
+    }
+
+    #[test]
+    fn fall_back_to_default_locale() {
+        let templates = EmailTemplates::builtin();
+        let fr = "fr".parse().unwrap();
+        let email = user_reset_password_email(&templates, Some(&fr), "<url>");
+        assert_eq!("Karte von morgen: Passwort zurücksetzen", email.subject);
+        let email = user_reset_password_email(&templates, None, "<url>");
+        assert_eq!("Karte von morgen: Passwort zurücksetzen", email.subject);
+        let templates = templates.with_default_locale("en".parse().unwrap());
+        let email = user_reset_password_email(&templates, Some(&fr), "<url>");
+        assert_eq!("Map of Tomorrow: Reset your password", email.subject);
+    }
+
+    #[test]
+    fn render_placeholders_with_unknown_names() {
+        assert_eq!(
+            "Hi foo, !",
+            render_placeholders("Hi {{ name }}, {{unknown}}!", &[("name", "foo")])
+        );
+        assert_eq!("{{ open", render_placeholders("{{ open", &[]));
+    }
+
+    #[test]
+    fn load_templates_from_dir() {
+        let dir = std::env::temp_dir().join(format!("ofdb-email-templates-{}", std::process::id()));
+        fs::create_dir_all(dir.join("fr")).unwrap();
+        fs::create_dir_all(dir.join("not a locale")).unwrap();
+        fs::write(
+            dir.join("fr").join("user_reset_password.txt"),
+            "Réinitialiser le mot de passe\n\nBonjour,\n{{url}}\n",
+        )
+        .unwrap();
+        let templates = EmailTemplates::builtin().load_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let fr = "fr-FR".parse().unwrap();
+        let email = user_reset_password_email(&templates, Some(&fr), "<url>");
+        assert_eq!("Réinitialiser le mot de passe", email.subject);
+        assert_eq!("Bonjour,\n<url>\n", email.body);
+        // Missing templates are taken from the default locale
+        let email = user_registration_email(&templates, Some(&fr), "<url>");
+        assert!(email.subject.starts_with("Karte von morgen"));
+    }
 }
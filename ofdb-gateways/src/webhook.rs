@@ -0,0 +1,185 @@
+use hmac::{Hmac, Mac, NewMac};
+use ofdb_core::gateways::webhook::{WebhookDeliveryError, WebhookGateway};
+use ofdb_entities::{url::Url, webhook::WebhookEventType};
+use sha2::Sha256;
+use std::{fmt::Write, time::Duration};
+
+pub const HEADER_EVENT: &str = "X-Ofdb-Event";
+pub const HEADER_DELIVERY: &str = "X-Ofdb-Delivery";
+pub const HEADER_SIGNATURE: &str = "X-Ofdb-Signature";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Delivers webhooks as JSON POST requests.
+///
+/// The body is signed with the shared secret of the webhook
+/// (HMAC-SHA256) and the hex-encoded signature is sent in
+/// the header `X-Ofdb-Signature` as `sha256=<signature>`.
+#[derive(Debug, Clone)]
+pub struct HttpWebhookGateway {
+    client: reqwest::blocking::Client,
+}
+
+impl HttpWebhookGateway {
+    pub fn new() -> Self {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("HTTP client");
+        Self { client }
+    }
+}
+
+impl Default for HttpWebhookGateway {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn sign_payload(secret: &str, payload: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_varkey(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(payload.as_bytes());
+    let code = mac.finalize().into_bytes();
+    let mut signature = String::with_capacity(code.len() * 2);
+    for byte in code.iter() {
+        write!(&mut signature, "{:02x}", byte).expect("hex");
+    }
+    signature
+}
+
+impl WebhookGateway for HttpWebhookGateway {
+    fn deliver(
+        &self,
+        url: &Url,
+        secret: &str,
+        delivery_id: &str,
+        event_type: WebhookEventType,
+        payload: &str,
+    ) -> Result<u16, WebhookDeliveryError> {
+        let signature = sign_payload(secret, payload);
+        let res = self
+            .client
+            .post(url.as_str())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(HEADER_EVENT, event_type.as_str())
+            .header(HEADER_DELIVERY, delivery_id)
+            .header(HEADER_SIGNATURE, format!("sha256={}", signature))
+            .body(payload.to_owned())
+            .send()
+            .map_err(|err| WebhookDeliveryError::Request(err.to_string()))?;
+        let status = res.status();
+        debug!("Webhook {} responded with {}", url.as_str(), status);
+        if status.is_success() {
+            Ok(status.as_u16())
+        } else {
+            Err(WebhookDeliveryError::Status(status.as_u16()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::mpsc,
+        thread,
+    };
+
+    // A local HTTP stand-in that responds to a single request with
+    // the given status and returns the received headers and body.
+    fn serve_once(status: u16) -> (Url, mpsc::Receiver<(Vec<String>, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut headers = vec![];
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end().to_string();
+                if line.is_empty() {
+                    break;
+                }
+                headers.push(line);
+            }
+            let content_length = headers
+                .iter()
+                .find_map(|h| {
+                    let (name, value) = h.split_at(h.find(':')?);
+                    if name.eq_ignore_ascii_case("content-length") {
+                        value[1..].trim().parse::<usize>().ok()
+                    } else {
+                        None
+                    }
+                })
+                .unwrap_or_default();
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status
+            )
+            .unwrap();
+            tx.send((headers, String::from_utf8(body).unwrap()))
+                .unwrap();
+        });
+        (url, rx)
+    }
+
+    fn header<'a>(headers: &'a [String], name: &str) -> Option<&'a str> {
+        headers.iter().find_map(|h| {
+            let (n, v) = h.split_at(h.find(':')?);
+            if n.eq_ignore_ascii_case(name) {
+                Some(v[1..].trim())
+            } else {
+                None
+            }
+        })
+    }
+
+    #[test]
+    fn sign_payload_with_hmac_sha256() {
+        assert_eq!(
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8",
+            sign_payload("key", "The quick brown fox jumps over the lazy dog")
+        );
+    }
+
+    #[test]
+    fn deliver_signed_payload() {
+        let (url, rx) = serve_once(204);
+        let gw = HttpWebhookGateway::new();
+        let payload = r#"{"foo":"bar"}"#;
+        let status = gw
+            .deliver(&url, "secret", "123", WebhookEventType::PlaceAdded, payload)
+            .unwrap();
+        assert_eq!(204, status);
+        let (headers, body) = rx.recv().unwrap();
+        assert!(headers[0].starts_with("POST /hook "));
+        assert_eq!(payload, body);
+        assert_eq!(Some("place_added"), header(&headers, HEADER_EVENT));
+        assert_eq!(Some("123"), header(&headers, HEADER_DELIVERY));
+        assert_eq!(
+            Some(format!("sha256={}", sign_payload("secret", payload)).as_str()),
+            header(&headers, HEADER_SIGNATURE)
+        );
+    }
+
+    #[test]
+    fn reject_unsuccessful_responses() {
+        let (url, _rx) = serve_once(500);
+        let gw = HttpWebhookGateway::new();
+        match gw.deliver(&url, "secret", "123", WebhookEventType::EventCreated, "{}") {
+            Err(WebhookDeliveryError::Status(500)) => (),
+            res => panic!("Unexpected result: {:?}", res),
+        }
+    }
+}
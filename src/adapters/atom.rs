@@ -0,0 +1,305 @@
+//! Syndication of places and events as Atom feeds (RFC 4287)
+
+use super::ical::format_address;
+use crate::core::entities::*;
+use chrono::prelude::*;
+
+pub const CONTENT_TYPE_SUBTYPE: &str = "atom+xml";
+
+const GENERATOR: &str = "OpenFairDB";
+
+// All feeds are published by the same author
+const AUTHOR_NAME: &str = "OpenFairDB";
+
+// Entries are identified by tag URIs (RFC 4151)
+const TAG_URI_PREFIX: &str = "tag:openfairdb.org,2021:";
+
+#[derive(Debug, Clone)]
+pub struct Feed {
+    // Distinguishes the feed from all other feeds
+    pub id: String,
+    pub title: String,
+    // The absolute URL of the feed itself
+    pub self_link: String,
+    pub entries: Vec<FeedEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeedEntry {
+    pub id: String,
+    pub title: String,
+    pub link: String,
+    pub updated: TimestampMs,
+    pub summary: Option<String>,
+    pub categories: Vec<String>,
+}
+
+impl Feed {
+    pub fn new(id: &str, title: impl Into<String>, self_link: impl Into<String>) -> Self {
+        Self {
+            id: format!("{}{}", TAG_URI_PREFIX, id),
+            title: title.into(),
+            self_link: self_link.into(),
+            entries: vec![],
+        }
+    }
+
+    // The feed has been updated when the latest entry has been
+    // updated. Empty feeds are updated at the given time.
+    fn updated(&self, now: TimestampMs) -> TimestampMs {
+        self.entries.iter().map(|e| e.updated).max().unwrap_or(now)
+    }
+
+    pub fn to_xml(&self, now: TimestampMs) -> String {
+        let mut xml = String::new();
+        xml.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+        xml.push('\n');
+        xml.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+        xml.push('\n');
+        write_element(&mut xml, 1, "id", &self.id);
+        write_element(&mut xml, 1, "title", &self.title);
+        write_element(&mut xml, 1, "updated", &format_timestamp(self.updated(now)));
+        write_element(&mut xml, 1, "generator", GENERATOR);
+        indent(&mut xml, 1);
+        xml.push_str("<author>\n");
+        write_element(&mut xml, 2, "name", AUTHOR_NAME);
+        indent(&mut xml, 1);
+        xml.push_str("</author>\n");
+        write_link(&mut xml, 1, "self", &self.self_link);
+        for entry in &self.entries {
+            write_entry(&mut xml, entry);
+        }
+        xml.push_str("</feed>\n");
+        xml
+    }
+}
+
+fn write_entry(xml: &mut String, entry: &FeedEntry) {
+    let FeedEntry {
+        id,
+        title,
+        link,
+        updated,
+        summary,
+        categories,
+    } = entry;
+    indent(xml, 1);
+    xml.push_str("<entry>\n");
+    write_element(xml, 2, "id", id);
+    write_element(xml, 2, "title", title);
+    write_element(xml, 2, "updated", &format_timestamp(*updated));
+    write_link(xml, 2, "alternate", link);
+    if let Some(summary) = summary.as_ref().filter(|s| !s.trim().is_empty()) {
+        write_element(xml, 2, "summary", summary);
+    }
+    for category in categories {
+        indent(xml, 2);
+        xml.push_str(&format!(r#"<category term="{}"/>"#, escape_xml(category)));
+        xml.push('\n');
+    }
+    indent(xml, 1);
+    xml.push_str("</entry>\n");
+}
+
+fn indent(xml: &mut String, level: usize) {
+    for _ in 0..level {
+        xml.push_str("  ");
+    }
+}
+
+fn write_element(xml: &mut String, level: usize, name: &str, text: &str) {
+    indent(xml, level);
+    xml.push_str(&format!("<{}>{}</{}>", name, escape_xml(text), name));
+    xml.push('\n');
+}
+
+fn write_link(xml: &mut String, level: usize, rel: &str, href: &str) {
+    indent(xml, level);
+    xml.push_str(&format!(
+        r#"<link rel="{}" href="{}"/>"#,
+        rel,
+        escape_xml(href)
+    ));
+    xml.push('\n');
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters are not allowed in XML 1.0
+            c if c.is_control() && c != '\n' && c != '\t' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// RFC 3339
+fn format_timestamp(timestamp: TimestampMs) -> String {
+    Utc.timestamp_millis(timestamp.into_inner())
+        .to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn format_date_time(dt: NaiveDateTime) -> String {
+    // All time stamps are stored in UTC
+    dt.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+/// The absolute URL of a feed with the given path and query
+/// relative to the public base URL of the API.
+pub fn feed_url(base_url: &str, path: &str, query: Option<&str>) -> String {
+    match query.filter(|q| !q.is_empty()) {
+        Some(query) => format!("{}{}?{}", base_url, path, query),
+        None => format!("{}{}", base_url, path),
+    }
+}
+
+fn entry_link(entry_url_prefix: &str, id: &Id) -> String {
+    format!("{}{}", entry_url_prefix, id)
+}
+
+pub fn place_entry(place: &Place, activity_log: &ActivityLog, entry_url_prefix: &str) -> FeedEntry {
+    let Place {
+        id,
+        title,
+        description,
+        location,
+        tags,
+        ..
+    } = place;
+    let mut summary = description.clone();
+    if let Some(address) = location.address.as_ref().filter(|a| !a.is_empty()) {
+        summary = format!("{}\n\n{}", summary, format_address(address));
+    }
+    FeedEntry {
+        id: format!("{}place:{}", TAG_URI_PREFIX, id),
+        title: title.clone(),
+        link: entry_link(entry_url_prefix, id),
+        updated: activity_log.activity.at,
+        summary: Some(summary),
+        categories: tags.clone(),
+    }
+}
+
+pub fn event_entry(event: &Event, updated: TimestampMs, entry_url_prefix: &str) -> FeedEntry {
+    let Event {
+        id,
+        title,
+        description,
+        start,
+        end,
+        location,
+        tags,
+        homepage,
+        series_id,
+        ..
+    } = event;
+    let mut summary = format_date_time(*start);
+    if let Some(end) = end {
+        summary = format!("{} - {}", summary, format_date_time(*end));
+    }
+    if let Some(address) = location
+        .as_ref()
+        .and_then(|l| l.address.as_ref())
+        .filter(|a| !a.is_empty())
+    {
+        summary = format!("{}\n{}", summary, format_address(address));
+    }
+    if let Some(description) = description {
+        summary = format!("{}\n\n{}", summary, description);
+    }
+    let id_suffix = if series_id.as_ref() == Some(id) {
+        // Distinguishes the occurrences of a recurring event
+        format!(":{}", start.timestamp())
+    } else {
+        String::new()
+    };
+    FeedEntry {
+        id: format!("{}event:{}{}", TAG_URI_PREFIX, id, id_suffix),
+        title: title.clone(),
+        link: homepage
+            .as_ref()
+            .map(|url| url.as_str().to_owned())
+            .unwrap_or_else(|| entry_link(entry_url_prefix, id)),
+        updated,
+        summary: Some(summary),
+        categories: tags.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, updated: i64) -> FeedEntry {
+        FeedEntry {
+            id: id.into(),
+            title: format!("Entry {}", id),
+            link: format!("https://example.com/{}", id),
+            updated: TimestampMs::from_inner(updated),
+            summary: None,
+            categories: vec![],
+        }
+    }
+
+    #[test]
+    fn escape_special_characters() {
+        assert_eq!(
+            "a &lt;b&gt; &amp; &quot;c&quot; &apos;d&apos;",
+            escape_xml("a <b> & \"c\" 'd'")
+        );
+        assert_eq!("ab", escape_xml("a\u{1}b"));
+    }
+
+    #[test]
+    fn format_timestamps_as_rfc3339() {
+        assert_eq!(
+            "2021-03-05T12:00:00Z",
+            format_timestamp(TimestampMs::from_inner(1_614_945_600_123))
+        );
+    }
+
+    #[test]
+    fn feed_is_updated_with_latest_entry() {
+        let now = TimestampMs::from_inner(3_000_000);
+        let mut feed = Feed::new("places", "Places", "/entries/recently-changed.atom");
+        assert_eq!(now, feed.updated(now));
+        feed.entries = vec![entry("a", 1_000_000), entry("b", 2_000_000)];
+        assert_eq!(TimestampMs::from_inner(2_000_000), feed.updated(now));
+    }
+
+    #[test]
+    fn write_feed_with_entries() {
+        let mut feed = Feed::new(
+            "places",
+            "Places & more",
+            feed_url(
+                "https://example.com/api",
+                "/feed.atom",
+                Some("tag=a&bbox=1"),
+            ),
+        );
+        let mut e = entry("a", 0);
+        e.summary = Some("<b>bold</b>".into());
+        e.categories = vec!["foo".into()];
+        feed.entries = vec![e];
+        let xml = feed.to_xml(TimestampMs::now());
+        assert!(xml.starts_with("<?xml"));
+        assert!(xml.contains("<id>tag:openfairdb.org,2021:places</id>"));
+        assert!(xml.contains("<title>Places &amp; more</title>"));
+        assert!(xml.contains(
+            r#"<link rel="self" href="https://example.com/api/feed.atom?tag=a&amp;bbox=1"/>"#
+        ));
+        assert!(xml.contains("<author>\n    <name>OpenFairDB</name>\n  </author>"));
+        assert!(xml.contains("<updated>1970-01-01T00:00:00Z</updated>"));
+        assert!(xml.contains("<summary>&lt;b&gt;bold&lt;/b&gt;</summary>"));
+        assert!(xml.contains(r#"<category term="foo"/>"#));
+        assert!(xml.trim_end().ends_with("</feed>"));
+    }
+}
@@ -0,0 +1,119 @@
+//! GeoJSON (RFC 7946) representation of search results
+
+use super::json::{EntrySearchRatings, PlaceSearchResult, ReviewStatus};
+
+#[derive(Serialize)]
+#[serde(tag = "type")]
+pub struct FeatureCollection {
+    pub features: Vec<Feature>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type")]
+pub struct Feature {
+    pub id: String,
+    pub geometry: Geometry,
+    pub properties: PlaceProperties,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type")]
+pub enum Geometry {
+    Point {
+        // [longitude, latitude]
+        coordinates: [f64; 2],
+    },
+}
+
+#[derive(Serialize)]
+pub struct PlaceProperties {
+    pub id: String,
+    pub status: Option<ReviewStatus>,
+    pub title: String,
+    pub description: String,
+    pub categories: Vec<String>,
+    pub tags: Vec<String>,
+    pub ratings: EntrySearchRatings,
+}
+
+impl From<PlaceSearchResult> for Feature {
+    fn from(from: PlaceSearchResult) -> Self {
+        let PlaceSearchResult {
+            id,
+            status,
+            lat,
+            lng,
+            title,
+            description,
+            categories,
+            tags,
+            ratings,
+        } = from;
+        Self {
+            id: id.clone(),
+            geometry: Geometry::Point {
+                coordinates: [lng, lat],
+            },
+            properties: PlaceProperties {
+                id,
+                status,
+                title,
+                description,
+                categories,
+                tags,
+                ratings,
+            },
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for FeatureCollection
+where
+    T: Into<Feature>,
+{
+    fn from(from: Vec<T>) -> Self {
+        Self {
+            features: from.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        db::IndexedPlace,
+        entities::{self as e, Category, MapPoint},
+    };
+
+    #[test]
+    fn serialize_feature_collection() {
+        let place = IndexedPlace {
+            id: "foo".into(),
+            status: Some(e::ReviewStatus::Confirmed),
+            pos: MapPoint::from_lat_lng_deg(48.5, 9.25),
+            title: "Foo".into(),
+            description: "Bar".into(),
+            tags: vec!["tag".into(), Category::TAG_NON_PROFIT.into()],
+            ratings: Default::default(),
+        };
+        let features = FeatureCollection::from(vec![PlaceSearchResult::from(place)]);
+        let json = serde_json::to_value(&features).unwrap();
+        assert_eq!("FeatureCollection", json["type"]);
+        let feature = &json["features"][0];
+        assert_eq!("Feature", feature["type"]);
+        assert_eq!("foo", feature["id"]);
+        assert_eq!("Point", feature["geometry"]["type"]);
+        let coordinates = feature["geometry"]["coordinates"].as_array().unwrap();
+        assert!((coordinates[0].as_f64().unwrap() - 9.25).abs() < 1e-6);
+        assert!((coordinates[1].as_f64().unwrap() - 48.5).abs() < 1e-6);
+        assert_eq!("Foo", feature["properties"]["title"]);
+        assert_eq!("confirmed", feature["properties"]["status"]);
+        assert_eq!(serde_json::json!(["tag"]), feature["properties"]["tags"]);
+        assert_eq!(
+            serde_json::json!([Category::ID_NON_PROFIT]),
+            feature["properties"]["categories"]
+        );
+        assert!(feature["properties"]["ratings"]["total"].is_number());
+    }
+}
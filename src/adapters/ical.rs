@@ -0,0 +1,943 @@
+//! Conversion of events from and into the iCalendar format (RFC 5545)
+
+use crate::core::{entities::*, error::ParameterError, usecases};
+use chrono::{prelude::*, Duration};
+use ofdb_core::recurrence::occurrences;
+use std::collections::HashMap;
+
+const PRODUCT_ID: &str = "-//openFairDB//Events//EN";
+
+// Appended to the event id to obtain a globally unique identifier
+const UID_DOMAIN: &str = "openfairdb.org";
+
+// Content lines should not be longer than 75 octets, excluding
+// the line break (RFC 5545, section 3.1)
+const MAX_LINE_LEN: usize = 75;
+
+const LINE_BREAK: &str = "\r\n";
+
+// Time zones that don't need to be defined by the calendar
+const UTC_TZIDS: &[&str] = &["UTC", "Etc/UTC", "GMT", "Etc/GMT", "Z"];
+
+pub fn events_to_calendar(events: &[Event], dtstamp: NaiveDateTime) -> String {
+    let mut cal = String::new();
+    write_line(&mut cal, "BEGIN", "VCALENDAR");
+    write_line(&mut cal, "VERSION", "2.0");
+    write_line(&mut cal, "PRODID", PRODUCT_ID);
+    write_line(&mut cal, "CALSCALE", "GREGORIAN");
+    write_line(&mut cal, "METHOD", "PUBLISH");
+    for event in events {
+        write_event(&mut cal, event, dtstamp);
+    }
+    write_line(&mut cal, "END", "VCALENDAR");
+    cal
+}
+
+fn write_event(cal: &mut String, event: &Event, dtstamp: NaiveDateTime) {
+    let Event {
+        id,
+        title,
+        description,
+        start,
+        end,
+        location,
+        contact,
+        tags,
+        homepage,
+        series_id,
+        ..
+    } = event;
+    write_line(cal, "BEGIN", "VEVENT");
+    if series_id.as_ref() == Some(id) {
+        // Each occurrence of a recurring event is exported separately
+        write_line(
+            cal,
+            "UID",
+            &format!("{}-{}@{}", id, start.timestamp(), UID_DOMAIN),
+        );
+    } else {
+        write_line(cal, "UID", &format!("{}@{}", id, UID_DOMAIN));
+    }
+    write_line(cal, "DTSTAMP", &format_date_time(dtstamp));
+    write_line(cal, "DTSTART", &format_date_time(*start));
+    if let Some(end) = end {
+        write_line(cal, "DTEND", &format_date_time(*end));
+    }
+    write_line(cal, "SUMMARY", &escape_text(title));
+    if let Some(description) = description {
+        write_line(cal, "DESCRIPTION", &escape_text(description));
+    }
+    if let Some(location) = location {
+        if let Some(address) = location.address.as_ref().filter(|a| !a.is_empty()) {
+            write_line(cal, "LOCATION", &escape_text(&format_address(address)));
+        }
+        if location.pos.is_valid() {
+            let geo = format!(
+                "{:.6};{:.6}",
+                location.pos.lat().to_deg(),
+                location.pos.lng().to_deg()
+            );
+            write_line(cal, "GEO", &geo);
+        }
+    }
+    if let Some(contact) = contact {
+        let details: Vec<_> = contact
+            .name
+            .as_deref()
+            .into_iter()
+            .chain(contact.email.as_ref().map(|email| email.as_str()))
+            .chain(contact.phone.as_deref())
+            .collect();
+        if !details.is_empty() {
+            write_line(cal, "CONTACT", &escape_text(&details.join(", ")));
+        }
+    }
+    if let Some(homepage) = homepage {
+        write_line(cal, "URL", homepage.as_str());
+    }
+    if !tags.is_empty() {
+        let categories: Vec<_> = tags.iter().map(|t| escape_text(t)).collect();
+        write_line(cal, "CATEGORIES", &categories.join(","));
+    }
+    write_line(cal, "END", "VEVENT");
+}
+
+fn format_date_time(dt: NaiveDateTime) -> String {
+    // All time stamps are stored in UTC
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+pub(crate) fn format_address(address: &Address) -> String {
+    let Address {
+        street,
+        zip,
+        city,
+        country,
+        state,
+    } = address;
+    let zip_city = [zip, city]
+        .iter()
+        .filter_map(|s| s.as_ref().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let zip_city = if zip_city.is_empty() {
+        None
+    } else {
+        Some(zip_city)
+    };
+    street
+        .iter()
+        .cloned()
+        .chain(zip_city)
+        .chain(state.iter().cloned())
+        .chain(country.iter().cloned())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Long lines are folded by inserting a line break followed by
+// a single space without splitting multi-byte characters.
+fn write_line(cal: &mut String, name: &str, value: &str) {
+    let mut line_len = 0;
+    for c in name
+        .chars()
+        .chain(std::iter::once(':'))
+        .chain(value.chars())
+    {
+        if line_len + c.len_utf8() > MAX_LINE_LEN {
+            cal.push_str(LINE_BREAK);
+            cal.push(' ');
+            line_len = 1;
+        }
+        cal.push(c);
+        line_len += c.len_utf8();
+    }
+    cal.push_str(LINE_BREAK);
+}
+
+struct ContentLine {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl ContentLine {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+fn invalid_calendar(msg: impl Into<String>) -> ParameterError {
+    ParameterError::InvalidCalendar(msg.into())
+}
+
+fn unfold_lines(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for line in ics.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(&line[1..]);
+                continue;
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line.to_owned());
+        }
+    }
+    lines
+}
+
+// Splits at the given delimiter outside of quoted parameter values
+fn split_unquoted(s: &str, delim: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if c == delim && !quoted {
+            parts.push(&s[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+fn parse_content_line(line: &str) -> Result<ContentLine, ParameterError> {
+    let mut quoted = false;
+    let colon = line
+        .char_indices()
+        .find(|&(_, c)| {
+            if c == '"' {
+                quoted = !quoted;
+            }
+            c == ':' && !quoted
+        })
+        .map(|(i, _)| i)
+        .ok_or_else(|| invalid_calendar(format!("malformed line '{}'", line)))?;
+    let (name_and_params, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = split_unquoted(name_and_params, ';').into_iter();
+    let name = parts.next().unwrap_or_default().trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|param| {
+            let mut split = param.splitn(2, '=');
+            let name = split.next()?.trim().to_owned();
+            let value = split.next()?.trim_matches('"').to_owned();
+            Some((name, value))
+        })
+        .collect();
+    Ok(ContentLine {
+        name,
+        params,
+        value: value.to_owned(),
+    })
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => unescaped.push('\n'),
+                Some(c) => unescaped.push(c),
+                None => {}
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+// Splits a list of text values at unescaped commas
+fn split_text_list(text: &str) -> Vec<String> {
+    let mut values = vec![];
+    let mut value = String::new();
+    let mut escaped = false;
+    for c in text.chars() {
+        if escaped {
+            value.push('\\');
+            value.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == ',' {
+            values.push(unescape_text(&value));
+            value.clear();
+        } else {
+            value.push(c);
+        }
+    }
+    values.push(unescape_text(&value));
+    values
+        .into_iter()
+        .map(|v| v.trim().to_owned())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+fn parse_local_date_time(value: &str) -> Result<NaiveDateTime, ParameterError> {
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .map_err(|_| invalid_calendar(format!("invalid date/time '{}'", value)))
+}
+
+// Offsets are given as [+-]HHMM[SS] and returned in seconds
+fn parse_utc_offset(value: &str) -> Result<i64, ParameterError> {
+    let value = value.trim();
+    let invalid = || invalid_calendar(format!("invalid UTC offset '{}'", value));
+    let (sign, digits) = if let Some(digits) = value.strip_prefix('+') {
+        (1, digits)
+    } else if let Some(digits) = value.strip_prefix('-') {
+        (-1, digits)
+    } else {
+        return Err(invalid());
+    };
+    if !(digits.len() == 4 || digits.len() == 6) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let field = |i: usize| {
+        digits
+            .get(i..i + 2)
+            .map_or(0, |d| d.parse::<i64>().unwrap_or(0))
+    };
+    Ok(sign * (field(0) * 3600 + field(2) * 60 + field(4)))
+}
+
+// Time zone rules recur yearly in a single month, e.g.
+// FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU. Starting in this month they
+// are equivalent to a monthly rule with an interval of 12 months.
+// Note: UNTIL is compared with local times.
+fn parse_onset_rule(value: &str, start: NaiveDateTime) -> Result<Recurrence, ParameterError> {
+    let unsupported = || invalid_calendar(format!("unsupported time zone rule '{}'", value));
+    let mut yearly = false;
+    let mut parts = vec![];
+    for part in value.split(';').filter(|p| !p.is_empty()) {
+        let mut key_value = part.splitn(2, '=');
+        let key = key_value.next().unwrap_or_default().to_ascii_uppercase();
+        let value = key_value.next().unwrap_or_default();
+        match key.as_str() {
+            "FREQ" => yearly = value.eq_ignore_ascii_case("YEARLY"),
+            "BYMONTH" => {
+                if value.parse::<u32>().ok() != Some(start.month()) {
+                    return Err(unsupported());
+                }
+            }
+            "INTERVAL" if value != "1" => return Err(unsupported()),
+            "INTERVAL" => {}
+            _ => parts.push(part),
+        }
+    }
+    if !yearly {
+        return Err(unsupported());
+    }
+    let rule = format!("FREQ=MONTHLY;INTERVAL=12;{}", parts.join(";"))
+        .parse()
+        .map_err(|_| unsupported())?;
+    Ok(Recurrence {
+        rule,
+        exdates: vec![],
+    })
+}
+
+// The STANDARD or DAYLIGHT part of a time zone definition
+#[derive(Debug)]
+struct Observance {
+    // The local time of the first onset
+    start: NaiveDateTime,
+    // Seconds east of UTC before and after each onset
+    offset_from: i64,
+    offset_to: i64,
+    recurrence: Option<Recurrence>,
+    rdates: Vec<NaiveDateTime>,
+}
+
+impl Observance {
+    fn parse(props: &HashMap<String, Vec<ContentLine>>) -> Result<Self, ParameterError> {
+        let first = |name: &str| {
+            props
+                .get(name)
+                .and_then(|lines| lines.first())
+                .ok_or_else(|| invalid_calendar(format!("missing {} of time zone", name)))
+        };
+        let start = parse_local_date_time(first("DTSTART")?.value.trim())?;
+        let offset_from = parse_utc_offset(&first("TZOFFSETFROM")?.value)?;
+        let offset_to = parse_utc_offset(&first("TZOFFSETTO")?.value)?;
+        let recurrence = props
+            .get("RRULE")
+            .and_then(|lines| lines.first())
+            .map(|line| parse_onset_rule(line.value.trim(), start))
+            .transpose()?;
+        let rdates = props
+            .get("RDATE")
+            .into_iter()
+            .flatten()
+            .flat_map(|line| line.value.split(','))
+            .map(|value| parse_local_date_time(value.trim()))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            start,
+            offset_from,
+            offset_to,
+            recurrence,
+            rdates,
+        })
+    }
+
+    fn last_onset_until(&self, local: NaiveDateTime) -> Option<NaiveDateTime> {
+        let recurring = self.recurrence.as_ref().and_then(|recurrence| {
+            occurrences(self.start, recurrence)
+                .take_while(|onset| *onset <= local)
+                .last()
+        });
+        std::iter::once(self.start)
+            .chain(recurring)
+            .chain(self.rdates.iter().copied())
+            .filter(|onset| *onset <= local)
+            .max()
+    }
+}
+
+// The time zones that are defined by a calendar (VTIMEZONE)
+#[derive(Debug, Default)]
+struct TimeZones {
+    observances: HashMap<String, Vec<Observance>>,
+    // The time zone of floating local times (X-WR-TIMEZONE)
+    default_tzid: Option<String>,
+}
+
+impl TimeZones {
+    fn local_to_utc(
+        &self,
+        tzid: &str,
+        local: NaiveDateTime,
+    ) -> Result<NaiveDateTime, ParameterError> {
+        if UTC_TZIDS.iter().any(|id| id.eq_ignore_ascii_case(tzid)) {
+            return Ok(local);
+        }
+        let unknown = || ParameterError::UnknownTimeZone(tzid.to_owned());
+        let observances = self.observances.get(tzid).ok_or_else(unknown)?;
+        // The observance with the latest onset applies. Before the
+        // first onset the offset of the earliest observance applies.
+        let offset = observances
+            .iter()
+            .filter_map(|o| {
+                o.last_onset_until(local)
+                    .map(|onset| (onset - Duration::seconds(o.offset_from), o.offset_to))
+            })
+            .max_by_key(|(onset, _)| *onset)
+            .map(|(_, offset)| offset)
+            .or_else(|| {
+                observances
+                    .iter()
+                    .min_by_key(|o| o.start)
+                    .map(|o| o.offset_from)
+            })
+            .ok_or_else(unknown)?;
+        Ok(local - Duration::seconds(offset))
+    }
+}
+
+// Dates without a time are interpreted as UTC. Local times are
+// resolved by their TZID, or the default time zone of the calendar
+// if floating, and rejected if the time zone is not defined.
+fn parse_date_time(line: &ContentLine, time_zones: &TimeZones) -> Result<i64, ParameterError> {
+    let value = line.value.trim();
+    if value.len() == 8 || line.param("VALUE") == Some("DATE") {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d")
+            .map_err(|_| invalid_calendar(format!("invalid date/time '{}'", value)))?;
+        return Ok(date.and_hms(0, 0, 0).timestamp());
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        return Ok(parse_local_date_time(utc)?.timestamp());
+    }
+    let local = parse_local_date_time(value)?;
+    let tzid = line
+        .param("TZID")
+        .or_else(|| time_zones.default_tzid.as_deref())
+        .ok_or_else(|| ParameterError::FloatingDateTime(value.to_owned()))?;
+    Ok(time_zones.local_to_utc(tzid, local)?.timestamp())
+}
+
+fn parse_geo(value: &str) -> Result<(f64, f64), ParameterError> {
+    let invalid = || invalid_calendar(format!("invalid geographic position '{}'", value));
+    let mut split = value.splitn(2, ';');
+    let lat = split
+        .next()
+        .ok_or_else(invalid)?
+        .trim()
+        .parse()
+        .map_err(|_| invalid())?;
+    let lng = split
+        .next()
+        .ok_or_else(invalid)?
+        .trim()
+        .parse()
+        .map_err(|_| invalid())?;
+    Ok((lat, lng))
+}
+
+fn strip_mailto(value: &str) -> &str {
+    let value = value.trim();
+    if value.len() > 7 && value[..7].eq_ignore_ascii_case("mailto:") {
+        &value[7..]
+    } else {
+        value
+    }
+}
+
+fn vevent_to_external_event(
+    props: HashMap<String, Vec<ContentLine>>,
+    time_zones: &TimeZones,
+) -> Result<Option<usecases::ExternalEvent>, ParameterError> {
+    let first = |name: &str| props.get(name).and_then(|lines| lines.first());
+    let text = |name: &str| {
+        first(name)
+            .map(|line| unescape_text(&line.value).trim().to_owned())
+            .filter(|s| !s.is_empty())
+    };
+    let uid = text("UID").ok_or_else(|| invalid_calendar("missing UID of event"))?;
+    if first("STATUS").map(|line| line.value.trim().eq_ignore_ascii_case("CANCELLED")) == Some(true)
+    {
+        // Cancelled events are treated like removed events
+        return Ok(None);
+    }
+    let start = first("DTSTART")
+        .ok_or_else(|| invalid_calendar(format!("missing start of event {}", uid)))
+        .and_then(|line| parse_date_time(line, time_zones))?;
+    let end = first("DTEND")
+        .map(|line| parse_date_time(line, time_zones))
+        .transpose()?;
+    let (lat, lng) = match first("GEO") {
+        Some(line) => {
+            let (lat, lng) = parse_geo(&line.value)?;
+            (Some(lat), Some(lng))
+        }
+        None => (None, None),
+    };
+    let tags = props
+        .get("CATEGORIES")
+        .map(|lines| {
+            lines
+                .iter()
+                .flat_map(|line| split_text_list(&line.value))
+                .collect::<Vec<_>>()
+        })
+        .filter(|tags| !tags.is_empty());
+    let (organizer, email) = match first("ORGANIZER") {
+        Some(line) => (
+            line.param("CN").map(ToOwned::to_owned),
+            Some(strip_mailto(&line.value).to_owned()).filter(|s| !s.is_empty()),
+        ),
+        None => (None, None),
+    };
+    let new_event = usecases::NewEvent {
+        title: text("SUMMARY").unwrap_or_default(),
+        description: text("DESCRIPTION"),
+        start,
+        end,
+        lat,
+        lng,
+        // The free text location is not structured
+        street: text("LOCATION"),
+        email,
+        homepage: text("URL"),
+        tags,
+        organizer,
+        ..Default::default()
+    };
+    Ok(Some(usecases::ExternalEvent { uid, new_event }))
+}
+
+// Cancelled events are omitted. The creator of the events is not
+// available and must be set by the caller.
+pub fn calendar_to_external_events(
+    ics: &str,
+) -> Result<Vec<usecases::ExternalEvent>, ParameterError> {
+    let mut vevents = vec![];
+    let mut time_zones = TimeZones::default();
+    let mut components: Vec<String> = vec![];
+    // Properties of the current event or time zone observance
+    let mut props: HashMap<String, Vec<ContentLine>> = HashMap::new();
+    let mut tzid = None;
+    let mut observances = vec![];
+    for line in unfold_lines(ics) {
+        let line = parse_content_line(&line)?;
+        match line.name.as_str() {
+            "BEGIN" => {
+                components.push(line.value.trim().to_ascii_uppercase());
+            }
+            "END" => {
+                let component = components
+                    .pop()
+                    .ok_or_else(|| invalid_calendar("unexpected end of component"))?;
+                if component != line.value.trim().to_ascii_uppercase() {
+                    return Err(invalid_calendar(format!(
+                        "unexpected end of component {}",
+                        component
+                    )));
+                }
+                let parent = components.last().map(String::as_str);
+                match (component.as_str(), parent) {
+                    ("VEVENT", _) => {
+                        vevents.push(std::mem::take(&mut props));
+                    }
+                    ("STANDARD", Some("VTIMEZONE")) | ("DAYLIGHT", Some("VTIMEZONE")) => {
+                        observances.push(Observance::parse(&std::mem::take(&mut props))?);
+                    }
+                    ("VTIMEZONE", _) => {
+                        let tzid = tzid
+                            .take()
+                            .ok_or_else(|| invalid_calendar("missing TZID of time zone"))?;
+                        time_zones
+                            .observances
+                            .insert(tzid, std::mem::take(&mut observances));
+                    }
+                    _ => {}
+                }
+            }
+            _ => {
+                let component = components.last().map(String::as_str);
+                let parent = components.iter().rev().nth(1).map(String::as_str);
+                match (component, parent) {
+                    (Some("VEVENT"), _)
+                    | (Some("STANDARD"), Some("VTIMEZONE"))
+                    | (Some("DAYLIGHT"), Some("VTIMEZONE")) => {
+                        props.entry(line.name.clone()).or_default().push(line);
+                    }
+                    (Some("VTIMEZONE"), _) if line.name == "TZID" => {
+                        tzid = Some(line.value.trim().to_owned());
+                    }
+                    (Some("VCALENDAR"), _) if line.name == "X-WR-TIMEZONE" => {
+                        time_zones.default_tzid = Some(line.value.trim().to_owned());
+                    }
+                    // Ignore properties of nested components, e.g. VALARM
+                    _ => {}
+                }
+            }
+        }
+    }
+    if !components.is_empty() {
+        return Err(invalid_calendar("unexpected end of calendar"));
+    }
+    // Time zones might be defined after the events that refer to them
+    let mut events = vec![];
+    for props in vevents {
+        if let Some(event) = vevent_to_external_event(props, &time_zones)? {
+            events.push(event);
+        }
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_special_characters() {
+        assert_eq!("a\\, b\\; c\\\\d\\ne", escape_text("a, b; c\\d\r\ne"));
+    }
+
+    #[test]
+    fn fold_long_lines() {
+        let mut cal = String::new();
+        let value = "ä".repeat(100);
+        write_line(&mut cal, "SUMMARY", &value);
+        let lines: Vec<_> = cal.split(LINE_BREAK).collect();
+        assert_eq!(4, lines.len());
+        assert!(lines[3].is_empty());
+        assert!(lines.iter().all(|l| l.len() <= MAX_LINE_LEN));
+        assert!(lines[1].starts_with(' '));
+        assert!(lines[2].starts_with(' '));
+        let unfolded: String = lines[..3]
+            .iter()
+            .enumerate()
+            .map(|(i, l)| if i > 0 { &l[1..] } else { *l })
+            .collect();
+        assert_eq!(format!("SUMMARY:{}", value), unfolded);
+    }
+
+    #[test]
+    fn event_with_location_and_contact() {
+        let event = Event {
+            id: "e1".into(),
+            title: "Title".into(),
+            description: Some("Line 1\nLine 2".into()),
+            start: NaiveDateTime::from_timestamp(1_600_000_000, 0),
+            end: Some(NaiveDateTime::from_timestamp(1_600_003_600, 0)),
+            location: Some(Location {
+                pos: MapPoint::from_lat_lng_deg(48.5, 9.25),
+                address: Some(Address {
+                    street: Some("Street 1".into()),
+                    zip: Some("12345".into()),
+                    city: Some("City".into()),
+                    ..Default::default()
+                }),
+            }),
+            contact: Some(Contact {
+                name: Some("Name".into()),
+                email: Some("info@example.com".into()),
+                phone: None,
+            }),
+            tags: vec!["foo".into(), "bar".into()],
+            homepage: Some("https://example.com/".parse().unwrap()),
+            created_by: None,
+            registration: None,
+            archived: None,
+            image_url: None,
+            image_link_url: None,
+            image_id: None,
+            recurrence: None,
+            series_id: None,
+        };
+        let cal = events_to_calendar(&[event], NaiveDateTime::from_timestamp(1_500_000_000, 0));
+        assert!(cal.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(cal.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert!(cal.contains("\r\nUID:e1@openfairdb.org\r\n"));
+        assert!(cal.contains("\r\nDTSTAMP:20170714T024000Z\r\n"));
+        assert!(cal.contains("\r\nDTSTART:20200913T122640Z\r\n"));
+        assert!(cal.contains("\r\nDTEND:20200913T132640Z\r\n"));
+        assert!(cal.contains("\r\nDESCRIPTION:Line 1\\nLine 2\r\n"));
+        assert!(cal.contains("\r\nLOCATION:Street 1\\, 12345 City\r\n"));
+        assert!(cal.contains("\r\nGEO:48.500000;9.250000\r\n"));
+        assert!(cal.contains("\r\nCONTACT:Name\\, info@example.com\r\n"));
+        assert!(cal.contains("\r\nURL:https://example.com/\r\n"));
+        assert!(cal.contains("\r\nCATEGORIES:foo,bar\r\n"));
+    }
+
+    const VTIMEZONE_BERLIN: &str = "BEGIN:VTIMEZONE\r\n\
+                                    TZID:Europe/Berlin\r\n\
+                                    BEGIN:DAYLIGHT\r\n\
+                                    TZOFFSETFROM:+0100\r\n\
+                                    TZOFFSETTO:+0200\r\n\
+                                    TZNAME:CEST\r\n\
+                                    DTSTART:19700329T020000\r\n\
+                                    RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU\r\n\
+                                    END:DAYLIGHT\r\n\
+                                    BEGIN:STANDARD\r\n\
+                                    TZOFFSETFROM:+0200\r\n\
+                                    TZOFFSETTO:+0100\r\n\
+                                    TZNAME:CET\r\n\
+                                    DTSTART:19701025T030000\r\n\
+                                    RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU\r\n\
+                                    END:STANDARD\r\n\
+                                    END:VTIMEZONE\r\n";
+
+    fn calendar(header: &str, vevent: &str) -> String {
+        format!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}BEGIN:VEVENT\r\nUID:1\r\n{}END:VEVENT\r\nEND:VCALENDAR\r\n",
+            header, vevent
+        )
+    }
+
+    #[test]
+    fn parse_calendar() {
+        let ics = format!(
+            "BEGIN:VCALENDAR\r\n\
+                   VERSION:2.0\r\n\
+                   {}\
+                   BEGIN:VEVENT\r\n\
+                   UID:1@example.com\r\n\
+                   DTSTART:20200913T122640Z\r\n\
+                   DTEND;TZID=Europe/Berlin:20200913T152640\r\n\
+                   SUMMARY:Title\\, with comma\r\n\
+                   DESCRIPTION:Line 1\\nLine 2 that is folded \r\n \
+                   into the next line\r\n\
+                   LOCATION:Street 1\\, 12345 City\r\n\
+                   GEO:48.5;9.25\r\n\
+                   URL:https://example.com/\r\n\
+                   CATEGORIES:foo,bar\r\n\
+                   CATEGORIES:baz\r\n\
+                   ORGANIZER;CN=\"Name: Org\":mailto:info@example.com\r\n\
+                   BEGIN:VALARM\r\n\
+                   DESCRIPTION:Alarm\r\n\
+                   END:VALARM\r\n\
+                   END:VEVENT\r\n\
+                   BEGIN:VEVENT\r\n\
+                   UID:2@example.com\r\n\
+                   DTSTART;VALUE=DATE:20200914\r\n\
+                   SUMMARY:Cancelled\r\n\
+                   STATUS:CANCELLED\r\n\
+                   END:VEVENT\r\n\
+                   END:VCALENDAR\r\n",
+            VTIMEZONE_BERLIN
+        );
+        let events = calendar_to_external_events(&ics).unwrap();
+        assert_eq!(1, events.len());
+        let usecases::ExternalEvent { uid, new_event } = &events[0];
+        assert_eq!("1@example.com", uid);
+        assert_eq!("Title, with comma", new_event.title);
+        assert_eq!(
+            Some("Line 1\nLine 2 that is folded into the next line"),
+            new_event.description.as_deref()
+        );
+        assert_eq!(1_600_000_000, new_event.start);
+        assert_eq!(Some(1_600_003_600), new_event.end);
+        assert_eq!(Some("Street 1, 12345 City"), new_event.street.as_deref());
+        assert_eq!(Some(48.5), new_event.lat);
+        assert_eq!(Some(9.25), new_event.lng);
+        assert_eq!(Some("https://example.com/"), new_event.homepage.as_deref());
+        assert_eq!(
+            Some(vec!["foo".to_string(), "bar".into(), "baz".into()]),
+            new_event.tags
+        );
+        assert_eq!(Some("Name: Org"), new_event.organizer.as_deref());
+        assert_eq!(Some("info@example.com"), new_event.email.as_deref());
+        assert!(new_event.created_by.is_none());
+    }
+
+    #[test]
+    fn resolve_local_times_with_time_zone_definitions() {
+        let start = |header: &str, dtstart: &str| {
+            calendar_to_external_events(&calendar(header, &format!("DTSTART{}\r\n", dtstart)))
+                .map(|events| events[0].new_event.start)
+        };
+        let utc = |s: &str| {
+            NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M")
+                .unwrap()
+                .timestamp()
+        };
+        // Summer time (CEST)
+        assert_eq!(
+            utc("2021-07-01 08:00"),
+            start(VTIMEZONE_BERLIN, ";TZID=Europe/Berlin:20210701T100000").unwrap()
+        );
+        // Winter time (CET)
+        assert_eq!(
+            utc("2021-12-24 17:00"),
+            start(VTIMEZONE_BERLIN, ";TZID=Europe/Berlin:20211224T180000").unwrap()
+        );
+        // Right before and after the change to summer time
+        assert_eq!(
+            utc("2021-03-28 00:30"),
+            start(VTIMEZONE_BERLIN, ";TZID=Europe/Berlin:20210328T013000").unwrap()
+        );
+        assert_eq!(
+            utc("2021-03-28 01:30"),
+            start(VTIMEZONE_BERLIN, ";TZID=Europe/Berlin:20210328T033000").unwrap()
+        );
+        // Before the first onset of the definition
+        assert_eq!(
+            utc("1960-07-01 09:00"),
+            start(VTIMEZONE_BERLIN, ";TZID=Europe/Berlin:19600701T100000").unwrap()
+        );
+        // Time zones might be defined after the events
+        let ics = format!(
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:1\r\n\
+             DTSTART;TZID=Europe/Berlin:20210701T100000\r\nEND:VEVENT\r\n{}END:VCALENDAR\r\n",
+            VTIMEZONE_BERLIN
+        );
+        assert_eq!(
+            utc("2021-07-01 08:00"),
+            calendar_to_external_events(&ics).unwrap()[0]
+                .new_event
+                .start
+        );
+        // UTC doesn't need to be defined
+        assert_eq!(
+            utc("2021-07-01 10:00"),
+            start("", ";TZID=UTC:20210701T100000").unwrap()
+        );
+    }
+
+    #[test]
+    fn resolve_floating_local_times_with_the_default_time_zone() {
+        let header = format!("X-WR-TIMEZONE:Europe/Berlin\r\n{}", VTIMEZONE_BERLIN);
+        let events =
+            calendar_to_external_events(&calendar(&header, "DTSTART:20210701T100000\r\n")).unwrap();
+        assert_eq!(1_625_126_400, events[0].new_event.start);
+    }
+
+    #[test]
+    fn reject_unresolvable_local_times() {
+        match calendar_to_external_events(&calendar("", "DTSTART:20210701T100000\r\n")) {
+            Err(ParameterError::FloatingDateTime(value)) => assert_eq!("20210701T100000", value),
+            res => panic!("Unexpected result: {:?}", res.map(|_| ())),
+        }
+        match calendar_to_external_events(&calendar(
+            "",
+            "DTSTART;TZID=Europe/Berlin:20210701T100000\r\n",
+        )) {
+            Err(ParameterError::UnknownTimeZone(tzid)) => assert_eq!("Europe/Berlin", tzid),
+            res => panic!("Unexpected result: {:?}", res.map(|_| ())),
+        }
+        // The default time zone must be defined, too
+        match calendar_to_external_events(&calendar(
+            "X-WR-TIMEZONE:Europe/Berlin\r\n",
+            "DTSTART:20210701T100000\r\n",
+        )) {
+            Err(ParameterError::UnknownTimeZone(tzid)) => assert_eq!("Europe/Berlin", tzid),
+            res => panic!("Unexpected result: {:?}", res.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn reject_invalid_calendars() {
+        assert!(calendar_to_external_events("BEGIN:VCALENDAR\r\n").is_err());
+        assert!(calendar_to_external_events(
+            "BEGIN:VEVENT\r\nDTSTART:20200913T122640Z\r\nEND:VEVENT\r\n"
+        )
+        .is_err());
+        assert!(calendar_to_external_events(
+            "BEGIN:VEVENT\r\nUID:1\r\nDTSTART:2020-09-13\r\nEND:VEVENT\r\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn export_and_import_roundtrip() {
+        let event = Event {
+            id: "e1".into(),
+            title: "Title; with semicolon".into(),
+            description: Some("Line 1\nLine 2".into()),
+            start: NaiveDateTime::from_timestamp(1_600_000_000, 0),
+            end: None,
+            location: None,
+            contact: None,
+            tags: vec!["foo".into(), "with,comma".into()],
+            homepage: None,
+            created_by: None,
+            registration: None,
+            archived: None,
+            image_url: None,
+            image_link_url: None,
+            image_id: None,
+            recurrence: None,
+            series_id: None,
+        };
+        let cal = events_to_calendar(&[event], NaiveDateTime::from_timestamp(0, 0));
+        let events = calendar_to_external_events(&cal).unwrap();
+        assert_eq!(1, events.len());
+        assert_eq!("e1@openfairdb.org", events[0].uid);
+        assert_eq!("Title; with semicolon", events[0].new_event.title);
+        assert_eq!(
+            Some("Line 1\nLine 2"),
+            events[0].new_event.description.as_deref()
+        );
+        assert_eq!(
+            Some(vec!["foo".to_string(), "with,comma".into()]),
+            events[0].new_event.tags
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Processing of uploaded images
+
+use crate::core::{
+    entities::{ImageFormat, ThumbnailSize},
+    usecases::NewImage,
+};
+use ::image::{
+    imageops::FilterType, io::Reader, DynamicImage, GenericImageView, ImageFormat as InputFormat,
+    ImageOutputFormat,
+};
+use std::io::{self, Cursor};
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+/// The maximum size of uploaded files in bytes
+pub const MAX_UPLOAD_SIZE: u64 = 10 * 1024 * 1024;
+
+// Protects against decompression bombs
+const MAX_INPUT_PIXELS: u64 = 50_000_000;
+
+// Larger images are scaled down before they are stored
+const MAX_STORED_DIMENSION: u32 = 2048;
+
+const JPEG_QUALITY: u8 = 85;
+
+#[derive(Debug, Error)]
+pub enum ImageError {
+    #[error("Unsupported image format")]
+    UnsupportedFormat,
+    #[error("Too many pixels: {0}x{1}")]
+    TooManyPixels(u32, u32),
+    #[error(transparent)]
+    Image(#[from] ::image::ImageError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+fn reader(data: &[u8]) -> Result<Reader<Cursor<&[u8]>>, ImageError> {
+    let reader = Reader::new(Cursor::new(data)).with_guessed_format()?;
+    // The format is detected by the content and not by any
+    // file name or content type that has been provided
+    match reader.format() {
+        Some(InputFormat::Jpeg)
+        | Some(InputFormat::Png)
+        | Some(InputFormat::Gif)
+        | Some(InputFormat::WebP) => Ok(reader),
+        _ => Err(ImageError::UnsupportedFormat),
+    }
+}
+
+fn encode(img: &DynamicImage, format: ImageFormat) -> Result<Vec<u8>, ImageError> {
+    let output_format = match format {
+        ImageFormat::Jpeg => ImageOutputFormat::Jpeg(JPEG_QUALITY),
+        ImageFormat::Png => ImageOutputFormat::Png,
+    };
+    let mut data = vec![];
+    img.write_to(&mut data, output_format)?;
+    Ok(data)
+}
+
+/// Decodes an uploaded image and re-encodes it together with
+/// all thumbnails.
+///
+/// Re-encoding drops all metadata like EXIF tags that might
+/// reveal the location or the camera of the photographer.
+pub fn process_upload(data: &[u8]) -> Result<NewImage, ImageError> {
+    let (width, height) = reader(data)?.into_dimensions()?;
+    if u64::from(width) * u64::from(height) > MAX_INPUT_PIXELS {
+        return Err(ImageError::TooManyPixels(width, height));
+    }
+    let img = reader(data)?.decode()?;
+    // Only images with transparency are stored as PNG
+    let (format, mut img) = if img.color().has_alpha() {
+        (ImageFormat::Png, DynamicImage::ImageRgba8(img.to_rgba8()))
+    } else {
+        (ImageFormat::Jpeg, DynamicImage::ImageRgb8(img.to_rgb8()))
+    };
+    if img.width() > MAX_STORED_DIMENSION || img.height() > MAX_STORED_DIMENSION {
+        img = img.resize(
+            MAX_STORED_DIMENSION,
+            MAX_STORED_DIMENSION,
+            FilterType::Lanczos3,
+        );
+    }
+    let thumbnails = ThumbnailSize::iter()
+        .map(|size| {
+            let max = size.max_dimension();
+            let data = if img.width() > max || img.height() > max {
+                encode(&img.thumbnail(max, max), format)?
+            } else {
+                encode(&img, format)?
+            };
+            Ok((size, data))
+        })
+        .collect::<Result<_, ImageError>>()?;
+    Ok(NewImage {
+        format,
+        width: img.width(),
+        height: img.height(),
+        data: encode(&img, format)?,
+        thumbnails,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::image::{Rgb, RgbImage, Rgba, RgbaImage};
+
+    fn jpeg_with_exif(width: u32, height: u32) -> Vec<u8> {
+        let img = DynamicImage::ImageRgb8(RgbImage::from_pixel(width, height, Rgb([200, 10, 10])));
+        let jpeg = encode(&img, ImageFormat::Jpeg).unwrap();
+        let exif = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\0\0\0\0\0";
+        let len = (exif.len() + 2) as u16;
+        let mut data = jpeg[..2].to_vec();
+        data.extend_from_slice(&[0xFF, 0xE1]);
+        data.extend_from_slice(&len.to_be_bytes());
+        data.extend_from_slice(exif);
+        data.extend_from_slice(&jpeg[2..]);
+        data
+    }
+
+    fn contains(data: &[u8], pattern: &[u8]) -> bool {
+        data.windows(pattern.len()).any(|w| w == pattern)
+    }
+
+    #[test]
+    fn strip_metadata_and_create_thumbnails() {
+        let data = jpeg_with_exif(3000, 1000);
+        assert!(contains(&data, b"Exif"));
+        let img = process_upload(&data).unwrap();
+        assert_eq!(ImageFormat::Jpeg, img.format);
+        assert_eq!((2048, 682), (img.width, img.height));
+        assert!(!contains(&img.data, b"Exif"));
+        assert_eq!(3, img.thumbnails.len());
+        for (size, data) in img.thumbnails {
+            let (w, h) = ::image::load_from_memory(&data).unwrap().dimensions();
+            assert_eq!(size.max_dimension(), w);
+            assert!(h < w);
+            assert!(!contains(&data, b"Exif"));
+        }
+    }
+
+    #[test]
+    fn keep_small_images_with_transparency() {
+        let img = DynamicImage::ImageRgba8(RgbaImage::from_pixel(200, 100, Rgba([0, 0, 0, 0])));
+        let png = encode(&img, ImageFormat::Png).unwrap();
+        let img = process_upload(&png).unwrap();
+        assert_eq!(ImageFormat::Png, img.format);
+        assert_eq!((200, 100), (img.width, img.height));
+        let thumbnail_dimensions: Vec<_> = img
+            .thumbnails
+            .iter()
+            .map(|(_, data)| ::image::load_from_memory(data).unwrap().dimensions())
+            .collect();
+        assert_eq!(
+            vec![(160, 80), (200, 100), (200, 100)],
+            thumbnail_dimensions
+        );
+    }
+
+    #[test]
+    fn reject_unsupported_data() {
+        assert!(matches!(
+            process_upload(b"<svg></svg>"),
+            Err(ImageError::UnsupportedFormat)
+        ));
+        assert!(process_upload(&jpeg_with_exif(10, 10)[..100]).is_err());
+    }
+}
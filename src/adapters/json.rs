@@ -1,4 +1,11 @@
-use crate::core::{db::IndexedPlace, entities as e, usecases};
+use crate::core::{
+    db::{self, IndexedPlace},
+    entities as e,
+    error::ParameterError,
+    usecases,
+};
+use ofdb_core::cluster;
+use std::convert::TryFrom;
 
 pub use ofdb_boundary::*;
 
@@ -9,6 +16,186 @@ impl From<Credentials> for usecases::Login {
     }
 }
 
+impl From<usecases::ImportedEvents> for EventImportResult {
+    fn from(from: usecases::ImportedEvents) -> Self {
+        let usecases::ImportedEvents {
+            created,
+            updated,
+            archived,
+            failed,
+        } = from;
+        let into_strings =
+            |ids: Vec<e::Id>| -> Vec<String> { ids.into_iter().map(String::from).collect() };
+        Self {
+            created: into_strings(created),
+            updated: into_strings(updated),
+            archived: into_strings(archived),
+            failed: failed
+                .into_iter()
+                .map(|(uid, err)| FailedEventImport {
+                    uid,
+                    error: err.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+pub fn place_cluster(from: cluster::PlaceCluster) -> PlaceCluster {
+    let cluster::PlaceCluster {
+        centroid,
+        count,
+        category,
+        bbox,
+    } = from;
+    PlaceCluster {
+        centroid: centroid.into(),
+        count: count as u64,
+        category: category.map(Into::into),
+        bbox: bbox.into(),
+    }
+}
+
+impl TryFrom<NewWebhook> for usecases::NewWebhook {
+    type Error = ParameterError;
+
+    fn try_from(from: NewWebhook) -> Result<Self, Self::Error> {
+        let NewWebhook {
+            url,
+            secret,
+            event_types,
+            bbox,
+            tags,
+        } = from;
+        let bbox = bbox
+            .map(|MapBbox { sw, ne }| {
+                let sw = e::MapPoint::try_from_lat_lng_deg(sw.lat, sw.lng)?;
+                let ne = e::MapPoint::try_from_lat_lng_deg(ne.lat, ne.lng)?;
+                Ok(e::MapBbox::new(sw, ne))
+            })
+            .transpose()
+            .map_err(|_: e::CoordRangeError| ParameterError::Bbox)?;
+        Ok(Self {
+            url,
+            secret,
+            event_types,
+            bbox,
+            tags,
+        })
+    }
+}
+
+impl TryFrom<NewBboxSubscription> for usecases::NewBboxSubscription {
+    type Error = ParameterError;
+
+    fn try_from(from: NewBboxSubscription) -> Result<Self, Self::Error> {
+        let NewBboxSubscription {
+            south_west_lat,
+            south_west_lng,
+            north_east_lat,
+            north_east_lng,
+            locale,
+            tags,
+            categories,
+            events,
+            delivery,
+        } = from;
+        let sw = e::MapPoint::try_from_lat_lng_deg(south_west_lat, south_west_lng)
+            .map_err(|_| ParameterError::Bbox)?;
+        let ne = e::MapPoint::try_from_lat_lng_deg(north_east_lat, north_east_lng)
+            .map_err(|_| ParameterError::Bbox)?;
+        let locale = locale
+            .map(|l| l.parse().map_err(|_| ParameterError::InvalidLocale(l)))
+            .transpose()?;
+        Ok(Self {
+            bbox: e::MapBbox::new(sw, ne),
+            locale,
+            tags,
+            categories,
+            events,
+            delivery,
+        })
+    }
+}
+
+impl From<PlaceMergeField> for usecases::PlaceMergeField {
+    fn from(from: PlaceMergeField) -> Self {
+        use PlaceMergeField as F;
+        match from {
+            F::Title => Self::Title,
+            F::Description => Self::Description,
+            F::Location => Self::Location,
+            F::Contact => Self::Contact,
+            F::OpeningHours => Self::OpeningHours,
+            F::FoundedOn => Self::FoundedOn,
+            F::Links => Self::Links,
+            F::Tags => Self::Tags,
+        }
+    }
+}
+
+impl From<usecases::PlaceDiffField> for PlaceDiffField {
+    fn from(from: usecases::PlaceDiffField) -> Self {
+        use usecases::PlaceDiffField as F;
+        match from {
+            F::Title => Self::Title,
+            F::Description => Self::Description,
+            F::Street => Self::Street,
+            F::Zip => Self::Zip,
+            F::City => Self::City,
+            F::Country => Self::Country,
+            F::State => Self::State,
+            F::ContactName => Self::ContactName,
+            F::ContactEmail => Self::ContactEmail,
+            F::ContactPhone => Self::ContactPhone,
+            F::OpeningHours => Self::OpeningHours,
+            F::FoundedOn => Self::FoundedOn,
+            F::Homepage => Self::Homepage,
+            F::Image => Self::Image,
+            F::ImageHref => Self::ImageHref,
+            F::ImageId => Self::ImageId,
+        }
+    }
+}
+
+impl From<usecases::PlaceDiff> for PlaceDiff {
+    fn from(from: usecases::PlaceDiff) -> Self {
+        let usecases::PlaceDiff {
+            id,
+            from,
+            to,
+            changed_fields,
+            added_tags,
+            removed_tags,
+            added_custom_links,
+            removed_custom_links,
+            moved,
+        } = from;
+        Self {
+            id: id.into(),
+            from: from.into(),
+            to: to.into(),
+            changed_fields: changed_fields
+                .into_iter()
+                .map(|c| ChangedPlaceField {
+                    field: c.field.into(),
+                    from: c.from,
+                    to: c.to,
+                })
+                .collect(),
+            added_tags,
+            removed_tags,
+            added_custom_links: added_custom_links.into_iter().map(Into::into).collect(),
+            removed_custom_links: removed_custom_links.into_iter().map(Into::into).collect(),
+            moved: moved.map(|m| MovedPlace {
+                from: m.from.into(),
+                to: m.to.into(),
+                distance_meters: m.distance.map(e::Distance::to_meters),
+            }),
+        }
+    }
+}
+
 impl From<IndexedPlace> for PlaceSearchResult {
     fn from(from: IndexedPlace) -> Self {
         let IndexedPlace {
@@ -99,6 +286,7 @@ impl From<NewPlace> for usecases::NewPlace {
             license,
             image_url,
             image_link_url,
+            image_id,
             links,
         } = p;
         usecases::NewPlace {
@@ -122,6 +310,7 @@ impl From<NewPlace> for usecases::NewPlace {
             license,
             image_url,
             image_link_url,
+            image_id,
             custom_links: links.into_iter().map(Into::into).collect(),
         }
     }
@@ -150,6 +339,7 @@ impl From<UpdatePlace> for usecases::UpdatePlace {
             tags,
             image_url,
             image_link_url,
+            image_id,
             links,
         } = p;
         usecases::UpdatePlace {
@@ -173,6 +363,7 @@ impl From<UpdatePlace> for usecases::UpdatePlace {
             tags,
             image_url,
             image_link_url,
+            image_id,
             custom_links: links.into_iter().map(Into::into).collect(),
         }
     }
@@ -213,14 +404,15 @@ pub fn entry_from_place_with_ratings(place: e::Place, ratings: Vec<e::Rating>) -
         phone: telephone,
     } = contact.unwrap_or_default();
 
-    let (homepage_url, image_url, image_link_url, custom_links) = links
+    let (homepage_url, image_url, image_link_url, image_id, custom_links) = links
         .map(
             |e::Links {
                  homepage,
                  image,
                  image_href,
+                 image_id,
                  custom,
-             }| (homepage, image, image_href, custom),
+             }| (homepage, image, image_href, image_id, custom),
         )
         .unwrap_or_default();
 
@@ -251,6 +443,102 @@ pub fn entry_from_place_with_ratings(place: e::Place, ratings: Vec<e::Rating>) -
         license: Some(license),
         image_url: image_url.map(e::Url::into_string),
         image_link_url: image_link_url.map(e::Url::into_string),
+        image_id: image_id.map(Into::into),
         custom_links: custom_links.into_iter().map(Into::into).collect(),
     }
 }
+
+impl From<NewOrganization> for usecases::NewOrganization {
+    fn from(from: NewOrganization) -> Self {
+        let NewOrganization {
+            name,
+            api_token,
+            moderated_tags,
+        } = from;
+        Self {
+            name,
+            api_token,
+            moderated_tags: moderated_tags.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<UpdateOrganization> for usecases::UpdateOrganization {
+    fn from(from: UpdateOrganization) -> Self {
+        let UpdateOrganization { name } = from;
+        Self { name }
+    }
+}
+
+impl From<NewApiToken> for usecases::NewApiToken {
+    fn from(from: NewApiToken) -> Self {
+        let NewApiToken {
+            name,
+            scopes,
+            expires_at,
+        } = from;
+        Self {
+            name,
+            scopes,
+            expires_at: expires_at.map(e::TimestampMs::from_inner),
+        }
+    }
+}
+
+impl From<usecases::OwnedEntries> for OrganizationEntries {
+    fn from(from: usecases::OwnedEntries) -> Self {
+        let usecases::OwnedEntries {
+            place_ids,
+            event_ids,
+        } = from;
+        Self {
+            places: place_ids.into_iter().map(Into::into).collect(),
+            events: event_ids.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<db::UserActivity> for UserActivity {
+    fn from(from: db::UserActivity) -> Self {
+        use db::UserActivityKind::*;
+        let db::UserActivity {
+            kind,
+            id,
+            revision,
+            at,
+        } = from;
+        let kind = match kind {
+            PlaceRevision => "place-revision",
+            PlaceReview => "place-review",
+            PlaceRating => "place-rating",
+            PlaceRatingArchived => "place-rating-archived",
+            PlaceRatingComment => "place-rating-comment",
+            PlaceRatingCommentArchived => "place-rating-comment-archived",
+            Event => "event",
+            Image => "image",
+        };
+        Self {
+            kind: kind.into(),
+            id: id.into(),
+            revision: revision.map(Into::into),
+            at: at.map(e::TimestampMs::into_inner),
+        }
+    }
+}
+
+impl From<usecases::UserDataExport> for UserDataExport {
+    fn from(from: usecases::UserDataExport) -> Self {
+        let usecases::UserDataExport {
+            user,
+            bbox_subscriptions,
+            user_token_expires_at,
+            activities,
+        } = from;
+        Self {
+            user: user.into(),
+            bbox_subscriptions: bbox_subscriptions.into_iter().map(Into::into).collect(),
+            user_token_expires_at: user_token_expires_at.map(e::Timestamp::into_seconds),
+            activities: activities.into_iter().map(Into::into).collect(),
+        }
+    }
+}
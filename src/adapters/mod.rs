@@ -1,2 +1,7 @@
+pub mod atom;
 pub mod csv;
+pub mod geojson;
+pub mod ical;
+pub mod image;
 pub mod json;
+pub mod mvt;
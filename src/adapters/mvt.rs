@@ -0,0 +1,360 @@
+//! Encoding of places as Mapbox Vector Tiles (MVT 2.1)
+//!
+//! <https://github.com/mapbox/vector-tile-spec/tree/master/2.1>
+
+use crate::core::{db::IndexedPlace, entities::*};
+use std::{collections::HashMap, f64::consts::PI, fmt, str::FromStr};
+
+pub const LAYER_NAME: &str = "places";
+
+// Number of units along each axis of a tile
+const EXTENT: u32 = 4096;
+
+// Features outside of a tile are clipped by clients
+const BUFFER: f64 = 64.0;
+
+pub const MAX_ZOOM: u8 = 22;
+
+// Web Mercator is only defined up to ~85.0511 degrees latitude
+const MAX_LATITUDE: f64 = 85.051_128_779_806_59;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileCoord {
+    pub z: u8,
+    pub x: u32,
+    pub y: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidTileCoord;
+
+impl fmt::Display for InvalidTileCoord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Invalid tile coordinates")
+    }
+}
+
+impl std::error::Error for InvalidTileCoord {}
+
+impl TileCoord {
+    pub fn new(z: u8, x: u32, y: u32) -> Result<Self, InvalidTileCoord> {
+        if z > MAX_ZOOM || u64::from(x) >= 1 << z || u64::from(y) >= 1 << z {
+            return Err(InvalidTileCoord);
+        }
+        Ok(Self { z, x, y })
+    }
+
+    fn tile_count(self) -> f64 {
+        f64::from(1u32 << self.z)
+    }
+
+    // The bounding box of the tile including the buffer
+    pub fn bbox(self) -> MapBbox {
+        let n = self.tile_count();
+        let buffer = BUFFER / f64::from(EXTENT);
+        let x_min = f64::from(self.x) - buffer;
+        let x_max = f64::from(self.x + 1) + buffer;
+        let y_min = f64::from(self.y) - buffer;
+        let y_max = f64::from(self.y + 1) + buffer;
+        let lng = |x: f64| (x / n * 360.0 - 180.0).max(-180.0).min(180.0);
+        let lat = |y: f64| {
+            (PI * (1.0 - 2.0 * y / n))
+                .sinh()
+                .atan()
+                .to_degrees()
+                .max(-MAX_LATITUDE)
+                .min(MAX_LATITUDE)
+        };
+        MapBbox::new(
+            MapPoint::from_lat_lng_deg(lat(y_max), lng(x_min)),
+            MapPoint::from_lat_lng_deg(lat(y_min), lng(x_max)),
+        )
+    }
+
+    // Projects a position onto the integer grid of the tile
+    fn project(self, pos: MapPoint) -> (i32, i32) {
+        let n = self.tile_count();
+        let lat = pos.lat().to_deg().max(-MAX_LATITUDE).min(MAX_LATITUDE);
+        let lng = pos.lng().to_deg();
+        let x = (lng + 180.0) / 360.0 * n;
+        let y = (1.0 - lat.to_radians().tan().asinh() / PI) / 2.0 * n;
+        let extent = f64::from(EXTENT);
+        (
+            ((x - f64::from(self.x)) * extent).round() as i32,
+            ((y - f64::from(self.y)) * extent).round() as i32,
+        )
+    }
+}
+
+// Syntax: <z>/<x>/<y>
+impl FromStr for TileCoord {
+    type Err = InvalidTileCoord;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut split = s.split('/');
+        let mut next = || split.next().ok_or(InvalidTileCoord);
+        let z = next()?.parse().map_err(|_| InvalidTileCoord)?;
+        let x = next()?.parse().map_err(|_| InvalidTileCoord)?;
+        let y = next()?.parse().map_err(|_| InvalidTileCoord)?;
+        if split.next().is_some() {
+            return Err(InvalidTileCoord);
+        }
+        Self::new(z, x, y)
+    }
+}
+
+// Minimal Protocol Buffers encoding
+struct Encoder(Vec<u8>);
+
+const WIRE_TYPE_VARINT: u32 = 0;
+const WIRE_TYPE_64BIT: u32 = 1;
+const WIRE_TYPE_LEN: u32 = 2;
+
+impl Encoder {
+    fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+
+    fn key(&mut self, field: u32, wire_type: u32) {
+        self.varint(u64::from(field << 3 | wire_type));
+    }
+
+    fn uint(&mut self, field: u32, value: u64) {
+        self.key(field, WIRE_TYPE_VARINT);
+        self.varint(value);
+    }
+
+    fn double(&mut self, field: u32, value: f64) {
+        self.key(field, WIRE_TYPE_64BIT);
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn bytes(&mut self, field: u32, bytes: &[u8]) {
+        self.key(field, WIRE_TYPE_LEN);
+        self.varint(bytes.len() as u64);
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn string(&mut self, field: u32, s: &str) {
+        self.bytes(field, s.as_bytes());
+    }
+
+    fn packed(&mut self, field: u32, values: &[u32]) {
+        let mut packed = Encoder::new();
+        for value in values {
+            packed.varint(u64::from(*value));
+        }
+        self.bytes(field, &packed.0);
+    }
+}
+
+const CMD_MOVE_TO: u32 = 1;
+
+fn command(id: u32, count: u32) -> u32 {
+    (id & 0x7) | (count << 3)
+}
+
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+#[derive(Clone, PartialEq)]
+enum Value {
+    String(String),
+    Double(f64),
+}
+
+impl Value {
+    fn encode(&self) -> Vec<u8> {
+        let mut enc = Encoder::new();
+        match self {
+            Value::String(s) => enc.string(1, s),
+            Value::Double(d) => enc.double(3, *d),
+        }
+        enc.0
+    }
+}
+
+// Keys and values are shared by all features of a layer
+#[derive(Default)]
+struct LayerBuilder {
+    keys: Vec<&'static str>,
+    values: Vec<Value>,
+    string_values: HashMap<String, u32>,
+    features: Vec<Vec<u8>>,
+}
+
+impl LayerBuilder {
+    fn key_index(&mut self, key: &'static str) -> u32 {
+        if let Some(index) = self.keys.iter().position(|k| *k == key) {
+            return index as u32;
+        }
+        self.keys.push(key);
+        (self.keys.len() - 1) as u32
+    }
+
+    fn value_index(&mut self, value: Value) -> u32 {
+        if let Value::String(s) = &value {
+            if let Some(index) = self.string_values.get(s) {
+                return *index;
+            }
+            self.string_values
+                .insert(s.clone(), self.values.len() as u32);
+        }
+        self.values.push(value);
+        (self.values.len() - 1) as u32
+    }
+
+    fn add_point(&mut self, (x, y): (i32, i32), properties: Vec<(&'static str, Value)>) {
+        let mut tags = Vec::with_capacity(properties.len() * 2);
+        for (key, value) in properties {
+            tags.push(self.key_index(key));
+            tags.push(self.value_index(value));
+        }
+        let geometry = [command(CMD_MOVE_TO, 1), zigzag(x), zigzag(y)];
+        let mut feature = Encoder::new();
+        feature.packed(2, &tags);
+        feature.uint(3, 1); // POINT
+        feature.packed(4, &geometry);
+        self.features.push(feature.0);
+    }
+
+    fn encode(self, name: &str) -> Vec<u8> {
+        let mut layer = Encoder::new();
+        layer.uint(15, 2); // version
+        layer.string(1, name);
+        for feature in &self.features {
+            layer.bytes(2, feature);
+        }
+        for key in &self.keys {
+            layer.string(3, key);
+        }
+        for value in &self.values {
+            layer.bytes(4, &value.encode());
+        }
+        layer.uint(5, u64::from(EXTENT));
+        layer.0
+    }
+}
+
+// Encodes the places as point features of a single layer. The
+// properties of each feature are the id, title, categories and
+// tags (comma-separated), and the average rating.
+pub fn encode_places_tile(tile: TileCoord, places: Vec<IndexedPlace>) -> Vec<u8> {
+    let mut layer = LayerBuilder::default();
+    for place in places {
+        let IndexedPlace {
+            id,
+            title,
+            tags,
+            pos,
+            ratings,
+            ..
+        } = place;
+        let (tags, categories) = Category::split_from_tags(tags);
+        let categories: Vec<_> = categories.into_iter().map(|c| c.id.to_string()).collect();
+        let properties = vec![
+            ("id", Value::String(id)),
+            ("title", Value::String(title)),
+            ("categories", Value::String(categories.join(","))),
+            ("tags", Value::String(tags.join(","))),
+            ("rating", Value::Double(ratings.total().into())),
+        ];
+        layer.add_point(tile.project(pos), properties);
+    }
+    let mut tile = Encoder::new();
+    if !layer.features.is_empty() {
+        tile.bytes(3, &layer.encode(LAYER_NAME));
+    }
+    tile.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_tile_coord() {
+        assert_eq!(Ok(TileCoord { z: 2, x: 1, y: 3 }), "2/1/3".parse());
+        assert!("2/4/0".parse::<TileCoord>().is_err());
+        assert!("2/1".parse::<TileCoord>().is_err());
+        assert!("2/1/1/1".parse::<TileCoord>().is_err());
+        assert!("23/0/0".parse::<TileCoord>().is_err());
+    }
+
+    #[test]
+    fn tile_bbox() {
+        let bbox = TileCoord::new(1, 1, 0).unwrap().bbox();
+        assert!(bbox.southwest().lat().to_deg() < 0.0);
+        assert!(bbox.southwest().lng().to_deg() < 0.0);
+        assert!((bbox.northeast().lat().to_deg() - MAX_LATITUDE).abs() < 1e-6);
+        assert!((bbox.northeast().lng().to_deg() - 180.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn project_into_tile() {
+        let tile = TileCoord::new(1, 1, 0).unwrap();
+        assert_eq!(
+            (0, 4096),
+            tile.project(MapPoint::from_lat_lng_deg(0.0, 0.0))
+        );
+        let (x, y) = tile.project(MapPoint::from_lat_lng_deg(45.0, 90.0));
+        assert_eq!(2048, x);
+        assert!(y > 0 && y < 4096);
+    }
+
+    #[test]
+    fn encode_varints_and_zigzag() {
+        let mut enc = Encoder::new();
+        enc.varint(300);
+        assert_eq!(vec![0xac, 0x02], enc.0);
+        assert_eq!(0, zigzag(0));
+        assert_eq!(1, zigzag(-1));
+        assert_eq!(2, zigzag(1));
+        assert_eq!(3, zigzag(-2));
+    }
+
+    #[test]
+    fn encode_empty_tile() {
+        let tile = TileCoord::new(0, 0, 0).unwrap();
+        assert!(encode_places_tile(tile, vec![]).is_empty());
+    }
+
+    #[test]
+    fn encode_tile_with_places() {
+        let tile = TileCoord::new(0, 0, 0).unwrap();
+        let places = vec![
+            IndexedPlace {
+                id: "a".into(),
+                title: "A".into(),
+                tags: vec!["foo".into()],
+                pos: MapPoint::from_lat_lng_deg(0.0, 0.0),
+                ..Default::default()
+            },
+            IndexedPlace {
+                id: "b".into(),
+                title: "B".into(),
+                tags: vec!["foo".into()],
+                pos: MapPoint::from_lat_lng_deg(10.0, 10.0),
+                ..Default::default()
+            },
+        ];
+        let bytes = encode_places_tile(tile, places);
+        // Tile.layers (field 3, length-delimited)
+        assert_eq!((3 << 3) | 2, bytes[0]);
+        let contains = |needle: &[u8]| bytes.windows(needle.len()).any(|w| w == needle);
+        assert!(contains(LAYER_NAME.as_bytes()));
+        // The shared tag value is only encoded once
+        assert_eq!(1, bytes.windows(5).filter(|w| w == b"\x0a\x03foo").count());
+        // First point at the center of the tile: MoveTo(2048, 2048)
+        assert!(contains(&[0x22, 0x05, 0x09, 0x80, 0x20, 0x80, 0x20]));
+    }
+}
@@ -9,6 +9,10 @@ use super::{
 };
 
 use anyhow::Result as Fallible;
+use ofdb_core::{
+    cluster::{ClusterGrid, PlaceCluster},
+    opening_hours::MinuteOfWeek,
+};
 
 type Result<T> = std::result::Result<T, RepoError>;
 
@@ -31,6 +35,11 @@ pub trait PlaceRepo {
     fn all_places(&self) -> Result<Vec<(Place, ReviewStatus)>>;
     fn count_places(&self) -> Result<usize>;
 
+    fn all_place_ids(&self) -> Result<Vec<Id>>;
+    // Places that have been created, modified, reviewed, or rated
+    // since the given time stamp (inclusive).
+    fn place_ids_changed_since(&self, since: TimestampMs) -> Result<Vec<Id>>;
+
     fn recently_changed_places(
         &self,
         params: &RecentlyChangedEntriesParams,
@@ -55,6 +64,15 @@ pub trait PlaceRepo {
     fn get_place_history(&self, id: &str, revision: Option<Revision>) -> Result<PlaceHistory>;
 
     fn load_place_revision(&self, id: &str, rev: Revision) -> Result<(Place, ReviewStatus)>;
+
+    // Appends an entry to the review log of the current revision
+    // without changing its review status.
+    fn log_place_review(&self, id: &str, activity_log: &ActivityLog) -> Result<()>;
+
+    // Redirects an (archived) place to the place it has been merged
+    // into. Existing redirects to the old place are forwarded.
+    fn create_place_redirect(&self, old_id: &str, new_id: &str) -> Result<()>;
+    fn get_place_redirect(&self, old_id: &str) -> Result<Option<Id>>;
 }
 
 pub trait EventGateway {
@@ -64,11 +82,27 @@ pub trait EventGateway {
 
     fn get_event(&self, id: &str) -> Result<Event>;
     fn get_events_chronologically(&self, ids: &[&str]) -> Result<Vec<Event>>;
+    // Every create or update of an event is stored as a new
+    // revision, starting with the initial revision.
+    fn get_event_current_revision(&self, id: &str) -> Result<Revision>;
+    fn load_event_revision(&self, id: &str, rev: Revision) -> Result<Event>;
 
     fn all_events_chronologically(&self) -> Result<Vec<Event>>;
 
     fn count_events(&self) -> Result<usize>;
 
+    // Excludes archived events.
+    fn all_event_ids(&self) -> Result<Vec<Id>>;
+    // Includes events that have been archived since the given
+    // time stamp (inclusive).
+    fn event_ids_changed_since(&self, since: TimestampMs) -> Result<Vec<Id>>;
+    // The time of the last modification of each event. Events
+    // that don't exist are omitted.
+    fn load_event_update_times(&self, ids: &[&str]) -> Result<Vec<(Id, TimestampMs)>>;
+    // Single occurrences of the given recurring events that have
+    // been updated separately. Excludes archived events.
+    fn detached_event_occurrence_ids(&self, series_ids: &[&str]) -> Result<Vec<Id>>;
+
     // Delete an event, but only if tagged with at least one of the given tags.
     // If no tags are provided the event is deleted unconditionally.
     // Ok(true)  => Found and deleted
@@ -77,6 +111,13 @@ pub trait EventGateway {
     fn delete_event_with_matching_tags(&self, id: &str, tags: &[&str]) -> Result<bool>;
 
     fn is_event_owned_by_any_organization(&self, id: &str) -> Result<bool>;
+
+    // Events that have been imported by an organization from an
+    // external calendar: (external UID, event id). Excludes archived
+    // events.
+    fn load_external_event_uids(&self, org_id: &Id) -> Result<Vec<(String, Id)>>;
+    // Replaces any existing mapping of the external UID.
+    fn store_external_event_uid(&self, org_id: &Id, external_uid: &str, id: &str) -> Result<()>;
 }
 
 pub trait UserGateway {
@@ -93,7 +134,17 @@ pub trait UserGateway {
 
 pub trait OrganizationRepo {
     fn create_org(&mut self, _: Organization) -> Result<()>;
-    fn get_org_by_api_token(&self, token: &str) -> Result<Organization>;
+    // Replaces all properties including the moderated tags
+    fn update_org(&mut self, _: &Organization) -> Result<()>;
+    // Deletes the organization together with its moderated tags,
+    // pending clearances, and webhooks
+    fn delete_org(&mut self, id: &Id) -> Result<()>;
+    fn get_org(&self, id: &Id) -> Result<Organization>;
+    fn all_orgs(&self) -> Result<Vec<Organization>>;
+    // Places and events that are not archived and tagged with
+    // any of the moderated tags of the organization
+    fn load_owned_place_ids(&self, org_id: &Id) -> Result<Vec<Id>>;
+    fn load_owned_event_ids(&self, org_id: &Id) -> Result<Vec<Id>>;
     fn map_tag_to_clearance_org_id(&self, tag: &str) -> Result<Option<Id>>;
     fn get_moderated_tags_by_org(
         &self,
@@ -126,6 +177,155 @@ pub trait PlaceClearanceRepo {
     fn cleanup_pending_clearances_for_places(&self, org_id: &Id) -> Result<u64>;
 }
 
+pub trait EventClearanceRepo {
+    fn add_pending_clearance_for_events(
+        &self,
+        org_ids: &[Id],
+        pending_clearance: &PendingClearanceForEvent,
+    ) -> Result<usize>;
+    fn count_pending_clearances_for_events(&self, org_id: &Id) -> Result<u64>;
+    fn list_pending_clearances_for_events(
+        &self,
+        org_id: &Id,
+        pagination: &Pagination,
+    ) -> Result<Vec<PendingClearanceForEvent>>;
+    fn load_pending_clearances_for_events(
+        &self,
+        org_id: &Id,
+        event_ids: &[&str],
+    ) -> Result<Vec<PendingClearanceForEvent>>;
+    fn update_pending_clearances_for_events(
+        &self,
+        org_id: &Id,
+        clearances: &[ClearanceForEvent],
+    ) -> Result<usize>;
+    fn cleanup_pending_clearances_for_events(&self, org_id: &Id) -> Result<u64>;
+}
+
+#[derive(Clone, Debug)]
+pub struct QueuedJob {
+    pub id: Id,
+    pub payload: String,
+    // Number of attempts including the current one
+    pub attempts: u32,
+}
+
+pub trait JobQueue {
+    fn enqueue_job(&self, id: &Id, payload: &str, run_at: TimestampMs) -> Result<()>;
+
+    // Claims the next job that is due by postponing it until the
+    // lease expires. Jobs that have neither been completed nor
+    // rescheduled until then, e.g. because the worker crashed,
+    // become due again.
+    fn claim_next_job(
+        &self,
+        now: TimestampMs,
+        lease_until: TimestampMs,
+    ) -> Result<Option<QueuedJob>>;
+    // Claims a single job if it is still due.
+    fn claim_job(
+        &self,
+        id: &Id,
+        now: TimestampMs,
+        lease_until: TimestampMs,
+    ) -> Result<Option<QueuedJob>>;
+
+    fn reschedule_job(&self, id: &Id, run_at: TimestampMs, last_error: &str) -> Result<()>;
+    fn complete_job(&self, id: &Id) -> Result<()>;
+}
+
+pub trait WebhookRepo {
+    fn create_webhook(&self, webhook: &Webhook) -> Result<()>;
+    // Deletes the webhook of the organization including its deliveries
+    fn delete_webhook(&self, org_id: &Id, id: &Id) -> Result<()>;
+    fn get_webhook(&self, id: &Id) -> Result<Webhook>;
+    fn all_webhooks(&self) -> Result<Vec<Webhook>>;
+    fn load_webhooks_of_org(&self, org_id: &Id) -> Result<Vec<Webhook>>;
+
+    fn create_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()>;
+    fn get_webhook_delivery(&self, id: &Id) -> Result<WebhookDelivery>;
+    // Updates the status, attempts, response, and error of the delivery
+    fn update_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()>;
+    // Most recent deliveries first
+    fn load_webhook_deliveries(
+        &self,
+        webhook_id: &Id,
+        pagination: &Pagination,
+    ) -> Result<Vec<WebhookDelivery>>;
+}
+
+pub trait ApiTokenRepo {
+    fn create_api_token(&self, token: &ApiToken) -> Result<()>;
+    fn get_api_token_by_hash(&self, token_hash: &str) -> Result<ApiToken>;
+    fn load_api_tokens_of_org(&self, org_id: &Id) -> Result<Vec<ApiToken>>;
+    fn update_api_token_last_used_at(&self, id: &Id, last_used_at: TimestampMs) -> Result<()>;
+    fn revoke_api_token(&self, org_id: &Id, id: &Id, revoked_at: TimestampMs) -> Result<()>;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeoCodingCacheEntry {
+    pub query: String,
+    pub cached_at: TimestampMs,
+    // Both the position (forward) and the address (reverse)
+    // are missing if the query could not be resolved.
+    pub pos: Option<MapPoint>,
+    pub address: Option<Address>,
+}
+
+pub trait GeoCodingCacheRepo {
+    fn get_geocoding_cache_entry(&self, query: &str) -> Result<Option<GeoCodingCacheEntry>>;
+    // Replaces an existing entry with the same query
+    fn put_geocoding_cache_entry(&self, entry: &GeoCodingCacheEntry) -> Result<()>;
+    fn delete_geocoding_cache_entries_before(&self, cached_before: TimestampMs) -> Result<usize>;
+}
+
+pub trait ImageRepo {
+    fn create_image(&self, image: &Image) -> Result<()>;
+    fn get_image(&self, id: &Id) -> Result<Image>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserActivityKind {
+    PlaceRevision,
+    PlaceReview,
+    PlaceRating,
+    PlaceRatingArchived,
+    PlaceRatingComment,
+    PlaceRatingCommentArchived,
+    Event,
+    Image,
+}
+
+/// A record that refers to the account of a user.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserActivity {
+    pub kind: UserActivityKind,
+    /// The id of the place, rating, comment, event, or image
+    pub id: Id,
+    pub revision: Option<Revision>,
+    pub at: Option<TimestampMs>,
+}
+
+pub trait UserDataRepo {
+    fn load_user_activities(&self, email: &str) -> Result<Vec<UserActivity>>;
+    // Replaces the account of a user and keeps all references
+    // to it. Tokens and subscriptions of the user are deleted.
+    // Copies of the e-mail address in the payloads of webhook
+    // deliveries and jobs and in audit log values are replaced.
+    fn pseudonymize_user(&self, email: &str, pseudonym: &User) -> Result<()>;
+}
+
+pub trait AuditLogRepo {
+    // There are no operations to update or delete entries
+    fn append_audit_log(&self, entry: &AuditLogEntry) -> Result<()>;
+    // Most recent entries first
+    fn query_audit_log(
+        &self,
+        query: &AuditLogQuery,
+        pagination: &Pagination,
+    ) -> Result<Vec<AuditLogEntry>>;
+}
+
 //TODO:
 //  - TagGeatway
 //  - SubscriptionGateway
@@ -145,6 +345,9 @@ pub trait Db:
     + RatingRepository
     + UserTokenRepo
     + PlaceClearanceRepo
+    + EventClearanceRepo
+    + AuditLogRepo
+    + ImageRepo
 {
     fn create_tag_if_it_does_not_exist(&self, _: &Tag) -> Result<()>;
 
@@ -161,7 +364,26 @@ pub trait Db:
     fn create_bbox_subscription(&self, _: &BboxSubscription) -> Result<()>;
     fn all_bbox_subscriptions(&self) -> Result<Vec<BboxSubscription>>;
     fn all_bbox_subscriptions_by_email(&self, user_email: &str) -> Result<Vec<BboxSubscription>>;
+    fn get_bbox_subscription(&self, id: &Id) -> Result<BboxSubscription>;
+    // Deletes the subscription including its pending changes
+    fn delete_bbox_subscription(&self, id: &Id) -> Result<()>;
     fn delete_bbox_subscriptions_by_email(&self, user_email: &str) -> Result<()>;
+
+    // Subsequent changes of the same item are ignored
+    fn add_pending_subscription_change(&self, change: &PendingSubscriptionChange) -> Result<()>;
+    // Oldest changes first
+    fn load_pending_subscription_changes(
+        &self,
+        subscription_id: &Id,
+    ) -> Result<Vec<PendingSubscriptionChange>>;
+    // Deletes all pending changes up to the given time and
+    // records when the digest has been sent
+    fn complete_subscription_digest(
+        &self,
+        subscription_id: &Id,
+        changes_until: TimestampMs,
+        sent_at: TimestampMs,
+    ) -> Result<()>;
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -190,6 +412,8 @@ pub struct IndexQuery<'a, 'b> {
     pub ts_min_ub: Option<Timestamp>, // upper bound (inclusive)
     pub ts_max_lb: Option<Timestamp>, // lower bound (inclusive)
     pub ts_max_ub: Option<Timestamp>, // upper bound (inclusive)
+    // Only places that are open at this local time
+    pub open_at: Option<MinuteOfWeek>,
 }
 
 pub trait Indexer {
@@ -222,6 +446,14 @@ pub struct IndexedPlace {
 
 pub trait PlaceIndex {
     fn query_places(&self, query: &IndexQuery, limit: usize) -> Fallible<Vec<IndexedPlace>>;
+
+    // Aggregates up to `limit` matching places into the cells of the grid
+    fn cluster_places(
+        &self,
+        query: &IndexQuery,
+        grid: ClusterGrid,
+        limit: usize,
+    ) -> Fallible<Vec<PlaceCluster>>;
 }
 
 pub trait PlaceIndexer: IdIndexer + PlaceIndex {
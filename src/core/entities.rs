@@ -1,7 +1,8 @@
 pub use ofdb_entities::{
-    activity::*, address::*, category::*, clearance::*, comment::*, contact::*, email::*, event::*,
-    geo::*, id::*, links::*, location::*, nonce::*, organization::*, password::*, place::*,
-    rating::*, review::*, revision::*, subscription::*, tag::*, time::*, url::Url, user::*,
+    activity::*, address::*, api_token::*, audit::*, category::*, clearance::*, comment::*,
+    contact::*, email::*, event::*, geo::*, id::*, image::*, links::*, locale::*, location::*,
+    nonce::*, organization::*, password::*, place::*, rating::*, review::*, revision::*,
+    subscription::*, tag::*, time::*, url::Url, user::*, webhook::*,
 };
 
 #[cfg(test)]
@@ -47,8 +47,8 @@ pub enum ParameterError {
     ModeratedTag,
     #[error("Missing the email of the creator")]
     CreatorEmail,
-    #[error("Invalid opening hours")]
-    InvalidOpeningHours,
+    #[error("Invalid opening hours: {0}")]
+    InvalidOpeningHours(String),
     #[error("Invalid position")]
     InvalidPosition,
     #[error("Invalid limit")]
@@ -61,6 +61,44 @@ pub enum ParameterError {
     InvalidNonce,
     #[error("Missing id list")]
     EmptyIdList,
+    #[error("Invalid organization name")]
+    InvalidOrganizationName,
+    #[error("Invalid tag: {0}")]
+    InvalidTag(String),
+    #[error("Invalid calendar: {0}")]
+    InvalidCalendar(String),
+    #[error("Unknown time zone: {0}")]
+    UnknownTimeZone(String),
+    #[error("Local date/time without a time zone: {0}")]
+    FloatingDateTime(String),
+    #[error("Invalid webhook event type: {0}")]
+    InvalidWebhookEventType(String),
+    #[error("The webhook secret is too short")]
+    WebhookSecret,
+    #[error("Invalid recurrence: {0}")]
+    InvalidRecurrence(String),
+    #[error("Invalid locale: {0}")]
+    InvalidLocale(String),
+    #[error("Invalid API token name")]
+    InvalidApiTokenName,
+    #[error("Invalid API token scope: {0}")]
+    InvalidApiTokenScope(String),
+    #[error("Invalid image: {0}")]
+    InvalidImage(String),
+    #[error("The image is too large")]
+    ImageTooLarge,
+    #[error("Unknown image: {0}")]
+    UnknownImage(String),
+    #[error("Invalid category: {0}")]
+    InvalidCategory(String),
+    #[error("Invalid subscription delivery: {0}")]
+    InvalidSubscriptionDelivery(String),
+    #[error("A place cannot be merged with itself")]
+    MergeSamePlace,
+    #[error("Only an earlier revision of a place can be restored")]
+    RevertRevision,
+    #[error("Invalid audit action: {0}")]
+    InvalidAuditAction(String),
 }
 
 #[derive(Debug, Error)]
@@ -92,6 +130,16 @@ pub enum Error {
     Internal(String),
 }
 
+impl From<ofdb_core::gateways::image::ImageStorageError> for RepoError {
+    fn from(err: ofdb_core::gateways::image::ImageStorageError) -> Self {
+        use ofdb_core::gateways::image::ImageStorageError as E;
+        match err {
+            E::Io(err) => RepoError::Io(err),
+            err => RepoError::Other(err.into()),
+        }
+    }
+}
+
 impl From<String> for Error {
     fn from(s: String) -> Self {
         Error::Internal(s)
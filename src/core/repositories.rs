@@ -51,6 +51,9 @@ pub trait RatingRepository {
     fn archive_ratings_of_places(&self, place_ids: &[&str], activity: &Activity) -> Result<usize>;
 
     fn load_place_ids_of_ratings(&self, ids: &[&str]) -> Result<Vec<String>>;
+
+    // Moves all ratings including their comments to another place
+    fn move_ratings_of_place(&self, from_place_id: &str, to_place_id: &str) -> Result<usize>;
 }
 
 pub trait UserTokenRepo {
@@ -0,0 +1,152 @@
+use crate::core::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct NewApiToken {
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<TimestampMs>,
+}
+
+/// Creates a new API token for an organization.
+///
+/// The secret token is only returned once and cannot be
+/// recovered afterwards.
+pub fn create_api_token<R: OrganizationRepo + ApiTokenRepo>(
+    repo: &R,
+    org_id: &Id,
+    new_token: NewApiToken,
+) -> Result<(ApiToken, String)> {
+    let NewApiToken {
+        name,
+        scopes,
+        expires_at,
+    } = new_token;
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(ParameterError::InvalidApiTokenName.into());
+    }
+    let mut parsed_scopes = Vec::with_capacity(scopes.len());
+    for s in scopes {
+        let s = s
+            .parse::<ApiTokenScope>()
+            .map_err(|_| ParameterError::InvalidApiTokenScope(s))?;
+        if !parsed_scopes.contains(&s) {
+            parsed_scopes.push(s);
+        }
+    }
+    if parsed_scopes.is_empty() {
+        return Err(ParameterError::InvalidApiTokenScope(String::new()).into());
+    }
+    let created_at = TimestampMs::now();
+    if expires_at.map(|t| t <= created_at).unwrap_or(false) {
+        return Err(ParameterError::TokenExpired.into());
+    }
+    // Fails if the organization doesn't exist
+    repo.get_org(org_id)?;
+    let secret = Nonce::new().to_string();
+    let token = ApiToken {
+        id: Id::new(),
+        org_id: org_id.clone(),
+        name,
+        token_hash: ApiToken::hash_token(&secret),
+        scopes: parsed_scopes,
+        created_at,
+        expires_at,
+        last_used_at: None,
+        revoked_at: None,
+    };
+    repo.create_api_token(&token)?;
+    Ok((token, secret))
+}
+
+pub fn load_api_tokens<R: ApiTokenRepo>(repo: &R, org_id: &Id) -> Result<Vec<ApiToken>> {
+    Ok(repo.load_api_tokens_of_org(org_id)?)
+}
+
+pub fn revoke_api_token<R: ApiTokenRepo>(repo: &R, org_id: &Id, id: &Id) -> Result<()> {
+    Ok(repo.revoke_api_token(org_id, id, TimestampMs::now())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{tests::MockDb, *};
+
+    fn new_token(scopes: &[&str]) -> NewApiToken {
+        NewApiToken {
+            name: "Import".into(),
+            scopes: scopes.iter().map(|s| (*s).to_string()).collect(),
+            expires_at: None,
+        }
+    }
+
+    fn create_org(db: &mut MockDb) -> Organization {
+        create_organization(
+            db,
+            NewOrganization {
+                name: "Foo".into(),
+                api_token: Some("initial".into()),
+                moderated_tags: vec![],
+            },
+        )
+        .unwrap()
+        .0
+    }
+
+    fn authorize(db: &MockDb, token: &str, scope: ApiTokenScope) -> Result<Organization> {
+        authorize_organization_by_possible_api_tokens(
+            db,
+            &[token.to_string()],
+            scope,
+            TimestampMs::now(),
+        )
+        .map(|(org, _)| org)
+    }
+
+    #[test]
+    fn create_api_token_with_invalid_parameters() {
+        let mut db = MockDb::default();
+        let org = create_org(&mut db);
+        assert!(create_api_token(&db, &org.id, new_token(&[])).is_err());
+        assert!(create_api_token(&db, &org.id, new_token(&["events"])).is_err());
+        let mut expired = new_token(&["events:write"]);
+        expired.expires_at = Some(TimestampMs::from_inner(0));
+        assert!(create_api_token(&db, &org.id, expired).is_err());
+        assert!(create_api_token(&db, &Id::new(), new_token(&["events:write"])).is_err());
+        assert_eq!(1, db.api_tokens.borrow().len());
+    }
+
+    #[test]
+    fn authorize_with_scoped_api_tokens() {
+        let mut db = MockDb::default();
+        let org = create_org(&mut db);
+        let (token, secret) =
+            create_api_token(&db, &org.id, new_token(&["events:write", "events:write"])).unwrap();
+        assert_eq!(vec![ApiTokenScope::EventsWrite], token.scopes);
+        // Only the hash is stored
+        assert_ne!(secret, db.api_tokens.borrow()[1].token_hash);
+
+        assert_eq!(
+            org.id,
+            authorize(&db, &secret, ApiTokenScope::EventsWrite)
+                .unwrap()
+                .id
+        );
+        assert!(matches!(
+            authorize(&db, &secret, ApiTokenScope::PlacesClearance),
+            Err(Error::Parameter(ParameterError::Forbidden))
+        ));
+        // The initial token is granted all scopes
+        assert!(authorize(&db, "initial", ApiTokenScope::PlacesClearance).is_ok());
+        assert!(matches!(
+            authorize(&db, "unknown", ApiTokenScope::EventsWrite),
+            Err(Error::Parameter(ParameterError::Unauthorized))
+        ));
+
+        revoke_api_token(&db, &org.id, &token.id).unwrap();
+        assert!(revoke_api_token(&db, &org.id, &token.id).is_err());
+        assert!(matches!(
+            authorize(&db, &secret, ApiTokenScope::EventsWrite),
+            Err(Error::Parameter(ParameterError::Unauthorized))
+        ));
+    }
+}
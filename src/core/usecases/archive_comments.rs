@@ -8,7 +8,14 @@ pub fn archive_comments<D: Db>(db: &D, user_email: &str, ids: &[&str]) -> Result
     if let Some(user) = user {
         if user.role >= Role::Scout {
             let archived = Activity::now(Some(user_email.into()));
-            return Ok(db.archive_comments(ids, &archived)?);
+            let count = db.archive_comments(ids, &archived)?;
+            let entry = AuditLogEntry::now(
+                Some(user_email),
+                AuditAction::ArchiveComments,
+                ids.iter().map(ToString::to_string).collect(),
+            );
+            super::record_audit_log(db, entry)?;
+            return Ok(count);
         }
     }
     Err(ParameterError::Forbidden.into())
@@ -1,7 +1,14 @@
 use crate::core::prelude::*;
 
-pub fn archive_events<D: Db>(db: &D, ids: &[&str]) -> Result<usize> {
+pub fn archive_events<D: Db>(db: &D, ids: &[&str], archived_by: Option<&str>) -> Result<usize> {
     debug!("Archiving events {:?}", ids);
     let archived = Timestamp::now();
-    Ok(db.archive_events(ids, archived)?)
+    let count = db.archive_events(ids, archived)?;
+    let entry = AuditLogEntry::now(
+        archived_by,
+        AuditAction::ArchiveEvents,
+        ids.iter().map(ToString::to_string).collect(),
+    );
+    super::record_audit_log(db, entry)?;
+    Ok(count)
 }
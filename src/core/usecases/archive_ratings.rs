@@ -9,7 +9,14 @@ pub fn archive_ratings<D: Db>(db: &D, user_email: &str, ids: &[&str]) -> Result<
         if user.role >= Role::Scout {
             let archived = Activity::now(Some(user_email.into()));
             db.archive_comments_of_ratings(ids, &archived)?;
-            return Ok(db.archive_ratings(ids, &archived)?);
+            let count = db.archive_ratings(ids, &archived)?;
+            let entry = AuditLogEntry::now(
+                Some(user_email),
+                AuditAction::ArchiveRatings,
+                ids.iter().map(ToString::to_string).collect(),
+            );
+            super::record_audit_log(db, entry)?;
+            return Ok(count);
         }
     }
     Err(ParameterError::Forbidden.into())
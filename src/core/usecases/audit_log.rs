@@ -0,0 +1,91 @@
+use crate::core::prelude::*;
+
+/// The maximum number of audit log entries per request.
+pub const AUDIT_LOG_MAX_LIMIT: u64 = 1000;
+
+/// Appends an entry to the audit log.
+///
+/// Usecases of privileged actions invoke this hook after the
+/// action itself has succeeded. Both are committed or rolled
+/// back together when executed within a transaction.
+pub fn record_audit_log<R: AuditLogRepo + ?Sized>(repo: &R, entry: AuditLogEntry) -> Result<()> {
+    debug!(
+        "Recording {} of {:?} by {:?}",
+        entry.action, entry.target_ids, entry.actor
+    );
+    Ok(repo.append_audit_log(&entry)?)
+}
+
+pub fn query_audit_log<R: AuditLogRepo>(
+    repo: &R,
+    query: &AuditLogQuery,
+    pagination: &Pagination,
+) -> Result<Vec<AuditLogEntry>> {
+    let limit = pagination
+        .limit
+        .unwrap_or(AUDIT_LOG_MAX_LIMIT)
+        .min(AUDIT_LOG_MAX_LIMIT);
+    let pagination = Pagination {
+        offset: pagination.offset,
+        limit: Some(limit),
+    };
+    Ok(repo.query_audit_log(query, &pagination)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+
+    #[test]
+    fn record_role_change_of_user() {
+        let db = MockDb::default();
+        db.users.borrow_mut().push(User {
+            email: "admin@example.com".into(),
+            email_confirmed: true,
+            password: "secret".parse::<Password>().unwrap(),
+            role: Role::Admin,
+            locale: None,
+        });
+        db.users.borrow_mut().push(User {
+            email: "user@example.com".into(),
+            email_confirmed: true,
+            password: "secret".parse::<Password>().unwrap(),
+            role: Role::User,
+            locale: None,
+        });
+        super::super::change_user_role(&db, "admin@example.com", "user@example.com", Role::Scout)
+            .unwrap();
+        let entries =
+            query_audit_log(&db, &AuditLogQuery::default(), &Pagination::default()).unwrap();
+        assert_eq!(1, entries.len());
+        let entry = &entries[0];
+        assert_eq!(Some("admin@example.com"), entry.actor.as_deref());
+        assert_eq!(AuditAction::ChangeUserRole, entry.action);
+        assert_eq!(vec!["user@example.com".to_string()], entry.target_ids);
+        assert_eq!(Some("user"), entry.before.as_deref());
+        assert_eq!(Some("scout"), entry.after.as_deref());
+    }
+
+    #[test]
+    fn record_role_change_without_actor() {
+        let db = MockDb::default();
+        db.users.borrow_mut().push(User {
+            email: "user@example.com".into(),
+            email_confirmed: true,
+            password: "secret".parse::<Password>().unwrap(),
+            role: Role::User,
+            locale: None,
+        });
+        super::super::set_user_role(&db, "user@example.com", Role::Admin).unwrap();
+        let entries =
+            query_audit_log(&db, &AuditLogQuery::default(), &Pagination::default()).unwrap();
+        assert_eq!(1, entries.len());
+        let entry = &entries[0];
+        assert_eq!(None, entry.actor);
+        assert_eq!(AuditAction::ChangeUserRole, entry.action);
+        assert_eq!(vec!["user@example.com".to_string()], entry.target_ids);
+        assert_eq!(Some("user"), entry.before.as_deref());
+        assert_eq!(Some("admin"), entry.after.as_deref());
+    }
+}
@@ -1,16 +1,37 @@
 use crate::core::prelude::*;
 
-pub fn authorize_organization_by_possible_api_tokens<D: OrganizationRepo>(
+/// Authorizes an organization by any of the given tokens with
+/// the required scope.
+///
+/// Fails with `Forbidden` if a valid token has been found that
+/// lacks the required scope and with `Unauthorized` otherwise.
+pub fn authorize_organization_by_possible_api_tokens<D: OrganizationRepo + ApiTokenRepo>(
     db: &D,
     tokens: &[String],
-) -> Result<Organization> {
+    scope: ApiTokenScope,
+    now: TimestampMs,
+) -> Result<(Organization, ApiToken)> {
+    let mut insufficient_scope = false;
     for token in tokens {
-        match db.get_org_by_api_token(token) {
-            Ok(org) => return Ok(org),
+        match db.get_api_token_by_hash(&ApiToken::hash_token(token)) {
+            Ok(api_token) => {
+                if !api_token.is_valid_at(now) {
+                    continue;
+                }
+                if !api_token.has_scope(scope) {
+                    insufficient_scope = true;
+                    continue;
+                }
+                let org = db.get_org(&api_token.org_id)?;
+                return Ok((org, api_token));
+            }
             Err(RepoError::NotFound) => (),
             Err(e) => return Err(Error::Repo(e)),
         }
     }
+    if insufficient_scope {
+        return Err(Error::Parameter(ParameterError::Forbidden));
+    }
     Err(Error::Parameter(ParameterError::Unauthorized))
 }
 
@@ -0,0 +1,291 @@
+use crate::core::{prelude::*, util::validate};
+use ofdb_core::gateways::notify::Recipient;
+
+#[derive(Debug, Clone)]
+pub struct NewBboxSubscription {
+    pub bbox: MapBbox,
+    pub locale: Option<Locale>,
+    pub tags: Vec<String>,
+    pub categories: Vec<String>,
+    pub events: bool,
+    /// Immediate delivery if missing
+    pub delivery: Option<String>,
+}
+
+/// Changes of pending digests that are due at the time
+/// of the query.
+#[derive(Debug, Clone)]
+pub struct SubscriptionDigest {
+    pub subscription: BboxSubscription,
+    pub recipient: Recipient,
+    pub changes: Vec<PendingSubscriptionChange>,
+}
+
+/// Adds a new subscription of a user without replacing
+/// any existing subscriptions.
+pub fn create_bbox_subscription(
+    db: &dyn Db,
+    user_email: &str,
+    new_subscription: NewBboxSubscription,
+) -> Result<BboxSubscription> {
+    let NewBboxSubscription {
+        bbox,
+        locale,
+        tags,
+        categories,
+        events,
+        delivery,
+    } = new_subscription;
+    validate::bbox(&bbox)?;
+    let all_categories = db.all_categories()?;
+    let categories = categories
+        .into_iter()
+        .map(|c| {
+            if all_categories.iter().any(|known| known.id.as_str() == c) {
+                Ok(Id::from(c))
+            } else {
+                Err(ParameterError::InvalidCategory(c))
+            }
+        })
+        .collect::<std::result::Result<_, _>>()?;
+    let delivery = delivery
+        .map(|d| {
+            d.parse()
+                .map_err(|_| ParameterError::InvalidSubscriptionDelivery(d))
+        })
+        .transpose()?
+        .unwrap_or_default();
+    let subscription = BboxSubscription {
+        id: Id::new(),
+        user_email: user_email.to_string(),
+        bbox,
+        locale,
+        tags: super::prepare_tag_list(tags.iter().map(String::as_str)),
+        categories,
+        events,
+        delivery,
+        last_digest_at: None,
+    };
+    db.create_bbox_subscription(&subscription)?;
+    Ok(subscription)
+}
+
+/// Deletes a subscription of the user. Subscriptions of
+/// other users are not found.
+pub fn delete_bbox_subscription(db: &dyn Db, user_email: &str, id: &Id) -> Result<()> {
+    let subscription = db.get_bbox_subscription(id)?;
+    if subscription.user_email != user_email {
+        return Err(RepoError::NotFound.into());
+    }
+    Ok(db.delete_bbox_subscription(id)?)
+}
+
+/// Deletes a subscription by its id that is only known to
+/// the subscribed user, e.g. from an unsubscribe link.
+pub fn unsubscribe_bbox(db: &dyn Db, id: &Id) -> Result<()> {
+    info!("Unsubscribing bbox subscription {}", id);
+    Ok(db.delete_bbox_subscription(id)?)
+}
+
+/// The locale of a subscription takes precedence over
+/// the preferred locale of the subscribed user.
+fn subscription_recipient(db: &dyn Db, s: &BboxSubscription) -> Result<Recipient> {
+    let locale = match &s.locale {
+        Some(locale) => Some(locale.clone()),
+        None => db
+            .try_get_user_by_email(&s.user_email)?
+            .and_then(|u| u.locale),
+    };
+    Ok(Recipient {
+        email: s.user_email.clone(),
+        locale,
+        subscription_id: s.id.clone(),
+    })
+}
+
+/// All recipients of immediate notifications about a change
+/// of a place or event.
+///
+/// The change is queued for the next digest of all other
+/// matching subscriptions.
+pub fn recipients_of_change(
+    db: &dyn Db,
+    kind: SubscriptionChangeKind,
+    item_id: &Id,
+    pos: MapPoint,
+    tags: &[String],
+) -> Result<Vec<Recipient>> {
+    let now = TimestampMs::now();
+    let mut recipients: Vec<Recipient> = Vec::new();
+    for s in db.all_bbox_subscriptions()? {
+        if !s.matches(kind, pos, tags) {
+            continue;
+        }
+        if s.delivery != SubscriptionDelivery::Immediate {
+            db.add_pending_subscription_change(&PendingSubscriptionChange {
+                subscription_id: s.id,
+                kind,
+                item_id: item_id.clone(),
+                created_at: now,
+            })?;
+            continue;
+        }
+        // Only a single e-mail for multiple matching subscriptions
+        if recipients.iter().any(|r| r.email == s.user_email) {
+            continue;
+        }
+        recipients.push(subscription_recipient(db, &s)?);
+    }
+    Ok(recipients)
+}
+
+pub fn due_subscription_digests(db: &dyn Db, now: TimestampMs) -> Result<Vec<SubscriptionDigest>> {
+    let mut digests = Vec::new();
+    for subscription in db.all_bbox_subscriptions()? {
+        if subscription.delivery == SubscriptionDelivery::Immediate {
+            continue;
+        }
+        let changes = db.load_pending_subscription_changes(&subscription.id)?;
+        let due_at = match changes.first() {
+            Some(oldest) => subscription.digest_due_at(oldest.created_at),
+            None => continue,
+        };
+        if due_at.map(|due_at| due_at > now).unwrap_or(true) {
+            continue;
+        }
+        let recipient = subscription_recipient(db, &subscription)?;
+        digests.push(SubscriptionDigest {
+            subscription,
+            recipient,
+            changes,
+        });
+    }
+    Ok(digests)
+}
+
+/// Discards the delivered changes of the digest while keeping
+/// all changes that have been queued in the meantime.
+pub fn complete_subscription_digest(
+    db: &dyn Db,
+    digest: &SubscriptionDigest,
+    sent_at: TimestampMs,
+) -> Result<()> {
+    let changes_until = digest
+        .changes
+        .iter()
+        .map(|c| c.created_at)
+        .max()
+        .unwrap_or(sent_at);
+    Ok(db.complete_subscription_digest(&digest.subscription.id, changes_until, sent_at)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{tests::MockDb, *};
+
+    fn new_subscription(delivery: Option<&str>) -> NewBboxSubscription {
+        NewBboxSubscription {
+            bbox: MapBbox::new(
+                MapPoint::from_lat_lng_deg(0.0, 0.0),
+                MapPoint::from_lat_lng_deg(10.0, 10.0),
+            ),
+            locale: None,
+            tags: vec!["#Foo".into()],
+            categories: vec![],
+            events: false,
+            delivery: delivery.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn create_bbox_subscription_with_invalid_parameters() {
+        let db = MockDb::default();
+        let mut new = new_subscription(Some("hourly"));
+        assert!(create_bbox_subscription(&db, "a@foo.bar", new.clone()).is_err());
+        new.delivery = None;
+        new.categories = vec!["unknown".into()];
+        assert!(create_bbox_subscription(&db, "a@foo.bar", new.clone()).is_err());
+        new.categories = vec![Category::ID_NON_PROFIT.into()];
+        let s = create_bbox_subscription(&db, "a@foo.bar", new).unwrap();
+        assert_eq!(vec!["foo".to_string()], s.tags);
+        assert_eq!(SubscriptionDelivery::Immediate, s.delivery);
+    }
+
+    #[test]
+    fn queue_changes_for_digests() {
+        let db = MockDb::default();
+        let immediate = create_bbox_subscription(&db, "a@foo.bar", new_subscription(None)).unwrap();
+        let daily =
+            create_bbox_subscription(&db, "b@foo.bar", new_subscription(Some("daily"))).unwrap();
+        let pos = MapPoint::from_lat_lng_deg(5.0, 5.0);
+        let tags = vec!["foo".to_string()];
+
+        let place_id = Id::new();
+        let recipients = recipients_of_change(
+            &db,
+            SubscriptionChangeKind::PlaceAdded,
+            &place_id,
+            pos,
+            &tags,
+        )
+        .unwrap();
+        assert_eq!(1, recipients.len());
+        assert_eq!("a@foo.bar", recipients[0].email);
+        recipients_of_change(
+            &db,
+            SubscriptionChangeKind::PlaceUpdated,
+            &place_id,
+            pos,
+            &tags,
+        )
+        .unwrap();
+        // Not matching
+        recipients_of_change(
+            &db,
+            SubscriptionChangeKind::PlaceAdded,
+            &Id::new(),
+            pos,
+            &[],
+        )
+        .unwrap();
+        recipients_of_change(
+            &db,
+            SubscriptionChangeKind::EventCreated,
+            &Id::new(),
+            pos,
+            &tags,
+        )
+        .unwrap();
+        let changes = db.load_pending_subscription_changes(&daily.id).unwrap();
+        assert_eq!(1, changes.len());
+        assert_eq!(SubscriptionChangeKind::PlaceAdded, changes[0].kind);
+        assert!(db
+            .load_pending_subscription_changes(&immediate.id)
+            .unwrap()
+            .is_empty());
+
+        // Not due before the end of the period
+        let now = TimestampMs::now();
+        assert!(due_subscription_digests(&db, now).unwrap().is_empty());
+        let tomorrow = TimestampMs::from_inner(now.into_inner() + 24 * 60 * 60 * 1000);
+        let digests = due_subscription_digests(&db, tomorrow).unwrap();
+        assert_eq!(1, digests.len());
+        assert_eq!(daily.id, digests[0].subscription.id);
+        assert_eq!("b@foo.bar", digests[0].recipient.email);
+        complete_subscription_digest(&db, &digests[0], tomorrow).unwrap();
+        assert!(db
+            .load_pending_subscription_changes(&daily.id)
+            .unwrap()
+            .is_empty());
+        assert!(due_subscription_digests(&db, tomorrow).unwrap().is_empty());
+    }
+
+    #[test]
+    fn delete_only_own_bbox_subscriptions() {
+        let db = MockDb::default();
+        let s = create_bbox_subscription(&db, "a@foo.bar", new_subscription(None)).unwrap();
+        assert!(delete_bbox_subscription(&db, "b@foo.bar", &s.id).is_err());
+        delete_bbox_subscription(&db, "a@foo.bar", &s.id).unwrap();
+        assert!(unsubscribe_bbox(&db, &s.id).is_err());
+    }
+}
@@ -17,10 +17,37 @@ pub fn change_user_role<D: Db>(
         .try_get_user_by_email(user_email)?
         .ok_or(ParameterError::UserDoesNotExist)?;
     if account.role > user.role && role < account.role {
+        let mut entry = AuditLogEntry::now(
+            Some(account_email),
+            AuditAction::ChangeUserRole,
+            vec![user.email.clone()],
+        );
+        entry.before = Some(format!("{:?}", user.role).to_lowercase());
+        entry.after = Some(format!("{:?}", role).to_lowercase());
         user.role = role;
         db.update_user(&user)?;
+        super::record_audit_log(db, entry)?;
         Ok(())
     } else {
         Err(ParameterError::Forbidden.into())
     }
 }
+
+/// Assign a role to a user without any authorization checks.
+///
+/// Only intended for administrative tools with direct
+/// access to the database. The change is recorded in the
+/// audit log without an actor.
+pub fn set_user_role<D: Db>(db: &D, user_email: &str, role: Role) -> Result<()> {
+    info!("Setting role {:?} for {}", role, user_email);
+    let mut user = db
+        .try_get_user_by_email(user_email)?
+        .ok_or(ParameterError::UserDoesNotExist)?;
+    let mut entry = AuditLogEntry::now(None, AuditAction::ChangeUserRole, vec![user.email.clone()]);
+    entry.before = Some(format!("{:?}", user.role).to_lowercase());
+    entry.after = Some(format!("{:?}", role).to_lowercase());
+    user.role = role;
+    db.update_user(&user)?;
+    super::record_audit_log(db, entry)?;
+    Ok(())
+}
@@ -25,7 +25,7 @@ pub fn list_pending_clearances<R: OrganizationRepo + PlaceClearanceRepo>(
     Ok(repo.list_pending_clearances_for_places(&org.id, pagination)?)
 }
 
-pub fn update_pending_clearances<R: OrganizationRepo + PlaceClearanceRepo>(
+pub fn update_pending_clearances<R: OrganizationRepo + PlaceClearanceRepo + AuditLogRepo>(
     repo: &R,
     org: &Organization,
     clearances: &[ClearanceForPlace],
@@ -37,6 +37,17 @@ pub fn update_pending_clearances<R: OrganizationRepo + PlaceClearanceRepo>(
         clearances.len(),
         org.name
     );
+    for clearance in clearances {
+        let mut entry = AuditLogEntry::now(
+            Some(org.id.as_str()),
+            AuditAction::ClearPlace,
+            vec![clearance.place_id.to_string()],
+        );
+        entry.after = clearance
+            .cleared_revision
+            .map(|rev| RevisionValue::from(rev).to_string());
+        super::super::record_audit_log(repo, entry)?;
+    }
     repo.cleanup_pending_clearances_for_places(&org.id)?;
     Ok(count)
 }
@@ -29,6 +29,7 @@ mod tests {
             email_confirmed: false,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Guest,
+            locale: None,
         });
         let email_nonce = EmailNonce {
             email: email.into(),
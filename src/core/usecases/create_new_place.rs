@@ -1,4 +1,4 @@
-use super::{parse_custom_link_param, CustomLinkParam};
+use super::{parse_custom_link_param, parse_opening_hours_param, CustomLinkParam};
 
 use crate::core::{
     prelude::*,
@@ -30,6 +30,7 @@ pub struct NewPlace {
     pub license        : String,
     pub image_url      : Option<String>,
     pub image_link_url : Option<String>,
+    pub image_id       : Option<String>,
     pub custom_links   : Vec<CustomLinkParam>,
 }
 
@@ -39,6 +40,13 @@ pub struct Storable {
     clearance_org_ids: Vec<Id>,
 }
 
+impl Storable {
+    // Organizations that need to clear this revision
+    pub fn clearance_org_ids(&self) -> &[Id] {
+        &self.clearance_org_ids
+    }
+}
+
 pub fn prepare_new_place<D: Db>(
     db: &D,
     e: NewPlace,
@@ -66,6 +74,7 @@ pub fn prepare_new_place<D: Db>(
         founded_on,
         image_url,
         image_link_url,
+        image_id,
         custom_links: custom_links_param,
     } = e;
     let pos =
@@ -114,23 +123,31 @@ pub fn prepare_new_place<D: Db>(
     let image_href = image_link_url
         .and_then(|ref url| parse_url_param(url).transpose())
         .transpose()?;
+    let image_id = image_id.map(Id::from);
+    if let Some(ref image_id) = image_id {
+        super::check_image_exists(db, image_id)?;
+    }
     let mut custom_links = Vec::with_capacity(custom_links_param.len());
     for custom_link_param in custom_links_param {
         custom_links.push(parse_custom_link_param(custom_link_param)?);
     }
 
-    let links =
-        if homepage.is_none() && image.is_none() && image_href.is_none() && custom_links.is_empty()
-        {
-            None
-        } else {
-            Some(Links {
-                homepage,
-                image,
-                image_href,
-                custom: custom_links,
-            })
-        };
+    let links = if homepage.is_none()
+        && image.is_none()
+        && image_href.is_none()
+        && image_id.is_none()
+        && custom_links.is_empty()
+    {
+        None
+    } else {
+        Some(Links {
+            homepage,
+            image,
+            image_href,
+            image_id,
+            custom: custom_links,
+        })
+    };
 
     let place = Place {
         id: Id::new(),
@@ -142,10 +159,7 @@ pub fn prepare_new_place<D: Db>(
         location,
         contact,
         opening_hours: opening_hours
-            .map(|s| {
-                s.parse()
-                    .map_err(|_| Error::Parameter(ParameterError::InvalidOpeningHours))
-            })
+            .map(|from| parse_opening_hours_param(from, None))
             .transpose()?,
         founded_on,
         links,
@@ -211,6 +225,7 @@ mod tests {
             license     : "CC0-1.0".into(),
             image_url     : None,
             image_link_url: None,
+            image_id      : None,
             custom_links: vec![],
         };
         let mock_db = MockDb::default();
@@ -251,6 +266,7 @@ mod tests {
             license     : "CC0-1.0".into(),
             image_url     : None,
             image_link_url: None,
+            image_id      : None,
             custom_links: vec![],
         };
         let mock_db: MockDb = MockDb::default();
@@ -281,6 +297,7 @@ mod tests {
             license     : "CC0-1.0".into(),
             image_url     : None,
             image_link_url: None,
+            image_id      : None,
             custom_links: vec![],
         };
         let mock_db = MockDb::default();
@@ -289,4 +306,60 @@ mod tests {
         assert_eq!(mock_db.tags.borrow().len(), 2);
         assert_eq!(mock_db.entries.borrow().len(), 1);
     }
+
+    #[test]
+    fn create_place_with_uploaded_image() {
+        #[rustfmt::skip]
+        let new_place = |image_id: &str| NewPlace {
+            title       : "foo".into(),
+            description : "bar".into(),
+            lat         : 0.0,
+            lng         : 0.0,
+            street      : None,
+            zip         : None,
+            city        : None,
+            country     : None,
+            state       : None,
+            contact_name: None,
+            email       : None,
+            telephone   : None,
+            homepage    : None,
+            opening_hours: None,
+            founded_on  : None,
+            categories  : vec![],
+            tags        : vec![],
+            license     : "CC0-1.0".into(),
+            image_url     : None,
+            image_link_url: None,
+            image_id      : Some(image_id.into()),
+            custom_links: vec![],
+        };
+        let mock_db = MockDb::default();
+        mock_db
+            .create_image(&Image {
+                id: "uploaded".into(),
+                format: ImageFormat::Jpeg,
+                width: 640,
+                height: 480,
+                created_at: TimestampMs::now(),
+                created_by: None,
+            })
+            .unwrap();
+
+        match prepare_new_place(&mock_db, new_place("unknown"), None, None) {
+            Err(Error::Parameter(ParameterError::UnknownImage(id))) => assert_eq!("unknown", id),
+            res => panic!("Unexpected result: {:?}", res.map(|_| ())),
+        }
+
+        let storable = prepare_new_place(&mock_db, new_place("uploaded"), None, None).unwrap();
+        let (place, _) = store_new_place(&mock_db, storable).unwrap();
+        assert_eq!(
+            Some("uploaded"),
+            place
+                .links
+                .as_ref()
+                .and_then(|l| l.image_id.as_ref())
+                .map(Id::as_str)
+        );
+    }
 }
@@ -6,11 +6,15 @@ use passwords::PasswordGenerator;
 pub struct NewUser {
     pub email: String,
     pub password: String,
+    /// The preferred language for notifications, e.g. `de` or `en`
+    #[serde(default)]
+    pub locale: Option<String>,
 }
 
 pub fn create_new_user<D: UserGateway>(db: &D, u: NewUser) -> Result<()> {
     let password = u.password.parse::<Password>()?;
     validate::email(&u.email)?;
+    let locale = u.locale.as_deref().map(parse_locale).transpose()?;
     if db.try_get_user_by_email(&u.email)?.is_some() {
         return Err(ParameterError::UserExists.into());
     }
@@ -19,12 +23,19 @@ pub fn create_new_user<D: UserGateway>(db: &D, u: NewUser) -> Result<()> {
         email_confirmed: false,
         password,
         role: Role::Guest,
+        locale,
     };
     debug!("Creating new user: email = {}", new_user.email);
     db.create_user(&new_user)?;
     Ok(())
 }
 
+pub fn parse_locale(locale: &str) -> Result<Locale> {
+    locale
+        .parse()
+        .map_err(|_| ParameterError::InvalidLocale(locale.to_string()).into())
+}
+
 const PW_GEN: PasswordGenerator = PasswordGenerator {
     length: 8,
     numbers: true,
@@ -45,6 +56,7 @@ pub fn create_user_from_email<D: Db>(db: &D, email: &str) -> Result<User> {
     let u = NewUser {
         email: email.into(),
         password,
+        locale: None,
     };
     create_new_user(db, u)?;
     Ok(db.get_user_by_email(email)?)
@@ -63,6 +75,7 @@ mod tests {
         let u = NewUser {
             email: "foo@bar.de".into(),
             password: "secret1".into(),
+            locale: None,
         };
         assert!(create_new_user(&db, u).is_ok());
         assert!(db.get_user_by_email("foo@bar.de").is_ok());
@@ -71,6 +84,7 @@ mod tests {
         let u = NewUser {
             email: "baz@bar.de".into(),
             password: "secret2".into(),
+            locale: None,
         };
         assert!(create_new_user(&db, u).is_ok());
         assert!(db.get_user_by_email("foo@bar.de").is_ok());
@@ -83,11 +97,13 @@ mod tests {
         let u = NewUser {
             email: "foo@baz.io".into(),
             password: "hello".into(),
+            locale: None,
         };
         assert!(create_new_user(&db, u).is_err());
         let u = NewUser {
             email: "foo@baz.io".into(),
             password: "valid pass".into(),
+            locale: None,
         };
         assert!(create_new_user(&db, u).is_ok());
     }
@@ -98,16 +114,19 @@ mod tests {
         let u = NewUser {
             email: "".into(),
             password: "secret".into(),
+            locale: None,
         };
         assert!(create_new_user(&db, u).is_err());
         let u = NewUser {
             email: "fooo@".into(),
             password: "secret".into(),
+            locale: None,
         };
         assert!(create_new_user(&db, u).is_err());
         let u = NewUser {
             email: "fooo@bar.io".into(),
             password: "secret".into(),
+            locale: None,
         };
         assert!(create_new_user(&db, u).is_ok());
     }
@@ -120,19 +139,16 @@ mod tests {
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Guest,
+            locale: None,
         });
         let u = NewUser {
             email: "baz@foo.bar".into(),
             password: "secret".into(),
+            locale: None,
         };
         match create_new_user(&db, u).err().unwrap() {
-            Error::Parameter(err) => {
-                match err {
-                    ParameterError::UserExists => {
-                        // ok
-                    }
-                    _ => panic!("invalid error"),
-                }
+            Error::Parameter(ParameterError::UserExists) => {
+                // ok
             }
             _ => panic!("invalid error"),
         }
@@ -144,6 +160,7 @@ mod tests {
         let u = NewUser {
             email: "foo@bar.io".into(),
             password: "secret".into(),
+            locale: None,
         };
         assert!(create_new_user(&db, u).is_ok());
         assert_eq!(db.users.borrow()[0].email_confirmed, false);
@@ -155,12 +172,34 @@ mod tests {
         let u = NewUser {
             email: "foo@bar.io".into(),
             password: "secret".into(),
+            locale: None,
         };
         assert!(create_new_user(&db, u).is_ok());
         assert!(db.users.borrow()[0].password.as_ref() != "secret");
         assert!(db.users.borrow()[0].password.verify("secret"));
     }
 
+    #[test]
+    fn create_user_with_locale() {
+        let db = MockDb::default();
+        let u = NewUser {
+            email: "foo@bar.io".into(),
+            password: "secret".into(),
+            locale: Some("en_gb".into()),
+        };
+        assert!(create_new_user(&db, u).is_ok());
+        assert_eq!(
+            Some("en-GB"),
+            db.users.borrow()[0].locale.as_ref().map(Locale::as_str)
+        );
+        let u = NewUser {
+            email: "bar@foo.io".into(),
+            password: "secret".into(),
+            locale: Some("english".into()),
+        };
+        assert!(create_new_user(&db, u).is_err());
+    }
+
     #[test]
     fn test_create_user_from_email() {
         let db = MockDb::default();
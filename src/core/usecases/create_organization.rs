@@ -0,0 +1,132 @@
+use crate::core::prelude::*;
+use strum::IntoEnumIterator;
+
+#[derive(Debug, Clone)]
+pub struct NewOrganization {
+    pub name: String,
+    /// The secret of the initial API token that is granted
+    /// all scopes. A new, random token is generated if missing.
+    pub api_token: Option<String>,
+    pub moderated_tags: Vec<ModeratedTag>,
+}
+
+pub(super) fn prepare_organization_name(name: &str) -> Result<String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(ParameterError::InvalidOrganizationName.into());
+    }
+    Ok(name)
+}
+
+fn prepare_api_token(api_token: &str) -> Result<String> {
+    let api_token = api_token.trim().to_string();
+    if api_token.is_empty() {
+        return Err(ParameterError::TokenInvalid.into());
+    }
+    Ok(api_token)
+}
+
+// Normalizes the label of a moderated tag
+pub(super) fn prepare_moderated_tag(tag: ModeratedTag) -> Result<ModeratedTag> {
+    let mut labels = super::prepare_tag_list(Some(tag.label.as_str()));
+    if labels.len() != 1 {
+        return Err(ParameterError::InvalidTag(tag.label).into());
+    }
+    let label = labels.remove(0);
+    Ok(ModeratedTag { label, ..tag })
+}
+
+/// Creates a new organization together with an initial API token
+/// that is granted all scopes.
+///
+/// The secret token is only returned once and cannot be
+/// recovered afterwards.
+pub fn create_organization<D: OrganizationRepo + ApiTokenRepo>(
+    db: &mut D,
+    new_org: NewOrganization,
+) -> Result<(Organization, String)> {
+    let NewOrganization {
+        name,
+        api_token,
+        moderated_tags,
+    } = new_org;
+    let name = prepare_organization_name(&name)?;
+    let secret = api_token
+        .map(|t| prepare_api_token(&t))
+        .transpose()?
+        .unwrap_or_else(|| Nonce::new().to_string());
+    let mut tags: Vec<ModeratedTag> = Vec::with_capacity(moderated_tags.len());
+    for tag in moderated_tags {
+        let tag = prepare_moderated_tag(tag)?;
+        if tags.iter().any(|t| t.label == tag.label) {
+            return Err(ParameterError::InvalidTag(tag.label).into());
+        }
+        tags.push(tag);
+    }
+    let org = Organization {
+        id: Id::new(),
+        name,
+        moderated_tags: tags,
+    };
+    let token = ApiToken {
+        id: Id::new(),
+        org_id: org.id.clone(),
+        name: "default".into(),
+        token_hash: ApiToken::hash_token(&secret),
+        scopes: ApiTokenScope::iter().collect(),
+        created_at: TimestampMs::now(),
+        expires_at: None,
+        last_used_at: None,
+        revoked_at: None,
+    };
+    debug!("Creating new organization: {} ({})", org.name, org.id);
+    db.create_org(org.clone())?;
+    db.create_api_token(&token)?;
+    Ok((org, secret))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::MockDb;
+    use super::*;
+
+    fn new_org(name: &str, tags: &[&str]) -> NewOrganization {
+        NewOrganization {
+            name: name.into(),
+            api_token: None,
+            moderated_tags: tags.iter().map(|t| ModeratedTag::from(*t)).collect(),
+        }
+    }
+
+    #[test]
+    fn create_organization_with_generated_api_token() {
+        let mut db = MockDb::default();
+        let (org, secret) =
+            create_organization(&mut db, new_org(" Foo ", &["Bar", "baz"])).unwrap();
+        assert_eq!("Foo", org.name);
+        assert!(!secret.is_empty());
+        assert_eq!(
+            vec!["bar", "baz"],
+            org.moderated_tags
+                .iter()
+                .map(|t| t.label.as_str())
+                .collect::<Vec<_>>()
+        );
+        // Only the hash is stored
+        let token = db.api_tokens.borrow()[0].clone();
+        assert_eq!(org.id, token.org_id);
+        assert_eq!(ApiToken::hash_token(&secret), token.token_hash);
+        assert!(token.has_scope(ApiTokenScope::PlacesClearance));
+        assert!(token.has_scope(ApiTokenScope::EventsWrite));
+    }
+
+    #[test]
+    fn create_organization_with_invalid_parameters() {
+        let mut db = MockDb::default();
+        assert!(create_organization(&mut db, new_org("  ", &[])).is_err());
+        assert!(create_organization(&mut db, new_org("Foo", &["foo bar"])).is_err());
+        assert!(create_organization(&mut db, new_org("Foo", &["foo", "#foo"])).is_err());
+        assert!(db.orgs.is_empty());
+        assert!(db.api_tokens.borrow().is_empty());
+    }
+}
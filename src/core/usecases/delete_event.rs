@@ -1,10 +1,8 @@
 use crate::core::prelude::*;
 
-pub fn delete_event<D: Db>(db: &mut D, token: &str, id: &str) -> Result<()> {
-    let org = db.get_org_by_api_token(token).map_err(|e| match e {
-        RepoError::NotFound => Error::Parameter(ParameterError::Unauthorized),
-        _ => Error::Repo(e),
-    })?;
+// The organization must have been authorized in advance.
+pub fn delete_event<D: Db>(db: &mut D, org_id: &Id, id: &str) -> Result<()> {
+    let org = db.get_org(org_id)?;
     let moderated_tags: Vec<_> = org
         .moderated_tags
         .iter()
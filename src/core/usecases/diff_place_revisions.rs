@@ -0,0 +1,221 @@
+use crate::core::prelude::*;
+
+/// The scalar fields of a place that are compared
+/// between two revisions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceDiffField {
+    Title,
+    Description,
+    Street,
+    Zip,
+    City,
+    Country,
+    State,
+    ContactName,
+    ContactEmail,
+    ContactPhone,
+    OpeningHours,
+    FoundedOn,
+    Homepage,
+    Image,
+    ImageHref,
+    ImageId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedPlaceField {
+    pub field: PlaceDiffField,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MovedPlace {
+    pub from: MapPoint,
+    pub to: MapPoint,
+    /// The distance between both positions
+    pub distance: Option<Distance>,
+}
+
+/// The structured difference between two revisions of a place.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaceDiff {
+    pub id: Id,
+    pub from: Revision,
+    pub to: Revision,
+    pub changed_fields: Vec<ChangedPlaceField>,
+    pub added_tags: Vec<String>,
+    pub removed_tags: Vec<String>,
+    pub added_custom_links: Vec<CustomLink>,
+    pub removed_custom_links: Vec<CustomLink>,
+    /// Only present if the position has changed
+    pub moved: Option<MovedPlace>,
+}
+
+fn scalar_fields(place: &Place) -> Vec<(PlaceDiffField, Option<String>)> {
+    use PlaceDiffField as F;
+    let address = place.location.address.as_ref();
+    let contact = place.contact.as_ref();
+    let links = place.links.as_ref();
+    vec![
+        (F::Title, Some(place.title.clone())),
+        (F::Description, Some(place.description.clone())),
+        (F::Street, address.and_then(|a| a.street.clone())),
+        (F::Zip, address.and_then(|a| a.zip.clone())),
+        (F::City, address.and_then(|a| a.city.clone())),
+        (F::Country, address.and_then(|a| a.country.clone())),
+        (F::State, address.and_then(|a| a.state.clone())),
+        (F::ContactName, contact.and_then(|c| c.name.clone())),
+        (
+            F::ContactEmail,
+            contact.and_then(|c| c.email.as_ref().map(ToString::to_string)),
+        ),
+        (F::ContactPhone, contact.and_then(|c| c.phone.clone())),
+        (
+            F::OpeningHours,
+            place.opening_hours.as_ref().map(|o| o.as_str().to_string()),
+        ),
+        (F::FoundedOn, place.founded_on.map(|d| d.to_string())),
+        (
+            F::Homepage,
+            links.and_then(|l| l.homepage.as_ref().map(ToString::to_string)),
+        ),
+        (
+            F::Image,
+            links.and_then(|l| l.image.as_ref().map(ToString::to_string)),
+        ),
+        (
+            F::ImageHref,
+            links.and_then(|l| l.image_href.as_ref().map(ToString::to_string)),
+        ),
+        (
+            F::ImageId,
+            links.and_then(|l| l.image_id.as_ref().map(ToString::to_string)),
+        ),
+    ]
+}
+
+fn added<T: PartialEq + Clone>(from: &[T], to: &[T]) -> Vec<T> {
+    to.iter().filter(|x| !from.contains(x)).cloned().collect()
+}
+
+fn custom_links(place: &Place) -> &[CustomLink] {
+    place
+        .links
+        .as_ref()
+        .map(|l| l.custom.as_slice())
+        .unwrap_or_default()
+}
+
+/// Compares two revisions of the same place field by field.
+pub fn diff_places(from: &Place, to: &Place) -> PlaceDiff {
+    let changed_fields = scalar_fields(from)
+        .into_iter()
+        .zip(scalar_fields(to))
+        .filter(|((_, from), (_, to))| from != to)
+        .map(|((field, from), (_, to))| ChangedPlaceField { field, from, to })
+        .collect();
+    let moved = if from.location.pos != to.location.pos {
+        Some(MovedPlace {
+            from: from.location.pos,
+            to: to.location.pos,
+            distance: MapPoint::distance(from.location.pos, to.location.pos),
+        })
+    } else {
+        None
+    };
+    PlaceDiff {
+        id: to.id.clone(),
+        from: from.revision,
+        to: to.revision,
+        changed_fields,
+        added_tags: added(&from.tags, &to.tags),
+        removed_tags: added(&to.tags, &from.tags),
+        added_custom_links: added(custom_links(from), custom_links(to)),
+        removed_custom_links: added(custom_links(to), custom_links(from)),
+        moved,
+    }
+}
+
+/// Loads two revisions of a place and returns their difference.
+pub fn diff_place_revisions<R: PlaceRepo>(
+    repo: &R,
+    id: &str,
+    from: Revision,
+    to: Revision,
+) -> Result<PlaceDiff> {
+    let (from, _) = repo.load_place_revision(id, from)?;
+    let (to, _) = repo.load_place_revision(id, to)?;
+    Ok(diff_places(&from, &to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn place() -> Place {
+        Place::build()
+            .id("foo")
+            .title("foo")
+            .description("bar")
+            .pos(MapPoint::from_lat_lng_deg(48.0, 9.0))
+            .tags(vec!["a", "b"])
+            .finish()
+    }
+
+    #[test]
+    fn diff_of_unchanged_place_is_empty() {
+        let p = place();
+        let diff = diff_places(&p, &p);
+        assert!(diff.changed_fields.is_empty());
+        assert!(diff.added_tags.is_empty());
+        assert!(diff.removed_tags.is_empty());
+        assert!(diff.added_custom_links.is_empty());
+        assert!(diff.removed_custom_links.is_empty());
+        assert!(diff.moved.is_none());
+    }
+
+    #[test]
+    fn diff_scalars_tags_links_and_position() {
+        let from = place();
+        let mut to = place();
+        to.revision = Revision::from(1);
+        to.title = "baz".into();
+        to.contact = Some(Contact {
+            name: None,
+            email: Some("foo@example.com".into()),
+            phone: None,
+        });
+        to.tags = vec!["b".into(), "c".into()];
+        to.links = Some(Links {
+            custom: vec![CustomLink::from_url("https://example.com".parse().unwrap())],
+            ..Default::default()
+        });
+        to.location.pos = MapPoint::from_lat_lng_deg(48.001, 9.0);
+
+        let diff = diff_places(&from, &to);
+        assert_eq!(Revision::initial(), diff.from);
+        assert_eq!(Revision::from(1), diff.to);
+        assert_eq!(
+            vec![
+                ChangedPlaceField {
+                    field: PlaceDiffField::Title,
+                    from: Some("foo".into()),
+                    to: Some("baz".into()),
+                },
+                ChangedPlaceField {
+                    field: PlaceDiffField::ContactEmail,
+                    from: None,
+                    to: Some("foo@example.com".into()),
+                },
+            ],
+            diff.changed_fields
+        );
+        assert_eq!(vec!["c".to_string()], diff.added_tags);
+        assert_eq!(vec!["a".to_string()], diff.removed_tags);
+        assert_eq!(1, diff.added_custom_links.len());
+        assert!(diff.removed_custom_links.is_empty());
+        let meters = diff.moved.unwrap().distance.unwrap().to_meters();
+        assert!(meters > 100.0 && meters < 120.0);
+    }
+}
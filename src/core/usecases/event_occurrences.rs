@@ -0,0 +1,126 @@
+use crate::core::prelude::*;
+use chrono::prelude::*;
+use ofdb_core::recurrence::occurrences;
+
+/// Expands a recurring event into its occurrences that start
+/// within the given (inclusive) bounds. Each occurrence keeps
+/// the id of the event and refers to it as its series. Other
+/// events are returned unmodified.
+pub fn expand_event_occurrences(
+    event: Event,
+    start_min: Option<NaiveDateTime>,
+    start_max: Option<NaiveDateTime>,
+    limit: usize,
+) -> Vec<Event> {
+    let recurrence = match event.recurrence {
+        Some(ref recurrence) => recurrence,
+        None => return vec![event],
+    };
+    let duration = event.end.map(|end| end - event.start);
+    occurrences(event.start, recurrence)
+        .skip_while(|start| start_min.map(|min| *start < min).unwrap_or(false))
+        .take_while(|start| start_max.map(|max| *start <= max).unwrap_or(true))
+        .take(limit)
+        .map(|start| Event {
+            start,
+            end: duration.map(|duration| start + duration),
+            recurrence: None,
+            series_id: Some(event.id.clone()),
+            ..event.clone()
+        })
+        .collect()
+}
+
+/// Removes a single occurrence from a recurring event by adding
+/// its start time to the excluded dates.
+pub fn exclude_event_occurrence<D: Db>(
+    db: &D,
+    id: &str,
+    occurrence: NaiveDateTime,
+) -> Result<Event> {
+    let mut event = db.get_event(id)?;
+    let start = event.start;
+    let recurrence = event.recurrence.as_mut().ok_or(RepoError::NotFound)?;
+    let is_occurrence = occurrences(start, recurrence)
+        .take_while(|start| *start <= occurrence)
+        .any(|start| start == occurrence);
+    if !is_occurrence {
+        return Err(RepoError::NotFound.into());
+    }
+    recurrence.exdates.push(occurrence);
+    recurrence.exdates.sort_unstable();
+    db.update_event(&event)?;
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::tests::MockDb, *};
+
+    fn event(rrule: Option<&str>) -> Event {
+        Event {
+            id: "series".into(),
+            title: "Weekly".into(),
+            description: None,
+            start: NaiveDate::from_ymd(2021, 3, 1).and_hms(18, 0, 0),
+            end: Some(NaiveDate::from_ymd(2021, 3, 1).and_hms(20, 0, 0)),
+            location: None,
+            contact: None,
+            homepage: None,
+            tags: vec![],
+            created_by: None,
+            registration: None,
+            archived: None,
+            image_url: None,
+            image_link_url: None,
+            image_id: None,
+            recurrence: rrule.map(|rrule| Recurrence {
+                rule: rrule.parse().unwrap(),
+                exdates: vec![],
+            }),
+            series_id: None,
+        }
+    }
+
+    #[test]
+    fn expand_occurrences_within_bounds() {
+        let e = event(Some("FREQ=WEEKLY;COUNT=5"));
+        let start_min = NaiveDate::from_ymd(2021, 3, 8).and_hms(0, 0, 0);
+        let start_max = NaiveDate::from_ymd(2021, 3, 22).and_hms(18, 0, 0);
+        let occurrences = expand_event_occurrences(e, Some(start_min), Some(start_max), 10);
+        assert_eq!(3, occurrences.len());
+        let first = &occurrences[0];
+        assert_eq!(start_min.date().and_hms(18, 0, 0), first.start);
+        assert_eq!(Some(start_min.date().and_hms(20, 0, 0)), first.end);
+        assert_eq!(Some("series".into()), first.series_id);
+        assert!(first.recurrence.is_none());
+        assert_eq!(start_max, occurrences[2].start);
+
+        let occurrences =
+            expand_event_occurrences(event(Some("FREQ=DAILY")), Some(start_min), None, 4);
+        assert_eq!(4, occurrences.len());
+
+        let single = expand_event_occurrences(event(None), Some(start_min), None, 10);
+        assert_eq!(1, single.len());
+        assert!(single[0].series_id.is_none());
+    }
+
+    #[test]
+    fn exclude_occurrences() {
+        let db = MockDb::default();
+        db.events.borrow_mut().push(event(Some("FREQ=WEEKLY")));
+        db.events.borrow_mut().push(Event {
+            id: "single".into(),
+            ..event(None)
+        });
+        let second = NaiveDate::from_ymd(2021, 3, 8).and_hms(18, 0, 0);
+        let e = exclude_event_occurrence(&db, "series", second).unwrap();
+        assert_eq!(vec![second], e.recurrence.unwrap().exdates);
+        let stored = db.get_event("series").unwrap();
+        assert_eq!(vec![second], stored.recurrence.unwrap().exdates);
+        // Not an occurrence
+        assert!(exclude_event_occurrence(&db, "series", second.date().and_hms(17, 0, 0)).is_err());
+        // Not a recurring event
+        assert!(exclude_event_occurrence(&db, "single", second).is_err());
+    }
+}
@@ -0,0 +1,91 @@
+use crate::core::{
+    prelude::*,
+    util::{geo::MapBbox, validate},
+};
+use std::collections::HashSet;
+
+// Recently changed places are loaded in chunks until the
+// requested number of places has been found
+const RECENT_CHANGES_CHUNK_SIZE: u64 = 100;
+
+// Limits the number of changes that are scanned for a single feed
+const MAX_SCANNED_RECENT_CHANGES: u64 = 1000;
+
+#[derive(Debug, Clone, Default)]
+pub struct RecentlyChangedPlacesQuery {
+    pub bbox: Option<MapBbox>,
+    pub tag: Option<String>,
+    pub since: Option<TimestampMs>,
+    pub limit: usize,
+}
+
+// Returns the most recent change of each place, starting with
+// the latest. Places that have been rejected or archived by their
+// latest change are omitted.
+pub fn recently_changed_places<R: PlaceRepo>(
+    repo: &R,
+    query: &RecentlyChangedPlacesQuery,
+) -> Result<Vec<(Place, ActivityLog)>> {
+    let RecentlyChangedPlacesQuery {
+        bbox,
+        tag,
+        since,
+        limit,
+    } = query;
+    if let Some(bbox) = bbox {
+        validate::bbox(bbox)?;
+    }
+    let tag = tag
+        .as_deref()
+        .map(|tag| super::prepare_tag_list(Some(tag)))
+        .map(|mut tags| {
+            if tags.len() == 1 {
+                Ok(tags.remove(0))
+            } else {
+                Err(ParameterError::InvalidTag(
+                    query.tag.clone().unwrap_or_default(),
+                ))
+            }
+        })
+        .transpose()?;
+    let params = RecentlyChangedEntriesParams {
+        since: *since,
+        until: None,
+    };
+    let mut visited_ids = HashSet::new();
+    let mut results = Vec::with_capacity(*limit);
+    let mut offset = 0;
+    while results.len() < *limit && offset < MAX_SCANNED_RECENT_CHANGES {
+        let pagination = Pagination {
+            offset: Some(offset),
+            limit: Some(RECENT_CHANGES_CHUNK_SIZE),
+        };
+        let changes = repo.recently_changed_places(&params, &pagination)?;
+        let count = changes.len() as u64;
+        for (place, status, activity_log) in changes {
+            // Only the latest change of each place is considered
+            if !visited_ids.insert(place.id.to_string()) || !status.exists() {
+                continue;
+            }
+            if let Some(bbox) = bbox {
+                if !bbox.contains_point(place.location.pos) {
+                    continue;
+                }
+            }
+            if let Some(tag) = &tag {
+                if !place.tags.contains(tag) {
+                    continue;
+                }
+            }
+            results.push((place, activity_log));
+            if results.len() >= *limit {
+                break;
+            }
+        }
+        if count < RECENT_CHANGES_CHUNK_SIZE {
+            break;
+        }
+        offset += count;
+    }
+    Ok(results)
+}
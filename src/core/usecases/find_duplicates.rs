@@ -316,6 +316,7 @@ mod tests {
             license: "CC0-1.0".into(),
             image_url: None,
             image_link_url: None,
+            image_id: None,
             custom_links: vec![],
         };
         let new_y = NewPlace {
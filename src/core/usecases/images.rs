@@ -0,0 +1,85 @@
+use crate::core::prelude::*;
+use ofdb_core::gateways::image::ImageStorageGateway;
+
+/// An uploaded image that has already been re-encoded
+/// together with all of its thumbnails.
+#[derive(Debug, Clone)]
+pub struct NewImage {
+    pub format: ImageFormat,
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+    pub thumbnails: Vec<(ThumbnailSize, Vec<u8>)>,
+}
+
+pub fn store_image<R: ImageRepo>(
+    repo: &R,
+    storage: &dyn ImageStorageGateway,
+    new_image: NewImage,
+    created_by: Option<Email>,
+) -> Result<Image> {
+    let NewImage {
+        format,
+        width,
+        height,
+        data,
+        thumbnails,
+    } = new_image;
+    let image = Image {
+        id: Id::new(),
+        format,
+        width,
+        height,
+        created_at: TimestampMs::now(),
+        created_by,
+    };
+    let mut stored_keys = vec![];
+    let mut store = || -> Result<()> {
+        let key = image.storage_key(None);
+        storage.store(&key, &data).map_err(RepoError::from)?;
+        stored_keys.push(key);
+        for (size, data) in &thumbnails {
+            let key = image.storage_key(Some(*size));
+            storage.store(&key, data).map_err(RepoError::from)?;
+            stored_keys.push(key);
+        }
+        Ok(repo.create_image(&image)?)
+    };
+    if let Err(err) = store() {
+        // Don't leave any orphaned files behind
+        for key in stored_keys {
+            if let Err(err) = storage.delete(&key) {
+                warn!("Failed to delete stored image {}: {}", key, err);
+            }
+        }
+        return Err(err);
+    }
+    debug!("Stored image {} ({}x{})", image.id, width, height);
+    Ok(image)
+}
+
+/// Loads the data of either the original image or one of its
+/// thumbnails.
+pub fn load_image_data<R: ImageRepo>(
+    repo: &R,
+    storage: &dyn ImageStorageGateway,
+    id: &Id,
+    size: Option<ThumbnailSize>,
+) -> Result<(Image, Vec<u8>)> {
+    let image = repo.get_image(id)?;
+    let data = storage
+        .load(&image.storage_key(size))
+        .map_err(RepoError::from)?
+        .ok_or(RepoError::NotFound)?;
+    Ok((image, data))
+}
+
+/// Verifies that an image that should be attached to a place
+/// or an event has actually been uploaded before.
+pub fn check_image_exists<R: ImageRepo>(repo: &R, id: &Id) -> Result<()> {
+    match repo.get_image(id) {
+        Ok(_) => Ok(()),
+        Err(RepoError::NotFound) => Err(ParameterError::UnknownImage(id.to_string()).into()),
+        Err(err) => Err(err.into()),
+    }
+}
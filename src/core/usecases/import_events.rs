@@ -0,0 +1,78 @@
+use super::{
+    archive_events, import_new_event, store_created_event, store_updated_event, NewEvent,
+    NewEventMode,
+};
+use crate::core::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+// An event from an external calendar that is identified by
+// a unique id within this calendar.
+#[derive(Debug, Clone)]
+pub struct ExternalEvent {
+    pub uid: String,
+    pub new_event: NewEvent,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportedEvents {
+    pub created: Vec<Id>,
+    pub updated: Vec<Id>,
+    pub archived: Vec<Id>,
+    // Events that have been rejected, identified by their external UID
+    pub failed: Vec<(String, Error)>,
+}
+
+// Synchronizes all events of an organization that have been imported
+// from an external calendar. Events are matched by their external UID.
+// Previously imported events that are missing are archived.
+// Invalid events are skipped and reported.
+// The organization must have been authorized in advance.
+pub fn import_events<D: Db>(
+    db: &D,
+    org_id: &Id,
+    external_events: Vec<ExternalEvent>,
+) -> Result<ImportedEvents> {
+    let org = db.get_org(org_id)?;
+    let mut imported_ids: HashMap<_, _> =
+        db.load_external_event_uids(&org.id)?.into_iter().collect();
+    let mut imported = ImportedEvents::default();
+    let mut external_uids = HashSet::with_capacity(external_events.len());
+    for ExternalEvent { uid, new_event } in external_events {
+        if !external_uids.insert(uid.clone()) {
+            warn!("Skipping duplicate event {} in calendar", uid);
+            continue;
+        }
+        let mode = match imported_ids.get(&uid) {
+            Some(id) => NewEventMode::Update(id.as_str()),
+            None => NewEventMode::Create,
+        };
+        let storable = match import_new_event(db, Some(&org.id), new_event, mode) {
+            Ok(storable) => storable,
+            Err(err) => {
+                info!("Failed to import event {}: {}", uid, err);
+                imported.failed.push((uid, err));
+                continue;
+            }
+        };
+        if let Some(id) = imported_ids.get(&uid) {
+            store_updated_event(db, storable)?;
+            imported.updated.push(id.clone());
+        } else {
+            let event = store_created_event(db, storable)?;
+            db.store_external_event_uid(&org.id, &uid, event.id.as_str())?;
+            imported_ids.insert(uid, event.id.clone());
+            imported.created.push(event.id);
+        }
+    }
+    // Invalid events are not archived, they are still in the calendar
+    imported.archived = imported_ids
+        .into_iter()
+        .filter(|(uid, _)| !external_uids.contains(uid))
+        .map(|(_, id)| id)
+        .collect();
+    if !imported.archived.is_empty() {
+        let ids: Vec<_> = imported.archived.iter().map(Id::as_str).collect();
+        archive_events(db, &ids, Some(org.id.as_str()))?;
+    }
+    Ok(imported)
+}
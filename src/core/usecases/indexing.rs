@@ -14,10 +14,52 @@ pub fn reindex_place(
     Ok(avg_ratings)
 }
 
-pub fn index_event(indexer: &dyn EventIndexer, event: &Event) -> Fallible<()> {
-    indexer.add_or_update_event(event)
+// Reindex the given places, e.g. when rebuilding the search
+// index in chunks. The index is not flushed.
+pub fn reindex_places<D, I>(db: &D, indexer: &I, ids: &[&str]) -> Result<()>
+where
+    D: PlaceRepo + RatingRepository,
+    I: PlaceIndexer + ?Sized,
+{
+    if ids.is_empty() {
+        // Loading places with an empty list of ids would
+        // return all places!
+        return Ok(());
+    }
+    let places = db.get_places(ids)?;
+    for (place, status) in places {
+        let ratings = db.load_ratings_of_place(place.id.as_ref())?;
+        if let Err(err) =
+            indexer.add_or_update_place(&place, status, &place.avg_ratings(&ratings[..]))
+        {
+            error!("Failed to index place {:?}: {}", place, err);
+        }
+    }
+    Ok(())
 }
 
-pub fn unindex_event(indexer: &dyn EventIndexer, id: &Id) -> Fallible<()> {
-    indexer.remove_by_id(id)
+// Reindex the given events, e.g. when rebuilding the search
+// index in chunks. Events that don't exist anymore or that
+// have been archived are removed from the index. The index
+// is not flushed.
+pub fn reindex_events<D, I>(db: &D, indexer: &I, ids: &[&str]) -> Result<()>
+where
+    D: EventGateway,
+    I: EventIndexer + ?Sized,
+{
+    let events = db.get_events_chronologically(ids)?;
+    for event in &events {
+        if let Err(err) = indexer.add_or_update_event(event) {
+            error!("Failed to index event {:?}: {}", event, err);
+        }
+    }
+    for id in ids {
+        if events.iter().any(|e| e.id.as_str() == *id) {
+            continue;
+        }
+        if let Err(err) = indexer.remove_by_id(&Id::from(*id)) {
+            error!("Failed to remove event {} from index: {}", id, err);
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,140 @@
+use super::UpdatePlace;
+use crate::core::prelude::*;
+
+/// The review context of both places of a merge.
+pub const MERGE_REVIEW_CONTEXT: &str = "merge";
+
+/// The fields of a place that can be taken from either place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceMergeField {
+    Title,
+    Description,
+    Location,
+    Contact,
+    OpeningHours,
+    FoundedOn,
+    Links,
+    Tags,
+}
+
+#[derive(Debug, Clone)]
+pub struct MergePlaces {
+    /// The place that is kept
+    pub survivor_id: Id,
+    /// The new revision of the surviving place (optimistic locking)
+    pub version: u64,
+    /// The place that is archived and redirected to the survivor
+    pub merged_id: Id,
+    /// Fields that are taken from the merged place instead of
+    /// the survivor
+    pub fields_from_merged: Vec<PlaceMergeField>,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MergedPlaces {
+    /// The new revision of the surviving place
+    pub place: Place,
+    /// Organizations that need to clear the new revision
+    pub clearance_org_ids: Vec<Id>,
+    pub moved_rating_count: usize,
+}
+
+fn take_field(place: &mut Place, from: &Place, field: PlaceMergeField) {
+    use PlaceMergeField as F;
+    match field {
+        F::Title => place.title = from.title.clone(),
+        F::Description => place.description = from.description.clone(),
+        F::Location => place.location = from.location.clone(),
+        F::Contact => place.contact = from.contact.clone(),
+        F::OpeningHours => place.opening_hours = from.opening_hours.clone(),
+        F::FoundedOn => place.founded_on = from.founded_on,
+        F::Links => place.links = from.links.clone(),
+        F::Tags => place.tags = from.tags.clone(),
+    }
+}
+
+fn merge_comment(summary: String, comment: Option<&str>) -> String {
+    match comment.map(str::trim).filter(|c| !c.is_empty()) {
+        Some(comment) => format!("{}: {}", summary, comment),
+        None => summary,
+    }
+}
+
+/// Merges a duplicate place into another place.
+///
+/// The surviving place is updated with a new revision that
+/// combines the fields of both places. All ratings and their
+/// comments are moved to the survivor. The merged place is
+/// archived and redirected to the survivor. Both places record
+/// the merge in their review history.
+pub fn merge_places<D: Db>(db: &D, merge: MergePlaces, merged_by: &str) -> Result<MergedPlaces> {
+    let MergePlaces {
+        survivor_id,
+        version,
+        merged_id,
+        fields_from_merged,
+        comment,
+    } = merge;
+    if survivor_id == merged_id {
+        return Err(ParameterError::MergeSamePlace.into());
+    }
+    let (mut place, survivor_status) = db.get_place(survivor_id.as_str())?;
+    let (merged, merged_status) = db.get_place(merged_id.as_str())?;
+    if !survivor_status.exists() || !merged_status.exists() {
+        return Err(RepoError::NotFound.into());
+    }
+    for field in fields_from_merged {
+        take_field(&mut place, &merged, field);
+    }
+    info!("Merging place {} into {}", merged_id, survivor_id);
+
+    // The new revision is validated and authorized like
+    // any other update of the survivor
+    let update = UpdatePlace {
+        version,
+        ..UpdatePlace::from(place)
+    };
+    let storable =
+        super::prepare_updated_place(db, survivor_id.clone(), update, Some(merged_by), None)?;
+    let clearance_org_ids = storable.clearance_org_ids().to_vec();
+    let (place, _) = super::store_updated_place(db, storable)?;
+
+    let moved_rating_count = db.move_ratings_of_place(merged_id.as_str(), survivor_id.as_str())?;
+
+    let activity = Activity::now(Some(merged_by.into()));
+    let archived = ActivityLog {
+        activity: activity.clone(),
+        context: Some(MERGE_REVIEW_CONTEXT.to_string()),
+        comment: Some(merge_comment(
+            format!("Merged into {}", survivor_id),
+            comment.as_deref(),
+        )),
+    };
+    db.review_places(&[merged_id.as_str()], ReviewStatus::Archived, &archived)?;
+    let survived = ActivityLog {
+        activity,
+        context: Some(MERGE_REVIEW_CONTEXT.to_string()),
+        comment: Some(merge_comment(
+            format!("Merged with {}", merged_id),
+            comment.as_deref(),
+        )),
+    };
+    db.log_place_review(survivor_id.as_str(), &survived)?;
+    db.create_place_redirect(merged_id.as_str(), survivor_id.as_str())?;
+
+    Ok(MergedPlaces {
+        place,
+        clearance_org_ids,
+        moved_rating_count,
+    })
+}
+
+/// The id of the place that a place has been merged into
+/// or the given id if it has not been merged.
+pub fn resolve_place_redirect<R: PlaceRepo>(repo: &R, id: &str) -> Result<String> {
+    Ok(repo
+        .get_place_redirect(id)?
+        .map(String::from)
+        .unwrap_or_else(|| id.to_string()))
+}
@@ -1,50 +1,65 @@
 use crate::core::{
     error::ParameterError,
     prelude::*,
-    util::{
-        geo::{MapBbox, MapPoint},
-        parse::parse_url_param,
-        validate,
-    },
+    util::{geo::MapBbox, parse::parse_url_param, validate},
 };
+use ofdb_core::opening_hours::WeeklySchedule;
 
+mod api_tokens;
 mod archive_comments;
 mod archive_events;
 mod archive_ratings;
+mod audit_log;
 mod authorize;
+mod bbox_subscriptions;
 mod change_user_role;
 pub mod clearance;
 mod confirm_email;
 mod confirm_email_and_reset_password;
 mod create_new_place;
 mod create_new_user;
+mod create_organization;
 mod delete_event;
+mod diff_place_revisions;
+mod event_occurrences;
 mod export_event;
 mod export_place;
+mod feeds;
 mod filter_event;
 mod filter_place;
 mod find_duplicates;
+mod images;
+mod import_events;
 mod indexing;
 mod load_places;
 mod login;
+mod merge_places;
+mod organizations;
 mod query_events;
 mod rate_place;
 mod register;
+mod revert_place;
 mod review_places;
 mod search;
 mod store_event;
 mod update_place;
+mod user_data;
 mod user_tokens;
+mod webhooks;
 
 #[cfg(test)]
 pub mod tests;
 
 pub use self::{
-    archive_comments::*, archive_events::*, archive_ratings::*, authorize::*, change_user_role::*,
-    confirm_email::*, confirm_email_and_reset_password::*, create_new_place::*, create_new_user::*,
-    delete_event::*, export_event::*, export_place::*, filter_event::*, filter_place::*,
-    find_duplicates::*, indexing::*, load_places::*, login::*, query_events::*, rate_place::*,
-    register::*, review_places::*, search::*, store_event::*, update_place::*, user_tokens::*,
+    api_tokens::*, archive_comments::*, archive_events::*, archive_ratings::*, audit_log::*,
+    authorize::*, bbox_subscriptions::*, change_user_role::*, confirm_email::*,
+    confirm_email_and_reset_password::*, create_new_place::*, create_new_user::*,
+    create_organization::*, delete_event::*, diff_place_revisions::*, event_occurrences::*,
+    export_event::*, export_place::*, feeds::*, filter_event::*, filter_place::*,
+    find_duplicates::*, images::*, import_events::*, indexing::*, load_places::*, login::*,
+    merge_places::*, organizations::*, query_events::*, rate_place::*, register::*,
+    revert_place::*, review_places::*, search::*, store_event::*, update_place::*, user_data::*,
+    user_tokens::*, webhooks::*,
 };
 
 //TODO: move usecases into separate files
@@ -77,6 +92,9 @@ pub struct EventQuery {
     pub start_max: Option<Timestamp>,
     pub tags: Option<Vec<String>>,
     pub text: Option<String>,
+    // Only events with this moderated tag in their last
+    // cleared revision
+    pub org_tag: Option<String>,
 
     pub limit: Option<usize>,
 }
@@ -90,6 +108,7 @@ impl EventQuery {
             ref start_max,
             ref tags,
             ref text,
+            ref org_tag,
             ref limit,
         } = self;
         bbox.is_none()
@@ -98,6 +117,7 @@ impl EventQuery {
             && start_max.is_none()
             && tags.is_none()
             && text.is_none()
+            && org_tag.is_none()
             && limit.is_none()
     }
 }
@@ -106,10 +126,21 @@ pub fn delete_user(db: &dyn Db, login_email: &str, email: &str) -> Result<()> {
     if login_email != email {
         return Err(Error::Parameter(ParameterError::Forbidden));
     }
-    Ok(db.delete_user_by_email(email)?)
+    db.delete_user_by_email(email)?;
+    let entry = AuditLogEntry::now(
+        Some(login_email),
+        AuditAction::DeleteUser,
+        vec![email.to_string()],
+    );
+    record_audit_log(db, entry)
 }
 
-pub fn subscribe_to_bbox(db: &dyn Db, user_email: String, bbox: MapBbox) -> Result<()> {
+pub fn subscribe_to_bbox(
+    db: &dyn Db,
+    user_email: String,
+    bbox: MapBbox,
+    locale: Option<Locale>,
+) -> Result<()> {
     validate::bbox(&bbox)?;
 
     // TODO: support multiple subscriptions in KVM (frontend)
@@ -122,6 +153,13 @@ pub fn subscribe_to_bbox(db: &dyn Db, user_email: String, bbox: MapBbox) -> Resu
         id,
         user_email,
         bbox,
+        locale,
+        tags: vec![],
+        categories: vec![],
+        // Kept for backwards compatibility
+        events: true,
+        delivery: SubscriptionDelivery::Immediate,
+        last_digest_at: None,
     })?;
     Ok(())
 }
@@ -138,24 +176,6 @@ pub fn get_bbox_subscriptions(db: &dyn Db, user_email: &str) -> Result<Vec<BboxS
         .collect())
 }
 
-pub fn bbox_subscriptions_by_coordinate(
-    db: &dyn Db,
-    pos: MapPoint,
-) -> Result<Vec<BboxSubscription>> {
-    Ok(db
-        .all_bbox_subscriptions()?
-        .into_iter()
-        .filter(|s| s.bbox.contains_point(pos))
-        .collect())
-}
-
-pub fn email_addresses_by_coordinate(db: &dyn Db, pos: MapPoint) -> Result<Vec<String>> {
-    Ok(bbox_subscriptions_by_coordinate(db, pos)?
-        .into_iter()
-        .map(|s| s.user_email)
-        .collect())
-}
-
 pub fn prepare_tag_list<'a>(tags: impl IntoIterator<Item = &'a str>) -> Vec<String> {
     let mut tags: Vec<_> = tags
         .into_iter()
@@ -209,3 +229,23 @@ fn parse_custom_link_param(from: CustomLinkParam) -> Result<CustomLink> {
         description,
     })
 }
+
+// Only opening hours that can be evaluated are accepted, except
+// for unchanged opening hours of an existing place that have been
+// stored before they were validated.
+fn parse_opening_hours_param(from: String, stored: Option<&OpeningHours>) -> Result<OpeningHours> {
+    let opening_hours: OpeningHours = from.parse().map_err(|_| {
+        ParameterError::InvalidOpeningHours(format!(
+            "At least {} characters are required",
+            OpeningHours::min_len()
+        ))
+    })?;
+    if stored == Some(&opening_hours) {
+        return Ok(opening_hours);
+    }
+    opening_hours
+        .as_str()
+        .parse::<WeeklySchedule>()
+        .map_err(|err| ParameterError::InvalidOpeningHours(err.to_string()))?;
+    Ok(opening_hours)
+}
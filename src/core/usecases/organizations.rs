@@ -0,0 +1,146 @@
+use super::create_organization::{prepare_moderated_tag, prepare_organization_name};
+use crate::core::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct UpdateOrganization {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnedEntries {
+    pub place_ids: Vec<Id>,
+    pub event_ids: Vec<Id>,
+}
+
+pub fn update_organization<D: OrganizationRepo>(
+    db: &mut D,
+    id: &Id,
+    update: UpdateOrganization,
+) -> Result<Organization> {
+    let UpdateOrganization { name } = update;
+    let mut org = db.get_org(id)?;
+    org.name = prepare_organization_name(&name)?;
+    debug!("Updating organization: {} ({})", org.name, org.id);
+    db.update_org(&org)?;
+    Ok(org)
+}
+
+pub fn delete_organization<D: OrganizationRepo>(db: &mut D, id: &Id) -> Result<()> {
+    debug!("Deleting organization {}", id);
+    Ok(db.delete_org(id)?)
+}
+
+/// Adds a moderated tag to an organization or replaces the
+/// flags of an existing tag with the same label.
+pub fn add_moderated_tag<D: OrganizationRepo>(
+    db: &mut D,
+    org_id: &Id,
+    tag: ModeratedTag,
+) -> Result<Organization> {
+    let tag = prepare_moderated_tag(tag)?;
+    let mut org = db.get_org(org_id)?;
+    if let Some(existing) = org.moderated_tags.iter_mut().find(|t| t.label == tag.label) {
+        *existing = tag;
+    } else {
+        org.moderated_tags.push(tag);
+    }
+    db.update_org(&org)?;
+    Ok(org)
+}
+
+pub fn remove_moderated_tag<D: OrganizationRepo>(
+    db: &mut D,
+    org_id: &Id,
+    label: &str,
+) -> Result<Organization> {
+    let label = prepare_moderated_tag(ModeratedTag::from(label))?.label;
+    let mut org = db.get_org(org_id)?;
+    let count = org.moderated_tags.len();
+    org.moderated_tags.retain(|t| t.label != label);
+    if org.moderated_tags.len() == count {
+        return Err(RepoError::NotFound.into());
+    }
+    db.update_org(&org)?;
+    Ok(org)
+}
+
+pub fn load_owned_entries<D: OrganizationRepo>(db: &D, org_id: &Id) -> Result<OwnedEntries> {
+    Ok(OwnedEntries {
+        place_ids: db.load_owned_place_ids(org_id)?,
+        event_ids: db.load_owned_event_ids(org_id)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{tests::MockDb, *};
+
+    fn create_org(db: &mut MockDb) -> Organization {
+        create_organization(
+            db,
+            NewOrganization {
+                name: "Foo".into(),
+                api_token: Some("foo".into()),
+                moderated_tags: vec![ModeratedTag::from("foo")],
+            },
+        )
+        .unwrap()
+        .0
+    }
+
+    #[test]
+    fn update_organization_and_keep_moderated_tags() {
+        let mut db = MockDb::default();
+        let org = create_org(&mut db);
+        let update = UpdateOrganization {
+            name: " Bar ".into(),
+        };
+        update_organization(&mut db, &org.id, update).unwrap();
+        let updated = db.get_org(&org.id).unwrap();
+        assert_eq!("Bar", updated.name);
+        assert_eq!(org.moderated_tags, updated.moderated_tags);
+
+        let update = UpdateOrganization { name: "".into() };
+        assert!(update_organization(&mut db, &org.id, update).is_err());
+    }
+
+    #[test]
+    fn add_and_remove_moderated_tags() {
+        let mut db = MockDb::default();
+        let org = create_org(&mut db);
+        let tag = ModeratedTag {
+            label: "#Bar".into(),
+            allow_add: true,
+            allow_remove: false,
+            require_clearance: true,
+        };
+        add_moderated_tag(&mut db, &org.id, tag).unwrap();
+        let tag = ModeratedTag {
+            allow_add: false,
+            ..ModeratedTag::from("foo")
+        };
+        add_moderated_tag(&mut db, &org.id, tag).unwrap();
+        let tags = db.get_org(&org.id).unwrap().moderated_tags;
+        assert_eq!(2, tags.len());
+        assert_eq!("bar", tags[1].label);
+        assert!(tags[1].allow_add && tags[1].require_clearance);
+        assert!(add_moderated_tag(&mut db, &org.id, ModeratedTag::from("a b")).is_err());
+
+        remove_moderated_tag(&mut db, &org.id, "#Foo").unwrap();
+        assert!(remove_moderated_tag(&mut db, &org.id, "foo").is_err());
+        let tags = db.get_org(&org.id).unwrap().moderated_tags;
+        assert_eq!(
+            vec!["bar"],
+            tags.iter().map(|t| t.label.as_str()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn delete_organization_only_once() {
+        let mut db = MockDb::default();
+        let org = create_org(&mut db);
+        delete_organization(&mut db, &org.id).unwrap();
+        assert!(db.orgs.is_empty());
+        assert!(delete_organization(&mut db, &org.id).is_err());
+    }
+}
@@ -1,8 +1,9 @@
-use super::EventQuery;
+use super::{expand_event_occurrences, EventQuery};
 use crate::core::{
     prelude::*,
     util::{extract_hash_tags, remove_hash_tags},
 };
+use chrono::NaiveDateTime;
 use ofdb_core::{bbox, tag};
 
 const DEFAULT_RESULT_LIMIT: usize = 100;
@@ -20,6 +21,7 @@ pub fn query_events<D: Db>(db: &D, index: &dyn IdIndex, query: EventQuery) -> Re
         start_max,
         tags,
         text,
+        org_tag,
         limit,
     } = query;
 
@@ -30,6 +32,9 @@ pub fn query_events<D: Db>(db: &D, index: &dyn IdIndex, query: EventQuery) -> Re
             hash_tags.push(hashtag.to_owned());
         }
     }
+    if let Some(ref org_tag) = org_tag {
+        hash_tags.push(org_tag.to_owned());
+    }
 
     let text = text.as_deref().map(remove_hash_tags).and_then(|text| {
         if text.trim().is_empty() {
@@ -100,6 +105,14 @@ pub fn query_events<D: Db>(db: &D, index: &dyn IdIndex, query: EventQuery) -> Re
         .collect();
     let mut events = db.get_events_chronologically(&event_ids)?;
 
+    if let Some(ref org_tag) = org_tag {
+        if let Some(org_id) = db.map_tag_to_clearance_org_id(org_tag)? {
+            events = super::clearance::event::clear_repo_results(db, &org_id, org_tag, events)?;
+            // The last cleared revision might start at a different time
+            events.sort_by(|a, b| a.start.cmp(&b.start));
+        }
+    }
+
     if let Some(ref email) = created_by {
         if let Some(user) = db.try_get_user_by_email(email)? {
             events = events
@@ -111,5 +124,17 @@ pub fn query_events<D: Db>(db: &D, index: &dyn IdIndex, query: EventQuery) -> Re
         }
     }
 
+    // Recurring events are replaced by their occurrences
+    if events.iter().any(|e| e.recurrence.is_some()) {
+        let start_min = start_min.map(NaiveDateTime::from);
+        let start_max = start_max.map(NaiveDateTime::from);
+        events = events
+            .into_iter()
+            .flat_map(|e| expand_event_occurrences(e, start_min, start_max, limit))
+            .collect();
+        events.sort_by(|a, b| a.start.cmp(&b.start));
+        events.truncate(limit);
+    }
+
     Ok(events)
 }
@@ -0,0 +1,79 @@
+use super::UpdatePlace;
+use crate::core::prelude::*;
+
+/// The review context of a reverted place.
+pub const REVERT_REVIEW_CONTEXT: &str = "revert";
+
+#[derive(Debug, Clone)]
+pub struct RevertPlace {
+    pub id: Id,
+    /// The earlier revision that is restored
+    pub revision: Revision,
+    /// The new revision of the place (optimistic locking)
+    pub version: u64,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RevertedPlace {
+    /// The new revision of the place
+    pub place: Place,
+    /// Organizations that need to clear the new revision
+    pub clearance_org_ids: Vec<Id>,
+}
+
+/// Reverts a place to an earlier revision.
+///
+/// A new revision is created as a copy of the earlier revision
+/// and is validated and authorized like any other update.
+/// The revert is recorded in the review history of the place.
+pub fn revert_place<D: Db>(
+    db: &D,
+    revert: RevertPlace,
+    reverted_by: &str,
+) -> Result<RevertedPlace> {
+    let RevertPlace {
+        id,
+        revision,
+        version,
+        comment,
+    } = revert;
+    let (current, status) = db.get_place(id.as_str())?;
+    if !status.exists() {
+        return Err(RepoError::NotFound.into());
+    }
+    if revision >= current.revision {
+        return Err(ParameterError::RevertRevision.into());
+    }
+    let (restored, _) = db.load_place_revision(id.as_str(), revision)?;
+    info!(
+        "Reverting place {} to revision {}",
+        id,
+        RevisionValue::from(revision)
+    );
+
+    let update = UpdatePlace {
+        version,
+        ..UpdatePlace::from(restored)
+    };
+    let storable = super::prepare_updated_place(db, id.clone(), update, Some(reverted_by), None)?;
+    let clearance_org_ids = storable.clearance_org_ids().to_vec();
+    let (place, _) = super::store_updated_place(db, storable)?;
+
+    let summary = format!("Reverted to revision {}", RevisionValue::from(revision));
+    let comment = match comment.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+        Some(comment) => format!("{}: {}", summary, comment),
+        None => summary,
+    };
+    let log = ActivityLog {
+        activity: Activity::now(Some(reverted_by.into())),
+        context: Some(REVERT_REVIEW_CONTEXT.to_string()),
+        comment: Some(comment),
+    };
+    db.log_place_review(id.as_str(), &log)?;
+
+    Ok(RevertedPlace {
+        place,
+        clearance_org_ids,
+    })
+}
@@ -1,4 +1,5 @@
 use crate::core::prelude::*;
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Review {
@@ -15,6 +16,38 @@ pub fn review_places<D: Db>(db: &D, ids: &[&str], review: Review) -> Result<usiz
         status,
         comment,
     } = review;
+    // The places are grouped by their previous review status
+    // that is recorded in the audit log
+    let mut ids_by_status: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    // Loading places without any ids would load all places
+    let places = if ids.is_empty() {
+        vec![]
+    } else {
+        db.get_places(ids)?
+    };
+    for (place, old_status) in places {
+        if old_status == status {
+            // Unchanged
+            continue;
+        }
+        ids_by_status
+            .entry(format!("{:?}", old_status).to_lowercase())
+            .or_default()
+            .push(place.id.into());
+    }
+    let entries: Vec<_> = ids_by_status
+        .into_iter()
+        .map(|(old_status, ids)| {
+            let mut entry = AuditLogEntry::now(
+                Some(reviewer_email.as_str()),
+                AuditAction::ReviewPlaces,
+                ids,
+            );
+            entry.before = Some(old_status);
+            entry.after = Some(format!("{:?}", status).to_lowercase());
+            entry
+        })
+        .collect();
     let activity = Activity::now(Some(reviewer_email));
     //  TODO: Verify user role here instead of in web api
     info!(
@@ -33,5 +66,8 @@ pub fn review_places<D: Db>(db: &D, ids: &[&str], review: Review) -> Result<usiz
         place_count,
         ReviewStatusPrimitive::from(status)
     );
+    for entry in entries {
+        super::record_audit_log(db, entry)?;
+    }
     Ok(place_count)
 }
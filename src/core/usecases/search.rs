@@ -1,5 +1,10 @@
 use crate::core::{prelude::*, util};
-use ofdb_core::{bbox, tag};
+use ofdb_core::{
+    bbox,
+    cluster::{ClusterGrid, PlaceCluster, PlaceClusterer},
+    opening_hours::MinuteOfWeek,
+    tag,
+};
 use ofdb_entities::geo::MapBbox;
 
 use std::collections::HashMap;
@@ -14,6 +19,7 @@ pub struct SearchRequest<'a> {
     pub hash_tags  : Vec<&'a str>,
     pub text       : Option<&'a str>,
     pub status     : Vec<ReviewStatus>,
+    pub open_at    : Option<MinuteOfWeek>,
 }
 
 pub fn clear_search_results<D: Db>(
@@ -79,20 +85,18 @@ pub fn clear_search_results<D: Db>(
     Ok(cleared_results)
 }
 
-pub fn search<D: Db>(
-    db: &D,
-    index: &dyn PlaceIndex,
-    req: SearchRequest,
-    limit: usize,
-) -> Result<(Vec<IndexedPlace>, Vec<IndexedPlace>)> {
+// Translates the search request into a query for all
+// places within the bounding box.
+fn visible_places_query(req: SearchRequest) -> IndexQuery {
     let SearchRequest {
-        bbox: visible_bbox,
+        bbox,
         ids,
         categories,
         org_tag,
         hash_tags: req_hash_tags,
         text,
         status,
+        open_at,
     } = req;
 
     let mut hash_tags = text.map(util::extract_hash_tags).unwrap_or_default();
@@ -117,8 +121,8 @@ pub fn search<D: Db>(
         .map(tag::split_text_into_tags)
         .unwrap_or_default();
 
-    let visible_places_query = IndexQuery {
-        include_bbox: Some(visible_bbox),
+    IndexQuery {
+        include_bbox: Some(bbox),
         exclude_bbox: None,
         categories,
         ids,
@@ -126,8 +130,20 @@ pub fn search<D: Db>(
         text_tags,
         text,
         status: Some(status),
+        open_at,
         ..Default::default()
-    };
+    }
+}
+
+pub fn search<D: Db>(
+    db: &D,
+    index: &dyn PlaceIndex,
+    req: SearchRequest,
+    limit: usize,
+) -> Result<(Vec<IndexedPlace>, Vec<IndexedPlace>)> {
+    let visible_bbox = req.bbox;
+    let org_tag = req.org_tag;
+    let visible_places_query = visible_places_query(req);
 
     // 1st query: Search for visible results only
     // This is required to reliably retrieve all available results!
@@ -169,6 +185,37 @@ pub fn search<D: Db>(
     Ok((visible_places, invisible_places))
 }
 
+// Aggregates up to `limit` places within the bounding box
+// into clusters instead of returning them individually.
+pub fn search_clusters<D: Db>(
+    db: &D,
+    index: &dyn PlaceIndex,
+    req: SearchRequest,
+    grid: ClusterGrid,
+    limit: usize,
+) -> Result<Vec<PlaceCluster>> {
+    let org_tag = req.org_tag;
+    let query = visible_places_query(req);
+    if let Some(org_tag) = org_tag {
+        if let Some(org_id) = db.map_tag_to_clearance_org_id(org_tag)? {
+            // Pending clearances can only be applied to individual places
+            let places = index
+                .query_places(&query, limit)
+                .map_err(RepoError::Other)?;
+            let places = clear_search_results(db, &org_id, org_tag, places)?;
+            let mut clusterer = PlaceClusterer::new(grid);
+            for place in &places {
+                clusterer.add_place(place.pos, place.tags.iter().map(String::as_str));
+            }
+            return Ok(clusterer.into_clusters());
+        }
+    }
+    let clusters = index
+        .cluster_places(&query, grid, limit)
+        .map_err(RepoError::Other)?;
+    Ok(clusters)
+}
+
 /// The global search usecase is like the one
 /// of usual internet search engines that exists
 /// of only one single search input.
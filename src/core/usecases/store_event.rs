@@ -32,19 +32,53 @@ pub struct NewEvent {
     pub organizer    : Option<String>,
     pub image_url     : Option<String>,
     pub image_link_url: Option<String>,
+    pub image_id      : Option<String>,
+    pub recurrence    : Option<NewEventRecurrence>,
+}
+
+#[derive(Deserialize, Default, Debug, Clone)]
+pub struct NewEventRecurrence {
+    /// RFC 5545 recurrence rule, e.g. `FREQ=WEEKLY;BYDAY=SA`
+    pub rrule: String,
+    /// Start times of excluded occurrences
+    #[serde(default)]
+    pub exdates: Vec<i64>,
 }
 
 pub enum NewEventMode<'a> {
     Create,
     Update(&'a str),
+    /// Replaces a single occurrence of the given recurring
+    /// event by a new event that refers to the series.
+    UpdateOccurrence(&'a str),
+}
+
+fn parse_recurrence(from: NewEventRecurrence) -> Result<Recurrence> {
+    let NewEventRecurrence { rrule, exdates } = from;
+    let rule = rrule.parse().map_err(|err: RecurrenceRuleParseError| {
+        ParameterError::InvalidRecurrence(err.to_string())
+    })?;
+    let mut exdates = exdates
+        .into_iter()
+        .map(|ts| {
+            NaiveDateTime::from_timestamp_opt(ts, 0).ok_or(ParameterError::DateTimeOutOfRange)
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    exdates.sort_unstable();
+    exdates.dedup();
+    Ok(Recurrence { rule, exdates })
 }
 
 #[derive(Debug, Clone)]
-pub struct Storable(Event);
+pub struct Storable {
+    event: Event,
+    clearance_org_ids: Vec<Id>,
+}
 
+// The organization, if any, must have been authorized in advance.
 pub fn import_new_event<D: Db>(
     db: &D,
-    token: Option<&str>,
+    org_id: Option<&Id>,
     e: NewEvent,
     mode: NewEventMode,
 ) -> Result<Storable> {
@@ -69,21 +103,13 @@ pub fn import_new_event<D: Db>(
         homepage,
         image_url,
         image_link_url,
+        image_id,
+        recurrence,
         ..
     } = e;
-    let org = token
-        .map(|t| {
-            db.get_org_by_api_token(t).map_err(|e| {
-                log::warn!("Unknown or invalid API token: {}", t);
-                match e {
-                    RepoError::NotFound => Error::Parameter(ParameterError::Unauthorized),
-                    _ => Error::Repo(e),
-                }
-            })
-        })
-        .transpose()?;
+    let org = org_id.map(|id| db.get_org(id)).transpose()?;
     let mut new_tags = super::prepare_tag_list(tags.unwrap_or_default().iter().map(String::as_str));
-    let _clearance_org_ids = if let Some(org) = org {
+    let clearance_org_ids = if let Some(org) = org {
         // Implicitly add missing owned tags to prevent events with
         // undefined ownership!
         let org_tag_count = new_tags
@@ -112,7 +138,7 @@ pub fn import_new_event<D: Db>(
                 new_tags.dedup();
                 super::authorize_editing_of_tagged_entry(db, &[], &new_tags, Some(&org))?
             }
-            NewEventMode::Update(id) => {
+            NewEventMode::Update(id) | NewEventMode::UpdateOccurrence(id) => {
                 let old_event = db.get_event(id)?;
                 // Reject update if the organization does not own the event
                 let mut owned_tag_count = 0;
@@ -154,8 +180,6 @@ pub fn import_new_event<D: Db>(
     } else {
         super::authorize_editing_of_tagged_entry(db, &[], &new_tags, None)?
     };
-    // TODO: Record pending clearance for events
-    debug_assert!(_clearance_org_ids.is_empty());
     new_tags.sort_unstable();
     new_tags.dedup();
 
@@ -209,11 +233,22 @@ pub fn import_new_event<D: Db>(
         None
     };
 
-    let id = match mode {
-        NewEventMode::Create => Id::new(),
-        NewEventMode::Update(id) => Id::from(id),
+    let (id, series_id) = match mode {
+        NewEventMode::Create => (Id::new(), None),
+        NewEventMode::Update(id) => (Id::from(id), None),
+        NewEventMode::UpdateOccurrence(series_id) => {
+            if recurrence.is_some() {
+                return Err(ParameterError::InvalidRecurrence(
+                    "A single occurrence cannot recur".into(),
+                )
+                .into());
+            }
+            (Id::new(), Some(Id::from(series_id)))
+        }
     };
 
+    let recurrence = recurrence.map(parse_recurrence).transpose()?;
+
     let created_by = if let Some(ref email) = created_by {
         Some(create_user_from_email(db, email)?.email)
     } else {
@@ -272,6 +307,10 @@ pub fn import_new_event<D: Db>(
     let image_link_url = image_link_url
         .and_then(|ref url| parse_url_param(url).transpose())
         .transpose()?;
+    let image_id = image_id.map(Id::from);
+    if let Some(ref image_id) = image_id {
+        super::check_image_exists(db, image_id)?;
+    }
 
     let event = Event {
         id,
@@ -288,29 +327,60 @@ pub fn import_new_event<D: Db>(
         archived: None,
         image_url,
         image_link_url,
+        image_id,
+        recurrence,
+        series_id,
     };
     let event = event.auto_correct();
     event.validate()?;
-    Ok(Storable(event))
+    Ok(Storable {
+        event,
+        clearance_org_ids,
+    })
 }
 
 pub fn store_created_event<D: Db>(db: &D, storable: Storable) -> Result<Event> {
-    let Storable(event) = storable;
+    let Storable {
+        event,
+        clearance_org_ids,
+    } = storable;
     debug!("Storing newly created event: {:?}", event);
     for t in &event.tags {
         db.create_tag_if_it_does_not_exist(&Tag { id: t.clone() })?;
     }
     db.create_event(event.clone())?;
+    if !clearance_org_ids.is_empty() {
+        let pending_clearance = PendingClearanceForEvent {
+            event_id: event.id.clone(),
+            created_at: TimestampMs::now(),
+            last_cleared_revision: None,
+        };
+        super::clearance::event::add_pending_clearance(db, &clearance_org_ids, &pending_clearance)?;
+    }
     Ok(event)
 }
 
 pub fn store_updated_event<D: Db>(db: &D, storable: Storable) -> Result<Event> {
-    let Storable(event) = storable;
+    let Storable {
+        event,
+        clearance_org_ids,
+    } = storable;
     debug!("Storing updated event: {:?}", event);
     for t in &event.tags {
         db.create_tag_if_it_does_not_exist(&Tag { id: t.clone() })?;
     }
+    // The revision before this update has either been cleared
+    // or is already pending
+    let last_cleared_revision = db.get_event_current_revision(event.id.as_str())?;
     db.update_event(&event)?;
+    if !clearance_org_ids.is_empty() {
+        let pending_clearance = PendingClearanceForEvent {
+            event_id: event.id.clone(),
+            created_at: TimestampMs::now(),
+            last_cleared_revision: Some(last_cleared_revision),
+        };
+        super::clearance::event::add_pending_clearance(db, &clearance_org_ids, &pending_clearance)?;
+    }
     Ok(event)
 }
 
@@ -320,8 +390,8 @@ mod tests {
     use super::super::tests::MockDb;
     use super::*;
 
-    fn create_new_event<D: Db>(db: &D, token: Option<&str>, e: NewEvent) -> Result<Event> {
-        let s = import_new_event(db, token, e, NewEventMode::Create)?;
+    fn create_new_event<D: Db>(db: &D, org_id: Option<&Id>, e: NewEvent) -> Result<Event> {
+        let s = import_new_event(db, org_id, e, NewEventMode::Create)?;
         store_created_event(db, s)
     }
 
@@ -350,6 +420,8 @@ mod tests {
             organizer    : None,
             image_url     : Some("http://somewhere.com/image_url.jpg".to_string()),
             image_link_url: Some("my.url/test.ext".to_string()),
+            image_id      : None,
+            recurrence    : None,
         };
         let mock_db = MockDb::default();
         let id = create_new_event(&mock_db, None, x).unwrap().id;
@@ -397,6 +469,8 @@ mod tests {
             organizer    : None,
             image_url     : None,
             image_link_url: None,
+            image_id      : None,
+            recurrence    : None,
         };
         let mock_db: MockDb = MockDb::default();
         assert!(create_new_event(&mock_db, None, x).is_err());
@@ -426,6 +500,8 @@ mod tests {
             organizer    : None,
             image_url     : None,
             image_link_url: None,
+            image_id      : None,
+            recurrence    : None,
         };
         let mock_db: MockDb = MockDb::default();
         assert!(create_new_event(&mock_db, None, x).is_ok());
@@ -443,6 +519,7 @@ mod tests {
                 email_confirmed: true,
                 password: "secret".parse::<Password>().unwrap(),
                 role: Role::User,
+                locale: None,
             })
             .unwrap();
         let users = mock_db.all_users().unwrap();
@@ -469,9 +546,60 @@ mod tests {
             organizer    : None,
             image_url     : None,
             image_link_url: None,
+            image_id      : None,
+            recurrence    : None,
         };
         assert!(create_new_event(&mock_db, None, x).is_ok());
         let users = mock_db.all_users().unwrap();
         assert_eq!(users.len(), 1);
     }
+
+    #[test]
+    fn update_event_with_uploaded_image() {
+        let mock_db = MockDb::default();
+        mock_db
+            .create_image(&Image {
+                id: "uploaded".into(),
+                format: ImageFormat::Png,
+                width: 320,
+                height: 240,
+                created_at: TimestampMs::now(),
+                created_by: None,
+            })
+            .unwrap();
+        let new_event = |image_id: Option<&str>| NewEvent {
+            title: "foo".into(),
+            start: Utc::now().naive_utc().timestamp(),
+            created_by: Some("foo@bar.tld".into()),
+            image_id: image_id.map(Into::into),
+            ..Default::default()
+        };
+        let id = create_new_event(&mock_db, None, new_event(None))
+            .unwrap()
+            .id;
+
+        match import_new_event(
+            &mock_db,
+            None,
+            new_event(Some("unknown")),
+            NewEventMode::Update(id.as_str()),
+        ) {
+            Err(Error::Parameter(ParameterError::UnknownImage(id))) => assert_eq!("unknown", id),
+            res => panic!("Unexpected result: {:?}", res.map(|_| ())),
+        }
+        assert!(mock_db.events.borrow()[0].image_id.is_none());
+
+        let storable = import_new_event(
+            &mock_db,
+            None,
+            new_event(Some("uploaded")),
+            NewEventMode::Update(id.as_str()),
+        )
+        .unwrap();
+        store_updated_event(&mock_db, storable).unwrap();
+        assert_eq!(
+            Some("uploaded"),
+            mock_db.events.borrow()[0].image_id.as_ref().map(Id::as_str)
+        );
+    }
 }
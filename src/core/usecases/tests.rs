@@ -4,6 +4,7 @@ use crate::core::{usecases, util::geo};
 
 use anyhow::Result as Fallible;
 use chrono::prelude::*;
+use ofdb_core::cluster::{ClusterGrid, PlaceCluster};
 use std::{cell::RefCell, result};
 
 //TODO: move tests to corresponding usecase
@@ -68,6 +69,12 @@ impl Key for Organization {
     }
 }
 
+impl Key for Image {
+    fn key(&self) -> &str {
+        self.id.as_ref()
+    }
+}
+
 #[derive(Default)]
 pub struct MockDb {
     pub entries: RefCell<Vec<(Place, ReviewStatus)>>,
@@ -77,8 +84,54 @@ pub struct MockDb {
     pub ratings: RefCell<Vec<Rating>>,
     pub comments: RefCell<Vec<Comment>>,
     pub bbox_subscriptions: RefCell<Vec<BboxSubscription>>,
+    pub pending_subscription_changes: RefCell<Vec<PendingSubscriptionChange>>,
     pub orgs: Vec<Organization>,
     pub token: RefCell<Vec<UserToken>>,
+    pub api_tokens: RefCell<Vec<ApiToken>>,
+    pub audit_log: RefCell<Vec<AuditLogEntry>>,
+    pub images: RefCell<Vec<Image>>,
+}
+
+impl ApiTokenRepo for MockDb {
+    fn create_api_token(&self, token: &ApiToken) -> RepoResult<()> {
+        self.api_tokens.borrow_mut().push(token.clone());
+        Ok(())
+    }
+    fn get_api_token_by_hash(&self, token_hash: &str) -> RepoResult<ApiToken> {
+        self.api_tokens
+            .borrow()
+            .iter()
+            .find(|t| t.token_hash == token_hash)
+            .cloned()
+            .ok_or(RepoError::NotFound)
+    }
+    fn load_api_tokens_of_org(&self, org_id: &Id) -> RepoResult<Vec<ApiToken>> {
+        Ok(self
+            .api_tokens
+            .borrow()
+            .iter()
+            .filter(|t| &t.org_id == org_id)
+            .cloned()
+            .collect())
+    }
+    fn update_api_token_last_used_at(&self, id: &Id, last_used_at: TimestampMs) -> RepoResult<()> {
+        let mut tokens = self.api_tokens.borrow_mut();
+        let token = tokens
+            .iter_mut()
+            .find(|t| &t.id == id)
+            .ok_or(RepoError::NotFound)?;
+        token.last_used_at = Some(last_used_at);
+        Ok(())
+    }
+    fn revoke_api_token(&self, org_id: &Id, id: &Id, revoked_at: TimestampMs) -> RepoResult<()> {
+        let mut tokens = self.api_tokens.borrow_mut();
+        let token = tokens
+            .iter_mut()
+            .find(|t| &t.org_id == org_id && &t.id == id && t.revoked_at.is_none())
+            .ok_or(RepoError::NotFound)?;
+        token.revoked_at = Some(revoked_at);
+        Ok(())
+    }
 }
 
 impl UserTokenRepo for MockDb {
@@ -152,6 +205,15 @@ impl PlaceIndex for DummySearchEngine {
     fn query_places(&self, _query: &IndexQuery, _limit: usize) -> Fallible<Vec<IndexedPlace>> {
         unimplemented!();
     }
+
+    fn cluster_places(
+        &self,
+        _query: &IndexQuery,
+        _grid: ClusterGrid,
+        _limit: usize,
+    ) -> Fallible<Vec<PlaceCluster>> {
+        unimplemented!();
+    }
 }
 
 impl PlaceIndexer for DummySearchEngine {
@@ -189,15 +251,14 @@ fn create<T: Clone + Key>(objects: &mut Vec<T>, e: T) -> RepoResult<()> {
     Ok(())
 }
 
-fn create_or_replace<T: Clone + Key>(objects: &mut Vec<T>, e: T) -> RepoResult<()> {
+fn create_or_replace<T: Clone + Key>(objects: &mut Vec<T>, e: T) {
     for elem in objects.iter_mut() {
         if elem.key() == e.key() {
             *elem = e;
-            return Ok(());
+            return;
         }
     }
     objects.push(e);
-    Ok(())
 }
 
 fn update<T: Clone + Key>(objects: &mut Vec<T>, e: &T) -> RepoResult<()> {
@@ -214,7 +275,8 @@ impl PlaceRepo for MockDb {
         create_or_replace(
             &mut self.entries.borrow_mut(),
             (place, ReviewStatus::Created),
-        )
+        );
+        Ok(())
     }
     fn get_place(&self, id: &str) -> RepoResult<(Place, ReviewStatus)> {
         get(&self.entries.borrow(), id).and_then(|(p, s)| {
@@ -262,6 +324,23 @@ impl PlaceRepo for MockDb {
     fn count_places(&self) -> RepoResult<usize> {
         self.all_places().map(|v| v.len())
     }
+    fn all_place_ids(&self) -> RepoResult<Vec<Id>> {
+        Ok(self
+            .entries
+            .borrow()
+            .iter()
+            .map(|(p, _)| p.id.clone())
+            .collect())
+    }
+    fn place_ids_changed_since(&self, since: TimestampMs) -> RepoResult<Vec<Id>> {
+        Ok(self
+            .entries
+            .borrow()
+            .iter()
+            .filter(|(p, _)| p.created.at >= since)
+            .map(|(p, _)| p.id.clone())
+            .collect())
+    }
 
     fn review_places(
         &self,
@@ -283,6 +362,18 @@ impl PlaceRepo for MockDb {
     fn load_place_revision(&self, _id: &str, _rev: Revision) -> RepoResult<(Place, ReviewStatus)> {
         unimplemented!();
     }
+
+    fn log_place_review(&self, _id: &str, _activity_log: &ActivityLog) -> RepoResult<()> {
+        unimplemented!();
+    }
+
+    fn create_place_redirect(&self, _old_id: &str, _new_id: &str) -> RepoResult<()> {
+        unimplemented!();
+    }
+
+    fn get_place_redirect(&self, _old_id: &str) -> RepoResult<Option<Id>> {
+        Ok(None)
+    }
 }
 
 impl EventGateway for MockDb {
@@ -325,10 +416,36 @@ impl EventGateway for MockDb {
         Ok(events)
     }
 
+    fn get_event_current_revision(&self, id: &str) -> RepoResult<Revision> {
+        // The mock doesn't store any history
+        get(&self.events.borrow(), id).map(|_| Revision::initial())
+    }
+
+    fn load_event_revision(&self, _id: &str, _rev: Revision) -> RepoResult<Event> {
+        unimplemented!();
+    }
+
     fn count_events(&self) -> RepoResult<usize> {
         self.all_events_chronologically().map(|v| v.len())
     }
 
+    fn all_event_ids(&self) -> RepoResult<Vec<Id>> {
+        self.all_events_chronologically()
+            .map(|v| v.into_iter().map(|e| e.id).collect())
+    }
+
+    fn event_ids_changed_since(&self, _since: TimestampMs) -> RepoResult<Vec<Id>> {
+        unimplemented!();
+    }
+
+    fn load_event_update_times(&self, _ids: &[&str]) -> RepoResult<Vec<(Id, TimestampMs)>> {
+        unimplemented!();
+    }
+
+    fn detached_event_occurrence_ids(&self, _series_ids: &[&str]) -> RepoResult<Vec<Id>> {
+        unimplemented!();
+    }
+
     fn update_event(&self, e: &Event) -> RepoResult<()> {
         update(&mut self.events.borrow_mut(), e)
     }
@@ -344,6 +461,19 @@ impl EventGateway for MockDb {
     fn is_event_owned_by_any_organization(&self, _id: &str) -> RepoResult<bool> {
         unimplemented!();
     }
+
+    fn load_external_event_uids(&self, _org_id: &Id) -> RepoResult<Vec<(String, Id)>> {
+        unimplemented!();
+    }
+
+    fn store_external_event_uid(
+        &self,
+        _org_id: &Id,
+        _external_uid: &str,
+        _id: &str,
+    ) -> RepoResult<()> {
+        unimplemented!();
+    }
 }
 
 impl UserGateway for MockDb {
@@ -441,13 +571,43 @@ impl OrganizationRepo for MockDb {
     fn create_org(&mut self, o: Organization) -> RepoResult<()> {
         create(&mut self.orgs, o)
     }
-    fn get_org_by_api_token(&self, token: &str) -> RepoResult<Organization> {
-        let o = self
-            .orgs
+    fn update_org(&mut self, o: &Organization) -> RepoResult<()> {
+        update(&mut self.orgs, o)
+    }
+    fn delete_org(&mut self, id: &Id) -> RepoResult<()> {
+        let count = self.orgs.len();
+        self.orgs.retain(|o| &o.id != id);
+        if self.orgs.len() == count {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+    fn get_org(&self, id: &Id) -> RepoResult<Organization> {
+        get(&self.orgs, id.as_ref())
+    }
+    fn all_orgs(&self) -> RepoResult<Vec<Organization>> {
+        Ok(self.orgs.clone())
+    }
+    fn load_owned_place_ids(&self, org_id: &Id) -> RepoResult<Vec<Id>> {
+        let org = get(&self.orgs, org_id.as_ref())?;
+        Ok(self
+            .entries
+            .borrow()
             .iter()
-            .find(|o| o.api_token == token)
-            .ok_or(RepoError::NotFound)?;
-        Ok(o.clone())
+            .filter(|(p, _)| org.moderated_tags.iter().any(|t| p.tags.contains(&t.label)))
+            .map(|(p, _)| p.id.clone())
+            .collect())
+    }
+    fn load_owned_event_ids(&self, org_id: &Id) -> RepoResult<Vec<Id>> {
+        let org = get(&self.orgs, org_id.as_ref())?;
+        Ok(self
+            .events
+            .borrow()
+            .iter()
+            .filter(|e| e.archived.is_none())
+            .filter(|e| org.moderated_tags.iter().any(|t| e.tags.contains(&t.label)))
+            .map(|e| e.id.clone())
+            .collect())
     }
     fn map_tag_to_clearance_org_id(&self, tag: &str) -> RepoResult<Option<Id>> {
         Ok(self
@@ -516,6 +676,9 @@ impl RatingRepository for MockDb {
     fn load_place_ids_of_ratings(&self, _ids: &[&str]) -> RepoResult<Vec<String>> {
         unimplemented!();
     }
+    fn move_ratings_of_place(&self, _from_place_id: &str, _to_place_id: &str) -> RepoResult<usize> {
+        unimplemented!();
+    }
     fn archive_ratings(&self, _ids: &[&str], _activity: &Activity) -> RepoResult<usize> {
         unimplemented!();
     }
@@ -570,6 +733,72 @@ impl PlaceClearanceRepo for MockDb {
     }
 }
 
+impl EventClearanceRepo for MockDb {
+    fn add_pending_clearance_for_events(
+        &self,
+        org_ids: &[Id],
+        _pending_clearance: &PendingClearanceForEvent,
+    ) -> RepoResult<usize> {
+        Ok(org_ids.len())
+    }
+
+    fn count_pending_clearances_for_events(&self, _org_id: &Id) -> RepoResult<u64> {
+        Ok(0)
+    }
+
+    fn list_pending_clearances_for_events(
+        &self,
+        _org_id: &Id,
+        _pagination: &Pagination,
+    ) -> RepoResult<Vec<PendingClearanceForEvent>> {
+        Ok(vec![])
+    }
+
+    fn load_pending_clearances_for_events(
+        &self,
+        _org_id: &Id,
+        _event_ids: &[&str],
+    ) -> RepoResult<Vec<PendingClearanceForEvent>> {
+        Ok(vec![])
+    }
+
+    fn update_pending_clearances_for_events(
+        &self,
+        _org_id: &Id,
+        _clearances: &[ClearanceForEvent],
+    ) -> RepoResult<usize> {
+        Ok(0)
+    }
+
+    fn cleanup_pending_clearances_for_events(&self, _org_id: &Id) -> RepoResult<u64> {
+        Ok(0)
+    }
+}
+
+impl AuditLogRepo for MockDb {
+    fn append_audit_log(&self, entry: &AuditLogEntry) -> RepoResult<()> {
+        self.audit_log.borrow_mut().push(entry.clone());
+        Ok(())
+    }
+
+    fn query_audit_log(
+        &self,
+        _query: &AuditLogQuery,
+        _pagination: &Pagination,
+    ) -> RepoResult<Vec<AuditLogEntry>> {
+        Ok(self.audit_log.borrow().iter().rev().cloned().collect())
+    }
+}
+
+impl ImageRepo for MockDb {
+    fn create_image(&self, image: &Image) -> RepoResult<()> {
+        create(&mut self.images.borrow_mut(), image.clone())
+    }
+    fn get_image(&self, id: &Id) -> RepoResult<Image> {
+        get(&self.images.borrow(), id.as_ref())
+    }
+}
+
 impl Db for MockDb {
     fn create_tag_if_it_does_not_exist(&self, e: &Tag) -> RepoResult<()> {
         if let Err(err) = create(&mut self.tags.borrow_mut(), e.clone()) {
@@ -611,12 +840,73 @@ impl Db for MockDb {
             .collect())
     }
 
+    fn get_bbox_subscription(&self, id: &Id) -> RepoResult<BboxSubscription> {
+        get(&self.bbox_subscriptions.borrow(), id.as_str())
+    }
+
+    fn delete_bbox_subscription(&self, id: &Id) -> RepoResult<()> {
+        self.pending_subscription_changes
+            .borrow_mut()
+            .retain(|c| &c.subscription_id != id);
+        let mut subscriptions = self.bbox_subscriptions.borrow_mut();
+        let count = subscriptions.len();
+        subscriptions.retain(|s| &s.id != id);
+        if subscriptions.len() == count {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+
     fn delete_bbox_subscriptions_by_email(&self, user_email: &str) -> RepoResult<()> {
         self.bbox_subscriptions
             .borrow_mut()
             .retain(|s| s.user_email != user_email);
         Ok(())
     }
+
+    fn add_pending_subscription_change(
+        &self,
+        change: &PendingSubscriptionChange,
+    ) -> RepoResult<()> {
+        let mut changes = self.pending_subscription_changes.borrow_mut();
+        if !changes
+            .iter()
+            .any(|c| c.subscription_id == change.subscription_id && c.item_id == change.item_id)
+        {
+            changes.push(change.clone());
+        }
+        Ok(())
+    }
+
+    fn load_pending_subscription_changes(
+        &self,
+        subscription_id: &Id,
+    ) -> RepoResult<Vec<PendingSubscriptionChange>> {
+        Ok(self
+            .pending_subscription_changes
+            .borrow()
+            .iter()
+            .filter(|c| &c.subscription_id == subscription_id)
+            .cloned()
+            .collect())
+    }
+
+    fn complete_subscription_digest(
+        &self,
+        subscription_id: &Id,
+        changes_until: TimestampMs,
+        sent_at: TimestampMs,
+    ) -> RepoResult<()> {
+        self.pending_subscription_changes
+            .borrow_mut()
+            .retain(|c| &c.subscription_id != subscription_id || c.created_at > changes_until);
+        for s in self.bbox_subscriptions.borrow_mut().iter_mut() {
+            if &s.id == subscription_id {
+                s.last_digest_at = Some(sent_at);
+            }
+        }
+        Ok(())
+    }
 }
 
 #[test]
@@ -627,12 +917,14 @@ fn receive_different_user() {
         email_confirmed: true,
         password: "secret".parse::<Password>().unwrap(),
         role: Role::Guest,
+        locale: None,
     });
     db.users.borrow_mut().push(User {
         email: "b@foo.bar".into(),
         email_confirmed: true,
         password: "secret".parse::<Password>().unwrap(),
         role: Role::Guest,
+        locale: None,
     });
     assert!(get_user(&db, "a@foo.bar", "b@foo.bar").is_err());
     assert!(get_user(&db, "a@foo.bar", "a@foo.bar").is_ok());
@@ -652,9 +944,10 @@ fn create_bbox_subscription() {
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Guest,
+            locale: None,
         })
         .is_ok());
-    assert!(usecases::subscribe_to_bbox(&db, "abc@abc.de".into(), bbox_new).is_ok());
+    assert!(usecases::subscribe_to_bbox(&db, "abc@abc.de".into(), bbox_new, None).is_ok());
 
     let bbox_subscription = db.all_bbox_subscriptions().unwrap()[0].clone();
     assert_eq!(
@@ -683,6 +976,7 @@ fn modify_bbox_subscription() {
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Guest,
+            locale: None,
         })
         .is_ok());
 
@@ -690,10 +984,16 @@ fn modify_bbox_subscription() {
         id: "123".into(),
         user_email: "abc@abc.de".into(),
         bbox: bbox_old,
+        locale: None,
+        tags: vec![],
+        categories: vec![],
+        events: true,
+        delivery: SubscriptionDelivery::Immediate,
+        last_digest_at: None,
     };
     db.create_bbox_subscription(&bbox_subscription).unwrap();
 
-    usecases::subscribe_to_bbox(&db, "abc@abc.de".into(), bbox_new).unwrap();
+    usecases::subscribe_to_bbox(&db, "abc@abc.de".into(), bbox_new, None).unwrap();
 
     let bbox_subscriptions: Vec<_> = db
         .all_bbox_subscriptions()
@@ -729,12 +1029,19 @@ fn get_bbox_subscriptions() {
             email_confirmed: true,
             password: "secret1".parse::<Password>().unwrap(),
             role: Role::Guest,
+            locale: None,
         })
         .is_ok());
     let bbox_subscription = BboxSubscription {
         id: "1".into(),
         user_email: "a@abc.de".into(),
         bbox: bbox1,
+        locale: None,
+        tags: vec![],
+        categories: vec![],
+        events: true,
+        delivery: SubscriptionDelivery::Immediate,
+        last_digest_at: None,
     };
     assert!(db.create_bbox_subscription(&bbox_subscription).is_ok());
 
@@ -744,12 +1051,19 @@ fn get_bbox_subscriptions() {
             email_confirmed: true,
             password: "secret2".parse::<Password>().unwrap(),
             role: Role::Guest,
+            locale: None,
         })
         .is_ok());
     let bbox_subscription2 = BboxSubscription {
         id: "2".into(),
         user_email: "b@abc.de".into(),
         bbox: bbox2,
+        locale: None,
+        tags: vec![],
+        categories: vec![],
+        events: true,
+        delivery: SubscriptionDelivery::Immediate,
+        last_digest_at: None,
     };
     assert!(db.create_bbox_subscription(&bbox_subscription2).is_ok());
     let bbox_subscriptions = usecases::get_bbox_subscriptions(&db, "b@abc.de");
@@ -758,7 +1072,7 @@ fn get_bbox_subscriptions() {
 }
 
 #[test]
-fn email_addresses_by_coordinate() {
+fn recipients_of_change() {
     let db = MockDb::default();
     let bbox_new = geo::MapBbox::new(
         MapPoint::from_lat_lng_deg(0.0, 0.0),
@@ -770,20 +1084,41 @@ fn email_addresses_by_coordinate() {
         email_confirmed: true,
         password: "secret".parse::<Password>().unwrap(),
         role: Role::Guest,
+        locale: Some("en".parse().unwrap()),
     })
     .unwrap();
 
-    usecases::subscribe_to_bbox(&db, "abc@abc.de".into(), bbox_new).unwrap();
+    usecases::subscribe_to_bbox(&db, "abc@abc.de".into(), bbox_new, None).unwrap();
 
-    let email_addresses =
-        usecases::email_addresses_by_coordinate(&db, MapPoint::from_lat_lng_deg(5.0, 5.0)).unwrap();
-    assert_eq!(email_addresses.len(), 1);
-    assert_eq!(email_addresses[0], "abc@abc.de");
-
-    let no_email_addresses =
-        usecases::email_addresses_by_coordinate(&db, MapPoint::from_lat_lng_deg(20.0, 20.0))
-            .unwrap();
-    assert_eq!(no_email_addresses.len(), 0);
+    let recipients_at = |lat, lng| {
+        usecases::recipients_of_change(
+            &db,
+            SubscriptionChangeKind::PlaceAdded,
+            &Id::new(),
+            MapPoint::from_lat_lng_deg(lat, lng),
+            &[],
+        )
+        .unwrap()
+    };
+    let recipients = recipients_at(5.0, 5.0);
+    assert_eq!(recipients.len(), 1);
+    assert_eq!(recipients[0].email, "abc@abc.de");
+    // The locale of the user
+    assert_eq!(recipients[0].locale, Some("en".parse().unwrap()));
+
+    let no_recipients = recipients_at(20.0, 20.0);
+    assert_eq!(no_recipients.len(), 0);
+
+    // The locale of the subscription overrides the locale of the user
+    usecases::subscribe_to_bbox(
+        &db,
+        "abc@abc.de".into(),
+        bbox_new,
+        Some("de".parse().unwrap()),
+    )
+    .unwrap();
+    let recipients = recipients_at(5.0, 5.0);
+    assert_eq!(recipients[0].locale, Some("de".parse().unwrap()));
 }
 
 #[test]
@@ -795,6 +1130,7 @@ fn delete_user() {
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Guest,
+            locale: None,
         })
         .is_ok());
     assert!(db
@@ -803,6 +1139,7 @@ fn delete_user() {
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Guest,
+            locale: None,
         })
         .is_ok());
     assert_eq!(db.count_users().unwrap(), 2);
@@ -819,6 +1156,7 @@ fn receive_event_with_creators_email() {
         email_confirmed: true,
         password: "secret".parse::<Password>().unwrap(),
         role: Role::Guest,
+        locale: None,
     })
     .unwrap();
     db.create_event(Event {
@@ -836,6 +1174,9 @@ fn receive_event_with_creators_email() {
         archived: None,
         image_url: None,
         image_link_url: None,
+        image_id: None,
+        recurrence: None,
+        series_id: None,
     })
     .unwrap();
     let e = usecases::get_event(&db, "x").unwrap();
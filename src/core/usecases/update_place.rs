@@ -1,4 +1,4 @@
-use super::{parse_custom_link_param, CustomLinkParam};
+use super::{parse_custom_link_param, parse_opening_hours_param, CustomLinkParam};
 
 use crate::core::{
     prelude::*,
@@ -30,6 +30,7 @@ pub struct UpdatePlace {
     pub tags           : Vec<String>,
     pub image_url      : Option<String>,
     pub image_link_url : Option<String>,
+    pub image_id       : Option<String>,
     pub custom_links   : Vec<CustomLinkParam>,
 }
 
@@ -52,14 +53,15 @@ impl From<Place> for UpdatePlace {
         let (city, country, state, street, zip) = address
             .map(|a| (a.city, a.country, a.state, a.street, a.zip))
             .unwrap_or_default();
-        let (homepage_url, image_url, image_link_url, custom_links) = links
+        let (homepage_url, image_url, image_link_url, image_id, custom_links) = links
             .map(
                 |Links {
                      homepage,
                      image,
                      image_href,
+                     image_id,
                      custom,
-                 }| (homepage, image, image_href, custom),
+                 }| (homepage, image, image_href, image_id, custom),
             )
             .unwrap_or_default();
         let (contact_name, email, telephone) = contact
@@ -76,6 +78,7 @@ impl From<Place> for UpdatePlace {
             homepage: homepage_url.map(|url| url.to_string()),
             image_link_url: image_link_url.map(|url| url.to_string()),
             image_url: image_url.map(|url| url.to_string()),
+            image_id: image_id.map(Into::into),
             lat: pos.lat().to_deg(),
             lng: pos.lng().to_deg(),
             opening_hours: opening_hours.map(Into::into),
@@ -97,6 +100,13 @@ pub struct Storable {
     last_cleared_revision: Revision,
 }
 
+impl Storable {
+    // Organizations that need to clear this revision
+    pub fn clearance_org_ids(&self) -> &[Id] {
+        &self.clearance_org_ids
+    }
+}
+
 pub fn prepare_updated_place<D: Db>(
     db: &D,
     place_id: Id,
@@ -125,6 +135,7 @@ pub fn prepare_updated_place<D: Db>(
         homepage,
         image_url,
         image_link_url,
+        image_id,
         custom_links: custom_links_param,
         ..
     } = e;
@@ -143,7 +154,7 @@ pub fn prepare_updated_place<D: Db>(
         Some(address)
     };
 
-    let (revision, last_cleared_revision, old_tags, license) = {
+    let (revision, last_cleared_revision, old_tags, old_opening_hours, license) = {
         let (old_place, _review_status) = db.get_place(place_id.as_str())?;
         // Check for revision conflict (optimistic locking)
         let revision = Revision::from(version);
@@ -155,7 +166,13 @@ pub fn prepare_updated_place<D: Db>(
         let license = old_place.license;
         // The existing tags are needed for authorization
         let old_tags = old_place.tags;
-        (revision, last_cleared_revision, old_tags, license)
+        (
+            revision,
+            last_cleared_revision,
+            old_tags,
+            old_place.opening_hours,
+            license,
+        )
     };
 
     let categories: Vec<_> = categories.into_iter().map(Id::from).collect();
@@ -176,22 +193,30 @@ pub fn prepare_updated_place<D: Db>(
     let image_href = image_link_url
         .and_then(|ref url| parse_url_param(url).transpose())
         .transpose()?;
+    let image_id = image_id.map(Id::from);
+    if let Some(ref image_id) = image_id {
+        super::check_image_exists(db, image_id)?;
+    }
     let mut custom_links = Vec::with_capacity(custom_links_param.len());
     for custom_link_param in custom_links_param {
         custom_links.push(parse_custom_link_param(custom_link_param)?);
     }
-    let links =
-        if homepage.is_none() && image.is_none() && image_href.is_none() && custom_links.is_empty()
-        {
-            None
-        } else {
-            Some(Links {
-                homepage,
-                image,
-                image_href,
-                custom: custom_links,
-            })
-        };
+    let links = if homepage.is_none()
+        && image.is_none()
+        && image_href.is_none()
+        && image_id.is_none()
+        && custom_links.is_empty()
+    {
+        None
+    } else {
+        Some(Links {
+            homepage,
+            image,
+            image_href,
+            image_id,
+            custom: custom_links,
+        })
+    };
 
     let place = Place {
         id: place_id,
@@ -207,10 +232,7 @@ pub fn prepare_updated_place<D: Db>(
             phone,
         }),
         opening_hours: opening_hours
-            .map(|s| {
-                s.parse()
-                    .map_err(|_| Error::Parameter(ParameterError::InvalidOpeningHours))
-            })
+            .map(|from| parse_opening_hours_param(from, old_opening_hours.as_ref()))
             .transpose()?,
         founded_on,
         links,
@@ -288,10 +310,13 @@ mod tests {
             tags        : vec![],
             image_url     : Some("img2".into()),
             image_link_url: old.links.as_ref().and_then(|l| l.image_href.as_ref()).map(|url| url.as_str().to_string()),
+            image_id      : None,
             custom_links: vec![],
         };
-        let mut mock_db = MockDb::default();
-        mock_db.entries = vec![(old, ReviewStatus::Created)].into();
+        let mock_db = MockDb {
+            entries: vec![(old, ReviewStatus::Created)].into(),
+            ..Default::default()
+        };
         let now = TimestampMs::now();
         let storable =
             prepare_updated_place(&mock_db, id, new, Some("test@example.com"), None).unwrap();
@@ -331,6 +356,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn update_place_with_unchanged_unsupported_opening_hours() {
+        let id = Id::new();
+        let mut old = Place::build()
+            .id(id.as_ref())
+            .revision(1)
+            .title("foo")
+            .description("bar")
+            .license("CC0-1.0")
+            .finish();
+        old.opening_hours = Some("Mo-Fr 08:00-12:00 || \"on appointment\"".parse().unwrap());
+
+        let update = |version, opening_hours: &str| UpdatePlace {
+            version,
+            title: "new title".into(),
+            description: "bar".into(),
+            lat: 0.0,
+            lng: 0.0,
+            street: None,
+            zip: None,
+            city: None,
+            country: None,
+            state: None,
+            contact_name: None,
+            email: None,
+            telephone: None,
+            homepage: None,
+            opening_hours: Some(opening_hours.into()),
+            founded_on: None,
+            categories: vec![],
+            tags: vec![],
+            image_url: None,
+            image_link_url: None,
+            image_id: None,
+            custom_links: vec![],
+        };
+        let mock_db = MockDb {
+            entries: vec![(old, ReviewStatus::Created)].into(),
+            ..Default::default()
+        };
+        assert!(
+            parse_opening_hours_param("Mo-Fr 08:00-12:00 || \"on appointment\"".into(), None)
+                .is_err()
+        );
+        // Other fields can still be edited
+        let storable = prepare_updated_place(
+            &mock_db,
+            id.clone(),
+            update(2, " Mo-Fr 08:00-12:00 || \"on appointment\""),
+            None,
+            None,
+        )
+        .unwrap();
+        store_updated_place(&mock_db, storable).unwrap();
+        // Changed opening hours are validated
+        assert!(matches!(
+            prepare_updated_place(&mock_db, id, update(3, "Mo-Fr by appointment"), None, None),
+            Err(Error::Parameter(ParameterError::InvalidOpeningHours(_)))
+        ));
+    }
+
     #[test]
     fn update_place_with_invalid_version() {
         let id = Id::new();
@@ -364,24 +450,22 @@ mod tests {
             tags        : vec![],
             image_url     : None,
             image_link_url: None,
+            image_id      : None,
             custom_links: vec![],
         };
-        let mut mock_db = MockDb::default();
-        mock_db.entries = vec![(old, ReviewStatus::Created)].into();
+        let mock_db = MockDb {
+            entries: vec![(old, ReviewStatus::Created)].into(),
+            ..Default::default()
+        };
         let err = match prepare_updated_place(&mock_db, id, new, None, None) {
             Ok(storable) => store_updated_place(&mock_db, storable).err(),
             Err(err) => Some(err),
         };
         assert!(err.is_some());
         match err.unwrap() {
-            Error::Repo(err) => match err {
-                RepoError::InvalidVersion => {}
-                e => {
-                    panic!(format!("Unexpected error: {:?}", e));
-                }
-            },
+            Error::Repo(RepoError::InvalidVersion) => {}
             e => {
-                panic!(format!("Unexpected error: {:?}", e));
+                panic!("Unexpected error: {:?}", e);
             }
         }
         assert_eq!(mock_db.entries.borrow().len(), 1);
@@ -412,19 +496,17 @@ mod tests {
             tags        : vec![],
             image_url     : None,
             image_link_url: None,
+            image_id      : None,
             custom_links: vec![],
         };
-        let mut mock_db = MockDb::default();
-        mock_db.entries = vec![].into();
+        let mock_db = MockDb {
+            entries: vec![].into(),
+            ..Default::default()
+        };
         let result = prepare_updated_place(&mock_db, id, new, None, None);
         assert!(result.is_err());
         match result.err().unwrap() {
-            Error::Repo(err) => match err {
-                RepoError::NotFound => {}
-                _ => {
-                    panic!("invalid error type");
-                }
-            },
+            Error::Repo(RepoError::NotFound) => {}
             _ => {
                 panic!("invalid error type");
             }
@@ -463,11 +545,14 @@ mod tests {
             tags        : vec!["vegan".into()],
             image_url     : None,
             image_link_url: None,
+            image_id      : None,
             custom_links: vec![],
         };
-        let mut mock_db = MockDb::default();
-        mock_db.entries = vec![(old, ReviewStatus::Created)].into();
-        mock_db.tags = vec![Tag { id: "bio".into() }, Tag { id: "fair".into() }].into();
+        let mock_db = MockDb {
+            entries: vec![(old, ReviewStatus::Created)].into(),
+            tags: vec![Tag { id: "bio".into() }, Tag { id: "fair".into() }].into(),
+            ..Default::default()
+        };
         let storable = prepare_updated_place(&mock_db, id.clone(), new, None, None).unwrap();
         assert!(store_updated_place(&mock_db, storable).is_ok());
         let (e, _) = mock_db.get_place(id.as_ref()).unwrap();
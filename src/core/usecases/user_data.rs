@@ -0,0 +1,80 @@
+use super::authorize_user_by_email;
+use crate::core::prelude::*;
+
+// Reserved top-level domain that will never be resolved
+const ERASED_EMAIL_DOMAIN: &str = "erased.invalid";
+
+/// All personal data that is linked to the e-mail address
+/// of a user account.
+#[derive(Debug, Clone)]
+pub struct UserDataExport {
+    pub user: User,
+    pub bbox_subscriptions: Vec<BboxSubscription>,
+    pub user_token_expires_at: Option<Timestamp>,
+    pub activities: Vec<UserActivity>,
+}
+
+/// Users are only permitted to access their own data
+/// unless they are admins.
+pub fn authorize_user_data_access<D: Db>(db: &D, login_email: &str, email: &str) -> Result<()> {
+    if login_email == email {
+        return Ok(());
+    }
+    authorize_user_by_email(db, login_email, Role::Admin)
+        .map(|_| ())
+        .map_err(|_| Error::Parameter(ParameterError::Forbidden))
+}
+
+pub fn export_user_data<D: Db + UserDataRepo>(db: &D, email: &str) -> Result<UserDataExport> {
+    let user = db
+        .try_get_user_by_email(email)?
+        .ok_or(ParameterError::UserDoesNotExist)?;
+    let bbox_subscriptions = db.all_bbox_subscriptions_by_email(email)?;
+    let user_token_expires_at = match db.get_user_token_by_email(email) {
+        Ok(token) => Some(token.expires_at),
+        Err(RepoError::NotFound) => None,
+        Err(err) => return Err(err.into()),
+    };
+    let activities = db.load_user_activities(email)?;
+    Ok(UserDataExport {
+        user,
+        bbox_subscriptions,
+        user_token_expires_at,
+        activities,
+    })
+}
+
+/// Replaces the account of a user with a pseudonymous account
+/// that can neither log in nor receive any e-mails.
+///
+/// All activities of the user like place revisions, reviews,
+/// ratings, and events are kept and refer to the pseudonym
+/// instead. Returns the pseudonymous account.
+pub fn erase_user_data<D: Db + UserDataRepo>(
+    db: &D,
+    email: &str,
+    erased_by: Option<&str>,
+) -> Result<User> {
+    let user = db
+        .try_get_user_by_email(email)?
+        .ok_or(ParameterError::UserDoesNotExist)?;
+    let pseudonym = User {
+        email: format!("erased-{}@{}", Id::new(), ERASED_EMAIL_DOMAIN),
+        email_confirmed: false,
+        // Not a valid hash that could ever be verified
+        password: String::new().into(),
+        role: Role::Guest,
+        locale: None,
+    };
+    // The e-mail address in the audit log is replaced
+    // by the pseudonym together with all other references
+    let entry = AuditLogEntry::now(
+        erased_by,
+        AuditAction::EraseUserData,
+        vec![user.email.clone()],
+    );
+    super::record_audit_log(db, entry)?;
+    db.pseudonymize_user(&user.email, &pseudonym)?;
+    info!("Erased the personal data of user {}", pseudonym.email);
+    Ok(pseudonym)
+}
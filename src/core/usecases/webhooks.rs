@@ -0,0 +1,129 @@
+use crate::core::{
+    prelude::*,
+    util::{geo::MapBbox, validate},
+};
+
+// The secret is used as the key for signing the payload
+pub const MIN_WEBHOOK_SECRET_LEN: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct NewWebhook {
+    pub url: String,
+    pub secret: String,
+    pub event_types: Vec<String>,
+    pub bbox: Option<MapBbox>,
+    pub tags: Vec<String>,
+}
+
+pub fn register_webhook<R: WebhookRepo>(
+    repo: &R,
+    org_id: &Id,
+    new_webhook: NewWebhook,
+) -> Result<Webhook> {
+    let NewWebhook {
+        url,
+        secret,
+        event_types,
+        bbox,
+        tags,
+    } = new_webhook;
+    let url: Url = url.trim().parse().map_err(|_| ParameterError::Url)?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(ParameterError::Url.into());
+    }
+    if secret.len() < MIN_WEBHOOK_SECRET_LEN {
+        return Err(ParameterError::WebhookSecret.into());
+    }
+    let mut parsed_event_types = Vec::with_capacity(event_types.len());
+    for t in event_types {
+        let t = t
+            .parse::<WebhookEventType>()
+            .map_err(|_| ParameterError::InvalidWebhookEventType(t))?;
+        if !parsed_event_types.contains(&t) {
+            parsed_event_types.push(t);
+        }
+    }
+    if parsed_event_types.is_empty() {
+        return Err(ParameterError::InvalidWebhookEventType(String::new()).into());
+    }
+    if let Some(bbox) = &bbox {
+        validate::bbox(bbox)?;
+    }
+    let webhook = Webhook {
+        id: Id::new(),
+        org_id: org_id.clone(),
+        url,
+        secret,
+        event_types: parsed_event_types,
+        bbox,
+        tags: super::prepare_tag_list(tags.iter().map(String::as_str)),
+        created_at: TimestampMs::now(),
+    };
+    repo.create_webhook(&webhook)?;
+    Ok(webhook)
+}
+
+pub fn load_webhooks<R: WebhookRepo>(repo: &R, org_id: &Id) -> Result<Vec<Webhook>> {
+    Ok(repo.load_webhooks_of_org(org_id)?)
+}
+
+pub fn delete_webhook<R: WebhookRepo>(repo: &R, org_id: &Id, id: &Id) -> Result<()> {
+    Ok(repo.delete_webhook(org_id, id)?)
+}
+
+pub fn load_webhook_deliveries<R: WebhookRepo>(
+    repo: &R,
+    org_id: &Id,
+    webhook_id: &Id,
+    pagination: &Pagination,
+) -> Result<Vec<WebhookDelivery>> {
+    // Webhooks of other organizations are not disclosed
+    let webhook = repo.get_webhook(webhook_id)?;
+    if webhook.org_id != *org_id {
+        return Err(RepoError::NotFound.into());
+    }
+    Ok(repo.load_webhook_deliveries(webhook_id, pagination)?)
+}
+
+// Creates a pending delivery for each webhook that matches
+// the change. Only webhooks of the given organizations are
+// considered if specified. The payload is created for each
+// delivery individually from its webhook, id, and creation
+// time, because the content depends on the receiver.
+pub fn create_webhook_deliveries<R: WebhookRepo>(
+    repo: &R,
+    event_type: WebhookEventType,
+    pos: Option<MapPoint>,
+    tags: &[String],
+    org_ids: Option<&[Id]>,
+    payload: impl Fn(&Webhook, &Id, TimestampMs) -> Result<String>,
+) -> Result<Vec<WebhookDelivery>> {
+    let now = TimestampMs::now();
+    let mut deliveries = vec![];
+    for webhook in repo.all_webhooks()? {
+        if let Some(org_ids) = org_ids {
+            if !org_ids.contains(&webhook.org_id) {
+                continue;
+            }
+        }
+        if !webhook.matches(event_type, pos, tags) {
+            continue;
+        }
+        let id = Id::new();
+        let delivery = WebhookDelivery {
+            payload: payload(&webhook, &id, now)?,
+            id,
+            webhook_id: webhook.id,
+            event_type,
+            status: WebhookDeliveryStatus::Pending,
+            attempts: 0,
+            response_status: None,
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+        };
+        repo.create_webhook_delivery(&delivery)?;
+        deliveries.push(delivery);
+    }
+    Ok(deliveries)
+}
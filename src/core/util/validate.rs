@@ -200,6 +200,9 @@ mod tests {
             archived: None,
             image_url: None,
             image_link_url: None,
+            image_id: None,
+            recurrence: None,
+            series_id: None,
         };
 
         let mut x = e.clone();
@@ -304,6 +307,9 @@ mod tests {
             archived: None,
             image_url: None,
             image_link_url: None,
+            image_id: None,
+            recurrence: None,
+            series_id: None,
         };
         assert!(e.validate().is_ok());
         assert!(Event {
@@ -326,7 +332,7 @@ mod tests {
         .is_err());
         assert!(Event {
             start: min_valid_event_date_time(now) + Duration::seconds(10),
-            ..e.clone()
+            ..e
         }
         .validate()
         .is_ok());
@@ -349,6 +355,9 @@ mod tests {
             archived: None,
             image_url: None,
             image_link_url: None,
+            image_id: None,
+            recurrence: None,
+            series_id: None,
         };
         assert!(e.validate().is_err());
     }
@@ -0,0 +1,808 @@
+//! Backend-agnostic pooling of database connections.
+//!
+//! The storage backend is selected at runtime by the scheme of
+//! the database URL. Every backend implements the repository
+//! traits in `core::db` for its own diesel connection type and
+//! the `Connection` enum in this module delegates to it.
+
+#[cfg(feature = "postgres")]
+use super::postgres;
+use super::sqlite;
+use crate::core::{db::*, prelude::*};
+
+use anyhow::{bail, Result as Fallible};
+use diesel::{
+    r2d2::{self, ManageConnection},
+    result::Error as DieselError,
+};
+use owning_ref::RwLockReadGuardRef;
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+    result,
+    sync::{Arc, RwLock, RwLockWriteGuard},
+};
+
+type RepoResult<T> = result::Result<T, RepoError>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Sqlite,
+    #[cfg(feature = "postgres")]
+    Postgres,
+}
+
+impl Backend {
+    pub fn from_url(url: &str) -> Fallible<Self> {
+        if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            #[cfg(feature = "postgres")]
+            return Ok(Self::Postgres);
+            #[cfg(not(feature = "postgres"))]
+            bail!("Support for PostgreSQL has not been enabled");
+        }
+        if url.contains("://") && !url.starts_with("file://") {
+            bail!("Unsupported database URL: {}", url);
+        }
+        Ok(Self::Sqlite)
+    }
+}
+
+pub enum Connection {
+    Sqlite(sqlite::Connection),
+    #[cfg(feature = "postgres")]
+    Postgres(postgres::Connection),
+}
+
+impl Connection {
+    pub fn transaction<T, E, F>(&self, f: F) -> result::Result<T, E>
+    where
+        F: FnOnce() -> result::Result<T, E>,
+        E: From<DieselError>,
+    {
+        use diesel::Connection as _;
+        match self {
+            Self::Sqlite(conn) => conn.transaction(f),
+            #[cfg(feature = "postgres")]
+            Self::Postgres(conn) => conn.transaction(f),
+        }
+    }
+
+    pub fn run_embedded_migrations(&self) -> Fallible<()> {
+        match self {
+            Self::Sqlite(conn) => sqlite::run_embedded_migrations(conn),
+            #[cfg(feature = "postgres")]
+            Self::Postgres(conn) => postgres::run_embedded_migrations(conn),
+        }
+    }
+}
+
+pub enum ConnectionManager {
+    Sqlite(sqlite::ConnectionManager),
+    #[cfg(feature = "postgres")]
+    Postgres(postgres::ConnectionManager),
+}
+
+impl ConnectionManager {
+    pub fn new(url: &str) -> Fallible<Self> {
+        Ok(match Backend::from_url(url)? {
+            Backend::Sqlite => Self::Sqlite(sqlite::ConnectionManager::new(url)),
+            #[cfg(feature = "postgres")]
+            Backend::Postgres => Self::Postgres(postgres::ConnectionManager::new(url)),
+        })
+    }
+
+    pub fn backend(&self) -> Backend {
+        match self {
+            Self::Sqlite(_) => Backend::Sqlite,
+            #[cfg(feature = "postgres")]
+            Self::Postgres(_) => Backend::Postgres,
+        }
+    }
+}
+
+// The connection managers of diesel only implement Debug
+// if their connection does.
+impl fmt::Debug for ConnectionManager {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ConnectionManager")
+            .field(&self.backend())
+            .finish()
+    }
+}
+
+impl ManageConnection for ConnectionManager {
+    type Connection = Connection;
+    type Error = r2d2::Error;
+
+    fn connect(&self) -> result::Result<Self::Connection, Self::Error> {
+        match self {
+            Self::Sqlite(manager) => manager.connect().map(Connection::Sqlite),
+            #[cfg(feature = "postgres")]
+            Self::Postgres(manager) => manager.connect().map(Connection::Postgres),
+        }
+    }
+
+    fn is_valid(&self, conn: &mut Self::Connection) -> result::Result<(), Self::Error> {
+        match (self, conn) {
+            (Self::Sqlite(manager), Connection::Sqlite(conn)) => manager.is_valid(conn),
+            #[cfg(feature = "postgres")]
+            (Self::Postgres(manager), Connection::Postgres(conn)) => manager.is_valid(conn),
+            #[cfg(feature = "postgres")]
+            _ => unreachable!("mismatching database backends"),
+        }
+    }
+
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        match (self, conn) {
+            (Self::Sqlite(manager), Connection::Sqlite(conn)) => manager.has_broken(conn),
+            #[cfg(feature = "postgres")]
+            (Self::Postgres(manager), Connection::Postgres(conn)) => manager.has_broken(conn),
+            #[cfg(feature = "postgres")]
+            _ => unreachable!("mismatching database backends"),
+        }
+    }
+}
+
+pub type ConnectionPool = r2d2::Pool<ConnectionManager>;
+pub type PooledConnection = r2d2::PooledConnection<ConnectionManager>;
+
+pub type SharedConnectionPool = Arc<RwLock<ConnectionPool>>;
+
+enum PoolLock<'a> {
+    #[cfg(feature = "postgres")]
+    Shared(RwLockReadGuardRef<'a, ConnectionPool>),
+    Exclusive(RwLockWriteGuard<'a, ConnectionPool>),
+}
+
+impl<'a> Deref for PoolLock<'a> {
+    type Target = ConnectionPool;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Shared(locked) => &*locked,
+            Self::Exclusive(locked) => &*locked,
+        }
+    }
+}
+
+pub struct DbReadOnly<'a> {
+    _locked_pool: RwLockReadGuardRef<'a, ConnectionPool>,
+    conn: PooledConnection,
+}
+
+impl<'a> DbReadOnly<'a> {
+    fn try_new(pool: &'a SharedConnectionPool) -> Fallible<Self> {
+        let locked_pool = RwLockReadGuardRef::new(pool.read().unwrap_or_else(|err| {
+            error!("Failed to lock database connection pool for read-only access");
+            err.into_inner()
+        }));
+        let conn = locked_pool.get().map_err(|err| {
+            error!("Failed to obtain pooled database connection for read-only access");
+            err
+        })?;
+        Ok(Self {
+            _locked_pool: locked_pool,
+            conn,
+        })
+    }
+}
+
+impl<'a> Deref for DbReadOnly<'a> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.conn
+    }
+}
+
+pub struct DbReadWrite<'a> {
+    _locked_pool: PoolLock<'a>,
+    conn: PooledConnection,
+}
+
+impl<'a> DbReadWrite<'a> {
+    fn try_new(pool: &'a SharedConnectionPool, backend: Backend) -> Fallible<Self> {
+        let locked_pool = match backend {
+            Backend::Sqlite => PoolLock::Exclusive(pool.write().unwrap_or_else(|err| {
+                error!("Failed to lock database connection pool for read/write access");
+                err.into_inner()
+            })),
+            // PostgreSQL handles concurrent writers on its own and
+            // doesn't require exclusive access to the pool.
+            #[cfg(feature = "postgres")]
+            Backend::Postgres => {
+                PoolLock::Shared(RwLockReadGuardRef::new(pool.read().unwrap_or_else(|err| {
+                    error!("Failed to lock database connection pool for read/write access");
+                    err.into_inner()
+                })))
+            }
+        };
+        let conn = locked_pool.get().map_err(|err| {
+            error!("Failed to obtain pooled database connection for read/write access");
+            err
+        })?;
+        Ok(Self {
+            _locked_pool: locked_pool,
+            conn,
+        })
+    }
+}
+
+impl<'a> Deref for DbReadWrite<'a> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.conn
+    }
+}
+
+impl<'a> DerefMut for DbReadWrite<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut *self.conn
+    }
+}
+
+#[derive(Clone)]
+pub struct Connections {
+    // SQLite: Only a single connection with write access will be
+    // handed out at a time from the pool. Multiple read
+    // connections can be accessed concurrently. This locking
+    // pattern around the connection pool prevents SQLITE_LOCKED
+    // ("database is locked") errors that are causing internal
+    // server errors and failed requests.
+    pool: SharedConnectionPool,
+    backend: Backend,
+}
+
+impl Connections {
+    pub fn init(url: &str, pool_size: u32) -> Fallible<Self> {
+        let manager = ConnectionManager::new(url)?;
+        let backend = manager.backend();
+        let pool = ConnectionPool::builder()
+            .max_size(pool_size)
+            .build(manager)?;
+        Ok(Self::new(pool, backend))
+    }
+
+    pub fn new(pool: ConnectionPool, backend: Backend) -> Self {
+        Self {
+            pool: Arc::new(RwLock::new(pool)),
+            backend,
+        }
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    pub fn shared(&self) -> Fallible<DbReadOnly> {
+        DbReadOnly::try_new(&self.pool)
+    }
+
+    pub fn exclusive(&self) -> Fallible<DbReadWrite> {
+        DbReadWrite::try_new(&self.pool, self.backend)
+    }
+
+    pub fn run_embedded_migrations(&self) -> Fallible<()> {
+        self.exclusive()?.run_embedded_migrations()
+    }
+}
+
+// Forwards a method call to the connection of the actual backend
+macro_rules! delegate {
+    ($self:ident, $conn:ident => $call:expr) => {
+        match $self {
+            Connection::Sqlite($conn) => $call,
+            #[cfg(feature = "postgres")]
+            Connection::Postgres($conn) => $call,
+        }
+    };
+}
+
+impl PlaceRepo for Connection {
+    fn get_place(&self, id: &str) -> RepoResult<(Place, ReviewStatus)> {
+        delegate!(self, conn => conn.get_place(id))
+    }
+    fn get_places(&self, ids: &[&str]) -> RepoResult<Vec<(Place, ReviewStatus)>> {
+        delegate!(self, conn => conn.get_places(ids))
+    }
+    fn all_places(&self) -> RepoResult<Vec<(Place, ReviewStatus)>> {
+        delegate!(self, conn => conn.all_places())
+    }
+    fn count_places(&self) -> RepoResult<usize> {
+        delegate!(self, conn => conn.count_places())
+    }
+    fn all_place_ids(&self) -> RepoResult<Vec<Id>> {
+        delegate!(self, conn => conn.all_place_ids())
+    }
+    fn place_ids_changed_since(&self, since: TimestampMs) -> RepoResult<Vec<Id>> {
+        delegate!(self, conn => conn.place_ids_changed_since(since))
+    }
+    fn recently_changed_places(
+        &self,
+        params: &RecentlyChangedEntriesParams,
+        pagination: &Pagination,
+    ) -> RepoResult<Vec<(Place, ReviewStatus, ActivityLog)>> {
+        delegate!(self, conn => conn.recently_changed_places(params, pagination))
+    }
+    fn most_popular_place_revision_tags(
+        &self,
+        params: &MostPopularTagsParams,
+        pagination: &Pagination,
+    ) -> RepoResult<Vec<TagFrequency>> {
+        delegate!(self, conn => conn.most_popular_place_revision_tags(params, pagination))
+    }
+    fn review_places(
+        &self,
+        ids: &[&str],
+        status: ReviewStatus,
+        activity: &ActivityLog,
+    ) -> RepoResult<usize> {
+        delegate!(self, conn => conn.review_places(ids, status, activity))
+    }
+    fn create_or_update_place(&self, place: Place) -> RepoResult<()> {
+        delegate!(self, conn => conn.create_or_update_place(place))
+    }
+    fn get_place_history(&self, id: &str, revision: Option<Revision>) -> RepoResult<PlaceHistory> {
+        delegate!(self, conn => conn.get_place_history(id, revision))
+    }
+    fn load_place_revision(&self, id: &str, rev: Revision) -> RepoResult<(Place, ReviewStatus)> {
+        delegate!(self, conn => conn.load_place_revision(id, rev))
+    }
+    fn log_place_review(&self, id: &str, activity_log: &ActivityLog) -> RepoResult<()> {
+        delegate!(self, conn => conn.log_place_review(id, activity_log))
+    }
+    fn create_place_redirect(&self, old_id: &str, new_id: &str) -> RepoResult<()> {
+        delegate!(self, conn => conn.create_place_redirect(old_id, new_id))
+    }
+    fn get_place_redirect(&self, old_id: &str) -> RepoResult<Option<Id>> {
+        delegate!(self, conn => conn.get_place_redirect(old_id))
+    }
+}
+
+impl EventGateway for Connection {
+    fn create_event(&self, event: Event) -> RepoResult<()> {
+        delegate!(self, conn => conn.create_event(event))
+    }
+    fn update_event(&self, event: &Event) -> RepoResult<()> {
+        delegate!(self, conn => conn.update_event(event))
+    }
+    fn archive_events(&self, ids: &[&str], archived: Timestamp) -> RepoResult<usize> {
+        delegate!(self, conn => conn.archive_events(ids, archived))
+    }
+    fn get_event(&self, id: &str) -> RepoResult<Event> {
+        delegate!(self, conn => conn.get_event(id))
+    }
+    fn get_events_chronologically(&self, ids: &[&str]) -> RepoResult<Vec<Event>> {
+        delegate!(self, conn => conn.get_events_chronologically(ids))
+    }
+    fn get_event_current_revision(&self, id: &str) -> RepoResult<Revision> {
+        delegate!(self, conn => conn.get_event_current_revision(id))
+    }
+    fn load_event_revision(&self, id: &str, rev: Revision) -> RepoResult<Event> {
+        delegate!(self, conn => conn.load_event_revision(id, rev))
+    }
+    fn all_events_chronologically(&self) -> RepoResult<Vec<Event>> {
+        delegate!(self, conn => conn.all_events_chronologically())
+    }
+    fn count_events(&self) -> RepoResult<usize> {
+        delegate!(self, conn => conn.count_events())
+    }
+    fn all_event_ids(&self) -> RepoResult<Vec<Id>> {
+        delegate!(self, conn => conn.all_event_ids())
+    }
+    fn event_ids_changed_since(&self, since: TimestampMs) -> RepoResult<Vec<Id>> {
+        delegate!(self, conn => conn.event_ids_changed_since(since))
+    }
+    fn load_event_update_times(&self, ids: &[&str]) -> RepoResult<Vec<(Id, TimestampMs)>> {
+        delegate!(self, conn => conn.load_event_update_times(ids))
+    }
+    fn detached_event_occurrence_ids(&self, series_ids: &[&str]) -> RepoResult<Vec<Id>> {
+        delegate!(self, conn => conn.detached_event_occurrence_ids(series_ids))
+    }
+    fn delete_event_with_matching_tags(&self, id: &str, tags: &[&str]) -> RepoResult<bool> {
+        delegate!(self, conn => conn.delete_event_with_matching_tags(id, tags))
+    }
+    fn is_event_owned_by_any_organization(&self, id: &str) -> RepoResult<bool> {
+        delegate!(self, conn => conn.is_event_owned_by_any_organization(id))
+    }
+    fn load_external_event_uids(&self, org_id: &Id) -> RepoResult<Vec<(String, Id)>> {
+        delegate!(self, conn => conn.load_external_event_uids(org_id))
+    }
+    fn store_external_event_uid(
+        &self,
+        org_id: &Id,
+        external_uid: &str,
+        id: &str,
+    ) -> RepoResult<()> {
+        delegate!(self, conn => conn.store_external_event_uid(org_id, external_uid, id))
+    }
+}
+
+impl UserGateway for Connection {
+    fn create_user(&self, user: &User) -> RepoResult<()> {
+        delegate!(self, conn => conn.create_user(user))
+    }
+    fn update_user(&self, user: &User) -> RepoResult<()> {
+        delegate!(self, conn => conn.update_user(user))
+    }
+    fn delete_user_by_email(&self, email: &str) -> RepoResult<()> {
+        delegate!(self, conn => conn.delete_user_by_email(email))
+    }
+    fn all_users(&self) -> RepoResult<Vec<User>> {
+        delegate!(self, conn => conn.all_users())
+    }
+    fn count_users(&self) -> RepoResult<usize> {
+        delegate!(self, conn => conn.count_users())
+    }
+    fn get_user_by_email(&self, email: &str) -> RepoResult<User> {
+        delegate!(self, conn => conn.get_user_by_email(email))
+    }
+    fn try_get_user_by_email(&self, email: &str) -> RepoResult<Option<User>> {
+        delegate!(self, conn => conn.try_get_user_by_email(email))
+    }
+}
+
+impl OrganizationRepo for Connection {
+    fn create_org(&mut self, org: Organization) -> RepoResult<()> {
+        delegate!(self, conn => conn.create_org(org))
+    }
+    fn update_org(&mut self, org: &Organization) -> RepoResult<()> {
+        delegate!(self, conn => conn.update_org(org))
+    }
+    fn delete_org(&mut self, id: &Id) -> RepoResult<()> {
+        delegate!(self, conn => conn.delete_org(id))
+    }
+    fn get_org(&self, id: &Id) -> RepoResult<Organization> {
+        delegate!(self, conn => conn.get_org(id))
+    }
+    fn all_orgs(&self) -> RepoResult<Vec<Organization>> {
+        delegate!(self, conn => conn.all_orgs())
+    }
+    fn load_owned_place_ids(&self, org_id: &Id) -> RepoResult<Vec<Id>> {
+        delegate!(self, conn => conn.load_owned_place_ids(org_id))
+    }
+    fn load_owned_event_ids(&self, org_id: &Id) -> RepoResult<Vec<Id>> {
+        delegate!(self, conn => conn.load_owned_event_ids(org_id))
+    }
+    fn map_tag_to_clearance_org_id(&self, tag: &str) -> RepoResult<Option<Id>> {
+        delegate!(self, conn => conn.map_tag_to_clearance_org_id(tag))
+    }
+    fn get_moderated_tags_by_org(
+        &self,
+        excluded_org_id: Option<&Id>,
+    ) -> RepoResult<Vec<(Id, ModeratedTag)>> {
+        delegate!(self, conn => conn.get_moderated_tags_by_org(excluded_org_id))
+    }
+}
+
+impl PlaceClearanceRepo for Connection {
+    fn add_pending_clearance_for_places(
+        &self,
+        org_ids: &[Id],
+        pending_clearance: &PendingClearanceForPlace,
+    ) -> RepoResult<usize> {
+        delegate!(self, conn => conn.add_pending_clearance_for_places(org_ids, pending_clearance))
+    }
+    fn count_pending_clearances_for_places(&self, org_id: &Id) -> RepoResult<u64> {
+        delegate!(self, conn => conn.count_pending_clearances_for_places(org_id))
+    }
+    fn list_pending_clearances_for_places(
+        &self,
+        org_id: &Id,
+        pagination: &Pagination,
+    ) -> RepoResult<Vec<PendingClearanceForPlace>> {
+        delegate!(self, conn => conn.list_pending_clearances_for_places(org_id, pagination))
+    }
+    fn load_pending_clearances_for_places(
+        &self,
+        org_id: &Id,
+        place_ids: &[&str],
+    ) -> RepoResult<Vec<PendingClearanceForPlace>> {
+        delegate!(self, conn => conn.load_pending_clearances_for_places(org_id, place_ids))
+    }
+    fn update_pending_clearances_for_places(
+        &self,
+        org_id: &Id,
+        clearances: &[ClearanceForPlace],
+    ) -> RepoResult<usize> {
+        delegate!(self, conn => conn.update_pending_clearances_for_places(org_id, clearances))
+    }
+    fn cleanup_pending_clearances_for_places(&self, org_id: &Id) -> RepoResult<u64> {
+        delegate!(self, conn => conn.cleanup_pending_clearances_for_places(org_id))
+    }
+}
+
+impl EventClearanceRepo for Connection {
+    fn add_pending_clearance_for_events(
+        &self,
+        org_ids: &[Id],
+        pending_clearance: &PendingClearanceForEvent,
+    ) -> RepoResult<usize> {
+        delegate!(self, conn => conn.add_pending_clearance_for_events(org_ids, pending_clearance))
+    }
+    fn count_pending_clearances_for_events(&self, org_id: &Id) -> RepoResult<u64> {
+        delegate!(self, conn => conn.count_pending_clearances_for_events(org_id))
+    }
+    fn list_pending_clearances_for_events(
+        &self,
+        org_id: &Id,
+        pagination: &Pagination,
+    ) -> RepoResult<Vec<PendingClearanceForEvent>> {
+        delegate!(self, conn => conn.list_pending_clearances_for_events(org_id, pagination))
+    }
+    fn load_pending_clearances_for_events(
+        &self,
+        org_id: &Id,
+        event_ids: &[&str],
+    ) -> RepoResult<Vec<PendingClearanceForEvent>> {
+        delegate!(self, conn => conn.load_pending_clearances_for_events(org_id, event_ids))
+    }
+    fn update_pending_clearances_for_events(
+        &self,
+        org_id: &Id,
+        clearances: &[ClearanceForEvent],
+    ) -> RepoResult<usize> {
+        delegate!(self, conn => conn.update_pending_clearances_for_events(org_id, clearances))
+    }
+    fn cleanup_pending_clearances_for_events(&self, org_id: &Id) -> RepoResult<u64> {
+        delegate!(self, conn => conn.cleanup_pending_clearances_for_events(org_id))
+    }
+}
+
+impl JobQueue for Connection {
+    fn enqueue_job(&self, id: &Id, payload: &str, run_at: TimestampMs) -> RepoResult<()> {
+        delegate!(self, conn => conn.enqueue_job(id, payload, run_at))
+    }
+    fn claim_next_job(
+        &self,
+        now: TimestampMs,
+        lease_until: TimestampMs,
+    ) -> RepoResult<Option<QueuedJob>> {
+        delegate!(self, conn => conn.claim_next_job(now, lease_until))
+    }
+    fn claim_job(
+        &self,
+        id: &Id,
+        now: TimestampMs,
+        lease_until: TimestampMs,
+    ) -> RepoResult<Option<QueuedJob>> {
+        delegate!(self, conn => conn.claim_job(id, now, lease_until))
+    }
+    fn reschedule_job(&self, id: &Id, run_at: TimestampMs, last_error: &str) -> RepoResult<()> {
+        delegate!(self, conn => conn.reschedule_job(id, run_at, last_error))
+    }
+    fn complete_job(&self, id: &Id) -> RepoResult<()> {
+        delegate!(self, conn => conn.complete_job(id))
+    }
+}
+
+impl WebhookRepo for Connection {
+    fn create_webhook(&self, webhook: &Webhook) -> RepoResult<()> {
+        delegate!(self, conn => conn.create_webhook(webhook))
+    }
+    fn delete_webhook(&self, org_id: &Id, id: &Id) -> RepoResult<()> {
+        delegate!(self, conn => conn.delete_webhook(org_id, id))
+    }
+    fn get_webhook(&self, id: &Id) -> RepoResult<Webhook> {
+        delegate!(self, conn => conn.get_webhook(id))
+    }
+    fn all_webhooks(&self) -> RepoResult<Vec<Webhook>> {
+        delegate!(self, conn => conn.all_webhooks())
+    }
+    fn load_webhooks_of_org(&self, org_id: &Id) -> RepoResult<Vec<Webhook>> {
+        delegate!(self, conn => conn.load_webhooks_of_org(org_id))
+    }
+    fn create_webhook_delivery(&self, delivery: &WebhookDelivery) -> RepoResult<()> {
+        delegate!(self, conn => conn.create_webhook_delivery(delivery))
+    }
+    fn get_webhook_delivery(&self, id: &Id) -> RepoResult<WebhookDelivery> {
+        delegate!(self, conn => conn.get_webhook_delivery(id))
+    }
+    fn update_webhook_delivery(&self, delivery: &WebhookDelivery) -> RepoResult<()> {
+        delegate!(self, conn => conn.update_webhook_delivery(delivery))
+    }
+    fn load_webhook_deliveries(
+        &self,
+        webhook_id: &Id,
+        pagination: &Pagination,
+    ) -> RepoResult<Vec<WebhookDelivery>> {
+        delegate!(self, conn => conn.load_webhook_deliveries(webhook_id, pagination))
+    }
+}
+
+impl ApiTokenRepo for Connection {
+    fn create_api_token(&self, token: &ApiToken) -> RepoResult<()> {
+        delegate!(self, conn => conn.create_api_token(token))
+    }
+    fn get_api_token_by_hash(&self, token_hash: &str) -> RepoResult<ApiToken> {
+        delegate!(self, conn => conn.get_api_token_by_hash(token_hash))
+    }
+    fn load_api_tokens_of_org(&self, org_id: &Id) -> RepoResult<Vec<ApiToken>> {
+        delegate!(self, conn => conn.load_api_tokens_of_org(org_id))
+    }
+    fn update_api_token_last_used_at(&self, id: &Id, last_used_at: TimestampMs) -> RepoResult<()> {
+        delegate!(self, conn => conn.update_api_token_last_used_at(id, last_used_at))
+    }
+    fn revoke_api_token(&self, org_id: &Id, id: &Id, revoked_at: TimestampMs) -> RepoResult<()> {
+        delegate!(self, conn => conn.revoke_api_token(org_id, id, revoked_at))
+    }
+}
+
+impl ImageRepo for Connection {
+    fn create_image(&self, image: &Image) -> RepoResult<()> {
+        delegate!(self, conn => conn.create_image(image))
+    }
+    fn get_image(&self, id: &Id) -> RepoResult<Image> {
+        delegate!(self, conn => conn.get_image(id))
+    }
+}
+
+impl UserDataRepo for Connection {
+    fn load_user_activities(&self, email: &str) -> RepoResult<Vec<UserActivity>> {
+        delegate!(self, conn => conn.load_user_activities(email))
+    }
+    fn pseudonymize_user(&self, email: &str, pseudonym: &User) -> RepoResult<()> {
+        delegate!(self, conn => conn.pseudonymize_user(email, pseudonym))
+    }
+}
+
+impl AuditLogRepo for Connection {
+    fn append_audit_log(&self, entry: &AuditLogEntry) -> RepoResult<()> {
+        delegate!(self, conn => conn.append_audit_log(entry))
+    }
+    fn query_audit_log(
+        &self,
+        query: &AuditLogQuery,
+        pagination: &Pagination,
+    ) -> RepoResult<Vec<AuditLogEntry>> {
+        delegate!(self, conn => conn.query_audit_log(query, pagination))
+    }
+}
+
+impl GeoCodingCacheRepo for Connection {
+    fn get_geocoding_cache_entry(&self, query: &str) -> RepoResult<Option<GeoCodingCacheEntry>> {
+        delegate!(self, conn => conn.get_geocoding_cache_entry(query))
+    }
+    fn put_geocoding_cache_entry(&self, entry: &GeoCodingCacheEntry) -> RepoResult<()> {
+        delegate!(self, conn => conn.put_geocoding_cache_entry(entry))
+    }
+    fn delete_geocoding_cache_entries_before(
+        &self,
+        cached_before: TimestampMs,
+    ) -> RepoResult<usize> {
+        delegate!(self, conn => conn.delete_geocoding_cache_entries_before(cached_before))
+    }
+}
+
+impl CommentRepository for Connection {
+    fn create_comment(&self, comment: Comment) -> RepoResult<()> {
+        delegate!(self, conn => conn.create_comment(comment))
+    }
+    fn load_comment(&self, id: &str) -> RepoResult<Comment> {
+        delegate!(self, conn => conn.load_comment(id))
+    }
+    fn load_comments(&self, ids: &[&str]) -> RepoResult<Vec<Comment>> {
+        delegate!(self, conn => conn.load_comments(ids))
+    }
+    fn load_comments_of_rating(&self, rating_id: &str) -> RepoResult<Vec<Comment>> {
+        delegate!(self, conn => conn.load_comments_of_rating(rating_id))
+    }
+    fn archive_comments(&self, ids: &[&str], activity: &Activity) -> RepoResult<usize> {
+        delegate!(self, conn => conn.archive_comments(ids, activity))
+    }
+    fn archive_comments_of_ratings(
+        &self,
+        rating_ids: &[&str],
+        activity: &Activity,
+    ) -> RepoResult<usize> {
+        delegate!(self, conn => conn.archive_comments_of_ratings(rating_ids, activity))
+    }
+    fn archive_comments_of_places(
+        &self,
+        place_ids: &[&str],
+        activity: &Activity,
+    ) -> RepoResult<usize> {
+        delegate!(self, conn => conn.archive_comments_of_places(place_ids, activity))
+    }
+}
+
+impl RatingRepository for Connection {
+    fn create_rating(&self, rating: Rating) -> RepoResult<()> {
+        delegate!(self, conn => conn.create_rating(rating))
+    }
+    fn load_rating(&self, id: &str) -> RepoResult<Rating> {
+        delegate!(self, conn => conn.load_rating(id))
+    }
+    fn load_ratings(&self, ids: &[&str]) -> RepoResult<Vec<Rating>> {
+        delegate!(self, conn => conn.load_ratings(ids))
+    }
+    fn load_ratings_of_place(&self, place_id: &str) -> RepoResult<Vec<Rating>> {
+        delegate!(self, conn => conn.load_ratings_of_place(place_id))
+    }
+    fn archive_ratings(&self, ids: &[&str], activity: &Activity) -> RepoResult<usize> {
+        delegate!(self, conn => conn.archive_ratings(ids, activity))
+    }
+    fn archive_ratings_of_places(
+        &self,
+        place_ids: &[&str],
+        activity: &Activity,
+    ) -> RepoResult<usize> {
+        delegate!(self, conn => conn.archive_ratings_of_places(place_ids, activity))
+    }
+    fn load_place_ids_of_ratings(&self, ids: &[&str]) -> RepoResult<Vec<String>> {
+        delegate!(self, conn => conn.load_place_ids_of_ratings(ids))
+    }
+    fn move_ratings_of_place(&self, from_place_id: &str, to_place_id: &str) -> RepoResult<usize> {
+        delegate!(self, conn => conn.move_ratings_of_place(from_place_id, to_place_id))
+    }
+}
+
+impl UserTokenRepo for Connection {
+    fn replace_user_token(&self, user_token: UserToken) -> RepoResult<EmailNonce> {
+        delegate!(self, conn => conn.replace_user_token(user_token))
+    }
+    fn consume_user_token(&self, email_nonce: &EmailNonce) -> RepoResult<UserToken> {
+        delegate!(self, conn => conn.consume_user_token(email_nonce))
+    }
+    fn delete_expired_user_tokens(&self, expired_before: Timestamp) -> RepoResult<usize> {
+        delegate!(self, conn => conn.delete_expired_user_tokens(expired_before))
+    }
+    fn get_user_token_by_email(&self, email: &str) -> RepoResult<UserToken> {
+        delegate!(self, conn => conn.get_user_token_by_email(email))
+    }
+}
+
+impl Db for Connection {
+    fn create_tag_if_it_does_not_exist(&self, tag: &Tag) -> RepoResult<()> {
+        delegate!(self, conn => conn.create_tag_if_it_does_not_exist(tag))
+    }
+    fn all_tags(&self) -> RepoResult<Vec<Tag>> {
+        delegate!(self, conn => conn.all_tags())
+    }
+    fn count_tags(&self) -> RepoResult<usize> {
+        delegate!(self, conn => conn.count_tags())
+    }
+    fn create_bbox_subscription(&self, subscription: &BboxSubscription) -> RepoResult<()> {
+        delegate!(self, conn => conn.create_bbox_subscription(subscription))
+    }
+    fn all_bbox_subscriptions(&self) -> RepoResult<Vec<BboxSubscription>> {
+        delegate!(self, conn => conn.all_bbox_subscriptions())
+    }
+    fn all_bbox_subscriptions_by_email(
+        &self,
+        user_email: &str,
+    ) -> RepoResult<Vec<BboxSubscription>> {
+        delegate!(self, conn => conn.all_bbox_subscriptions_by_email(user_email))
+    }
+    fn get_bbox_subscription(&self, id: &Id) -> RepoResult<BboxSubscription> {
+        delegate!(self, conn => conn.get_bbox_subscription(id))
+    }
+    fn delete_bbox_subscription(&self, id: &Id) -> RepoResult<()> {
+        delegate!(self, conn => conn.delete_bbox_subscription(id))
+    }
+    fn delete_bbox_subscriptions_by_email(&self, user_email: &str) -> RepoResult<()> {
+        delegate!(self, conn => conn.delete_bbox_subscriptions_by_email(user_email))
+    }
+    fn add_pending_subscription_change(
+        &self,
+        change: &PendingSubscriptionChange,
+    ) -> RepoResult<()> {
+        delegate!(self, conn => conn.add_pending_subscription_change(change))
+    }
+    fn load_pending_subscription_changes(
+        &self,
+        subscription_id: &Id,
+    ) -> RepoResult<Vec<PendingSubscriptionChange>> {
+        delegate!(self, conn => conn.load_pending_subscription_changes(subscription_id))
+    }
+    fn complete_subscription_digest(
+        &self,
+        subscription_id: &Id,
+        changes_until: TimestampMs,
+        sent_at: TimestampMs,
+    ) -> RepoResult<()> {
+        delegate!(self, conn => conn.complete_subscription_digest(subscription_id, changes_until, sent_at))
+    }
+}
@@ -1,2 +1,10 @@
+mod connections;
+#[cfg(feature = "postgres")]
+pub(crate) mod postgres;
 pub(crate) mod sqlite;
 pub(crate) mod tantivy;
+
+#[cfg(test)]
+pub mod tests;
+
+pub use self::connections::{Backend, Connection, Connections, DbReadOnly, DbReadWrite};
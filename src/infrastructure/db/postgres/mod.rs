@@ -0,0 +1,85 @@
+// The diesel-based repository implementation is shared with the
+// SQLite backend. Both the table definitions and the models are
+// backend-agnostic and the connection-specific code is compiled
+// a second time for PgConnection.
+
+use super::sqlite::{models, schema, util};
+use diesel::{pg::PgConnection, r2d2};
+
+// Duplicate entries are silently skipped with ON CONFLICT DO NOTHING.
+// A unique constraint violation would otherwise abort the surrounding
+// transaction.
+//
+// NOTE: Must be defined before declaring the modules that use it!
+macro_rules! insert_or_ignore_into {
+    ($table:expr, $values:expr) => {
+        diesel::insert_into($table)
+            .values($values)
+            .on_conflict_do_nothing()
+    };
+}
+
+#[path = "../sqlite/connection.rs"]
+mod connection;
+
+pub type Connection = PgConnection;
+
+pub type ConnectionManager = r2d2::ConnectionManager<Connection>;
+
+embed_migrations!("migrations_postgres");
+
+pub fn run_embedded_migrations(connection: &Connection) -> anyhow::Result<()> {
+    embedded_migrations::run(connection)?;
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use diesel::prelude::*;
+    use uuid::Uuid;
+
+    /// A uniquely named schema that isolates the tables of a single
+    /// test from all other tests running on the same database.
+    pub struct TestSchema {
+        url: String,
+        name: String,
+    }
+
+    impl TestSchema {
+        pub fn create(url: &str) -> Self {
+            let name = format!("test_{}", Uuid::new_v4().to_simple());
+            let conn = PgConnection::establish(url).unwrap();
+            diesel::sql_query(format!("CREATE SCHEMA {}", name))
+                .execute(&conn)
+                .unwrap();
+            Self {
+                url: url.to_owned(),
+                name,
+            }
+        }
+
+        /// The database URL for connections that use this schema.
+        pub fn url(&self) -> String {
+            let separator = if self.url.contains('?') { '&' } else { '?' };
+            format!(
+                "{}{}options=-csearch_path%3D{}",
+                self.url, separator, self.name
+            )
+        }
+    }
+
+    impl Drop for TestSchema {
+        fn drop(&mut self) {
+            let dropped = PgConnection::establish(&self.url)
+                .map_err(anyhow::Error::from)
+                .and_then(|conn| {
+                    diesel::sql_query(format!("DROP SCHEMA {} CASCADE", self.name))
+                        .execute(&conn)?;
+                    Ok(())
+                });
+            if let Err(err) = dropped {
+                log::warn!("Failed to drop test schema {}: {}", self.name, err);
+            }
+        }
+    }
+}
@@ -1,11 +1,10 @@
-use super::{util::load_url, *};
+use super::{util::load_url, Connection, *};
 use crate::core::prelude::*;
 use anyhow::anyhow;
 use chrono::prelude::*;
 use diesel::{
     self,
     prelude::{Connection as DieselConnection, *},
-    result::{DatabaseErrorKind, Error as DieselError},
 };
 use std::result;
 
@@ -16,13 +15,11 @@ fn load_review_status(status: ReviewStatusPrimitive) -> Result<ReviewStatus> {
         .ok_or_else(|| RepoError::Other(anyhow!("Invalid review status: {}", status)))
 }
 
-fn load_place_revision_tags(
-    conn: &SqliteConnection,
-    place_revision_rowid: i64,
-) -> Result<Vec<String>> {
+fn load_place_revision_tags(conn: &Connection, place_revision_rowid: i64) -> Result<Vec<String>> {
     use schema::place_revision_tag::dsl;
     Ok(schema::place_revision_tag::table
         .filter(dsl::parent_rowid.eq(&place_revision_rowid))
+        .order_by(dsl::tag)
         .load::<models::PlaceRevisionTag>(conn)?
         .into_iter()
         .map(
@@ -35,7 +32,7 @@ fn load_place_revision_tags(
 }
 
 fn load_place_revision_custom_links(
-    conn: &SqliteConnection,
+    conn: &Connection,
     place_revision_rowid: i64,
 ) -> Result<Vec<CustomLink>> {
     use schema::place_revision_custom_link::dsl;
@@ -68,7 +65,7 @@ fn load_place_revision_custom_links(
 }
 
 fn load_place(
-    conn: &SqliteConnection,
+    conn: &Connection,
     place: models::JoinedPlaceRevision,
 ) -> Result<(Place, ReviewStatus)> {
     let models::JoinedPlaceRevision {
@@ -96,6 +93,7 @@ fn load_place(
         founded_on,
         image_url,
         image_link_url,
+        image_id,
         ..
     } = place;
 
@@ -146,6 +144,7 @@ fn load_place(
             homepage: homepage.and_then(load_url),
             image: image_url.and_then(load_url),
             image_href: image_link_url.and_then(load_url),
+            image_id: image_id.map(Into::into),
             custom: custom_links,
         }),
         opening_hours: opening_hours.map(Into::into),
@@ -157,7 +156,7 @@ fn load_place(
 }
 
 fn load_place_with_status_review(
-    conn: &SqliteConnection,
+    conn: &Connection,
     place_with_status_review: models::JoinedPlaceRevisionWithStatusReview,
 ) -> Result<(Place, ReviewStatus, ActivityLog)> {
     let models::JoinedPlaceRevisionWithStatusReview {
@@ -182,6 +181,7 @@ fn load_place_with_status_review(
         founded_on,
         image_url,
         image_link_url,
+        image_id,
         place_id,
         place_license: license,
         review_created_at,
@@ -223,6 +223,7 @@ fn load_place_with_status_review(
         homepage: homepage.and_then(load_url),
         image: image_url.and_then(load_url),
         image_href: image_link_url.and_then(load_url),
+        image_id: image_id.map(Into::into),
         custom: custom_links,
     };
 
@@ -276,6 +277,13 @@ fn load_place_with_status_review(
     Ok((place, load_review_status(review_status)?, activity_log))
 }
 
+// Matches the wildcard characters of LIKE patterns literally
+fn escape_like_pattern(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
 #[derive(QueryableByName)]
 struct TagCountRow {
     #[sql_type = "diesel::sql_types::Text"]
@@ -285,7 +293,7 @@ struct TagCountRow {
     count: i64,
 }
 
-fn resolve_organization_rowid(conn: &SqliteConnection, id: &Id) -> Result<i64> {
+fn resolve_organization_rowid(conn: &Connection, id: &Id) -> Result<i64> {
     use schema::organization::dsl;
     Ok(schema::organization::table
         .select(dsl::rowid)
@@ -297,7 +305,49 @@ fn resolve_organization_rowid(conn: &SqliteConnection, id: &Id) -> Result<i64> {
         })?)
 }
 
-fn resolve_place_rowid(conn: &SqliteConnection, id: &Id) -> Result<i64> {
+fn load_organization(conn: &Connection, org: models::Organization) -> Result<Organization> {
+    use schema::organization_tag::dsl;
+    let models::Organization { rowid, id, name } = org;
+    let moderated_tags = dsl::organization_tag
+        .filter(dsl::org_rowid.eq(rowid))
+        .load::<models::OrganizationTag>(conn)?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    Ok(Organization {
+        id: id.into(),
+        name,
+        moderated_tags,
+    })
+}
+
+fn insert_organization_tags(
+    conn: &Connection,
+    org_rowid: i64,
+    moderated_tags: &[ModeratedTag],
+) -> diesel::QueryResult<()> {
+    for ModeratedTag {
+        label,
+        allow_add,
+        allow_remove,
+        require_clearance,
+    } in moderated_tags
+    {
+        let org_tag = models::NewOrganizationTag {
+            org_rowid,
+            tag_label: label,
+            tag_allow_add: if *allow_add { 1 } else { 0 },
+            tag_allow_remove: if *allow_remove { 1 } else { 0 },
+            require_clearance: if *require_clearance { 1 } else { 0 },
+        };
+        diesel::insert_into(schema::organization_tag::table)
+            .values(&org_tag)
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+fn resolve_place_rowid(conn: &Connection, id: &Id) -> Result<i64> {
     use schema::place::dsl;
     Ok(schema::place::table
         .select(dsl::rowid)
@@ -310,7 +360,7 @@ fn resolve_place_rowid(conn: &SqliteConnection, id: &Id) -> Result<i64> {
 }
 
 fn resolve_place_rowid_verify_revision(
-    conn: &SqliteConnection,
+    conn: &Connection,
     id: &Id,
     revision: Revision,
 ) -> Result<i64> {
@@ -335,7 +385,7 @@ fn resolve_place_rowid_verify_revision(
 }
 
 fn resolve_place_rowid_with_current_revision(
-    conn: &SqliteConnection,
+    conn: &Connection,
     id: &Id,
 ) -> Result<(i64, Revision)> {
     use schema::place::dsl;
@@ -350,7 +400,7 @@ fn resolve_place_rowid_with_current_revision(
         .map(|(id, rev)| (id, Revision::from(rev as u64)))?)
 }
 
-fn resolve_rating_rowid(conn: &SqliteConnection, id: &str) -> Result<i64> {
+fn resolve_rating_rowid(conn: &Connection, id: &str) -> Result<i64> {
     use schema::place_rating::dsl;
     Ok(schema::place_rating::table
         .select(dsl::rowid)
@@ -363,7 +413,7 @@ fn resolve_rating_rowid(conn: &SqliteConnection, id: &str) -> Result<i64> {
 }
 
 fn into_new_place_revision(
-    conn: &SqliteConnection,
+    conn: &Connection,
     place: Place,
 ) -> Result<(Id, models::NewPlaceRevision, Vec<String>, Vec<CustomLink>)> {
     let Place {
@@ -433,6 +483,7 @@ fn into_new_place_revision(
         homepage,
         image: image_url,
         image_href: image_link_url,
+        image_id,
         custom: custom_links,
     } = links.unwrap_or_default();
     let new_place = models::NewPlaceRevision {
@@ -458,11 +509,12 @@ fn into_new_place_revision(
         founded_on,
         image_url: image_url.map(Url::into_string),
         image_link_url: image_link_url.map(Url::into_string),
+        image_id: image_id.map(Into::into),
     };
     Ok((place_id, new_place, tags, custom_links))
 }
 
-impl PlaceRepo for SqliteConnection {
+impl PlaceRepo for Connection {
     fn create_or_update_place(&self, place: Place) -> Result<()> {
         let (_place_id, new_place, tags, custom_links) = into_new_place_revision(self, place)?;
         diesel::insert_into(schema::place_revision::table)
@@ -636,6 +688,7 @@ impl PlaceRepo for SqliteConnection {
                 rev_dsl::founded_on,
                 rev_dsl::image_url,
                 rev_dsl::image_link_url,
+                rev_dsl::image_id,
                 dsl::id,
                 dsl::license,
             ))
@@ -666,6 +719,64 @@ impl PlaceRepo for SqliteConnection {
         self.get_places(&[])
     }
 
+    fn all_place_ids(&self) -> Result<Vec<Id>> {
+        use schema::place::dsl;
+        Ok(schema::place::table
+            .select(dsl::id)
+            .order_by(dsl::rowid)
+            .load::<String>(self)?
+            .into_iter()
+            .map(Id::from)
+            .collect())
+    }
+
+    fn place_ids_changed_since(&self, since: TimestampMs) -> Result<Vec<Id>> {
+        use schema::place::dsl;
+        use schema::place_rating::dsl as rating_dsl;
+        use schema::place_revision::dsl as rev_dsl;
+        use schema::place_revision_review::dsl as review_dsl;
+
+        let since_ms = since.into_inner();
+        // Ratings are created with a time stamp in seconds, but
+        // archived with a time stamp in milliseconds
+        let since_secs = since.into_seconds();
+        Ok(schema::place::table
+            .select(dsl::id)
+            .filter(
+                dsl::rowid
+                    .eq_any(
+                        schema::place_revision::table
+                            .select(rev_dsl::parent_rowid)
+                            .filter(rev_dsl::created_at.ge(since_ms)),
+                    )
+                    .or(dsl::rowid.eq_any(
+                        schema::place_revision::table
+                            .select(rev_dsl::parent_rowid)
+                            .filter(
+                                rev_dsl::rowid.eq_any(
+                                    schema::place_revision_review::table
+                                        .select(review_dsl::parent_rowid)
+                                        .filter(review_dsl::created_at.ge(since_ms)),
+                                ),
+                            ),
+                    ))
+                    .or(dsl::rowid.eq_any(
+                        schema::place_rating::table
+                            .select(rating_dsl::parent_rowid)
+                            .filter(
+                                rating_dsl::created_at
+                                    .ge(since_secs)
+                                    .or(rating_dsl::archived_at.ge(since_ms)),
+                            ),
+                    )),
+            )
+            .order_by(dsl::rowid)
+            .load::<String>(self)?
+            .into_iter()
+            .map(Id::from)
+            .collect())
+    }
+
     fn recently_changed_places(
         &self,
         params: &RecentlyChangedEntriesParams,
@@ -707,6 +818,7 @@ impl PlaceRepo for SqliteConnection {
                 rev_dsl::founded_on,
                 rev_dsl::image_url,
                 rev_dsl::image_link_url,
+                rev_dsl::image_id,
                 dsl::id,
                 dsl::license,
                 review_dsl::rev,
@@ -762,12 +874,12 @@ impl PlaceRepo for SqliteConnection {
             .to_string();
         if params.min_count.is_some() || params.max_count.is_some() {
             if let Some(min_count) = params.min_count {
-                sql.push_str(&format!(" HAVING count>={}", min_count));
+                sql.push_str(&format!(" HAVING COUNT(*)>={}", min_count));
                 if let Some(max_count) = params.max_count {
-                    sql.push_str(&format!(" AND count<={}", max_count));
+                    sql.push_str(&format!(" AND COUNT(*)<={}", max_count));
                 }
             } else if let Some(max_count) = params.max_count {
-                sql.push_str(&format!(" HAVING count<={}", max_count));
+                sql.push_str(&format!(" HAVING COUNT(*)<={}", max_count));
             }
         }
         sql.push_str(" ORDER BY count DESC, tag");
@@ -830,6 +942,7 @@ impl PlaceRepo for SqliteConnection {
                 rev_dsl::founded_on,
                 rev_dsl::image_url,
                 rev_dsl::image_link_url,
+                rev_dsl::image_id,
                 dsl::id,
                 dsl::license,
             ))
@@ -928,6 +1041,7 @@ impl PlaceRepo for SqliteConnection {
                 rev_dsl::founded_on,
                 rev_dsl::image_url,
                 rev_dsl::image_link_url,
+                rev_dsl::image_id,
                 dsl::id,
                 dsl::license,
             ))
@@ -935,10 +1049,99 @@ impl PlaceRepo for SqliteConnection {
         let row = query.first::<models::JoinedPlaceRevision>(self)?;
         load_place(self, row)
     }
+
+    fn log_place_review(&self, id: &str, activity_log: &ActivityLog) -> Result<()> {
+        use schema::place::dsl;
+        use schema::place_revision::dsl as rev_dsl;
+        use schema::place_revision_review::dsl as review_dsl;
+
+        let (rev_id, status) = schema::place_revision::table
+            .inner_join(
+                schema::place::table.on(rev_dsl::parent_rowid
+                    .eq(dsl::rowid)
+                    .and(rev_dsl::rev.eq(dsl::current_rev))),
+            )
+            .select((rev_dsl::rowid, rev_dsl::current_status))
+            .filter(dsl::id.eq(id))
+            .first::<(i64, ReviewStatusPrimitive)>(self)?;
+        let prev_rev = Revision::from(
+            schema::place_revision_review::table
+                .select(diesel::dsl::max(review_dsl::rev))
+                .filter(review_dsl::parent_rowid.eq(rev_id))
+                .first::<Option<i64>>(self)?
+                .ok_or(RepoError::NotFound)? as u64,
+        );
+        let ActivityLog {
+            activity,
+            context,
+            comment,
+        } = activity_log;
+        let created_by = if let Some(ref email) = activity.by {
+            Some(resolve_user_created_by_email(self, email.as_ref())?)
+        } else {
+            None
+        };
+        let new_review = models::NewPlaceReviewedRevision {
+            parent_rowid: rev_id,
+            rev: u64::from(prev_rev.next()) as i64,
+            status,
+            created_at: activity.at.into_inner(),
+            created_by,
+            context: context.as_deref(),
+            comment: comment.as_deref(),
+        };
+        diesel::insert_into(schema::place_revision_review::table)
+            .values(new_review)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn create_place_redirect(&self, old_id: &str, new_id: &str) -> Result<()> {
+        use schema::place_redirect::dsl;
+        let old_place_rowid = resolve_place_rowid(self, &Id::from(old_id))?;
+        let new_place_rowid = resolve_place_rowid(self, &Id::from(new_id))?;
+        // The new place must not be redirected itself
+        diesel::delete(
+            schema::place_redirect::table.filter(dsl::old_place_rowid.eq(new_place_rowid)),
+        )
+        .execute(self)?;
+        diesel::update(
+            schema::place_redirect::table.filter(dsl::new_place_rowid.eq(old_place_rowid)),
+        )
+        .set(dsl::new_place_rowid.eq(new_place_rowid))
+        .execute(self)?;
+        diesel::insert_into(schema::place_redirect::table)
+            .values(&models::NewPlaceRedirect {
+                old_place_rowid,
+                new_place_rowid,
+            })
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn get_place_redirect(&self, old_id: &str) -> Result<Option<Id>> {
+        use schema::place::dsl;
+        use schema::place_redirect::dsl as redirect_dsl;
+        let new_place_rowid = schema::place_redirect::table
+            .inner_join(schema::place::table.on(redirect_dsl::old_place_rowid.eq(dsl::rowid)))
+            .select(redirect_dsl::new_place_rowid)
+            .filter(dsl::id.eq(old_id))
+            .first::<i64>(self)
+            .optional()?;
+        if let Some(new_place_rowid) = new_place_rowid {
+            let new_id = schema::place::table
+                .select(dsl::id)
+                .filter(dsl::rowid.eq(new_place_rowid))
+                .first::<String>(self)?;
+            Ok(Some(new_id.into()))
+        } else {
+            Ok(None)
+        }
+    }
 }
 
 fn into_new_event_with_tags(
-    conn: &SqliteConnection,
+    conn: &Connection,
     event: Event,
 ) -> Result<(models::NewEvent, Vec<String>)> {
     let Event {
@@ -955,7 +1158,10 @@ fn into_new_event_with_tags(
         archived,
         image_url,
         image_link_url,
+        image_id,
         tags,
+        recurrence,
+        series_id,
         ..
     } = event;
 
@@ -992,6 +1198,8 @@ fn into_new_event_with_tags(
 
     let registration = registration.map(util::registration_type_into_i16);
 
+    let (recurrence_rule, recurrence_exdates) = util::recurrence_into_columns(recurrence);
+
     let created_by = if let Some(ref email) = created_by {
         Some(resolve_user_created_by_email(conn, email)?)
     } else {
@@ -1021,12 +1229,17 @@ fn into_new_event_with_tags(
             archived: archived.map(Timestamp::into_inner),
             image_url: image_url.map(Url::into_string),
             image_link_url: image_link_url.map(Url::into_string),
+            image_id: image_id.map(Into::into),
+            updated_at: TimestampMs::now().into_inner(),
+            recurrence_rule,
+            recurrence_exdates,
+            series_uid: series_id.map(Into::into),
         },
         tags,
     ))
 }
 
-fn resolve_event_id(conn: &SqliteConnection, uid: &str) -> Result<i64> {
+fn resolve_event_id(conn: &Connection, uid: &str) -> Result<i64> {
     use schema::events::dsl;
     Ok(dsl::events
         .select(dsl::id)
@@ -1034,7 +1247,130 @@ fn resolve_event_id(conn: &SqliteConnection, uid: &str) -> Result<i64> {
         .first(conn)?)
 }
 
-impl EventGateway for SqliteConnection {
+fn resolve_event_id_with_current_revision(conn: &Connection, uid: &str) -> Result<(i64, Revision)> {
+    use schema::events::dsl;
+    Ok(dsl::events
+        .select((dsl::id, dsl::current_rev))
+        .filter(dsl::uid.eq(uid))
+        .first::<(i64, i64)>(conn)
+        .map_err(|e| {
+            log::warn!("Failed to resolve event id '{}': {}", uid, e);
+            e
+        })
+        .map(|(id, rev)| (id, Revision::from(rev as u64)))?)
+}
+
+fn resolve_event_id_verify_revision(
+    conn: &Connection,
+    uid: &str,
+    revision: Revision,
+) -> Result<i64> {
+    use schema::event_revision::dsl as rev_dsl;
+    use schema::events::dsl;
+    let revision = RevisionValue::from(revision);
+    Ok(schema::events::table
+        .inner_join(schema::event_revision::table)
+        .select(dsl::id)
+        .filter(dsl::uid.eq(uid))
+        .filter(rev_dsl::rev.eq(revision as i64))
+        .first::<i64>(conn)
+        .map_err(|e| {
+            log::warn!(
+                "Failed to resolve event id '{}' with revision {}: {}",
+                uid,
+                revision,
+                e
+            );
+            e
+        })?)
+}
+
+// Stores a copy of the event including its tags in the history
+fn insert_event_revision(
+    conn: &Connection,
+    parent_rowid: i64,
+    rev: Revision,
+    new_event: &models::NewEvent,
+    tags: &[String],
+) -> result::Result<(), diesel::result::Error> {
+    let models::NewEvent {
+        title,
+        description,
+        start,
+        end,
+        lat,
+        lng,
+        street,
+        zip,
+        city,
+        country,
+        state,
+        email,
+        telephone,
+        homepage,
+        created_by,
+        registration,
+        organizer,
+        image_url,
+        image_link_url,
+        image_id,
+        updated_at,
+        recurrence_rule,
+        recurrence_exdates,
+        series_uid,
+        ..
+    } = new_event;
+    let new_revision = models::NewEventRevision {
+        parent_rowid,
+        rev: RevisionValue::from(rev) as i64,
+        created_at: *updated_at,
+        title: title.clone(),
+        description: description.clone(),
+        start: *start,
+        end: *end,
+        lat: *lat,
+        lng: *lng,
+        street: street.clone(),
+        zip: zip.clone(),
+        city: city.clone(),
+        country: country.clone(),
+        state: state.clone(),
+        email: email.clone(),
+        telephone: telephone.clone(),
+        homepage: homepage.clone(),
+        created_by: *created_by,
+        registration: *registration,
+        organizer: organizer.clone(),
+        image_url: image_url.clone(),
+        image_link_url: image_link_url.clone(),
+        image_id: image_id.clone(),
+        recurrence_rule: recurrence_rule.clone(),
+        recurrence_exdates: recurrence_exdates.clone(),
+        series_uid: series_uid.clone(),
+    };
+    diesel::insert_into(schema::event_revision::table)
+        .values(&new_revision)
+        .execute(conn)?;
+    let revision_rowid = {
+        use schema::event_revision::dsl;
+        dsl::event_revision
+            .select(dsl::rowid)
+            .filter(dsl::parent_rowid.eq(parent_rowid))
+            .filter(dsl::rev.eq(new_revision.rev))
+            .first::<i64>(conn)?
+    };
+    let tags: Vec<_> = tags
+        .iter()
+        .map(|tag| models::NewEventRevisionTag {
+            parent_rowid: revision_rowid,
+            tag: &tag,
+        })
+        .collect();
+    insert_or_ignore_into!(schema::event_revision_tag::table, &tags).execute(conn)?;
+    Ok(())
+}
+
+impl EventGateway for Connection {
     fn create_event(&self, e: Event) -> Result<()> {
         let (new_event, tags) = into_new_event_with_tags(self, e)?;
         self.transaction::<_, diesel::result::Error, _>(|| {
@@ -1050,24 +1386,24 @@ impl EventGateway for SqliteConnection {
                 diesel::result::Error::RollbackTransaction
             })?;
             // Insert event tags
-            let tags: Vec<_> = tags
+            let new_tags: Vec<_> = tags
                 .iter()
                 .map(|tag| models::NewEventTag {
                     event_id: id,
                     tag: &tag,
                 })
                 .collect();
-            diesel::insert_or_ignore_into(schema::event_tags::table)
-                .values(&tags)
-                .execute(self)?;
+            insert_or_ignore_into!(schema::event_tags::table, &new_tags).execute(self)?;
+            insert_event_revision(self, id, Revision::initial(), &new_event, &tags)?;
             Ok(())
         })?;
         Ok(())
     }
 
     fn update_event(&self, event: &Event) -> Result<()> {
-        let id = resolve_event_id(self, event.id.as_ref())?;
+        let (id, current_rev) = resolve_event_id_with_current_revision(self, event.id.as_ref())?;
         let (new_event, new_tags) = into_new_event_with_tags(self, event.clone())?;
+        let next_rev = current_rev.next();
         self.transaction::<_, diesel::result::Error, _>(|| {
             use schema::event_tags::dsl as et_dsl;
             use schema::events::dsl as e_dsl;
@@ -1075,6 +1411,23 @@ impl EventGateway for SqliteConnection {
             diesel::update(e_dsl::events.filter(e_dsl::id.eq(&id)))
                 .set(&new_event)
                 .execute(self)?;
+            diesel::update(
+                e_dsl::events
+                    .filter(e_dsl::id.eq(&id))
+                    .filter(e_dsl::current_rev.eq(RevisionValue::from(current_rev) as i64)),
+            )
+            .set(e_dsl::current_rev.eq(RevisionValue::from(next_rev) as i64))
+            .execute(self)?;
+            // Optional columns are not reset by the changeset, but
+            // the recurrence of a series or the attached image might
+            // have been removed
+            diesel::update(e_dsl::events.filter(e_dsl::id.eq(&id)))
+                .set((
+                    e_dsl::recurrence_rule.eq(&new_event.recurrence_rule),
+                    e_dsl::recurrence_exdates.eq(&new_event.recurrence_exdates),
+                    e_dsl::image_id.eq(&new_event.image_id),
+                ))
+                .execute(self)?;
             // Update event tags
             let tags_diff = {
                 let old_tags = et_dsl::event_tags
@@ -1098,10 +1451,9 @@ impl EventGateway for SqliteConnection {
                         tag: &tag,
                     })
                     .collect();
-                diesel::insert_or_ignore_into(et_dsl::event_tags)
-                    .values(&new_tags)
-                    .execute(self)?;
+                insert_or_ignore_into!(et_dsl::event_tags, &new_tags).execute(self)?;
             }
+            insert_event_revision(self, id, next_rev, &new_event, &new_tags)?;
             Ok(())
         })?;
         Ok(())
@@ -1135,6 +1487,10 @@ impl EventGateway for SqliteConnection {
                 e_dsl::archived,
                 e_dsl::image_url,
                 e_dsl::image_link_url,
+                e_dsl::image_id,
+                e_dsl::recurrence_rule,
+                e_dsl::recurrence_exdates,
+                e_dsl::series_uid,
                 u_dsl::email.nullable(),
             ))
             .filter(e_dsl::uid.eq_any(ids))
@@ -1166,6 +1522,10 @@ impl EventGateway for SqliteConnection {
                 archived,
                 image_url,
                 image_link_url,
+                image_id,
+                recurrence_rule,
+                recurrence_exdates,
+                series_uid,
                 created_by_email,
                 ..
             } = row;
@@ -1173,6 +1533,7 @@ impl EventGateway for SqliteConnection {
             let tags = et_dsl::event_tags
                 .select(et_dsl::tag)
                 .filter(et_dsl::event_id.eq(id))
+                .order_by(et_dsl::tag)
                 .load::<String>(self)?;
 
             let address = Address {
@@ -1231,6 +1592,9 @@ impl EventGateway for SqliteConnection {
                 archived: archived.map(Timestamp::from_inner),
                 image_url: image_url.and_then(load_url),
                 image_link_url: image_link_url.and_then(load_url),
+                image_id: image_id.map(Into::into),
+                recurrence: util::load_recurrence(recurrence_rule, recurrence_exdates),
+                series_id: series_uid.map(Into::into),
             };
             events.push(event);
         }
@@ -1244,6 +1608,62 @@ impl EventGateway for SqliteConnection {
         events.into_iter().next().ok_or(RepoError::NotFound)
     }
 
+    fn get_event_current_revision(&self, id: &str) -> Result<Revision> {
+        use schema::events::dsl;
+        Ok(dsl::events
+            .select(dsl::current_rev)
+            .filter(dsl::uid.eq(id))
+            .first::<i64>(self)
+            .map(|rev| Revision::from(rev as u64))?)
+    }
+
+    fn load_event_revision(&self, id: &str, rev: Revision) -> Result<Event> {
+        use schema::{
+            event_revision::dsl as rev_dsl, event_revision_tag::dsl as tag_dsl,
+            events::dsl as e_dsl, users::dsl as u_dsl,
+        };
+        let entity = schema::event_revision::table
+            .inner_join(schema::events::table)
+            .left_outer_join(schema::users::table.on(rev_dsl::created_by.eq(u_dsl::id.nullable())))
+            .select((
+                rev_dsl::rowid,
+                e_dsl::uid,
+                rev_dsl::title,
+                rev_dsl::description,
+                rev_dsl::start,
+                rev_dsl::end,
+                rev_dsl::lat,
+                rev_dsl::lng,
+                rev_dsl::street,
+                rev_dsl::zip,
+                rev_dsl::city,
+                rev_dsl::country,
+                rev_dsl::state,
+                rev_dsl::email,
+                rev_dsl::telephone,
+                rev_dsl::homepage,
+                rev_dsl::created_by,
+                rev_dsl::registration,
+                rev_dsl::organizer,
+                e_dsl::archived,
+                rev_dsl::image_url,
+                rev_dsl::image_link_url,
+                rev_dsl::image_id,
+                rev_dsl::recurrence_rule,
+                rev_dsl::recurrence_exdates,
+                rev_dsl::series_uid,
+                u_dsl::email.nullable(),
+            ))
+            .filter(e_dsl::uid.eq(id))
+            .filter(rev_dsl::rev.eq(RevisionValue::from(rev) as i64))
+            .first::<models::EventEntity>(self)?;
+        let tag_rels = tag_dsl::event_revision_tag
+            .select((tag_dsl::parent_rowid, tag_dsl::tag))
+            .filter(tag_dsl::parent_rowid.eq(entity.id))
+            .load::<models::EventTag>(self)?;
+        Ok(util::event_from_event_entity_and_tags(entity, &tag_rels))
+    }
+
     fn all_events_chronologically(&self) -> Result<Vec<Event>> {
         use schema::{event_tags::dsl as et_dsl, events::dsl as e_dsl, users::dsl as u_dsl};
         let events: Vec<_> = e_dsl::events
@@ -1271,18 +1691,72 @@ impl EventGateway for SqliteConnection {
                 e_dsl::archived,
                 e_dsl::image_url,
                 e_dsl::image_link_url,
+                e_dsl::image_id,
+                e_dsl::recurrence_rule,
+                e_dsl::recurrence_exdates,
+                e_dsl::series_uid,
                 u_dsl::email.nullable(),
             ))
             .filter(e_dsl::archived.is_null())
             .order_by(e_dsl::start)
             .load::<models::EventEntity>(self)?;
-        let tag_rels = et_dsl::event_tags.load(self)?;
+        let tag_rels = et_dsl::event_tags
+            .order_by((et_dsl::event_id, et_dsl::tag))
+            .load(self)?;
         Ok(events
             .into_iter()
             .map(|e| util::event_from_event_entity_and_tags(e, &tag_rels))
             .collect())
     }
 
+    fn all_event_ids(&self) -> Result<Vec<Id>> {
+        use schema::events::dsl;
+        Ok(dsl::events
+            .select(dsl::uid)
+            .filter(dsl::archived.is_null())
+            .order_by(dsl::id)
+            .load::<String>(self)?
+            .into_iter()
+            .map(Id::from)
+            .collect())
+    }
+
+    fn event_ids_changed_since(&self, since: TimestampMs) -> Result<Vec<Id>> {
+        use schema::events::dsl;
+        Ok(dsl::events
+            .select(dsl::uid)
+            .filter(dsl::updated_at.ge(since.into_inner()))
+            .order_by(dsl::id)
+            .load::<String>(self)?
+            .into_iter()
+            .map(Id::from)
+            .collect())
+    }
+
+    fn load_event_update_times(&self, ids: &[&str]) -> Result<Vec<(Id, TimestampMs)>> {
+        use schema::events::dsl;
+        Ok(dsl::events
+            .select((dsl::uid, dsl::updated_at))
+            .filter(dsl::uid.eq_any(ids))
+            .load::<(String, i64)>(self)?
+            .into_iter()
+            .map(|(id, updated_at)| (Id::from(id), TimestampMs::from_inner(updated_at)))
+            .collect())
+    }
+
+    fn detached_event_occurrence_ids(&self, series_ids: &[&str]) -> Result<Vec<Id>> {
+        use schema::events::dsl;
+        Ok(dsl::events
+            .select(dsl::uid)
+            .filter(dsl::series_uid.eq_any(series_ids))
+            .filter(dsl::archived.is_null())
+            .order_by(dsl::start)
+            .load::<String>(self)?
+            .into_iter()
+            .map(Id::from)
+            .collect())
+    }
+
     fn count_events(&self) -> Result<usize> {
         use schema::events::dsl;
         Ok(dsl::events
@@ -1298,7 +1772,10 @@ impl EventGateway for SqliteConnection {
                 .filter(dsl::uid.eq_any(ids))
                 .filter(dsl::archived.is_null()),
         )
-        .set(dsl::archived.eq(Some(archived.into_inner())))
+        .set((
+            dsl::archived.eq(Some(archived.into_inner())),
+            dsl::updated_at.eq(TimestampMs::now().into_inner()),
+        ))
         .execute(self)?;
         debug_assert!(count <= ids.len());
         Ok(count)
@@ -1321,6 +1798,30 @@ impl EventGateway for SqliteConnection {
             debug_assert_eq!(id, *ids.first().unwrap());
         }
         diesel::delete(et_dsl::event_tags.filter(et_dsl::event_id.eq(id))).execute(self)?;
+        diesel::delete(
+            schema::event_external_uid::table
+                .filter(schema::event_external_uid::event_rowid.eq(id)),
+        )
+        .execute(self)?;
+        diesel::delete(
+            schema::organization_event_clearance::table
+                .filter(schema::organization_event_clearance::event_rowid.eq(id)),
+        )
+        .execute(self)?;
+        diesel::delete(
+            schema::event_revision_tag::table.filter(
+                schema::event_revision_tag::parent_rowid.eq_any(
+                    schema::event_revision::table
+                        .select(schema::event_revision::rowid)
+                        .filter(schema::event_revision::parent_rowid.eq(id)),
+                ),
+            ),
+        )
+        .execute(self)?;
+        diesel::delete(
+            schema::event_revision::table.filter(schema::event_revision::parent_rowid.eq(id)),
+        )
+        .execute(self)?;
         diesel::delete(e_dsl::events.filter(e_dsl::id.eq(id))).execute(self)?;
         Ok(true)
     }
@@ -1342,9 +1843,43 @@ impl EventGateway for SqliteConnection {
             .optional()?
             .is_some())
     }
+
+    fn load_external_event_uids(&self, org_id: &Id) -> Result<Vec<(String, Id)>> {
+        use schema::{event_external_uid, events};
+        let org_rowid = resolve_organization_rowid(self, org_id)?;
+        Ok(event_external_uid::table
+            .inner_join(events::table)
+            .select((event_external_uid::external_uid, events::uid))
+            .filter(event_external_uid::org_rowid.eq(org_rowid))
+            .filter(events::archived.is_null())
+            .load::<(String, String)>(self)?
+            .into_iter()
+            .map(|(external_uid, id)| (external_uid, Id::from(id)))
+            .collect())
+    }
+
+    fn store_external_event_uid(&self, org_id: &Id, external_uid: &str, id: &str) -> Result<()> {
+        use schema::event_external_uid::dsl;
+        let org_rowid = resolve_organization_rowid(self, org_id)?;
+        let event_rowid = resolve_event_id(self, id)?;
+        diesel::delete(
+            dsl::event_external_uid
+                .filter(dsl::org_rowid.eq(org_rowid))
+                .filter(dsl::external_uid.eq(external_uid)),
+        )
+        .execute(self)?;
+        diesel::insert_into(dsl::event_external_uid)
+            .values(&models::NewEventExternalUid {
+                org_rowid,
+                external_uid,
+                event_rowid,
+            })
+            .execute(self)?;
+        Ok(())
+    }
 }
 
-fn resolve_user_created_by_email(conn: &SqliteConnection, email: &str) -> Result<i64> {
+fn resolve_user_created_by_email(conn: &Connection, email: &str) -> Result<i64> {
     use schema::users::dsl;
     Ok(dsl::users
         .select(dsl::id)
@@ -1356,7 +1891,7 @@ fn resolve_user_created_by_email(conn: &SqliteConnection, email: &str) -> Result
         })?)
 }
 
-impl UserGateway for SqliteConnection {
+impl UserGateway for Connection {
     fn create_user(&self, u: &User) -> Result<()> {
         let new_user = models::NewUser::from(u);
         diesel::insert_into(schema::users::table)
@@ -1414,7 +1949,7 @@ impl UserGateway for SqliteConnection {
     }
 }
 
-impl RatingRepository for SqliteConnection {
+impl RatingRepository for Connection {
     fn create_rating(&self, rating: Rating) -> Result<()> {
         let Rating {
             id,
@@ -1562,9 +2097,21 @@ impl RatingRepository for SqliteConnection {
         ))
         .execute(self)?)
     }
+
+    fn move_ratings_of_place(&self, from_place_id: &str, to_place_id: &str) -> Result<usize> {
+        use schema::place_rating::dsl;
+        let from_rowid = resolve_place_rowid(self, &Id::from(from_place_id))?;
+        let to_rowid = resolve_place_rowid(self, &Id::from(to_place_id))?;
+        // The comments still refer to their ratings
+        Ok(
+            diesel::update(schema::place_rating::table.filter(dsl::parent_rowid.eq(from_rowid)))
+                .set(dsl::parent_rowid.eq(to_rowid))
+                .execute(self)?,
+        )
+    }
 }
 
-impl CommentRepository for SqliteConnection {
+impl CommentRepository for Connection {
     fn create_comment(&self, comment: Comment) -> Result<()> {
         let Comment {
             id,
@@ -1735,21 +2282,12 @@ impl CommentRepository for SqliteConnection {
     }
 }
 
-impl Db for SqliteConnection {
+impl Db for Connection {
     fn create_tag_if_it_does_not_exist(&self, t: &Tag) -> Result<()> {
-        let res = diesel::insert_into(schema::tags::table)
-            .values(&models::Tag::from(t.clone()))
-            .execute(self);
-        if let Err(err) = res {
-            match err {
-                DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
-                    // that's ok :)
-                }
-                _ => {
-                    return Err(err.into());
-                }
-            }
-        }
+        // Existing tags are silently ignored instead of failing with
+        // a unique constraint violation that would otherwise abort any
+        // surrounding transaction on PostgreSQL.
+        insert_or_ignore_into!(schema::tags::table, &models::Tag::from(t.clone())).execute(self)?;
         Ok(())
     }
 
@@ -1764,6 +2302,17 @@ impl Db for SqliteConnection {
             south_west_lng,
             north_east_lat,
             north_east_lng,
+            locale: new.locale.as_ref().map(Locale::as_str),
+            tags: new.tags.join(","),
+            categories: new
+                .categories
+                .iter()
+                .map(Id::as_str)
+                .collect::<Vec<_>>()
+                .join(","),
+            events: new.events,
+            delivery: util::subscription_delivery_into_i16(new.delivery),
+            last_digest_at: new.last_digest_at.map(TimestampMs::into_inner),
         };
         diesel::insert_into(schema::bbox_subscriptions::table)
             .values(&insertable)
@@ -1784,6 +2333,12 @@ impl Db for SqliteConnection {
                 s_dsl::south_west_lng,
                 s_dsl::north_east_lat,
                 s_dsl::north_east_lng,
+                s_dsl::locale,
+                s_dsl::tags,
+                s_dsl::categories,
+                s_dsl::events,
+                s_dsl::delivery,
+                s_dsl::last_digest_at,
                 u_dsl::email,
             ))
             .load::<models::BboxSubscriptionEntity>(self)?
@@ -1805,6 +2360,12 @@ impl Db for SqliteConnection {
                 s_dsl::south_west_lng,
                 s_dsl::north_east_lat,
                 s_dsl::north_east_lng,
+                s_dsl::locale,
+                s_dsl::tags,
+                s_dsl::categories,
+                s_dsl::events,
+                s_dsl::delivery,
+                s_dsl::last_digest_at,
                 u_dsl::email,
             ))
             .load::<models::BboxSubscriptionEntity>(self)?
@@ -1812,20 +2373,143 @@ impl Db for SqliteConnection {
             .map(BboxSubscription::from)
             .collect())
     }
+    fn get_bbox_subscription(&self, id: &Id) -> Result<BboxSubscription> {
+        use schema::bbox_subscriptions::dsl as s_dsl;
+        use schema::users::dsl as u_dsl;
+        Ok(s_dsl::bbox_subscriptions
+            .inner_join(u_dsl::users)
+            .filter(s_dsl::uid.eq(id.as_str()))
+            .select((
+                s_dsl::id,
+                s_dsl::uid,
+                s_dsl::user_id,
+                s_dsl::south_west_lat,
+                s_dsl::south_west_lng,
+                s_dsl::north_east_lat,
+                s_dsl::north_east_lng,
+                s_dsl::locale,
+                s_dsl::tags,
+                s_dsl::categories,
+                s_dsl::events,
+                s_dsl::delivery,
+                s_dsl::last_digest_at,
+                u_dsl::email,
+            ))
+            .first::<models::BboxSubscriptionEntity>(self)?
+            .into())
+    }
+    fn delete_bbox_subscription(&self, id: &Id) -> Result<()> {
+        use schema::bbox_subscription_change::dsl as c_dsl;
+        use schema::bbox_subscriptions::dsl as s_dsl;
+        let subscription_rowid = s_dsl::bbox_subscriptions
+            .select(s_dsl::id)
+            .filter(s_dsl::uid.eq(id.as_str()))
+            .first::<i64>(self)?;
+        diesel::delete(
+            c_dsl::bbox_subscription_change
+                .filter(c_dsl::subscription_rowid.eq(subscription_rowid)),
+        )
+        .execute(self)?;
+        diesel::delete(s_dsl::bbox_subscriptions.filter(s_dsl::id.eq(subscription_rowid)))
+            .execute(self)?;
+        Ok(())
+    }
     fn delete_bbox_subscriptions_by_email(&self, email: &str) -> Result<()> {
+        use schema::bbox_subscription_change::dsl as c_dsl;
         use schema::bbox_subscriptions::dsl as s_dsl;
         use schema::users::dsl as u_dsl;
         let users_id = u_dsl::users
             .select(u_dsl::id)
             .filter(u_dsl::email.eq(email));
+        let subscription_rowids = s_dsl::bbox_subscriptions
+            .select(s_dsl::id)
+            .filter(s_dsl::user_id.eq_any(users_id));
+        diesel::delete(
+            c_dsl::bbox_subscription_change
+                .filter(c_dsl::subscription_rowid.eq_any(subscription_rowids)),
+        )
+        .execute(self)?;
         diesel::delete(s_dsl::bbox_subscriptions.filter(s_dsl::user_id.eq_any(users_id)))
             .execute(self)?;
         Ok(())
     }
-    fn all_tags(&self) -> Result<Vec<Tag>> {
-        use schema::tags::dsl::*;
-        Ok(tags
-            .load::<models::Tag>(self)?
+    fn add_pending_subscription_change(&self, change: &PendingSubscriptionChange) -> Result<()> {
+        use schema::bbox_subscriptions::dsl as s_dsl;
+        let subscription_rowid = s_dsl::bbox_subscriptions
+            .select(s_dsl::id)
+            .filter(s_dsl::uid.eq(change.subscription_id.as_str()))
+            .first::<i64>(self)?;
+        let insertable = models::NewBboxSubscriptionChange {
+            subscription_rowid,
+            kind: change.kind.as_str(),
+            item_id: change.item_id.as_str(),
+            created_at: change.created_at.into_inner(),
+        };
+        // Only the first change of an item is kept until the
+        // next digest has been sent, e.g. an added place that
+        // has been updated afterwards is still reported as added.
+        insert_or_ignore_into!(schema::bbox_subscription_change::table, &insertable)
+            .execute(self)?;
+        Ok(())
+    }
+    fn load_pending_subscription_changes(
+        &self,
+        subscription_id: &Id,
+    ) -> Result<Vec<PendingSubscriptionChange>> {
+        use schema::bbox_subscription_change::dsl as c_dsl;
+        use schema::bbox_subscriptions::dsl as s_dsl;
+        c_dsl::bbox_subscription_change
+            .inner_join(s_dsl::bbox_subscriptions)
+            .filter(s_dsl::uid.eq(subscription_id.as_str()))
+            .select((c_dsl::kind, c_dsl::item_id, c_dsl::created_at))
+            .order_by(c_dsl::created_at)
+            .then_order_by(c_dsl::rowid)
+            .load::<models::BboxSubscriptionChange>(self)?
+            .into_iter()
+            .map(|change| {
+                let models::BboxSubscriptionChange {
+                    kind,
+                    item_id,
+                    created_at,
+                } = change;
+                Ok(PendingSubscriptionChange {
+                    subscription_id: subscription_id.clone(),
+                    kind: kind
+                        .parse()
+                        .map_err(|err| RepoError::Other(anyhow!("{}", err)))?,
+                    item_id: item_id.into(),
+                    created_at: TimestampMs::from_inner(created_at),
+                })
+            })
+            .collect()
+    }
+    fn complete_subscription_digest(
+        &self,
+        subscription_id: &Id,
+        changes_until: TimestampMs,
+        sent_at: TimestampMs,
+    ) -> Result<()> {
+        use schema::bbox_subscription_change::dsl as c_dsl;
+        use schema::bbox_subscriptions::dsl as s_dsl;
+        let subscription_rowid = s_dsl::bbox_subscriptions
+            .select(s_dsl::id)
+            .filter(s_dsl::uid.eq(subscription_id.as_str()))
+            .first::<i64>(self)?;
+        diesel::delete(
+            c_dsl::bbox_subscription_change
+                .filter(c_dsl::subscription_rowid.eq(subscription_rowid))
+                .filter(c_dsl::created_at.le(changes_until.into_inner())),
+        )
+        .execute(self)?;
+        diesel::update(s_dsl::bbox_subscriptions.filter(s_dsl::id.eq(subscription_rowid)))
+            .set(s_dsl::last_digest_at.eq(sent_at.into_inner()))
+            .execute(self)?;
+        Ok(())
+    }
+    fn all_tags(&self) -> Result<Vec<Tag>> {
+        use schema::tags::dsl::*;
+        Ok(tags
+            .load::<models::Tag>(self)?
             .into_iter()
             .map(Tag::from)
             .collect())
@@ -1836,7 +2520,7 @@ impl Db for SqliteConnection {
     }
 }
 
-impl OrganizationRepo for SqliteConnection {
+impl OrganizationRepo for Connection {
     fn create_org(&mut self, mut o: Organization) -> Result<()> {
         let org_id = o.id.clone();
         let moderated_tags = std::mem::replace(&mut o.moderated_tags, vec![]);
@@ -1852,54 +2536,150 @@ impl OrganizationRepo for SqliteConnection {
                 );
                 diesel::result::Error::RollbackTransaction
             })?;
-            for ModeratedTag {
-                label,
-                allow_add,
-                allow_remove,
-                require_clearance,
-            } in &moderated_tags
-            {
-                let org_tag = models::NewOrganizationTag {
-                    org_rowid,
-                    tag_label: label,
-                    tag_allow_add: if *allow_add { 1 } else { 0 },
-                    tag_allow_remove: if *allow_remove { 1 } else { 0 },
-                    require_clearance: if *require_clearance { 1 } else { 0 },
-                };
-                diesel::insert_into(schema::organization_tag::table)
-                    .values(&org_tag)
-                    .execute(self)?;
-            }
-            Ok(())
+            insert_organization_tags(self, org_rowid, &moderated_tags)
         })?;
         Ok(())
     }
 
-    fn get_org_by_api_token(&self, token: &str) -> Result<Organization> {
+    fn update_org(&mut self, o: &Organization) -> Result<()> {
         use schema::{organization::dsl as org_dsl, organization_tag::dsl as org_tag_dsl};
+        let org_rowid = resolve_organization_rowid(self, &o.id)?;
+        self.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::update(org_dsl::organization.filter(org_dsl::rowid.eq(org_rowid)))
+                .set(org_dsl::name.eq(&o.name))
+                .execute(self)?;
+            diesel::delete(
+                org_tag_dsl::organization_tag.filter(org_tag_dsl::org_rowid.eq(org_rowid)),
+            )
+            .execute(self)?;
+            insert_organization_tags(self, org_rowid, &o.moderated_tags)
+        })?;
+        Ok(())
+    }
 
-        let models::Organization {
-            rowid,
-            id,
-            name,
-            api_token,
-        } = org_dsl::organization
-            .filter(org_dsl::api_token.eq(token))
+    fn delete_org(&mut self, id: &Id) -> Result<()> {
+        use schema::{
+            event_external_uid, organization, organization_api_token, organization_event_clearance,
+            organization_place_clearance, organization_tag, webhook, webhook_delivery,
+        };
+        let org_rowid = resolve_organization_rowid(self, id)?;
+        self.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::delete(
+                webhook_delivery::table.filter(
+                    webhook_delivery::webhook_rowid.eq_any(
+                        webhook::table
+                            .select(webhook::rowid)
+                            .filter(webhook::org_rowid.eq(org_rowid)),
+                    ),
+                ),
+            )
+            .execute(self)?;
+            diesel::delete(webhook::table.filter(webhook::org_rowid.eq(org_rowid)))
+                .execute(self)?;
+            diesel::delete(
+                event_external_uid::table.filter(event_external_uid::org_rowid.eq(org_rowid)),
+            )
+            .execute(self)?;
+            diesel::delete(
+                organization_place_clearance::table
+                    .filter(organization_place_clearance::org_rowid.eq(org_rowid)),
+            )
+            .execute(self)?;
+            diesel::delete(
+                organization_event_clearance::table
+                    .filter(organization_event_clearance::org_rowid.eq(org_rowid)),
+            )
+            .execute(self)?;
+            diesel::delete(
+                organization_api_token::table
+                    .filter(organization_api_token::org_rowid.eq(org_rowid)),
+            )
+            .execute(self)?;
+            diesel::delete(
+                organization_tag::table.filter(organization_tag::org_rowid.eq(org_rowid)),
+            )
+            .execute(self)?;
+            diesel::delete(organization::table.filter(organization::rowid.eq(org_rowid)))
+                .execute(self)?;
+            Ok(())
+        })?;
+        Ok(())
+    }
+
+    fn get_org(&self, id: &Id) -> Result<Organization> {
+        use schema::organization::dsl;
+        let org = dsl::organization
+            .filter(dsl::id.eq(id.as_str()))
             .first(self)?;
+        load_organization(self, org)
+    }
 
-        let moderated_tags = org_tag_dsl::organization_tag
-            .filter(org_tag_dsl::org_rowid.eq(rowid))
-            .load::<models::OrganizationTag>(self)?
+    fn all_orgs(&self) -> Result<Vec<Organization>> {
+        use schema::organization::dsl;
+        dsl::organization
+            .order_by(dsl::name)
+            .load::<models::Organization>(self)?
+            .into_iter()
+            .map(|org| load_organization(self, org))
+            .collect()
+    }
+
+    fn load_owned_place_ids(&self, org_id: &Id) -> Result<Vec<Id>> {
+        use schema::{organization_tag, place, place_revision, place_revision_tag};
+        let org_rowid = resolve_organization_rowid(self, org_id)?;
+        Ok(place_revision::table
+            .inner_join(
+                place::table.on(place_revision::parent_rowid
+                    .eq(place::rowid)
+                    .and(place_revision::rev.eq(place::current_rev))),
+            )
+            .select(place::id)
+            .filter(
+                place_revision::current_status
+                    .ge(ReviewStatusPrimitive::from(ReviewStatus::Created)),
+            )
+            .filter(
+                place_revision::rowid.eq_any(
+                    place_revision_tag::table
+                        .select(place_revision_tag::parent_rowid)
+                        .filter(
+                            place_revision_tag::tag.eq_any(
+                                organization_tag::table
+                                    .select(organization_tag::tag_label)
+                                    .filter(organization_tag::org_rowid.eq(org_rowid)),
+                            ),
+                        ),
+                ),
+            )
+            .order_by(place::id)
+            .load::<String>(self)?
             .into_iter()
             .map(Into::into)
-            .collect();
+            .collect())
+    }
 
-        Ok(Organization {
-            id: id.into(),
-            name,
-            api_token,
-            moderated_tags,
-        })
+    fn load_owned_event_ids(&self, org_id: &Id) -> Result<Vec<Id>> {
+        use schema::{event_tags, events, organization_tag};
+        let org_rowid = resolve_organization_rowid(self, org_id)?;
+        Ok(events::table
+            .select(events::uid)
+            .filter(events::archived.is_null())
+            .filter(
+                events::id.eq_any(
+                    event_tags::table.select(event_tags::event_id).filter(
+                        event_tags::tag.eq_any(
+                            organization_tag::table
+                                .select(organization_tag::tag_label)
+                                .filter(organization_tag::org_rowid.eq(org_rowid)),
+                        ),
+                    ),
+                ),
+            )
+            .order_by(events::start)
+            .load::<String>(self)?
+            .into_iter()
+            .map(Into::into)
+            .collect())
     }
 
     fn map_tag_to_clearance_org_id(&self, tag: &str) -> Result<Option<Id>> {
@@ -1941,7 +2721,7 @@ impl OrganizationRepo for SqliteConnection {
     }
 }
 
-impl PlaceClearanceRepo for SqliteConnection {
+impl PlaceClearanceRepo for Connection {
     fn add_pending_clearance_for_places(
         &self,
         org_ids: &[Id],
@@ -1966,8 +2746,7 @@ impl PlaceClearanceRepo for SqliteConnection {
                 last_cleared_revision,
             };
             insert_count +=
-                diesel::insert_or_ignore_into(schema::organization_place_clearance::table)
-                    .values(&insertable)
+                insert_or_ignore_into!(schema::organization_place_clearance::table, &insertable)
                     .execute(self)?;
         }
         Ok(insert_count)
@@ -2126,7 +2905,175 @@ impl PlaceClearanceRepo for SqliteConnection {
     }
 }
 
-impl UserTokenRepo for SqliteConnection {
+impl EventClearanceRepo for Connection {
+    fn add_pending_clearance_for_events(
+        &self,
+        org_ids: &[Id],
+        pending_clearance: &PendingClearanceForEvent,
+    ) -> Result<usize> {
+        let PendingClearanceForEvent {
+            event_id,
+            created_at,
+            last_cleared_revision,
+        } = pending_clearance;
+        let event_rowid = resolve_event_id(self, event_id.as_str())?;
+        let created_at = created_at.into_inner();
+        let last_cleared_revision =
+            last_cleared_revision.map(|rev| RevisionValue::from(rev) as i64);
+        let mut insert_count = 0;
+        for org_id in org_ids {
+            let org_rowid = resolve_organization_rowid(self, org_id)?;
+            let insertable = models::NewPendingClearanceForEvent {
+                org_rowid,
+                event_rowid,
+                created_at,
+                last_cleared_revision,
+            };
+            insert_count +=
+                insert_or_ignore_into!(schema::organization_event_clearance::table, &insertable)
+                    .execute(self)?;
+        }
+        Ok(insert_count)
+    }
+
+    fn count_pending_clearances_for_events(&self, org_id: &Id) -> Result<u64> {
+        use schema::organization::dsl as org_dsl;
+        use schema::organization_event_clearance::dsl;
+        Ok(schema::organization_event_clearance::table
+            .filter(
+                dsl::org_rowid.eq_any(
+                    schema::organization::table
+                        .select(org_dsl::rowid)
+                        .filter(org_dsl::id.eq(org_id.as_str())),
+                ),
+            )
+            .count()
+            .get_result::<i64>(self)? as u64)
+    }
+
+    fn list_pending_clearances_for_events(
+        &self,
+        org_id: &Id,
+        pagination: &Pagination,
+    ) -> Result<Vec<PendingClearanceForEvent>> {
+        use schema::events::dsl as e_dsl;
+        use schema::organization::dsl as org_dsl;
+        use schema::organization_event_clearance::dsl;
+        let mut query = schema::organization_event_clearance::table
+            .inner_join(schema::events::table)
+            .select((e_dsl::uid, dsl::created_at, dsl::last_cleared_revision))
+            .filter(
+                dsl::org_rowid.eq_any(
+                    schema::organization::table
+                        .select(org_dsl::rowid)
+                        .filter(org_dsl::id.eq(org_id.as_str())),
+                ),
+            )
+            .order_by(dsl::created_at)
+            .into_boxed();
+
+        // Pagination
+        let offset = pagination.offset.unwrap_or(0);
+        if offset > 0 {
+            query = query.offset(offset as i64);
+        }
+        if let Some(limit) = pagination.limit {
+            query = query.limit(limit as i64);
+        }
+
+        Ok(query
+            .load::<models::PendingClearanceForEvent>(self)?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    fn load_pending_clearances_for_events(
+        &self,
+        org_id: &Id,
+        event_ids: &[&str],
+    ) -> Result<Vec<PendingClearanceForEvent>> {
+        use schema::events::dsl as e_dsl;
+        use schema::organization::dsl as org_dsl;
+        use schema::organization_event_clearance::dsl;
+        Ok(schema::organization_event_clearance::table
+            .inner_join(schema::events::table)
+            .select((e_dsl::uid, dsl::created_at, dsl::last_cleared_revision))
+            .filter(
+                dsl::org_rowid.eq_any(
+                    schema::organization::table
+                        .select(org_dsl::rowid)
+                        .filter(org_dsl::id.eq(org_id.as_str())),
+                ),
+            )
+            .filter(e_dsl::uid.eq_any(event_ids))
+            .load::<models::PendingClearanceForEvent>(self)?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    fn update_pending_clearances_for_events(
+        &self,
+        org_id: &Id,
+        clearances: &[ClearanceForEvent],
+    ) -> Result<usize> {
+        let org_rowid = resolve_organization_rowid(self, org_id)?;
+        let created_at = TimestampMs::now().into_inner();
+        let mut total_rows_affected = 0;
+        for clearance in clearances {
+            let ClearanceForEvent {
+                event_id,
+                cleared_revision,
+            } = clearance;
+            let (event_rowid, cleared_revision) = if let Some(cleared_revision) = cleared_revision {
+                let event_rowid =
+                    resolve_event_id_verify_revision(self, event_id.as_str(), *cleared_revision)?;
+                (event_rowid, *cleared_revision)
+            } else {
+                resolve_event_id_with_current_revision(self, event_id.as_str())?
+            };
+            use schema::organization_event_clearance::dsl;
+            let last_cleared_revision = Some(RevisionValue::from(cleared_revision) as i64);
+            let updatable = models::NewPendingClearanceForEvent {
+                org_rowid,
+                event_rowid,
+                created_at,
+                last_cleared_revision,
+            };
+            let rows_affected = diesel::update(schema::organization_event_clearance::table)
+                .set(&updatable)
+                .filter(dsl::org_rowid.eq(org_rowid))
+                .filter(dsl::event_rowid.eq(event_rowid))
+                .execute(self)?;
+            debug_assert!(rows_affected <= 1);
+            total_rows_affected += rows_affected;
+        }
+        Ok(total_rows_affected)
+    }
+
+    fn cleanup_pending_clearances_for_events(&self, org_id: &Id) -> Result<u64> {
+        let org_rowid = resolve_organization_rowid(self, org_id)?;
+        use schema::events::dsl as e_dsl;
+        use schema::organization_event_clearance::dsl;
+        let subselect = schema::events::table
+            .inner_join(schema::organization_event_clearance::table)
+            .select(dsl::rowid)
+            .filter(dsl::org_rowid.eq(org_rowid))
+            .filter(dsl::last_cleared_revision.eq(e_dsl::current_rev.nullable()));
+        // TODO: Diesel 1.4.5 does not allow to use a subselect in the
+        // following delete statement and requires to temporarily load
+        // the subselect results into memory
+        let delete_rowids = subselect.load::<i64>(self)?;
+        let delete_count = diesel::delete(
+            schema::organization_event_clearance::table.filter(dsl::rowid.eq_any(delete_rowids)),
+        )
+        .execute(self)?;
+        Ok(delete_count as u64)
+    }
+}
+
+impl UserTokenRepo for Connection {
     fn replace_user_token(&self, token: UserToken) -> Result<EmailNonce> {
         use schema::user_tokens::dsl;
         let user_id = resolve_user_created_by_email(self, &token.email_nonce.email)?;
@@ -2189,3 +3136,897 @@ impl UserTokenRepo for SqliteConnection {
             .into())
     }
 }
+
+fn try_claim_queued_job(
+    conn: &Connection,
+    job: models::QueuedJob,
+    lease_until: TimestampMs,
+) -> Result<Option<QueuedJob>> {
+    use schema::job_queue::dsl;
+    let attempts = job.attempts + 1;
+    // The job might have been claimed concurrently by another worker
+    // since it has been loaded
+    let count = diesel::update(
+        dsl::job_queue
+            .filter(dsl::rowid.eq(job.rowid))
+            .filter(dsl::run_at.eq(job.run_at)),
+    )
+    .set((
+        dsl::run_at.eq(lease_until.into_inner()),
+        dsl::attempts.eq(attempts),
+    ))
+    .execute(conn)?;
+    debug_assert!(count <= 1);
+    if count == 0 {
+        return Ok(None);
+    }
+    Ok(Some(QueuedJob {
+        id: job.id.into(),
+        payload: job.payload,
+        attempts: attempts as u32,
+    }))
+}
+
+impl JobQueue for Connection {
+    fn enqueue_job(&self, id: &Id, payload: &str, run_at: TimestampMs) -> Result<()> {
+        let new_job = models::NewQueuedJob {
+            id: id.as_str(),
+            created_at: TimestampMs::now().into_inner(),
+            run_at: run_at.into_inner(),
+            attempts: 0,
+            payload,
+        };
+        diesel::insert_into(schema::job_queue::table)
+            .values(&new_job)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn claim_next_job(
+        &self,
+        now: TimestampMs,
+        lease_until: TimestampMs,
+    ) -> Result<Option<QueuedJob>> {
+        use schema::job_queue::dsl;
+        loop {
+            let next_job = dsl::job_queue
+                .select((
+                    dsl::rowid,
+                    dsl::id,
+                    dsl::run_at,
+                    dsl::attempts,
+                    dsl::payload,
+                ))
+                .filter(dsl::run_at.le(now.into_inner()))
+                .order_by((dsl::run_at, dsl::rowid))
+                .first::<models::QueuedJob>(self)
+                .optional()?;
+            let next_job = match next_job {
+                Some(next_job) => next_job,
+                None => return Ok(None),
+            };
+            if let Some(claimed_job) = try_claim_queued_job(self, next_job, lease_until)? {
+                return Ok(Some(claimed_job));
+            }
+        }
+    }
+
+    fn claim_job(
+        &self,
+        id: &Id,
+        now: TimestampMs,
+        lease_until: TimestampMs,
+    ) -> Result<Option<QueuedJob>> {
+        use schema::job_queue::dsl;
+        let job = dsl::job_queue
+            .select((
+                dsl::rowid,
+                dsl::id,
+                dsl::run_at,
+                dsl::attempts,
+                dsl::payload,
+            ))
+            .filter(dsl::id.eq(id.as_str()))
+            .filter(dsl::run_at.le(now.into_inner()))
+            .first::<models::QueuedJob>(self)
+            .optional()?;
+        if let Some(job) = job {
+            try_claim_queued_job(self, job, lease_until)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn reschedule_job(&self, id: &Id, run_at: TimestampMs, last_error: &str) -> Result<()> {
+        use schema::job_queue::dsl;
+        let count = diesel::update(dsl::job_queue.filter(dsl::id.eq(id.as_str())))
+            .set((
+                dsl::run_at.eq(run_at.into_inner()),
+                dsl::last_error.eq(last_error),
+            ))
+            .execute(self)?;
+        if count == 0 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+
+    fn complete_job(&self, id: &Id) -> Result<()> {
+        use schema::job_queue::dsl;
+        diesel::delete(dsl::job_queue.filter(dsl::id.eq(id.as_str()))).execute(self)?;
+        Ok(())
+    }
+}
+
+fn split_comma_separated(s: &str) -> impl Iterator<Item = &str> {
+    s.split(',').filter(|s| !s.is_empty())
+}
+
+fn load_webhook(webhook: models::Webhook, org_id: String) -> Result<Webhook> {
+    let models::Webhook {
+        id,
+        created_at,
+        url,
+        secret,
+        event_types,
+        south_west_lat,
+        south_west_lng,
+        north_east_lat,
+        north_east_lng,
+        tags,
+        ..
+    } = webhook;
+    let event_types = split_comma_separated(&event_types)
+        .map(|t| {
+            t.parse()
+                .map_err(|err| RepoError::Other(anyhow!("{}", err)))
+        })
+        .collect::<Result<_>>()?;
+    let bbox = match (
+        south_west_lat,
+        south_west_lng,
+        north_east_lat,
+        north_east_lng,
+    ) {
+        (Some(sw_lat), Some(sw_lng), Some(ne_lat), Some(ne_lng)) => Some(MapBbox::new(
+            MapPoint::from_lat_lng_deg(sw_lat, sw_lng),
+            MapPoint::from_lat_lng_deg(ne_lat, ne_lng),
+        )),
+        _ => None,
+    };
+    Ok(Webhook {
+        id: id.into(),
+        org_id: org_id.into(),
+        url: url
+            .parse()
+            .map_err(|err| RepoError::Other(anyhow!("Invalid webhook URL: {}", err)))?,
+        secret,
+        event_types,
+        bbox,
+        tags: split_comma_separated(&tags)
+            .map(ToString::to_string)
+            .collect(),
+        created_at: TimestampMs::from_inner(created_at),
+    })
+}
+
+fn webhook_delivery_status_to_primitive(status: WebhookDeliveryStatus) -> i16 {
+    match status {
+        WebhookDeliveryStatus::Pending => 0,
+        WebhookDeliveryStatus::Delivered => 1,
+        WebhookDeliveryStatus::Failed => 2,
+    }
+}
+
+fn load_webhook_delivery(
+    delivery: models::WebhookDelivery,
+    webhook_id: String,
+) -> Result<WebhookDelivery> {
+    let models::WebhookDelivery {
+        id,
+        created_at,
+        updated_at,
+        event_type,
+        payload,
+        status,
+        attempts,
+        response_status,
+        last_error,
+        ..
+    } = delivery;
+    let status = match status {
+        0 => WebhookDeliveryStatus::Pending,
+        1 => WebhookDeliveryStatus::Delivered,
+        2 => WebhookDeliveryStatus::Failed,
+        _ => {
+            return Err(RepoError::Other(anyhow!(
+                "Invalid webhook delivery status: {}",
+                status
+            )))
+        }
+    };
+    Ok(WebhookDelivery {
+        id: id.into(),
+        webhook_id: webhook_id.into(),
+        event_type: event_type
+            .parse()
+            .map_err(|err| RepoError::Other(anyhow!("{}", err)))?,
+        payload,
+        status,
+        attempts: attempts as u32,
+        response_status: response_status.map(|s| s as u16),
+        last_error,
+        created_at: TimestampMs::from_inner(created_at),
+        updated_at: TimestampMs::from_inner(updated_at),
+    })
+}
+
+fn resolve_webhook_rowid(conn: &Connection, id: &Id) -> Result<i64> {
+    use schema::webhook::dsl;
+    Ok(schema::webhook::table
+        .select(dsl::rowid)
+        .filter(dsl::id.eq(id.as_str()))
+        .first::<i64>(conn)?)
+}
+
+impl WebhookRepo for Connection {
+    fn create_webhook(&self, webhook: &Webhook) -> Result<()> {
+        let org_rowid = resolve_organization_rowid(self, &webhook.org_id)?;
+        let (south_west_lat, south_west_lng, north_east_lat, north_east_lng) =
+            if let Some(bbox) = &webhook.bbox {
+                let (sw_lat, sw_lng) = bbox.southwest().to_lat_lng_deg();
+                let (ne_lat, ne_lng) = bbox.northeast().to_lat_lng_deg();
+                (Some(sw_lat), Some(sw_lng), Some(ne_lat), Some(ne_lng))
+            } else {
+                (None, None, None, None)
+            };
+        let new_webhook = models::NewWebhook {
+            org_rowid,
+            id: webhook.id.as_str(),
+            created_at: webhook.created_at.into_inner(),
+            url: webhook.url.as_str(),
+            secret: &webhook.secret,
+            event_types: webhook
+                .event_types
+                .iter()
+                .map(|t| t.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+            south_west_lat,
+            south_west_lng,
+            north_east_lat,
+            north_east_lng,
+            tags: webhook.tags.join(","),
+        };
+        diesel::insert_into(schema::webhook::table)
+            .values(&new_webhook)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn delete_webhook(&self, org_id: &Id, id: &Id) -> Result<()> {
+        use schema::webhook::dsl;
+        let org_rowid = resolve_organization_rowid(self, org_id)?;
+        let rowid = dsl::webhook
+            .select(dsl::rowid)
+            .filter(dsl::id.eq(id.as_str()))
+            .filter(dsl::org_rowid.eq(org_rowid))
+            .first::<i64>(self)?;
+        diesel::delete(
+            schema::webhook_delivery::table
+                .filter(schema::webhook_delivery::webhook_rowid.eq(rowid)),
+        )
+        .execute(self)?;
+        diesel::delete(dsl::webhook.filter(dsl::rowid.eq(rowid))).execute(self)?;
+        Ok(())
+    }
+
+    fn get_webhook(&self, id: &Id) -> Result<Webhook> {
+        use schema::{organization, webhook};
+        let (webhook, org_id) = webhook::table
+            .inner_join(organization::table)
+            .select((webhook::all_columns, organization::id))
+            .filter(webhook::id.eq(id.as_str()))
+            .first::<(models::Webhook, String)>(self)?;
+        load_webhook(webhook, org_id)
+    }
+
+    fn all_webhooks(&self) -> Result<Vec<Webhook>> {
+        use schema::{organization, webhook};
+        webhook::table
+            .inner_join(organization::table)
+            .select((webhook::all_columns, organization::id))
+            .order_by(webhook::rowid)
+            .load::<(models::Webhook, String)>(self)?
+            .into_iter()
+            .map(|(webhook, org_id)| load_webhook(webhook, org_id))
+            .collect()
+    }
+
+    fn load_webhooks_of_org(&self, org_id: &Id) -> Result<Vec<Webhook>> {
+        use schema::{organization, webhook};
+        webhook::table
+            .inner_join(organization::table)
+            .select((webhook::all_columns, organization::id))
+            .filter(organization::id.eq(org_id.as_str()))
+            .order_by(webhook::rowid)
+            .load::<(models::Webhook, String)>(self)?
+            .into_iter()
+            .map(|(webhook, org_id)| load_webhook(webhook, org_id))
+            .collect()
+    }
+
+    fn create_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()> {
+        let webhook_rowid = resolve_webhook_rowid(self, &delivery.webhook_id)?;
+        let new_delivery = models::NewWebhookDelivery {
+            webhook_rowid,
+            id: delivery.id.as_str(),
+            created_at: delivery.created_at.into_inner(),
+            updated_at: delivery.updated_at.into_inner(),
+            event_type: delivery.event_type.as_str(),
+            payload: &delivery.payload,
+            status: webhook_delivery_status_to_primitive(delivery.status),
+            attempts: i64::from(delivery.attempts),
+        };
+        diesel::insert_into(schema::webhook_delivery::table)
+            .values(&new_delivery)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn get_webhook_delivery(&self, id: &Id) -> Result<WebhookDelivery> {
+        use schema::{webhook, webhook_delivery};
+        let (delivery, webhook_id) = webhook_delivery::table
+            .inner_join(webhook::table)
+            .select((webhook_delivery::all_columns, webhook::id))
+            .filter(webhook_delivery::id.eq(id.as_str()))
+            .first::<(models::WebhookDelivery, String)>(self)?;
+        load_webhook_delivery(delivery, webhook_id)
+    }
+
+    fn update_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<()> {
+        use schema::webhook_delivery::dsl;
+        let count = diesel::update(dsl::webhook_delivery.filter(dsl::id.eq(delivery.id.as_str())))
+            .set((
+                dsl::updated_at.eq(delivery.updated_at.into_inner()),
+                dsl::status.eq(webhook_delivery_status_to_primitive(delivery.status)),
+                dsl::attempts.eq(i64::from(delivery.attempts)),
+                dsl::response_status.eq(delivery.response_status.map(i64::from)),
+                dsl::last_error.eq(delivery.last_error.as_deref()),
+            ))
+            .execute(self)?;
+        if count == 0 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+
+    fn load_webhook_deliveries(
+        &self,
+        webhook_id: &Id,
+        pagination: &Pagination,
+    ) -> Result<Vec<WebhookDelivery>> {
+        use schema::{webhook, webhook_delivery};
+        let mut query = webhook_delivery::table
+            .inner_join(webhook::table)
+            .select((webhook_delivery::all_columns, webhook::id))
+            .filter(webhook::id.eq(webhook_id.as_str()))
+            .order_by((
+                webhook_delivery::created_at.desc(),
+                webhook_delivery::rowid.desc(),
+            ))
+            .into_boxed();
+
+        // Pagination
+        let offset = pagination.offset.unwrap_or(0);
+        if offset > 0 {
+            query = query.offset(offset as i64);
+        }
+        if let Some(limit) = pagination.limit {
+            query = query.limit(limit as i64);
+        }
+
+        query
+            .load::<(models::WebhookDelivery, String)>(self)?
+            .into_iter()
+            .map(|(delivery, webhook_id)| load_webhook_delivery(delivery, webhook_id))
+            .collect()
+    }
+}
+
+impl GeoCodingCacheRepo for Connection {
+    fn get_geocoding_cache_entry(&self, query: &str) -> Result<Option<GeoCodingCacheEntry>> {
+        use schema::geocoding_cache::dsl;
+        Ok(dsl::geocoding_cache
+            .filter(dsl::query.eq(query))
+            .first::<models::GeoCodingCacheEntry>(self)
+            .optional()?
+            .map(load_geocoding_cache_entry))
+    }
+
+    fn put_geocoding_cache_entry(&self, entry: &GeoCodingCacheEntry) -> Result<()> {
+        use schema::geocoding_cache::dsl;
+        let (lat, lng) = entry
+            .pos
+            .map(|pos| {
+                let (lat, lng) = pos.to_lat_lng_deg();
+                (Some(lat), Some(lng))
+            })
+            .unwrap_or_default();
+        let address = entry.address.as_ref();
+        let new_entry = models::NewGeoCodingCacheEntry {
+            query: &entry.query,
+            cached_at: entry.cached_at.into_inner(),
+            lat,
+            lng,
+            street: address.and_then(|a| a.street.as_deref()),
+            zip: address.and_then(|a| a.zip.as_deref()),
+            city: address.and_then(|a| a.city.as_deref()),
+            country: address.and_then(|a| a.country.as_deref()),
+            state: address.and_then(|a| a.state.as_deref()),
+        };
+        diesel::delete(dsl::geocoding_cache.filter(dsl::query.eq(&entry.query))).execute(self)?;
+        diesel::insert_into(schema::geocoding_cache::table)
+            .values(&new_entry)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn delete_geocoding_cache_entries_before(&self, cached_before: TimestampMs) -> Result<usize> {
+        use schema::geocoding_cache::dsl;
+        Ok(diesel::delete(
+            dsl::geocoding_cache.filter(dsl::cached_at.lt(cached_before.into_inner())),
+        )
+        .execute(self)?)
+    }
+}
+
+fn load_geocoding_cache_entry(from: models::GeoCodingCacheEntry) -> GeoCodingCacheEntry {
+    let models::GeoCodingCacheEntry {
+        query,
+        cached_at,
+        lat,
+        lng,
+        street,
+        zip,
+        city,
+        country,
+        state,
+        ..
+    } = from;
+    let pos = match (lat, lng) {
+        (Some(lat), Some(lng)) => MapPoint::try_from_lat_lng_deg(lat, lng).ok(),
+        _ => None,
+    };
+    let address = Address {
+        street,
+        zip,
+        city,
+        country,
+        state,
+    };
+    GeoCodingCacheEntry {
+        query,
+        cached_at: TimestampMs::from_inner(cached_at),
+        pos,
+        address: Some(address).filter(|a| !a.is_empty()),
+    }
+}
+
+fn load_api_token(token: models::OrganizationApiToken, org_id: String) -> Result<ApiToken> {
+    let models::OrganizationApiToken {
+        id,
+        name,
+        token_hash,
+        scopes,
+        created_at,
+        expires_at,
+        last_used_at,
+        revoked_at,
+        ..
+    } = token;
+    let scopes = split_comma_separated(&scopes)
+        .map(|s| {
+            s.parse()
+                .map_err(|err| RepoError::Other(anyhow!("{}", err)))
+        })
+        .collect::<Result<_>>()?;
+    Ok(ApiToken {
+        id: id.into(),
+        org_id: org_id.into(),
+        name,
+        token_hash,
+        scopes,
+        created_at: TimestampMs::from_inner(created_at),
+        expires_at: expires_at.map(TimestampMs::from_inner),
+        last_used_at: last_used_at.map(TimestampMs::from_inner),
+        revoked_at: revoked_at.map(TimestampMs::from_inner),
+    })
+}
+
+impl ApiTokenRepo for Connection {
+    fn create_api_token(&self, token: &ApiToken) -> Result<()> {
+        let org_rowid = resolve_organization_rowid(self, &token.org_id)?;
+        let new_token = models::NewOrganizationApiToken {
+            org_rowid,
+            id: token.id.as_str(),
+            name: &token.name,
+            token_hash: &token.token_hash,
+            scopes: token
+                .scopes
+                .iter()
+                .map(|s| s.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+            created_at: token.created_at.into_inner(),
+            expires_at: token.expires_at.map(TimestampMs::into_inner),
+        };
+        diesel::insert_into(schema::organization_api_token::table)
+            .values(&new_token)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn get_api_token_by_hash(&self, token_hash: &str) -> Result<ApiToken> {
+        use schema::{organization, organization_api_token};
+        let (token, org_id) = organization_api_token::table
+            .inner_join(organization::table)
+            .select((organization_api_token::all_columns, organization::id))
+            .filter(organization_api_token::token_hash.eq(token_hash))
+            .first::<(models::OrganizationApiToken, String)>(self)?;
+        load_api_token(token, org_id)
+    }
+
+    fn load_api_tokens_of_org(&self, org_id: &Id) -> Result<Vec<ApiToken>> {
+        use schema::organization_api_token::dsl;
+        let org_rowid = resolve_organization_rowid(self, org_id)?;
+        dsl::organization_api_token
+            .filter(dsl::org_rowid.eq(org_rowid))
+            .order_by(dsl::created_at)
+            .load::<models::OrganizationApiToken>(self)?
+            .into_iter()
+            .map(|token| load_api_token(token, org_id.to_string()))
+            .collect()
+    }
+
+    fn update_api_token_last_used_at(&self, id: &Id, last_used_at: TimestampMs) -> Result<()> {
+        use schema::organization_api_token::dsl;
+        let count = diesel::update(dsl::organization_api_token.filter(dsl::id.eq(id.as_str())))
+            .set(dsl::last_used_at.eq(last_used_at.into_inner()))
+            .execute(self)?;
+        if count == 0 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+
+    fn revoke_api_token(&self, org_id: &Id, id: &Id, revoked_at: TimestampMs) -> Result<()> {
+        use schema::organization_api_token::dsl;
+        let org_rowid = resolve_organization_rowid(self, org_id)?;
+        let count = diesel::update(
+            dsl::organization_api_token
+                .filter(dsl::id.eq(id.as_str()))
+                .filter(dsl::org_rowid.eq(org_rowid))
+                .filter(dsl::revoked_at.is_null()),
+        )
+        .set(dsl::revoked_at.eq(revoked_at.into_inner()))
+        .execute(self)?;
+        if count == 0 {
+            return Err(RepoError::NotFound);
+        }
+        Ok(())
+    }
+}
+
+impl ImageRepo for Connection {
+    fn create_image(&self, image: &Image) -> Result<()> {
+        let new_image = models::NewImage {
+            id: image.id.as_str(),
+            format: image.format.as_str(),
+            width: image.width.into(),
+            height: image.height.into(),
+            created_at: image.created_at.into_inner(),
+            created_by: image.created_by.as_ref().map(|email| email.as_str()),
+        };
+        diesel::insert_into(schema::image::table)
+            .values(&new_image)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn get_image(&self, id: &Id) -> Result<Image> {
+        use schema::image::dsl;
+        let models::Image {
+            id,
+            format,
+            width,
+            height,
+            created_at,
+            created_by,
+            ..
+        } = dsl::image
+            .filter(dsl::id.eq(id.as_str()))
+            .first::<models::Image>(self)?;
+        Ok(Image {
+            id: id.into(),
+            format: format
+                .parse()
+                .map_err(|err| RepoError::Other(anyhow!("{}", err)))?,
+            width: width as u32,
+            height: height as u32,
+            created_at: TimestampMs::from_inner(created_at),
+            created_by: created_by.map(Into::into),
+        })
+    }
+}
+
+impl UserDataRepo for Connection {
+    fn load_user_activities(&self, email: &str) -> Result<Vec<UserActivity>> {
+        use schema::{
+            events, image, place, place_rating, place_rating_comment, place_revision,
+            place_revision_review, users,
+        };
+        let user_rowid = users::table
+            .select(users::id)
+            .filter(users::email.eq(email))
+            .first::<i64>(self)?;
+        let activity = |kind, id: String, revision: Option<i64>, at: Option<i64>| UserActivity {
+            kind,
+            id: id.into(),
+            revision: revision.map(|rev| Revision::from(rev as u64)),
+            at: at.map(TimestampMs::from_inner),
+        };
+        let mut activities = vec![];
+        for (id, rev, created_at) in place_revision::table
+            .inner_join(place::table)
+            .select((place::id, place_revision::rev, place_revision::created_at))
+            .filter(place_revision::created_by.eq(user_rowid))
+            .order_by(place_revision::created_at)
+            .load::<(String, i64, i64)>(self)?
+        {
+            activities.push(activity(
+                UserActivityKind::PlaceRevision,
+                id,
+                Some(rev),
+                Some(created_at),
+            ));
+        }
+        for (id, rev, created_at) in place_revision_review::table
+            .inner_join(place_revision::table.inner_join(place::table))
+            .select((
+                place::id,
+                place_revision::rev,
+                place_revision_review::created_at,
+            ))
+            .filter(place_revision_review::created_by.eq(user_rowid))
+            .order_by(place_revision_review::created_at)
+            .load::<(String, i64, i64)>(self)?
+        {
+            activities.push(activity(
+                UserActivityKind::PlaceReview,
+                id,
+                Some(rev),
+                Some(created_at),
+            ));
+        }
+        for (id, created_at) in place_rating::table
+            .select((place_rating::id, place_rating::created_at))
+            .filter(place_rating::created_by.eq(user_rowid))
+            .load::<(String, i64)>(self)?
+        {
+            activities.push(activity(
+                UserActivityKind::PlaceRating,
+                id,
+                None,
+                Some(created_at),
+            ));
+        }
+        for (id, archived_at) in place_rating::table
+            .select((place_rating::id, place_rating::archived_at))
+            .filter(place_rating::archived_by.eq(user_rowid))
+            .load::<(String, Option<i64>)>(self)?
+        {
+            activities.push(activity(
+                UserActivityKind::PlaceRatingArchived,
+                id,
+                None,
+                archived_at,
+            ));
+        }
+        for (id, created_at) in place_rating_comment::table
+            .select((place_rating_comment::id, place_rating_comment::created_at))
+            .filter(place_rating_comment::created_by.eq(user_rowid))
+            .load::<(String, i64)>(self)?
+        {
+            activities.push(activity(
+                UserActivityKind::PlaceRatingComment,
+                id,
+                None,
+                Some(created_at),
+            ));
+        }
+        for (id, archived_at) in place_rating_comment::table
+            .select((place_rating_comment::id, place_rating_comment::archived_at))
+            .filter(place_rating_comment::archived_by.eq(user_rowid))
+            .load::<(String, Option<i64>)>(self)?
+        {
+            activities.push(activity(
+                UserActivityKind::PlaceRatingCommentArchived,
+                id,
+                None,
+                archived_at,
+            ));
+        }
+        for id in events::table
+            .select(events::uid)
+            .filter(events::created_by.eq(user_rowid))
+            .load::<String>(self)?
+        {
+            activities.push(activity(UserActivityKind::Event, id, None, None));
+        }
+        for (id, created_at) in image::table
+            .select((image::id, image::created_at))
+            .filter(image::created_by.eq(email))
+            .load::<(String, i64)>(self)?
+        {
+            activities.push(activity(
+                UserActivityKind::Image,
+                id,
+                None,
+                Some(created_at),
+            ));
+        }
+        Ok(activities)
+    }
+
+    fn pseudonymize_user(&self, email: &str, pseudonym: &User) -> Result<()> {
+        use schema::{audit_log, image, job_queue, user_tokens, users, webhook_delivery};
+        let user_rowid = users::table
+            .select(users::id)
+            .filter(users::email.eq(email))
+            .first::<i64>(self)?;
+        diesel::delete(user_tokens::table.filter(user_tokens::user_id.eq(user_rowid)))
+            .execute(self)?;
+        self.delete_bbox_subscriptions_by_email(email)?;
+        // All activity logs refer to the row of the user and
+        // not to the e-mail address
+        diesel::update(users::table.filter(users::id.eq(user_rowid)))
+            .set(&models::NewUser::from(pseudonym))
+            .execute(self)?;
+        if pseudonym.locale.is_none() {
+            // Missing values are skipped by the changeset
+            diesel::update(users::table.filter(users::id.eq(user_rowid)))
+                .set(users::locale.eq(None::<String>))
+                .execute(self)?;
+        }
+        diesel::update(image::table.filter(image::created_by.eq(email)))
+            .set(image::created_by.eq(&pseudonym.email))
+            .execute(self)?;
+        // The audit log refers to users by their e-mail address
+        diesel::update(audit_log::table.filter(audit_log::actor.eq(email)))
+            .set(audit_log::actor.eq(&pseudonym.email))
+            .execute(self)?;
+        diesel::update(audit_log::table.filter(audit_log::target_ids.eq(email)))
+            .set(audit_log::target_ids.eq(&pseudonym.email))
+            .execute(self)?;
+        // Serialized copies of the e-mail address, e.g. in the JSON
+        // payloads of webhooks and jobs or in audit log values
+        let pattern = format!("%{}%", escape_like_pattern(email));
+        let replace = |text: String| text.replace(email, &pseudonym.email);
+        for (rowid, payload) in webhook_delivery::table
+            .select((webhook_delivery::rowid, webhook_delivery::payload))
+            .filter(webhook_delivery::payload.like(&pattern).escape('\\'))
+            .load::<(i64, String)>(self)?
+        {
+            diesel::update(webhook_delivery::table.filter(webhook_delivery::rowid.eq(rowid)))
+                .set(webhook_delivery::payload.eq(replace(payload)))
+                .execute(self)?;
+        }
+        for (rowid, payload) in job_queue::table
+            .select((job_queue::rowid, job_queue::payload))
+            .filter(job_queue::payload.like(&pattern).escape('\\'))
+            .load::<(i64, String)>(self)?
+        {
+            diesel::update(job_queue::table.filter(job_queue::rowid.eq(rowid)))
+                .set(job_queue::payload.eq(replace(payload)))
+                .execute(self)?;
+        }
+        for (rowid, old_value, new_value) in audit_log::table
+            .select((audit_log::rowid, audit_log::old_value, audit_log::new_value))
+            .filter(
+                audit_log::old_value
+                    .like(&pattern)
+                    .escape('\\')
+                    .or(audit_log::new_value.like(&pattern).escape('\\')),
+            )
+            .load::<(i64, Option<String>, Option<String>)>(self)?
+        {
+            diesel::update(audit_log::table.filter(audit_log::rowid.eq(rowid)))
+                .set((
+                    audit_log::old_value.eq(old_value.map(&replace)),
+                    audit_log::new_value.eq(new_value.map(&replace)),
+                ))
+                .execute(self)?;
+        }
+        Ok(())
+    }
+}
+
+impl AuditLogRepo for Connection {
+    fn append_audit_log(&self, entry: &AuditLogEntry) -> Result<()> {
+        let target_ids = entry.target_ids.join(",");
+        let new_entry = models::NewAuditLogEntry {
+            at: entry.at.into_inner(),
+            actor: entry.actor.as_deref(),
+            action: entry.action.as_str(),
+            target_ids,
+            old_value: entry.before.as_deref(),
+            new_value: entry.after.as_deref(),
+        };
+        diesel::insert_into(schema::audit_log::table)
+            .values(&new_entry)
+            .execute(self)?;
+        Ok(())
+    }
+
+    fn query_audit_log(
+        &self,
+        query: &AuditLogQuery,
+        pagination: &Pagination,
+    ) -> Result<Vec<AuditLogEntry>> {
+        use schema::audit_log::dsl;
+        let mut q = dsl::audit_log
+            .order_by((dsl::at.desc(), dsl::rowid.desc()))
+            .into_boxed();
+        if let Some(ref actor) = query.actor {
+            q = q.filter(dsl::actor.eq(actor));
+        }
+        if let Some(action) = query.action {
+            q = q.filter(dsl::action.eq(action.as_str()));
+        }
+        if let Some(since) = query.since {
+            q = q.filter(dsl::at.ge(since.into_inner()));
+        }
+        if let Some(until) = query.until {
+            q = q.filter(dsl::at.lt(until.into_inner()));
+        }
+
+        // Pagination
+        let offset = pagination.offset.unwrap_or(0);
+        if offset > 0 {
+            q = q.offset(offset as i64);
+        }
+        if let Some(limit) = pagination.limit {
+            q = q.limit(limit as i64);
+        }
+
+        q.load::<models::AuditLogEntry>(self)?
+            .into_iter()
+            .map(|e| {
+                let models::AuditLogEntry {
+                    at,
+                    actor,
+                    action,
+                    target_ids,
+                    old_value,
+                    new_value,
+                    ..
+                } = e;
+                Ok(AuditLogEntry {
+                    at: TimestampMs::from_inner(at),
+                    actor,
+                    action: action
+                        .parse()
+                        .map_err(|err| RepoError::Other(anyhow!("{}", err)))?,
+                    target_ids: target_ids
+                        .split(',')
+                        .filter(|id| !id.is_empty())
+                        .map(ToString::to_string)
+                        .collect(),
+                    before: old_value,
+                    after: new_value,
+                })
+            })
+            .collect()
+    }
+}
@@ -43,6 +43,7 @@ pub struct NewPlaceRevision {
     pub founded_on: Option<NaiveDate>,
     pub image_url: Option<String>,
     pub image_link_url: Option<String>,
+    pub image_id: Option<String>,
 }
 
 #[derive(Queryable)]
@@ -69,6 +70,7 @@ pub struct JoinedPlaceRevision {
     pub founded_on: Option<NaiveDate>,
     pub image_url: Option<String>,
     pub image_link_url: Option<String>,
+    pub image_id: Option<String>,
     // Joined columns
     pub place_id: String,
     pub place_license: String,
@@ -97,6 +99,7 @@ pub struct JoinedPlaceRevisionWithStatusReview {
     pub founded_on: Option<NaiveDate>,
     pub image_url: Option<String>,
     pub image_link_url: Option<String>,
+    pub image_id: Option<String>,
     // Joined columns
     pub place_id: String,
     pub place_license: String,
@@ -217,6 +220,13 @@ pub struct PlaceRatingComment {
     pub rating_id: String,
 }
 
+#[derive(Insertable)]
+#[table_name = "place_redirect"]
+pub struct NewPlaceRedirect {
+    pub old_place_rowid: i64,
+    pub new_place_rowid: i64,
+}
+
 #[derive(Insertable, AsChangeset)]
 #[table_name = "events"]
 pub struct NewEvent {
@@ -241,6 +251,11 @@ pub struct NewEvent {
     pub archived: Option<i64>,
     pub image_url: Option<String>,
     pub image_link_url: Option<String>,
+    pub image_id: Option<String>,
+    pub updated_at: i64,
+    pub recurrence_rule: Option<String>,
+    pub recurrence_exdates: Option<String>,
+    pub series_uid: Option<String>,
 }
 
 #[derive(Queryable)]
@@ -267,6 +282,10 @@ pub struct EventEntity {
     pub archived: Option<i64>,
     pub image_url: Option<String>,
     pub image_link_url: Option<String>,
+    pub image_id: Option<String>,
+    pub recurrence_rule: Option<String>,
+    pub recurrence_exdates: Option<String>,
+    pub series_uid: Option<String>,
     // Joined columns
     pub created_by_email: Option<String>,
 }
@@ -276,7 +295,6 @@ pub struct EventEntity {
 pub struct NewOrganization {
     pub id: String,
     pub name: String,
-    pub api_token: String,
 }
 
 #[derive(Queryable)]
@@ -284,7 +302,6 @@ pub struct Organization {
     pub rowid: i64,
     pub id: String,
     pub name: String,
-    pub api_token: String,
 }
 
 #[derive(Queryable)]
@@ -300,6 +317,52 @@ pub struct NewEventTag<'a> {
     pub tag: &'a str,
 }
 
+#[derive(Insertable)]
+#[table_name = "event_revision"]
+pub struct NewEventRevision {
+    pub parent_rowid: i64,
+    pub rev: i64,
+    pub created_at: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub start: i64,
+    pub end: Option<i64>,
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+    pub street: Option<String>,
+    pub zip: Option<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+    pub state: Option<String>,
+    pub email: Option<String>,
+    pub telephone: Option<String>,
+    pub homepage: Option<String>,
+    pub created_by: Option<i64>,
+    pub registration: Option<i16>,
+    pub organizer: Option<String>,
+    pub image_url: Option<String>,
+    pub image_link_url: Option<String>,
+    pub image_id: Option<String>,
+    pub recurrence_rule: Option<String>,
+    pub recurrence_exdates: Option<String>,
+    pub series_uid: Option<String>,
+}
+
+#[derive(Insertable)]
+#[table_name = "event_revision_tag"]
+pub struct NewEventRevisionTag<'a> {
+    pub parent_rowid: i64,
+    pub tag: &'a str,
+}
+
+#[derive(Insertable)]
+#[table_name = "event_external_uid"]
+pub struct NewEventExternalUid<'a> {
+    pub org_rowid: i64,
+    pub external_uid: &'a str,
+    pub event_rowid: i64,
+}
+
 #[derive(Queryable)]
 pub struct OrganizationTag {
     pub org_rowid: i64,
@@ -341,6 +404,7 @@ pub struct NewUser<'a> {
     pub email_confirmed: bool,
     pub password: String,
     pub role: i16,
+    pub locale: Option<&'a str>,
 }
 
 #[derive(Queryable)]
@@ -350,6 +414,7 @@ pub struct UserEntity {
     pub email_confirmed: bool,
     pub password: String,
     pub role: i16,
+    pub locale: Option<String>,
 }
 
 #[derive(Insertable)]
@@ -361,6 +426,12 @@ pub struct NewBboxSubscription<'a> {
     pub south_west_lng: f64,
     pub north_east_lat: f64,
     pub north_east_lng: f64,
+    pub locale: Option<&'a str>,
+    pub tags: String,
+    pub categories: String,
+    pub events: bool,
+    pub delivery: i16,
+    pub last_digest_at: Option<i64>,
 }
 
 #[derive(Queryable)]
@@ -372,10 +443,32 @@ pub struct BboxSubscriptionEntity {
     pub south_west_lng: f64,
     pub north_east_lat: f64,
     pub north_east_lng: f64,
+    pub locale: Option<String>,
+    pub tags: String,
+    pub categories: String,
+    pub events: bool,
+    pub delivery: i16,
+    pub last_digest_at: Option<i64>,
     // Joined columns
     pub user_email: String,
 }
 
+#[derive(Insertable)]
+#[table_name = "bbox_subscription_change"]
+pub struct NewBboxSubscriptionChange<'a> {
+    pub subscription_rowid: i64,
+    pub kind: &'a str,
+    pub item_id: &'a str,
+    pub created_at: i64,
+}
+
+#[derive(Queryable)]
+pub struct BboxSubscriptionChange {
+    pub kind: String,
+    pub item_id: String,
+    pub created_at: i64,
+}
+
 #[derive(Insertable, AsChangeset)]
 #[table_name = "user_tokens"]
 pub struct NewUserToken {
@@ -409,3 +502,197 @@ pub struct PendingClearanceForPlace {
     pub created_at: i64,
     pub last_cleared_revision: Option<i64>,
 }
+
+#[derive(Insertable, AsChangeset)]
+#[table_name = "organization_event_clearance"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct NewPendingClearanceForEvent {
+    pub org_rowid: i64,
+    pub event_rowid: i64,
+    pub created_at: i64,
+    pub last_cleared_revision: Option<i64>,
+}
+
+#[derive(Queryable)]
+pub struct PendingClearanceForEvent {
+    pub event_id: String,
+    pub created_at: i64,
+    pub last_cleared_revision: Option<i64>,
+}
+
+#[derive(Insertable)]
+#[table_name = "job_queue"]
+pub struct NewQueuedJob<'a> {
+    pub id: &'a str,
+    pub created_at: i64,
+    pub run_at: i64,
+    pub attempts: i64,
+    pub payload: &'a str,
+}
+
+#[derive(Queryable)]
+pub struct QueuedJob {
+    pub rowid: i64,
+    pub id: String,
+    pub run_at: i64,
+    pub attempts: i64,
+    pub payload: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "webhook"]
+pub struct NewWebhook<'a> {
+    pub org_rowid: i64,
+    pub id: &'a str,
+    pub created_at: i64,
+    pub url: &'a str,
+    pub secret: &'a str,
+    pub event_types: String,
+    pub south_west_lat: Option<f64>,
+    pub south_west_lng: Option<f64>,
+    pub north_east_lat: Option<f64>,
+    pub north_east_lng: Option<f64>,
+    pub tags: String,
+}
+
+#[derive(Queryable)]
+pub struct Webhook {
+    pub rowid: i64,
+    pub org_rowid: i64,
+    pub id: String,
+    pub created_at: i64,
+    pub url: String,
+    pub secret: String,
+    pub event_types: String,
+    pub south_west_lat: Option<f64>,
+    pub south_west_lng: Option<f64>,
+    pub north_east_lat: Option<f64>,
+    pub north_east_lng: Option<f64>,
+    pub tags: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "organization_api_token"]
+pub struct NewOrganizationApiToken<'a> {
+    pub org_rowid: i64,
+    pub id: &'a str,
+    pub name: &'a str,
+    pub token_hash: &'a str,
+    pub scopes: String,
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Queryable)]
+pub struct OrganizationApiToken {
+    pub rowid: i64,
+    pub org_rowid: i64,
+    pub id: String,
+    pub name: String,
+    pub token_hash: String,
+    pub scopes: String,
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+    pub last_used_at: Option<i64>,
+    pub revoked_at: Option<i64>,
+}
+
+#[derive(Insertable)]
+#[table_name = "webhook_delivery"]
+pub struct NewWebhookDelivery<'a> {
+    pub webhook_rowid: i64,
+    pub id: &'a str,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub event_type: &'a str,
+    pub payload: &'a str,
+    pub status: i16,
+    pub attempts: i64,
+}
+
+#[derive(Queryable)]
+pub struct WebhookDelivery {
+    pub rowid: i64,
+    pub webhook_rowid: i64,
+    pub id: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub event_type: String,
+    pub payload: String,
+    pub status: i16,
+    pub attempts: i64,
+    pub response_status: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Insertable)]
+#[table_name = "geocoding_cache"]
+pub struct NewGeoCodingCacheEntry<'a> {
+    pub query: &'a str,
+    pub cached_at: i64,
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+    pub street: Option<&'a str>,
+    pub zip: Option<&'a str>,
+    pub city: Option<&'a str>,
+    pub country: Option<&'a str>,
+    pub state: Option<&'a str>,
+}
+
+#[derive(Queryable)]
+pub struct GeoCodingCacheEntry {
+    pub rowid: i64,
+    pub query: String,
+    pub cached_at: i64,
+    pub lat: Option<f64>,
+    pub lng: Option<f64>,
+    pub street: Option<String>,
+    pub zip: Option<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+    pub state: Option<String>,
+}
+
+#[derive(Insertable)]
+#[table_name = "image"]
+pub struct NewImage<'a> {
+    pub id: &'a str,
+    pub format: &'a str,
+    pub width: i64,
+    pub height: i64,
+    pub created_at: i64,
+    pub created_by: Option<&'a str>,
+}
+
+#[derive(Queryable)]
+pub struct Image {
+    pub rowid: i64,
+    pub id: String,
+    pub format: String,
+    pub width: i64,
+    pub height: i64,
+    pub created_at: i64,
+    pub created_by: Option<String>,
+}
+
+#[derive(Insertable)]
+#[table_name = "audit_log"]
+pub struct NewAuditLogEntry<'a> {
+    pub at: i64,
+    pub actor: Option<&'a str>,
+    pub action: &'a str,
+    pub target_ids: String,
+    pub old_value: Option<&'a str>,
+    pub new_value: Option<&'a str>,
+}
+
+#[derive(Queryable)]
+pub struct AuditLogEntry {
+    pub rowid: i64,
+    pub at: i64,
+    pub actor: Option<String>,
+    pub action: String,
+    pub target_ids: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
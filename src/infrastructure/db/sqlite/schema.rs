@@ -17,7 +17,6 @@ table! {
         rowid -> BigInt,
         id -> Text,
         name -> Text,
-        api_token -> Text,
     }
 }
 
@@ -47,6 +46,38 @@ table! {
 joinable!(organization_place_clearance -> organization (org_rowid));
 joinable!(organization_place_clearance -> place (place_rowid));
 
+table! {
+    organization_event_clearance (org_rowid, event_rowid) {
+        rowid -> BigInt,
+        org_rowid -> BigInt,
+        event_rowid -> BigInt,
+        created_at -> BigInt,
+        // last cleared revision or NULL if the event is new and has not been cleared yet
+        last_cleared_revision -> Nullable<BigInt>,
+    }
+}
+
+joinable!(organization_event_clearance -> organization (org_rowid));
+joinable!(organization_event_clearance -> events (event_rowid));
+
+table! {
+    organization_api_token (rowid) {
+        rowid -> BigInt,
+        org_rowid -> BigInt,
+        id -> Text,
+        name -> Text,
+        token_hash -> Text,
+        // comma-separated
+        scopes -> Text,
+        created_at -> BigInt,
+        expires_at -> Nullable<BigInt>,
+        last_used_at -> Nullable<BigInt>,
+        revoked_at -> Nullable<BigInt>,
+    }
+}
+
+joinable!(organization_api_token -> organization (org_rowid));
+
 ///////////////////////////////////////////////////////////////////////
 // Users
 ///////////////////////////////////////////////////////////////////////
@@ -58,6 +89,7 @@ table! {
         email_confirmed -> Bool,
         password -> Text,
         role -> SmallInt,
+        locale -> Nullable<Text>,
     }
 }
 
@@ -110,6 +142,7 @@ table! {
         founded_on -> Nullable<Date>,
         image_url -> Nullable<Text>,
         image_link_url -> Nullable<Text>,
+        image_id -> Nullable<Text>,
     }
 }
 
@@ -183,6 +216,13 @@ table! {
 
 joinable!(place_rating_comment -> place_rating (parent_rowid));
 
+table! {
+    place_redirect (old_place_rowid) {
+        old_place_rowid -> BigInt,
+        new_place_rowid -> BigInt,
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////
 // Events
 ///////////////////////////////////////////////////////////////////////
@@ -213,11 +253,60 @@ table! {
         archived -> Nullable<BigInt>,
         image_url -> Nullable<Text>,
         image_link_url -> Nullable<Text>,
+        updated_at -> BigInt,
+        recurrence_rule -> Nullable<Text>,
+        recurrence_exdates -> Nullable<Text>,
+        series_uid -> Nullable<Text>,
+        current_rev -> BigInt,
+        image_id -> Nullable<Text>,
     }
 }
 
 joinable!(events -> users (created_by));
 
+table! {
+    event_revision (rowid) {
+        rowid -> BigInt,
+        parent_rowid -> BigInt,
+        rev -> BigInt,
+        created_at -> BigInt,
+        title -> Text,
+        description -> Nullable<Text>,
+        start -> BigInt,
+        end -> Nullable<BigInt>,
+        lat -> Nullable<Double>,
+        lng -> Nullable<Double>,
+        street -> Nullable<Text>,
+        zip -> Nullable<Text>,
+        city -> Nullable<Text>,
+        country -> Nullable<Text>,
+        state -> Nullable<Text>,
+        email -> Nullable<Text>,
+        telephone -> Nullable<Text>,
+        homepage -> Nullable<Text>,
+        created_by -> Nullable<BigInt>,
+        registration -> Nullable<SmallInt>,
+        organizer -> Nullable<Text>,
+        image_url -> Nullable<Text>,
+        image_link_url -> Nullable<Text>,
+        recurrence_rule -> Nullable<Text>,
+        recurrence_exdates -> Nullable<Text>,
+        series_uid -> Nullable<Text>,
+        image_id -> Nullable<Text>,
+    }
+}
+
+joinable!(event_revision -> events (parent_rowid));
+
+table! {
+    event_revision_tag (parent_rowid, tag) {
+        parent_rowid -> BigInt,
+        tag -> Text,
+    }
+}
+
+joinable!(event_revision_tag -> event_revision (parent_rowid));
+
 table! {
     event_tags (event_id, tag) {
         event_id -> BigInt,
@@ -227,6 +316,17 @@ table! {
 
 joinable!(event_tags -> events (event_id));
 
+table! {
+    event_external_uid (org_rowid, external_uid) {
+        org_rowid -> BigInt,
+        external_uid -> Text,
+        event_rowid -> BigInt,
+    }
+}
+
+joinable!(event_external_uid -> organization (org_rowid));
+joinable!(event_external_uid -> events (event_rowid));
+
 ///////////////////////////////////////////////////////////////////////
 // Subscriptions
 ///////////////////////////////////////////////////////////////////////
@@ -243,20 +343,148 @@ table! {
         south_west_lng -> Double,
         north_east_lat -> Double,
         north_east_lng -> Double,
+        locale -> Nullable<Text>,
+        tags -> Text,
+        categories -> Text,
+        events -> Bool,
+        delivery -> SmallInt,
+        last_digest_at -> Nullable<BigInt>,
     }
 }
 
 joinable!(bbox_subscriptions -> users (user_id));
 
+table! {
+    bbox_subscription_change (rowid) {
+        rowid -> BigInt,
+        subscription_rowid -> BigInt,
+        kind -> Text,
+        item_id -> Text,
+        created_at -> BigInt,
+    }
+}
+
+joinable!(bbox_subscription_change -> bbox_subscriptions (subscription_rowid));
+
+///////////////////////////////////////////////////////////////////////
+// Jobs
+///////////////////////////////////////////////////////////////////////
+
+table! {
+    job_queue (rowid) {
+        rowid -> BigInt,
+        id -> Text,
+        created_at -> BigInt,
+        // due time stamp or lease expiration while running
+        run_at -> BigInt,
+        attempts -> BigInt,
+        payload -> Text,
+        last_error -> Nullable<Text>,
+    }
+}
+
+///////////////////////////////////////////////////////////////////////
+// Webhooks
+///////////////////////////////////////////////////////////////////////
+
+table! {
+    webhook (rowid) {
+        rowid -> BigInt,
+        org_rowid -> BigInt,
+        id -> Text,
+        created_at -> BigInt,
+        url -> Text,
+        secret -> Text,
+        // comma-separated
+        event_types -> Text,
+        south_west_lat -> Nullable<Double>,
+        south_west_lng -> Nullable<Double>,
+        north_east_lat -> Nullable<Double>,
+        north_east_lng -> Nullable<Double>,
+        // comma-separated
+        tags -> Text,
+    }
+}
+
+joinable!(webhook -> organization (org_rowid));
+
+table! {
+    webhook_delivery (rowid) {
+        rowid -> BigInt,
+        webhook_rowid -> BigInt,
+        id -> Text,
+        created_at -> BigInt,
+        updated_at -> BigInt,
+        event_type -> Text,
+        payload -> Text,
+        // 0 = pending, 1 = delivered, 2 = failed
+        status -> SmallInt,
+        attempts -> BigInt,
+        response_status -> Nullable<BigInt>,
+        last_error -> Nullable<Text>,
+    }
+}
+
+joinable!(webhook_delivery -> webhook (webhook_rowid));
+
+table! {
+    geocoding_cache (rowid) {
+        rowid -> BigInt,
+        query -> Text,
+        cached_at -> BigInt,
+        lat -> Nullable<Double>,
+        lng -> Nullable<Double>,
+        street -> Nullable<Text>,
+        zip -> Nullable<Text>,
+        city -> Nullable<Text>,
+        country -> Nullable<Text>,
+        state -> Nullable<Text>,
+    }
+}
+
+table! {
+    audit_log (rowid) {
+        rowid -> BigInt,
+        at -> BigInt,
+        actor -> Nullable<Text>,
+        action -> Text,
+        // comma-separated
+        target_ids -> Text,
+        old_value -> Nullable<Text>,
+        new_value -> Nullable<Text>,
+    }
+}
+
+table! {
+    image (rowid) {
+        rowid -> BigInt,
+        id -> Text,
+        format -> Text,
+        width -> BigInt,
+        height -> BigInt,
+        created_at -> BigInt,
+        created_by -> Nullable<Text>,
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////
 
 allow_tables_to_appear_in_same_query!(
+    audit_log,
     bbox_subscriptions,
+    bbox_subscription_change,
     events,
+    event_external_uid,
+    event_revision,
+    event_revision_tag,
     event_tags,
+    geocoding_cache,
+    image,
+    job_queue,
     place,
     place_rating,
     place_rating_comment,
+    place_redirect,
     place_revision,
     place_revision_review,
     place_revision_tag,
@@ -264,7 +492,11 @@ allow_tables_to_appear_in_same_query!(
     organization,
     organization_tag,
     organization_place_clearance,
+    organization_event_clearance,
+    organization_api_token,
     tags,
     users,
     user_tokens,
+    webhook,
+    webhook_delivery,
 );
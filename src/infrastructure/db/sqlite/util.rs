@@ -21,6 +21,17 @@ pub(crate) fn load_url(url: String) -> Option<e::Url> {
     }
 }
 
+fn load_locale(locale: String) -> Option<e::Locale> {
+    match locale.parse() {
+        Ok(locale) => Some(locale),
+        Err(err) => {
+            // The database should only contain valid locales
+            log::error!("Failed to load locale from database: {}", err);
+            None
+        }
+    }
+}
+
 pub(crate) fn registration_type_from_i16(i: i16) -> e::RegistrationType {
     use crate::core::entities::RegistrationType::*;
     match i {
@@ -73,6 +84,45 @@ mod tests {
     }
 }
 
+pub(crate) fn load_recurrence(
+    rule: Option<String>,
+    exdates: Option<String>,
+) -> Option<e::Recurrence> {
+    let rule = rule?;
+    let rule = match rule.parse() {
+        Ok(rule) => rule,
+        Err(err) => {
+            // The database should only contain valid rules
+            log::error!(
+                "Failed to load recurrence rule '{}' from database: {}",
+                rule,
+                err
+            );
+            return None;
+        }
+    };
+    let exdates = exdates
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .filter(|s| !s.is_empty())
+        .filter_map(|s| s.parse().ok())
+        .map(|ts| NaiveDateTime::from_timestamp(ts, 0))
+        .collect();
+    Some(e::Recurrence { rule, exdates })
+}
+
+pub(crate) fn recurrence_into_columns(
+    recurrence: Option<e::Recurrence>,
+) -> (Option<String>, Option<String>) {
+    if let Some(e::Recurrence { rule, exdates }) = recurrence {
+        let exdates: Vec<_> = exdates.iter().map(|x| x.timestamp().to_string()).collect();
+        (Some(rule.to_string()), Some(exdates.join(",")))
+    } else {
+        (None, None)
+    }
+}
+
 pub(crate) fn event_from_event_entity_and_tags(e: EventEntity, tag_rels: &[EventTag]) -> e::Event {
     let EventEntity {
         id,
@@ -96,6 +146,10 @@ pub(crate) fn event_from_event_entity_and_tags(e: EventEntity, tag_rels: &[Event
         archived,
         image_url,
         image_link_url,
+        image_id,
+        recurrence_rule,
+        recurrence_exdates,
+        series_uid,
         created_by_email,
         ..
     } = e;
@@ -163,6 +217,9 @@ pub(crate) fn event_from_event_entity_and_tags(e: EventEntity, tag_rels: &[Event
         archived: archived.map(Timestamp::from_inner),
         image_url: image_url.and_then(load_url),
         image_link_url: image_link_url.and_then(load_url),
+        image_id: image_id.map(Into::into),
+        recurrence: load_recurrence(recurrence_rule, recurrence_exdates),
+        series_id: series_uid.map(Into::into),
     }
 }
 
@@ -189,6 +246,7 @@ impl<'a> From<&'a e::User> for NewUser<'a> {
                 warn!("Could not convert role {:?} to i16. Use 0 instead.", u.role);
                 0
             }),
+            locale: u.locale.as_ref().map(e::Locale::as_str),
         }
     }
 }
@@ -201,6 +259,7 @@ impl From<UserEntity> for e::User {
             email_confirmed,
             password,
             role,
+            locale,
             ..
         } = u;
         Self {
@@ -215,6 +274,7 @@ impl From<UserEntity> for e::User {
                 );
                 e::Role::default()
             }),
+            locale: locale.and_then(load_locale),
         }
     }
 }
@@ -274,6 +334,12 @@ impl From<BboxSubscriptionEntity> for e::BboxSubscription {
             south_west_lng,
             north_east_lat,
             north_east_lng,
+            locale,
+            tags,
+            categories,
+            events,
+            delivery,
+            last_digest_at,
             ..
         } = from;
         let south_west =
@@ -285,10 +351,49 @@ impl From<BboxSubscriptionEntity> for e::BboxSubscription {
             id: uid.into(),
             user_email,
             bbox,
+            locale: locale.and_then(load_locale),
+            tags: tags
+                .split(',')
+                .filter(|s| !s.is_empty())
+                .map(ToString::to_string)
+                .collect(),
+            categories: categories
+                .split(',')
+                .filter(|s| !s.is_empty())
+                .map(Into::into)
+                .collect(),
+            events,
+            delivery: subscription_delivery_from_i16(delivery),
+            last_digest_at: last_digest_at.map(e::TimestampMs::from_inner),
         }
     }
 }
 
+pub(crate) fn subscription_delivery_from_i16(i: i16) -> e::SubscriptionDelivery {
+    use crate::core::entities::SubscriptionDelivery::*;
+    match i {
+        0 => Immediate,
+        1 => Daily,
+        2 => Weekly,
+        _ => {
+            error!(
+                "Invalid subscription delivery {}: Use 'Immediate' instead",
+                i
+            );
+            Immediate
+        }
+    }
+}
+
+pub(crate) fn subscription_delivery_into_i16(x: e::SubscriptionDelivery) -> i16 {
+    use crate::core::entities::SubscriptionDelivery::*;
+    match x {
+        Immediate => 0,
+        Daily => 1,
+        Weekly => 2,
+    }
+}
+
 impl From<UserTokenEntity> for e::UserToken {
     fn from(from: UserTokenEntity) -> Self {
         Self {
@@ -332,13 +437,11 @@ impl From<e::Organization> for NewOrganization {
         let e::Organization {
             id,
             name,
-            api_token,
             moderated_tags: _,
         } = o;
         NewOrganization {
             id: id.into(),
             name,
-            api_token,
         }
     }
 }
@@ -430,6 +533,22 @@ impl From<PendingClearanceForPlace> for e::PendingClearanceForPlace {
     }
 }
 
+impl From<PendingClearanceForEvent> for e::PendingClearanceForEvent {
+    fn from(from: PendingClearanceForEvent) -> Self {
+        let PendingClearanceForEvent {
+            event_id,
+            created_at,
+            last_cleared_revision,
+        } = from;
+        let last_cleared_revision = last_cleared_revision.map(|rev| e::Revision::from(rev as u64));
+        Self {
+            event_id: event_id.into(),
+            created_at: e::TimestampMs::from_inner(created_at),
+            last_cleared_revision,
+        }
+    }
+}
+
 #[test]
 fn test_tag_diff() {
     let x = tags_diff(&[], &["b".into()]);
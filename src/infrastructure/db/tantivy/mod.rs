@@ -9,34 +9,72 @@ use crate::core::{
     },
     util::{
         geo::{LatCoord, LngCoord, MapPoint},
-        time::Timestamp,
+        time::{Timestamp, TimestampMs},
     },
 };
 
 use anyhow::{bail, Result as Fallible};
 use failure::Fail;
 use num_traits::ToPrimitive;
+use ofdb_core::{
+    cluster::{ClusterGrid, PlaceCluster, PlaceClusterer},
+    opening_hours::WeeklySchedule,
+    recurrence::latest_occurrence_start,
+};
 use std::{
+    fs,
     ops::Bound,
-    path::Path,
-    sync::{Arc, Mutex},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
 };
 use strum::IntoEnumIterator;
 use tantivy::{
-    collector::TopDocs,
-    query::{BooleanQuery, Occur, Query, QueryParser, RangeQuery, TermQuery},
+    collector::{Count, TopDocs},
+    directory::{Directory, MmapDirectory, INDEX_WRITER_LOCK},
+    query::{BooleanQuery, FuzzyTermQuery, Occur, Query, QueryParser, RangeQuery, TermQuery},
     schema::*,
-    tokenizer::{LowerCaser, RawTokenizer, RemoveLongFilter, SimpleTokenizer, TextAnalyzer},
+    tokenizer::{
+        AsciiFoldingFilter, Language, LowerCaser, RawTokenizer, RemoveLongFilter, SimpleTokenizer,
+        Stemmer, TextAnalyzer,
+    },
     DocAddress, DocId, Document, Index, IndexReader, IndexWriter, ReloadPolicy, Score,
     SegmentReader,
 };
 
 const OVERALL_INDEX_HEAP_SIZE_IN_BYTES: usize = 50_000_000;
 
+// Increment this version whenever the schema, the tokenizers, or
+// the contents of the indexed documents are modified! Persistent
+// indexes with a different version are discarded and rebuilt
+// from scratch.
+const INDEX_SCHEMA_VERSION: u32 = 5;
+
+// Stored as the payload of every commit
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexCommitPayload {
+    schema_version: u32,
+    // All database changes before this time stamp (in milliseconds)
+    // have been indexed. Undefined while the index is (re-)built.
+    high_water_mark: Option<i64>,
+}
+
 const PLACE_KIND_FLAG: i64 = 1;
 const EVENT_KIND_FLAG: i64 = 2;
 const ALL_KINDS_MASK: i64 = PLACE_KIND_FLAG | EVENT_KIND_FLAG;
 
+// The weekly opening hours of places are indexed as the slots
+// of this length during which they are open
+const OPENING_HOURS_SLOT_MINUTES: u32 = 15;
+
+// Places that are always open are indexed with this single
+// slot instead of all slots of the week
+const ALWAYS_OPEN_SLOT: i64 = -1;
+
+// If the exact matching of the query text finds less results
+// than this the terms are also matched with a small edit distance
+// to tolerate typos
+const MIN_EXACT_TEXT_HITS: usize = 3;
+
 fn get_category_kind_flag(category: &Category) -> i64 {
     if category.id.as_str() == Category::ID_EVENT {
         EVENT_KIND_FLAG
@@ -52,10 +90,14 @@ struct IndexedFields {
     status: Field,
     lat: Field,
     lng: Field,
-    ts_min: Field, // minimum time stamp with second precision, e.g. event start
-    ts_max: Field, // maximum time stamp with second precision, e.g. event end
+    ts_min: Field,      // minimum time stamp with second precision, e.g. event start
+    ts_max: Field,      // maximum time stamp with second precision, e.g. event end
+    ts_min_last: Field, // upper bound of ts_min for all occurrences of recurring events
+    open_slot: Field,
     title: Field,
     description: Field,
+    title_en: Field,
+    description_en: Field,
     address_street: Field,
     address_city: Field,
     address_zip: Field,
@@ -94,6 +136,13 @@ impl IndexedFields {
                 .set_tokenizer(TEXT_TOKENIZER)
                 .set_index_option(IndexRecordOption::WithFreqsAndPositions),
         );
+        // Titles and descriptions are additionally indexed with
+        // English stemming
+        let indexed_text_en_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer(TEXT_EN_TOKENIZER)
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        );
         // Text fields that are returned as part of the search result
         // additionally need to be stored explicitly
         let stored_text_options = indexed_text_options.clone().set_stored();
@@ -106,8 +155,12 @@ impl IndexedFields {
             lng: schema_builder.add_f64_field("lon", INDEXED | STORED),
             ts_min: schema_builder.add_i64_field("ts_min", INDEXED | STORED),
             ts_max: schema_builder.add_i64_field("ts_max", INDEXED | STORED),
+            ts_min_last: schema_builder.add_i64_field("ts_min_last", INDEXED),
+            open_slot: schema_builder.add_i64_field("open_slot", INDEXED),
             title: schema_builder.add_text_field("tit", stored_text_options.clone()),
             description: schema_builder.add_text_field("dsc", stored_text_options),
+            title_en: schema_builder.add_text_field("tit_en", indexed_text_en_options.clone()),
+            description_en: schema_builder.add_text_field("dsc_en", indexed_text_en_options),
             contact_name: schema_builder.add_text_field("cnt_name", indexed_text_options.clone()),
             address_street: schema_builder
                 .add_text_field("adr_street", indexed_text_options.clone()),
@@ -228,28 +281,55 @@ pub(crate) struct TantivyIndex {
     index_reader: IndexReader,
     index_writer: IndexWriter,
     text_query_parser: QueryParser,
+    // All fields of the query parser with their analyzer
+    text_fields: Vec<(Field, TextAnalyzer)>,
+    high_water_mark: Option<TimestampMs>,
 }
 
 const ID_TOKENIZER: &str = "raw";
 const TAG_TOKENIZER: &str = "tag";
-const TEXT_TOKENIZER: &str = "default";
+const TEXT_TOKENIZER: &str = "text";
+const TEXT_EN_TOKENIZER: &str = "text_en";
 
 const MAX_TOKEN_LEN: usize = 40;
 
 fn register_tokenizers(index: &Index) {
     // Predefined tokenizers
     debug_assert!(index.tokenizers().get(ID_TOKENIZER).is_some());
-    debug_assert!(index.tokenizers().get(TEXT_TOKENIZER).is_some());
     // Custom tokenizer(s)
     debug_assert!(index.tokenizers().get(TAG_TOKENIZER).is_none());
+    debug_assert!(index.tokenizers().get(TEXT_TOKENIZER).is_none());
+    debug_assert!(index.tokenizers().get(TEXT_EN_TOKENIZER).is_none());
     let tag_tokenizer = TextAnalyzer::from(RawTokenizer)
         .filter(LowerCaser)
         .filter(RemoveLongFilter::limit(MAX_TOKEN_LEN));
     index.tokenizers().register(TAG_TOKENIZER, tag_tokenizer);
+    // Most entries are written in German. Diacritics are removed
+    // to find "Bäckerei" when searching for "Backerei" and vice versa.
     let text_tokenizer = TextAnalyzer::from(SimpleTokenizer)
         .filter(LowerCaser)
-        .filter(RemoveLongFilter::limit(MAX_TOKEN_LEN));
+        .filter(RemoveLongFilter::limit(MAX_TOKEN_LEN))
+        .filter(AsciiFoldingFilter)
+        .filter(Stemmer::new(Language::German));
     index.tokenizers().register(TEXT_TOKENIZER, text_tokenizer);
+    let text_en_tokenizer = TextAnalyzer::from(SimpleTokenizer)
+        .filter(LowerCaser)
+        .filter(RemoveLongFilter::limit(MAX_TOKEN_LEN))
+        .filter(AsciiFoldingFilter)
+        .filter(Stemmer::new(Language::English));
+    index
+        .tokenizers()
+        .register(TEXT_EN_TOKENIZER, text_en_tokenizer);
+}
+
+// Short terms are only matched exactly, because almost
+// any other short term would be within the edit distance.
+fn fuzzy_distance(term: &str) -> u8 {
+    match term.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
 }
 
 fn f64_to_u64(val: f64, min: f64, max: f64) -> u64 {
@@ -295,6 +375,13 @@ fn u64_to_avg_rating(val: u64) -> AvgRatingValue {
     .into()
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum TextMatching {
+    Exact,
+    // Exact matches are still ranked higher
+    Fuzzy,
+}
+
 #[derive(Copy, Clone, Debug)]
 enum TopDocsMode {
     Score,
@@ -302,26 +389,192 @@ enum TopDocsMode {
     ScoreBoostedByRating,
 }
 
+// Open an existing index that is compatible with the current
+// schema and that has been built completely.
+fn open_complete_index_in_dir(path: &Path) -> Fallible<Option<(Index, TimestampMs)>> {
+    if !path.join("meta.json").is_file() {
+        return Ok(None);
+    }
+    let index = Index::open_in_dir(path).map_err(Fail::compat)?;
+    let payload = index.load_metas().map_err(Fail::compat)?.payload;
+    let payload = match payload {
+        Some(payload) => serde_json::from_str::<IndexCommitPayload>(&payload)?,
+        None => {
+            warn!("Missing payload in full-text search index");
+            return Ok(None);
+        }
+    };
+    if payload.schema_version != INDEX_SCHEMA_VERSION {
+        info!(
+            "Schema version of full-text search index has changed: {} -> {}",
+            payload.schema_version, INDEX_SCHEMA_VERSION
+        );
+        return Ok(None);
+    }
+    if let Some(high_water_mark) = payload.high_water_mark {
+        Ok(Some((index, TimestampMs::from_inner(high_water_mark))))
+    } else {
+        warn!("Full-text search index has not been built completely");
+        Ok(None)
+    }
+}
+
+const INDEX_SUBDIR_PREFIX: &str = "tantivy-v";
+
+// The index is kept in a dedicated subdirectory of the configured
+// directory that is owned by Tantivy. Other files in the configured
+// directory, e.g. an SQLite database, are never touched.
+fn index_subdir(path: &Path, schema_version: u32) -> PathBuf {
+    path.join(format!("{}{}", INDEX_SUBDIR_PREFIX, schema_version))
+}
+
+// Removes an index subdirectory unless the index is still
+// opened for writing by another process.
+fn remove_index_subdir(path: &Path) -> Fallible<()> {
+    if !path.is_dir() {
+        return Ok(());
+    }
+    let directory = match MmapDirectory::open(path) {
+        Ok(directory) => directory,
+        Err(err) => bail!("Failed to open directory {}: {:?}", path.display(), err),
+    };
+    if let Err(err) = directory.acquire_lock(&INDEX_WRITER_LOCK) {
+        bail!(
+            "Full-text search index in {} is in use: {:?}",
+            path.display(),
+            err
+        );
+    }
+    fs::remove_dir_all(path)?;
+    Ok(())
+}
+
+// Indexes with an outdated schema are never reopened
+fn remove_outdated_index_subdirs(path: &Path) -> Fallible<()> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let is_outdated = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_prefix(INDEX_SUBDIR_PREFIX))
+            .and_then(|version| version.parse::<u32>().ok())
+            .map(|version| version != INDEX_SCHEMA_VERSION)
+            .unwrap_or(false);
+        if is_outdated && entry.file_type()?.is_dir() {
+            info!(
+                "Removing outdated full-text search index: {}",
+                entry.path().display()
+            );
+            if let Err(err) = remove_index_subdir(&entry.path()) {
+                warn!("Failed to remove outdated full-text search index: {}", err);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Indexes have been stored directly in the configured directory
+// before the dedicated subdirectories were introduced. Only the
+// well-known files of Tantivy are removed from there, i.e. the
+// meta files, the lock files, and the segment files that are named
+// by a UUID.
+fn is_legacy_index_file(file_name: &str) -> bool {
+    match file_name {
+        "meta.json" | ".managed.json" | ".tantivy-meta.lock" | ".tantivy-writer.lock" => true,
+        _ => file_name
+            .split('.')
+            .next()
+            .map(|uuid| uuid.len() == 32 && uuid.chars().all(|c| c.is_ascii_hexdigit()))
+            .unwrap_or(false),
+    }
+}
+
+fn remove_legacy_index_files(path: &Path) -> Fallible<()> {
+    if !path.join("meta.json").is_file() {
+        return Ok(());
+    }
+    info!(
+        "Removing legacy full-text search index from directory {}",
+        path.display()
+    );
+    let directory = match MmapDirectory::open(path) {
+        Ok(directory) => directory,
+        Err(err) => bail!("Failed to open directory {}: {:?}", path.display(), err),
+    };
+    let lock = match directory.acquire_lock(&INDEX_WRITER_LOCK) {
+        Ok(lock) => lock,
+        Err(err) => bail!(
+            "Legacy full-text search index in {} is in use: {:?}",
+            path.display(),
+            err
+        ),
+    };
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let is_legacy = entry
+            .file_name()
+            .to_str()
+            .map(is_legacy_index_file)
+            .unwrap_or(false);
+        if is_legacy && entry.file_type()?.is_file() {
+            debug!("Removing {}", entry.path().display());
+            fs::remove_file(entry.path())?;
+        }
+    }
+    drop(lock);
+    Ok(())
+}
+
 impl TantivyIndex {
-    #[allow(dead_code)]
+    #[cfg(test)]
     pub fn create_in_ram() -> Fallible<Self> {
         let no_path: Option<&Path> = None;
-        Self::create(no_path)
+        Self::open_or_create(no_path, false)
     }
 
-    pub fn create<P: AsRef<Path>>(path: Option<P>) -> Fallible<Self> {
+    // Reopens an existing index in the given directory unless
+    // `recreate` is requested or the existing index is incompatible
+    // or incomplete. An index in RAM is always created from scratch.
+    pub fn open_or_create<P: AsRef<Path>>(path: Option<P>, recreate: bool) -> Fallible<Self> {
         let (fields, schema) = IndexedFields::build_schema();
 
-        // TODO: Open index from existing directory
-        let index = if let Some(path) = path {
-            info!(
-                "Creating full-text search index in directory: {}",
-                path.as_ref().to_string_lossy()
-            );
-            Index::create_in_dir(path, schema).map_err(Fail::compat)?
+        let (index, high_water_mark) = if let Some(path) = path {
+            fs::create_dir_all(path.as_ref())?;
+            remove_outdated_index_subdirs(path.as_ref())?;
+            if let Err(err) = remove_legacy_index_files(path.as_ref()) {
+                warn!("Failed to remove legacy full-text search index: {}", err);
+            }
+            let path = &index_subdir(path.as_ref(), INDEX_SCHEMA_VERSION);
+            let existing_index = if recreate {
+                None
+            } else {
+                open_complete_index_in_dir(path).unwrap_or_else(|err| {
+                    warn!("Failed to open full-text search index: {}", err);
+                    None
+                })
+            };
+            if let Some((index, high_water_mark)) = existing_index {
+                info!(
+                    "Opened full-text search index in directory {} (high-water mark = {})",
+                    path.to_string_lossy(),
+                    high_water_mark
+                );
+                (index, Some(high_water_mark))
+            } else {
+                info!(
+                    "Creating full-text search index in directory: {}",
+                    path.to_string_lossy()
+                );
+                remove_index_subdir(path)?;
+                fs::create_dir_all(path)?;
+                (
+                    Index::create_in_dir(path, schema).map_err(Fail::compat)?,
+                    None,
+                )
+            }
         } else {
             warn!("Creating full-text search index in RAM");
-            Index::create_in_ram(schema)
+            (Index::create_in_ram(schema), None)
         };
 
         register_tokenizers(&index);
@@ -338,31 +591,143 @@ impl TantivyIndex {
         let index_writer = index
             .writer(OVERALL_INDEX_HEAP_SIZE_IN_BYTES)
             .map_err(Fail::compat)?;
-        let text_query_parser = QueryParser::for_index(
-            &index,
-            vec![
-                fields.title,
-                fields.description,
-                fields.address_street,
-                fields.address_city,
-                fields.address_zip,
-                fields.address_country,
-                fields.address_state,
-                fields.contact_name,
-            ],
-        );
+        let text_query_fields = vec![
+            fields.title,
+            fields.description,
+            fields.title_en,
+            fields.description_en,
+            fields.address_street,
+            fields.address_city,
+            fields.address_zip,
+            fields.address_country,
+            fields.address_state,
+            fields.contact_name,
+        ];
+        let text_fields = text_query_fields
+            .iter()
+            .map(|field| {
+                index
+                    .tokenizer_for_field(*field)
+                    .map(|analyzer| (*field, analyzer))
+                    .map_err(Fail::compat)
+            })
+            .collect::<Result<_, _>>()?;
+        let text_query_parser = QueryParser::for_index(&index, text_query_fields);
         Ok(Self {
             fields,
             index_reader,
             index_writer,
             text_query_parser,
+            text_fields,
+            high_water_mark,
         })
     }
 
+    // All database changes before this time stamp have been indexed.
+    // Undefined if the index has not been built completely yet.
+    pub fn high_water_mark(&self) -> Option<TimestampMs> {
+        self.high_water_mark
+    }
+
+    pub fn flush_index_with_high_water_mark(
+        &mut self,
+        high_water_mark: TimestampMs,
+    ) -> Fallible<()> {
+        self.high_water_mark = Some(high_water_mark);
+        self.flush_index()
+    }
+
+    // Matches all terms of the query text with a small edit distance.
+    // Only plain terms that are optionally prefixed with '+' or '-'
+    // are supported. Excluded terms are still matched exactly.
+    fn build_fuzzy_text_query(&self, text: &str) -> Option<BooleanQuery> {
+        if text.contains(|c| c == '"' || c == ':') {
+            return None;
+        }
+        let mut word_queries: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for word in text.split_whitespace() {
+            let (occur, word) = if let Some(word) = word.strip_prefix('+') {
+                (Occur::Must, word)
+            } else if let Some(word) = word.strip_prefix('-') {
+                (Occur::MustNot, word)
+            } else {
+                (Occur::Should, word)
+            };
+            // A word may consist of multiple tokens, e.g. "foo-bar",
+            // that are all required like in a phrase
+            let mut token_queries: Vec<Vec<(Occur, Box<dyn Query>)>> = Vec::new();
+            for (field, analyzer) in &self.text_fields {
+                analyzer.token_stream(word).process(&mut |token| {
+                    let term = Term::from_field_text(*field, &token.text);
+                    let distance = if occur == Occur::MustNot {
+                        0
+                    } else {
+                        fuzzy_distance(&token.text)
+                    };
+                    let term_query: Box<dyn Query> = if distance > 0 {
+                        Box::new(FuzzyTermQuery::new(term, distance, true))
+                    } else {
+                        Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs))
+                    };
+                    if token_queries.len() <= token.position {
+                        token_queries.resize_with(token.position + 1, Vec::new);
+                    }
+                    token_queries[token.position].push((Occur::Should, term_query));
+                });
+            }
+            let token_queries: Vec<(Occur, Box<dyn Query>)> = token_queries
+                .into_iter()
+                .filter(|field_queries| !field_queries.is_empty())
+                .map(|field_queries| {
+                    let query: Box<dyn Query> = Box::new(BooleanQuery::from(field_queries));
+                    (Occur::Must, query)
+                })
+                .collect();
+            if !token_queries.is_empty() {
+                let word_query: Box<dyn Query> = Box::new(BooleanQuery::from(token_queries));
+                word_queries.push((occur, word_query));
+            }
+        }
+        if word_queries.is_empty() {
+            None
+        } else {
+            Some(word_queries.into())
+        }
+    }
+
+    fn build_text_query(&self, text: &str, text_matching: TextMatching) -> Option<Box<dyn Query>> {
+        let exact_query = match self.text_query_parser.parse_query(text) {
+            Ok(text_query) => Some(text_query),
+            Err(err) => {
+                warn!("Failed to parse query text '{}': {:?}", text, err);
+                None
+            }
+        };
+        if text_matching == TextMatching::Exact {
+            return exact_query;
+        }
+        match (exact_query, self.build_fuzzy_text_query(text)) {
+            (Some(exact_query), Some(fuzzy_query)) => {
+                let fuzzy_query: Box<dyn Query> = Box::new(fuzzy_query);
+                let text_query: Box<dyn Query> = Box::new(BooleanQuery::from(vec![
+                    (Occur::Should, exact_query),
+                    (Occur::Should, fuzzy_query),
+                ]));
+                Some(text_query)
+            }
+            (exact_query, None) => exact_query,
+            (None, Some(fuzzy_query)) => {
+                let text_query: Box<dyn Query> = Box::new(fuzzy_query);
+                Some(text_query)
+            }
+        }
+    }
+
     fn build_query(
         &self,
         query_mode: IndexQueryMode,
         query: &IndexQuery,
+        text_matching: TextMatching,
     ) -> (BooleanQuery, TopDocsMode) {
         let mut sub_queries: Vec<(Occur, Box<dyn Query>)> = Vec::with_capacity(1 + 2 + 1 + 1 + 1);
 
@@ -604,16 +969,11 @@ impl TantivyIndex {
             debug!("Query text: {}", text);
             debug_assert!(!text.trim().is_empty());
             let text = text.to_lowercase();
-            match self.text_query_parser.parse_query(&text) {
-                Ok(text_query) => {
-                    if query.hash_tags.is_empty() && query.text_tags.is_empty() {
-                        sub_queries.push((Occur::Must, Box::new(text_query)));
-                    } else {
-                        text_and_tags_queries.push((Occur::Should, Box::new(text_query)));
-                    }
-                }
-                Err(err) => {
-                    warn!("Failed to parse query text '{}': {:?}", text, err);
+            if let Some(text_query) = self.build_text_query(&text, text_matching) {
+                if query.hash_tags.is_empty() && query.text_tags.is_empty() {
+                    sub_queries.push((Occur::Must, text_query));
+                } else {
+                    text_and_tags_queries.push((Occur::Should, text_query));
                 }
             }
         }
@@ -636,10 +996,35 @@ impl TantivyIndex {
             .ts_min_ub
             .map(|x| Bound::Included(x.into_inner()))
             .unwrap_or(Bound::Unbounded);
-        if (ts_min_lb, ts_min_ub) != (Bound::Unbounded, Bound::Unbounded) {
-            let ts_min_query = RangeQuery::new_i64_bounds(self.fields.ts_min, ts_min_lb, ts_min_ub);
+        if ts_min_ub != Bound::Unbounded {
+            let ts_min_query =
+                RangeQuery::new_i64_bounds(self.fields.ts_min, Bound::Unbounded, ts_min_ub);
             sub_queries.push((Occur::Must, Box::new(ts_min_query)));
         }
+        if ts_min_lb != Bound::Unbounded {
+            // Recurring events are indexed with the bounds of their series
+            // and match if any of their occurrences might start within the
+            // requested range. The actual occurrences are filtered later.
+            let ts_min_lb_queries: Vec<(Occur, Box<dyn Query>)> = vec![
+                (
+                    Occur::Should,
+                    Box::new(RangeQuery::new_i64_bounds(
+                        self.fields.ts_min,
+                        ts_min_lb,
+                        Bound::Unbounded,
+                    )),
+                ),
+                (
+                    Occur::Should,
+                    Box::new(RangeQuery::new_i64_bounds(
+                        self.fields.ts_min_last,
+                        ts_min_lb,
+                        Bound::Unbounded,
+                    )),
+                ),
+            ];
+            sub_queries.push((Occur::Must, Box::new(BooleanQuery::from(ts_min_lb_queries))));
+        }
 
         // ts_max
         let ts_max_lb = query
@@ -655,6 +1040,22 @@ impl TantivyIndex {
             sub_queries.push((Occur::Must, Box::new(ts_max_query)));
         }
 
+        // open_slot
+        if let Some(open_at) = query.open_at {
+            let slot = i64::from(open_at.slot(OPENING_HOURS_SLOT_MINUTES));
+            debug!("Query open slot: {}", slot);
+            let open_slot_queries: Vec<(Occur, Box<dyn Query>)> = [slot, ALWAYS_OPEN_SLOT]
+                .iter()
+                .map(|slot| {
+                    let slot_term = Term::from_field_i64(self.fields.open_slot, *slot);
+                    let slot_query: Box<dyn Query> =
+                        Box::new(TermQuery::new(slot_term, IndexRecordOption::Basic));
+                    (Occur::Should, slot_query)
+                })
+                .collect();
+            sub_queries.push((Occur::Must, Box::new(BooleanQuery::from(open_slot_queries))));
+        }
+
         // Boosting the score by the rating does only make sense if the
         // query actually contains search terms or tags. Otherwise the
         // results are sorted only by their rating, e.g. if the query
@@ -693,8 +1094,21 @@ impl TantivyIndex {
             bail!("Invalid limit: {}", limit);
         }
 
-        let (search_query, top_docs_mode) = self.build_query(query_mode, query);
+        let (mut search_query, top_docs_mode) =
+            self.build_query(query_mode, query, TextMatching::Exact);
         let searcher = self.index_reader.searcher();
+        if query.text.is_some() {
+            let exact_hits = searcher
+                .search(&search_query, &Count)
+                .map_err(Fail::compat)?;
+            if exact_hits < limit.min(MIN_EXACT_TEXT_HITS) {
+                debug!(
+                    "Too few exact text matches ({}), retrying fuzzy",
+                    exact_hits
+                );
+                search_query = self.build_query(query_mode, query, TextMatching::Fuzzy).0;
+            }
+        }
         // TODO: Try to combine redundant code from different search strategies
         match top_docs_mode {
             TopDocsMode::Score => {
@@ -853,6 +1267,48 @@ impl<'a> DocumentCollector for IndexedPlaceCollector<'a> {
     }
 }
 
+// Aggregates places into clusters without loading
+// all the fields of each document.
+struct PlaceClusterCollector<'a> {
+    fields: &'a IndexedFields,
+    clusterer: PlaceClusterer,
+}
+
+impl<'a> PlaceClusterCollector<'a> {
+    fn new(fields: &'a IndexedFields, grid: ClusterGrid) -> Self {
+        Self {
+            fields,
+            clusterer: PlaceClusterer::new(grid),
+        }
+    }
+}
+
+impl<'a> From<PlaceClusterCollector<'a>> for Vec<PlaceCluster> {
+    fn from(from: PlaceClusterCollector<'a>) -> Self {
+        from.clusterer.into_clusters()
+    }
+}
+
+impl<'a> DocumentCollector for PlaceClusterCollector<'a> {
+    fn collect_document(&mut self, doc_addr: DocAddress, doc: Document) {
+        let lat = doc.get_first(self.fields.lat).map(Value::f64_value);
+        let lng = doc.get_first(self.fields.lng).map(Value::f64_value);
+        if let (Some(lat), Some(lng)) = (lat, lng) {
+            let pos = MapPoint::new(LatCoord::from_deg(lat), LngCoord::from_deg(lng));
+            let tags = doc
+                .get_all(self.fields.tag)
+                .into_iter()
+                .filter_map(Value::text);
+            self.clusterer.add_place(pos, tags);
+        } else {
+            error!(
+                "Document ({:?}) has an invalid position: lat = {:?}, lng = {:?}",
+                doc_addr, lat, lng
+            );
+        }
+    }
+}
+
 impl IdIndex for TantivyIndex {
     fn query_ids(
         &self,
@@ -868,7 +1324,15 @@ impl IdIndex for TantivyIndex {
 
 impl Indexer for TantivyIndex {
     fn flush_index(&mut self) -> Fallible<()> {
-        self.index_writer.commit().map_err(Fail::compat)?;
+        // The payload of the previous commit is not preserved and
+        // must be written again on every commit
+        let payload = serde_json::to_string(&IndexCommitPayload {
+            schema_version: INDEX_SCHEMA_VERSION,
+            high_water_mark: self.high_water_mark.map(TimestampMs::into_inner),
+        })?;
+        let mut prepared_commit = self.index_writer.prepare_commit().map_err(Fail::compat)?;
+        prepared_commit.set_payload(&payload);
+        prepared_commit.commit().map_err(Fail::compat)?;
         // Manually reload the reader to ensure that all committed changes
         // become visible immediately.
         self.index_reader.reload().map_err(Fail::compat)?;
@@ -902,7 +1366,9 @@ impl PlaceIndexer for TantivyIndex {
         doc.add_f64(self.fields.lat, place.location.pos.lat().to_deg());
         doc.add_f64(self.fields.lng, place.location.pos.lng().to_deg());
         doc.add_text(self.fields.title, &place.title);
+        doc.add_text(self.fields.title_en, &place.title);
         doc.add_text(self.fields.description, &place.description);
+        doc.add_text(self.fields.description_en, &place.description);
         if let Some(ref address) = place.location.address {
             let Address {
                 street,
@@ -936,6 +1402,20 @@ impl PlaceIndexer for TantivyIndex {
         for tag in &place.tags {
             doc.add_text(self.fields.tag, tag);
         }
+        // Opening hours that cannot be evaluated are not indexed
+        if let Some(schedule) = place
+            .opening_hours
+            .as_ref()
+            .and_then(|oh| oh.as_str().parse::<WeeklySchedule>().ok())
+        {
+            if schedule.is_always_open() {
+                doc.add_i64(self.fields.open_slot, ALWAYS_OPEN_SLOT);
+            } else {
+                for slot in schedule.open_slots(OPENING_HOURS_SLOT_MINUTES) {
+                    doc.add_i64(self.fields.open_slot, i64::from(slot));
+                }
+            }
+        }
         doc.add_u64(self.fields.total_rating, avg_rating_to_u64(ratings.total()));
         doc.add_f64(self.fields.ratings_diversity, ratings.diversity.into());
         doc.add_f64(self.fields.ratings_fairness, ratings.fairness.into());
@@ -990,13 +1470,32 @@ impl EventIndexer for TantivyIndex {
             self.fields.ts_min,
             Timestamp::from(event.start).into_inner(),
         );
-        if let Some(end) = event.end {
+        if let Some(ref recurrence) = event.recurrence {
+            // Recurring events are indexed with the bounds of their
+            // series, i.e. from the start of the first occurrence until
+            // the end of the last occurrence. Open-ended series have no
+            // upper bound.
+            let last_start = latest_occurrence_start(event.start, recurrence);
+            let ts_min_last = last_start
+                .map(|start| Timestamp::from(start).into_inner())
+                .unwrap_or(i64::MAX);
+            doc.add_i64(self.fields.ts_min_last, ts_min_last);
+            if let Some(end) = event.end {
+                debug_assert!(event.start <= end);
+                let ts_max = last_start
+                    .map(|start| Timestamp::from(start + (end - event.start)).into_inner())
+                    .unwrap_or(i64::MAX);
+                doc.add_i64(self.fields.ts_max, ts_max);
+            }
+        } else if let Some(end) = event.end {
             debug_assert!(event.start <= end);
             doc.add_i64(self.fields.ts_max, Timestamp::from(end).into_inner());
         }
         doc.add_text(self.fields.title, &event.title);
+        doc.add_text(self.fields.title_en, &event.title);
         if let Some(ref description) = event.description {
             doc.add_text(self.fields.description, description);
+            doc.add_text(self.fields.description_en, description);
         }
         if let Some(ref contact) = event.contact {
             let Contact { name, .. } = contact;
@@ -1018,33 +1517,69 @@ impl PlaceIndex for TantivyIndex {
         self.query_documents(IndexQueryMode::WithRating, query, limit, collector)
             .map(Into::into)
     }
+
+    fn cluster_places(
+        &self,
+        query: &IndexQuery,
+        grid: ClusterGrid,
+        limit: usize,
+    ) -> Fallible<Vec<PlaceCluster>> {
+        let collector = PlaceClusterCollector::new(&self.fields, grid);
+        // The order of the places doesn't matter
+        self.query_documents(IndexQueryMode::WithoutRating, query, limit, collector)
+            .map(Into::into)
+    }
 }
 
 impl EventAndPlaceIndexer for TantivyIndex {}
 
 #[derive(Clone)]
-pub struct SearchEngine(Arc<Mutex<Box<dyn EventAndPlaceIndexer + Send>>>);
+pub struct SearchEngine(Arc<Mutex<TantivyIndex>>);
 
 impl SearchEngine {
-    #[allow(dead_code)]
+    #[cfg(test)]
     pub fn init_in_ram() -> Fallible<SearchEngine> {
         let index = TantivyIndex::create_in_ram()?;
-        Ok(SearchEngine(Arc::new(Mutex::new(Box::new(index)))))
+        Ok(SearchEngine(Arc::new(Mutex::new(index))))
     }
 
     pub fn init_with_path<P: AsRef<Path>>(path: Option<P>) -> Fallible<SearchEngine> {
-        let index = TantivyIndex::create(path)?;
-        Ok(SearchEngine(Arc::new(Mutex::new(Box::new(index)))))
+        let index = TantivyIndex::open_or_create(path, false)?;
+        Ok(SearchEngine(Arc::new(Mutex::new(index))))
+    }
+
+    // Discards the contents of an existing index in the given
+    // directory.
+    pub fn recreate_with_path<P: AsRef<Path>>(path: Option<P>) -> Fallible<SearchEngine> {
+        let index = TantivyIndex::open_or_create(path, true)?;
+        Ok(SearchEngine(Arc::new(Mutex::new(index))))
+    }
+
+    // Keep the index locked while performing multiple operations,
+    // e.g. while reindexing a whole chunk of documents.
+    pub(crate) fn lock(&self) -> MutexGuard<TantivyIndex> {
+        match self.0.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    pub fn high_water_mark(&self) -> Option<TimestampMs> {
+        self.lock().high_water_mark()
+    }
+
+    pub fn flush_index_with_high_water_mark(
+        &mut self,
+        high_water_mark: TimestampMs,
+    ) -> Fallible<()> {
+        self.lock()
+            .flush_index_with_high_water_mark(high_water_mark)
     }
 }
 
 impl Indexer for SearchEngine {
     fn flush_index(&mut self) -> Fallible<()> {
-        let mut inner = match self.0.lock() {
-            Ok(guard) => guard,
-            Err(poisoned) => poisoned.into_inner(),
-        };
-        inner.flush_index()
+        self.lock().flush_index()
     }
 }
 
@@ -1055,31 +1590,28 @@ impl IdIndex for SearchEngine {
         query: &IndexQuery,
         limit: usize,
     ) -> Fallible<Vec<Id>> {
-        let inner = match self.0.lock() {
-            Ok(guard) => guard,
-            Err(poisoned) => poisoned.into_inner(),
-        };
-        inner.query_ids(mode, query, limit)
+        self.lock().query_ids(mode, query, limit)
     }
 }
 
 impl IdIndexer for SearchEngine {
     fn remove_by_id(&self, id: &Id) -> Fallible<()> {
-        let inner = match self.0.lock() {
-            Ok(guard) => guard,
-            Err(poisoned) => poisoned.into_inner(),
-        };
-        inner.remove_by_id(id)
+        self.lock().remove_by_id(id)
     }
 }
 
 impl PlaceIndex for SearchEngine {
     fn query_places(&self, query: &IndexQuery, limit: usize) -> Fallible<Vec<IndexedPlace>> {
-        let inner = match self.0.lock() {
-            Ok(guard) => guard,
-            Err(poisoned) => poisoned.into_inner(),
-        };
-        inner.query_places(query, limit)
+        self.lock().query_places(query, limit)
+    }
+
+    fn cluster_places(
+        &self,
+        query: &IndexQuery,
+        grid: ClusterGrid,
+        limit: usize,
+    ) -> Fallible<Vec<PlaceCluster>> {
+        self.lock().cluster_places(query, grid, limit)
     }
 }
 
@@ -1090,22 +1622,59 @@ impl PlaceIndexer for SearchEngine {
         status: ReviewStatus,
         ratings: &AvgRatings,
     ) -> Fallible<()> {
-        let inner = match self.0.lock() {
-            Ok(guard) => guard,
-            Err(poisoned) => poisoned.into_inner(),
-        };
-        inner.add_or_update_place(place, status, ratings)
+        self.lock().add_or_update_place(place, status, ratings)
     }
 }
 
 impl EventIndexer for SearchEngine {
     fn add_or_update_event(&self, event: &Event) -> Fallible<()> {
-        let inner = match self.0.lock() {
-            Ok(guard) => guard,
-            Err(poisoned) => poisoned.into_inner(),
-        };
-        inner.add_or_update_event(event)
+        self.lock().add_or_update_event(event)
     }
 }
 
 impl EventAndPlaceIndexer for SearchEngine {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebuild_index_without_touching_other_files() {
+        let dir = std::env::temp_dir().join(format!("ofdb-tantivy-{}", Id::new()));
+        fs::create_dir_all(&dir).unwrap();
+        let db_file = dir.join("openfairdb.sqlite");
+        fs::write(&db_file, "foo").unwrap();
+        let outdated_subdir = index_subdir(&dir, INDEX_SCHEMA_VERSION - 1);
+        fs::create_dir_all(&outdated_subdir).unwrap();
+        let legacy_files: Vec<_> = [
+            "meta.json",
+            ".managed.json",
+            "36d6936d8fb24cdc988bef7c1560c362.pos",
+            "36d6936d8fb24cdc988bef7c1560c362.3.del",
+        ]
+        .iter()
+        .map(|file_name| dir.join(file_name))
+        .collect();
+        for file in &legacy_files {
+            fs::write(file, "").unwrap();
+        }
+
+        let mut index = TantivyIndex::open_or_create(Some(&dir), false).unwrap();
+        index.flush_index().unwrap();
+        drop(index);
+        assert!(index_subdir(&dir, INDEX_SCHEMA_VERSION)
+            .join("meta.json")
+            .is_file());
+        assert!(!outdated_subdir.exists());
+        assert!(legacy_files.iter().all(|file| !file.exists()));
+
+        // Rebuild from scratch
+        let index = TantivyIndex::open_or_create(Some(&dir), true).unwrap();
+        // The index cannot be rebuilt while it is opened for writing
+        assert!(TantivyIndex::open_or_create(Some(&dir), true).is_err());
+        drop(index);
+
+        assert_eq!("foo", fs::read_to_string(&db_file).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
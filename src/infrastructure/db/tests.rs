@@ -0,0 +1,47 @@
+//! Databases for tests.
+//!
+//! Tests run against an in-memory SQLite database. If the `postgres`
+//! feature is enabled and `DATABASE_URL` points to a PostgreSQL server
+//! each test creates its own schema on that server instead, which is
+//! dropped again when the test has finished.
+
+#[cfg(feature = "postgres")]
+use super::postgres;
+use super::Connections;
+
+#[cfg(feature = "postgres")]
+use std::env;
+
+pub struct TestDatabase {
+    pub connections: Connections,
+    // Must be dropped after the connections
+    #[cfg(feature = "postgres")]
+    _schema: Option<postgres::tests::TestSchema>,
+}
+
+impl TestDatabase {
+    pub fn new() -> Self {
+        #[cfg(feature = "postgres")]
+        {
+            if let Some(url) = env::var("DATABASE_URL")
+                .ok()
+                .filter(|url| url.starts_with("postgres://") || url.starts_with("postgresql://"))
+            {
+                let schema = postgres::tests::TestSchema::create(&url);
+                let connections = Connections::init(&schema.url(), 1).unwrap();
+                connections.run_embedded_migrations().unwrap();
+                return Self {
+                    connections,
+                    _schema: Some(schema),
+                };
+            }
+        }
+        let connections = Connections::init(":memory:", 1).unwrap();
+        connections.run_embedded_migrations().unwrap();
+        Self {
+            connections,
+            #[cfg(feature = "postgres")]
+            _schema: None,
+        }
+    }
+}
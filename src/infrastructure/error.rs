@@ -1,4 +1,4 @@
-use crate::core::error::{Error as BError, RepoError};
+use crate::core::error::{Error as BError, ParameterError, RepoError};
 use diesel::r2d2;
 use diesel::result::Error as DieselError;
 use diesel_migrations::RunMigrationsError;
@@ -62,3 +62,13 @@ impl From<ofdb_entities::nonce::EmailNonceDecodingError> for AppError {
         BError::from(err).into()
     }
 }
+
+impl From<crate::adapters::image::ImageError> for AppError {
+    fn from(err: crate::adapters::image::ImageError) -> Self {
+        use crate::adapters::image::ImageError;
+        match err {
+            ImageError::Io(err) => err.into(),
+            err => BError::Parameter(ParameterError::InvalidImage(err.to_string())).into(),
+        }
+    }
+}
@@ -1,8 +1,7 @@
 use super::*;
-use diesel::connection::Connection;
 
 pub fn archive_comments(
-    connections: &sqlite::Connections,
+    connections: &Connections,
     account_email: &str,
     ids: &[&str],
 ) -> Result<usize> {
@@ -45,6 +44,7 @@ mod tests {
             usecases::NewUser {
                 email: "scout@foo.tld".into(),
                 password: "123456".into(),
+                locale: None,
             },
             Some(Role::Scout),
         );
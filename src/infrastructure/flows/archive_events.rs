@@ -1,21 +1,47 @@
-use super::*;
-
-use diesel::connection::Connection;
+use super::{jobs::*, *};
+use chrono::NaiveDateTime;
 
 fn exec_archive_events(
-    connections: &sqlite::Connections,
+    connections: &Connections,
     ids: &[&str],
-    _archived_by_email: &str,
-) -> Result<usize> {
+    archived_by_email: &str,
+) -> Result<(usize, Vec<Id>)> {
     let mut repo_err = None;
     let connection = connections.exclusive()?;
     Ok(connection
         .transaction::<_, diesel::result::Error, _>(|| {
-            usecases::archive_events(&*connection, ids).map_err(|err| {
-                warn!("Failed to archive {} events: {}", ids.len(), err);
-                repo_err = Some(err);
+            // Detached occurrences are archived together with their series
+            let occurrence_ids = connection
+                .detached_event_occurrence_ids(ids)
+                .map_err(|err| {
+                    warn!(
+                        "Failed to load occurrences of {} events: {}",
+                        ids.len(),
+                        err
+                    );
+                    repo_err = Some(err.into());
+                    diesel::result::Error::RollbackTransaction
+                })?;
+            let ids: Vec<_> = ids
+                .iter()
+                .copied()
+                .chain(occurrence_ids.iter().map(Id::as_str))
+                .collect();
+            let count = usecases::archive_events(&*connection, &ids, Some(archived_by_email))
+                .map_err(|err| {
+                    warn!("Failed to archive {} events: {}", ids.len(), err);
+                    repo_err = Some(err);
+                    diesel::result::Error::RollbackTransaction
+                })?;
+            // Archived events are removed from the search index
+            let jobs = [Job::ReindexEvents {
+                ids: ids.iter().map(|id| (*id).to_owned()).collect(),
+            }];
+            let job_ids = enqueue_jobs(&*connection, &jobs).map_err(|err| {
+                warn!("Failed to enqueue jobs for archived events: {}", err);
                 diesel::result::Error::RollbackTransaction
-            })
+            })?;
+            Ok((count, job_ids))
         })
         .map_err(|err| {
             if let Some(repo_err) = repo_err {
@@ -26,32 +52,63 @@ fn exec_archive_events(
         })?)
 }
 
-fn post_archive_events(indexer: &mut dyn EventIndexer, ids: &[&str]) {
-    // Remove archived events from search index
-    for id in ids {
-        if let Err(err) = usecases::unindex_event(indexer, &Id::from(*id)) {
-            error!(
-                "Failed to remove archived event {} from search index: {}",
-                id, err
-            );
-        }
-    }
-    if let Err(err) = indexer.flush_index() {
-        error!(
-            "Failed to finish updating the search index after archiving events: {}",
-            err
-        );
-    }
-}
-
+// Without an indexer the jobs for removing the archived events
+// from the search index are left to the background workers of
+// a running server.
 pub fn archive_events(
-    connections: &sqlite::Connections,
-    indexer: &mut dyn EventIndexer,
+    connections: &Connections,
+    indexer: Option<&mut dyn EventAndPlaceIndexer>,
     ids: &[&str],
     archived_by_email: &str,
 ) -> Result<usize> {
-    let count = exec_archive_events(connections, ids, archived_by_email)?;
-    // TODO: Move post processing to a separate task/thread that doesn't delay this request
-    post_archive_events(indexer, ids);
+    let (count, job_ids) = exec_archive_events(connections, ids, archived_by_email)?;
+    // Remove archived events from search index
+    if let Some(indexer) = indexer {
+        dispatch_jobs(connections, indexer, None, &job_ids);
+    }
     Ok(count)
 }
+
+fn exec_archive_event_occurrence(
+    connections: &Connections,
+    id: &str,
+    occurrence: NaiveDateTime,
+) -> Result<Vec<Id>> {
+    let mut repo_err = None;
+    let connection = connections.exclusive()?;
+    Ok(connection
+        .transaction::<_, diesel::result::Error, _>(|| {
+            usecases::exclude_event_occurrence(&*connection, id, occurrence).map_err(|err| {
+                warn!("Failed to archive occurrence of event {}: {}", id, err);
+                repo_err = Some(err);
+                diesel::result::Error::RollbackTransaction
+            })?;
+            let jobs = [Job::ReindexEvents {
+                ids: vec![id.to_owned()],
+            }];
+            enqueue_jobs(&*connection, &jobs).map_err(|err| {
+                warn!("Failed to enqueue jobs for archived occurrence: {}", err);
+                diesel::result::Error::RollbackTransaction
+            })
+        })
+        .map_err(|err| {
+            if let Some(repo_err) = repo_err {
+                repo_err
+            } else {
+                RepoError::from(err).into()
+            }
+        })?)
+}
+
+/// Archives a single occurrence of a recurring event while
+/// keeping all other occurrences.
+pub fn archive_event_occurrence(
+    connections: &Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
+    id: &str,
+    occurrence: NaiveDateTime,
+) -> Result<()> {
+    let job_ids = exec_archive_event_occurrence(connections, id, occurrence)?;
+    dispatch_jobs(connections, indexer, None, &job_ids);
+    Ok(())
+}
@@ -1,8 +1,7 @@
 use super::*;
-use diesel::connection::Connection;
 
 pub fn change_user_role(
-    connections: &sqlite::Connections,
+    connections: &Connections,
     account_email: &str,
     user_email: &str,
     role: Role,
@@ -28,6 +27,26 @@ pub fn change_user_role(
         })?)
 }
 
+pub fn set_user_role(connections: &Connections, user_email: &str, role: Role) -> Result<()> {
+    let mut repo_err = None;
+    let connection = connections.exclusive()?;
+    Ok(connection
+        .transaction::<_, diesel::result::Error, _>(|| {
+            usecases::set_user_role(&*connection, user_email, role).map_err(|err| {
+                warn!("Failed to set role for email {}: {}", user_email, err);
+                repo_err = Some(err);
+                diesel::result::Error::RollbackTransaction
+            })
+        })
+        .map_err(|err| {
+            if let Some(repo_err) = repo_err {
+                repo_err
+            } else {
+                RepoError::from(err).into()
+            }
+        })?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::tests::prelude::*;
@@ -48,6 +67,7 @@ mod tests {
             usecases::NewUser {
                 email: "user@bar.tld".into(),
                 password: "123456".into(),
+                locale: None,
             },
             None,
         );
@@ -55,6 +75,7 @@ mod tests {
             usecases::NewUser {
                 email: "admin@foo.tld".into(),
                 password: "123456".into(),
+                locale: None,
             },
             Some(Role::Admin),
         );
@@ -1,24 +1,23 @@
-use super::*;
+use super::{jobs::*, *};
 use crate::core::error::RepoError;
-use diesel::Connection;
 use ofdb_core::gateways::notify::NotificationGateway;
 
 pub fn create_event(
-    connections: &sqlite::Connections,
-    indexer: &mut dyn EventIndexer,
+    connections: &Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
     notify: &dyn NotificationGateway,
-    token: Option<&str>,
+    org_id: Option<&Id>,
     new_event: usecases::NewEvent,
 ) -> Result<Event> {
     // Create and add new event
-    let event = {
+    let (event, job_ids) = {
         let connection = connections.exclusive()?;
         let mut prepare_err = None;
         connection
             .transaction::<_, diesel::result::Error, _>(|| {
                 match usecases::import_new_event(
                     &*connection,
-                    token,
+                    org_id,
                     new_event,
                     usecases::NewEventMode::Create,
                 ) {
@@ -29,7 +28,19 @@ pub fn create_event(
                                 diesel::result::Error::RollbackTransaction
                             },
                         )?;
-                        Ok(event)
+                        let id = event.id.to_string();
+                        let jobs = [
+                            Job::ReindexEvents {
+                                ids: vec![id.clone()],
+                            },
+                            Job::NotifyEventCreated { id: id.clone() },
+                            Job::trigger_webhooks(WebhookEventType::EventCreated, id),
+                        ];
+                        let job_ids = enqueue_jobs(&*connection, &jobs).map_err(|err| {
+                            warn!("Failed to enqueue jobs for newly created event: {}", err);
+                            diesel::result::Error::RollbackTransaction
+                        })?;
+                        Ok((event, job_ids))
                     }
                     Err(err) => {
                         prepare_err = Some(err);
@@ -46,35 +57,8 @@ pub fn create_event(
             })
     }?;
 
-    // Index newly added event
-    // TODO: Move to a separate task/thread that doesn't delay this request
-    if let Err(err) = usecases::index_event(indexer, &event).and_then(|_| indexer.flush_index()) {
-        error!("Failed to index newly added event {}: {}", event.id, err);
-    }
-
-    // Send subscription e-mails
-    // TODO: Move to a separate task/thread that doesn't delay this request
-    if let Err(err) = notify_event_created(connections, notify, &event) {
-        error!(
-            "Failed to send notifications for newly added event {}: {}",
-            event.id, err
-        );
-    }
+    // Index newly added event and send subscription e-mails
+    dispatch_jobs(connections, indexer, Some(notify), &job_ids);
 
     Ok(event)
 }
-
-fn notify_event_created(
-    connections: &sqlite::Connections,
-    notify: &dyn NotificationGateway,
-    event: &Event,
-) -> Result<()> {
-    if let Some(ref location) = event.location {
-        let email_addresses = {
-            let conn = connections.shared()?;
-            usecases::email_addresses_by_coordinate(&*conn, location.pos)?
-        };
-        notify.event_created(&email_addresses, event);
-    }
-    Ok(())
-}
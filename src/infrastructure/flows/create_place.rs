@@ -1,18 +1,17 @@
-use super::*;
+use super::{jobs::*, *};
 use crate::core::error::RepoError;
-use diesel::Connection;
 use ofdb_core::gateways::notify::NotificationGateway;
 
 pub fn create_place(
-    connections: &sqlite::Connections,
-    indexer: &mut dyn PlaceIndexer,
+    connections: &Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
     notify: &dyn NotificationGateway,
     new_place: usecases::NewPlace,
     created_by_email: Option<&str>,
     created_by_org: Option<&Organization>,
 ) -> Result<Place> {
     // Create and add new entry
-    let (place, ratings) = {
+    let (place, job_ids) = {
         let connection = connections.exclusive()?;
         let mut prepare_err = None;
         connection
@@ -24,12 +23,28 @@ pub fn create_place(
                     created_by_org,
                 ) {
                     Ok(storable) => {
-                        let (place, ratings) = usecases::store_new_place(&*connection, storable)
+                        let clearance_org_ids = storable.clearance_org_ids().to_vec();
+                        let (place, _) = usecases::store_new_place(&*connection, storable)
                             .map_err(|err| {
                                 warn!("Failed to store newly created place: {}", err);
                                 diesel::result::Error::RollbackTransaction
                             })?;
-                        Ok((place, ratings))
+                        let id = place.id.to_string();
+                        let mut jobs = vec![
+                            Job::ReindexPlaces {
+                                ids: vec![id.clone()],
+                            },
+                            Job::NotifyPlaceAdded { id: id.clone() },
+                            Job::trigger_webhooks(WebhookEventType::PlaceAdded, id.clone()),
+                        ];
+                        if !clearance_org_ids.is_empty() {
+                            jobs.push(Job::trigger_clearance_webhooks(id, &clearance_org_ids));
+                        }
+                        let job_ids = enqueue_jobs(&*connection, &jobs).map_err(|err| {
+                            warn!("Failed to enqueue jobs for newly created place: {}", err);
+                            diesel::result::Error::RollbackTransaction
+                        })?;
+                        Ok((place, job_ids))
                     }
                     Err(err) => {
                         log::info!("Failed to prepare new place revision: {}", err);
@@ -47,38 +62,8 @@ pub fn create_place(
             })
     }?;
 
-    // Index newly added place
-    // TODO: Move to a separate task/thread that doesn't delay this request
-    if let Err(err) = usecases::reindex_place(indexer, &place, ReviewStatus::Created, &ratings)
-        .and_then(|_| indexer.flush_index())
-    {
-        error!("Failed to index newly added place {}: {}", place.id, err);
-    }
-
-    // Send subscription e-mails
-    // TODO: Move to a separate task/thread that doesn't delay this request
-    if let Err(err) = notify_place_added(connections, notify, &place) {
-        error!(
-            "Failed to send notifications for newly added place {}: {}",
-            place.id, err
-        );
-    }
+    // Index newly added place and send subscription e-mails
+    dispatch_jobs(connections, indexer, Some(notify), &job_ids);
 
     Ok(place)
 }
-
-fn notify_place_added(
-    connections: &sqlite::Connections,
-    notify: &dyn NotificationGateway,
-    place: &Place,
-) -> Result<()> {
-    let (email_addresses, all_categories) = {
-        let connection = connections.shared()?;
-        let email_addresses =
-            usecases::email_addresses_by_coordinate(&*connection, place.location.pos)?;
-        let all_categories = connection.all_categories()?;
-        (email_addresses, all_categories)
-    };
-    notify.place_added(&email_addresses, place, all_categories);
-    Ok(())
-}
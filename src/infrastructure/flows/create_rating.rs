@@ -1,14 +1,12 @@
-use super::*;
-
-use diesel::connection::Connection;
+use super::{jobs::*, *};
 
 pub fn create_rating(
-    connections: &sqlite::Connections,
-    indexer: &mut dyn PlaceIndexer,
+    connections: &Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
     rate_entry: usecases::NewPlaceRating,
 ) -> Result<(String, String)> {
     // Add new rating to existing entry
-    let (rating_id, comment_id, place, status, ratings) = {
+    let (rating_id, comment_id, job_ids) = {
         let connection = connections.exclusive()?;
         let mut prepare_err = None;
         connection
@@ -17,12 +15,19 @@ pub fn create_rating(
                     Ok(storable) => {
                         let rating_id = storable.rating_id().to_owned();
                         let comment_id = storable.comment_id().to_owned();
-                        let (place, status, ratings) =
-                            usecases::store_new_rating(&*connection, storable).map_err(|err| {
+                        let (place, _, _) = usecases::store_new_rating(&*connection, storable)
+                            .map_err(|err| {
                                 warn!("Failed to store new rating for entry: {}", err);
                                 diesel::result::Error::RollbackTransaction
                             })?;
-                        Ok((rating_id, comment_id, place, status, ratings))
+                        let jobs = [Job::ReindexPlaces {
+                            ids: vec![place.id.to_string()],
+                        }];
+                        let job_ids = enqueue_jobs(&*connection, &jobs).map_err(|err| {
+                            warn!("Failed to enqueue jobs for new rating: {}", err);
+                            diesel::result::Error::RollbackTransaction
+                        })?;
+                        Ok((rating_id, comment_id, job_ids))
                     }
                     Err(err) => {
                         prepare_err = Some(err);
@@ -40,15 +45,7 @@ pub fn create_rating(
     }?;
 
     // Reindex entry after adding the new rating
-    // TODO: Move to a separate task/thread that doesn't delay this request
-    if let Err(err) = usecases::reindex_place(indexer, &place, status, &ratings)
-        .and_then(|_| indexer.flush_index())
-    {
-        error!(
-            "Failed to reindex place {} after adding a new rating: {}",
-            place.id, err
-        );
-    }
+    dispatch_jobs(connections, indexer, None, &job_ids);
 
     Ok((rating_id, comment_id))
 }
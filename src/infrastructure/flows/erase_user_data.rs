@@ -0,0 +1,246 @@
+use super::*;
+
+/// Erases the personal data of a user within a single transaction.
+///
+/// Either all references in all repositories are pseudonymized
+/// or none of them.
+pub fn erase_user_data(
+    connections: &Connections,
+    email: &str,
+    erased_by: Option<&str>,
+) -> Result<User> {
+    let mut repo_err = None;
+    let connection = connections.exclusive()?;
+    Ok(connection
+        .transaction::<_, diesel::result::Error, _>(|| {
+            usecases::erase_user_data(&*connection, email, erased_by).map_err(|err| {
+                warn!("Failed to erase the data of user {}: {}", email, err);
+                repo_err = Some(err);
+                diesel::result::Error::RollbackTransaction
+            })
+        })
+        .map_err(|err| {
+            if let Some(repo_err) = repo_err {
+                repo_err
+            } else {
+                RepoError::from(err).into()
+            }
+        })?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::prelude::*;
+    use crate::infrastructure::db::Connection;
+    use diesel::{
+        dsl::sql_query,
+        sql_types::{BigInt, Text},
+        RunQueryDsl,
+    };
+
+    #[derive(QueryableByName)]
+    struct NameRow {
+        #[sql_type = "Text"]
+        name: String,
+    }
+
+    #[derive(QueryableByName)]
+    struct CountRow {
+        #[sql_type = "BigInt"]
+        count: i64,
+    }
+
+    fn load_names(db: &Connection, sql: &str) -> Vec<String> {
+        match db {
+            Connection::Sqlite(conn) => sql_query(sql).load::<NameRow>(conn),
+            #[cfg(feature = "postgres")]
+            Connection::Postgres(conn) => sql_query(sql).load::<NameRow>(conn),
+        }
+        .unwrap()
+        .into_iter()
+        .map(|row| row.name)
+        .collect()
+    }
+
+    // Counts the rows in all tables with any column that contains the text
+    fn count_rows_containing(connections: &Connections, text: &str) -> i64 {
+        let db = connections.shared().unwrap();
+        let tables = match &*db {
+            Connection::Sqlite(_) => load_names(
+                &db,
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+            ),
+            #[cfg(feature = "postgres")]
+            Connection::Postgres(_) => load_names(
+                &db,
+                "SELECT table_name::TEXT AS name FROM information_schema.tables \
+                 WHERE table_schema = current_schema()",
+            ),
+        };
+        let mut count = 0;
+        for table in tables {
+            let (columns_sql, contains) = match &*db {
+                Connection::Sqlite(_) => (
+                    format!("SELECT name FROM pragma_table_info('{}')", table),
+                    "instr(CAST(\"{}\" AS TEXT), ?1) > 0",
+                ),
+                #[cfg(feature = "postgres")]
+                Connection::Postgres(_) => (
+                    format!(
+                        "SELECT column_name::TEXT AS name FROM information_schema.columns \
+                         WHERE table_schema = current_schema() AND table_name = '{}'",
+                        table
+                    ),
+                    // Substring searches don't support nondeterministic collations
+                    "strpos(CAST(\"{}\" AS TEXT) COLLATE \"C\", $1) > 0",
+                ),
+            };
+            let condition = load_names(&db, &columns_sql)
+                .iter()
+                .map(|column| contains.replace("{}", column))
+                .collect::<Vec<_>>()
+                .join(" OR ");
+            let sql = format!(
+                "SELECT COUNT(*) AS count FROM \"{}\" WHERE {}",
+                table, condition
+            );
+            let rows = match &*db {
+                Connection::Sqlite(conn) => {
+                    sql_query(sql).bind::<Text, _>(text).load::<CountRow>(conn)
+                }
+                #[cfg(feature = "postgres")]
+                Connection::Postgres(conn) => {
+                    sql_query(sql).bind::<Text, _>(text).load::<CountRow>(conn)
+                }
+            };
+            count += rows.unwrap()[0].count;
+        }
+        count
+    }
+
+    // Stores copies of the e-mail address in serialized values
+    fn store_serialized_email(fixture: &BackendFixture, email: &str) {
+        let mut db = fixture.db_connections.exclusive().unwrap();
+        db.create_org(Organization {
+            id: "org".into(),
+            name: "org".into(),
+            moderated_tags: vec![],
+        })
+        .unwrap();
+        let now = TimestampMs::now();
+        let webhook = Webhook {
+            id: Id::new(),
+            org_id: "org".into(),
+            url: "https://example.com/hook".parse().unwrap(),
+            secret: "0123456789abcdef".into(),
+            event_types: vec![WebhookEventType::EventCreated],
+            bbox: None,
+            tags: vec![],
+            created_at: now,
+        };
+        db.create_webhook(&webhook).unwrap();
+        let payload = format!(r#"{{"data":{{"created_by":"{}"}}}}"#, email);
+        db.create_webhook_delivery(&WebhookDelivery {
+            id: Id::new(),
+            webhook_id: webhook.id,
+            event_type: WebhookEventType::EventCreated,
+            payload: payload.clone(),
+            status: WebhookDeliveryStatus::Pending,
+            attempts: 0,
+            response_status: None,
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+        })
+        .unwrap();
+        db.enqueue_job(&Id::new(), &payload, now).unwrap();
+        usecases::record_audit_log(
+            &*db,
+            AuditLogEntry {
+                before: Some(payload),
+                after: Some(email.to_string()),
+                ..AuditLogEntry::now(None, AuditAction::ChangeUserRole, vec![])
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn should_export_and_pseudonymize_all_activities_of_a_user() {
+        let fixture = BackendFixture::new();
+        fixture.create_user(
+            usecases::NewUser {
+                email: "user@bar.tld".into(),
+                password: "123456".into(),
+                locale: None,
+            },
+            None,
+        );
+        let place_id = fixture.create_place(0.into(), Some("user@bar.tld"));
+        usecases::subscribe_to_bbox(
+            &*fixture.db_connections.exclusive().unwrap(),
+            "user@bar.tld".into(),
+            MapBbox::new(
+                MapPoint::from_lat_lng_deg(-10.0, -10.0),
+                MapPoint::from_lat_lng_deg(10.0, 10.0),
+            ),
+            None,
+        )
+        .unwrap();
+        store_serialized_email(&fixture, "user@bar.tld");
+        assert!(count_rows_containing(&fixture.db_connections, "user@bar.tld") > 0);
+
+        let export =
+            usecases::export_user_data(&*fixture.db_connections.shared().unwrap(), "user@bar.tld")
+                .unwrap();
+        assert_eq!("user@bar.tld", export.user.email);
+        assert_eq!(1, export.bbox_subscriptions.len());
+        // Creating a place also records its initial review status
+        assert_eq!(2, export.activities.len());
+        assert_eq!(UserActivityKind::PlaceRevision, export.activities[0].kind);
+        assert_eq!(UserActivityKind::PlaceReview, export.activities[1].kind);
+        assert!(export.activities.iter().all(|a| a.id.as_str() == place_id));
+
+        let pseudonym = flows::erase_user_data(
+            &fixture.db_connections,
+            "user@bar.tld",
+            Some("user@bar.tld"),
+        )
+        .unwrap();
+        assert!(fixture.try_get_user("user@bar.tld").is_none());
+        assert!(fixture.try_get_user(&pseudonym.email).is_some());
+        let db = fixture.db_connections.shared().unwrap();
+        assert!(db
+            .all_bbox_subscriptions_by_email("user@bar.tld")
+            .unwrap()
+            .is_empty());
+        // The history of the place refers to the pseudonym
+        let history = db.get_place_history(&place_id, None).unwrap();
+        assert_eq!(1, history.revisions.len());
+        assert_eq!(
+            Some(pseudonym.email.as_str()),
+            history.revisions[0]
+                .0
+                .created
+                .by
+                .as_ref()
+                .map(|email| email.as_str())
+        );
+        assert!(usecases::export_user_data(&*db, "user@bar.tld").is_err());
+        // The audit log refers to the pseudonym
+        let query = AuditLogQuery {
+            action: Some(AuditAction::EraseUserData),
+            ..Default::default()
+        };
+        let entries = db.query_audit_log(&query, &Pagination::default()).unwrap();
+        assert_eq!(1, entries.len());
+        assert_eq!(Some(pseudonym.email.as_str()), entries[0].actor.as_deref());
+        assert_eq!(vec![pseudonym.email], entries[0].target_ids);
+        drop(db);
+        // The e-mail address has been erased everywhere
+        assert_eq!(
+            0,
+            count_rows_containing(&fixture.db_connections, "user@bar.tld")
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use super::{jobs::*, *};
+use crate::core::error::RepoError;
+use ofdb_core::gateways::notify::NotificationGateway;
+
+// Without an indexer all jobs, i.e. also the notifications about
+// the imported events, are left to the background workers of a
+// running server.
+pub fn import_events(
+    connections: &Connections,
+    indexer: Option<&mut dyn EventAndPlaceIndexer>,
+    notify: &dyn NotificationGateway,
+    org_id: &Id,
+    external_events: Vec<usecases::ExternalEvent>,
+) -> Result<usecases::ImportedEvents> {
+    let (imported, job_ids) = {
+        let connection = connections.exclusive()?;
+        let mut prepare_err = None;
+        connection
+            .transaction::<_, diesel::result::Error, _>(|| {
+                match usecases::import_events(&*connection, org_id, external_events) {
+                    Ok(imported) => {
+                        let ids = imported
+                            .created
+                            .iter()
+                            .chain(imported.updated.iter())
+                            .chain(imported.archived.iter())
+                            .map(ToString::to_string)
+                            .collect();
+                        // Only newly created events are announced to
+                        // subscribers, because repeated imports would
+                        // otherwise flood them with updates.
+                        let jobs: Vec<_> = std::iter::once(Job::ReindexEvents { ids })
+                            .chain(imported.created.iter().flat_map(|id| {
+                                vec![
+                                    Job::NotifyEventCreated { id: id.to_string() },
+                                    Job::trigger_webhooks(
+                                        WebhookEventType::EventCreated,
+                                        id.to_string(),
+                                    ),
+                                ]
+                            }))
+                            .collect();
+                        let job_ids = enqueue_jobs(&*connection, &jobs).map_err(|err| {
+                            warn!("Failed to enqueue jobs for imported events: {}", err);
+                            diesel::result::Error::RollbackTransaction
+                        })?;
+                        Ok((imported, job_ids))
+                    }
+                    Err(err) => {
+                        prepare_err = Some(err);
+                        Err(diesel::result::Error::RollbackTransaction)
+                    }
+                }
+            })
+            .map_err(|err| {
+                if let Some(err) = prepare_err {
+                    err
+                } else {
+                    RepoError::from(err).into()
+                }
+            })
+    }?;
+
+    // Reindex all imported events and send subscription e-mails
+    if let Some(indexer) = indexer {
+        dispatch_jobs(connections, indexer, Some(notify), &job_ids);
+    }
+
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::prelude::*;
+
+    fn new_event(title: &str) -> usecases::NewEvent {
+        usecases::NewEvent {
+            title: title.into(),
+            start: chrono::Utc::now().naive_utc().timestamp(),
+            created_by: Some("importer@example.com".into()),
+            ..Default::default()
+        }
+    }
+
+    fn external_event(uid: &str, title: &str) -> usecases::ExternalEvent {
+        usecases::ExternalEvent {
+            uid: uid.into(),
+            new_event: new_event(title),
+        }
+    }
+
+    fn import_events(
+        fixture: &BackendFixture,
+        external_events: Vec<usecases::ExternalEvent>,
+    ) -> usecases::ImportedEvents {
+        flows::import_events(
+            &fixture.db_connections,
+            Some(&mut *fixture.search_engine.borrow_mut()),
+            &fixture.notify,
+            &"foo".into(),
+            external_events,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn should_create_update_and_archive_imported_events() {
+        let fixture = BackendFixture::new();
+        fixture
+            .db_connections
+            .exclusive()
+            .unwrap()
+            .create_org(Organization {
+                id: "foo".into(),
+                name: "foo".into(),
+                moderated_tags: vec!["foo".into()],
+            })
+            .unwrap();
+
+        let imported = import_events(
+            &fixture,
+            vec![external_event("a", "A"), external_event("b", "B")],
+        );
+        assert_eq!(2, imported.created.len());
+        assert!(imported.updated.is_empty());
+        assert!(imported.archived.is_empty());
+        let id_a = imported.created[0].clone();
+        let id_b = imported.created[1].clone();
+
+        let imported = import_events(
+            &fixture,
+            vec![
+                external_event("a", "A2"),
+                external_event("c", "C"),
+                usecases::ExternalEvent {
+                    uid: "d".into(),
+                    new_event: usecases::NewEvent {
+                        created_by: None,
+                        ..new_event("D")
+                    },
+                },
+            ],
+        );
+        assert_eq!(1, imported.created.len());
+        assert_eq!(vec![id_a.clone()], imported.updated);
+        assert_eq!(vec![id_b.clone()], imported.archived);
+        assert_eq!(1, imported.failed.len());
+        assert_eq!("d", imported.failed[0].0);
+
+        let db = fixture.db_connections.shared().unwrap();
+        let event_a = db.get_event(id_a.as_str()).unwrap();
+        assert_eq!("A2", event_a.title);
+        assert!(event_a.tags.iter().any(|t| t == "foo"));
+        assert!(db.get_event(id_b.as_str()).is_err());
+        assert_eq!(2, db.count_events().unwrap());
+    }
+}
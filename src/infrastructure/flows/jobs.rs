@@ -0,0 +1,711 @@
+use super::*;
+use crate::{
+    adapters::json,
+    infrastructure::{db::tantivy, error::AppError, WEBHOOK_GW},
+};
+use ofdb_core::gateways::{
+    notify::NotificationGateway,
+    webhook::{WebhookDeliveryError, WebhookGateway},
+};
+use std::{
+    ops::Deref,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Condvar, Mutex, PoisonError,
+    },
+    thread,
+    time::Duration,
+};
+
+// Jobs that have neither been completed nor rescheduled within
+// this period, e.g. because the worker crashed, are retried.
+const JOB_LEASE_DURATION: Duration = Duration::from_secs(300);
+
+// The delay is doubled after each failed attempt
+const JOB_RETRY_INITIAL_DELAY: Duration = Duration::from_secs(10);
+
+const MAX_JOB_ATTEMPTS: u32 = 10;
+
+// Idle workers check for jobs that have become due again
+const JOB_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+// Side effects of modifications that are executed after the
+// corresponding transaction has been committed. The jobs are
+// stored in the database within the same transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Job {
+    ReindexPlaces {
+        ids: Vec<String>,
+    },
+    ReindexEvents {
+        ids: Vec<String>,
+    },
+    NotifyPlaceAdded {
+        id: String,
+    },
+    NotifyPlaceUpdated {
+        id: String,
+    },
+    NotifyEventCreated {
+        id: String,
+    },
+    NotifyEventUpdated {
+        id: String,
+    },
+    // Creates a delivery for each matching webhook. Only webhooks
+    // of the given organizations are considered if specified.
+    TriggerWebhooks {
+        event_type: String,
+        id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        org_ids: Option<Vec<String>>,
+    },
+    DeliverWebhook {
+        delivery_id: String,
+    },
+}
+
+impl Job {
+    pub fn trigger_webhooks(event_type: WebhookEventType, id: String) -> Self {
+        Self::TriggerWebhooks {
+            event_type: event_type.as_str().to_owned(),
+            id,
+            org_ids: None,
+        }
+    }
+
+    pub fn trigger_clearance_webhooks(id: String, org_ids: &[Id]) -> Self {
+        Self::TriggerWebhooks {
+            event_type: WebhookEventType::ClearancePending.as_str().to_owned(),
+            id,
+            org_ids: Some(org_ids.iter().map(ToString::to_string).collect()),
+        }
+    }
+}
+
+static RUNNING_JOB_WORKERS: AtomicUsize = AtomicUsize::new(0);
+
+// The flag must be guarded by the mutex of the condition variable
+#[allow(clippy::mutex_atomic)]
+fn new_pending_jobs() -> (Mutex<bool>, Condvar) {
+    (Mutex::new(false), Condvar::new())
+}
+
+lazy_static! {
+    static ref PENDING_JOBS: (Mutex<bool>, Condvar) = new_pending_jobs();
+}
+
+fn wake_up_job_workers() {
+    let (pending, condvar) = &*PENDING_JOBS;
+    *pending.lock().unwrap_or_else(PoisonError::into_inner) = true;
+    condvar.notify_one();
+}
+
+fn wait_for_pending_jobs(timeout: Duration) {
+    let (pending, condvar) = &*PENDING_JOBS;
+    let pending = pending.lock().unwrap_or_else(PoisonError::into_inner);
+    let (mut pending, _) = condvar
+        .wait_timeout_while(pending, timeout, |pending| !*pending)
+        .unwrap_or_else(PoisonError::into_inner);
+    *pending = false;
+}
+
+fn add_duration(timestamp: TimestampMs, duration: Duration) -> TimestampMs {
+    TimestampMs::from_inner(timestamp.into_inner() + duration.as_millis() as i64)
+}
+
+fn retry_delay(attempts: u32) -> Duration {
+    JOB_RETRY_INITIAL_DELAY * 2u32.pow(attempts.max(1).min(MAX_JOB_ATTEMPTS) - 1)
+}
+
+// Must be invoked within the transaction of the corresponding
+// modifications.
+pub(crate) fn enqueue_jobs<R: JobQueue + ?Sized>(
+    repo: &R,
+    jobs: &[Job],
+) -> std::result::Result<Vec<Id>, RepoError> {
+    let run_at = TimestampMs::now();
+    let mut ids = Vec::with_capacity(jobs.len());
+    for job in jobs {
+        let id = Id::new();
+        let payload = serde_json::to_string(job).map_err(|err| RepoError::Other(err.into()))?;
+        repo.enqueue_job(&id, &payload, run_at)?;
+        ids.push(id);
+    }
+    Ok(ids)
+}
+
+// Wakes up the background workers after the jobs have been committed.
+// Without any background workers, e.g. when invoked from the command
+// line, the jobs are executed immediately. Failed jobs are retried by
+// the background workers later.
+pub(crate) fn dispatch_jobs(
+    connections: &Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
+    notify: Option<&dyn NotificationGateway>,
+    job_ids: &[Id],
+) {
+    if RUNNING_JOB_WORKERS.load(Ordering::Acquire) > 0 {
+        wake_up_job_workers();
+        return;
+    }
+    for id in job_ids {
+        if let Err(err) = run_job(connections, indexer, notify, id) {
+            error!("Failed to run job {}: {}", id, err);
+        }
+    }
+}
+
+fn run_job(
+    connections: &Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
+    notify: Option<&dyn NotificationGateway>,
+    id: &Id,
+) -> Result<()> {
+    let now = TimestampMs::now();
+    let queued_job =
+        connections
+            .exclusive()?
+            .claim_job(id, now, add_duration(now, JOB_LEASE_DURATION))?;
+    if let Some(queued_job) = queued_job {
+        run_claimed_job(connections, indexer, notify, queued_job)?;
+    }
+    Ok(())
+}
+
+// Returns `false` if no job is due.
+pub fn run_next_job(
+    connections: &Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
+    notify: Option<&dyn NotificationGateway>,
+) -> Result<bool> {
+    let now = TimestampMs::now();
+    let queued_job = connections
+        .exclusive()?
+        .claim_next_job(now, add_duration(now, JOB_LEASE_DURATION))?;
+    if let Some(queued_job) = queued_job {
+        run_claimed_job(connections, indexer, notify, queued_job)?;
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+fn run_claimed_job(
+    connections: &Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
+    notify: Option<&dyn NotificationGateway>,
+    queued_job: QueuedJob,
+) -> Result<()> {
+    let QueuedJob {
+        id,
+        payload,
+        attempts,
+    } = queued_job;
+    let res = serde_json::from_str::<Job>(&payload)
+        .map_err(AppError::from)
+        .and_then(|job| {
+            debug!("Running job {} (attempt #{}): {:?}", id, attempts, job);
+            execute_job(connections, indexer, notify, &job)
+        });
+    let connection = connections.exclusive()?;
+    match res {
+        Ok(()) => connection.complete_job(&id)?,
+        Err(err) if attempts >= MAX_JOB_ATTEMPTS => {
+            error!(
+                "Discarding job {} after {} failed attempts: {}",
+                id, attempts, err
+            );
+            connection.complete_job(&id)?;
+        }
+        Err(err) => {
+            let delay = retry_delay(attempts);
+            warn!(
+                "Job {} failed (attempt #{}) and will be retried in {} s: {}",
+                id,
+                attempts,
+                delay.as_secs(),
+                err
+            );
+            let run_at = add_duration(TimestampMs::now(), delay);
+            connection.reschedule_job(&id, run_at, &err.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+fn execute_job(
+    connections: &Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
+    notify: Option<&dyn NotificationGateway>,
+    job: &Job,
+) -> Result<()> {
+    match job {
+        Job::ReindexPlaces { ids } => {
+            let ids: Vec<_> = ids.iter().map(String::as_str).collect();
+            {
+                let connection = connections.shared()?;
+                usecases::reindex_places(&*connection, &*indexer, &ids)?;
+            }
+            indexer.flush_index()?;
+        }
+        Job::ReindexEvents { ids } => {
+            let ids: Vec<_> = ids.iter().map(String::as_str).collect();
+            {
+                let connection = connections.shared()?;
+                usecases::reindex_events(&*connection, &*indexer, &ids)?;
+            }
+            indexer.flush_index()?;
+        }
+        Job::NotifyPlaceAdded { id } => {
+            let (place, _) = connections.shared()?.get_place(id)?;
+            notify_place_added(connections, required_notify(notify)?, &place)?;
+        }
+        Job::NotifyPlaceUpdated { id } => {
+            let (place, _) = connections.shared()?.get_place(id)?;
+            notify_place_updated(connections, required_notify(notify)?, &place)?;
+        }
+        Job::NotifyEventCreated { id } => {
+            if let Some(event) = load_event_for_notification(connections, id)? {
+                notify_event_created(connections, required_notify(notify)?, &event)?;
+            }
+        }
+        Job::NotifyEventUpdated { id } => {
+            if let Some(event) = load_event_for_notification(connections, id)? {
+                notify_event_updated(connections, required_notify(notify)?, &event)?;
+            }
+        }
+        Job::TriggerWebhooks {
+            event_type,
+            id,
+            org_ids,
+        } => {
+            let event_type = event_type
+                .parse::<WebhookEventType>()
+                .map_err(anyhow::Error::from)?;
+            let org_ids: Option<Vec<Id>> = org_ids
+                .as_ref()
+                .map(|ids| ids.iter().map(|id| id.as_str().into()).collect());
+            let job_ids = trigger_webhooks(connections, event_type, id, org_ids.as_deref())?;
+            dispatch_jobs(connections, indexer, notify, &job_ids);
+        }
+        Job::DeliverWebhook { delivery_id } => {
+            deliver_webhook(connections, &*WEBHOOK_GW, &delivery_id.as_str().into())?;
+        }
+    }
+    Ok(())
+}
+
+enum WebhookData {
+    Place(serde_json::Value),
+    // Filtered individually for each receiving organization
+    Event(Box<Event>),
+}
+
+// Creates the deliveries and enqueues a job for each of them
+fn trigger_webhooks(
+    connections: &Connections,
+    event_type: WebhookEventType,
+    id: &str,
+    org_ids: Option<&[Id]>,
+) -> Result<Vec<Id>> {
+    let (data, pos, tags) = match event_type {
+        WebhookEventType::PlaceAdded
+        | WebhookEventType::PlaceUpdated
+        | WebhookEventType::ClearancePending => {
+            let connection = connections.shared()?;
+            let (place, _) = connection.get_place(id)?;
+            let ratings = connection.load_ratings_of_place(id)?;
+            let pos = place.location.pos;
+            let tags = place.tags.clone();
+            let entry = json::entry_from_place_with_ratings(place, ratings);
+            (
+                WebhookData::Place(serde_json::to_value(entry)?),
+                Some(pos),
+                tags,
+            )
+        }
+        WebhookEventType::EventCreated | WebhookEventType::EventUpdated => {
+            let event = match load_event_for_notification(connections, id)? {
+                Some(event) => event,
+                None => return Ok(vec![]),
+            };
+            let pos = event.location.as_ref().map(|l| l.pos);
+            let tags = event.tags.clone();
+            (WebhookData::Event(Box::new(event)), pos, tags)
+        }
+    };
+    let connection = connections.exclusive()?;
+    let mut repo_err = None;
+    let job_ids = connection
+        .transaction::<_, diesel::result::Error, _>(|| {
+            let deliveries = usecases::create_webhook_deliveries(
+                &*connection,
+                event_type,
+                pos,
+                &tags,
+                org_ids,
+                |webhook, delivery_id, created_at| {
+                    let data = match &data {
+                        WebhookData::Place(data) => data.clone(),
+                        WebhookData::Event(event) => {
+                            // Only the owners of an event receive all details
+                            // like for any other request of an organization
+                            let org = connection.get_org(&webhook.org_id)?;
+                            let event = usecases::filter_event(
+                                (**event).clone(),
+                                org.moderated_tags.iter().map(|t| t.label.as_str()),
+                            );
+                            serde_json::json!(json::Event::from(event))
+                        }
+                    };
+                    Ok(serde_json::json!({
+                        "id": delivery_id.as_str(),
+                        "event": event_type.as_str(),
+                        "created_at": created_at.into_inner(),
+                        "data": data,
+                    })
+                    .to_string())
+                },
+            )
+            .map_err(|err| {
+                warn!("Failed to create webhook deliveries: {}", err);
+                repo_err = Some(err);
+                diesel::result::Error::RollbackTransaction
+            })?;
+            let jobs: Vec<_> = deliveries
+                .into_iter()
+                .map(|d| Job::DeliverWebhook {
+                    delivery_id: d.id.into(),
+                })
+                .collect();
+            enqueue_jobs(&*connection, &jobs).map_err(|err| {
+                warn!("Failed to enqueue webhook deliveries: {}", err);
+                diesel::result::Error::RollbackTransaction
+            })
+        })
+        .map_err(|err| {
+            if let Some(err) = repo_err {
+                AppError::from(err)
+            } else {
+                RepoError::from(err).into()
+            }
+        })?;
+    Ok(job_ids)
+}
+
+// Failed deliveries are retried together with the job
+fn deliver_webhook(
+    connections: &Connections,
+    gateway: &dyn WebhookGateway,
+    delivery_id: &Id,
+) -> Result<()> {
+    let (mut delivery, webhook) = {
+        let connection = connections.shared()?;
+        let delivery = match connection.get_webhook_delivery(delivery_id) {
+            Ok(delivery) => delivery,
+            Err(RepoError::NotFound) => {
+                info!("Skipping delivery {} of deleted webhook", delivery_id);
+                return Ok(());
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let webhook = connection.get_webhook(&delivery.webhook_id)?;
+        (delivery, webhook)
+    };
+    let res = gateway.deliver(
+        &webhook.url,
+        &webhook.secret,
+        delivery.id.as_str(),
+        delivery.event_type,
+        &delivery.payload,
+    );
+    delivery.attempts += 1;
+    delivery.updated_at = TimestampMs::now();
+    match &res {
+        Ok(status) => {
+            delivery.status = WebhookDeliveryStatus::Delivered;
+            delivery.response_status = Some(*status);
+            delivery.last_error = None;
+        }
+        Err(err) => {
+            delivery.status = WebhookDeliveryStatus::Failed;
+            delivery.response_status = match err {
+                WebhookDeliveryError::Status(status) => Some(*status),
+                WebhookDeliveryError::Request(_) => None,
+            };
+            delivery.last_error = Some(err.to_string());
+        }
+    }
+    connections
+        .exclusive()?
+        .update_webhook_delivery(&delivery)?;
+    res.map(|_| ())
+        .map_err(|err| anyhow::anyhow!("Failed to deliver webhook {}: {}", webhook.id, err).into())
+}
+
+fn required_notify(notify: Option<&dyn NotificationGateway>) -> Result<&dyn NotificationGateway> {
+    notify.ok_or_else(|| anyhow::anyhow!("No notification gateway available").into())
+}
+
+fn load_event_for_notification(connections: &Connections, id: &str) -> Result<Option<Event>> {
+    match connections.shared()?.get_event(id) {
+        Ok(event) => Ok(Some(event)),
+        Err(RepoError::NotFound) => {
+            info!(
+                "Skipping notifications for deleted or archived event {}",
+                id
+            );
+            Ok(None)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn notify_place_added(
+    connections: &Connections,
+    notify: &dyn NotificationGateway,
+    place: &Place,
+) -> Result<()> {
+    // Changes for digests are queued as a side effect
+    let recipients = usecases::recipients_of_change(
+        &*connections.exclusive()?,
+        SubscriptionChangeKind::PlaceAdded,
+        &place.id,
+        place.location.pos,
+        &place.tags,
+    )?;
+    let all_categories = connections.shared()?.all_categories()?;
+    notify.place_added(&recipients, place, all_categories);
+    Ok(())
+}
+
+fn notify_place_updated(
+    connections: &Connections,
+    notify: &dyn NotificationGateway,
+    place: &Place,
+) -> Result<()> {
+    // Changes for digests are queued as a side effect
+    let recipients = usecases::recipients_of_change(
+        &*connections.exclusive()?,
+        SubscriptionChangeKind::PlaceUpdated,
+        &place.id,
+        place.location.pos,
+        &place.tags,
+    )?;
+    let all_categories = connections.shared()?.all_categories()?;
+    notify.place_updated(&recipients, place, all_categories);
+    Ok(())
+}
+
+fn notify_event_created(
+    connections: &Connections,
+    notify: &dyn NotificationGateway,
+    event: &Event,
+) -> Result<()> {
+    if let Some(ref location) = event.location {
+        let recipients = usecases::recipients_of_change(
+            &*connections.exclusive()?,
+            SubscriptionChangeKind::EventCreated,
+            &event.id,
+            location.pos,
+            &event.tags,
+        )?;
+        notify.event_created(&recipients, event);
+    }
+    Ok(())
+}
+
+fn notify_event_updated(
+    connections: &Connections,
+    notify: &dyn NotificationGateway,
+    event: &Event,
+) -> Result<()> {
+    if let Some(ref location) = event.location {
+        let recipients = usecases::recipients_of_change(
+            &*connections.exclusive()?,
+            SubscriptionChangeKind::EventUpdated,
+            &event.id,
+            location.pos,
+            &event.tags,
+        )?;
+        notify.event_updated(&recipients, event);
+    }
+    Ok(())
+}
+
+// Starts background threads that execute all pending jobs. Without
+// any running workers jobs are executed immediately after they have
+// been enqueued.
+pub fn spawn_job_workers<N>(
+    connections: &Connections,
+    search_engine: &tantivy::SearchEngine,
+    notify: Arc<N>,
+    count: usize,
+) -> Result<()>
+where
+    N: Deref<Target = dyn NotificationGateway> + Send + Sync + 'static,
+{
+    for i in 0..count {
+        let connections = connections.clone();
+        let mut search_engine = search_engine.clone();
+        let notify = Arc::clone(&notify);
+        thread::Builder::new()
+            .name(format!("job-worker-{}", i))
+            .spawn(move || loop {
+                match run_next_job(&connections, &mut search_engine, Some(&**notify)) {
+                    Ok(true) => {}
+                    Ok(false) => wait_for_pending_jobs(JOB_POLL_INTERVAL),
+                    Err(err) => {
+                        error!("Failed to run next job: {}", err);
+                        wait_for_pending_jobs(JOB_POLL_INTERVAL);
+                    }
+                }
+            })?;
+        RUNNING_JOB_WORKERS.fetch_add(1, Ordering::AcqRel);
+    }
+    info!("Started {} background job worker(s)", count);
+    // Pick up jobs that have been left over since the last run
+    wake_up_job_workers();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::prelude::*;
+    use super::*;
+
+    #[test]
+    fn should_reschedule_failed_jobs() {
+        let fixture = BackendFixture::new();
+        let jobs = [Job::NotifyPlaceAdded {
+            id: "missing".into(),
+        }];
+        let job_ids = enqueue_jobs(&*fixture.db_connections.exclusive().unwrap(), &jobs).unwrap();
+        dispatch_jobs(
+            &fixture.db_connections,
+            &mut *fixture.search_engine.borrow_mut(),
+            None,
+            &job_ids,
+        );
+
+        let now = TimestampMs::now();
+        let connection = fixture.db_connections.exclusive().unwrap();
+        assert!(connection
+            .claim_next_job(now, add_duration(now, JOB_LEASE_DURATION))
+            .unwrap()
+            .is_none());
+        let later = add_duration(now, retry_delay(1));
+        let job = connection
+            .claim_next_job(later, add_duration(later, JOB_LEASE_DURATION))
+            .unwrap()
+            .unwrap();
+        assert_eq!(job_ids[0], job.id);
+        assert_eq!(2, job.attempts);
+        assert_eq!(jobs[0], serde_json::from_str::<Job>(&job.payload).unwrap());
+    }
+
+    #[test]
+    fn should_complete_jobs_without_workers() {
+        let fixture = BackendFixture::new();
+        fixture.create_place(0.into(), None);
+
+        let now = TimestampMs::now();
+        assert!(fixture
+            .db_connections
+            .exclusive()
+            .unwrap()
+            .claim_next_job(now, add_duration(now, JOB_LEASE_DURATION))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn should_strip_event_details_for_webhooks_of_other_organizations() {
+        let fixture = BackendFixture::new();
+        fixture.create_user(
+            usecases::NewUser {
+                email: "owner@example.com".into(),
+                password: "123456".into(),
+                locale: None,
+            },
+            None,
+        );
+        let mut connection = fixture.db_connections.exclusive().unwrap();
+        let mut webhook_ids = vec![];
+        for (org_id, moderated_tags) in &[("owner", vec!["foo".into()]), ("other", vec![])] {
+            connection
+                .create_org(Organization {
+                    id: (*org_id).into(),
+                    name: (*org_id).into(),
+                    moderated_tags: moderated_tags.clone(),
+                })
+                .unwrap();
+            let webhook = Webhook {
+                id: Id::new(),
+                org_id: (*org_id).into(),
+                url: "https://example.com/hook".parse().unwrap(),
+                secret: "0123456789abcdef".into(),
+                event_types: vec![WebhookEventType::EventCreated],
+                bbox: None,
+                tags: vec![],
+                created_at: TimestampMs::now(),
+            };
+            connection.create_webhook(&webhook).unwrap();
+            webhook_ids.push(webhook.id);
+        }
+        let event = Event {
+            id: Id::new(),
+            title: "foo".into(),
+            description: None,
+            start: chrono::Utc::now().naive_utc(),
+            end: None,
+            location: None,
+            contact: None,
+            tags: vec!["foo".into()],
+            homepage: None,
+            created_by: Some("owner@example.com".into()),
+            registration: None,
+            archived: None,
+            image_url: None,
+            image_link_url: None,
+            image_id: None,
+            recurrence: None,
+            series_id: None,
+        };
+        connection.create_event(event.clone()).unwrap();
+        drop(connection);
+
+        trigger_webhooks(
+            &fixture.db_connections,
+            WebhookEventType::EventCreated,
+            event.id.as_str(),
+            None,
+        )
+        .unwrap();
+
+        let connection = fixture.db_connections.shared().unwrap();
+        let payloads: Vec<serde_json::Value> = webhook_ids
+            .iter()
+            .map(|id| {
+                let deliveries = connection
+                    .load_webhook_deliveries(id, &Pagination::default())
+                    .unwrap();
+                assert_eq!(1, deliveries.len());
+                serde_json::from_str(&deliveries[0].payload).unwrap()
+            })
+            .collect();
+        // Like for the events API the JSON representation never
+        // contains the creator
+        assert!(payloads.iter().all(|p| p["data"]["created_by"].is_null()));
+        assert_eq!(
+            serde_json::json!(json::Event::from(event.clone())),
+            payloads[0]["data"]
+        );
+        assert_eq!(
+            serde_json::json!(json::Event::from(event.strip_activity_details())),
+            payloads[1]["data"]
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use super::{jobs::*, *};
+use ofdb_core::gateways::notify::NotificationGateway;
+
+pub fn merge_places(
+    connections: &Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
+    notify: &dyn NotificationGateway,
+    merge: usecases::MergePlaces,
+    merged_by: &str,
+) -> Result<usecases::MergedPlaces> {
+    let merged_id = merge.merged_id.to_string();
+    let (merged, job_ids) = {
+        let connection = connections.exclusive()?;
+        let mut repo_err = None;
+        connection
+            .transaction::<_, diesel::result::Error, _>(|| {
+                let merged =
+                    usecases::merge_places(&*connection, merge, merged_by).map_err(|err| {
+                        warn!("Failed to merge place {}: {}", merged_id, err);
+                        repo_err = Some(err);
+                        diesel::result::Error::RollbackTransaction
+                    })?;
+                let id = merged.place.id.to_string();
+                let mut jobs = vec![
+                    Job::ReindexPlaces {
+                        ids: vec![id.clone(), merged_id.clone()],
+                    },
+                    Job::NotifyPlaceUpdated { id: id.clone() },
+                    Job::trigger_webhooks(WebhookEventType::PlaceUpdated, id.clone()),
+                ];
+                if !merged.clearance_org_ids.is_empty() {
+                    jobs.push(Job::trigger_clearance_webhooks(
+                        id,
+                        &merged.clearance_org_ids,
+                    ));
+                }
+                let job_ids = enqueue_jobs(&*connection, &jobs).map_err(|err| {
+                    warn!("Failed to enqueue jobs for merged places: {}", err);
+                    diesel::result::Error::RollbackTransaction
+                })?;
+                Ok((merged, job_ids))
+            })
+            .map_err(|err| {
+                if let Some(repo_err) = repo_err {
+                    repo_err
+                } else {
+                    RepoError::from(err).into()
+                }
+            })
+    }?;
+
+    // Reindex both places and send subscription e-mails
+    dispatch_jobs(connections, indexer, Some(notify), &job_ids);
+
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::prelude::*;
+
+    fn merge_places(
+        fixture: &BackendFixture,
+        merge: usecases::MergePlaces,
+    ) -> super::Result<usecases::MergedPlaces> {
+        super::merge_places(
+            &fixture.db_connections,
+            &mut *fixture.search_engine.borrow_mut(),
+            &fixture.notify,
+            merge,
+            "scout@example.com",
+        )
+    }
+
+    #[test]
+    fn should_merge_place_with_ratings_into_survivor() {
+        let fixture = BackendFixture::new();
+        fixture.create_user(
+            usecases::NewUser {
+                email: "scout@example.com".into(),
+                password: "secret".into(),
+                locale: None,
+            },
+            Some(Role::Scout),
+        );
+        let survivor_id = fixture.create_place(0.into(), None);
+        let merged_id = fixture.create_place(1.into(), None);
+        let (rating_id, comment_id) = fixture.create_rating(new_entry_rating(
+            0,
+            &merged_id,
+            RatingContext::Fairness,
+            RatingValue::new(1),
+        ));
+        let merged_tags = fixture.try_get_place(&merged_id).unwrap().0.tags;
+
+        let merge = |version| usecases::MergePlaces {
+            survivor_id: survivor_id.clone().into(),
+            version,
+            merged_id: merged_id.clone().into(),
+            fields_from_merged: vec![usecases::PlaceMergeField::Title],
+            comment: Some("duplicate".into()),
+        };
+        // Revision conflict
+        assert!(merge_places(&fixture, merge(0)).is_err());
+        assert!(fixture.place_exists(&merged_id));
+
+        let merged = merge_places(&fixture, merge(1)).unwrap();
+        assert_eq!(1, merged.moved_rating_count);
+        assert_eq!("Title 1", merged.place.title);
+        assert_ne!(merged_tags, merged.place.tags);
+
+        assert!(!fixture.place_exists(&merged_id));
+        assert!(fixture.query_places_by_tag(&merged_tags[0]).is_empty());
+        let db = fixture.db_connections.shared().unwrap();
+        let rating = db.load_rating(&rating_id).unwrap();
+        assert_eq!(survivor_id, rating.place_id.as_str());
+        assert!(db.load_comment(&comment_id).is_ok());
+        assert_eq!(
+            survivor_id,
+            usecases::resolve_place_redirect(&*db, &merged_id).unwrap()
+        );
+
+        // The merge appears in the history of both places
+        let history = db.get_place_history(&survivor_id, None).unwrap();
+        let (revision, reviews) = history
+            .revisions
+            .iter()
+            .max_by_key(|(r, _)| r.revision)
+            .unwrap();
+        assert_eq!(
+            Some("scout@example.com"),
+            revision.created.by.as_ref().map(|email| email.as_str())
+        );
+        assert!(reviews
+            .iter()
+            .any(|r| r.activity.context.as_deref() == Some(usecases::MERGE_REVIEW_CONTEXT)));
+        let history = db.get_place_history(&merged_id, None).unwrap();
+        let comment = format!("Merged into {}: duplicate", survivor_id);
+        assert!(history.revisions.iter().any(|(_, reviews)| reviews
+            .iter()
+            .any(|r| r.status == ReviewStatus::Archived
+                && r.activity.comment.as_deref() == Some(comment.as_str()))));
+    }
+
+    #[test]
+    fn should_not_merge_place_with_itself() {
+        let fixture = BackendFixture::new();
+        fixture.create_user(
+            usecases::NewUser {
+                email: "scout@example.com".into(),
+                password: "secret".into(),
+                locale: None,
+            },
+            Some(Role::Scout),
+        );
+        let id = fixture.create_place(0.into(), None);
+        let merge = usecases::MergePlaces {
+            survivor_id: id.clone().into(),
+            version: 1,
+            merged_id: id.clone().into(),
+            fields_from_merged: vec![],
+            comment: None,
+        };
+        assert!(merge_places(&fixture, merge).is_err());
+        assert!(fixture.place_exists(&id));
+    }
+}
@@ -5,22 +5,30 @@ mod change_user_role;
 mod create_event;
 mod create_place;
 mod create_rating;
+mod erase_user_data;
+mod import_events;
+mod jobs;
+mod merge_places;
 mod reset_password;
+mod revert_place;
 mod review_places;
+mod subscription_digests;
 mod update_event;
 mod update_place;
+mod update_search_index;
 
 pub mod prelude {
     pub use super::{
         archive_comments::*, archive_events::*, archive_ratings::*, change_user_role::*,
-        create_event::*, create_place::*, create_rating::*, reset_password::*, review_places::*,
-        update_event::*, update_place::*,
+        create_event::*, create_place::*, create_rating::*, erase_user_data::*, import_events::*,
+        jobs::*, merge_places::*, reset_password::*, revert_place::*, review_places::*,
+        subscription_digests::*, update_event::*, update_place::*, update_search_index::*,
     };
 }
 
 pub type Result<T> = std::result::Result<T, error::AppError>;
 
-pub(crate) use super::{db::sqlite, error};
+pub(crate) use super::{db::Connections, error};
 pub(crate) use crate::core::{prelude::*, usecases};
 
 #[cfg(test)]
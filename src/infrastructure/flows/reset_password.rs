@@ -1,9 +1,8 @@
 use super::*;
 use crate::core::error::Error;
-use diesel::connection::Connection;
 use ofdb_core::gateways::notify::NotificationGateway;
 
-fn refresh_user_token(connections: &sqlite::Connections, user: &User) -> Result<EmailNonce> {
+fn refresh_user_token(connections: &Connections, user: &User) -> Result<EmailNonce> {
     let mut rollback_err: Option<Error> = None;
     let connection = connections.exclusive()?;
     Ok(connection
@@ -17,7 +16,7 @@ fn refresh_user_token(connections: &sqlite::Connections, user: &User) -> Result<
 }
 
 pub fn reset_password_request(
-    connections: &sqlite::Connections,
+    connections: &Connections,
     notify: &dyn NotificationGateway,
     email: &str,
 ) -> Result<EmailNonce> {
@@ -26,12 +25,12 @@ pub fn reset_password_request(
     // writing.
     let user = connections.shared()?.get_user_by_email(email)?;
     let email_nonce = refresh_user_token(&connections, &user)?;
-    notify.user_reset_password_requested(&email_nonce);
+    notify.user_reset_password_requested(&email_nonce, user.locale.as_ref());
     Ok(email_nonce)
 }
 
 pub fn reset_password_with_email_nonce(
-    connections: &sqlite::Connections,
+    connections: &Connections,
     email_nonce: EmailNonce,
     new_password: Password,
 ) -> Result<()> {
@@ -108,6 +107,7 @@ mod tests {
             usecases::NewUser {
                 email: email1.to_string(),
                 password: "old pass1".to_string(),
+                locale: None,
             },
             None,
         );
@@ -122,6 +122,7 @@ mod tests {
             usecases::NewUser {
                 email: email2.to_string(),
                 password: "old pass2".to_string(),
+                locale: None,
             },
             None,
         );
@@ -0,0 +1,136 @@
+use super::{jobs::*, *};
+use ofdb_core::gateways::notify::NotificationGateway;
+
+pub fn revert_place(
+    connections: &Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
+    notify: &dyn NotificationGateway,
+    revert: usecases::RevertPlace,
+    reverted_by: &str,
+) -> Result<usecases::RevertedPlace> {
+    let id = revert.id.to_string();
+    let (reverted, job_ids) = {
+        let connection = connections.exclusive()?;
+        let mut repo_err = None;
+        connection
+            .transaction::<_, diesel::result::Error, _>(|| {
+                let reverted =
+                    usecases::revert_place(&*connection, revert, reverted_by).map_err(|err| {
+                        warn!("Failed to revert place {}: {}", id, err);
+                        repo_err = Some(err);
+                        diesel::result::Error::RollbackTransaction
+                    })?;
+                let mut jobs = vec![
+                    Job::ReindexPlaces {
+                        ids: vec![id.clone()],
+                    },
+                    Job::NotifyPlaceUpdated { id: id.clone() },
+                    Job::trigger_webhooks(WebhookEventType::PlaceUpdated, id.clone()),
+                ];
+                if !reverted.clearance_org_ids.is_empty() {
+                    jobs.push(Job::trigger_clearance_webhooks(
+                        id.clone(),
+                        &reverted.clearance_org_ids,
+                    ));
+                }
+                let job_ids = enqueue_jobs(&*connection, &jobs).map_err(|err| {
+                    warn!("Failed to enqueue jobs for reverted place: {}", err);
+                    diesel::result::Error::RollbackTransaction
+                })?;
+                Ok((reverted, job_ids))
+            })
+            .map_err(|err| {
+                if let Some(repo_err) = repo_err {
+                    repo_err
+                } else {
+                    RepoError::from(err).into()
+                }
+            })
+    }?;
+
+    // Reindex the place and send subscription e-mails
+    dispatch_jobs(connections, indexer, Some(notify), &job_ids);
+
+    Ok(reverted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::prelude::*;
+
+    fn revert_place(
+        fixture: &BackendFixture,
+        revert: usecases::RevertPlace,
+    ) -> super::Result<usecases::RevertedPlace> {
+        super::revert_place(
+            &fixture.db_connections,
+            &mut *fixture.search_engine.borrow_mut(),
+            &fixture.notify,
+            revert,
+            "scout@example.com",
+        )
+    }
+
+    #[test]
+    fn should_revert_place_to_earlier_revision() {
+        let fixture = BackendFixture::new();
+        fixture.create_user(
+            usecases::NewUser {
+                email: "scout@example.com".into(),
+                password: "secret".into(),
+                locale: None,
+            },
+            Some(Role::Scout),
+        );
+        let id = fixture.create_place(0.into(), None);
+        let (original, _) = fixture.try_get_place(&id).unwrap();
+        let mut vandalized = usecases::UpdatePlace::from(original.clone());
+        vandalized.version = 1;
+        vandalized.title = "Spam".into();
+        vandalized.tags = vec!["spam".into()];
+        flows::update_place(
+            &fixture.db_connections,
+            &mut *fixture.search_engine.borrow_mut(),
+            &fixture.notify,
+            id.clone().into(),
+            vandalized,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(1, fixture.query_places_by_tag("spam").len());
+
+        let revert = |revision: u64, version| usecases::RevertPlace {
+            id: id.clone().into(),
+            revision: revision.into(),
+            version,
+            comment: Some("vandalism".into()),
+        };
+        // Only earlier revisions can be restored
+        assert!(revert_place(&fixture, revert(1, 2)).is_err());
+        // Revision conflict
+        assert!(revert_place(&fixture, revert(0, 1)).is_err());
+
+        let reverted = revert_place(&fixture, revert(0, 2)).unwrap();
+        assert_eq!(Revision::from(2), reverted.place.revision);
+        assert_eq!(original.title, reverted.place.title);
+        assert_eq!(original.tags, reverted.place.tags);
+        assert!(fixture.query_places_by_tag("spam").is_empty());
+        assert_eq!(1, fixture.query_places_by_tag(&original.tags[0]).len());
+
+        let db = fixture.db_connections.shared().unwrap();
+        let history = db.get_place_history(&id, None).unwrap();
+        let (revision, reviews) = history
+            .revisions
+            .iter()
+            .max_by_key(|(r, _)| r.revision)
+            .unwrap();
+        assert_eq!(
+            Some("scout@example.com"),
+            revision.created.by.as_ref().map(|email| email.as_str())
+        );
+        assert!(reviews.iter().any(|r| r.activity.context.as_deref()
+            == Some(usecases::REVERT_REVIEW_CONTEXT)
+            && r.activity.comment.as_deref() == Some("Reverted to revision 0: vandalism")));
+    }
+}
@@ -1,21 +1,27 @@
-use super::*;
-
-use diesel::connection::Connection;
+use super::{jobs::*, *};
 
 fn exec_review_places(
-    connections: &sqlite::Connections,
+    connections: &Connections,
     ids: &[&str],
     review: usecases::Review,
-) -> Result<usize> {
+) -> Result<(usize, Vec<Id>)> {
     let mut repo_err = None;
     let connection = connections.exclusive()?;
     Ok(connection
         .transaction::<_, diesel::result::Error, _>(|| {
-            usecases::review_places(&*connection, ids, review).map_err(|err| {
+            let count = usecases::review_places(&*connection, ids, review).map_err(|err| {
                 warn!("Failed to review {} places: {}", ids.len(), err);
                 repo_err = Some(err);
                 diesel::result::Error::RollbackTransaction
-            })
+            })?;
+            let jobs = [Job::ReindexPlaces {
+                ids: ids.iter().map(|id| (*id).to_owned()).collect(),
+            }];
+            let job_ids = enqueue_jobs(&*connection, &jobs).map_err(|err| {
+                warn!("Failed to enqueue jobs for reviewed places: {}", err);
+                diesel::result::Error::RollbackTransaction
+            })?;
+            Ok((count, job_ids))
         })
         .map_err(|err| {
             if let Some(repo_err) = repo_err {
@@ -26,50 +32,15 @@ fn exec_review_places(
         })?)
 }
 
-fn post_review_places(
-    connections: &sqlite::Connections,
-    indexer: &mut dyn PlaceIndexer,
-    ids: &[&str],
-) -> Result<()> {
-    let db = connections.shared()?;
-    let places_with_status = db.get_places(ids)?;
-    for (place, status) in places_with_status {
-        let ratings = match db.load_ratings_of_place(place.id.as_str()) {
-            Ok(ratings) => ratings,
-            Err(err) => {
-                log::error!(
-                    "Failed to load ratings of place {} after reviewing: {}",
-                    place.id,
-                    err
-                );
-                continue;
-            }
-        };
-        if let Err(err) = usecases::reindex_place(indexer, &place, status, &ratings) {
-            error!(
-                "Failed to (re-)index place {} after reviewing: {}",
-                place.id, err
-            );
-        }
-    }
-    if let Err(err) = indexer.flush_index() {
-        error!(
-            "Failed to flush search index after reviewing places: {}",
-            err
-        );
-    }
-    Ok(())
-}
-
 pub fn review_places(
-    connections: &sqlite::Connections,
-    indexer: &mut dyn PlaceIndexer,
+    connections: &Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
     ids: &[&str],
     review: usecases::Review,
 ) -> Result<usize> {
-    let count = exec_review_places(connections, ids, review)?;
-    // TODO: Move post processing to a separate task/thread that doesn't delay this request?
-    post_review_places(connections, indexer, ids)?;
+    let (count, job_ids) = exec_review_places(connections, ids, review)?;
+    // Reindex reviewed places
+    dispatch_jobs(connections, indexer, None, &job_ids);
     Ok(count)
 }
 
@@ -107,6 +78,7 @@ mod tests {
             usecases::NewUser {
                 email: "test@example.com".into(),
                 password: "test123".into(),
+                locale: None,
             },
             None,
         );
@@ -231,6 +203,7 @@ mod tests {
             usecases::NewUser {
                 email: "test@example.com".into(),
                 password: "test123".into(),
+                locale: None,
             },
             None,
         );
@@ -293,4 +266,62 @@ mod tests {
         assert!(fixture.comment_exists(&rating_comment_ids[2].1));
         assert!(fixture.comment_exists(&rating_comment_ids[3].1));
     }
+
+    #[test]
+    fn should_record_previous_review_status_in_audit_log() {
+        let fixture = BackendFixture::new();
+
+        fixture.create_user(
+            usecases::NewUser {
+                email: "test@example.com".into(),
+                password: "test123".into(),
+                locale: None,
+            },
+            None,
+        );
+
+        let place_ids = vec![
+            fixture.create_place(0.into(), None),
+            fixture.create_place(1.into(), None),
+        ];
+        let confirmed = usecases::Review {
+            status: ReviewStatus::Confirmed,
+            ..archived_by("test@example.com")
+        };
+        assert_eq!(
+            1,
+            review_places(&fixture, &[&*place_ids[0]], confirmed).unwrap()
+        );
+        assert_eq!(
+            2,
+            review_places(
+                &fixture,
+                &[&*place_ids[0], &*place_ids[1]],
+                archived_by("test@example.com"),
+            )
+            .unwrap()
+        );
+
+        let query = AuditLogQuery {
+            action: Some(AuditAction::ReviewPlaces),
+            ..Default::default()
+        };
+        let mut entries = fixture
+            .db_connections
+            .shared()
+            .unwrap()
+            .query_audit_log(&query, &Default::default())
+            .unwrap();
+        entries.sort_by(|a, b| (&a.after, &a.before).cmp(&(&b.after, &b.before)));
+        assert_eq!(3, entries.len());
+        assert_eq!(Some("archived"), entries[0].after.as_deref());
+        assert_eq!(Some("confirmed"), entries[0].before.as_deref());
+        assert_eq!(vec![place_ids[0].clone()], entries[0].target_ids);
+        assert_eq!(Some("archived"), entries[1].after.as_deref());
+        assert_eq!(Some("created"), entries[1].before.as_deref());
+        assert_eq!(vec![place_ids[1].clone()], entries[1].target_ids);
+        assert_eq!(Some("confirmed"), entries[2].after.as_deref());
+        assert_eq!(Some("created"), entries[2].before.as_deref());
+        assert_eq!(vec![place_ids[0].clone()], entries[2].target_ids);
+    }
 }
@@ -0,0 +1,161 @@
+use super::*;
+use ofdb_core::gateways::notify::{DigestItem, NotificationGateway};
+use std::{ops::Deref, sync::Arc, thread, time::Duration};
+
+// Digests are due at most once per day and a delay of
+// a few minutes doesn't matter.
+const DIGEST_POLL_INTERVAL: Duration = Duration::from_secs(600);
+
+fn load_digest_item(db: &dyn Db, change: &PendingSubscriptionChange) -> Result<Option<DigestItem>> {
+    let id = change.item_id.as_str();
+    let item = match change.kind {
+        SubscriptionChangeKind::PlaceAdded | SubscriptionChangeKind::PlaceUpdated => {
+            match db.get_place(id) {
+                Ok((place, _)) if change.kind == SubscriptionChangeKind::PlaceAdded => {
+                    DigestItem::PlaceAdded(place)
+                }
+                Ok((place, _)) => DigestItem::PlaceUpdated(place),
+                Err(RepoError::NotFound) => return Ok(None),
+                Err(err) => return Err(err.into()),
+            }
+        }
+        SubscriptionChangeKind::EventCreated | SubscriptionChangeKind::EventUpdated => {
+            match db.get_event(id) {
+                Ok(event) if change.kind == SubscriptionChangeKind::EventCreated => {
+                    DigestItem::EventCreated(event)
+                }
+                Ok(event) => DigestItem::EventUpdated(event),
+                Err(RepoError::NotFound) => return Ok(None),
+                Err(err) => return Err(err.into()),
+            }
+        }
+    };
+    Ok(Some(item))
+}
+
+/// Sends all digests that are due and returns their number.
+///
+/// Changes of items that have been deleted in the meantime
+/// are silently dropped.
+pub fn send_subscription_digests(
+    connections: &Connections,
+    notify: &dyn NotificationGateway,
+    now: TimestampMs,
+) -> Result<usize> {
+    let digests = usecases::due_subscription_digests(&*connections.shared()?, now)?;
+    let mut count = 0;
+    for digest in digests {
+        let items = {
+            let db = connections.shared()?;
+            let mut items = Vec::with_capacity(digest.changes.len());
+            for change in &digest.changes {
+                if let Some(item) = load_digest_item(&*db, change)? {
+                    items.push(item);
+                }
+            }
+            items
+        };
+        if !items.is_empty() {
+            notify.subscription_digest(&digest.recipient, &items);
+            count += 1;
+        }
+        usecases::complete_subscription_digest(&*connections.exclusive()?, &digest, now)?;
+    }
+    Ok(count)
+}
+
+/// Starts a background thread that periodically sends
+/// all subscription digests that are due.
+pub fn spawn_subscription_digest_scheduler<N>(
+    connections: &Connections,
+    notify: Arc<N>,
+) -> Result<()>
+where
+    N: Deref<Target = dyn NotificationGateway> + Send + Sync + 'static,
+{
+    let connections = connections.clone();
+    thread::Builder::new()
+        .name("subscription-digests".to_string())
+        .spawn(move || loop {
+            match send_subscription_digests(&connections, &**notify, TimestampMs::now()) {
+                Ok(0) => {}
+                Ok(count) => info!("Sent {} subscription digest(s)", count),
+                Err(err) => error!("Failed to send subscription digests: {}", err),
+            }
+            thread::sleep(DIGEST_POLL_INTERVAL);
+        })?;
+    info!("Started subscription digest scheduler");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::prelude::*;
+
+    #[test]
+    fn should_send_due_digests_only_once() {
+        let fixture = BackendFixture::new();
+        fixture.create_user(
+            usecases::NewUser {
+                email: "user@bar.tld".into(),
+                password: "123456".into(),
+                locale: None,
+            },
+            None,
+        );
+        let subscription = usecases::create_bbox_subscription(
+            &*fixture.db_connections.exclusive().unwrap(),
+            "user@bar.tld",
+            usecases::NewBboxSubscription {
+                bbox: MapBbox::new(
+                    MapPoint::from_lat_lng_deg(-10.0, -10.0),
+                    MapPoint::from_lat_lng_deg(10.0, 10.0),
+                ),
+                locale: None,
+                tags: vec![],
+                categories: vec![],
+                events: false,
+                delivery: Some("daily".into()),
+            },
+        )
+        .unwrap();
+        fixture.create_place(0.into(), None);
+        fixture.create_place(1.into(), None);
+        let pending = fixture
+            .db_connections
+            .shared()
+            .unwrap()
+            .load_pending_subscription_changes(&subscription.id)
+            .unwrap();
+        assert_eq!(2, pending.len());
+
+        let now = TimestampMs::now();
+        assert_eq!(
+            0,
+            flows::send_subscription_digests(&fixture.db_connections, &fixture.notify, now)
+                .unwrap()
+        );
+        let tomorrow = TimestampMs::from_inner(now.into_inner() + 24 * 60 * 60 * 1000);
+        assert_eq!(
+            1,
+            flows::send_subscription_digests(&fixture.db_connections, &fixture.notify, tomorrow)
+                .unwrap()
+        );
+        assert_eq!(
+            0,
+            flows::send_subscription_digests(&fixture.db_connections, &fixture.notify, tomorrow)
+                .unwrap()
+        );
+        let db = fixture.db_connections.shared().unwrap();
+        assert!(db
+            .load_pending_subscription_changes(&subscription.id)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            Some(tomorrow),
+            db.get_bbox_subscription(&subscription.id)
+                .unwrap()
+                .last_digest_at
+        );
+    }
+}
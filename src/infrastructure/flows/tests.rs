@@ -1,9 +1,7 @@
 pub mod prelude {
     pub use crate::core::{prelude::*, usecases};
 
-    pub mod sqlite {
-        pub use super::super::super::sqlite::*;
-    }
+    pub use crate::infrastructure::db::Connections;
 
     pub mod tantivy {
         pub use crate::infrastructure::db::tantivy::SearchEngine;
@@ -14,27 +12,26 @@ pub mod prelude {
         ports::web::api,
     };
 
-    use crate::ports::web::tests::DummyNotifyGW;
+    use crate::{infrastructure::db::tests::TestDatabase, ports::web::tests::DummyNotifyGw};
 
     use std::cell::RefCell;
 
-    embed_migrations!();
-
     pub struct BackendFixture {
-        pub db_connections: sqlite::Connections,
+        pub db_connections: Connections,
         pub search_engine: RefCell<tantivy::SearchEngine>,
-        pub notify: DummyNotifyGW,
+        pub notify: DummyNotifyGw,
+        _db: TestDatabase,
     }
 
     impl BackendFixture {
         pub fn new() -> Self {
-            let db_connections = sqlite::Connections::init(":memory:", 1).unwrap();
-            embedded_migrations::run(&*db_connections.exclusive().unwrap()).unwrap();
+            let db = TestDatabase::new();
             let search_engine = tantivy::SearchEngine::init_in_ram().unwrap();
             Self {
-                db_connections,
+                db_connections: db.connections.clone(),
                 search_engine: RefCell::new(search_engine),
-                notify: DummyNotifyGW,
+                notify: DummyNotifyGw,
+                _db: db,
             }
         }
 
@@ -184,7 +181,7 @@ pub mod prelude {
             usecases::NewPlace {
                 lat: pos.lat().to_deg(),
                 lng: pos.lng().to_deg(),
-                title: title,
+                title,
                 description,
                 categories,
                 tags,
@@ -202,6 +199,7 @@ pub mod prelude {
                 founded_on: None,
                 image_url: None,
                 image_link_url: None,
+                image_id: None,
                 custom_links: custom_links.into_iter().map(Into::into).collect(),
             }
         }
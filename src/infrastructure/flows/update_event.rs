@@ -1,25 +1,25 @@
-use super::*;
+use super::{jobs::*, *};
 use crate::core::error::RepoError;
-use diesel::Connection;
+use chrono::NaiveDateTime;
 use ofdb_core::gateways::notify::NotificationGateway;
 
 pub fn update_event(
-    connections: &sqlite::Connections,
-    indexer: &mut dyn EventIndexer,
+    connections: &Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
     notify: &dyn NotificationGateway,
-    token: Option<&str>,
+    org_id: Option<&Id>,
     id: Id,
     new_event: usecases::NewEvent,
 ) -> Result<Event> {
     // Create and add new event
-    let event = {
+    let (event, job_ids) = {
         let connection = connections.exclusive()?;
         let mut prepare_err = None;
         connection
             .transaction::<_, diesel::result::Error, _>(|| {
                 match usecases::import_new_event(
                     &*connection,
-                    token,
+                    org_id,
                     new_event,
                     usecases::NewEventMode::Update(id.as_str()),
                 ) {
@@ -30,7 +30,19 @@ pub fn update_event(
                                 diesel::result::Error::RollbackTransaction
                             },
                         )?;
-                        Ok(event)
+                        let id = event.id.to_string();
+                        let jobs = [
+                            Job::ReindexEvents {
+                                ids: vec![id.clone()],
+                            },
+                            Job::NotifyEventUpdated { id: id.clone() },
+                            Job::trigger_webhooks(WebhookEventType::EventUpdated, id),
+                        ];
+                        let job_ids = enqueue_jobs(&*connection, &jobs).map_err(|err| {
+                            warn!("Failed to enqueue jobs for updated event: {}", err);
+                            diesel::result::Error::RollbackTransaction
+                        })?;
+                        Ok((event, job_ids))
                     }
                     Err(err) => {
                         prepare_err = Some(err);
@@ -47,35 +59,85 @@ pub fn update_event(
             })
     }?;
 
-    // Index newly added event
-    // TODO: Move to a separate task/thread that doesn't delay this request
-    if let Err(err) = usecases::index_event(indexer, &event).and_then(|_| indexer.flush_index()) {
-        error!("Failed to re-index updated event {}: {}", event.id, err);
-    }
-
-    // Send subscription e-mails
-    // TODO: Move to a separate task/thread that doesn't delay this request
-    if let Err(err) = notify_event_updated(connections, notify, &event) {
-        error!(
-            "Failed to send notifications for updated event {}: {}",
-            event.id, err
-        );
-    }
+    // Reindex updated event and send subscription e-mails
+    dispatch_jobs(connections, indexer, Some(notify), &job_ids);
 
     Ok(event)
 }
 
-fn notify_event_updated(
-    connections: &sqlite::Connections,
+/// Replaces a single occurrence of a recurring event by a new
+/// event that refers to the series. The occurrence is excluded
+/// from the series.
+pub fn update_event_occurrence(
+    connections: &Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
     notify: &dyn NotificationGateway,
-    event: &Event,
-) -> Result<()> {
-    if let Some(ref location) = event.location {
-        let email_addresses = {
-            let conn = connections.shared()?;
-            usecases::email_addresses_by_coordinate(&*conn, location.pos)?
-        };
-        notify.event_updated(&email_addresses, event);
-    }
-    Ok(())
+    org_id: Option<&Id>,
+    series_id: Id,
+    occurrence: NaiveDateTime,
+    new_event: usecases::NewEvent,
+) -> Result<Event> {
+    let (event, job_ids) = {
+        let connection = connections.exclusive()?;
+        let mut prepare_err = None;
+        connection
+            .transaction::<_, diesel::result::Error, _>(|| {
+                let storable = usecases::import_new_event(
+                    &*connection,
+                    org_id,
+                    new_event,
+                    usecases::NewEventMode::UpdateOccurrence(series_id.as_str()),
+                )
+                .and_then(|storable| {
+                    usecases::exclude_event_occurrence(
+                        &*connection,
+                        series_id.as_str(),
+                        occurrence,
+                    )?;
+                    Ok(storable)
+                });
+                match storable {
+                    Ok(storable) => {
+                        let event = usecases::store_created_event(&*connection, storable).map_err(
+                            |err| {
+                                warn!("Failed to store updated event occurrence: {}", err);
+                                diesel::result::Error::RollbackTransaction
+                            },
+                        )?;
+                        let id = event.id.to_string();
+                        let jobs = [
+                            Job::ReindexEvents {
+                                ids: vec![series_id.to_string(), id.clone()],
+                            },
+                            Job::NotifyEventUpdated { id: id.clone() },
+                            Job::trigger_webhooks(WebhookEventType::EventUpdated, id),
+                        ];
+                        let job_ids = enqueue_jobs(&*connection, &jobs).map_err(|err| {
+                            warn!(
+                                "Failed to enqueue jobs for updated event occurrence: {}",
+                                err
+                            );
+                            diesel::result::Error::RollbackTransaction
+                        })?;
+                        Ok((event, job_ids))
+                    }
+                    Err(err) => {
+                        prepare_err = Some(err);
+                        Err(diesel::result::Error::RollbackTransaction)
+                    }
+                }
+            })
+            .map_err(|err| {
+                if let Some(err) = prepare_err {
+                    err
+                } else {
+                    RepoError::from(err).into()
+                }
+            })
+    }?;
+
+    // Reindex the series and the detached occurrence and send subscription e-mails
+    dispatch_jobs(connections, indexer, Some(notify), &job_ids);
+
+    Ok(event)
 }
@@ -1,10 +1,9 @@
-use super::*;
-use diesel::connection::Connection;
+use super::{jobs::*, *};
 use ofdb_core::gateways::notify::NotificationGateway;
 
 pub fn update_place(
-    connections: &sqlite::Connections,
-    indexer: &mut dyn PlaceIndexer,
+    connections: &Connections,
+    indexer: &mut dyn EventAndPlaceIndexer,
     notify: &dyn NotificationGateway,
     id: Id,
     update_place: usecases::UpdatePlace,
@@ -12,7 +11,7 @@ pub fn update_place(
     created_by_org: Option<&Organization>,
 ) -> Result<Place> {
     // Update existing entry
-    let (place, ratings) = {
+    let (place, job_ids) = {
         let connection = connections.exclusive()?;
         let mut prepare_err = None;
         connection
@@ -25,14 +24,28 @@ pub fn update_place(
                     created_by_org,
                 ) {
                     Ok(storable) => {
-                        let (place, ratings) =
-                            usecases::store_updated_place(&*connection, storable).map_err(
-                                |err| {
-                                    warn!("Failed to store updated place: {}", err);
-                                    diesel::result::Error::RollbackTransaction
-                                },
-                            )?;
-                        Ok((place, ratings))
+                        let clearance_org_ids = storable.clearance_org_ids().to_vec();
+                        let (place, _) = usecases::store_updated_place(&*connection, storable)
+                            .map_err(|err| {
+                                warn!("Failed to store updated place: {}", err);
+                                diesel::result::Error::RollbackTransaction
+                            })?;
+                        let id = place.id.to_string();
+                        let mut jobs = vec![
+                            Job::ReindexPlaces {
+                                ids: vec![id.clone()],
+                            },
+                            Job::NotifyPlaceUpdated { id: id.clone() },
+                            Job::trigger_webhooks(WebhookEventType::PlaceUpdated, id.clone()),
+                        ];
+                        if !clearance_org_ids.is_empty() {
+                            jobs.push(Job::trigger_clearance_webhooks(id, &clearance_org_ids));
+                        }
+                        let job_ids = enqueue_jobs(&*connection, &jobs).map_err(|err| {
+                            warn!("Failed to enqueue jobs for updated place: {}", err);
+                            diesel::result::Error::RollbackTransaction
+                        })?;
+                        Ok((place, job_ids))
                     }
                     Err(err) => {
                         prepare_err = Some(err);
@@ -49,38 +62,8 @@ pub fn update_place(
             })
     }?;
 
-    // Reindex updated place
-    // TODO: Move to a separate task/thread that doesn't delay this request
-    if let Err(err) = usecases::reindex_place(indexer, &place, ReviewStatus::Created, &ratings)
-        .and_then(|_| indexer.flush_index())
-    {
-        error!("Failed to reindex updated place {}: {}", place.id, err);
-    }
-
-    // Send subscription e-mails
-    // TODO: Move to a separate task/thread that doesn't delay this request
-    if let Err(err) = notify_place_updated(connections, notify, &place) {
-        error!(
-            "Failed to send notifications for updated place {}: {}",
-            place.id, err
-        );
-    }
+    // Reindex updated place and send subscription e-mails
+    dispatch_jobs(connections, indexer, Some(notify), &job_ids);
 
     Ok(place)
 }
-
-fn notify_place_updated(
-    connections: &sqlite::Connections,
-    notify: &dyn NotificationGateway,
-    place: &Place,
-) -> Result<()> {
-    let (email_addresses, all_categories) = {
-        let connection = connections.shared()?;
-        let email_addresses =
-            usecases::email_addresses_by_coordinate(&*connection, place.location.pos)?;
-        let all_categories = connection.all_categories()?;
-        (email_addresses, all_categories)
-    };
-    notify.place_updated(&email_addresses, &place, all_categories);
-    Ok(())
-}
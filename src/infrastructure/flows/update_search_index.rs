@@ -0,0 +1,101 @@
+use super::*;
+use crate::infrastructure::db::tantivy;
+
+// Limits the time that both the database and the search index
+// are blocked while (re-)building the index
+const CHUNK_SIZE: usize = 500;
+
+// Brings the search index up to date with the database. A new or
+// rebuilt index is filled with all places and events. Otherwise
+// only those that have been modified after the high-water mark
+// of the index are reindexed.
+//
+// Safe to be invoked while serving requests: Each chunk is loaded
+// and indexed while holding both the database connection and the
+// index lock. Concurrent modifications cannot be overwritten by
+// stale contents, because they are committed before and indexed
+// after the chunk.
+//
+// Hard deletions of events that happened while the index was not
+// in use are not detected.
+pub fn update_search_index(
+    connections: &Connections,
+    search_engine: &mut tantivy::SearchEngine,
+) -> Result<()> {
+    // Capture the new high-water mark before loading anything
+    let high_water_mark = TimestampMs::now();
+    let (place_ids, event_ids) = {
+        let connection = connections.shared()?;
+        match search_engine.high_water_mark() {
+            Some(since) => {
+                info!("Updating search index with changes since {}", since);
+                (
+                    connection.place_ids_changed_since(since)?,
+                    connection.event_ids_changed_since(since)?,
+                )
+            }
+            None => {
+                info!("Building search index from scratch");
+                (connection.all_place_ids()?, connection.all_event_ids()?)
+            }
+        }
+    };
+
+    info!("Indexing {} place(s)...", place_ids.len());
+    for chunk in place_ids.chunks(CHUNK_SIZE) {
+        let ids: Vec<_> = chunk.iter().map(Id::as_str).collect();
+        let connection = connections.shared()?;
+        let mut indexer = search_engine.lock();
+        usecases::reindex_places(&*connection, &*indexer, &ids)?;
+        indexer.flush_index()?;
+    }
+
+    info!("Indexing {} event(s)...", event_ids.len());
+    for chunk in event_ids.chunks(CHUNK_SIZE) {
+        let ids: Vec<_> = chunk.iter().map(Id::as_str).collect();
+        let connection = connections.shared()?;
+        let mut indexer = search_engine.lock();
+        usecases::reindex_events(&*connection, &*indexer, &ids)?;
+        indexer.flush_index()?;
+    }
+
+    search_engine.flush_index_with_high_water_mark(high_water_mark)?;
+    info!("Search index is up to date");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::prelude::*;
+
+    fn count_places_by_tag(search_engine: &tantivy::SearchEngine, tag: &str) -> usize {
+        let query = IndexQuery {
+            hash_tags: vec![tag.into()],
+            status: Some(vec![]),
+            ..Default::default()
+        };
+        search_engine.query_places(&query, 100).unwrap().len()
+    }
+
+    #[test]
+    fn should_build_and_update_search_index() {
+        let fixture = BackendFixture::new();
+        fixture.create_place(0.into(), None);
+        fixture.create_place(1.into(), None);
+
+        let mut search_engine = tantivy::SearchEngine::init_in_ram().unwrap();
+        assert!(search_engine.high_water_mark().is_none());
+        assert_eq!(0, count_places_by_tag(&search_engine, "tag-0"));
+
+        flows::update_search_index(&fixture.db_connections, &mut search_engine).unwrap();
+        let high_water_mark = search_engine.high_water_mark().unwrap();
+        assert_eq!(1, count_places_by_tag(&search_engine, "tag-0"));
+        assert_eq!(1, count_places_by_tag(&search_engine, "tag-1"));
+
+        fixture.create_place(2.into(), None);
+        assert_eq!(0, count_places_by_tag(&search_engine, "tag-2"));
+        flows::update_search_index(&fixture.db_connections, &mut search_engine).unwrap();
+        assert!(search_engine.high_water_mark().unwrap() >= high_water_mark);
+        assert_eq!(1, count_places_by_tag(&search_engine, "tag-2"));
+    }
+}
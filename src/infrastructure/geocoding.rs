@@ -0,0 +1,288 @@
+use super::db::Connections;
+use crate::core::{
+    db::{GeoCodingCacheEntry, GeoCodingCacheRepo},
+    prelude::*,
+};
+use chrono::Duration;
+use ofdb_core::gateways::geocode::{GeoCodingGateway, GeoCodingResult};
+
+#[derive(Debug, Clone, Copy)]
+pub struct GeoCodingCacheTtl {
+    pub resolved: Duration,
+    // Unresolved queries are retried earlier
+    pub unresolved: Duration,
+}
+
+impl Default for GeoCodingCacheTtl {
+    fn default() -> Self {
+        Self {
+            resolved: Duration::days(90),
+            unresolved: Duration::days(7),
+        }
+    }
+}
+
+/// Caches the results of geocoding requests in the database.
+///
+/// Failed requests are never cached, only answers of the
+/// underlying gateway.
+pub struct CachedGeoCodingGateway<'a> {
+    gateway: &'a dyn GeoCodingGateway,
+    connections: &'a Connections,
+    ttl: GeoCodingCacheTtl,
+}
+
+impl<'a> CachedGeoCodingGateway<'a> {
+    pub fn new(
+        gateway: &'a dyn GeoCodingGateway,
+        connections: &'a Connections,
+        ttl: GeoCodingCacheTtl,
+    ) -> Self {
+        Self {
+            gateway,
+            connections,
+            ttl,
+        }
+    }
+
+    fn load(&self, query: &str) -> Option<GeoCodingCacheEntry> {
+        let entry = match self
+            .connections
+            .shared()
+            .map_err(|err| err.to_string())
+            .and_then(|conn| {
+                conn.get_geocoding_cache_entry(query)
+                    .map_err(|err| err.to_string())
+            }) {
+            Ok(entry) => entry?,
+            Err(err) => {
+                warn!("Failed to load cached geocoding result: {}", err);
+                return None;
+            }
+        };
+        let ttl = if entry.pos.is_some() || entry.address.is_some() {
+            self.ttl.resolved
+        } else {
+            self.ttl.unresolved
+        };
+        let expires_at = entry.cached_at.into_milliseconds() + ttl.num_milliseconds();
+        if expires_at <= TimestampMs::now().into_milliseconds() {
+            debug!("Cached geocoding result for '{}' has expired", query);
+            return None;
+        }
+        Some(entry)
+    }
+
+    fn store(&self, entry: GeoCodingCacheEntry) {
+        let res = self
+            .connections
+            .exclusive()
+            .map_err(|err| err.to_string())
+            .and_then(|conn| {
+                conn.put_geocoding_cache_entry(&entry)
+                    .map_err(|err| err.to_string())
+            });
+        if let Err(err) = res {
+            warn!("Failed to cache geocoding result: {}", err);
+        }
+    }
+}
+
+fn forward_query(addr: &Address) -> String {
+    let Address {
+        street,
+        zip,
+        city,
+        country,
+        state,
+    } = addr;
+    let parts: Vec<_> = [street, zip, city, country, state]
+        .iter()
+        .map(|part| {
+            part.as_deref()
+                .unwrap_or_default()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase()
+        })
+        .collect();
+    format!("forward:{}", parts.join("|"))
+}
+
+// Positions are rounded to about 1 m
+fn reverse_query(pos: MapPoint) -> String {
+    let (lat, lng) = pos.to_lat_lng_deg();
+    format!("reverse:{:.5},{:.5}", lat, lng)
+}
+
+impl<'a> GeoCodingGateway for CachedGeoCodingGateway<'a> {
+    fn resolve_address_lat_lng(&self, addr: &Address) -> GeoCodingResult<Option<(f64, f64)>> {
+        if addr.is_empty() {
+            return Ok(None);
+        }
+        let query = forward_query(addr);
+        if let Some(entry) = self.load(&query) {
+            return Ok(entry.pos.map(MapPoint::to_lat_lng_deg));
+        }
+        let resolved = self.gateway.resolve_address_lat_lng(addr)?;
+        let pos = resolved.and_then(|(lat, lng)| MapPoint::try_from_lat_lng_deg(lat, lng).ok());
+        self.store(GeoCodingCacheEntry {
+            query,
+            cached_at: TimestampMs::now(),
+            pos,
+            address: None,
+        });
+        Ok(resolved)
+    }
+
+    fn resolve_pos_address(&self, pos: MapPoint) -> GeoCodingResult<Option<Address>> {
+        if !pos.is_valid() {
+            return Ok(None);
+        }
+        let query = reverse_query(pos);
+        if let Some(entry) = self.load(&query) {
+            return Ok(entry.address);
+        }
+        let address = self.gateway.resolve_pos_address(pos)?;
+        self.store(GeoCodingCacheEntry {
+            query,
+            cached_at: TimestampMs::now(),
+            pos: None,
+            address: address.clone(),
+        });
+        Ok(address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ofdb_core::gateways::geocode::GeoCodingError;
+    use std::cell::Cell;
+
+    #[derive(Default)]
+    struct FakeGateway {
+        pos: Option<(f64, f64)>,
+        address: Option<Address>,
+        failed: Cell<bool>,
+        requests: Cell<usize>,
+    }
+
+    impl FakeGateway {
+        fn answer<T: Clone>(&self, answer: &T) -> GeoCodingResult<T> {
+            self.requests.set(self.requests.get() + 1);
+            if self.failed.get() {
+                return Err(GeoCodingError::Status(503));
+            }
+            Ok(answer.clone())
+        }
+    }
+
+    impl GeoCodingGateway for FakeGateway {
+        fn resolve_address_lat_lng(&self, _: &Address) -> GeoCodingResult<Option<(f64, f64)>> {
+            self.answer(&self.pos)
+        }
+        fn resolve_pos_address(&self, _: MapPoint) -> GeoCodingResult<Option<Address>> {
+            self.answer(&self.address)
+        }
+    }
+
+    fn connections() -> Connections {
+        let connections = Connections::init(":memory:", 1).unwrap();
+        connections.run_embedded_migrations().unwrap();
+        connections
+    }
+
+    fn address(city: &str) -> Address {
+        Address {
+            city: Some(city.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn cache_resolved_and_unresolved_addresses() {
+        let connections = connections();
+        let gw = FakeGateway {
+            pos: Some((52.5, 13.4)),
+            ..Default::default()
+        };
+        let cached = CachedGeoCodingGateway::new(&gw, &connections, Default::default());
+        let (lat, lng) = cached
+            .resolve_address_lat_lng(&address("Berlin"))
+            .unwrap()
+            .unwrap();
+        assert!((lat - 52.5).abs() < 1e-6 && (lng - 13.4).abs() < 1e-6);
+        // Normalized query
+        assert!(cached
+            .resolve_address_lat_lng(&address(" BERLIN "))
+            .unwrap()
+            .is_some());
+        assert_eq!(1, gw.requests.get());
+
+        let gw = FakeGateway::default();
+        let cached = CachedGeoCodingGateway::new(&gw, &connections, Default::default());
+        assert!(cached
+            .resolve_address_lat_lng(&address("Nowhere"))
+            .unwrap()
+            .is_none());
+        assert!(cached
+            .resolve_address_lat_lng(&address("Nowhere"))
+            .unwrap()
+            .is_none());
+        assert_eq!(1, gw.requests.get());
+    }
+
+    #[test]
+    fn do_not_cache_failures() {
+        let connections = connections();
+        let gw = FakeGateway {
+            pos: Some((52.5, 13.4)),
+            ..Default::default()
+        };
+        gw.failed.set(true);
+        let cached = CachedGeoCodingGateway::new(&gw, &connections, Default::default());
+        assert!(cached.resolve_address_lat_lng(&address("Berlin")).is_err());
+        gw.failed.set(false);
+        assert!(cached
+            .resolve_address_lat_lng(&address("Berlin"))
+            .unwrap()
+            .is_some());
+        assert_eq!(2, gw.requests.get());
+    }
+
+    #[test]
+    fn expire_cached_results() {
+        let connections = connections();
+        let gw = FakeGateway::default();
+        let ttl = GeoCodingCacheTtl {
+            resolved: Duration::days(1),
+            unresolved: Duration::zero(),
+        };
+        let cached = CachedGeoCodingGateway::new(&gw, &connections, ttl);
+        let pos = MapPoint::from_lat_lng_deg(52.5, 13.4);
+        assert!(cached.resolve_pos_address(pos).unwrap().is_none());
+        assert!(cached.resolve_pos_address(pos).unwrap().is_none());
+        assert_eq!(2, gw.requests.get());
+    }
+
+    #[test]
+    fn cache_reverse_geocoding() {
+        let connections = connections();
+        let gw = FakeGateway {
+            address: Some(Address {
+                street: Some("Unter den Linden 1".into()),
+                ..address("Berlin")
+            }),
+            ..Default::default()
+        };
+        let cached = CachedGeoCodingGateway::new(&gw, &connections, Default::default());
+        let pos = MapPoint::from_lat_lng_deg(52.5, 13.4);
+        assert_eq!(gw.address, cached.resolve_pos_address(pos).unwrap());
+        // Nearby positions share the cached result
+        let pos = MapPoint::from_lat_lng_deg(52.500_001, 13.400_001);
+        assert_eq!(gw.address, cached.resolve_pos_address(pos).unwrap());
+        assert_eq!(1, gw.requests.get());
+    }
+}
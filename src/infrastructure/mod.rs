@@ -1,22 +1,71 @@
 pub mod db;
 pub mod error;
 pub mod flows;
+pub mod geocoding;
 
-use ofdb_entities::email::*;
-use ofdb_gateways::{mailgun::*, opencage::*, sendmail::*};
-use std::env;
+use self::{
+    db::Connections,
+    geocoding::{CachedGeoCodingGateway, GeoCodingCacheTtl},
+};
+use chrono::Duration;
+use ofdb_core::gateways::{
+    geocode::{GeoCodingChain, GeoCodingGateway},
+    image::ImageStorageGateway,
+};
+use ofdb_entities::{email::*, locale::Locale};
+use ofdb_gateways::{
+    filesystem::LocalImageStorage,
+    mailgun::*,
+    nominatim::{self, Nominatim},
+    opencage::*,
+    sendmail::*,
+    user_communication::EmailTemplates,
+    webhook::*,
+};
+use std::{env, path::Path, sync::Arc};
+
+fn env_days(name: &str, default: Duration) -> Duration {
+    match env::var(name).map(|days| days.parse::<i64>()) {
+        Ok(Ok(days)) => Duration::days(days),
+        Ok(Err(err)) => {
+            warn!("Ignoring invalid value of {}: {}", name, err);
+            default
+        }
+        Err(_) => default,
+    }
+}
 
 lazy_static! {
 
-    pub static ref GEO_CODING_GW: OpenCage = {
-        let key = match env::var("OPENCAGE_API_KEY") {
-            Ok(key) => Some(key),
-            Err(_) => {
-                warn!("No OpenCage API key found");
-                None
+    pub static ref GEO_CODING_GW: GeoCodingChain = {
+        let providers = env::var("GEOCODING_PROVIDERS").unwrap_or_else(|_| "opencage".to_string());
+        let mut gateways: Vec<Box<dyn GeoCodingGateway + Send + Sync>> = vec![];
+        for provider in providers.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            match provider {
+                "opencage" => match env::var("OPENCAGE_API_KEY") {
+                    Ok(key) => gateways.push(Box::new(OpenCage::new(key))),
+                    Err(_) => warn!("No OpenCage API key found"),
+                },
+                "nominatim" => {
+                    let api_url = env::var("NOMINATIM_URL").unwrap_or_else(|_| nominatim::DEFAULT_API_URL.to_string());
+                    gateways.push(Box::new(Nominatim::new(api_url)));
+                }
+                _ => warn!("Ignoring unknown geocoding provider '{}'", provider),
             }
-        };
-        OpenCage::new(key)
+        }
+        let chain = GeoCodingChain::new(gateways);
+        if chain.is_empty() {
+            warn!("No geocoding provider available");
+        }
+        chain
+    };
+
+    pub static ref GEO_CODING_CACHE_TTL: GeoCodingCacheTtl = {
+        let default = GeoCodingCacheTtl::default();
+        GeoCodingCacheTtl {
+            resolved: env_days("GEOCODING_CACHE_TTL_DAYS", default.resolved),
+            unresolved: env_days("GEOCODING_NEGATIVE_CACHE_TTL_DAYS", default.unresolved),
+        }
     };
 
     pub static ref MAILGUN_GW: Option<Mailgun> = {
@@ -50,6 +99,68 @@ lazy_static! {
             None
         }
     };
+
+    pub static ref WEBHOOK_GW: HttpWebhookGateway = HttpWebhookGateway::new();
+
+    pub static ref IMAGE_STORAGE: Box<dyn ImageStorageGateway + Send + Sync> = {
+        let dir = env::var("IMAGE_STORAGE_DIR").unwrap_or_else(|_| "images".to_string());
+        info!("Storing uploaded images in {}", dir);
+        Box::new(LocalImageStorage::new(dir))
+    };
+
+    /// The public URL of the API, e.g. for links in e-mails.
+    ///
+    /// This value is required and must not be derived from
+    /// request headers that could be forged by clients.
+    pub static ref PUBLIC_API_URL: String = match env::var("PUBLIC_API_URL") {
+        Ok(url) => url.trim_end_matches('/').to_string(),
+        Err(_) if cfg!(test) => "http://localhost".to_string(),
+        Err(_) => panic!("The public URL of the API is missing (PUBLIC_API_URL)"),
+    };
+
+    /// The public URL of the image API, e.g. `https://api.ofdb.io/v0/images`.
+    pub static ref IMAGE_BASE_URL: String = env::var("IMAGE_BASE_URL")
+        .map(|url| url.trim_end_matches('/').to_string())
+        .unwrap_or_else(|_| format!("{}/images", *PUBLIC_API_URL));
+
+    /// The public URL of a single place or event on the map, followed by its id.
+    pub static ref ENTRY_URL_PREFIX: String = env::var("ENTRY_URL_PREFIX")
+        .unwrap_or_else(|_| "https://kartevonmorgen.org/#/?entry=".to_string());
+
+    pub static ref EMAIL_TEMPLATES: Arc<EmailTemplates> = {
+        let mut templates = EmailTemplates::builtin();
+        if let Ok(locale) = env::var("EMAIL_DEFAULT_LOCALE") {
+            match locale.parse::<Locale>() {
+                Ok(locale) => templates = templates.with_default_locale(locale),
+                Err(err) => warn!("Ignoring default locale for e-mails: {}", err),
+            }
+        }
+        if let Ok(dir) = env::var("EMAIL_TEMPLATES_DIR") {
+            info!("Loading e-mail templates from {}", dir);
+            templates = match templates.clone().load_dir(Path::new(&dir)) {
+                Ok(templates) => templates,
+                Err(err) => {
+                    warn!("Failed to load e-mail templates from {}: {}", dir, err);
+                    templates
+                }
+            };
+        }
+        Arc::new(templates)
+    };
+}
+
+/// The configured geocoding providers with the results
+/// being cached in the database, if any.
+pub fn cached_geo_coding_gw(connections: &Connections) -> Option<CachedGeoCodingGateway<'_>> {
+    if GEO_CODING_GW.is_empty() {
+        // Nothing to cache
+        return None;
+    }
+    Some(CachedGeoCodingGateway::new(
+        &*GEO_CODING_GW,
+        connections,
+        *GEO_CODING_CACHE_TTL,
+    ))
 }
 
 #[cfg(test)]
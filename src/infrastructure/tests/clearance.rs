@@ -112,13 +112,11 @@ impl PlaceClearanceFixture {
         let organization_without_moderated_tags = Organization {
             id: Id::new(),
             name: "organization_without_moderated_tags".into(),
-            api_token: "organization_without_moderated_tags".into(),
             moderated_tags: vec![],
         };
         let organization_with_add_clearance_tag = Organization {
             id: Id::new(),
             name: "organization_with_add_clearance_tag".into(),
-            api_token: "organization_with_add_clearance_tag".into(),
             moderated_tags: vec![ModeratedTag {
                 label: "add_clearance".into(),
                 allow_add: true,
@@ -129,7 +127,6 @@ impl PlaceClearanceFixture {
         let organization_with_remove_clearance_tag = Organization {
             id: Id::new(),
             name: "organization_with_remove_clearance_tag".into(),
-            api_token: "organization_with_remove_clearance_tag".into(),
             moderated_tags: vec![ModeratedTag {
                 label: "remove_clearance".into(),
                 allow_add: false,
@@ -140,7 +137,6 @@ impl PlaceClearanceFixture {
         let organization_with_add_remove_clearance_tag = Organization {
             id: Id::new(),
             name: "organization_with_add_remove_clearance_tag".into(),
-            api_token: "organization_with_add_remove_clearance_tag".into(),
             moderated_tags: vec![ModeratedTag {
                 label: "add_remove_clearance".into(),
                 allow_add: true,
@@ -198,7 +194,7 @@ impl PlaceClearanceFixture {
             .db_connections
             .exclusive()
             .unwrap()
-            .create_org(organization_without_moderated_tags.clone())
+            .create_org(organization_without_moderated_tags)
             .unwrap();
         backend
             .db_connections
@@ -270,7 +266,7 @@ fn should_create_pending_clearance_when_creating_place_with_moderated_tags() ->
 }
 
 #[test]
-fn should_deny_creation_of_place_with_moderated_tags_if_not_allowed() -> flows::Result<()> {
+fn should_deny_creation_of_place_with_moderated_tags_if_not_allowed() {
     let mut fixture = PlaceClearanceFixture::new();
     let org = &fixture.organization_with_remove_clearance_tag;
     let tag = &org.moderated_tags.first().unwrap().label;
@@ -301,8 +297,6 @@ fn should_deny_creation_of_place_with_moderated_tags_if_not_allowed() -> flows::
             .count_pending_clearances_for_places(&org.id)
             .unwrap()
     );
-
-    Ok(())
 }
 
 #[test]
@@ -338,11 +332,11 @@ fn should_create_pending_clearance_once_when_updating_place_with_moderated_tags(
     )?;
     assert_eq!(1, pending_clearances.len());
     assert_eq!(
-        Some(last_cleared_revision.clone()),
+        Some(last_cleared_revision),
         pending_clearances.first().unwrap().last_cleared_revision
     );
 
-    let mut update_place = usecases::UpdatePlace::from(new_place.clone());
+    let mut update_place = usecases::UpdatePlace::from(new_place);
     let new_revision = new_revision.next();
     update_place.version = new_revision.into();
     update_place.tags = vec![];
@@ -373,7 +367,7 @@ fn should_create_pending_clearance_once_when_updating_place_with_moderated_tags(
 }
 
 #[test]
-fn should_deny_adding_of_moderated_tag_to_place_if_not_allowed() -> flows::Result<()> {
+fn should_deny_adding_of_moderated_tag_to_place_if_not_allowed() {
     let mut fixture = PlaceClearanceFixture::new();
     let org = &fixture.organization_with_remove_clearance_tag;
     let tag = &org.moderated_tags.first().unwrap().label;
@@ -405,12 +399,10 @@ fn should_deny_adding_of_moderated_tag_to_place_if_not_allowed() -> flows::Resul
             .count_pending_clearances_for_places(&org.id)
             .unwrap()
     );
-
-    Ok(())
 }
 
 #[test]
-fn should_deny_removing_of_moderated_tag_from_place_if_not_allowed() -> flows::Result<()> {
+fn should_deny_removing_of_moderated_tag_from_place_if_not_allowed() {
     let mut fixture = PlaceClearanceFixture::new();
     let org = &fixture.organization_with_add_clearance_tag;
     let tag = &org.moderated_tags.first().unwrap().label;
@@ -448,8 +440,6 @@ fn should_deny_removing_of_moderated_tag_from_place_if_not_allowed() -> flows::R
             .count_pending_clearances_for_places(&org.id)
             .unwrap()
     );
-
-    Ok(())
 }
 
 #[test]
@@ -485,7 +475,7 @@ fn should_create_pending_clearance_when_updating_an_archived_place_with_moderate
     )?;
     assert_eq!(1, pending_clearances.len());
     assert_eq!(
-        Some(last_cleared_revision.clone()),
+        Some(last_cleared_revision),
         pending_clearances.first().unwrap().last_cleared_revision
     );
 
@@ -535,7 +525,7 @@ fn should_return_the_last_cleared_revision_when_loading_or_searching_cleared_pla
     )?;
     assert_eq!(1, pending_clearances.len());
     assert_eq!(
-        Some(last_cleared_revision.clone()),
+        Some(last_cleared_revision),
         pending_clearances.first().unwrap().last_cleared_revision
     );
 
@@ -555,7 +545,7 @@ fn should_return_the_last_cleared_revision_when_loading_or_searching_cleared_pla
     // Load uncleared (default)
     let uncleared_load_result = usecases::load_places(
         &*fixture.backend.db_connections.shared()?,
-        &vec![place_id.as_ref()],
+        &[place_id.as_ref()],
         None,
     )?;
     assert_eq!(1, uncleared_load_result.len());
@@ -580,7 +570,7 @@ fn should_return_the_last_cleared_revision_when_loading_or_searching_cleared_pla
     // Load cleared
     let cleared_load_result = usecases::load_places(
         &*fixture.backend.db_connections.shared()?,
-        &vec![place_id.as_ref()],
+        &[place_id.as_ref()],
         Some(tag.as_str()),
     )?;
     assert_eq!(1, cleared_load_result.len());
@@ -648,7 +638,7 @@ fn should_return_the_last_cleared_revision_when_loading_or_searching_cleared_pla
     // Load & search uncleared (default)
     let uncleared_load_result = usecases::load_places(
         &*fixture.backend.db_connections.shared()?,
-        &vec![place_id.as_ref()],
+        &[place_id.as_ref()],
         None,
     )?;
     let (uncleared_search_result, _) = usecases::search(
@@ -677,7 +667,7 @@ fn should_return_the_last_cleared_revision_when_loading_or_searching_cleared_pla
     // Load & search cleared - Not filtered, because no more pending clearances
     let cleared_load_result = usecases::load_places(
         &*fixture.backend.db_connections.shared()?,
-        &vec![place_id.as_ref()],
+        &[place_id.as_ref()],
         Some(tag.as_str()),
     )?;
     let (cleared_search_result, _) = usecases::search(
@@ -745,14 +735,14 @@ fn should_hide_untagged_cleared_revision_when_loading_or_searching_for_cleared_p
     )?;
     assert_eq!(1, pending_clearances.len());
     assert_eq!(
-        Some(last_cleared_revision.clone()),
+        Some(last_cleared_revision),
         pending_clearances.first().unwrap().last_cleared_revision
     );
 
     // Load & search uncleared (default)
     let uncleared_load_result = usecases::load_places(
         &*fixture.backend.db_connections.shared()?,
-        &vec![place_id.as_ref()],
+        &[place_id.as_ref()],
         None,
     )?;
     let (uncleared_search_result, _) = usecases::search(
@@ -775,7 +765,7 @@ fn should_hide_untagged_cleared_revision_when_loading_or_searching_for_cleared_p
     // Load & search cleared
     let cleared_load_result = usecases::load_places(
         &*fixture.backend.db_connections.shared()?,
-        &vec![place_id.as_ref()],
+        &[place_id.as_ref()],
         Some(tag.as_str()),
     )?;
     let (cleared_search_result, _) = usecases::search(
@@ -812,8 +802,8 @@ fn should_fail_when_trying_to_clear_future_revisions_of_places() -> flows::Resul
     let new_revision = old_place.revision.next();
 
     let mut update_place = usecases::UpdatePlace::from(old_place.clone());
-    update_place.title = new_title.clone();
-    update_place.tags = new_tags.clone();
+    update_place.title = new_title;
+    update_place.tags = new_tags;
     update_place.version = new_revision.into();
     let new_place = flows::update_place(
         &fixture.backend.db_connections,
@@ -834,7 +824,7 @@ fn should_fail_when_trying_to_clear_future_revisions_of_places() -> flows::Resul
     )?;
     assert_eq!(1, pending_clearances.len());
     assert_eq!(
-        Some(last_cleared_revision.clone()),
+        Some(last_cleared_revision),
         pending_clearances.first().unwrap().last_cleared_revision
     );
 
@@ -918,3 +908,113 @@ fn should_do_nothing_when_clearing_places_without_pending_clearances() -> flows:
 
     Ok(())
 }
+
+#[test]
+fn should_only_return_cleared_revisions_of_events_with_org_tag() -> flows::Result<()> {
+    let mut fixture = PlaceClearanceFixture::new();
+    let org = fixture.organization_with_add_remove_clearance_tag;
+    let tag = org.moderated_tags.first().unwrap().label.clone();
+    // Edited on behalf of another organization
+    let org_id = fixture.organization_with_add_clearance_tag.id;
+
+    let new_event = usecases::NewEvent {
+        title: "created_event".into(),
+        start: chrono::Utc::now().timestamp() + 3600,
+        tags: Some(vec![tag.clone()]),
+        created_by: Some(fixture.user_email.to_string()),
+        ..Default::default()
+    };
+    let event = flows::create_event(
+        &fixture.backend.db_connections,
+        fixture.backend.search_engine.get_mut(),
+        &fixture.backend.notify,
+        Some(&org_id),
+        new_event.clone(),
+    )?;
+    let query_cleared = |backend: &flows::BackendFixture| {
+        usecases::query_events(
+            &*backend.db_connections.shared().unwrap(),
+            &*backend.search_engine.borrow(),
+            usecases::EventQuery {
+                org_tag: Some(tag.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    };
+    let pending_clearances = usecases::clearance::event::list_pending_clearances(
+        &*fixture.backend.db_connections.shared()?,
+        &org,
+        &Default::default(),
+    )?;
+    assert_eq!(1, pending_clearances.len());
+    assert_eq!(event.id, pending_clearances[0].event_id);
+    assert!(pending_clearances[0].last_cleared_revision.is_none());
+    // Not yet cleared
+    assert!(query_cleared(&fixture.backend).is_empty());
+
+    assert_eq!(
+        1,
+        usecases::clearance::event::update_pending_clearances(
+            &*fixture.backend.db_connections.exclusive()?,
+            &org,
+            &[ClearanceForEvent {
+                event_id: event.id.clone(),
+                cleared_revision: None,
+            }],
+        )?
+    );
+    assert_eq!(
+        0,
+        usecases::clearance::event::count_pending_clearances(
+            &*fixture.backend.db_connections.shared()?,
+            &org,
+        )?
+    );
+    assert_eq!("created_event", query_cleared(&fixture.backend)[0].title);
+
+    // Update the event
+    flows::update_event(
+        &fixture.backend.db_connections,
+        fixture.backend.search_engine.get_mut(),
+        &fixture.backend.notify,
+        Some(&org_id),
+        event.id.clone(),
+        usecases::NewEvent {
+            title: "updated_event".into(),
+            ..new_event
+        },
+    )?;
+    let pending_clearances = usecases::clearance::event::list_pending_clearances(
+        &*fixture.backend.db_connections.shared()?,
+        &org,
+        &Default::default(),
+    )?;
+    assert_eq!(1, pending_clearances.len());
+    assert_eq!(
+        Some(Revision::initial()),
+        pending_clearances[0].last_cleared_revision
+    );
+    // The last cleared revision is returned instead of the current one
+    assert_eq!("created_event", query_cleared(&fixture.backend)[0].title);
+    assert_eq!(
+        "updated_event",
+        usecases::get_event(
+            &*fixture.backend.db_connections.shared()?,
+            event.id.as_str()
+        )?
+        .title
+    );
+
+    usecases::clearance::event::update_pending_clearances(
+        &*fixture.backend.db_connections.exclusive()?,
+        &org,
+        &[ClearanceForEvent {
+            event_id: event.id,
+            cleared_revision: Some(Revision::initial().next()),
+        }],
+    )?;
+    assert_eq!("updated_event", query_cleared(&fixture.backend)[0].title);
+
+    Ok(())
+}
@@ -29,6 +29,7 @@ pub fn default_new_place() -> usecases::NewPlace {
         license: "CC0-1.0".into(),
         image_url: None,
         image_link_url: None,
+        image_id: None,
         custom_links: vec![],
     }
 }
@@ -45,5 +46,6 @@ fn default_search_request<'a>() -> usecases::SearchRequest<'a> {
         ids: vec![],
         status: vec![],
         text: None,
+        open_at: None,
     }
 }
@@ -1,36 +1,57 @@
 use crate::{
-    core::prelude::*,
+    adapters::{self, json},
+    core::{prelude::*, usecases},
     infrastructure::{
-        db::{sqlite, tantivy},
-        GEO_CODING_GW,
+        cached_geo_coding_gw,
+        db::{tantivy, Connections},
+        flows::prelude as flows,
+        PUBLIC_API_URL,
     },
     ports::web,
 };
 
-use clap::{crate_authors, App, Arg};
+use anyhow::{bail, Result as Fallible};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use clap::{crate_authors, App, Arg, ArgMatches, SubCommand};
 use dotenv::dotenv;
-use ofdb_core::gateways::geocode::GeoCodingGateway;
-use std::{env, path::Path};
+use ofdb_core::{gateways::geocode::GeoCodingGateway, rating::Rated, recurrence::occurrences};
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+    process,
+};
 
 const DEFAULT_DB_URL: &str = "openfair.db";
 const DB_CONNECTION_POOL_SIZE: u32 = 10;
 
-embed_migrations!();
-
-fn update_event_locations<D: Db>(db: &mut D) -> Result<()> {
-    let events = db.all_events_chronologically()?;
+fn update_event_locations(connections: &Connections) -> Fallible<()> {
+    let geo_coding_gw = match cached_geo_coding_gw(connections) {
+        Some(gw) => gw,
+        None => {
+            warn!("Unable to update event locations without a geocoding provider");
+            return Ok(());
+        }
+    };
+    let events = connections.shared()?.all_events_chronologically()?;
     for mut e in events {
         if let Some(ref mut loc) = e.location {
             if let Some(ref addr) = loc.address {
-                if let Some((lat, lng)) = GEO_CODING_GW.resolve_address_lat_lng(addr) {
-                    if let Ok(pos) = MapPoint::try_from_lat_lng_deg(lat, lng) {
-                        if pos.is_valid() {
-                            if let Err(err) = db.update_event(&e) {
-                                warn!("Failed to update location of event {}: {}", e.id, err);
-                            } else {
-                                info!("Updated location of event {}", e.id);
-                            }
-                        }
+                let pos = match geo_coding_gw.resolve_address_lat_lng(addr) {
+                    Ok(Some((lat, lng))) => MapPoint::try_from_lat_lng_deg(lat, lng).ok(),
+                    Ok(None) => None,
+                    Err(err) => {
+                        warn!("Failed to resolve location of event {}: {}", e.id, err);
+                        None
+                    }
+                };
+                if let Some(pos) = pos.filter(|pos| pos.is_valid()) {
+                    loc.pos = pos;
+                    if let Err(err) = connections.exclusive()?.update_event(&e) {
+                        warn!("Failed to update location of event {}: {}", e.id, err);
+                    } else {
+                        info!("Updated location of event {}", e.id);
                     }
                 }
             }
@@ -39,6 +60,337 @@ fn update_event_locations<D: Db>(db: &mut D) -> Result<()> {
     Ok(())
 }
 
+fn serve_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("enable-cors")
+            .long("enable-cors")
+            .help("Allow requests from any origin"),
+        Arg::with_name("fix-event-address-location")
+            .long("fix-event-address-location")
+            .help("Update the location of ALL events by resolving their address"),
+    ]
+}
+
+fn idx_dir(matches: &ArgMatches) -> Option<String> {
+    matches
+        .value_of("idx-dir")
+        .map(ToString::to_string)
+        .or_else(|| env::var("INDEX_DIR").map(Option::Some).unwrap_or(None))
+}
+
+fn init_search_engine(matches: &ArgMatches) -> Fallible<tantivy::SearchEngine> {
+    let idx_dir = idx_dir(matches);
+    let idx_path = idx_dir.as_ref().map(|dir| Path::new(dir));
+    info!("Initializing Tantivy full-text search engine");
+    tantivy::SearchEngine::init_with_path(idx_path)
+}
+
+fn serve(connections: Connections, matches: &ArgMatches) {
+    // Fail early if the required configuration is missing
+    lazy_static::initialize(&PUBLIC_API_URL);
+    let search_engine = init_search_engine(matches).unwrap();
+    if matches.is_present("fix-event-address-location") {
+        info!("Updating all event locations...");
+        update_event_locations(&connections).unwrap();
+    }
+    web::run(
+        connections,
+        search_engine,
+        matches.is_present("enable-cors"),
+    );
+}
+
+fn reindex(connections: &Connections, matches: &ArgMatches) -> Fallible<()> {
+    let idx_dir = idx_dir(matches);
+    if idx_dir.is_none() {
+        bail!("No directory for the full-text search index");
+    }
+    let mut search_engine = if matches.is_present("full") {
+        tantivy::SearchEngine::recreate_with_path(idx_dir)?
+    } else {
+        tantivy::SearchEngine::init_with_path(idx_dir)?
+    };
+    flows::update_search_index(connections, &mut search_engine)?;
+    Ok(())
+}
+
+// Syntax: <label>[:<permission>,...]
+fn parse_moderated_tag(s: &str) -> Fallible<ModeratedTag> {
+    let mut split = s.splitn(2, ':');
+    let label = split.next().unwrap_or_default();
+    let mut tag = ModeratedTag::from(label);
+    for permission in split.next().unwrap_or_default().split(',') {
+        match permission.trim() {
+            "" => {}
+            "add" => tag.allow_add = true,
+            "remove" => tag.allow_remove = true,
+            "clearance" => tag.require_clearance = true,
+            p => bail!("Invalid permission '{}' for moderated tag '{}'", p, label),
+        }
+    }
+    Ok(tag)
+}
+
+fn create_org(connections: &Connections, matches: &ArgMatches) -> Fallible<()> {
+    let moderated_tags = matches
+        .values_of("moderated-tag")
+        .map(|values| values.map(parse_moderated_tag).collect())
+        .unwrap_or_else(|| Ok(vec![]))?;
+    let new_org = usecases::NewOrganization {
+        name: matches.value_of("name").unwrap_or_default().to_string(),
+        api_token: matches.value_of("api-token").map(ToString::to_string),
+        moderated_tags,
+    };
+    let (org, secret) = usecases::create_organization(&mut *connections.exclusive()?, new_org)?;
+    println!("id: {}", org.id);
+    println!("name: {}", org.name);
+    println!("api_token: {}", secret);
+    for tag in org.moderated_tags {
+        println!(
+            "moderated_tag: {} (add = {}, remove = {}, clearance = {})",
+            tag.label, tag.allow_add, tag.allow_remove, tag.require_clearance
+        );
+    }
+    Ok(())
+}
+
+fn parse_role(s: &str) -> Fallible<Role> {
+    Ok(match s {
+        "guest" => Role::Guest,
+        "user" => Role::User,
+        "scout" => Role::Scout,
+        "admin" => Role::Admin,
+        _ => bail!("Invalid role: {}", s),
+    })
+}
+
+fn set_role(connections: &Connections, matches: &ArgMatches) -> Fallible<()> {
+    let email = matches.value_of("email").unwrap_or_default();
+    let role = parse_role(matches.value_of("role").unwrap_or_default())?;
+    flows::set_user_role(connections, email, role)?;
+    Ok(())
+}
+
+// Opens the persistent index unless it is locked by a running
+// server. Otherwise the background workers of that server update
+// the index.
+fn open_search_index_unless_locked(idx_dir: Option<String>) -> Option<tantivy::SearchEngine> {
+    let idx_dir = idx_dir?;
+    match tantivy::SearchEngine::init_with_path(Some(&idx_dir)) {
+        Ok(search_engine) => Some(search_engine),
+        Err(err) => {
+            info!(
+                "Leaving the update of the full-text search index to the server: {}",
+                err
+            );
+            None
+        }
+    }
+}
+
+fn archive_events(connections: &Connections, matches: &ArgMatches) -> Fallible<()> {
+    let before = matches.value_of("before").unwrap_or_default();
+    let before = NaiveDate::parse_from_str(before, "%Y-%m-%d")?.and_hms(0, 0, 0);
+    let search_engine = open_search_index_unless_locked(idx_dir(matches));
+    archive_events_before(connections, search_engine, before)
+}
+
+fn archive_events_before(
+    connections: &Connections,
+    mut search_engine: Option<tantivy::SearchEngine>,
+    before: NaiveDateTime,
+) -> Fallible<()> {
+    let ids: Vec<_> = connections
+        .shared()?
+        .all_events_chronologically()?
+        .into_iter()
+        .filter(|e| e.end.unwrap_or(e.start) < before)
+        // Recurring events are archived after their last occurrence
+        .filter(|e| {
+            e.recurrence.as_ref().map_or(true, |r| {
+                let duration = e
+                    .end
+                    .map(|end| end - e.start)
+                    .unwrap_or_else(Duration::zero);
+                occurrences(e.start, r).all(|start| start + duration < before)
+            })
+        })
+        .map(|e| e.id)
+        .collect();
+    if ids.is_empty() {
+        info!("No events found that ended before {}", before);
+        return Ok(());
+    }
+    let ids: Vec<_> = ids.iter().map(Id::as_str).collect();
+    // There is no user account when running from the command line
+    let archived_by_email = "";
+    let count = flows::archive_events(
+        connections,
+        search_engine
+            .as_mut()
+            .map(|search_engine| search_engine as &mut dyn EventAndPlaceIndexer),
+        &ids,
+        archived_by_email,
+    )?;
+    info!("Archived {} event(s) that ended before {}", count, before);
+    Ok(())
+}
+
+// Accepts both plain file paths and file:// URLs
+fn read_calendar_file(file: &str) -> Fallible<String> {
+    let path = file.strip_prefix("file://").unwrap_or(file);
+    Ok(fs::read_to_string(path)?)
+}
+
+fn import_events(connections: &Connections, matches: &ArgMatches) -> Fallible<()> {
+    let ics = read_calendar_file(matches.value_of("file").unwrap_or_default())?;
+    let token = matches
+        .value_of("api-token")
+        .unwrap_or_default()
+        .to_string();
+    let (org, _) = usecases::authorize_organization_by_possible_api_tokens(
+        &*connections.shared()?,
+        &[token],
+        ApiTokenScope::EventsWrite,
+        TimestampMs::now(),
+    )?;
+    let created_by = matches.value_of("created-by").map(ToString::to_string);
+    let mut external_events = adapters::ical::calendar_to_external_events(&ics)?;
+    for e in &mut external_events {
+        e.new_event.created_by = created_by.clone();
+    }
+    let mut search_engine = open_search_index_unless_locked(idx_dir(matches));
+    let notify = web::notify::Notify::default();
+    let imported = flows::import_events(
+        connections,
+        search_engine
+            .as_mut()
+            .map(|search_engine| search_engine as &mut dyn EventAndPlaceIndexer),
+        &*notify,
+        &org.id,
+        external_events,
+    )?;
+    info!(
+        "Imported events: {} created, {} updated, {} archived, {} failed",
+        imported.created.len(),
+        imported.updated.len(),
+        imported.archived.len(),
+        imported.failed.len()
+    );
+    for (uid, err) in &imported.failed {
+        warn!("Failed to import event {}: {}", uid, err);
+    }
+    Ok(())
+}
+
+fn export_places(connections: &Connections, format: &str, out: &mut dyn Write) -> Fallible<()> {
+    let db = connections.shared()?;
+    let places: Vec<_> = db
+        .all_places()?
+        .into_iter()
+        .filter(|(_, status)| status.exists())
+        .collect();
+    match format {
+        "csv" => {
+            let all_categories = db.all_categories()?;
+            let mut wtr = csv::Writer::from_writer(out);
+            for (mut place, _) in places {
+                let ratings = db.load_ratings_of_place(place.id.as_ref())?;
+                let avg_rating = place.avg_ratings(&ratings).total();
+                let (tags, categories) = Category::split_from_tags(place.tags);
+                place.tags = tags;
+                let categories = all_categories
+                    .iter()
+                    .filter(|c1| categories.iter().any(|c2| c1.id == c2.id))
+                    .cloned()
+                    .collect();
+                wtr.serialize(adapters::csv::CsvRecord::from((
+                    place, categories, avg_rating,
+                )))?;
+            }
+            wtr.flush()?;
+        }
+        "json" => {
+            let places: Vec<_> = places
+                .into_iter()
+                .map(|(place, status)| {
+                    let (place_root, place_revision) = place.into();
+                    (
+                        json::PlaceRoot::from(place_root),
+                        json::PlaceRevision::from(place_revision),
+                        json::ReviewStatus::from(status),
+                    )
+                })
+                .collect();
+            serde_json::to_writer(out, &places)?;
+        }
+        _ => bail!("Unsupported export format: {}", format),
+    }
+    Ok(())
+}
+
+fn export_events(connections: &Connections, format: &str, out: &mut dyn Write) -> Fallible<()> {
+    let events = connections.shared()?.all_events_chronologically()?;
+    match format {
+        "csv" => {
+            let mut wtr = csv::Writer::from_writer(out);
+            for event in events {
+                wtr.serialize(adapters::csv::EventRecord::from(event))?;
+            }
+            wtr.flush()?;
+        }
+        "json" => {
+            let events: Vec<_> = events.into_iter().map(json::Event::from).collect();
+            serde_json::to_writer(out, &events)?;
+        }
+        _ => bail!("Unsupported export format: {}", format),
+    }
+    Ok(())
+}
+
+fn export(connections: &Connections, matches: &ArgMatches) -> Fallible<()> {
+    let format = matches.value_of("format").unwrap_or_default();
+    let mut out: Box<dyn Write> = match matches.value_of("output") {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    match matches.value_of("what").unwrap_or_default() {
+        "places" => export_places(connections, format, &mut out)?,
+        "events" => export_events(connections, format, &mut out)?,
+        what => bail!("Unsupported export: {}", what),
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn export_user_data(connections: &Connections, matches: &ArgMatches) -> Fallible<()> {
+    let email = matches.value_of("email").unwrap_or_default();
+    let export = usecases::export_user_data(&*connections.shared()?, email)?;
+    let mut out: Box<dyn Write> = match matches.value_of("output") {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    serde_json::to_writer(&mut out, &json::UserDataExport::from(export))?;
+    out.flush()?;
+    Ok(())
+}
+
+fn erase_user_data(connections: &Connections, matches: &ArgMatches) -> Fallible<()> {
+    let email = matches.value_of("email").unwrap_or_default();
+    // There is no user account when running from the command line
+    let pseudonym = flows::erase_user_data(connections, email, None)?;
+    println!("pseudonym: {}", pseudonym.email);
+    Ok(())
+}
+
+fn exit_on_error(res: Fallible<()>) {
+    if let Err(err) = res {
+        error!("{}", err);
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    }
+}
+
 #[allow(deprecated)]
 pub fn run() {
     dotenv().ok();
@@ -49,23 +401,162 @@ pub fn run() {
             Arg::with_name("db-url")
                 .long("db-url")
                 .value_name("DATABASE_URL")
-                .help("URL to the database"),
+                .global(true)
+                .help("URL to the database, i.e. a file path for SQLite or postgres://... for PostgreSQL"),
         )
         .arg(
             Arg::with_name("idx-dir")
                 .long("idx-dir")
                 .value_name("INDEX_DIR")
+                .global(true)
                 .help("File system directory for the full-text search index"),
         )
-        .arg(
-            Arg::with_name("enable-cors")
-                .long("enable-cors")
-                .help("Allow requests from any origin"),
+        // Serving is the default if no subcommand is given
+        .args(&serve_args())
+        .subcommand(
+            SubCommand::with_name("serve")
+                .about("Starts the web server (default)")
+                .args(&serve_args()),
         )
-        .arg(
-            Arg::with_name("fix-event-address-location")
-                .long("fix-event-address-location")
-                .help("Update the location of ALL events by resolving their address"),
+        .subcommand(
+            SubCommand::with_name("migrate")
+                .about("Runs all pending database migrations and exits"),
+        )
+        .subcommand(
+            SubCommand::with_name("reindex")
+                .about("Updates the persistent full-text search index")
+                .arg(
+                    Arg::with_name("full")
+                        .long("full")
+                        .help("Discards the existing index and rebuilds it from scratch"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("create-org")
+                .about("Creates a new organization and prints its API token")
+                .arg(
+                    Arg::with_name("name")
+                        .required(true)
+                        .index(1)
+                        .help("The name of the organization"),
+                )
+                .arg(
+                    Arg::with_name("api-token")
+                        .long("api-token")
+                        .value_name("TOKEN")
+                        .help("The initial API token with all scopes (default: random)"),
+                )
+                .arg(
+                    Arg::with_name("moderated-tag")
+                        .long("moderated-tag")
+                        .value_name("LABEL[:add,remove,clearance]")
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("A tag that is moderated by the organization with optional permissions"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("set-role")
+                .about("Changes the role of a user")
+                .arg(
+                    Arg::with_name("email")
+                        .required(true)
+                        .index(1)
+                        .help("The e-mail address of the user"),
+                )
+                .arg(
+                    Arg::with_name("role")
+                        .required(true)
+                        .index(2)
+                        .possible_values(&["guest", "user", "scout", "admin"]),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("archive-events")
+                .about("Archives all events that ended before the given date")
+                .arg(
+                    Arg::with_name("before")
+                        .long("before")
+                        .value_name("YYYY-MM-DD")
+                        .required(true)
+                        .help("The (exclusive) date"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("import-events")
+                .about("Imports events of an organization from an iCalendar file")
+                .arg(
+                    Arg::with_name("file")
+                        .required(true)
+                        .index(1)
+                        .value_name("FILE")
+                        .help("Path or file:// URL of the .ics file"),
+                )
+                .arg(
+                    Arg::with_name("api-token")
+                        .long("api-token")
+                        .value_name("TOKEN")
+                        .required(true)
+                        .help("The API token of the organization that owns the events"),
+                )
+                .arg(
+                    Arg::with_name("created-by")
+                        .long("created-by")
+                        .value_name("EMAIL")
+                        .required(true)
+                        .help("The e-mail address of the creator of new events"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("Exports all places or events")
+                .arg(
+                    Arg::with_name("what")
+                        .required(true)
+                        .index(1)
+                        .possible_values(&["places", "events"]),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .possible_values(&["csv", "json"])
+                        .default_value("json"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .short("o")
+                        .value_name("FILE")
+                        .help("The output file (default: stdout)"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("export-user-data")
+                .about("Exports all personal data of a user as JSON")
+                .arg(
+                    Arg::with_name("email")
+                        .required(true)
+                        .index(1)
+                        .help("The e-mail address of the user"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .short("o")
+                        .value_name("FILE")
+                        .help("The output file (default: stdout)"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("erase-user-data")
+                .about("Replaces the account of a user with a pseudonym and deletes all personal data")
+                .arg(
+                    Arg::with_name("email")
+                        .required(true)
+                        .index(1)
+                        .help("The e-mail address of the user"),
+                ),
         )
         .get_matches();
 
@@ -73,35 +564,103 @@ pub fn run() {
         .value_of("db-url")
         .map(ToString::to_string)
         .unwrap_or_else(|| env::var("DATABASE_URL").unwrap_or_else(|_| DEFAULT_DB_URL.to_string()));
+    let connections = Connections::init(&db_url, DB_CONNECTION_POOL_SIZE).unwrap();
     info!(
-        "Connecting to SQLite database '{}' (pool size = {})",
-        db_url, DB_CONNECTION_POOL_SIZE
+        "Connected to {:?} database '{}' (pool size = {})",
+        connections.backend(),
+        db_url,
+        DB_CONNECTION_POOL_SIZE
     );
-    let connections = sqlite::Connections::init(&db_url, DB_CONNECTION_POOL_SIZE).unwrap();
 
     info!("Running embedded database migrations");
-    embedded_migrations::run(&*connections.exclusive().unwrap()).unwrap();
+    connections.run_embedded_migrations().unwrap();
 
-    let idx_dir = matches
-        .value_of("idx-dir")
-        .map(ToString::to_string)
-        .or_else(|| env::var("INDEX_DIR").map(Option::Some).unwrap_or(None));
-    let idx_path = idx_dir.as_ref().map(|dir| Path::new(dir));
-    info!("Initializing Tantivy full-text search engine");
-    let search_engine = tantivy::SearchEngine::init_with_path(idx_path).unwrap();
-
-    #[allow(clippy::match_single_binding)]
     match matches.subcommand() {
-        _ => {
-            if matches.is_present("fix-event-address-location") {
-                info!("Updating all event locations...");
-                update_event_locations(&mut *connections.exclusive().unwrap()).unwrap();
-            }
-            web::run(
-                connections,
-                search_engine,
-                matches.is_present("enable-cors"),
-            );
+        ("migrate", Some(_)) => {}
+        ("reindex", Some(sub_matches)) => exit_on_error(reindex(&connections, sub_matches)),
+        ("create-org", Some(sub_matches)) => exit_on_error(create_org(&connections, sub_matches)),
+        ("set-role", Some(sub_matches)) => exit_on_error(set_role(&connections, sub_matches)),
+        ("archive-events", Some(sub_matches)) => {
+            exit_on_error(archive_events(&connections, sub_matches))
+        }
+        ("import-events", Some(sub_matches)) => {
+            exit_on_error(import_events(&connections, sub_matches))
+        }
+        ("export", Some(sub_matches)) => exit_on_error(export(&connections, sub_matches)),
+        ("export-user-data", Some(sub_matches)) => {
+            exit_on_error(export_user_data(&connections, sub_matches))
+        }
+        ("erase-user-data", Some(sub_matches)) => {
+            exit_on_error(erase_user_data(&connections, sub_matches))
         }
+        ("serve", Some(sub_matches)) => serve(connections, sub_matches),
+        _ => serve(connections, &matches),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{infrastructure::db::tests::TestDatabase, ports::web::tests::DummyNotifyGw};
+
+    fn query_event_ids(search_engine: &tantivy::SearchEngine) -> Vec<Id> {
+        let query = IndexQuery {
+            categories: vec![Category::ID_EVENT],
+            ..Default::default()
+        };
+        search_engine
+            .query_ids(IndexQueryMode::WithoutRating, &query, 10)
+            .unwrap()
+    }
+
+    #[test]
+    fn remove_archived_events_from_the_persistent_index() {
+        let db = TestDatabase::new();
+        let idx_dir = env::temp_dir().join(format!("ofdb-cli-{}", Id::new()));
+        let idx_dir_str = idx_dir.to_str().map(ToString::to_string);
+        let mut search_engine = tantivy::SearchEngine::init_with_path(Some(&idx_dir)).unwrap();
+        let mut create_event = |title: &str, start: NaiveDateTime| {
+            let new_event = usecases::NewEvent {
+                title: title.into(),
+                start: start.timestamp(),
+                created_by: Some("foo@bar.com".into()),
+                ..Default::default()
+            };
+            flows::create_event(
+                &db.connections,
+                &mut search_engine,
+                &DummyNotifyGw,
+                None,
+                new_event,
+            )
+            .unwrap()
+            .id
+        };
+        let now = chrono::Utc::now().naive_utc();
+        let past = create_event("past", now - Duration::days(4));
+        let recent = create_event("recent", now - Duration::days(2));
+        assert_eq!(2, query_event_ids(&search_engine).len());
+
+        // The index is locked by a running server
+        let cli_search_engine = open_search_index_unless_locked(idx_dir_str.clone());
+        assert!(cli_search_engine.is_none());
+        archive_events_before(&db.connections, cli_search_engine, now - Duration::days(3)).unwrap();
+        assert!(query_event_ids(&search_engine).contains(&past));
+        // ...and its background workers update the index
+        assert!(flows::run_next_job(&db.connections, &mut search_engine, None).unwrap());
+        assert_eq!(vec![recent], query_event_ids(&search_engine));
+        drop(search_engine);
+
+        // No server is running
+        let cli_search_engine = open_search_index_unless_locked(idx_dir_str);
+        assert!(cli_search_engine.is_some());
+        archive_events_before(&db.connections, cli_search_engine, now).unwrap();
+        let mut search_engine = tantivy::SearchEngine::init_with_path(Some(&idx_dir)).unwrap();
+        assert!(query_event_ids(&search_engine).is_empty());
+        // No pending jobs are left behind
+        assert!(!flows::run_next_job(&db.connections, &mut search_engine, None).unwrap());
+        drop(search_engine);
+
+        fs::remove_dir_all(&idx_dir).unwrap();
     }
 }
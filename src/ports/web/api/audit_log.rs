@@ -0,0 +1,99 @@
+use super::*;
+
+// The audit log is only visible for admins
+
+#[derive(FromForm, Clone)]
+pub struct AuditLogParams {
+    actor: Option<String>,
+    action: Option<String>,
+    since: Option<i64>, // in seconds
+    until: Option<i64>, // in seconds
+    offset: Option<u64>,
+    limit: Option<u64>,
+}
+
+#[get("/audit-log?<params..>")]
+pub fn get_audit_log(
+    db: Connections,
+    auth: Auth,
+    params: Form<AuditLogParams>,
+) -> Result<Vec<json::AuditLogEntry>> {
+    let db = db.shared()?;
+    auth.user_with_min_role(&*db, Role::Admin)?;
+    let AuditLogParams {
+        actor,
+        action,
+        since,
+        until,
+        offset,
+        limit,
+    } = params.into_inner();
+    let action = action
+        .map(|a| {
+            a.parse()
+                .map_err(|_| Error::Parameter(ParameterError::InvalidAuditAction(a)))
+        })
+        .transpose()?;
+    let query = AuditLogQuery {
+        actor,
+        action,
+        since: since.map(TimestampMs::from_seconds),
+        until: until.map(TimestampMs::from_seconds),
+    };
+    let pagination = Pagination { offset, limit };
+    let entries = usecases::query_audit_log(&*db, &query, &pagination)?;
+    Ok(Json(entries.into_iter().map(Into::into).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ports::web::{api::tests::prelude::*, tests::register_user};
+
+    #[test]
+    fn only_admins_can_read_the_audit_log() {
+        let (client, db) = setup();
+        register_user(&db, "admin@example.com", "secret", true);
+        register_user(&db, "scout@example.com", "secret", true);
+        register_user(&db, "user@example.com", "secret", true);
+        usecases::set_user_role(&*db.exclusive().unwrap(), "admin@example.com", Role::Admin)
+            .unwrap();
+        usecases::set_user_role(&*db.exclusive().unwrap(), "scout@example.com", Role::Scout)
+            .unwrap();
+        usecases::change_user_role(
+            &*db.exclusive().unwrap(),
+            "admin@example.com",
+            "user@example.com",
+            Role::Scout,
+        )
+        .unwrap();
+
+        let res = client
+            .post("/login")
+            .header(ContentType::JSON)
+            .body(r#"{"email":"scout@example.com","password":"secret"}"#)
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let res = client.get("/audit-log").dispatch();
+        assert_eq!(res.status(), Status::Unauthorized);
+
+        let res = client
+            .post("/login")
+            .header(ContentType::JSON)
+            .body(r#"{"email":"admin@example.com","password":"secret"}"#)
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let mut res = client
+            .get("/audit-log?action=change_user_role&actor=admin@example.com")
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let body = res.body().and_then(|b| b.into_string()).unwrap();
+        let entries: Vec<json::AuditLogEntry> = serde_json::from_str(&body).unwrap();
+        assert_eq!(1, entries.len());
+        assert_eq!(vec!["user@example.com".to_string()], entries[0].target_ids);
+        assert_eq!(Some("scout"), entries[0].after.as_deref());
+
+        let res = client.get("/audit-log?action=drop_tables").dispatch();
+        assert_eq!(res.status(), Status::BadRequest);
+    }
+}
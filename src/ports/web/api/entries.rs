@@ -1,14 +1,22 @@
 use super::{super::guards::*, Result};
 use crate::{
-    adapters::json,
+    adapters::{atom, json},
     core::{prelude::*, usecases, util},
     infrastructure::{
-        db::{sqlite, tantivy},
+        db::{tantivy, Connections},
+        error::AppError,
         flows::prelude as flows,
+        ENTRY_URL_PREFIX, PUBLIC_API_URL,
     },
     ports::web::{notify::*, popular_tags_cache::PopularTagsCache},
 };
-use rocket::{self, request::Form, State};
+use rocket::{
+    self,
+    http::{uri::Origin, ContentType},
+    request::Form,
+    response::content::Content,
+    State,
+};
 use rocket_contrib::json::Json;
 
 #[derive(FromForm, Clone)]
@@ -18,7 +26,7 @@ pub struct GetEntryQuery {
 
 #[get("/entries/<ids>?<query..>")]
 pub fn get_entry(
-    db: sqlite::Connections,
+    db: Connections,
     ids: String,
     query: Form<GetEntryQuery>,
 ) -> Result<Vec<json::Entry>> {
@@ -31,6 +39,15 @@ pub fn get_entry(
     let GetEntryQuery { ref org_tag } = query.into_inner();
     let results = {
         let db = db.shared()?;
+        // Merged places are redirected to the surviving place
+        let mut resolved_ids = Vec::with_capacity(ids.len());
+        for id in ids {
+            let id = usecases::resolve_place_redirect(&*db, id)?;
+            if !resolved_ids.contains(&id) {
+                resolved_ids.push(id);
+            }
+        }
+        let ids: Vec<_> = resolved_ids.iter().map(String::as_str).collect();
         let places = usecases::load_places(&*db, &ids, org_tag.as_ref().map(String::as_str))?;
         let mut results = Vec::with_capacity(places.len());
         for (place, _) in places.into_iter() {
@@ -53,7 +70,7 @@ const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
 
 #[get("/entries/recently-changed?<since>&<until>&<with_ratings>&<offset>&<limit>")]
 pub fn get_entries_recently_changed(
-    db: sqlite::Connections,
+    db: Connections,
     since: Option<i64>, // in seconds
     until: Option<i64>, // in seconds
     with_ratings: Option<bool>,
@@ -126,12 +143,59 @@ pub fn get_entries_recently_changed(
     Ok(Json(results))
 }
 
+const ENTRIES_RECENTLY_CHANGED_FEED_DEFAULT_LIMIT: usize = 100;
+
+#[get("/entries/recently-changed.atom?<bbox>&<tag>&<since>&<limit>")]
+pub fn get_entries_recently_changed_feed(
+    db: Connections,
+    uri: &Origin,
+    bbox: Option<String>,
+    tag: Option<String>,
+    since: Option<i64>, // in seconds
+    limit: Option<usize>,
+) -> std::result::Result<Content<String>, AppError> {
+    let since_min = Timestamp::now().into_seconds()
+        - ENTRIES_RECECENTLY_CHANGED_MAX_AGE_IN_DAYS * SECONDS_PER_DAY;
+    let bbox = bbox
+        .map(|bbox| bbox.parse::<MapBbox>())
+        .transpose()
+        .map_err(|_| Error::Parameter(ParameterError::Bbox))?;
+    let query = usecases::RecentlyChangedPlacesQuery {
+        bbox,
+        tag,
+        since: Some(TimestampMs::from_seconds(
+            since.unwrap_or(since_min).max(since_min),
+        )),
+        limit: limit
+            .unwrap_or(ENTRIES_RECENTLY_CHANGED_FEED_DEFAULT_LIMIT)
+            .min(ENTRIES_RECECENTLY_CHANGED_MAX_COUNT as usize),
+    };
+    let places = usecases::recently_changed_places(&*db.shared()?, &query)?;
+    let mut feed = atom::Feed::new(
+        "places/recently-changed",
+        "Recently changed places",
+        atom::feed_url(
+            &PUBLIC_API_URL,
+            "/entries/recently-changed.atom",
+            uri.query(),
+        ),
+    );
+    feed.entries = places
+        .iter()
+        .map(|(place, activity_log)| atom::place_entry(place, activity_log, &ENTRY_URL_PREFIX))
+        .collect();
+    Ok(Content(
+        ContentType::new("application", atom::CONTENT_TYPE_SUBTYPE),
+        feed.to_xml(TimestampMs::now()),
+    ))
+}
+
 const ENTRIES_MOST_POPULAR_TAGS_PAGINATION_LIMIT_MAX: u64 = 1000;
 const ENTRIES_MOST_POPULAR_TAGS_DEFAULT_MAX_CACHE_AGE_SECONDS: u64 = 3600;
 
 #[get("/entries/most-popular-tags?<min_count>&<max_count>&<offset>&<limit>&<max_cache_age>")]
 pub fn get_entries_most_popular_tags(
-    db: sqlite::Connections,
+    db: Connections,
     tags_cache: State<PopularTagsCache>,
     min_count: Option<u64>,
     max_count: Option<u64>,
@@ -165,12 +229,14 @@ pub fn get_entries_most_popular_tags(
 #[post("/entries", format = "application/json", data = "<body>")]
 pub fn post_entry(
     auth: Auth,
-    connections: sqlite::Connections,
+    connections: Connections,
     notify: Notify,
     mut search_engine: tantivy::SearchEngine,
     body: Json<json::NewPlace>,
 ) -> Result<String> {
-    let org = auth.organization(&*connections.shared()?).ok();
+    let org = auth
+        .organization(&connections, ApiTokenScope::PlacesWrite)
+        .ok();
     if org.is_none() && auth.account_email().is_err() {
         auth.has_captcha()?;
     }
@@ -192,13 +258,15 @@ pub fn post_entry(
 #[put("/entries/<id>", format = "application/json", data = "<data>")]
 pub fn put_entry(
     auth: Auth,
-    connections: sqlite::Connections,
+    connections: Connections,
     mut search_engine: tantivy::SearchEngine,
     notify: Notify,
     id: String,
     data: Json<json::UpdatePlace>,
 ) -> Result<String> {
-    let org = auth.organization(&*connections.shared()?).ok();
+    let org = auth
+        .organization(&connections, ApiTokenScope::PlacesWrite)
+        .ok();
     if org.is_none() && auth.account_email().is_err() {
         auth.has_captcha()?;
     }
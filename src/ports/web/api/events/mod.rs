@@ -5,19 +5,27 @@ use crate::{
         prelude::Result as CoreResult,
         util::{geo::MapBbox, validate},
     },
-    infrastructure::{flows::prelude as flows, GEO_CODING_GW},
+    infrastructure::{
+        cached_geo_coding_gw, flows::prelude as flows, ENTRY_URL_PREFIX, PUBLIC_API_URL,
+    },
 };
 use ofdb_core::gateways::geocode::GeoCodingGateway;
 
+use chrono::NaiveDateTime;
 use rocket::{
-    http::{RawStr, Status as HttpStatus},
+    data::Data,
+    http::{uri::Origin, RawStr, Status as HttpStatus},
     request::{FromQuery, Query},
 };
+use std::{collections::HashMap, io::Read};
 
 #[cfg(test)]
 mod tests;
 
-fn check_and_set_address_location(e: &mut usecases::NewEvent) -> Option<MapPoint> {
+fn check_and_set_address_location(
+    connections: &Connections,
+    e: &mut usecases::NewEvent,
+) -> Option<MapPoint> {
     let pos = if let (Some(lat), Some(lng)) = (e.lat, e.lng) {
         MapPoint::try_from_lat_lng_deg(lat, lng)
             .map(Some)
@@ -25,23 +33,42 @@ fn check_and_set_address_location(e: &mut usecases::NewEvent) -> Option<MapPoint
     } else {
         None
     };
-    if pos.unwrap_or_default().is_valid() {
-        // Preserve valid geo locations
-        return pos;
-    }
     // TODO: Parse logical parts of NewEvent earlier
-    let addr = Address {
+    let mut addr = Address {
         street: e.street.clone(),
         zip: e.zip.clone(),
         city: e.city.clone(),
         country: e.country.clone(),
         state: e.state.clone(),
     };
-
-    GEO_CODING_GW
-        .resolve_address_lat_lng(&addr)
-        .and_then(|(lat, lng)| {
-            if let Ok(pos) = MapPoint::try_from_lat_lng_deg(lat, lng) {
+    if let Some(pos) = pos.filter(|pos| pos.is_valid()) {
+        // Preserve valid geo locations and only complete
+        // addresses that have been entered partially
+        if addr.is_empty() || addr.is_complete() {
+            return Some(pos);
+        }
+        if let Some(geo_coding_gw) = cached_geo_coding_gw(connections) {
+            match geo_coding_gw.resolve_pos_address(pos) {
+                Ok(Some(resolved)) => {
+                    log::debug!("Completing event address {:?} with {:?}", addr, resolved);
+                    addr.fill_missing(resolved);
+                    e.street = addr.street;
+                    e.zip = addr.zip;
+                    e.city = addr.city;
+                    e.country = addr.country;
+                    e.state = addr.state;
+                }
+                Ok(None) => {}
+                Err(err) => log::warn!("Failed to resolve address of {:?}: {}", pos, err),
+            }
+        }
+        return Some(pos);
+    }
+    let geo_coding_gw = cached_geo_coding_gw(connections)?;
+    match geo_coding_gw.resolve_address_lat_lng(&addr) {
+        Ok(resolved) => resolved.and_then(|(lat, lng)| {
+            let pos = MapPoint::try_from_lat_lng_deg(lat, lng).ok();
+            if pos.is_some() {
                 log::debug!(
                     "Updating event location: ({:?}, {:?}) -> {:?}",
                     e.lat,
@@ -52,27 +79,26 @@ fn check_and_set_address_location(e: &mut usecases::NewEvent) -> Option<MapPoint
                 e.lng = Some(lng);
             }
             pos
-        })
+        }),
+        Err(err) => {
+            log::warn!("Failed to resolve location of {:?}: {}", addr, err);
+            None
+        }
+    }
 }
 
 #[post("/events", format = "application/json", data = "<e>")]
 pub fn post_event_with_token(
-    connections: sqlite::Connections,
+    connections: Connections,
     mut search_engine: tantivy::SearchEngine,
     notify: Notify,
     auth: Auth,
     e: Json<usecases::NewEvent>,
 ) -> Result<String> {
-    let org = auth.organization(&*connections.shared()?)?;
+    let org = auth.organization(&connections, ApiTokenScope::EventsWrite)?;
     let mut e = e.into_inner();
-    check_and_set_address_location(&mut e);
-    let event = flows::create_event(
-        &connections,
-        &mut search_engine,
-        &*notify,
-        Some(&org.api_token),
-        e,
-    )?;
+    check_and_set_address_location(&connections, &mut e);
+    let event = flows::create_event(&connections, &mut search_engine, &*notify, Some(&org.id), e)?;
     Ok(Json(event.id.to_string()))
 }
 
@@ -80,12 +106,12 @@ pub fn post_event_with_token(
 // NOTE:
 // At the moment we don't want to allow anonymous event creation.
 // So for now we assure that it's blocked:
-pub fn post_event(mut _db: sqlite::Connections, _e: Json<usecases::NewEvent>) -> HttpStatus {
+pub fn post_event(mut _db: Connections, _e: Json<usecases::NewEvent>) -> HttpStatus {
     HttpStatus::Unauthorized
 }
 // But in the future we might allow anonymous event creation:
 //
-// pub fn post_event(mut db: sqlite::Connections, e: Json<usecases::NewEvent>) -> Result<String> {
+// pub fn post_event(mut db: Connections, e: Json<usecases::NewEvent>) -> Result<String> {
 //     let mut e = e.into_inner();
 //     e.created_by = None; // ignore because of missing authorization
 //     e.token = None; // ignore token
@@ -94,44 +120,138 @@ pub fn post_event(mut _db: sqlite::Connections, _e: Json<usecases::NewEvent>) ->
 // }
 
 #[get("/events/<id>")]
-pub fn get_event(db: sqlite::Connections, id: String) -> Result<json::Event> {
+pub fn get_event(db: Connections, id: String) -> Result<json::Event> {
     let mut ev = usecases::get_event(&*db.shared()?, &id)?;
     ev.created_by = None; // don't show creators email to unregistered users
     Ok(Json(ev.into()))
 }
 
+#[get("/events/clearance/count")]
+pub fn count_pending_clearances(db: Connections, auth: Auth) -> Result<json::ResultCount> {
+    let org = auth.organization(&db, ApiTokenScope::EventsClearance)?;
+    let count = usecases::clearance::event::count_pending_clearances(&*db.shared()?, &org)?;
+    Ok(Json(json::ResultCount { count }))
+}
+
+#[get("/events/clearance?<offset>&<limit>")]
+pub fn list_pending_clearances(
+    db: Connections,
+    auth: Auth,
+    offset: Option<u64>,
+    limit: Option<u64>,
+) -> Result<Vec<json::PendingClearanceForEvent>> {
+    let pagination = Pagination { offset, limit };
+    let org = auth.organization(&db, ApiTokenScope::EventsClearance)?;
+    let pending_clearances =
+        usecases::clearance::event::list_pending_clearances(&*db.shared()?, &org, &pagination)?;
+    Ok(Json(
+        pending_clearances.into_iter().map(Into::into).collect(),
+    ))
+}
+
+#[post("/events/clearance", data = "<clearances>")]
+pub fn update_pending_clearances(
+    db: Connections,
+    auth: Auth,
+    clearances: Json<Vec<json::ClearanceForEvent>>,
+) -> Result<json::ResultCount> {
+    let clearances: Vec<_> = clearances
+        .into_inner()
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    let org = auth.organization(&db, ApiTokenScope::EventsClearance)?;
+    let count = usecases::clearance::event::update_pending_clearances(
+        &*db.exclusive()?,
+        &org,
+        &clearances,
+    )?;
+    Ok(Json(json::ResultCount {
+        count: count as u64,
+    }))
+}
+
 #[put("/events/<_id>", format = "application/json", data = "<_e>", rank = 2)]
 // At the moment we don't want to allow anonymous event creation.
 // So for now we assure that it's blocked:
-pub fn put_event(
-    mut _db: sqlite::Connections,
-    _id: &RawStr,
-    _e: Json<usecases::NewEvent>,
-) -> HttpStatus {
+pub fn put_event(mut _db: Connections, _id: &RawStr, _e: Json<usecases::NewEvent>) -> HttpStatus {
     HttpStatus::Unauthorized
 }
 
-#[put("/events/<id>", format = "application/json", data = "<e>")]
+fn parse_occurrence(occurrence: i64) -> CoreResult<NaiveDateTime> {
+    NaiveDateTime::from_timestamp_opt(occurrence, 0)
+        .ok_or_else(|| ParameterError::DateTimeOutOfRange.into())
+}
+
+// Updating a single occurrence of a recurring event creates a
+// new event and returns its id.
+#[put("/events/<id>?<occurrence>", format = "application/json", data = "<e>")]
 pub fn put_event_with_token(
-    connections: sqlite::Connections,
+    connections: Connections,
     mut search_engine: tantivy::SearchEngine,
     notify: Notify,
     auth: Auth,
     id: &RawStr,
+    occurrence: Option<i64>,
     e: Json<usecases::NewEvent>,
-) -> Result<()> {
-    let org = auth.organization(&*connections.shared()?)?;
+) -> Result<Option<String>> {
+    let org = auth.organization(&connections, ApiTokenScope::EventsWrite)?;
     let mut e = e.into_inner();
-    check_and_set_address_location(&mut e);
+    check_and_set_address_location(&connections, &mut e);
+    if let Some(occurrence) = occurrence {
+        let event = flows::update_event_occurrence(
+            &connections,
+            &mut search_engine,
+            &*notify,
+            Some(&org.id),
+            id.to_string().into(),
+            parse_occurrence(occurrence)?,
+            e,
+        )?;
+        return Ok(Json(Some(event.id.to_string())));
+    }
     flows::update_event(
         &connections,
         &mut search_engine,
         &*notify,
-        Some(&org.api_token),
+        Some(&org.id),
         id.to_string().into(),
         e,
     )?;
-    Ok(Json(()))
+    Ok(Json(None))
+}
+
+// Upper limit for the size of imported calendars
+const MAX_CALENDAR_SIZE: u64 = 10 * 1024 * 1024;
+
+#[post("/events/import?<created_by>", data = "<data>")]
+pub fn post_events_import(
+    connections: Connections,
+    mut search_engine: tantivy::SearchEngine,
+    notify: Notify,
+    auth: Auth,
+    created_by: Option<String>,
+    data: Data,
+) -> Result<json::EventImportResult> {
+    let org = auth.organization(&connections, ApiTokenScope::EventsWrite)?;
+    let mut ics = String::new();
+    data.open()
+        .take(MAX_CALENDAR_SIZE)
+        .read_to_string(&mut ics)?;
+    let mut external_events = adapters::ical::calendar_to_external_events(&ics)
+        .map_err(|err| AppError::Business(err.into()))?;
+    for e in &mut external_events {
+        e.new_event.created_by = created_by.clone();
+        check_and_set_address_location(&connections, &mut e.new_event);
+    }
+    let imported = flows::import_events(
+        &connections,
+        Some(&mut search_engine),
+        &*notify,
+        &org.id,
+        external_events,
+    )?;
+    Ok(Json(imported.into()))
 }
 
 impl<'q> FromQuery<'q> for usecases::EventQuery {
@@ -209,6 +329,12 @@ impl<'q> FromQuery<'q> for usecases::EventQuery {
             .map(|i| i.value.url_decode_lossy())
             .find(|v| !v.is_empty());
 
+        let org_tag = query
+            .clone()
+            .filter(|i| i.key == "org_tag")
+            .map(|i| i.value.url_decode_lossy())
+            .find(|v| !v.is_empty());
+
         drop(query); // silence clippy warning
         Ok(usecases::EventQuery {
             bbox,
@@ -218,6 +344,7 @@ impl<'q> FromQuery<'q> for usecases::EventQuery {
             start_min,
             tags,
             text,
+            org_tag,
         })
     }
 }
@@ -240,22 +367,49 @@ fn validate_and_adjust_query_limit(limit: usize) -> CoreResult<usize> {
     }
 }
 
+// Events are filtered by the moderated tags of the organization
+// of a valid API token with the scope for reading events.
+fn org_visibility(
+    auth: &Auth,
+    connections: &Connections,
+) -> result::Result<Option<Organization>, AppError> {
+    match auth.organization(connections, ApiTokenScope::EventsRead) {
+        Ok(org) => Ok(Some(org)),
+        // Fall back to the public view if either no valid token
+        // or a token without the required scope has been provided
+        Err(AppError::Business(Error::Parameter(ParameterError::Unauthorized)))
+        | Err(AppError::Business(Error::Parameter(ParameterError::Forbidden))) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// Same visibility rules as for the JSON endpoints
+fn visible_moderated_tags(
+    auth: &Auth,
+    connections: &Connections,
+    query: &usecases::EventQuery,
+) -> result::Result<Vec<String>, AppError> {
+    match org_visibility(auth, connections)? {
+        Some(org) => Ok(org.moderated_tags.into_iter().map(|t| t.label).collect()),
+        None if query.created_by.is_some() => {
+            Err(Error::Parameter(ParameterError::Unauthorized).into())
+        }
+        None => Ok(vec![]),
+    }
+}
+
 #[get("/events?<query..>")]
 pub fn get_events_with_token(
-    connections: sqlite::Connections,
+    connections: Connections,
     search_engine: tantivy::SearchEngine,
     auth: Auth,
     query: usecases::EventQuery,
 ) -> Result<Vec<json::Event>> {
-    let db = connections.shared()?;
-    let org = match auth.organization(&*db) {
-        Ok(org) => org,
-        Err(AppError::Business(Error::Parameter(ParameterError::Unauthorized))) => {
-            drop(db);
-            return get_events_chronologically(connections, search_engine, query);
-        }
-        Err(e) => return Err(e),
+    let org = match org_visibility(&auth, &connections)? {
+        Some(org) => org,
+        None => return get_events_chronologically(connections, search_engine, query),
     };
+    let db = connections.shared()?;
     let events = usecases::query_events(&*db, &search_engine, query)?;
     // Release the database connection asap
     drop(db);
@@ -279,7 +433,7 @@ pub fn get_events_with_token(
 
 #[get("/events?<query..>", rank = 2)]
 pub fn get_events_chronologically(
-    connections: sqlite::Connections,
+    connections: Connections,
     search_engine: tantivy::SearchEngine,
     query: usecases::EventQuery,
 ) -> Result<Vec<json::Event>> {
@@ -302,21 +456,93 @@ pub fn get_events_chronologically(
     Ok(Json(events))
 }
 
-#[get("/export/events.csv?<query..>")]
-pub fn csv_export(
-    connections: sqlite::Connections,
+#[get("/events.ics?<query..>")]
+pub fn ics_export(
+    connections: Connections,
     search_engine: tantivy::SearchEngine,
     auth: Auth,
     query: usecases::EventQuery,
 ) -> result::Result<Content<String>, AppError> {
+    let moderated_tags = visible_moderated_tags(&auth, &connections, &query)?;
+    let db = connections.shared()?;
+    let events = usecases::query_events(&*db, &search_engine, query)?;
+    // Release the database connection asap
+    drop(db);
+
+    let events: Vec<_> = events
+        .into_iter()
+        .map(|e| usecases::filter_event(e, moderated_tags.iter().map(String::as_str)))
+        .collect();
+    let data = adapters::ical::events_to_calendar(&events, chrono::Utc::now().naive_utc());
+
+    Ok(Content(
+        ContentType::with_params("text", "calendar", ("charset", "utf-8")),
+        data,
+    ))
+}
+
+#[get("/events.atom?<query..>")]
+pub fn atom_feed(
+    connections: Connections,
+    search_engine: tantivy::SearchEngine,
+    auth: Auth,
+    uri: &Origin,
+    mut query: usecases::EventQuery,
+) -> result::Result<Content<String>, AppError> {
+    let moderated_tags = visible_moderated_tags(&auth, &connections, &query)?;
+    // Only upcoming events by default
+    if query.start_min.is_none() {
+        query.start_min = Some(Timestamp::now());
+    }
     let db = connections.shared()?;
+    let events = usecases::query_events(&*db, &search_engine, query)?;
+    let ids: Vec<_> = events.iter().map(|e| e.id.as_str()).collect();
+    let update_times: HashMap<_, _> = db
+        .load_event_update_times(&ids)?
+        .into_iter()
+        .map(|(id, updated)| (String::from(id), updated))
+        .collect();
+    // Release the database connection asap
+    drop(db);
+
+    let now = TimestampMs::now();
+    let mut feed = adapters::atom::Feed::new(
+        "events",
+        "Upcoming events",
+        adapters::atom::feed_url(&PUBLIC_API_URL, "/events.atom", uri.query()),
+    );
+    feed.entries = events
+        .into_iter()
+        .map(|e| usecases::filter_event(e, moderated_tags.iter().map(String::as_str)))
+        .map(|e| {
+            let updated = update_times.get(e.id.as_str()).copied().unwrap_or(now);
+            adapters::atom::event_entry(&e, updated, &ENTRY_URL_PREFIX)
+        })
+        .collect();
+    let data = feed.to_xml(now);
 
-    let moderated_tags = if let Ok(org) = auth.organization(&*db) {
+    Ok(Content(
+        ContentType::new("application", adapters::atom::CONTENT_TYPE_SUBTYPE),
+        data,
+    ))
+}
+
+#[get("/export/events.csv?<query..>")]
+pub fn csv_export(
+    connections: Connections,
+    search_engine: tantivy::SearchEngine,
+    auth: Auth,
+    query: usecases::EventQuery,
+) -> result::Result<Content<String>, AppError> {
+    let moderated_tags = if let Ok(org) = auth.organization(&connections, ApiTokenScope::EventsRead)
+    {
         org.moderated_tags
     } else {
         vec![]
     };
 
+    let db = connections.shared()?;
+
     let user = auth.user_with_min_role(&*db, Role::Scout)?;
 
     let limit = if let Some(limit) = query.limit {
@@ -358,12 +584,13 @@ pub fn csv_export(
     Ok(Content(ContentType::CSV, data))
 }
 
-#[post("/events/<ids>/archive")]
+#[post("/events/<ids>/archive?<occurrence>")]
 pub fn post_events_archive(
     auth: Auth,
-    db: sqlite::Connections,
+    db: Connections,
     mut search_engine: tantivy::SearchEngine,
     ids: String,
+    occurrence: Option<i64>,
 ) -> StatusResult {
     let ids = util::split_ids(&ids);
     if ids.is_empty() {
@@ -374,7 +601,20 @@ pub fn post_events_archive(
         // Only scouts and admins are entitled to review events
         auth.user_with_min_role(&*db, Role::Scout)?.email
     };
-    let update_count = flows::archive_events(&db, &mut search_engine, &ids, &archived_by_email)?;
+    if let Some(occurrence) = occurrence {
+        // A single occurrence of a single recurring event
+        if ids.len() > 1 {
+            return Err(Error::Parameter(ParameterError::InvalidRecurrence(
+                "Occurrences can only be archived one at a time".into(),
+            ))
+            .into());
+        }
+        let occurrence = parse_occurrence(occurrence)?;
+        flows::archive_event_occurrence(&db, &mut search_engine, ids[0], occurrence)?;
+        return Ok(HttpStatus::NoContent);
+    }
+    let update_count =
+        flows::archive_events(&db, Some(&mut search_engine), &ids, &archived_by_email)?;
     if update_count < ids.len() {
         log::info!(
             "Archived only {} of {} event(s): {:?}",
@@ -387,14 +627,14 @@ pub fn post_events_archive(
 }
 
 #[delete("/events/<_id>", rank = 2)]
-pub fn delete_event(mut _db: sqlite::Connections, _id: &RawStr) -> HttpStatus {
+pub fn delete_event(mut _db: Connections, _id: &RawStr) -> HttpStatus {
     HttpStatus::Unauthorized
 }
 
 #[delete("/events/<id>")]
-pub fn delete_event_with_token(db: sqlite::Connections, auth: Auth, id: &RawStr) -> StatusResult {
-    let org = auth.organization(&*db.shared()?)?;
-    usecases::delete_event(&mut *db.exclusive()?, &org.api_token, &id.to_string())?;
+pub fn delete_event_with_token(db: Connections, auth: Auth, id: &RawStr) -> StatusResult {
+    let org = auth.organization(&db, ApiTokenScope::EventsWrite)?;
+    usecases::delete_event(&mut *db.exclusive()?, &org.id, &id.to_string())?;
     // TODO: Replace with HttpStatus::NoContent
     Ok(HttpStatus::Ok)
 }
@@ -73,9 +73,9 @@ mod with_api_token {
                 id: "foo".into(),
                 name: "bar".into(),
                 moderated_tags: vec![],
-                api_token: "foo".into(),
             })
             .unwrap();
+        create_api_token(&db, "foo", "foo");
         let mut res = client
             .post("/events")
             .header(ContentType::JSON)
@@ -100,9 +100,9 @@ mod with_api_token {
                 id: "foo".into(),
                 name: "bar".into(),
                 moderated_tags: vec!["org-tag".into()],
-                api_token: "foo".into(),
             })
             .unwrap();
+        create_api_token(&db, "foo", "foo");
         let mut res = client
             .post("/events")
             .header(ContentType::JSON)
@@ -127,9 +127,9 @@ mod with_api_token {
                 id: "foo".into(),
                 name: "bar".into(),
                 moderated_tags: vec!["org-tag".into()],
-                api_token: "foo".into(),
             })
             .unwrap();
+        create_api_token(&db, "foo", "foo");
         let res = client
                     .post("/events")
                     .header(ContentType::JSON)
@@ -153,9 +153,9 @@ mod with_api_token {
                 id: "foo".into(),
                 name: "bar".into(),
                 moderated_tags: vec!["org-tag".into()],
-                api_token: "foo".into(),
             })
             .unwrap();
+        create_api_token(&db, "foo", "foo");
         let res = client
                     .post("/events")
                     .header(ContentType::JSON)
@@ -179,9 +179,9 @@ mod with_api_token {
                 id: "foo".into(),
                 name: "bar".into(),
                 moderated_tags: vec!["org-tag".into()],
-                api_token: "foo".into(),
             })
             .unwrap();
+        create_api_token(&db, "foo", "foo");
         let res = client
                     .post("/events")
                     .header(ContentType::JSON)
@@ -203,18 +203,18 @@ mod with_api_token {
                 id: "a".into(),
                 name: "a".into(),
                 moderated_tags: vec!["a".into()],
-                api_token: "a".into(),
             })
             .unwrap();
+        create_api_token(&db, "a", "a");
         db.exclusive()
             .unwrap()
             .create_org(Organization {
                 id: "b".into(),
                 name: "b".into(),
                 moderated_tags: vec!["b".into()],
-                api_token: "b".into(),
             })
             .unwrap();
+        create_api_token(&db, "b", "b");
         let res = client
             .post("/events")
             .header(ContentType::JSON)
@@ -247,9 +247,9 @@ mod with_api_token {
                 id: "foo".into(),
                 name: "bar".into(),
                 moderated_tags: vec!["org-tag".into()],
-                api_token: "foo".into(),
             })
             .unwrap();
+        create_api_token(&db, "foo", "foo");
         let res = client
                     .post("/events")
                     .header(ContentType::JSON)
@@ -281,9 +281,9 @@ mod with_api_token {
                 id: "foo".into(),
                 name: "bar".into(),
                 moderated_tags: vec!["org-tag".into()],
-                api_token: "foo".into(),
             })
             .unwrap();
+        create_api_token(&db, "foo", "foo");
         let res = client
                     .post("/events")
                     .header(ContentType::JSON)
@@ -302,9 +302,9 @@ mod with_api_token {
                 id: "foo".into(),
                 name: "bar".into(),
                 moderated_tags: vec!["org-tag".into()],
-                api_token: "foo".into(),
             })
             .unwrap();
+        create_api_token(&db, "foo", "foo");
         let res = client
             .post("/events")
             .header(ContentType::JSON)
@@ -323,9 +323,9 @@ mod with_api_token {
                 id: "foo".into(),
                 name: "bar".into(),
                 moderated_tags: vec!["org-tag".into()],
-                api_token: "foo".into(),
             })
             .unwrap();
+        create_api_token(&db, "foo", "foo");
         let res = client
             .post("/events")
             .header(ContentType::JSON)
@@ -344,9 +344,9 @@ mod with_api_token {
                 id: "foo".into(),
                 name: "bar".into(),
                 moderated_tags: vec!["org-tag".into()],
-                api_token: "foo".into(),
             })
             .unwrap();
+        create_api_token(&db, "foo", "foo");
         let res = client
                     .post("/events")
                     .header(ContentType::JSON)
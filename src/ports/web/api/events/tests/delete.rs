@@ -19,9 +19,9 @@ fn with_invalid_api_token() {
             id: "foo".into(),
             name: "bar".into(),
             moderated_tags: vec!["org-tag".into()],
-            api_token: "foo".into(),
         })
         .unwrap();
+    create_api_token(&db, "foo", "foo");
     let res = client
         .delete("/events/foo")
         .header(ContentType::JSON)
@@ -39,9 +39,9 @@ fn with_api_token() {
             id: "foo".into(),
             name: "bar".into(),
             moderated_tags: vec!["tag".into()],
-            api_token: "foo".into(),
         })
         .unwrap();
+    create_api_token(&db, "foo", "foo");
     let e1 = usecases::NewEvent {
         title: "x".into(),
         start: Utc::now().naive_utc().timestamp(),
@@ -49,7 +49,7 @@ fn with_api_token() {
         created_by: Some("foo@bar.com".into()),
         ..Default::default()
     };
-    let id1 = flows::create_event(&db, &mut search_engine, &notify, Some("foo"), e1)
+    let id1 = flows::create_event(&db, &mut search_engine, &notify, Some(&"foo".into()), e1)
         .unwrap()
         .id;
     let e2 = usecases::NewEvent {
@@ -59,7 +59,7 @@ fn with_api_token() {
         created_by: Some("foo@bar.com".into()),
         ..Default::default()
     };
-    let id2 = flows::create_event(&db, &mut search_engine, &notify, Some("foo"), e2)
+    let id2 = flows::create_event(&db, &mut search_engine, &notify, Some(&"foo".into()), e2)
         .unwrap()
         .id;
     // Manually delete the implicitly added org tag from the 2nd event!
@@ -95,9 +95,9 @@ fn with_api_token_by_organization_without_any_moderated_tags() {
             id: "foo".into(),
             name: "bar".into(),
             moderated_tags: vec![],
-            api_token: "foo".into(),
         })
         .unwrap();
+    create_api_token(&db, "foo", "foo");
     let e = usecases::NewEvent {
         title: "x".into(),
         start: Utc::now().naive_utc().timestamp(),
@@ -105,7 +105,7 @@ fn with_api_token_by_organization_without_any_moderated_tags() {
         created_by: Some("foo@bar.com".into()),
         ..Default::default()
     };
-    let id = flows::create_event(&db, &mut search_engine, &notify, Some("foo"), e)
+    let id = flows::create_event(&db, &mut search_engine, &notify, Some(&"foo".into()), e)
         .unwrap()
         .id;
     assert_eq!(db.shared().unwrap().count_events().unwrap(), 1);
@@ -128,9 +128,9 @@ fn with_api_token_from_different_org_unauthorized() {
             id: "creator".into(),
             name: "creator".into(),
             moderated_tags: vec!["creator".into()],
-            api_token: "creator".into(),
         })
         .unwrap();
+    create_api_token(&db, "creator", "creator");
     let _deleter_org = db
         .exclusive()
         .unwrap()
@@ -138,9 +138,9 @@ fn with_api_token_from_different_org_unauthorized() {
             id: "deleter".into(),
             name: "deleter".into(),
             moderated_tags: vec!["deleter".into()],
-            api_token: "deleter".into(),
         })
         .unwrap();
+    create_api_token(&db, "deleter", "deleter");
     let e = usecases::NewEvent {
         title: "x".into(),
         tags: Some(vec!["bla".into(), "creator".into()]),
@@ -148,7 +148,7 @@ fn with_api_token_from_different_org_unauthorized() {
         start: Utc::now().naive_utc().timestamp(),
         ..Default::default()
     };
-    let id = flows::create_event(&db, &mut search_engine, &notify, Some("creator"), e)
+    let id = flows::create_event(&db, &mut search_engine, &notify, Some(&"creator".into()), e)
         .unwrap()
         .id;
     assert!(db.shared().unwrap().get_event(id.as_ref()).is_ok());
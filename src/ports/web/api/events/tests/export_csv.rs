@@ -11,18 +11,21 @@ fn export_csv() {
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Admin,
+            locale: None,
         },
         User {
             email: "scout@example.com".into(),
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Scout,
+            locale: None,
         },
         User {
             email: "user@example.com".into(),
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::User,
+            locale: None,
         },
     ];
     for u in users {
@@ -35,18 +38,18 @@ fn export_csv() {
             id: "foo".into(),
             name: "foo_name".into(),
             moderated_tags: vec!["tag".into()],
-            api_token: "foo".into(),
         })
         .unwrap();
+    create_api_token(&db, "foo", "foo");
     db.exclusive()
         .unwrap()
         .create_org(Organization {
             id: "bar".into(),
             name: "bar_name".into(),
             moderated_tags: vec!["tag2".into()],
-            api_token: "bar".into(),
         })
         .unwrap();
+    create_api_token(&db, "bar", "bar");
     let start1 = Utc::now().naive_utc().timestamp();
     let e1 = usecases::NewEvent {
         title: "title1".into(),
@@ -58,7 +61,7 @@ fn export_csv() {
         state: Some("state".into()),
         ..Default::default()
     };
-    let id1 = flows::create_event(&db, &mut search_engine, &notify, Some("foo"), e1)
+    let id1 = flows::create_event(&db, &mut search_engine, &notify, Some(&"foo".into()), e1)
         .unwrap()
         .id;
     let start2 = Utc::now().naive_utc().timestamp();
@@ -71,7 +74,7 @@ fn export_csv() {
         telephone: Some("phone2".into()),
         ..Default::default()
     };
-    let id2 = flows::create_event(&db, &mut search_engine, &notify, Some("bar"), e2)
+    let id2 = flows::create_event(&db, &mut search_engine, &notify, Some(&"bar".into()), e2)
         .unwrap()
         .id;
 
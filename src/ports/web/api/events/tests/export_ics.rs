@@ -0,0 +1,57 @@
+use super::*;
+
+#[test]
+fn export_ics() {
+    let (client, db, mut search_engine, notify) = setup2();
+    db.exclusive()
+        .unwrap()
+        .create_org(Organization {
+            id: "foo".into(),
+            name: "foo_name".into(),
+            moderated_tags: vec!["tag".into()],
+        })
+        .unwrap();
+    create_api_token(&db, "foo", "foo");
+    let e = usecases::NewEvent {
+        title: "title, with comma".into(),
+        start: Utc::now().naive_utc().timestamp(),
+        tags: Some(vec!["bla".into()]),
+        created_by: Some("createdby@example.com".into()),
+        email: Some("email@example.com".into()),
+        homepage: Some("https://example.com/".into()),
+        lat: Some(48.5),
+        lng: Some(9.25),
+        ..Default::default()
+    };
+    let id = flows::create_event(&db, &mut search_engine, &notify, Some(&"foo".into()), e)
+        .unwrap()
+        .id;
+
+    let mut response = client.get("/events.ics?tag=bla").dispatch();
+    assert_eq!(response.status(), HttpStatus::Ok);
+    assert_eq!(
+        response.headers().get("Content-Type").collect::<Vec<_>>()[0],
+        "text/calendar; charset=utf-8"
+    );
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    assert!(body_str.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(body_str.contains(&format!("\r\nUID:{}@openfairdb.org\r\n", id)));
+    assert!(body_str.contains("\r\nSUMMARY:title\\, with comma\r\n"));
+    assert!(body_str.contains("\r\nGEO:48.500000;9.250000\r\n"));
+    assert!(body_str.contains("\r\nCONTACT:email@example.com\r\n"));
+    assert!(body_str.contains("\r\nURL:https://example.com/\r\n"));
+    assert!(!body_str.contains("createdby@example.com"));
+
+    let response = client
+        .get("/events.ics?created_by=createdby%40example.com")
+        .dispatch();
+    assert_eq!(response.status(), HttpStatus::Unauthorized);
+
+    let mut response = client
+        .get("/events.ics?tag=foo")
+        .header(Header::new("Authorization", "Bearer foo"))
+        .dispatch();
+    assert_eq!(response.status(), HttpStatus::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    assert!(!body_str.contains("BEGIN:VEVENT"));
+}
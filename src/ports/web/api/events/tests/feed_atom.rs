@@ -0,0 +1,51 @@
+use super::*;
+
+#[test]
+fn upcoming_events_feed() {
+    let (client, db, mut search_engine, notify) = setup2();
+    let new_event = |title: &str, start: i64| usecases::NewEvent {
+        title: title.into(),
+        description: Some("Bring <friends> & family".into()),
+        start,
+        tags: Some(vec!["bla".into()]),
+        created_by: Some("createdby@example.com".into()),
+        ..Default::default()
+    };
+    let now = Utc::now().naive_utc().timestamp();
+    let upcoming_id = flows::create_event(
+        &db,
+        &mut search_engine,
+        &notify,
+        None,
+        new_event("upcoming", now + 3600),
+    )
+    .unwrap()
+    .id;
+    flows::create_event(
+        &db,
+        &mut search_engine,
+        &notify,
+        None,
+        new_event("past", now - 3600),
+    )
+    .unwrap();
+
+    let mut response = client.get("/events.atom?tag=bla").dispatch();
+    assert_eq!(response.status(), HttpStatus::Ok);
+    assert_eq!(
+        response.headers().get("Content-Type").collect::<Vec<_>>()[0],
+        "application/atom+xml"
+    );
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    assert!(body_str.contains("<id>tag:openfairdb.org,2021:events</id>"));
+    assert!(body_str.contains(r#"<link rel="self" href="http://localhost/events.atom?tag=bla"/>"#));
+    assert!(body_str.contains("<name>OpenFairDB</name>"));
+    assert!(body_str.contains(&format!(
+        "<id>tag:openfairdb.org,2021:event:{}</id>",
+        upcoming_id
+    )));
+    assert!(body_str.contains("<title>upcoming</title>"));
+    assert!(!body_str.contains("<title>past</title>"));
+    assert!(body_str.contains("Bring &lt;friends&gt; &amp; family"));
+    assert!(!body_str.contains("createdby@example.com"));
+}
@@ -0,0 +1,72 @@
+use super::*;
+
+fn calendar(events: &[(&str, &str)]) -> String {
+    let mut ics = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n".to_string();
+    for (uid, title) in events {
+        ics += &format!(
+            "BEGIN:VEVENT\r\nUID:{}\r\nDTSTART:20300913T122640Z\r\nSUMMARY:{}\r\nCATEGORIES:bla\r\nEND:VEVENT\r\n",
+            uid, title
+        );
+    }
+    ics += "END:VCALENDAR\r\n";
+    ics
+}
+
+#[test]
+fn import_calendar() {
+    let (client, db) = setup();
+    db.exclusive()
+        .unwrap()
+        .create_org(Organization {
+            id: "foo".into(),
+            name: "foo_name".into(),
+            moderated_tags: vec!["tag".into()],
+        })
+        .unwrap();
+    create_api_token(&db, "foo", "foo");
+
+    let response = client
+        .post("/events/import?created_by=test%40example.com")
+        .body(calendar(&[("a", "A")]))
+        .dispatch();
+    assert_eq!(response.status(), HttpStatus::Unauthorized);
+
+    let mut response = client
+        .post("/events/import?created_by=test%40example.com")
+        .header(Header::new("Authorization", "Bearer foo"))
+        .body(calendar(&[("a", "A"), ("b", "B")]))
+        .dispatch();
+    assert_eq!(response.status(), HttpStatus::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let result: json::EventImportResult = serde_json::from_str(&body_str).unwrap();
+    assert_eq!(2, result.created.len());
+    let events = db.shared().unwrap().all_events_chronologically().unwrap();
+    assert_eq!(2, events.len());
+    assert!(events.iter().all(|e| e.tags == vec!["bla", "tag"]));
+    assert!(events
+        .iter()
+        .all(|e| e.created_by.as_deref() == Some("test@example.com")));
+
+    // Repeated imports update existing and archive missing events
+    let mut response = client
+        .post("/events/import")
+        .header(Header::new("Authorization", "Bearer foo"))
+        .body(calendar(&[("a", "A2")]))
+        .dispatch();
+    assert_eq!(response.status(), HttpStatus::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let result: json::EventImportResult = serde_json::from_str(&body_str).unwrap();
+    assert!(result.created.is_empty());
+    assert_eq!(1, result.updated.len());
+    assert_eq!(1, result.archived.len());
+    let events = db.shared().unwrap().all_events_chronologically().unwrap();
+    assert_eq!(1, events.len());
+    assert_eq!("A2", events[0].title);
+
+    let response = client
+        .post("/events/import")
+        .header(Header::new("Authorization", "Bearer foo"))
+        .body("BEGIN:VCALENDAR\r\n")
+        .dispatch();
+    assert_eq!(response.status(), HttpStatus::BadRequest);
+}
@@ -7,5 +7,9 @@ mod archive;
 mod create;
 mod delete;
 mod export_csv;
+mod export_ics;
+mod feed_atom;
+mod import;
 mod read;
+mod recurring;
 mod update;
@@ -49,6 +49,9 @@ fn all() {
                 archived: None,
                 image_url: None,
                 image_link_url: None,
+                image_id: None,
+                recurrence: None,
+                series_id: None,
             })
             .unwrap();
     }
@@ -159,9 +162,9 @@ fn filtered_by_creator_with_valid_api_token() {
             id: "foo".into(),
             name: "bar".into(),
             moderated_tags: vec!["org-tag".into()],
-            api_token: "foo".into(),
         })
         .unwrap();
+    create_api_token(&db, "foo", "foo");
     let ids: Vec<_> = ["foo@bar.com", "test@test.com", "bla@bla.bla"]
         .iter()
         .map(|m| {
@@ -172,9 +175,15 @@ fn filtered_by_creator_with_valid_api_token() {
                 start: Utc::now().naive_utc().timestamp(),
                 ..Default::default()
             };
-            flows::create_event(&db, &mut search_engine, &notify, Some("foo"), new_event)
-                .unwrap()
-                .id
+            flows::create_event(
+                &db,
+                &mut search_engine,
+                &notify,
+                Some(&"foo".into()),
+                new_event,
+            )
+            .unwrap()
+            .id
         })
         .collect();
     let mut res = client
@@ -198,9 +207,9 @@ fn filtered_by_creator_with_invalid_api_token() {
             id: "foo".into(),
             name: "bar".into(),
             moderated_tags: vec!["org-tag".into()],
-            api_token: "foo".into(),
         })
         .unwrap();
+    create_api_token(&db, "foo", "foo");
 
     let res = client
         .get("/events?created_by=foo@bar.com")
@@ -0,0 +1,171 @@
+use super::*;
+
+const START: i64 = 4_132_508_400;
+const WEEK: i64 = 7 * 24 * 60 * 60;
+
+fn get_events(client: &Client, query: &str) -> Vec<json::Event> {
+    let mut response = client.get(format!("/events?{}", query)).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    serde_json::from_str(&body_str).unwrap()
+}
+
+#[test]
+fn create_update_and_archive_occurrences() {
+    let (client, db, _search_engine, _notify) = setup2();
+    db.exclusive()
+        .unwrap()
+        .create_org(Organization {
+            id: "foo".into(),
+            name: "bar".into(),
+            moderated_tags: vec!["org-tag".into()],
+        })
+        .unwrap();
+    create_api_token(&db, "foo", "foo");
+    db.exclusive()
+        .unwrap()
+        .create_user(&User {
+            email: "scout@example.com".into(),
+            email_confirmed: true,
+            password: "secret".parse::<Password>().unwrap(),
+            role: Role::Scout,
+            locale: None,
+        })
+        .unwrap();
+
+    let mut response = client
+        .post("/events")
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", "Bearer foo"))
+        .body(format!(
+            r#"{{"title":"weekly","start":{},"end":{},"created_by":"foo@bar.com","recurrence":{{"rrule":"FREQ=WEEKLY;COUNT=4"}}}}"#,
+            START,
+            START + 3600
+        ))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let id: String = serde_json::from_str(&body_str).unwrap();
+
+    // Occurrences within the requested time range
+    let range = format!("start_min={}&start_max={}", START, START + 3 * WEEK);
+    let events = get_events(&client, &range);
+    assert_eq!(4, events.len());
+    for (i, e) in events.iter().enumerate() {
+        assert_eq!(id, e.id);
+        assert_eq!(Some(&id), e.series_id.as_ref());
+        assert_eq!(START + i as i64 * WEEK, e.start);
+        assert_eq!(Some(e.start + 3600), e.end);
+        assert!(e.recurrence.is_none());
+    }
+    let events = get_events(&client, &format!("start_min={}", START + WEEK + 1));
+    assert_eq!(2, events.len());
+    assert_eq!(START + 2 * WEEK, events[0].start);
+
+    // The series itself
+    let mut response = client.get(format!("/events/{}", id)).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    assert!(body_str.contains(r#""recurrence":{"rrule":"FREQ=WEEKLY;COUNT=4"}"#));
+
+    // Update a single occurrence
+    let mut response = client
+        .put(format!("/events/{}?occurrence={}", id, START + WEEK))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", "Bearer foo"))
+        .body(format!(
+            r#"{{"title":"moved","start":{},"created_by":"foo@bar.com"}}"#,
+            START + WEEK + 7200
+        ))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let occurrence_id: String = serde_json::from_str(&body_str).unwrap();
+    assert_ne!(id, occurrence_id);
+    let events = get_events(&client, &range);
+    assert_eq!(4, events.len());
+    assert_eq!(occurrence_id, events[1].id);
+    assert_eq!("moved", events[1].title);
+    assert_eq!(START + WEEK + 7200, events[1].start);
+    assert_eq!(Some(&id), events[1].series_id.as_ref());
+    assert!(events[1].tags.contains(&"org-tag".to_string()));
+
+    // Unknown occurrences
+    let response = client
+        .put(format!("/events/{}?occurrence={}", id, START + WEEK))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", "Bearer foo"))
+        .body(format!(
+            r#"{{"title":"moved","start":{},"created_by":"foo@bar.com"}}"#,
+            START + WEEK
+        ))
+        .dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+
+    // Archive a single occurrence
+    let login = client
+        .post("/login")
+        .header(ContentType::JSON)
+        .body(r#"{"email": "scout@example.com", "password": "secret"}"#)
+        .dispatch();
+    assert_eq!(login.status(), Status::Ok);
+    let response = client
+        .post(format!(
+            "/events/{}/archive?occurrence={}",
+            id,
+            START + 2 * WEEK
+        ))
+        .dispatch();
+    assert_eq!(response.status(), Status::NoContent);
+    let events = get_events(&client, &range);
+    assert_eq!(3, events.len());
+    assert_eq!(START + 3 * WEEK, events[2].start);
+
+    // Archive the whole series including the updated occurrence
+    let response = client.post(format!("/events/{}/archive", id)).dispatch();
+    assert_eq!(response.status(), Status::NoContent);
+    assert!(get_events(&client, &range).is_empty());
+}
+
+#[test]
+fn find_occurrences_of_open_ended_series() {
+    let (client, db, _search_engine, _notify) = setup2();
+    db.exclusive()
+        .unwrap()
+        .create_org(Organization {
+            id: "foo".into(),
+            name: "bar".into(),
+            moderated_tags: vec![],
+        })
+        .unwrap();
+    create_api_token(&db, "foo", "foo");
+    let day = 24 * 60 * 60;
+    let response = client
+        .post("/events")
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", "Bearer foo"))
+        .body(format!(
+            r#"{{"title":"daily","start":{},"end":{},"created_by":"foo@bar.com","recurrence":{{"rrule":"FREQ=DAILY"}}}}"#,
+            START,
+            START + 3600
+        ))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+
+    // Far beyond the first 1000 occurrences
+    let start_min = START + 2000 * day;
+    let events = get_events(
+        &client,
+        &format!("start_min={}&start_max={}", start_min, start_min + 2 * day),
+    );
+    assert_eq!(3, events.len());
+    assert_eq!(start_min, events[0].start);
+    assert_eq!(start_min + 2 * day, events[2].start);
+
+    // Before the start of the series
+    let events = get_events(
+        &client,
+        &format!("start_min={}&start_max={}", START - 2 * day, START - day),
+    );
+    assert!(events.is_empty());
+}
@@ -20,9 +20,9 @@ fn with_invalid_api_token() {
             id: "foo".into(),
             name: "bar".into(),
             moderated_tags: vec!["org-tag".into()],
-            api_token: "foo".into(),
         })
         .unwrap();
+    create_api_token(&db, "foo", "foo");
     let res = client
         .put("/events/foo")
         .header(ContentType::JSON)
@@ -41,9 +41,9 @@ fn with_api_token() {
             id: "foo".into(),
             name: "bar".into(),
             moderated_tags: vec!["org-tag".into()],
-            api_token: "foo".into(),
         })
         .unwrap();
+    create_api_token(&db, "foo", "foo");
     let e = usecases::NewEvent {
         title: "x".into(),
         tags: Some(vec!["bla".into(), "org-tag".into()]),
@@ -51,7 +51,7 @@ fn with_api_token() {
         start: Utc::now().naive_utc().timestamp(),
         ..Default::default()
     };
-    let id = flows::create_event(&db, &mut search_engine, &notify, Some("foo"), e)
+    let id = flows::create_event(&db, &mut search_engine, &notify, Some(&"foo".into()), e)
         .unwrap()
         .id;
     assert!(db.shared().unwrap().get_event(id.as_ref()).is_ok());
@@ -77,9 +77,9 @@ fn with_api_token_for_organization_without_any_moderated_tags() {
             id: "foo".into(),
             name: "bar".into(),
             moderated_tags: vec![],
-            api_token: "foo".into(),
         })
         .unwrap();
+    create_api_token(&db, "foo", "foo");
     let e = usecases::NewEvent {
         title: "x".into(),
         tags: Some(vec!["bla".into()]),
@@ -87,7 +87,7 @@ fn with_api_token_for_organization_without_any_moderated_tags() {
         start: Utc::now().naive_utc().timestamp(),
         ..Default::default()
     };
-    let id = flows::create_event(&db, &mut search_engine, &notify, Some("foo"), e)
+    let id = flows::create_event(&db, &mut search_engine, &notify, Some(&"foo".into()), e)
         .unwrap()
         .id;
     assert!(db.shared().unwrap().get_event(id.as_ref()).is_ok());
@@ -113,9 +113,9 @@ fn with_api_token_but_mismatching_tag() {
             id: "foo".into(),
             name: "bar".into(),
             moderated_tags: vec!["org-tag".into()],
-            api_token: "foo".into(),
         })
         .unwrap();
+    create_api_token(&db, "foo", "foo");
     // The events needs an owner, otherwise the test may fail
     // with a debug assertion.
     db.exclusive()
@@ -124,9 +124,9 @@ fn with_api_token_but_mismatching_tag() {
             id: "bar".into(),
             name: "foo".into(),
             moderated_tags: vec!["bla".into()],
-            api_token: "bar".into(),
         })
         .unwrap();
+    create_api_token(&db, "bar", "bar");
     let e = usecases::NewEvent {
         title: "x".into(),
         tags: Some(vec!["bla".into()]),
@@ -134,7 +134,7 @@ fn with_api_token_but_mismatching_tag() {
         start: Utc::now().naive_utc().timestamp(),
         ..Default::default()
     };
-    let id = flows::create_event(&db, &mut search_engine, &notify, Some("bar"), e)
+    let id = flows::create_event(&db, &mut search_engine, &notify, Some(&"bar".into()), e)
         .unwrap()
         .id;
     assert!(db.shared().unwrap().get_event(id.as_ref()).is_ok());
@@ -156,9 +156,9 @@ fn with_api_token_keep_org_tag() {
             id: "foo".into(),
             name: "bar".into(),
             moderated_tags: vec!["org-tag".into()],
-            api_token: "foo".into(),
         })
         .unwrap();
+    create_api_token(&db, "foo", "foo");
     let e = usecases::NewEvent {
         title: "x".into(),
         tags: Some(vec!["bla".into(), "org-tag".into()]),
@@ -166,7 +166,7 @@ fn with_api_token_keep_org_tag() {
         start: Utc::now().naive_utc().timestamp(),
         ..Default::default()
     };
-    let id = flows::create_event(&db, &mut search_engine, &notify, Some("foo"), e)
+    let id = flows::create_event(&db, &mut search_engine, &notify, Some(&"foo".into()), e)
         .unwrap()
         .id;
     assert!(db.shared().unwrap().get_event(id.as_ref()).is_ok());
@@ -192,9 +192,9 @@ fn with_api_token_and_removing_tag() {
             id: "foo".into(),
             name: "bar".into(),
             moderated_tags: vec!["org-tag1".into(), "org-tag2".into()],
-            api_token: "foo".into(),
         })
         .unwrap();
+    create_api_token(&db, "foo", "foo");
     let e = usecases::NewEvent {
         title: "x".into(),
         tags: Some(vec![
@@ -208,7 +208,7 @@ fn with_api_token_and_removing_tag() {
         start: Utc::now().naive_utc().timestamp(),
         ..Default::default()
     };
-    let id = flows::create_event(&db, &mut search_engine, &notify, Some("foo"), e)
+    let id = flows::create_event(&db, &mut search_engine, &notify, Some(&"foo".into()), e)
         .unwrap()
         .id;
     assert!(db.shared().unwrap().get_event(id.as_ref()).is_ok());
@@ -236,9 +236,9 @@ fn with_api_token_created_by() {
             id: "foo".into(),
             name: "bar".into(),
             moderated_tags: vec!["bla".into()],
-            api_token: "foo".into(),
         })
         .unwrap();
+    create_api_token(&db, "foo", "foo");
     let created_by = Some("foo@bar.com".into());
     let start = Utc::now().naive_utc().timestamp();
     let e = usecases::NewEvent {
@@ -248,7 +248,7 @@ fn with_api_token_created_by() {
         start,
         ..Default::default()
     };
-    let id = flows::create_event(&db, &mut search_engine, &notify, Some("foo"), e)
+    let id = flows::create_event(&db, &mut search_engine, &notify, Some(&"foo".into()), e)
         .unwrap()
         .id;
     assert!(db.shared().unwrap().get_event(id.as_ref()).is_ok());
@@ -293,9 +293,9 @@ fn with_api_token_from_different_org_unauthorized() {
             id: "creator".into(),
             name: "creator".into(),
             moderated_tags: vec!["creator".into()],
-            api_token: "creator".into(),
         })
         .unwrap();
+    create_api_token(&db, "creator", "creator");
     let _updater_org = db
         .exclusive()
         .unwrap()
@@ -303,9 +303,9 @@ fn with_api_token_from_different_org_unauthorized() {
             id: "updater".into(),
             name: "updater".into(),
             moderated_tags: vec!["updater".into()],
-            api_token: "updater".into(),
         })
         .unwrap();
+    create_api_token(&db, "updater", "updater");
     let e = usecases::NewEvent {
         title: "x".into(),
         tags: Some(vec!["bla".into(), "creator".into()]),
@@ -313,7 +313,7 @@ fn with_api_token_from_different_org_unauthorized() {
         start: Utc::now().naive_utc().timestamp(),
         ..Default::default()
     };
-    let id = flows::create_event(&db, &mut search_engine, &notify, Some("creator"), e)
+    let id = flows::create_event(&db, &mut search_engine, &notify, Some(&"creator".into()), e)
         .unwrap()
         .id;
     assert!(db.shared().unwrap().get_event(id.as_ref()).is_ok());
@@ -339,9 +339,9 @@ fn update_geo_location() {
             id: "foo".into(),
             name: "bar".into(),
             moderated_tags: vec!["org-tag".into()],
-            api_token: "foo".into(),
         })
         .unwrap();
+    create_api_token(&db, "foo", "foo");
     let e = usecases::NewEvent {
         title: "x".into(),
         tags: Some(vec!["bla".into(), "org-tag".into()]),
@@ -351,7 +351,7 @@ fn update_geo_location() {
         lng: Some(2.0),
         ..Default::default()
     };
-    let id = flows::create_event(&db, &mut search_engine, &notify, Some("foo"), e)
+    let id = flows::create_event(&db, &mut search_engine, &notify, Some(&"foo".into()), e)
         .unwrap()
         .id;
     let created = db.shared().unwrap().get_event(id.as_ref()).unwrap();
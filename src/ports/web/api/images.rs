@@ -0,0 +1,93 @@
+use super::*;
+use crate::{
+    adapters::image::MAX_UPLOAD_SIZE, infrastructure::IMAGE_BASE_URL,
+    ports::web::image_storage::ImageStorage,
+};
+use rocket::data::Data;
+use std::io::Read;
+
+/// The public URL of all images.
+struct ImageBaseUrl<'a>(&'a str);
+
+impl ImageBaseUrl<'_> {
+    fn image_url(&self, id: &Id, size: Option<ThumbnailSize>) -> String {
+        match size {
+            Some(size) => format!("{}/{}/{}", self.0, id, size),
+            None => format!("{}/{}", self.0, id),
+        }
+    }
+
+    fn to_json(&self, image: Image) -> json::Image {
+        json::Image {
+            url: self.image_url(&image.id, None),
+            thumbnails: json::ImageThumbnails {
+                small: self.image_url(&image.id, Some(ThumbnailSize::Small)),
+                medium: self.image_url(&image.id, Some(ThumbnailSize::Medium)),
+                large: self.image_url(&image.id, Some(ThumbnailSize::Large)),
+            },
+            id: image.id.into(),
+            width: image.width,
+            height: image.height,
+        }
+    }
+}
+
+#[post("/images", data = "<data>")]
+pub fn post_image(
+    connections: Connections,
+    storage: ImageStorage,
+    auth: Auth,
+    data: Data,
+) -> Result<json::Image> {
+    if auth.account_email().is_err()
+        && auth
+            .organization(&connections, ApiTokenScope::ImagesWrite)
+            .is_err()
+    {
+        auth.has_captcha()?;
+    }
+    let mut buf = Vec::new();
+    data.open()
+        .take(MAX_UPLOAD_SIZE + 1)
+        .read_to_end(&mut buf)?;
+    if buf.len() as u64 > MAX_UPLOAD_SIZE {
+        return Err(Error::Parameter(ParameterError::ImageTooLarge).into());
+    }
+    let new_image = adapters::image::process_upload(&buf)?;
+    let created_by = auth.account_email().ok().map(Email::from);
+    let image =
+        usecases::store_image(&*connections.exclusive()?, &*storage, new_image, created_by)?;
+    Ok(Json(ImageBaseUrl(&IMAGE_BASE_URL).to_json(image)))
+}
+
+fn image_content(image: &Image, data: Vec<u8>) -> Content<Vec<u8>> {
+    let content_type = match image.format {
+        ImageFormat::Jpeg => ContentType::JPEG,
+        ImageFormat::Png => ContentType::PNG,
+    };
+    Content(content_type, data)
+}
+
+#[get("/images/<id>")]
+pub fn get_image(
+    connections: Connections,
+    storage: ImageStorage,
+    id: String,
+) -> result::Result<Content<Vec<u8>>, AppError> {
+    let (image, data) =
+        usecases::load_image_data(&*connections.shared()?, &*storage, &id.into(), None)?;
+    Ok(image_content(&image, data))
+}
+
+#[get("/images/<id>/<size>")]
+pub fn get_image_thumbnail(
+    connections: Connections,
+    storage: ImageStorage,
+    id: String,
+    size: String,
+) -> result::Result<Content<Vec<u8>>, AppError> {
+    let size = size.parse().map_err(|_| RepoError::NotFound)?;
+    let (image, data) =
+        usecases::load_image_data(&*connections.shared()?, &*storage, &id.into(), Some(size))?;
+    Ok(image_content(&image, data))
+}
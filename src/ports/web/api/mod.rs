@@ -7,7 +7,7 @@ use crate::{
         util::{self, geo},
     },
     infrastructure::{
-        db::{sqlite, tantivy},
+        db::{tantivy, Connections},
         error::AppError,
         flows::prelude as flows,
     },
@@ -21,18 +21,23 @@ use rocket::{
     Route, State,
 };
 use rocket_contrib::json::Json;
-use std::result;
+use std::{convert::TryFrom, result};
 
+mod audit_log;
 pub mod captcha;
 mod count;
 mod entries;
 pub mod events;
+mod images;
+mod organizations;
 mod places;
 mod ratings;
 mod search;
 #[cfg(test)]
 pub mod tests;
+mod tiles;
 mod users;
+mod webhooks;
 
 type Result<T> = result::Result<Json<T>, AppError>;
 type StatusResult = result::Result<Status, AppError>;
@@ -44,16 +49,24 @@ pub fn routes() -> Vec<Route> {
         confirm_email_address,
         subscribe_to_bbox,
         get_bbox_subscriptions,
+        post_bbox_subscription,
+        delete_bbox_subscription,
+        get_unsubscribe_bbox,
+        post_unsubscribe_bbox,
         unsubscribe_all_bboxes,
         entries::get_entry,
         entries::get_entries_recently_changed,
+        entries::get_entries_recently_changed_feed,
         entries::get_entries_most_popular_tags,
         entries::post_entry,
         entries::put_entry,
         get_place,
         get_place_history,
+        get_place_diff,
         get_place_history_revision,
         post_places_review,
+        post_places_merge,
+        post_places_revert,
         events::post_event,
         events::post_event_with_token,
         events::get_event,
@@ -62,9 +75,15 @@ pub fn routes() -> Vec<Route> {
         events::put_event,
         events::put_event_with_token,
         events::post_events_archive,
+        events::post_events_import,
         events::delete_event,
         events::delete_event_with_token,
         events::csv_export,
+        events::ics_export,
+        events::atom_feed,
+        events::count_pending_clearances,
+        events::list_pending_clearances,
+        events::update_pending_clearances,
         users::post_request_password_reset,
         users::post_reset_password,
         users::post_user,
@@ -73,10 +92,15 @@ pub fn routes() -> Vec<Route> {
         users::get_user,
         users::get_current_user,
         users::delete_user,
+        users::get_user_data,
+        users::delete_user_data,
         get_categories,
         get_category,
         get_tags,
         search::get_search,
+        search::get_search_geojson,
+        search::get_search_clusters,
+        tiles::get_tile,
         get_duplicates,
         search::post_search_duplicates,
         count::get_count_entries,
@@ -87,6 +111,25 @@ pub fn routes() -> Vec<Route> {
         places::count_pending_clearances,
         places::list_pending_clearances,
         places::update_pending_clearances,
+        webhooks::get_webhooks,
+        webhooks::post_webhook,
+        webhooks::delete_webhook,
+        webhooks::get_webhook_deliveries,
+        organizations::get_organizations,
+        organizations::post_organization,
+        organizations::get_organization,
+        organizations::put_organization,
+        organizations::delete_organization,
+        organizations::post_moderated_tag,
+        organizations::delete_moderated_tag,
+        organizations::get_organization_entries,
+        organizations::get_api_tokens,
+        organizations::post_api_token,
+        organizations::delete_api_token,
+        audit_log::get_audit_log,
+        images::post_image,
+        images::get_image,
+        images::get_image_thumbnail,
         captcha::post_captcha,
         captcha::get_captcha,
         captcha::post_captcha_verify,
@@ -95,11 +138,13 @@ pub fn routes() -> Vec<Route> {
 
 #[get("/places/<id>")]
 pub fn get_place(
-    db: sqlite::Connections,
+    db: Connections,
     id: String,
 ) -> Result<(json::PlaceRoot, json::PlaceRevision, json::ReviewStatus)> {
     let (place, status) = {
         let db = db.shared()?;
+        // Merged places are redirected to the surviving place
+        let id = usecases::resolve_place_redirect(&*db, &id)?;
         db.get_place(&id)?
     };
     let (place_root, place_revision) = place.into();
@@ -112,49 +157,60 @@ pub fn get_place(
 
 #[get("/places/<id>/history/<revision>")]
 pub fn get_place_history_revision(
-    db: sqlite::Connections,
+    db: Connections,
     auth: Auth,
     id: String,
     revision: RevisionValue,
 ) -> Result<json::PlaceHistory> {
-    let place_history = {
-        let db = db.shared()?;
-
-        // The history contains e-mail addresses of registered users
-        // is only permitted for scouts and admins or organizations!
-        if auth.user_with_min_role(&*db, Role::Scout).is_err() {
-            auth.organization(&*db)?;
-        }
-
-        db.get_place_history(&id, Some(revision.into()))?
-    };
+    // The history contains e-mail addresses of registered users
+    // is only permitted for scouts and admins or organizations!
+    if auth
+        .user_with_min_role(&*db.shared()?, Role::Scout)
+        .is_err()
+    {
+        auth.organization(&db, ApiTokenScope::PlacesHistoryRead)?;
+    }
+    let place_history = db.shared()?.get_place_history(&id, Some(revision.into()))?;
     Ok(Json(place_history.into()))
 }
 
 #[get("/places/<id>/history", rank = 2)]
-pub fn get_place_history(
-    db: sqlite::Connections,
+pub fn get_place_history(db: Connections, auth: Auth, id: String) -> Result<json::PlaceHistory> {
+    // The history contains e-mail addresses of registered users
+    // is only permitted for scouts and admins or for organizations!
+    if auth
+        .user_with_min_role(&*db.shared()?, Role::Scout)
+        .is_err()
+    {
+        auth.organization(&db, ApiTokenScope::PlacesHistoryRead)?;
+    }
+    let place_history = db.shared()?.get_place_history(&id, None)?;
+    Ok(Json(place_history.into()))
+}
+
+#[get("/places/<id>/diff?<from>&<to>")]
+pub fn get_place_diff(
+    db: Connections,
     auth: Auth,
     id: String,
-) -> Result<json::PlaceHistory> {
-    let place_history = {
-        let db = db.shared()?;
-
-        // The history contains e-mail addresses of registered users
-        // is only permitted for scouts and admins or for organizations!
-        if auth.user_with_min_role(&*db, Role::Scout).is_err() {
-            auth.organization(&*db)?;
-        }
-
-        db.get_place_history(&id, None)?
-    };
-    Ok(Json(place_history.into()))
+    from: RevisionValue,
+    to: RevisionValue,
+) -> Result<json::PlaceDiff> {
+    // The same permissions as for the history
+    if auth
+        .user_with_min_role(&*db.shared()?, Role::Scout)
+        .is_err()
+    {
+        auth.organization(&db, ApiTokenScope::PlacesHistoryRead)?;
+    }
+    let diff = usecases::diff_place_revisions(&*db.shared()?, &id, from.into(), to.into())?;
+    Ok(Json(diff.into()))
 }
 
 #[post("/places/<ids>/review", data = "<review>")]
 pub fn post_places_review(
     auth: Auth,
-    db: sqlite::Connections,
+    db: Connections,
     mut search_engine: tantivy::SearchEngine,
     ids: String,
     review: Json<json::Review>,
@@ -189,9 +245,79 @@ pub fn post_places_review(
     Ok(Json(()))
 }
 
+#[post("/places/<id>/merge", format = "application/json", data = "<merge>")]
+pub fn post_places_merge(
+    auth: Auth,
+    db: Connections,
+    mut search_engine: tantivy::SearchEngine,
+    notify: Notify,
+    id: String,
+    merge: Json<json::MergePlaces>,
+) -> Result<(json::PlaceRoot, json::PlaceRevision, json::ReviewStatus)> {
+    let merged_by = {
+        let db = db.shared()?;
+        // Only scouts and admins are entitled to merge places
+        auth.user_with_min_role(&*db, Role::Scout)?.email
+    };
+    let json::MergePlaces {
+        merged,
+        version,
+        fields_from_merged,
+        comment,
+    } = merge.into_inner();
+    let merge = usecases::MergePlaces {
+        survivor_id: id.into(),
+        version,
+        merged_id: merged.into(),
+        fields_from_merged: fields_from_merged.into_iter().map(Into::into).collect(),
+        comment,
+    };
+    let merged = flows::merge_places(&db, &mut search_engine, &*notify, merge, &merged_by)?;
+    let (place_root, place_revision) = merged.place.into();
+    Ok(Json((
+        place_root.into(),
+        place_revision.into(),
+        ReviewStatus::Created.into(),
+    )))
+}
+
+#[post("/places/<id>/revert", format = "application/json", data = "<revert>")]
+pub fn post_places_revert(
+    auth: Auth,
+    db: Connections,
+    mut search_engine: tantivy::SearchEngine,
+    notify: Notify,
+    id: String,
+    revert: Json<json::RevertPlace>,
+) -> Result<(json::PlaceRoot, json::PlaceRevision, json::ReviewStatus)> {
+    let reverted_by = {
+        let db = db.shared()?;
+        // Only scouts and admins are entitled to revert places
+        auth.user_with_min_role(&*db, Role::Scout)?.email
+    };
+    let json::RevertPlace {
+        revision,
+        version,
+        comment,
+    } = revert.into_inner();
+    let revert = usecases::RevertPlace {
+        id: id.into(),
+        revision: revision.into(),
+        version,
+        comment,
+    };
+    let reverted = flows::revert_place(&db, &mut search_engine, &*notify, revert, &reverted_by)?;
+    let (place_root, place_revision) = reverted.place.into();
+    Ok(Json((
+        place_root.into(),
+        place_revision.into(),
+        ReviewStatus::Created.into(),
+    )))
+}
+
 #[get("/duplicates/<ids>")]
 pub fn get_duplicates(
-    connections: sqlite::Connections,
+    connections: Connections,
     search_engine: tantivy::SearchEngine,
     ids: String,
 ) -> Result<Vec<(String, String, DuplicateType)>> {
@@ -223,7 +349,7 @@ fn get_api() -> Content<&'static str> {
 
 #[post("/login", format = "application/json", data = "<login>")]
 fn post_login(
-    db: sqlite::Connections,
+    db: Connections,
     mut cookies: Cookies,
     login: Json<json::Credentials>,
     jwt_state: State<jwt::JwtState>,
@@ -273,21 +399,22 @@ struct ConfirmationToken {
     format = "application/json",
     data = "<token>"
 )]
-fn confirm_email_address(db: sqlite::Connections, token: Json<ConfirmationToken>) -> Result<()> {
+fn confirm_email_address(db: Connections, token: Json<ConfirmationToken>) -> Result<()> {
     let token = token.into_inner().token;
     usecases::confirm_email_address(&*db.exclusive()?, &token)?;
     Ok(Json(()))
 }
 
 #[post(
-    "/subscribe-to-bbox",
+    "/subscribe-to-bbox?<locale>",
     format = "application/json",
     data = "<coordinates>"
 )]
 fn subscribe_to_bbox(
-    db: sqlite::Connections,
+    db: Connections,
     auth: Auth,
     coordinates: Json<Vec<json::Coordinate>>,
+    locale: Option<String>,
 ) -> Result<()> {
     let sw_ne: Vec<_> = coordinates
         .into_inner()
@@ -298,13 +425,14 @@ fn subscribe_to_bbox(
         return Err(Error::Parameter(ParameterError::Bbox).into());
     }
     let bbox = geo::MapBbox::new(sw_ne[0], sw_ne[1]);
+    let locale = locale.as_deref().map(usecases::parse_locale).transpose()?;
     let email = auth.account_email()?;
-    usecases::subscribe_to_bbox(&*db.exclusive()?, email.to_string(), bbox)?;
+    usecases::subscribe_to_bbox(&*db.exclusive()?, email.to_string(), bbox, locale)?;
     Ok(Json(()))
 }
 
 #[delete("/unsubscribe-all-bboxes")]
-fn unsubscribe_all_bboxes(db: sqlite::Connections, auth: Auth) -> Result<()> {
+fn unsubscribe_all_bboxes(db: Connections, auth: Auth) -> Result<()> {
     let email = auth.account_email()?;
     usecases::unsubscribe_all_bboxes(&*db.exclusive()?, email)?;
     Ok(Json(()))
@@ -312,31 +440,91 @@ fn unsubscribe_all_bboxes(db: sqlite::Connections, auth: Auth) -> Result<()> {
 
 #[get("/bbox-subscriptions")]
 fn get_bbox_subscriptions(
-    db: sqlite::Connections,
+    db: Connections,
     account: Account,
 ) -> Result<Vec<json::BboxSubscription>> {
     let email = account.email();
     let user_subscriptions = usecases::get_bbox_subscriptions(&*db.shared()?, &email)?
         .into_iter()
-        .map(|s| json::BboxSubscription {
-            id: s.id.into(),
-            south_west_lat: s.bbox.southwest().lat().to_deg(),
-            south_west_lng: s.bbox.southwest().lng().to_deg(),
-            north_east_lat: s.bbox.northeast().lat().to_deg(),
-            north_east_lng: s.bbox.northeast().lng().to_deg(),
-        })
+        .map(Into::into)
         .collect();
     Ok(Json(user_subscriptions))
 }
 
+#[post(
+    "/bbox-subscriptions",
+    format = "application/json",
+    data = "<new_subscription>"
+)]
+fn post_bbox_subscription(
+    db: Connections,
+    account: Account,
+    new_subscription: Json<json::NewBboxSubscription>,
+) -> Result<json::BboxSubscription> {
+    let new_subscription = usecases::NewBboxSubscription::try_from(new_subscription.into_inner())
+        .map_err(Error::Parameter)?;
+    let subscription =
+        usecases::create_bbox_subscription(&*db.exclusive()?, account.email(), new_subscription)?;
+    Ok(Json(subscription.into()))
+}
+
+#[delete("/bbox-subscriptions/<id>")]
+fn delete_bbox_subscription(db: Connections, account: Account, id: String) -> Result<()> {
+    usecases::delete_bbox_subscription(&*db.exclusive()?, account.email(), &id.into())?;
+    Ok(Json(()))
+}
+
+const UNSUBSCRIBE_CONFIRMATION_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><title>Unsubscribe</title></head>
+<body>
+<p>Do you really want to cancel this subscription?</p>
+<form method="post"><button type="submit">Unsubscribe</button></form>
+</body>
+</html>
+"#;
+
+const UNSUBSCRIBED_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><title>Unsubscribed</title></head>
+<body>
+<p>The subscription has been cancelled.</p>
+</body>
+</html>
+"#;
+
+// The unsubscribe links in e-mails are opened without
+// being logged in. Mail clients and link scanners follow
+// links on their own, so opening a link only shows a
+// confirmation form that posts back to the same URL.
+#[get("/bbox-subscriptions/<id>/unsubscribe")]
+fn get_unsubscribe_bbox(
+    db: Connections,
+    id: String,
+) -> result::Result<Content<&'static str>, AppError> {
+    db.shared()?.get_bbox_subscription(&id.into())?;
+    Ok(Content(ContentType::HTML, UNSUBSCRIBE_CONFIRMATION_PAGE))
+}
+
+// Also used for one-click unsubscribing (RFC 8058) by mail
+// clients that evaluate the `List-Unsubscribe` header.
+#[post("/bbox-subscriptions/<id>/unsubscribe")]
+fn post_unsubscribe_bbox(
+    db: Connections,
+    id: String,
+) -> result::Result<Content<&'static str>, AppError> {
+    usecases::unsubscribe_bbox(&*db.exclusive()?, &id.into())?;
+    Ok(Content(ContentType::HTML, UNSUBSCRIBED_PAGE))
+}
+
 #[get("/tags")]
-fn get_tags(connections: sqlite::Connections) -> Result<Vec<String>> {
+fn get_tags(connections: Connections) -> Result<Vec<String>> {
     let tags = connections.shared()?.all_tags()?;
     Ok(Json(tags.into_iter().map(|t| t.id).collect()))
 }
 
 #[get("/categories")]
-fn get_categories(connections: sqlite::Connections) -> Result<Vec<json::Category>> {
+fn get_categories(connections: Connections) -> Result<Vec<json::Category>> {
     let categories = connections
         .shared()?
         .all_categories()?
@@ -347,7 +535,7 @@ fn get_categories(connections: sqlite::Connections) -> Result<Vec<json::Category
 }
 
 #[get("/categories/<ids>")]
-fn get_category(connections: sqlite::Connections, ids: String) -> Result<Vec<json::Category>> {
+fn get_category(connections: Connections, ids: String) -> Result<Vec<json::Category>> {
     // TODO: Only lookup and return a single entity
     // TODO: Add a new method for searching multiple ids
     let uids = util::split_ids(&ids);
@@ -366,18 +554,18 @@ fn get_category(connections: sqlite::Connections, ids: String) -> Result<Vec<jso
 
 #[get("/export/entries.csv?<query..>")]
 fn entries_csv_export(
-    connections: sqlite::Connections,
+    connections: Connections,
     search_engine: tantivy::SearchEngine,
     auth: Auth,
     query: Form<search::SearchQuery>,
 ) -> result::Result<Content<String>, AppError> {
-    let db = connections.shared()?;
-
-    let moderated_tags = match auth.organization(&*db) {
+    let moderated_tags = match auth.organization(&connections, ApiTokenScope::PlacesRead) {
         Ok(org) => org.moderated_tags,
         _ => vec![],
     };
 
+    let db = connections.shared()?;
+
     let user = auth.user_with_min_role(&*db, Role::Scout)?;
 
     let (req, limit) = search::parse_search_query(&query)?;
@@ -458,6 +646,7 @@ impl<'r> Responder<'r> for AppError {
                         ParameterError::Forbidden | ParameterError::ModeratedTag => {
                             Status::Forbidden
                         }
+                        ParameterError::ImageTooLarge => Status::PayloadTooLarge,
                         _ => Status::BadRequest,
                     });
                 }
@@ -0,0 +1,133 @@
+use super::*;
+
+// All organizations are managed by admins
+
+#[get("/organizations")]
+pub fn get_organizations(db: Connections, auth: Auth) -> Result<Vec<json::Organization>> {
+    let db = db.shared()?;
+    auth.user_with_min_role(&*db, Role::Admin)?;
+    let orgs = db.all_orgs()?;
+    Ok(Json(orgs.into_iter().map(Into::into).collect()))
+}
+
+#[post("/organizations", format = "application/json", data = "<new_org>")]
+pub fn post_organization(
+    db: Connections,
+    auth: Auth,
+    new_org: Json<json::NewOrganization>,
+) -> Result<json::Organization> {
+    auth.user_with_min_role(&*db.shared()?, Role::Admin)?;
+    let (org, secret) =
+        usecases::create_organization(&mut *db.exclusive()?, new_org.into_inner().into())?;
+    Ok(Json(json::Organization {
+        api_token: Some(secret),
+        ..org.into()
+    }))
+}
+
+#[get("/organizations/<id>")]
+pub fn get_organization(db: Connections, auth: Auth, id: String) -> Result<json::Organization> {
+    let db = db.shared()?;
+    auth.user_with_min_role(&*db, Role::Admin)?;
+    let org = db.get_org(&id.into())?;
+    Ok(Json(org.into()))
+}
+
+#[put("/organizations/<id>", format = "application/json", data = "<update>")]
+pub fn put_organization(
+    db: Connections,
+    auth: Auth,
+    id: String,
+    update: Json<json::UpdateOrganization>,
+) -> Result<json::Organization> {
+    auth.user_with_min_role(&*db.shared()?, Role::Admin)?;
+    let org = usecases::update_organization(
+        &mut *db.exclusive()?,
+        &id.into(),
+        update.into_inner().into(),
+    )?;
+    Ok(Json(org.into()))
+}
+
+#[delete("/organizations/<id>")]
+pub fn delete_organization(db: Connections, auth: Auth, id: String) -> Result<()> {
+    auth.user_with_min_role(&*db.shared()?, Role::Admin)?;
+    usecases::delete_organization(&mut *db.exclusive()?, &id.into())?;
+    Ok(Json(()))
+}
+
+#[post(
+    "/organizations/<id>/moderated-tags",
+    format = "application/json",
+    data = "<tag>"
+)]
+pub fn post_moderated_tag(
+    db: Connections,
+    auth: Auth,
+    id: String,
+    tag: Json<json::ModeratedTag>,
+) -> Result<json::Organization> {
+    auth.user_with_min_role(&*db.shared()?, Role::Admin)?;
+    let org =
+        usecases::add_moderated_tag(&mut *db.exclusive()?, &id.into(), tag.into_inner().into())?;
+    Ok(Json(org.into()))
+}
+
+#[delete("/organizations/<id>/moderated-tags/<label>")]
+pub fn delete_moderated_tag(
+    db: Connections,
+    auth: Auth,
+    id: String,
+    label: String,
+) -> Result<json::Organization> {
+    auth.user_with_min_role(&*db.shared()?, Role::Admin)?;
+    let org = usecases::remove_moderated_tag(&mut *db.exclusive()?, &id.into(), &label)?;
+    Ok(Json(org.into()))
+}
+
+#[get("/organizations/<id>/entries")]
+pub fn get_organization_entries(
+    db: Connections,
+    auth: Auth,
+    id: String,
+) -> Result<json::OrganizationEntries> {
+    let db = db.shared()?;
+    auth.user_with_min_role(&*db, Role::Admin)?;
+    let entries = usecases::load_owned_entries(&*db, &id.into())?;
+    Ok(Json(entries.into()))
+}
+
+#[get("/organizations/<id>/api-tokens")]
+pub fn get_api_tokens(db: Connections, auth: Auth, id: String) -> Result<Vec<json::ApiToken>> {
+    let db = db.shared()?;
+    auth.user_with_min_role(&*db, Role::Admin)?;
+    let tokens = usecases::load_api_tokens(&*db, &id.into())?;
+    Ok(Json(tokens.into_iter().map(Into::into).collect()))
+}
+
+#[post(
+    "/organizations/<id>/api-tokens",
+    format = "application/json",
+    data = "<new_token>"
+)]
+pub fn post_api_token(
+    db: Connections,
+    auth: Auth,
+    id: String,
+    new_token: Json<json::NewApiToken>,
+) -> Result<json::ApiToken> {
+    auth.user_with_min_role(&*db.shared()?, Role::Admin)?;
+    let (token, secret) =
+        usecases::create_api_token(&*db.exclusive()?, &id.into(), new_token.into_inner().into())?;
+    Ok(Json(json::ApiToken {
+        token: Some(secret),
+        ..token.into()
+    }))
+}
+
+#[delete("/organizations/<id>/api-tokens/<token_id>")]
+pub fn delete_api_token(db: Connections, auth: Auth, id: String, token_id: String) -> Result<()> {
+    auth.user_with_min_role(&*db.shared()?, Role::Admin)?;
+    usecases::revoke_api_token(&*db.exclusive()?, &id.into(), &token_id.into())?;
+    Ok(Json(()))
+}
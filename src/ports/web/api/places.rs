@@ -1,27 +1,23 @@
 use super::*;
 
 #[get("/places/clearance/count")]
-pub fn count_pending_clearances(db: sqlite::Connections, auth: Auth) -> Result<json::ResultCount> {
-    let db = db.shared()?;
-    let count =
-        usecases::clearance::place::count_pending_clearances(&*db, &auth.organization(&*db)?)?;
+pub fn count_pending_clearances(db: Connections, auth: Auth) -> Result<json::ResultCount> {
+    let org = auth.organization(&db, ApiTokenScope::PlacesClearance)?;
+    let count = usecases::clearance::place::count_pending_clearances(&*db.shared()?, &org)?;
     Ok(Json(json::ResultCount { count }))
 }
 
 #[get("/places/clearance?<offset>&<limit>")]
 pub fn list_pending_clearances(
-    db: sqlite::Connections,
+    db: Connections,
     auth: Auth,
     offset: Option<u64>,
     limit: Option<u64>,
 ) -> Result<Vec<json::PendingClearanceForPlace>> {
     let pagination = Pagination { offset, limit };
-    let db = db.shared()?;
-    let pending_clearances = usecases::clearance::place::list_pending_clearances(
-        &*db,
-        &auth.organization(&*db)?,
-        &pagination,
-    )?;
+    let org = auth.organization(&db, ApiTokenScope::PlacesClearance)?;
+    let pending_clearances =
+        usecases::clearance::place::list_pending_clearances(&*db.shared()?, &org, &pagination)?;
     Ok(Json(
         pending_clearances.into_iter().map(Into::into).collect(),
     ))
@@ -29,7 +25,7 @@ pub fn list_pending_clearances(
 
 #[post("/places/clearance", data = "<clearances>")]
 pub fn update_pending_clearances(
-    db: sqlite::Connections,
+    db: Connections,
     auth: Auth,
     clearances: Json<Vec<json::ClearanceForPlace>>,
 ) -> Result<json::ResultCount> {
@@ -38,9 +34,10 @@ pub fn update_pending_clearances(
         .into_iter()
         .map(Into::into)
         .collect();
+    let org = auth.organization(&db, ApiTokenScope::PlacesClearance)?;
     let count = usecases::clearance::place::update_pending_clearances(
         &*db.exclusive()?,
-        &auth.organization(&*db.shared()?)?,
+        &org,
         &clearances,
     )?;
     Ok(Json(json::ResultCount {
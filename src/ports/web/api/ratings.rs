@@ -4,7 +4,7 @@ use crate::{core::util, infrastructure::flows::prelude as flows};
 
 #[post("/ratings", format = "application/json", data = "<data>")]
 pub fn post_rating(
-    connections: sqlite::Connections,
+    connections: Connections,
     mut search_engine: tantivy::SearchEngine,
     data: Json<usecases::NewPlaceRating>,
 ) -> Result<()> {
@@ -13,7 +13,7 @@ pub fn post_rating(
 }
 
 #[get("/ratings/<ids>")]
-pub fn load_rating(db: sqlite::Connections, ids: String) -> Result<Vec<json::Rating>> {
+pub fn load_rating(db: Connections, ids: String) -> Result<Vec<json::Rating>> {
     // TODO: RESTful API
     //   - Only lookup and return a single entity
     //   - Add a new action and method for getting multiple ids at once
@@ -1,17 +1,19 @@
 use crate::{
-    adapters::json,
+    adapters::{geojson, json},
     core::{
         prelude::*,
         usecases,
         util::{self, geo},
     },
     infrastructure::{
-        db::{sqlite, tantivy},
+        db::{tantivy, Connections},
         error::AppError,
     },
 };
 
-use rocket::{self, request::Form};
+use chrono::{Datelike, Local, NaiveDateTime, TimeZone, Timelike};
+use ofdb_core::{cluster::ClusterGrid, opening_hours::MinuteOfWeek};
+use rocket::{self, http::ContentType, request::Form, response::content::Content};
 use rocket_contrib::json::Json;
 use std::result;
 
@@ -25,6 +27,35 @@ pub struct SearchQuery {
     text: Option<String>,
     status: Option<String>,
     limit: Option<usize>,
+    open_at: Option<i64>,
+    open_now: Option<bool>,
+    utc_offset: Option<i32>,
+}
+
+// The maximum offset of local time zones from UTC in minutes
+const MAX_UTC_OFFSET: i32 = 14 * 60;
+
+// The opening hours of places are given in local time that is
+// calculated from the time stamp (in seconds) and the offset from
+// UTC (in minutes).
+fn open_at_local_time(timestamp: i64, utc_offset: i32) -> Option<MinuteOfWeek> {
+    if utc_offset.abs() > MAX_UTC_OFFSET {
+        return None;
+    }
+    let local =
+        NaiveDateTime::from_timestamp_opt(timestamp.checked_add(i64::from(utc_offset) * 60)?, 0)?;
+    Some(MinuteOfWeek::new(
+        local.weekday().num_days_from_monday(),
+        local.hour(),
+        local.minute(),
+    ))
+}
+
+// Without an explicit offset the local time zone of the server,
+// e.g. as configured by `TZ`, is used.
+fn server_utc_offset(timestamp: i64) -> Option<i32> {
+    let local = Local.timestamp_opt(timestamp, 0).single()?;
+    Some(local.offset().local_minus_utc() / 60)
 }
 
 pub fn parse_search_query(
@@ -39,6 +70,9 @@ pub fn parse_search_query(
         text,
         status,
         limit,
+        open_at,
+        open_now,
+        utc_offset,
     } = query;
 
     let bbox = bbox
@@ -80,6 +114,24 @@ pub fn parse_search_query(
         })
         .collect();
 
+    // An explicit time takes precedence over the current time
+    let open_at = open_at
+        .or_else(|| {
+            if open_now.unwrap_or(false) {
+                Some(Timestamp::now().into_inner())
+            } else {
+                None
+            }
+        })
+        .map(|timestamp| {
+            utc_offset
+                .or_else(|| server_utc_offset(timestamp))
+                .and_then(|utc_offset| open_at_local_time(timestamp, utc_offset))
+                .ok_or(ParameterError::DateTimeOutOfRange)
+        })
+        .transpose()
+        .map_err(Error::Parameter)?;
+
     Ok((
         usecases::SearchRequest {
             bbox,
@@ -89,6 +141,7 @@ pub fn parse_search_query(
             hash_tags,
             text,
             status,
+            open_at,
         },
         *limit,
     ))
@@ -99,38 +152,41 @@ type Result<T> = result::Result<Json<T>, AppError>;
 const DEFAULT_RESULT_LIMIT: usize = 100;
 const MAX_RESULT_LIMIT: usize = 500;
 
-#[get("/search?<query..>")]
 #[allow(clippy::absurd_extreme_comparisons)]
-pub fn get_search(
-    connections: sqlite::Connections,
-    search_engine: tantivy::SearchEngine,
-    query: Form<SearchQuery>,
-) -> Result<json::SearchResponse> {
-    let query = query.into_inner();
-    let (req, limit) = parse_search_query(&query)?;
-
-    let limit = if let Some(limit) = limit {
+fn result_limit(limit: Option<usize>) -> result::Result<usize, AppError> {
+    if let Some(limit) = limit {
         if limit > MAX_RESULT_LIMIT {
             info!(
                 "Requested limit {} exceeds maximum limit {} for search results",
                 limit, MAX_RESULT_LIMIT
             );
-            MAX_RESULT_LIMIT
+            Ok(MAX_RESULT_LIMIT)
         } else if limit <= 0 {
             warn!("Invalid search limit: {}", limit);
-            return Err(AppError::Business(Error::Parameter(
+            Err(AppError::Business(Error::Parameter(
                 ParameterError::InvalidLimit,
-            )));
+            )))
         } else {
-            limit
+            Ok(limit)
         }
     } else {
         info!(
             "No limit requested - Using default limit {} for search results",
             DEFAULT_RESULT_LIMIT
         );
-        DEFAULT_RESULT_LIMIT
-    };
+        Ok(DEFAULT_RESULT_LIMIT)
+    }
+}
+
+#[get("/search?<query..>")]
+pub fn get_search(
+    connections: Connections,
+    search_engine: tantivy::SearchEngine,
+    query: Form<SearchQuery>,
+) -> Result<json::SearchResponse> {
+    let query = query.into_inner();
+    let (req, limit) = parse_search_query(&query)?;
+    let limit = result_limit(limit)?;
 
     let (visible, invisible) =
         usecases::search(&*connections.shared()?, &search_engine, req, limit)?;
@@ -142,6 +198,54 @@ pub fn get_search(
     Ok(Json(json::SearchResponse { visible, invisible }))
 }
 
+// Only the visible places are included in the feature collection.
+#[get("/search.geojson?<query..>")]
+pub fn get_search_geojson(
+    connections: Connections,
+    search_engine: tantivy::SearchEngine,
+    query: Form<SearchQuery>,
+) -> result::Result<Content<Json<geojson::FeatureCollection>>, AppError> {
+    let query = query.into_inner();
+    let (req, limit) = parse_search_query(&query)?;
+    let limit = result_limit(limit)?;
+
+    let (visible, _) = usecases::search(&*connections.shared()?, &search_engine, req, limit)?;
+
+    let visible: Vec<json::PlaceSearchResult> = visible.into_iter().map(Into::into).collect();
+
+    Ok(Content(
+        ContentType::new("application", "geo+json"),
+        Json(visible.into()),
+    ))
+}
+
+// Limits the number of places that are aggregated into clusters
+const MAX_CLUSTERED_PLACES: usize = 100_000;
+
+#[get("/search/clusters?<zoom>&<query..>")]
+pub fn get_search_clusters(
+    connections: Connections,
+    search_engine: tantivy::SearchEngine,
+    zoom: u8,
+    query: Form<SearchQuery>,
+) -> Result<Vec<json::PlaceCluster>> {
+    let query = query.into_inner();
+    // The limit only applies to individual search results
+    let (req, _) = parse_search_query(&query)?;
+
+    let clusters = usecases::search_clusters(
+        &*connections.shared()?,
+        &search_engine,
+        req,
+        ClusterGrid::with_zoom(zoom),
+        MAX_CLUSTERED_PLACES,
+    )?;
+
+    Ok(Json(
+        clusters.into_iter().map(json::place_cluster).collect(),
+    ))
+}
+
 #[post("/search/duplicates", data = "<body>")]
 pub fn post_search_duplicates(
     search_engine: tantivy::SearchEngine,
@@ -11,24 +11,44 @@ pub mod prelude {
         },
     };
     use crate::{
-        infrastructure::db::{sqlite, tantivy},
+        infrastructure::db::{tantivy, Connections},
         ports::web::{self, api},
     };
     use ofdb_core::gateways::notify::NotificationGateway;
 
-    pub fn setup() -> (Client, sqlite::Connections) {
+    pub fn setup() -> (Client, Connections) {
         let (client, conn, _) = web::tests::setup(vec![("/", api::routes())]);
         (client, conn)
     }
 
     pub fn setup2() -> (
         Client,
-        sqlite::Connections,
+        Connections,
         tantivy::SearchEngine,
         impl NotificationGateway,
     ) {
         let (client, connections, search_engine) = web::tests::setup(vec![("/", api::routes())]);
-        (client, connections, search_engine, DummyNotifyGW {})
+        (client, connections, search_engine, DummyNotifyGw {})
+    }
+
+    /// Creates an API token with all scopes for an organization.
+    pub fn create_api_token(db: &Connections, org_id: &str, token: &str) {
+        use crate::core::entities::{ApiToken, ApiTokenScope, Id, TimestampMs};
+        use strum::IntoEnumIterator;
+        db.exclusive()
+            .unwrap()
+            .create_api_token(&ApiToken {
+                id: Id::new(),
+                org_id: org_id.into(),
+                name: token.into(),
+                token_hash: ApiToken::hash_token(token),
+                scopes: ApiTokenScope::iter().collect(),
+                created_at: TimestampMs::now(),
+                expires_at: None,
+                last_used_at: None,
+                revoked_at: None,
+            })
+            .unwrap();
     }
 
     pub fn test_json(r: &Response) {
@@ -70,9 +90,9 @@ fn create_place_with_reserved_tag() {
             id: "a".into(),
             name: "a".into(),
             moderated_tags: vec!["a".into()],
-            api_token: "a".into(),
         })
         .unwrap();
+    create_api_token(&db, "a", "a");
     let cookie = get_captcha_cookie(&client).unwrap();
     let res = client.post("/entries")
                     .header(ContentType::JSON)
@@ -267,6 +287,7 @@ fn default_new_entry() -> usecases::NewPlace {
         license: "CC0-1.0".into(),
         image_url: None,
         image_link_url: None,
+        image_id: None,
         custom_links: vec![],
     }
 }
@@ -358,6 +379,132 @@ fn search_with_categories_and_bbox() {
     assert!(!body_str.contains(&format!("\"{}\"", place_ids[2])));
 }
 
+#[test]
+fn search_geojson() {
+    let entries = vec![
+        new_entry_with_category(Category::ID_NON_PROFIT, 1.0, 1.0),
+        new_entry_with_category(Category::ID_COMMERCIAL, 3.0, 3.0),
+    ];
+    let (client, connections, mut search_engine, notify) = setup2();
+    let place_ids: Vec<_> = entries
+        .into_iter()
+        .map(|e| {
+            flows::create_place(&connections, &mut search_engine, &notify, e, None, None)
+                .unwrap()
+                .id
+                .to_string()
+        })
+        .collect();
+
+    let req = client.get("/search.geojson?bbox=0.5,0.5,2.0,2.0");
+    let mut response = req.dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.content_type(),
+        Some(ContentType::new("application", "geo+json"))
+    );
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&body_str).unwrap();
+    assert_eq!("FeatureCollection", json["type"]);
+    let features = json["features"].as_array().unwrap();
+    assert_eq!(1, features.len());
+    assert_eq!(place_ids[0], features[0]["id"]);
+    assert_eq!("Point", features[0]["geometry"]["type"]);
+    assert_eq!(
+        serde_json::json!([Category::ID_NON_PROFIT]),
+        features[0]["properties"]["categories"]
+    );
+}
+
+#[test]
+fn search_clusters() {
+    let entries = vec![
+        new_entry_with_category(Category::ID_NON_PROFIT, 1.0, 1.0),
+        new_entry_with_category(Category::ID_NON_PROFIT, 1.2, 1.2),
+        new_entry_with_category(Category::ID_COMMERCIAL, 1.1, 1.1),
+        new_entry_with_category(Category::ID_COMMERCIAL, 8.0, 8.0),
+    ];
+    let (client, connections, mut search_engine, notify) = setup2();
+    for e in entries {
+        flows::create_place(&connections, &mut search_engine, &notify, e, None, None).unwrap();
+    }
+
+    let mut response = client
+        .get("/search/clusters?bbox=-10,-10,10,10&zoom=4")
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    test_json(&response);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let clusters: Vec<json::PlaceCluster> = serde_json::from_str(&body_str).unwrap();
+    assert_eq!(2, clusters.len());
+    assert_eq!(3, clusters[0].count);
+    assert_eq!(Some(Category::ID_NON_PROFIT.into()), clusters[0].category);
+    assert!((clusters[0].centroid.lat - 1.1).abs() < 1e-6);
+    assert!((clusters[0].bbox.sw.lng - 1.0).abs() < 1e-6);
+    assert!((clusters[0].bbox.ne.lng - 1.2).abs() < 1e-6);
+    assert_eq!(1, clusters[1].count);
+    assert_eq!(Some(Category::ID_COMMERCIAL.into()), clusters[1].category);
+
+    let mut response = client
+        .get(format!(
+            "/search/clusters?bbox=-10,-10,10,10&zoom=0&categories={}",
+            Category::ID_COMMERCIAL
+        ))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let clusters: Vec<json::PlaceCluster> = serde_json::from_str(&body_str).unwrap();
+    assert_eq!(1, clusters.len());
+    assert_eq!(2, clusters[0].count);
+}
+
+#[test]
+fn get_vector_tile() {
+    let (client, connections, mut search_engine, notify) = setup2();
+    let place_id = flows::create_place(
+        &connections,
+        &mut search_engine,
+        &notify,
+        new_entry_with_category(Category::ID_NON_PROFIT, 1.0, 1.0),
+        None,
+        None,
+    )
+    .unwrap()
+    .id;
+
+    // North-east quadrant
+    let mut response = client.get("/tiles/1/1/0.mvt").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.content_type(),
+        Some(ContentType::new("application", "vnd.mapbox-vector-tile"))
+    );
+    let body = response.body_bytes().unwrap();
+    assert!(body
+        .windows(place_id.as_str().len())
+        .any(|w| w == place_id.as_str().as_bytes()));
+
+    // South-west corner (the buffer of tiles that adjoin the
+    // place would include it)
+    let mut response = client.get("/tiles/2/0/3.mvt").dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response.body_bytes().unwrap_or_default().is_empty());
+
+    let mut response = client
+        .get(format!(
+            "/tiles/1/1/0.mvt?categories={}",
+            Category::ID_COMMERCIAL
+        ))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response.body_bytes().unwrap_or_default().is_empty());
+
+    let response = client.get("/tiles/1/2/0.mvt").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    let response = client.get("/tiles/1/1/0.pbf").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+}
+
 fn new_entry_with_text(title: &str, description: &str, lat: f64, lng: f64) -> usecases::NewPlace {
     usecases::NewPlace {
         title: title.into(),
@@ -616,6 +763,48 @@ fn search_with_city() {
     assert!(body_str.contains(&format!("\"{}\"", place_ids[2])));
 }
 
+#[test]
+fn search_with_inflections_and_typos() {
+    let entries = vec![
+        new_entry_with_text("Bäckerei Müller", "Brötchen und Kuchen", 1.0, 1.0),
+        new_entry_with_text("Fahrradladen", "Repairing bikes", 2.0, 2.0),
+    ];
+    let (client, connections, mut search_engine, notify) = setup2();
+    let place_ids: Vec<_> = entries
+        .into_iter()
+        .map(|e| {
+            flows::create_place(&connections, &mut search_engine, &notify, e, None, None)
+                .unwrap()
+                .id
+                .to_string()
+        })
+        .collect();
+
+    let search = |text: &str| {
+        let mut response = client
+            .get(format!("/search?bbox=-10,-10,10,10&text={}", text))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        response.body().and_then(|b| b.into_string()).unwrap()
+    };
+
+    // Diacritics, plurals, transliterated umlauts and typos
+    for &text in &["Backerei", "B%C3%A4ckereien", "Baeckerei", "Bakerei"] {
+        let body_str = search(text);
+        assert!(body_str.contains(&format!("\"{}\"", place_ids[0])));
+        assert!(!body_str.contains(&format!("\"{}\"", place_ids[1])));
+    }
+
+    // English stemming
+    let body_str = search("repair%20bike");
+    assert!(!body_str.contains(&format!("\"{}\"", place_ids[0])));
+    assert!(body_str.contains(&format!("\"{}\"", place_ids[1])));
+
+    // Excluded terms are matched exactly
+    let body_str = search("Kuchen%20-Baeckerei");
+    assert!(body_str.contains(&format!("\"{}\"", place_ids[0])));
+}
+
 #[test]
 fn search_with_tags() {
     let entries = vec![
@@ -686,6 +875,88 @@ fn search_with_tags() {
     assert!(body_str.contains(&format!("\"{}\"", place_ids[2])));
 }
 
+#[test]
+fn search_places_open_at() {
+    let (client, connections, mut search_engine, notify) = setup2();
+    let place_ids: Vec<_> = vec![
+        Some("Mo-Fr 09:00-12:00; PH off"),
+        Some("Sa,Su 10:00-16:00"),
+        Some("24/7"),
+        None,
+    ]
+    .into_iter()
+    .map(|opening_hours| {
+        let e = usecases::NewPlace {
+            opening_hours: opening_hours.map(Into::into),
+            ..default_new_entry()
+        };
+        flows::create_place(&connections, &mut search_engine, &notify, e, None, None)
+            .unwrap()
+            .id
+            .to_string()
+    })
+    .collect();
+
+    let invalid = usecases::NewPlace {
+        opening_hours: Some("Mo-Fr 9-17".into()),
+        ..default_new_entry()
+    };
+    assert!(flows::create_place(
+        &connections,
+        &mut search_engine,
+        &notify,
+        invalid,
+        None,
+        None
+    )
+    .is_err());
+
+    let search_open_at = |query: &str| {
+        let mut response = client
+            .get(format!("/search?bbox=-10,-10,10,10&{}", query))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+        place_ids
+            .iter()
+            .map(|id| body_str.contains(&format!("\"{}\"", id)))
+            .collect::<Vec<_>>()
+    };
+
+    // Monday, 2021-03-08 10:00 UTC
+    assert_eq!(
+        vec![true, false, true, false],
+        search_open_at("open_at=1615197600&utc_offset=0")
+    );
+    // Monday, 2021-03-08 13:00 UTC+3
+    assert_eq!(
+        vec![false, false, true, false],
+        search_open_at("open_at=1615197600&utc_offset=180")
+    );
+    // Saturday, 2021-03-13 10:00 UTC
+    assert_eq!(
+        vec![false, true, true, false],
+        search_open_at("open_at=1615629600&utc_offset=0")
+    );
+    assert_eq!(
+        vec![true, true, true, true],
+        search_open_at("open_now=false")
+    );
+    assert!(search_open_at("open_now=true&utc_offset=60")[2]);
+
+    let response = client
+        .get("/search?bbox=-10,-10,10,10&open_at=1615197600&utc_offset=1000")
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+    // The local time zone of the server is used without an offset from UTC
+    for query in &["open_at=1615197600", "open_now=true"] {
+        let response = client
+            .get(format!("/search?bbox=-10,-10,10,10&{}", query))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+    }
+}
+
 #[test]
 fn search_with_uppercase_tags() {
     let entries = vec![
@@ -1053,6 +1324,7 @@ fn search_with_status() {
         email_confirmed: true,
         password: "secret".parse::<Password>().unwrap(),
         role: Role::Admin,
+        locale: None,
     };
     connections.exclusive().unwrap().create_user(&user).unwrap();
     let response = client
@@ -1308,6 +1580,7 @@ fn login_with_valid_credentials() {
         email_confirmed: true,
         password: "secret".parse::<Password>().unwrap(),
         role: Role::Guest,
+        locale: None,
     }];
     for u in users {
         db.exclusive().unwrap().create_user(&u).unwrap();
@@ -1330,6 +1603,7 @@ fn login_logout_succeeds() {
         email_confirmed: true,
         password: "secret".parse::<Password>().unwrap(),
         role: Role::Guest,
+        locale: None,
     }];
     for u in users {
         db.exclusive().unwrap().create_user(&u).unwrap();
@@ -1363,6 +1637,7 @@ fn login_logout_succeeds_jwt() {
         email_confirmed: true,
         password: "secret".parse::<Password>().unwrap(),
         role: Role::Guest,
+        locale: None,
     }];
     for u in users {
         db.exclusive().unwrap().create_user(&u).unwrap();
@@ -1406,6 +1681,7 @@ fn confirm_email_address() {
         email_confirmed: false,
         password: "secret".parse::<Password>().unwrap(),
         role: Role::Guest,
+        locale: None,
     }];
     for u in users {
         db.exclusive().unwrap().create_user(&u).unwrap();
@@ -1460,6 +1736,7 @@ fn send_confirmation_email() {
         email_confirmed: false,
         password: "secret".parse::<Password>().unwrap(),
         role: Role::Guest,
+        locale: None,
     }];
     for u in users {
         db.exclusive().unwrap().create_user(&u).unwrap();
@@ -1473,6 +1750,97 @@ fn send_confirmation_email() {
     assert_eq!(response.status(), Status::Ok);
 }
 
+#[test]
+fn merge_places() {
+    let (client, connections, mut search_engine, notify) = setup2();
+    let ids: Vec<_> = vec!["survivor", "duplicate"]
+        .into_iter()
+        .map(|title| {
+            let new_place = usecases::NewPlace {
+                title: title.into(),
+                ..default_new_entry()
+            };
+            flows::create_place(
+                &connections,
+                &mut search_engine,
+                &notify,
+                new_place,
+                None,
+                None,
+            )
+            .unwrap()
+            .id
+            .to_string()
+        })
+        .collect();
+    let body = format!(
+        r#"{{"merged":"{}","version":1,"fields_from_merged":["title"]}}"#,
+        ids[1]
+    );
+
+    for (email, role) in &[("user@bar", Role::User), ("scout@bar", Role::Scout)] {
+        connections
+            .exclusive()
+            .unwrap()
+            .create_user(&User {
+                email: (*email).into(),
+                email_confirmed: true,
+                password: "secret".parse::<Password>().unwrap(),
+                role: *role,
+                locale: None,
+            })
+            .unwrap();
+    }
+    let login = |email: &str| {
+        let response = client
+            .post("/login")
+            .header(ContentType::JSON)
+            .body(format!(r#"{{"email": "{}", "password": "secret"}}"#, email))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+    };
+
+    // Only scouts and admins are allowed to merge places
+    login("user@bar");
+    let response = client
+        .post(format!("/places/{}/merge", ids[0]))
+        .header(ContentType::JSON)
+        .body(&body)
+        .dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+
+    login("scout@bar");
+    let mut response = client
+        .post(format!("/places/{}/merge", ids[0]))
+        .header(ContentType::JSON)
+        .body(&body)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    // Place revisions are serialized with abbreviated keys
+    assert!(body_str.contains(r#""tit":"duplicate""#));
+
+    // The merged place points to the survivor
+    let mut response = client.get(format!("/places/{}", ids[1])).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    assert!(body_str.contains(&format!(r#""id":"{}""#, ids[0])));
+    let mut response = client.get(format!("/entries/{}", ids[1])).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let entries: Vec<json::Entry> = serde_json::from_str(&body_str).unwrap();
+    assert_eq!(1, entries.len());
+    assert_eq!(ids[0], entries[0].id);
+
+    // The merged place cannot be merged again
+    let response = client
+        .post(format!("/places/{}/merge", ids[0]))
+        .header(ContentType::JSON)
+        .body(body.replace(r#""version":1"#, r#""version":2"#))
+        .dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+}
+
 #[test]
 fn subscribe_to_bbox() {
     let (client, db) = setup();
@@ -1481,6 +1849,7 @@ fn subscribe_to_bbox() {
         email_confirmed: true,
         password: "secret".parse::<Password>().unwrap(),
         role: Role::Guest,
+        locale: None,
     }];
     for u in users {
         db.exclusive().unwrap().create_user(&u).unwrap();
@@ -1494,10 +1863,157 @@ fn subscribe_to_bbox() {
     let response = client
         .post("/subscribe-to-bbox")
         .header(ContentType::JSON)
+        .cookie(cookie.clone())
+        .body(r#"[{"lat":-10.0,"lng":-10.0},{"lat":10.0,"lng":10.0}]"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+
+    // Notifications in the preferred language
+    let response = client
+        .post("/subscribe-to-bbox?locale=en_gb")
+        .header(ContentType::JSON)
+        .cookie(cookie.clone())
+        .body(r#"[{"lat":-10.0,"lng":-10.0},{"lat":10.0,"lng":10.0}]"#)
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let mut response = client
+        .get("/bbox-subscriptions")
+        .cookie(cookie.clone())
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    assert!(body_str.contains(r#""locale":"en-GB""#));
+
+    let response = client
+        .post("/subscribe-to-bbox?locale=..%2Fen")
+        .header(ContentType::JSON)
         .cookie(cookie)
         .body(r#"[{"lat":-10.0,"lng":-10.0},{"lat":10.0,"lng":10.0}]"#)
         .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+#[test]
+fn create_and_delete_bbox_subscriptions() {
+    let (client, db) = setup();
+    for email in &["foo@bar", "baz@bar"] {
+        db.exclusive()
+            .unwrap()
+            .create_user(&User {
+                email: (*email).into(),
+                email_confirmed: true,
+                password: "secret".parse::<Password>().unwrap(),
+                role: Role::Guest,
+                locale: None,
+            })
+            .unwrap();
+    }
+    let login = |email: &str| {
+        let response = client
+            .post("/login")
+            .header(ContentType::JSON)
+            .body(format!(r#"{{"email": "{}", "password": "secret"}}"#, email))
+            .dispatch();
+        user_id_cookie(&response).unwrap()
+    };
+    let bbox = r#""south_west_lat":-10.0,"south_west_lng":-10.0,"north_east_lat":10.0,"north_east_lng":10.0"#;
+
+    // The client keeps the cookies of all responses
+    let response = client
+        .post("/bbox-subscriptions")
+        .header(ContentType::JSON)
+        .body(format!("{{{}}}", bbox))
+        .dispatch();
+    assert_eq!(response.status(), Status::Unauthorized);
+    let cookie = login("foo@bar");
+    let response = client
+        .post("/bbox-subscriptions")
+        .header(ContentType::JSON)
+        .cookie(cookie.clone())
+        .body(format!(r#"{{{},"delivery":"hourly"}}"#, bbox))
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+
+    let mut response = client
+        .post("/bbox-subscriptions")
+        .header(ContentType::JSON)
+        .cookie(cookie.clone())
+        .body(format!(
+            r##"{{{},"tags":["#Foo"],"events":true,"delivery":"weekly"}}"##,
+            bbox
+        ))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let subscription: json::BboxSubscription = serde_json::from_str(&body_str).unwrap();
+    assert_eq!(vec!["foo"], subscription.tags);
+    assert!(subscription.events);
+    assert_eq!("weekly", subscription.delivery);
+
+    // Subscriptions are added without replacing existing ones
+    let mut response = client
+        .post("/bbox-subscriptions")
+        .header(ContentType::JSON)
+        .cookie(cookie.clone())
+        .body(format!("{{{}}}", bbox))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    let other: json::BboxSubscription = serde_json::from_str(&body_str).unwrap();
+    assert!(!other.events);
+    assert_eq!("immediate", other.delivery);
+    assert_eq!(
+        2,
+        db.shared()
+            .unwrap()
+            .all_bbox_subscriptions_by_email("foo@bar")
+            .unwrap()
+            .len()
+    );
+
+    // Only the owner is allowed to delete a subscription
+    let response = client
+        .delete(format!("/bbox-subscriptions/{}", subscription.id))
+        .cookie(login("baz@bar"))
+        .dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    let response = client
+        .delete(format!("/bbox-subscriptions/{}", subscription.id))
+        .cookie(cookie)
+        .dispatch();
     assert_eq!(response.status(), Status::Ok);
+
+    // Unsubscribe links don't require a login and only
+    // show a confirmation form
+    let response = client
+        .get(format!("/bbox-subscriptions/{}/unsubscribe", other.id))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.content_type(), Some(ContentType::HTML));
+    assert_eq!(
+        1,
+        db.shared()
+            .unwrap()
+            .all_bbox_subscriptions_by_email("foo@bar")
+            .unwrap()
+            .len()
+    );
+    let response = client
+        .get("/bbox-subscriptions/does-not-exist/unsubscribe")
+        .dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+
+    // The confirmation is posted without a body
+    let response = client
+        .post(format!("/bbox-subscriptions/{}/unsubscribe", other.id))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert!(db
+        .shared()
+        .unwrap()
+        .all_bbox_subscriptions_by_email("foo@bar")
+        .unwrap()
+        .is_empty());
 }
 
 #[test]
@@ -1636,18 +2152,21 @@ fn entries_export_csv() {
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Admin,
+            locale: None,
         },
         User {
             email: "scout@example.com".into(),
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::Scout,
+            locale: None,
         },
         User {
             email: "user@example.com".into(),
             email_confirmed: true,
             password: "secret".parse::<Password>().unwrap(),
             role: Role::User,
+            locale: None,
         },
     ];
     for u in users {
@@ -1699,6 +2218,7 @@ fn entries_export_csv() {
         homepage: Some("http://homepage1".parse().unwrap()),
         image: Some("https://img".parse().unwrap()),
         image_href: Some("https://img,link".parse().unwrap()),
+        image_id: None,
         custom: vec![CustomLink::from_url(
             "http://custom-link.org".parse().unwrap(),
         )],
@@ -1867,3 +2387,446 @@ fn search_duplicates() {
     assert_eq!(1, duplicate_places.len());
     assert_eq!(place.id.to_string(), duplicate_places.first().unwrap().id);
 }
+
+// A local HTTP stand-in for the receiver of a webhook that
+// accepts a single request and returns its body.
+fn serve_webhook_once() -> (String, std::sync::mpsc::Receiver<String>) {
+    use std::io::{BufRead, BufReader, Read, Write};
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end().to_lowercase();
+            if line.is_empty() {
+                break;
+            }
+            if let Some(value) = line.strip_prefix("content-length:") {
+                content_length = value.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        tx.send(String::from_utf8(body).unwrap()).unwrap();
+    });
+    (url, rx)
+}
+
+#[test]
+fn register_and_deliver_webhooks() {
+    use rocket::http::Header;
+    let (client, db) = setup();
+    db.exclusive()
+        .unwrap()
+        .create_org(Organization {
+            id: "foo".into(),
+            name: "bar".into(),
+            moderated_tags: vec![],
+        })
+        .unwrap();
+    create_api_token(&db, "foo", "foo");
+    let (url, rx) = serve_webhook_once();
+
+    let res = client
+        .post("/webhooks")
+        .header(ContentType::JSON)
+        .body(r#"{"url":"http://example.com","secret":"0123456789abcdef","event_types":["place_added"]}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Unauthorized);
+
+    let res = client
+        .post("/webhooks")
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", "Bearer foo"))
+        .body(r#"{"url":"http://example.com","secret":"short","event_types":["place_added"]}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+
+    let res = client
+        .post("/webhooks")
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", "Bearer foo"))
+        .body(format!(
+            r#"{{"url":"{}","secret":"0123456789abcdef","event_types":["place_added"],"bbox":{{"sw":{{"lat":-1.0,"lng":-1.0}},"ne":{{"lat":1.0,"lng":1.0}}}}}}"#,
+            url
+        ))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let mut res = client
+        .get("/webhooks")
+        .header(Header::new("Authorization", "Bearer foo"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body_str = res.body().and_then(|b| b.into_string()).unwrap();
+    let webhooks: serde_json::Value = serde_json::from_str(&body_str).unwrap();
+    assert_eq!(1, webhooks.as_array().unwrap().len());
+    assert!(webhooks[0].get("secret").is_none());
+    let webhook_id = webhooks[0]["id"].as_str().unwrap().to_string();
+
+    let cookie = get_captcha_cookie(&client).unwrap();
+    let res = client.post("/entries")
+                    .header(ContentType::JSON)
+                    .cookie(cookie)
+                    .body(r#"{"title":"foo","description":"blablabla","lat":0.0,"lng":0.0,"categories":["x"],"license":"CC0-1.0","tags":[]}"#)
+                    .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let place_id = db.shared().unwrap().all_places().unwrap()[0]
+        .0
+        .id
+        .to_string();
+
+    let payload: serde_json::Value = serde_json::from_str(&rx.recv().unwrap()).unwrap();
+    assert_eq!("place_added", payload["event"]);
+    assert_eq!(place_id, payload["data"]["id"]);
+
+    let mut res = client
+        .get(format!("/webhooks/{}/deliveries", webhook_id))
+        .header(Header::new("Authorization", "Bearer foo"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body_str = res.body().and_then(|b| b.into_string()).unwrap();
+    let deliveries: serde_json::Value = serde_json::from_str(&body_str).unwrap();
+    assert_eq!(1, deliveries.as_array().unwrap().len());
+    assert_eq!("delivered", deliveries[0]["status"]);
+    assert_eq!(200, deliveries[0]["response_status"]);
+    assert_eq!(payload["id"], deliveries[0]["id"]);
+
+    let res = client
+        .delete(format!("/webhooks/{}", webhook_id))
+        .header(Header::new("Authorization", "Bearer foo"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert!(db
+        .shared()
+        .unwrap()
+        .load_webhooks_of_org(&"foo".into())
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn recently_changed_entries_feed() {
+    let (client, db, mut search_engine, notify) = setup2();
+    let mut inside = new_entry_with_category("x", 1.0, 1.0);
+    inside.title = "Inside & tagged".into();
+    inside.tags = vec!["foo".into()];
+    let inside_id = flows::create_place(&db, &mut search_engine, &notify, inside, None, None)
+        .unwrap()
+        .id;
+    let mut outside = new_entry_with_category("x", 20.0, 20.0);
+    outside.title = "Outside".into();
+    outside.tags = vec!["foo".into()];
+    flows::create_place(&db, &mut search_engine, &notify, outside, None, None).unwrap();
+    let mut untagged = new_entry_with_category("x", 1.5, 1.5);
+    untagged.title = "Untagged".into();
+    flows::create_place(&db, &mut search_engine, &notify, untagged, None, None).unwrap();
+
+    let mut response = client
+        .get("/entries/recently-changed.atom?bbox=0,0,2,2&tag=foo")
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.headers().get("Content-Type").collect::<Vec<_>>()[0],
+        "application/atom+xml"
+    );
+    let body_str = response.body().and_then(|b| b.into_string()).unwrap();
+    assert_eq!(1, body_str.matches("<entry>").count());
+    assert!(body_str.contains(&format!(
+        "<id>tag:openfairdb.org,2021:place:{}</id>",
+        inside_id
+    )));
+    assert!(body_str.contains("<title>Inside &amp; tagged</title>"));
+    assert!(body_str.contains(r#"<category term="foo"/>"#));
+    assert!(body_str.contains(&format!(
+        r#"<link rel="alternate" href="{}{}"/>"#,
+        *crate::infrastructure::ENTRY_URL_PREFIX,
+        inside_id
+    )));
+    assert!(body_str.contains(
+        r#"<link rel="self" href="http://localhost/entries/recently-changed.atom?bbox=0,0,2,2&amp;tag=foo"/>"#
+    ));
+
+    let response = client
+        .get("/entries/recently-changed.atom?bbox=invalid")
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+}
+
+#[test]
+fn manage_organizations() {
+    use rocket::http::Header;
+    let (client, db) = setup();
+    for (email, role) in &[
+        ("admin@example.com", Role::Admin),
+        ("scout@example.com", Role::Scout),
+    ] {
+        db.exclusive()
+            .unwrap()
+            .create_user(&User {
+                email: (*email).into(),
+                email_confirmed: true,
+                password: "secret".parse::<Password>().unwrap(),
+                role: *role,
+                locale: None,
+            })
+            .unwrap();
+    }
+    let login = |email: &str| {
+        let response = client
+            .post("/login")
+            .header(ContentType::JSON)
+            .body(format!(r#"{{"email": "{}", "password": "secret"}}"#, email))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+    };
+
+    let new_org = r#"{"name":"Foo","api_token":"foo","moderated_tags":[{"label":"foo","require_clearance":true}]}"#;
+    let res = client
+        .post("/organizations")
+        .header(ContentType::JSON)
+        .body(new_org)
+        .dispatch();
+    assert_eq!(res.status(), Status::Unauthorized);
+    login("scout@example.com");
+    let res = client
+        .post("/organizations")
+        .header(ContentType::JSON)
+        .body(new_org)
+        .dispatch();
+    assert_eq!(res.status(), Status::Unauthorized);
+
+    login("admin@example.com");
+    let mut res = client
+        .post("/organizations")
+        .header(ContentType::JSON)
+        .body(new_org)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body_str = res.body().and_then(|b| b.into_string()).unwrap();
+    let org: json::Organization = serde_json::from_str(&body_str).unwrap();
+    assert_eq!("Foo", org.name);
+    // The secret of the initial API token is only returned once
+    assert_eq!(Some("foo"), org.api_token.as_deref());
+    assert!(org.moderated_tags[0].require_clearance);
+
+    let res = client
+        .put(format!("/organizations/{}", org.id))
+        .header(ContentType::JSON)
+        .body(r#"{"name":"Bar"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client
+        .post(format!("/organizations/{}/moderated-tags", org.id))
+        .header(ContentType::JSON)
+        .body(r#"{"label":"bar","allow_add":true}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client
+        .delete(format!("/organizations/{}/moderated-tags/foo", org.id))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+
+    let mut res = client.get("/organizations").dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body_str = res.body().and_then(|b| b.into_string()).unwrap();
+    let orgs: Vec<json::Organization> = serde_json::from_str(&body_str).unwrap();
+    assert_eq!(1, orgs.len());
+    assert_eq!("Bar", orgs[0].name);
+    assert!(orgs[0].api_token.is_none());
+    assert_eq!(1, orgs[0].moderated_tags.len());
+    assert_eq!("bar", orgs[0].moderated_tags[0].label);
+    assert!(orgs[0].moderated_tags[0].allow_add);
+
+    // Events that are created by the organization are tagged
+    // with its moderated tags
+    let mut res = client
+        .post("/events")
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", "Bearer foo"))
+        .body(r#"{"title":"x","start":4132508400,"created_by":"foo@bar.com"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body_str = res.body().and_then(|b| b.into_string()).unwrap();
+    let event_id: String = serde_json::from_str(&body_str).unwrap();
+    let mut res = client
+        .get(format!("/organizations/{}/entries", org.id))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body_str = res.body().and_then(|b| b.into_string()).unwrap();
+    let entries: json::OrganizationEntries = serde_json::from_str(&body_str).unwrap();
+    assert!(entries.places.is_empty());
+    assert_eq!(vec![event_id], entries.events);
+
+    let res = client
+        .delete(format!("/organizations/{}", org.id))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client.get(format!("/organizations/{}", org.id)).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+fn authorize_with_scoped_api_tokens() {
+    use rocket::http::Header;
+    let (client, db) = setup();
+    db.exclusive()
+        .unwrap()
+        .create_org(Organization {
+            id: "foo".into(),
+            name: "Foo".into(),
+            moderated_tags: vec![],
+        })
+        .unwrap();
+    create_api_token(&db, "foo", "all-scopes");
+    db.exclusive()
+        .unwrap()
+        .create_user(&User {
+            email: "admin@example.com".into(),
+            email_confirmed: true,
+            password: "secret".parse::<Password>().unwrap(),
+            role: Role::Admin,
+            locale: None,
+        })
+        .unwrap();
+    let login = client
+        .post("/login")
+        .header(ContentType::JSON)
+        .body(r#"{"email": "admin@example.com", "password": "secret"}"#)
+        .dispatch();
+    assert_eq!(login.status(), Status::Ok);
+
+    let res = client
+        .post("/organizations/foo/api-tokens")
+        .header(ContentType::JSON)
+        .body(r#"{"name":"Import","scopes":["events:foo"]}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+    let mut res = client
+        .post("/organizations/foo/api-tokens")
+        .header(ContentType::JSON)
+        .body(r#"{"name":"Import","scopes":["events:write"]}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body_str = res.body().and_then(|b| b.into_string()).unwrap();
+    let token: json::ApiToken = serde_json::from_str(&body_str).unwrap();
+    assert_eq!(vec!["events:write"], token.scopes);
+    let secret = token.token.clone().unwrap();
+    let bearer = Header::new("Authorization", format!("Bearer {}", secret));
+
+    let res = client
+        .post("/events")
+        .header(ContentType::JSON)
+        .header(bearer.clone())
+        .body(r#"{"title":"x","start":4132508400,"created_by":"foo@bar.com"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client
+        .get("/places/clearance/count")
+        .header(bearer.clone())
+        .dispatch();
+    assert_eq!(res.status(), Status::Forbidden);
+    let res = client
+        .get("/places/clearance/count")
+        .header(Header::new("Authorization", "Bearer all-scopes"))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    // Reading events without the required scope falls back
+    // to the public view
+    for path in &["/events", "/events.ics", "/events.atom"] {
+        let res = client.get(*path).header(bearer.clone()).dispatch();
+        assert_eq!(res.status(), Status::Ok);
+    }
+
+    // The secret is never returned again, but the usage is recorded
+    let mut res = client.get("/organizations/foo/api-tokens").dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body_str = res.body().and_then(|b| b.into_string()).unwrap();
+    assert!(!body_str.contains(&secret));
+    let tokens: Vec<json::ApiToken> = serde_json::from_str(&body_str).unwrap();
+    assert_eq!(2, tokens.len());
+    let scoped_token = tokens.iter().find(|t| t.id == token.id).unwrap();
+    assert!(scoped_token.last_used_at.is_some());
+    assert!(tokens.iter().all(|t| t.token.is_none()));
+
+    let res = client
+        .delete(format!("/organizations/foo/api-tokens/{}", token.id))
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let res = client
+        .post("/events")
+        .header(ContentType::JSON)
+        .header(bearer)
+        .body(r#"{"title":"x","start":4132508400,"created_by":"foo@bar.com"}"#)
+        .dispatch();
+    assert_eq!(res.status(), Status::Unauthorized);
+}
+
+#[test]
+fn upload_and_serve_images() {
+    let (client, db) = setup();
+    let img = ::image::DynamicImage::ImageRgb8(::image::RgbImage::from_pixel(
+        800,
+        400,
+        ::image::Rgb([0, 128, 0]),
+    ));
+    let mut png = vec![];
+    img.write_to(&mut png, ::image::ImageOutputFormat::Png)
+        .unwrap();
+
+    let res = client.post("/images").body(png.clone()).dispatch();
+    assert_eq!(res.status(), Status::Unauthorized);
+
+    crate::ports::web::tests::register_user(&db, "foo@bar.com", "secret", true);
+    let login = client
+        .post("/login")
+        .header(ContentType::JSON)
+        .body(r#"{"email": "foo@bar.com", "password": "secret"}"#)
+        .dispatch();
+    assert_eq!(login.status(), Status::Ok);
+
+    let res = client.post("/images").body("<svg></svg>").dispatch();
+    assert_eq!(res.status(), Status::BadRequest);
+
+    // The URLs of images are never derived from the request
+    let mut res = client
+        .post("/images")
+        .header(rocket::http::Header::new("Host", "evil.example.com"))
+        .header(rocket::http::Header::new("X-Forwarded-Proto", "https"))
+        .body(png)
+        .dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let body_str = res.body().and_then(|b| b.into_string()).unwrap();
+    let image: json::Image = serde_json::from_str(&body_str).unwrap();
+    assert_eq!((800, 400), (image.width, image.height));
+    let base_url = &*crate::infrastructure::IMAGE_BASE_URL;
+    assert_eq!(format!("{}/{}", base_url, image.id), image.url);
+    let image_path = |url: &str| format!("/images{}", url.trim_start_matches(base_url));
+
+    let mut res = client.get(image_path(&image.url)).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    assert_eq!(res.content_type(), Some(ContentType::JPEG));
+    let data = res.body_bytes().unwrap();
+    let stored = ::image::load_from_memory(&data).unwrap();
+    assert_eq!((800, 400), ::image::GenericImageView::dimensions(&stored));
+
+    let mut res = client.get(image_path(&image.thumbnails.small)).dispatch();
+    assert_eq!(res.status(), Status::Ok);
+    let data = res.body_bytes().unwrap();
+    let thumbnail = ::image::load_from_memory(&data).unwrap();
+    assert_eq!((160, 80), ::image::GenericImageView::dimensions(&thumbnail));
+
+    let res = client.get(format!("/images/{}/tiny", image.id)).dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+    let res = client.get("/images/unknown").dispatch();
+    assert_eq!(res.status(), Status::NotFound);
+}
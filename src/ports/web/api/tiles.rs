@@ -0,0 +1,93 @@
+use crate::{
+    adapters::mvt::{self, TileCoord},
+    core::{prelude::*, usecases, util},
+    infrastructure::{
+        db::{tantivy, Connections},
+        error::AppError,
+    },
+};
+
+use rocket::{
+    self,
+    http::{ContentType, RawStr},
+    request::{Form, FromParam},
+    response::content::Content,
+};
+use std::result;
+
+// Limits the size of tiles with a low zoom level
+const MAX_TILE_FEATURES: usize = 1_000;
+
+#[derive(FromForm, Clone)]
+pub struct TileQuery {
+    categories: Option<String>,
+    org_tag: Option<String>,
+    tags: Option<String>,
+    text: Option<String>,
+}
+
+// The last path segment of a tile: <y>.mvt
+pub struct MvtTileY(u32);
+
+impl<'a> FromParam<'a> for MvtTileY {
+    type Error = &'a RawStr;
+
+    fn from_param(param: &'a RawStr) -> result::Result<Self, Self::Error> {
+        param
+            .as_str()
+            .strip_suffix(".mvt")
+            .and_then(|y| y.parse().ok())
+            .map(MvtTileY)
+            .ok_or(param)
+    }
+}
+
+#[get("/tiles/<z>/<x>/<y>?<query..>")]
+pub fn get_tile(
+    connections: Connections,
+    search_engine: tantivy::SearchEngine,
+    z: u8,
+    x: u32,
+    y: MvtTileY,
+    query: Form<TileQuery>,
+) -> result::Result<Content<Vec<u8>>, AppError> {
+    // Tiles outside of the world don't exist
+    let tile = TileCoord::new(z, x, y.0).map_err(|_| RepoError::NotFound)?;
+    let TileQuery {
+        categories,
+        org_tag,
+        tags,
+        text,
+    } = query.into_inner();
+    let categories = categories
+        .as_deref()
+        .map(util::split_ids)
+        .map(|ids| {
+            ids.into_iter()
+                // Only places, not events
+                .filter(|id| id != &Category::ID_EVENT)
+                .collect()
+        })
+        .unwrap_or_default();
+    let hash_tags = tags.as_deref().map(util::split_ids).unwrap_or_default();
+    let req = usecases::SearchRequest {
+        bbox: tile.bbox(),
+        ids: vec![],
+        categories,
+        org_tag: org_tag.as_deref(),
+        hash_tags,
+        text: text.as_deref(),
+        status: vec![],
+        open_at: None,
+    };
+    let (places, _) = usecases::search(
+        &*connections.shared()?,
+        &search_engine,
+        req,
+        MAX_TILE_FEATURES,
+    )?;
+    Ok(Content(
+        ContentType::new("application", "vnd.mapbox-vector-tile"),
+        mvt::encode_places_tile(tile, places),
+    ))
+}
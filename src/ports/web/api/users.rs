@@ -1,7 +1,7 @@
 use super::*;
 
 #[post("/users", format = "application/json", data = "<u>")]
-pub fn post_user(db: sqlite::Connections, n: Notify, u: Json<usecases::NewUser>) -> Result<()> {
+pub fn post_user(db: Connections, n: Notify, u: Json<usecases::NewUser>) -> Result<()> {
     let new_user = u.into_inner();
     let user = {
         let db = db.exclusive()?;
@@ -18,7 +18,7 @@ pub fn post_user(db: sqlite::Connections, n: Notify, u: Json<usecases::NewUser>)
     data = "<data>"
 )]
 pub fn post_request_password_reset(
-    connections: sqlite::Connections,
+    connections: Connections,
     notify: Notify,
     data: Json<json::RequestPasswordReset>,
 ) -> Result<()> {
@@ -30,7 +30,7 @@ pub fn post_request_password_reset(
 
 #[post("/users/reset-password", format = "application/json", data = "<data>")]
 pub fn post_reset_password(
-    connections: sqlite::Connections,
+    connections: Connections,
     data: Json<json::ResetPassword>,
 ) -> Result<()> {
     let req = data.into_inner();
@@ -43,23 +43,42 @@ pub fn post_reset_password(
 }
 
 #[delete("/users/<email>")]
-pub fn delete_user(db: sqlite::Connections, account: Account, email: String) -> Result<()> {
+pub fn delete_user(db: Connections, account: Account, email: String) -> Result<()> {
     usecases::delete_user(&*db.exclusive()?, account.email(), &email)?;
     Ok(Json(()))
 }
 
 #[get("/users/current", format = "application/json")]
-pub fn get_current_user(db: sqlite::Connections, account: Account) -> Result<json::User> {
+pub fn get_current_user(db: Connections, account: Account) -> Result<json::User> {
     let user = usecases::get_user(&*db.shared()?, account.email(), account.email())?;
     Ok(Json(user.into()))
 }
 
 #[get("/users/<email>", format = "application/json", rank = 2)]
-pub fn get_user(db: sqlite::Connections, account: Account, email: String) -> Result<json::User> {
+pub fn get_user(db: Connections, account: Account, email: String) -> Result<json::User> {
     let user = usecases::get_user(&*db.shared()?, account.email(), &email)?;
     Ok(Json(user.into()))
 }
 
+#[get("/users/<email>/data")]
+pub fn get_user_data(
+    db: Connections,
+    account: Account,
+    email: String,
+) -> Result<json::UserDataExport> {
+    let db = db.shared()?;
+    usecases::authorize_user_data_access(&*db, account.email(), &email)?;
+    let export = usecases::export_user_data(&*db, &email)?;
+    Ok(Json(export.into()))
+}
+
+#[delete("/users/<email>/data")]
+pub fn delete_user_data(db: Connections, account: Account, email: String) -> Result<()> {
+    usecases::authorize_user_data_access(&*db.shared()?, account.email(), &email)?;
+    flows::erase_user_data(&db, &email, Some(account.email()))?;
+    Ok(Json(()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -155,4 +174,48 @@ mod tests {
         assert_eq!(email_confirmed, current_user.email_confirmed);
         assert_eq!(Role::User, current_user.role.into());
     }
+
+    #[test]
+    fn export_and_erase_user_data() {
+        let (client, db) = setup();
+        register_user(&db, "user@example.com", "secret", true);
+        register_user(&db, "other@example.com", "secret", true);
+
+        let res = client
+            .post("/login")
+            .header(ContentType::JSON)
+            .body(r#"{"email":"other@example.com","password":"secret"}"#)
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let res = client.get("/users/user@example.com/data").dispatch();
+        assert_eq!(res.status(), Status::Forbidden);
+        let res = client.delete("/users/user@example.com/data").dispatch();
+        assert_eq!(res.status(), Status::Forbidden);
+
+        let res = client
+            .post("/login")
+            .header(ContentType::JSON)
+            .body(r#"{"email":"user@example.com","password":"secret"}"#)
+            .dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let mut res = client.get("/users/user@example.com/data").dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        let body = res.body().and_then(|b| b.into_string()).unwrap();
+        assert!(!body.contains("password"));
+        let export: json::UserDataExport = serde_json::from_str(&body).unwrap();
+        assert_eq!("user@example.com", export.user.email);
+        assert!(export.activities.is_empty());
+
+        let res = client.delete("/users/user@example.com/data").dispatch();
+        assert_eq!(res.status(), Status::Ok);
+        assert!(db
+            .shared()
+            .unwrap()
+            .try_get_user_by_email("user@example.com")
+            .unwrap()
+            .is_none());
+        // The session refers to an account that doesn't exist anymore
+        let res = client.get("/users/user@example.com/data").dispatch();
+        assert_ne!(res.status(), Status::Ok);
+    }
 }
@@ -0,0 +1,44 @@
+use super::*;
+use std::convert::TryFrom;
+
+#[get("/webhooks")]
+pub fn get_webhooks(db: Connections, auth: Auth) -> Result<Vec<json::Webhook>> {
+    let org = auth.organization(&db, ApiTokenScope::WebhooksWrite)?;
+    let webhooks = usecases::load_webhooks(&*db.shared()?, &org.id)?;
+    Ok(Json(webhooks.into_iter().map(Into::into).collect()))
+}
+
+#[post("/webhooks", format = "application/json", data = "<new_webhook>")]
+pub fn post_webhook(
+    db: Connections,
+    auth: Auth,
+    new_webhook: Json<json::NewWebhook>,
+) -> Result<json::Webhook> {
+    let new_webhook =
+        usecases::NewWebhook::try_from(new_webhook.into_inner()).map_err(Error::Parameter)?;
+    let org = auth.organization(&db, ApiTokenScope::WebhooksWrite)?;
+    let webhook = usecases::register_webhook(&*db.exclusive()?, &org.id, new_webhook)?;
+    Ok(Json(webhook.into()))
+}
+
+#[delete("/webhooks/<id>")]
+pub fn delete_webhook(db: Connections, auth: Auth, id: String) -> Result<()> {
+    let org = auth.organization(&db, ApiTokenScope::WebhooksWrite)?;
+    usecases::delete_webhook(&*db.exclusive()?, &org.id, &id.into())?;
+    Ok(Json(()))
+}
+
+#[get("/webhooks/<id>/deliveries?<offset>&<limit>")]
+pub fn get_webhook_deliveries(
+    db: Connections,
+    auth: Auth,
+    id: String,
+    offset: Option<u64>,
+    limit: Option<u64>,
+) -> Result<Vec<json::WebhookDelivery>> {
+    let pagination = Pagination { offset, limit };
+    let org = auth.organization(&db, ApiTokenScope::WebhooksWrite)?;
+    let deliveries =
+        usecases::load_webhook_deliveries(&*db.shared()?, &org.id, &id.into(), &pagination)?;
+    Ok(Json(deliveries.into_iter().map(Into::into).collect()))
+}
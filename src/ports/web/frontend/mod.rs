@@ -4,9 +4,14 @@ use crate::{
         prelude::*,
         usecases,
     },
-    infrastructure::{db::sqlite, error::*, flows::prelude::*},
-    ports::web::{guards::*, tantivy::SearchEngine},
+    infrastructure::{
+        db::Connections,
+        error::*,
+        flows::{prelude as flows, prelude::*},
+    },
+    ports::web::{guards::*, notify::Notify, tantivy::SearchEngine},
 };
+use chrono::NaiveDate;
 use maud::Markup;
 use num_traits::FromPrimitive;
 use rocket::{
@@ -36,6 +41,9 @@ const CLEARANCE_WASM: &[u8] =
 
 type Result<T> = std::result::Result<T, AppError>;
 
+// The most recent entries of the audit log that are displayed
+const AUDIT_LOG_PAGE_SIZE: u64 = 200;
+
 #[get("/")]
 pub fn get_index_user(auth: Auth) -> Markup {
     view::index(auth.account_email().ok())
@@ -74,7 +82,7 @@ pub fn get_search(search_engine: SearchEngine, q: &RawStr, limit: Option<usize>)
 }
 
 #[get("/search-users?<email>")]
-pub fn get_search_users(pool: sqlite::Connections, email: &RawStr, auth: Auth) -> Result<Markup> {
+pub fn get_search_users(pool: Connections, email: &RawStr, auth: Auth) -> Result<Markup> {
     let email = email.url_decode()?;
     {
         let db = pool.shared()?;
@@ -92,7 +100,7 @@ pub struct ChangeUserRoleAction {
 
 #[post("/change-user-role", data = "<data>")]
 pub fn post_change_user_role(
-    db: sqlite::Connections,
+    db: Connections,
     account: Account,
     data: Form<ChangeUserRoleAction>,
 ) -> std::result::Result<Redirect, Flash<Redirect>> {
@@ -102,7 +110,9 @@ pub fn post_change_user_role(
             Redirect::to(uri!(get_search_users:d.email)),
             "Failed to change user role: invalid role.",
         )),
-        Some(role) => match change_user_role(&db, account.email(), &d.email, role) {
+        // Both the updated user and the audit log entry are
+        // stored within a single transaction
+        Some(role) => match flows::change_user_role(&db, account.email(), &d.email, role) {
             Err(_) => Err(Flash::error(
                 Redirect::to(uri!(get_search_users:d.email)),
                 "Failed to change user role.",
@@ -123,7 +133,7 @@ pub fn get_main_css() -> Css<&'static str> {
 }
 
 #[get("/places/<id>/history")]
-pub fn get_place_history(db: sqlite::Connections, id: &RawStr, account: Account) -> Result<Markup> {
+pub fn get_place_history(db: Connections, id: &RawStr, account: Account) -> Result<Markup> {
     let db = db.shared()?;
     let user = db
         .try_get_user_by_email(account.email())?
@@ -138,8 +148,62 @@ pub fn get_place_history(db: sqlite::Connections, id: &RawStr, account: Account)
     Ok(view::place_history(&user, &place_history))
 }
 
+#[derive(FromForm)]
+pub struct RevertAction {
+    pub revision: u64,
+    pub version: u64,
+    pub comment: String,
+}
+
+#[post("/places/<id>/revert", data = "<data>")]
+pub fn post_place_revert(
+    db: Connections,
+    mut search_engine: SearchEngine,
+    notify: Notify,
+    id: &RawStr,
+    data: Form<RevertAction>,
+    account: Account,
+) -> std::result::Result<Redirect, Flash<Redirect>> {
+    let RevertAction {
+        revision,
+        version,
+        comment,
+    } = data.into_inner();
+    let id = id.as_str();
+    let revert = usecases::RevertPlace {
+        id: id.into(),
+        revision: revision.into(),
+        version,
+        comment: Some(comment),
+    };
+    revert_place_by_scout(&db, &mut search_engine, &notify, account.email(), revert)
+        .map(|_| Redirect::to(uri!(get_place_history: id)))
+        .map_err(|_| {
+            Flash::error(
+                Redirect::to(uri!(get_place_history: id)),
+                "Failed to revert the place.",
+            )
+        })
+}
+
+fn revert_place_by_scout(
+    db: &Connections,
+    search_engine: &mut SearchEngine,
+    notify: &Notify,
+    email: &str,
+    revert: usecases::RevertPlace,
+) -> Result<()> {
+    let reverted_by = {
+        let db = db.shared()?;
+        // Only scouts and admins are entitled to revert places
+        usecases::authorize_user_by_email(&*db, email, Role::Scout)?.email
+    };
+    revert_place(db, search_engine, &**notify, revert, &reverted_by)?;
+    Ok(())
+}
+
 #[get("/places/<id>/review")]
-pub fn get_place_review(db: sqlite::Connections, id: &RawStr, account: Account) -> Result<Markup> {
+pub fn get_place_review(db: Connections, id: &RawStr, account: Account) -> Result<Markup> {
     let db = db.shared()?;
     // Only scouts and admins are entitled to review places
     let reviewer_email =
@@ -156,7 +220,7 @@ pub struct Review {
 
 #[post("/places/<id>/review", data = "<review>")]
 pub fn post_place_review(
-    db: sqlite::Connections,
+    db: Connections,
     search_engine: SearchEngine,
     id: &RawStr,
     review: Form<Review>,
@@ -175,7 +239,7 @@ pub fn post_place_review(
 }
 
 fn review_place(
-    db: &sqlite::Connections,
+    db: &Connections,
     email: &str,
     status: i16,
     comment: String,
@@ -204,11 +268,7 @@ fn review_place(
 }
 
 #[get("/entries/<id>")]
-pub fn get_entry(
-    pool: sqlite::Connections,
-    id: &RawStr,
-    account: Option<Account>,
-) -> Result<Markup> {
+pub fn get_entry(pool: Connections, id: &RawStr, account: Option<Account>) -> Result<Markup> {
     //TODO: dry out
     let (user, place, ratings): (Option<User>, _, _) = {
         let db = pool.shared()?;
@@ -229,11 +289,7 @@ pub fn get_entry(
 }
 
 #[get("/events/<id>")]
-pub fn get_event(
-    pool: sqlite::Connections,
-    id: &RawStr,
-    account: Option<Account>,
-) -> Result<Markup> {
+pub fn get_event(pool: Connections, id: &RawStr, account: Option<Account>) -> Result<Markup> {
     let (user, mut ev): (Option<User>, _) = {
         let db = pool.shared()?;
         let ev = usecases::get_event(&*db, &id)?;
@@ -255,7 +311,7 @@ pub fn get_event(
 #[post("/events/<id>/archive")]
 pub fn post_archive_event(
     account: Account,
-    pool: sqlite::Connections,
+    pool: Connections,
     mut search_engine: SearchEngine,
     id: &RawStr,
 ) -> std::result::Result<Redirect, Flash<Redirect>> {
@@ -272,7 +328,7 @@ pub fn post_archive_event(
                 "Failed to achive the event.",
             )
         })?;
-    archive_events(&pool, &mut search_engine, &[id], &archived_by_email)
+    archive_events(&pool, Some(&mut search_engine), &[id], &archived_by_email)
         .map_err(|_| {
             Flash::error(
                 Redirect::to(uri!(get_event: id)),
@@ -289,7 +345,7 @@ pub fn post_archive_event(
 
 #[get("/events?<query..>")]
 pub fn get_events_chronologically(
-    db: sqlite::Connections,
+    db: Connections,
     search_engine: SearchEngine,
     mut query: usecases::EventQuery,
     account: Option<Account>,
@@ -312,7 +368,7 @@ pub fn get_events_chronologically(
 }
 
 #[get("/dashboard")]
-pub fn get_dashboard(db: sqlite::Connections, account: Account) -> Result<Markup> {
+pub fn get_dashboard(db: Connections, account: Account) -> Result<Markup> {
     let db = db.shared()?;
     let tag_count = db.count_tags()?;
     let place_count = db.count_places()?;
@@ -333,6 +389,75 @@ pub fn get_dashboard(db: sqlite::Connections, account: Account) -> Result<Markup
     Err(Error::Parameter(ParameterError::Unauthorized).into())
 }
 
+#[derive(FromForm)]
+pub struct AuditLogFilter {
+    actor: Option<String>,
+    action: Option<String>,
+    since: Option<String>, // YYYY-MM-DD
+    until: Option<String>, // YYYY-MM-DD (inclusive)
+}
+
+// Empty form fields are ignored
+fn non_empty(s: Option<String>) -> Option<String> {
+    s.map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+fn parse_date(s: Option<String>) -> Result<Option<NaiveDate>> {
+    non_empty(s)
+        .map(|s| NaiveDate::parse_from_str(&s, "%Y-%m-%d"))
+        .transpose()
+        .map_err(|_| Error::Parameter(ParameterError::DateTimeOutOfRange).into())
+}
+
+fn start_of_day(date: NaiveDate) -> TimestampMs {
+    TimestampMs::from_seconds(date.and_hms(0, 0, 0).timestamp())
+}
+
+#[get("/audit-log?<filter..>")]
+pub fn get_audit_log(
+    db: Connections,
+    account: Account,
+    filter: Form<AuditLogFilter>,
+) -> Result<Markup> {
+    let db = db.shared()?;
+    // The audit log is only visible for admins
+    let admin = usecases::authorize_user_by_email(&*db, &account.email(), Role::Admin)?;
+    let AuditLogFilter {
+        actor,
+        action,
+        since,
+        until,
+    } = filter.into_inner();
+    let actor = non_empty(actor);
+    let action = non_empty(action)
+        .map(|a| {
+            a.parse::<AuditAction>()
+                .map_err(|_| Error::Parameter(ParameterError::InvalidAuditAction(a)))
+        })
+        .transpose()?;
+    let since = parse_date(since)?;
+    let until = parse_date(until)?;
+    let query = AuditLogQuery {
+        actor: actor.clone(),
+        action,
+        since: since.map(start_of_day),
+        until: until.and_then(|d| d.succ_opt()).map(start_of_day),
+    };
+    let pagination = Pagination {
+        offset: None,
+        limit: Some(AUDIT_LOG_PAGE_SIZE),
+    };
+    let entries = usecases::query_audit_log(&*db, &query, &pagination)?;
+    Ok(view::audit_log(view::AuditLogPresenter {
+        email: admin.email,
+        actor,
+        action,
+        since,
+        until,
+        entries,
+    }))
+}
+
 #[derive(FromForm)]
 pub struct ArchiveAction {
     ids: String,
@@ -342,7 +467,7 @@ pub struct ArchiveAction {
 #[post("/comments/actions/archive", data = "<data>")]
 pub fn post_comments_archive(
     account: Account,
-    db: sqlite::Connections,
+    db: Connections,
     data: Form<ArchiveAction>,
 ) -> std::result::Result<Redirect, Flash<Redirect>> {
     //TODO: dry out
@@ -360,7 +485,7 @@ pub fn post_comments_archive(
 #[post("/ratings/actions/archive", data = "<data>")]
 pub fn post_ratings_archive(
     account: Account,
-    db: sqlite::Connections,
+    db: Connections,
     mut search_engine: SearchEngine,
     data: Form<ArchiveAction>,
 ) -> std::result::Result<Redirect, Flash<Redirect>> {
@@ -384,9 +509,11 @@ pub fn routes() -> Vec<Route> {
         get_index,
         get_index_html,
         get_dashboard,
+        get_audit_log,
         get_search,
         get_entry,
         get_place_history,
+        post_place_revert,
         get_place_review,
         post_place_review,
         get_events_chronologically,
@@ -2,7 +2,7 @@ use super::view;
 use crate::{
     core::prelude::*,
     infrastructure::flows::prelude::*,
-    ports::web::{notify::*, sqlite::Connections},
+    ports::web::{db::Connections, notify::*},
 };
 use maud::Markup;
 use rocket::{
@@ -1,14 +1,10 @@
 use super::*;
 use crate::{
-    infrastructure::db::{sqlite::Connections, tantivy},
+    infrastructure::db::{tantivy, Connections},
     ports::web::tests::{prelude::*, register_user},
 };
 
-fn setup() -> (
-    rocket::local::Client,
-    sqlite::Connections,
-    tantivy::SearchEngine,
-) {
+fn setup() -> (rocket::local::Client, Connections, tantivy::SearchEngine) {
     crate::ports::web::tests::setup(vec![("/", super::routes())])
 }
 
@@ -104,7 +100,7 @@ mod events {
                 ..Default::default()
             },
         ];
-        let gw = DummyNotifyGW;
+        let gw = DummyNotifyGw;
         let event_ids = {
             let mut event_ids = Vec::with_capacity(new_events.len());
             for e in new_events {
@@ -225,7 +221,7 @@ mod events {
                 ..Default::default()
             },
         ];
-        let gw = DummyNotifyGW;
+        let gw = DummyNotifyGw;
         let event_ids = {
             let mut event_ids = Vec::with_capacity(new_events.len());
             for e in new_events {
@@ -268,6 +264,9 @@ mod events {
             archived: None,
             image_url: None,
             image_link_url: None,
+            image_id: None,
+            recurrence: None,
+            series_id: None,
         }];
 
         {
@@ -310,7 +309,7 @@ mod entry {
     use crate::{core::usecases, infrastructure::flows};
 
     fn create_place_with_rating(
-        db: &sqlite::Connections,
+        db: &Connections,
         search: &mut tantivy::SearchEngine,
     ) -> (String, String, String) {
         let e = usecases::NewPlace {
@@ -334,9 +333,10 @@ mod entry {
             license: "CC0-1.0".into(),
             image_url: None,
             image_link_url: None,
+            image_id: None,
             custom_links: vec![],
         };
-        let gw = DummyNotifyGW;
+        let gw = DummyNotifyGw;
         let e_id = flows::prelude::create_place(db, search, &gw, e, None, None)
             .unwrap()
             .id;
@@ -491,6 +491,20 @@ mod admin {
         assert_eq!(login_res.status(), Status::SeeOther);
         let user = get_user(&db, "user");
         assert_eq!(user.role, Role::Scout);
+        let query = AuditLogQuery {
+            action: Some(AuditAction::ChangeUserRole),
+            ..Default::default()
+        };
+        let entries = db
+            .shared()
+            .unwrap()
+            .query_audit_log(&query, &Default::default())
+            .unwrap();
+        assert_eq!(1, entries.len());
+        assert_eq!(Some("admin@example.com"), entries[0].actor.as_deref());
+        assert_eq!(vec!["user@example.com".to_string()], entries[0].target_ids);
+        assert_eq!(Some("user"), entries[0].before.as_deref());
+        assert_eq!(Some("scout"), entries[0].after.as_deref());
     }
 }
 
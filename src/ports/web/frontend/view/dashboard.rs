@@ -1,5 +1,6 @@
 use super::page;
 use crate::core::entities::*;
+use chrono::NaiveDate;
 use maud::{html, Markup};
 
 pub struct DashBoardPresenter {
@@ -39,6 +40,72 @@ pub fn dashboard(data: DashBoardPresenter) -> Markup {
                 }
                 h3 { "User Management" }
                 (super::search_users_form())
+                h3 { "Audit Log" }
+                a href="/audit-log" { "Show privileged actions" }
+            }
+        },
+    )
+}
+
+pub struct AuditLogPresenter {
+    pub email: String,
+    pub actor: Option<String>,
+    pub action: Option<AuditAction>,
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    pub entries: Vec<AuditLogEntry>,
+}
+
+pub fn audit_log(data: AuditLogPresenter) -> Markup {
+    use strum::IntoEnumIterator;
+    let date = |d: Option<NaiveDate>| {
+        d.map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_default()
+    };
+    page(
+        "Audit Log",
+        Some(&data.email),
+        None,
+        None,
+        html! {
+            main class="audit-log" {
+                h3 { "Audit Log" }
+                form action="/audit-log" method="GET" {
+                    input type="email" name="actor" placeholder="actor" value=(data.actor.as_deref().unwrap_or_default());
+                    select name="action" {
+                        option value="" { "all actions" }
+                        @for a in AuditAction::iter() {
+                            option value=(a.as_str()) selected?[data.action == Some(a)] { (a.as_str()) }
+                        }
+                    }
+                    input type="date" name="since" value=(date(data.since));
+                    input type="date" name="until" value=(date(data.until));
+                    input type="submit" value="filter";
+                }
+                table {
+                    thead {
+                        tr {
+                            th { "Time" }
+                            th { "Actor" }
+                            th { "Action" }
+                            th { "Targets" }
+                            th { "Before" }
+                            th { "After" }
+                        }
+                    }
+                    tbody {
+                        @for e in &data.entries {
+                            tr {
+                                td { (e.at) }
+                                td { @if let Some(x) = &e.actor { (x) } @else { "command line" } }
+                                td { (e.action.as_str()) }
+                                td { (e.target_ids.join(", ")) }
+                                td { @if let Some(x) = &e.before { (x) } }
+                                td { @if let Some(x) = &e.after { (x) } }
+                            }
+                        }
+                    }
+                }
             }
         },
     )
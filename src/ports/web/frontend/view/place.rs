@@ -3,6 +3,7 @@ use crate::core::prelude::*;
 use maud::{html, Markup};
 
 pub fn place_history(user: &User, h: &PlaceHistory) -> Markup {
+    let current_rev = h.revisions.iter().map(|(r, _)| r.revision).max();
     page(
         "Place History",
         Some(&user.email),
@@ -34,6 +35,7 @@ pub fn place_history(user: &User, h: &PlaceHistory) -> Markup {
                             th{ "Image Link" }
 
                             th{ "Tags" }
+                            th{ "Revert" }
                         }
                     }
                     tbody {
@@ -91,6 +93,11 @@ pub fn place_history(user: &User, h: &PlaceHistory) -> Markup {
                                         }
                                     }
                                 }
+                                td{
+                                    @if let Some(current_rev) = current_rev.filter(|c| r.revision < *c) {
+                                        (revert_form(&h.place.id, r.revision, current_rev.next()))
+                                    }
+                                }
                             }
                         }
                     }
@@ -100,6 +107,17 @@ pub fn place_history(user: &User, h: &PlaceHistory) -> Markup {
     )
 }
 
+fn revert_form(id: &Id, revision: Revision, version: Revision) -> Markup {
+    html! {
+        form class="revert" action=(format!("/places/{}/revert", id)) method="POST" {
+            input type="hidden" name="revision" value=(u64::from(revision));
+            input type="hidden" name="version" value=(u64::from(version));
+            input required? name="comment" placeholder="Comment";
+            input type="submit" value="revert";
+        }
+    }
+}
+
 fn review_status_log(place_rev: Revision, l: &ReviewStatusLog) -> Markup {
     use ReviewStatus as S;
     let status = match l.status {
@@ -1,5 +1,7 @@
 use crate::{
-    core::db::OrganizationRepo, core::prelude::*, core::usecases, infrastructure::error::AppError,
+    core::prelude::*,
+    core::usecases,
+    infrastructure::{db::Connections, error::AppError},
     ports::web::jwt,
 };
 use chrono::prelude::*;
@@ -15,6 +17,9 @@ pub const COOKIE_EMAIL_KEY: &str = "ofdb-user-email";
 pub const COOKIE_CAPTCHA_KEY: &str = "ofdb-captcha";
 pub const MAX_CAPTCHA_TTL: Duration = Duration::from_secs(120);
 
+// Avoid a write access for every single request
+const API_TOKEN_LAST_USED_AT_RESOLUTION_MS: i64 = 60_000;
+
 type Result<T> = std::result::Result<T, AppError>;
 
 fn get_bearer_token(auth_header_val: &str) -> Option<&str> {
@@ -60,11 +65,39 @@ impl Auth {
         }
     }
 
-    pub fn organization<R: OrganizationRepo>(&self, db: &R) -> Result<Organization> {
-        Ok(usecases::authorize_organization_by_possible_api_tokens(
-            db,
+    /// Authorizes an organization with the required scope.
+    ///
+    /// Must not be called while holding a database connection,
+    /// because the usage of scoped API tokens is recorded.
+    pub fn organization(
+        &self,
+        connections: &Connections,
+        scope: ApiTokenScope,
+    ) -> Result<Organization> {
+        let now = TimestampMs::now();
+        let (org, api_token) = usecases::authorize_organization_by_possible_api_tokens(
+            &*connections.shared()?,
             &self.bearer_tokens,
-        )?)
+            scope,
+            now,
+        )?;
+        let outdated = api_token.last_used_at.map_or(true, |t| {
+            now.into_inner() - t.into_inner() >= API_TOKEN_LAST_USED_AT_RESOLUTION_MS
+        });
+        if outdated {
+            let record_usage = || -> Result<()> {
+                let db = connections.exclusive()?;
+                Ok(db.update_api_token_last_used_at(&api_token.id, now)?)
+            };
+            if let Err(err) = record_usage() {
+                log::warn!(
+                    "Failed to record the usage of API token {}: {}",
+                    api_token.id,
+                    err
+                );
+            }
+        }
+        Ok(org)
     }
 
     pub fn user_with_min_role<D: Db>(&self, db: &D, min_required_role: Role) -> Result<User> {
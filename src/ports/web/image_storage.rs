@@ -0,0 +1,33 @@
+#[cfg(not(test))]
+use crate::infrastructure::IMAGE_STORAGE;
+#[cfg(test)]
+use crate::ports::web::tests::IMAGE_STORAGE;
+use core::ops::Deref;
+use ofdb_core::gateways::image::ImageStorageGateway;
+use rocket::{
+    request::{self, FromRequest},
+    Outcome, Request,
+};
+
+pub struct ImageStorage(&'static (dyn ImageStorageGateway + Send + Sync));
+
+impl Deref for ImageStorage {
+    type Target = dyn ImageStorageGateway;
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl Default for ImageStorage {
+    fn default() -> Self {
+        ImageStorage(IMAGE_STORAGE.as_ref())
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for ImageStorage {
+    type Error = ();
+
+    fn from_request(_: &'a Request<'r>) -> request::Outcome<Self, ()> {
+        Outcome::Success(ImageStorage::default())
+    }
+}
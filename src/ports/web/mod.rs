@@ -1,83 +1,44 @@
 use crate::{
-    core::{
-        db::{EventIndexer, PlaceIndexer},
-        prelude::*,
-        usecases,
-    },
-    infrastructure::error::AppError,
+    core::usecases,
+    infrastructure::{db::Connections, flows::prelude as flows},
 };
-use ofdb_core::rating::Rated;
 use rocket::{config::Config, Rocket, Route};
-use rocket_contrib::json::Json;
-use std::result;
+use std::{sync::Arc, thread};
 
 pub mod api;
+mod db;
 #[cfg(feature = "frontend")]
 mod frontend;
 mod guards;
+mod image_storage;
 pub mod jwt;
 #[cfg(test)]
 mod mockdb;
 pub mod notify;
 mod popular_tags_cache;
-mod sqlite;
 mod tantivy;
 #[cfg(test)]
 pub mod tests;
 
-type Result<T> = result::Result<Json<T>, AppError>;
-
-fn index_all_places<D: PlaceRepo + RatingRepository>(
-    db: &D,
-    indexer: &mut dyn PlaceIndexer,
-) -> Result<()> {
-    // TODO: Split into chunks with fixed size instead of
-    // loading all places at once!
-    let places = db.all_places()?;
-    for (place, status) in places {
-        let ratings = db.load_ratings_of_place(place.id.as_ref())?;
-        if let Err(err) =
-            indexer.add_or_update_place(&place, status, &place.avg_ratings(&ratings[..]))
-        {
-            error!("Failed to index place {:?}: {}", place, err);
-        }
-    }
-    if let Err(err) = indexer.flush_index() {
-        error!("Failed to build place index: {}", err);
-    }
-    Ok(Json(()))
-}
-
-fn index_all_events_chronologically<D: EventGateway>(
-    db: &D,
-    indexer: &mut dyn EventIndexer,
-) -> Result<()> {
-    // TODO: Split into chunks with fixed size instead of
-    // loading all events at once!
-    let events = db.all_events_chronologically()?;
-    for event in events {
-        if let Err(err) = indexer.add_or_update_event(&event) {
-            error!("Failed to index event {:?}: {}", event, err);
-        }
-    }
-    if let Err(err) = indexer.flush_index() {
-        error!("Failed to build event index: {}", err);
-    }
-    Ok(Json(()))
-}
+// Executes indexing and notification jobs in the background
+const JOB_WORKER_COUNT: usize = 2;
 
 pub(crate) fn rocket_instance(
-    connections: sqlite::Connections,
-    mut search_engine: tantivy::SearchEngine,
+    connections: Connections,
+    search_engine: tantivy::SearchEngine,
     mounts: Vec<(&str, Vec<Route>)>,
     cfg: Option<Config>,
 ) -> Rocket {
-    info!("Indexing all places...");
-    index_all_places(&*connections.exclusive().unwrap(), &mut search_engine).unwrap();
-
-    info!("Indexing all events...");
-    index_all_events_chronologically(&*connections.exclusive().unwrap(), &mut search_engine)
-        .unwrap();
+    info!("Updating search index in the background...");
+    {
+        let connections = connections.clone();
+        let mut search_engine = search_engine.clone();
+        thread::spawn(move || {
+            if let Err(err) = flows::update_search_index(&connections, &mut search_engine) {
+                error!("Failed to update search index: {}", err);
+            }
+        });
+    }
 
     info!("Deleting expired user e-mail tokens...");
     usecases::delete_expired_user_tokens(&*connections.exclusive().unwrap()).unwrap();
@@ -118,11 +79,17 @@ fn mounts() -> Vec<(&'static str, Vec<Route>)> {
     vec![("/api", api::routes()), ("/", frontend::routes())]
 }
 
-pub fn run(
-    connections: sqlite::Connections,
-    search_engine: tantivy::SearchEngine,
-    enable_cors: bool,
-) {
+pub fn run(connections: Connections, search_engine: tantivy::SearchEngine, enable_cors: bool) {
+    let notify = Arc::new(notify::Notify::default());
+    if let Err(err) = flows::spawn_subscription_digest_scheduler(&connections, Arc::clone(&notify))
+    {
+        error!("Failed to start subscription digest scheduler: {}", err);
+    }
+    if let Err(err) =
+        flows::spawn_job_workers(&connections, &search_engine, notify, JOB_WORKER_COUNT)
+    {
+        error!("Failed to start background job workers: {}", err);
+    }
     if enable_cors {
         let cors = rocket_cors::CorsOptions {
             ..Default::default()
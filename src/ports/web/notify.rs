@@ -1,7 +1,7 @@
 #[cfg(not(test))]
-use crate::infrastructure::{MAILGUN_GW, SENDMAIL_GW};
+use crate::infrastructure::{EMAIL_TEMPLATES, MAILGUN_GW, PUBLIC_API_URL, SENDMAIL_GW};
 #[cfg(test)]
-use crate::ports::web::tests::DummyNotifyGW;
+use crate::ports::web::tests::DummyNotifyGw;
 use core::ops::Deref;
 use ofdb_core::gateways::email::EmailGateway;
 use ofdb_entities::email::*;
@@ -16,7 +16,7 @@ use rocket::{
 pub struct Notify(notify::Notify);
 
 #[cfg(test)]
-pub struct Notify(DummyNotifyGW);
+pub struct Notify(DummyNotifyGw);
 
 struct DummyMailGw;
 
@@ -24,6 +24,15 @@ impl EmailGateway for DummyMailGw {
     fn compose_and_send(&self, _recipients: &[Email], _subject: &str, _body: &str) {
         debug!("Cannot send emails because no e-mail gateway was configured");
     }
+    fn compose_and_send_unsubscribable(
+        &self,
+        _recipient: &Email,
+        _subject: &str,
+        _body: &str,
+        _unsubscribe_url: &str,
+    ) {
+        debug!("Cannot send emails because no e-mail gateway was configured");
+    }
 }
 
 impl Deref for Notify {
@@ -33,24 +42,42 @@ impl Deref for Notify {
     }
 }
 
-impl<'a, 'r> FromRequest<'a, 'r> for Notify {
-    type Error = ();
-
+impl Default for Notify {
     #[cfg(not(test))]
-    fn from_request(_: &'a Request<'r>) -> request::Outcome<Self, ()> {
+    fn default() -> Self {
         if let Some(gw) = &*MAILGUN_GW {
             info!("Use Mailgun gateway");
-            Outcome::Success(Notify(notify::Notify::new(gw.clone())))
+            Notify(notify::Notify::new(
+                gw.clone(),
+                EMAIL_TEMPLATES.clone(),
+                &*PUBLIC_API_URL,
+            ))
         } else if let Some(gw) = &*SENDMAIL_GW {
             warn!("Mailgun gateway was not configured: use sendmail as fallback");
-            Outcome::Success(Notify(notify::Notify::new(gw.clone())))
+            Notify(notify::Notify::new(
+                gw.clone(),
+                EMAIL_TEMPLATES.clone(),
+                &*PUBLIC_API_URL,
+            ))
         } else {
             warn!("No eMail gateway was not configured");
-            Outcome::Success(Notify(notify::Notify::new(DummyMailGw)))
+            Notify(notify::Notify::new(
+                DummyMailGw,
+                EMAIL_TEMPLATES.clone(),
+                &*PUBLIC_API_URL,
+            ))
         }
     }
     #[cfg(test)]
+    fn default() -> Self {
+        Notify(DummyNotifyGw)
+    }
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for Notify {
+    type Error = ();
+
     fn from_request(_: &'a Request<'r>) -> request::Outcome<Self, ()> {
-        Outcome::Success(Notify(DummyNotifyGW))
+        Outcome::Success(Notify::default())
     }
 }
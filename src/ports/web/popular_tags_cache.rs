@@ -1,7 +1,4 @@
-use crate::{
-    core::db::PlaceRepo,
-    ports::web::{MostPopularTagsParams, Pagination},
-};
+use crate::core::db::{MostPopularTagsParams, Pagination, PlaceRepo};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use ofdb_boundary::TagFrequency;
@@ -1,6 +1,10 @@
 use crate::{
     core::{prelude::*, usecases},
-    infrastructure::db::{sqlite, tantivy},
+    infrastructure::db::{tantivy, tests::TestDatabase, Connections},
+};
+use ofdb_core::gateways::{
+    image::{ImageStorageGateway, ImageStorageResult},
+    notify::{DigestItem, NotificationGateway, Recipient},
 };
 use rocket::{
     config::{Config, Environment},
@@ -8,9 +12,10 @@ use rocket::{
     logger::LoggingLevel,
     Route,
 };
+use std::{collections::HashMap, sync::Mutex};
 
 pub mod prelude {
-    pub use super::DummyNotifyGW;
+    pub use super::DummyNotifyGw;
     pub use crate::core::db::*;
     pub use rocket::{
         http::{ContentType, Cookie, Status},
@@ -19,39 +24,37 @@ pub mod prelude {
     };
 }
 
-embed_migrations!();
-
 pub fn setup(
     mounts: Vec<(&'static str, Vec<Route>)>,
-) -> (
-    rocket::local::Client,
-    sqlite::Connections,
-    tantivy::SearchEngine,
-) {
+) -> (rocket::local::Client, Connections, tantivy::SearchEngine) {
     let cfg = Config::build(Environment::Development)
         .log_level(LoggingLevel::Debug)
         .finalize()
         .unwrap();
-    let connections = sqlite::Connections::init(":memory:", 1).unwrap();
-    embedded_migrations::run(&*connections.exclusive().unwrap()).unwrap();
+    let db = TestDatabase::new();
+    let connections = db.connections.clone();
     let search_engine = tantivy::SearchEngine::init_in_ram().unwrap();
+    // The client owns the test database, i.e. a PostgreSQL test
+    // schema is dropped not before the client itself.
     let rocket = super::rocket_instance(
         connections.clone(),
         search_engine.clone(),
         mounts,
         Some(cfg),
-    );
+    )
+    .manage(db);
     let client = Client::new(rocket).unwrap();
     (client, connections, search_engine)
 }
 
-pub fn register_user(pool: &sqlite::Connections, email: &str, pw: &str, confirmed: bool) {
+pub fn register_user(pool: &Connections, email: &str, pw: &str, confirmed: bool) {
     let db = pool.exclusive().unwrap();
     usecases::create_new_user(
         &*db,
         usecases::NewUser {
             email: email.to_string(),
             password: pw.to_string(),
+            locale: None,
         },
     )
     .unwrap();
@@ -65,15 +68,42 @@ pub fn register_user(pool: &sqlite::Connections, email: &str, pw: &str, confirme
     }
 }
 
-pub struct DummyNotifyGW;
+pub struct DummyNotifyGw;
 
-impl ofdb_core::gateways::notify::NotificationGateway for DummyNotifyGW {
-    fn place_added(&self, _: &[String], _: &Place, _: Vec<Category>) {}
-    fn place_updated(&self, _: &[String], _: &Place, _: Vec<Category>) {}
-    fn event_created(&self, _: &[String], _: &Event) {}
-    fn event_updated(&self, _: &[String], _: &Event) {}
+impl NotificationGateway for DummyNotifyGw {
+    fn place_added(&self, _: &[Recipient], _: &Place, _: Vec<Category>) {}
+    fn place_updated(&self, _: &[Recipient], _: &Place, _: Vec<Category>) {}
+    fn event_created(&self, _: &[Recipient], _: &Event) {}
+    fn event_updated(&self, _: &[Recipient], _: &Event) {}
+    fn subscription_digest(&self, _: &Recipient, _: &[DigestItem]) {}
     fn user_registered_kvm(&self, _: &User) {}
     fn user_registered_ofdb(&self, _: &User) {}
     fn user_registered(&self, _: &User, _: &str) {}
-    fn user_reset_password_requested(&self, _: &EmailNonce) {}
+    fn user_reset_password_requested(&self, _: &EmailNonce, _: Option<&Locale>) {}
+}
+
+#[derive(Default)]
+pub struct InMemoryImageStorage(Mutex<HashMap<String, Vec<u8>>>);
+
+impl ImageStorageGateway for InMemoryImageStorage {
+    fn store(&self, key: &str, data: &[u8]) -> ImageStorageResult<()> {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), data.to_vec());
+        Ok(())
+    }
+    fn load(&self, key: &str) -> ImageStorageResult<Option<Vec<u8>>> {
+        Ok(self.0.lock().unwrap().get(key).cloned())
+    }
+    fn delete(&self, key: &str) -> ImageStorageResult<()> {
+        self.0.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+lazy_static! {
+    // Shared by all tests, because image keys are unique
+    pub static ref IMAGE_STORAGE: Box<dyn ImageStorageGateway + Send + Sync> =
+        Box::new(InMemoryImageStorage::default());
 }